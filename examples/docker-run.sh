@@ -62,23 +62,53 @@ echo "  Stop:    podman rm -f ${NAME}"
 #
 #   server
 #     RUSTS3_BIND_ADDRESS=0.0.0.0     RUSTS3_PORT=8002     RUSTS3_DATA_DIR=/data
+#     RUSTS3_TLS_ENABLED=false   RUSTS3_TLS_CERT=   RUSTS3_TLS_KEY=
+#     RUSTS3_TLS_CLIENT_AUTH=none (none|optional|required)   RUSTS3_TLS_CLIENT_CA=
+#     RUSTS3_TLS_RELOAD_SECS=30
 #   storage
 #     RUSTS3_META_CACHE_CAPACITY=200000   RUSTS3_DURABILITY=full   (full|relaxed)
 #     RUSTS3_REBUILD_READER_THREADS=0     RUSTS3_REBUILD_QUEUE_BOUND=1000
-#     RUSTS3_REBUILD_BATCH_SIZE=1000
+#     RUSTS3_REBUILD_BATCH_SIZE=1000      RUSTS3_COPY_MODE=link   (link|reflink|copy)
+#     RUSTS3_GROUP_COMMIT_WINDOW_US=200   RUSTS3_INLINE_THRESHOLD=0
+#     RUSTS3_DATA_ROOT_1=   RUSTS3_DATA_ROOT_2=   RUSTS3_DATA_ROOT_3=   (extra disks)
 #   logging
 #     RUSTS3_LOG_DIR=logs   RUSTS3_LOG_LEVEL=info   RUSTS3_BANDWIDTH_REPORT=true
 #     RUSTS3_LOG_ROTATION_MB=100   RUSTS3_LOG_KEEP_FILES=5   RUSTS3_LOG_COMPRESS=true
+#     RUSTS3_ACCESS_LOG_FLUSH_SECS=300
 #   auth
 #     RUSTS3_AUTH_ENABLED=true
 #     RUSTS3_ADMIN_USER=admin         RUSTS3_ADMIN_PASSWORD=rusts3admin
 #     RUSTS3_ACCESS_KEY=rusts3admin   RUSTS3_SECRET_KEY=rusts3admin
 #     RUSTS3_PUBLIC_HOSTNAME=         RUSTS3_PUBLIC_SCHEME=http
+#     RUSTS3_ADMIN_TOTP_SECRET=       (base32; enables two-factor for the admin)
 #   sweeper
 #     RUSTS3_SWEEP_INTERVAL_SECS=300      RUSTS3_INTENT_BATCH_SIZE=100
 #     RUSTS3_INTENT_GRACE_SECS=3600       RUSTS3_STAGING_EXPIRY_SECS=86400
 #     RUSTS3_MULTIPART_EXPIRY_SECS=2592000  (0 disables)
 #     RUSTS3_TRASH_EXPIRY_SECS=86400      RUSTS3_RECLAIM_INTERVAL_SECS=300
+#     RUSTS3_CHANGE_LOG_RETENTION_SECS=604800  (0 keeps forever)
 #   ui
 #     RUSTS3_UI_ENABLED=true   RUSTS3_UI_BIND_ADDRESS=0.0.0.0   RUSTS3_UI_PORT=8003
+#     RUSTS3_UI_TLS_ENABLED=false   RUSTS3_UI_TLS_CERT=   RUSTS3_UI_TLS_KEY=
+#     RUSTS3_UI_SESSION_IDLE_MINUTES=60   RUSTS3_UI_SESSION_MAX_HOURS=12
+#     RUSTS3_UI_REQUIRE_TOTP=false   RUSTS3_UI_MAX_ACCESS_KEYS_PER_USER=2
+#     RUSTS3_OIDC_ENABLED=false   RUSTS3_OIDC_ISSUER=   RUSTS3_OIDC_CLIENT_ID=
+#     RUSTS3_OIDC_CLIENT_SECRET=  RUSTS3_OIDC_REDIRECT_URL=
+#     RUSTS3_OIDC_USERNAME_CLAIM=preferred_username   RUSTS3_OIDC_GROUPS_CLAIM=groups
+#     RUSTS3_OIDC_AUTO_PROVISION=true   RUSTS3_OIDC_BUTTON_LABEL="Sign in with SSO"
+#   ldap
+#     RUSTS3_LDAP_ENABLED=false   RUSTS3_LDAP_URL=   RUSTS3_LDAP_START_TLS=false
+#     RUSTS3_LDAP_BIND_DN=   RUSTS3_LDAP_BIND_PASSWORD=   RUSTS3_LDAP_USER_BASE_DN=
+#     RUSTS3_LDAP_USER_FILTER="(uid={username})"   RUSTS3_LDAP_GROUP_BASE_DN=
+#     RUSTS3_LDAP_GROUP_FILTER="(|(member={dn})(uniqueMember={dn}))"
+#     RUSTS3_LDAP_GROUP_NAME_ATTRIBUTE=cn   RUSTS3_LDAP_AUTO_PROVISION=true
+#     RUSTS3_LDAP_TIMEOUT_SECS=10   RUSTS3_LDAP_STS_MAX_DURATION_SECS=43200
+#   audit
+#     RUSTS3_AUDIT_ENABLED=true   RUSTS3_AUDIT_RETENTION_DAYS=90
+#   admission
+#     RUSTS3_MAX_CONCURRENT_WRITES=64     RUSTS3_MAX_INFLIGHT_BYTES_PER_BUCKET=0
+#     RUSTS3_MAX_QUEUED_WRITES=256        RUSTS3_QUEUE_TIMEOUT_MS=10000
+#     RUSTS3_MIN_FREE_BYTES=1073741824    RUSTS3_MIN_FREE_PERCENT=0
+#   snapshots
+#     RUSTS3_SNAPSHOT_DIR=   RUSTS3_SNAPSHOT_RETAIN=0
 # ─────────────────────────────────────────────────────────────────────────────
@@ -55,6 +55,14 @@ enum Command {
         #[arg(long)]
         password: Option<String>,
     },
+    /// Generate a TOTP secret for a built-in user's `totp_secret`, with the
+    /// provisioning URI and QR code to add it to an authenticator app.
+    #[command(name = "gentotp")]
+    GenTotp {
+        /// Account name the authenticator app shows.
+        #[arg(long, default_value = "admin")]
+        user: String,
+    },
     /// Verify a cleartext console password against a bcrypt hash.
     #[command(name = "verifypassword")]
     VerifyPassword {
@@ -71,6 +79,278 @@ enum Command {
     },
     /// Write a documented config.yaml in the current directory.
     Init,
+    /// Take, list or prune whole-server snapshots.
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// Rebuild an empty data directory from a snapshot. The server must be
+    /// stopped; start it afterwards as usual.
+    Restore {
+        /// Path to config.yaml; the snapshot is restored into its base_dir.
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+        /// Snapshot directory to restore from.
+        #[arg(long, value_name = "DIR")]
+        from: String,
+    },
+    /// Write an incremental backup archive: the objects created, overwritten
+    /// or deleted since the checkpoint given with --since, or every object
+    /// without one. The next checkpoint is written to FILE.checkpoint. With
+    /// the server running, the archive is cut from a snapshot it takes, so a
+    /// console admin login is needed.
+    Backup {
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+        /// Archive to write.
+        #[arg(long, value_name = "FILE")]
+        out: String,
+        /// Checkpoint left by the previous backup.
+        #[arg(long, value_name = "CHECKPOINT")]
+        since: Option<String>,
+        /// Console admin user (running server only).
+        #[arg(long)]
+        user: Option<String>,
+        /// Console password. Omit to enter it securely at the prompt.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Replay a backup archive into the data directory. The server must be
+    /// stopped; apply archives in the order they were written.
+    BackupApply {
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+        /// Archive to apply.
+        #[arg(long, value_name = "FILE")]
+        from: String,
+    },
+    /// Administer a running server through its console API with an admin API
+    /// token (create one on the console's My Account page, or with `rusts3
+    /// admin token create`). Replies are printed as JSON.
+    Admin(AdminArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct AdminArgs {
+    /// Console URL. Defaults to $RUSTS3_ADMIN_URL, then http://127.0.0.1:8003.
+    #[arg(long, global = true)]
+    url: Option<String>,
+    /// Admin API token. Defaults to $RUSTS3_ADMIN_TOKEN, which keeps it out of
+    /// the process list.
+    #[arg(long, global = true)]
+    token: Option<String>,
+    /// Accept a console certificate that does not verify, e.g. self-signed.
+    #[arg(long, global = true)]
+    insecure: bool,
+    #[command(subcommand)]
+    command: AdminCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum AdminCommand {
+    /// IAM users.
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+    /// IAM groups.
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
+    /// Attach or detach the policy of a user or a group.
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+    /// S3 access keys.
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+    /// Buckets.
+    Bucket {
+        #[command(subcommand)]
+        action: BucketAction,
+    },
+    /// Storage scans.
+    Scan {
+        #[command(subcommand)]
+        action: ScanAction,
+    },
+    /// Whole-server snapshots, taken by the running server.
+    Snapshot {
+        #[command(subcommand)]
+        action: AdminSnapshotAction,
+    },
+    /// Admin API tokens.
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UserAction {
+    List,
+    Create {
+        name: String,
+        /// Omit to enter it securely at the prompt.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    Delete {
+        name: String,
+    },
+    /// Reset a user's password; their console sessions end.
+    Password {
+        name: String,
+        /// Omit to enter it securely at the prompt.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Replace a user's group memberships. No groups removes them all.
+    Groups {
+        name: String,
+        groups: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GroupAction {
+    List,
+    Create {
+        name: String,
+        /// Policy document to attach.
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
+    },
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PolicyAction {
+    /// Attach the policy document in FILE, replacing any other.
+    Set {
+        #[command(flatten)]
+        target: PolicyTarget,
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+    /// Detach the policy.
+    Clear {
+        #[command(flatten)]
+        target: PolicyTarget,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+#[group(required = true, multiple = false)]
+struct PolicyTarget {
+    #[arg(long)]
+    user: Option<String>,
+    #[arg(long)]
+    group: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum KeyAction {
+    /// A user's access keys.
+    List { user: String },
+    /// Issue a key for a user. The secret is only shown now.
+    Create { user: String },
+    Delete { access_key: String },
+    Activate { access_key: String },
+    /// Stop a key signing requests, without deleting it.
+    Deactivate { access_key: String },
+    /// Replace a key with a new one; the old one stops working.
+    Rotate { access_key: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum BucketAction {
+    List,
+    Create {
+        name: String,
+        /// Data root to place it on; omit to place it by free space.
+        #[arg(long)]
+        root: Option<String>,
+    },
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScanAction {
+    /// Start a scan of the given buckets, or of every bucket.
+    Start { buckets: Vec<String> },
+    /// The running or last scan.
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+enum AdminSnapshotAction {
+    List,
+    Create,
+    Delete { id: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum TokenAction {
+    /// Your tokens, or everyone's with admin:ManageSessions.
+    List,
+    /// Issue a token. This signs in to the console with a password rather
+    /// than a token, and prints the new token once.
+    Create {
+        #[arg(long)]
+        name: String,
+        /// An admin: or s3: action the token may use; repeat for more.
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+        /// Days until it expires.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        /// Console user. Omit to enter it at the prompt.
+        #[arg(long)]
+        user: Option<String>,
+        /// Console password. Omit to enter it securely at the prompt.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    Revoke { id: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum SnapshotAction {
+    /// Take a snapshot. With the server stopped it is taken directly; with it
+    /// running, the server takes it through its console, so a console admin
+    /// login is needed.
+    Create {
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+        /// Console admin user (running server only).
+        #[arg(long)]
+        user: Option<String>,
+        /// Console password. Omit to enter it securely at the prompt.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// List completed snapshots, oldest first.
+    List {
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+    },
+    /// Delete all but the newest snapshots.
+    Prune {
+        #[arg(short = 'c', long, value_name = "FILE", default_value = "config.yaml")]
+        config: String,
+        /// Snapshots to keep.
+        #[arg(long)]
+        keep: usize,
+    },
 }
 
 /// Header prepended to the config `init` writes. The body below it is the
@@ -89,26 +369,29 @@ const CONFIG_HEADER: &str = "\
 ";
 
 /// Minimal HTTP/1.1 GET of the unauthenticated liveness endpoint. Deliberately
-/// hand-rolled over `TcpStream`: a health check must not need an async runtime,
-/// a TLS stack, or anything installed in the image alongside the binary.
-fn health_probe(port: u16, timeout_secs: u64) -> Result<(), String> {
+/// hand-rolled over a blocking `TcpStream`: a health check must not need an
+/// async runtime or anything installed in the image alongside the binary. When
+/// the S3 listener terminates TLS the same request goes through a TLS session.
+fn health_probe(port: u16, timeout_secs: u64, tls: bool) -> Result<(), String> {
     use std::io::{Read, Write};
-    use std::net::{SocketAddr, TcpStream};
     use std::time::Duration;
 
     let timeout = Duration::from_secs(timeout_secs.max(1));
-    // Always loopback: the configured bind address may be 0.0.0.0, which is not
-    // a valid destination, and a health check is by definition local.
-    let address = SocketAddr::from(([127, 0, 0, 1], port));
-    let mut stream =
-        TcpStream::connect_timeout(&address, timeout).map_err(|err| format!("connect: {err}"))?;
-    stream
-        .set_read_timeout(Some(timeout))
-        .and_then(|()| stream.set_write_timeout(Some(timeout)))
-        .map_err(|err| format!("timeout setup: {err}"))?;
-    stream
+    let mut stream = loopback_connect(port, timeout, timeout, tls)?;
+    // A listener demanding client certificates refuses the probe mid-handshake;
+    // it answered in TLS, which is all a liveness check can ask of it.
+    let io_error = |stage: &str, err: std::io::Error| {
+        if tls && rust_s3_server::server::tls::is_client_certificate_demand(&err) {
+            Ok(())
+        } else {
+            Err(format!("{stage}: {err}"))
+        }
+    };
+    if let Err(err) = stream
         .write_all(b"GET /minio/health/live HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n")
-        .map_err(|err| format!("write: {err}"))?;
+    {
+        return io_error("write", err);
+    }
 
     let mut response = Vec::new();
     // The status line is all that matters; cap the read so a chatty or wedged
@@ -118,7 +401,7 @@ fn health_probe(port: u16, timeout_secs: u64) -> Result<(), String> {
         match stream.read(&mut buffer) {
             Ok(0) => break,
             Ok(n) => response.extend_from_slice(&buffer[..n]),
-            Err(err) => return Err(format!("read: {err}")),
+            Err(err) => return io_error("read", err),
         }
         if response.windows(4).any(|w| w == b"\r\n\r\n") {
             break;
@@ -133,24 +416,505 @@ fn health_probe(port: u16, timeout_secs: u64) -> Result<(), String> {
     }
 }
 
+trait ReadWrite: std::io::Read + std::io::Write {}
+impl<T: std::io::Read + std::io::Write> ReadWrite for T {}
+
+/// Connects to one of our own listeners. Always loopback: the configured bind
+/// address may be 0.0.0.0, which is not a valid destination, and the callers
+/// are by definition local.
+fn loopback_connect(
+    port: u16,
+    connect_timeout: std::time::Duration,
+    io_timeout: std::time::Duration,
+    tls: bool,
+) -> Result<Box<dyn ReadWrite>, String> {
+    use std::net::{SocketAddr, TcpStream};
+
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    let tcp = TcpStream::connect_timeout(&address, connect_timeout)
+        .map_err(|err| format!("connect: {err}"))?;
+    tcp.set_read_timeout(Some(io_timeout))
+        .and_then(|()| tcp.set_write_timeout(Some(io_timeout)))
+        .map_err(|err| format!("timeout setup: {err}"))?;
+    Ok(if tls {
+        Box::new(rust_s3_server::server::tls::loopback_probe(tcp)?)
+    } else {
+        Box::new(tcp)
+    })
+}
+
+/// One JSON request to the local console, same hand-rolled HTTP/1.1 as the
+/// health probe. Returns the status, the session cookie if one was set, and
+/// the body. Console JSON replies carry a Content-Length, so reading to EOF
+/// under `Connection: close` is the whole body.
+fn console_request(
+    port: u16,
+    tls: bool,
+    method: &str,
+    path: &str,
+    cookie: Option<&str>,
+    body: &str,
+) -> Result<(u16, Option<String>, String), String> {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    // A snapshot answers only once it is complete; allow it the time.
+    let mut stream = loopback_connect(port, Duration::from_secs(5), Duration::from_secs(3600), tls)?;
+    let cookie = cookie.map(|c| format!("Cookie: {c}\r\n")).unwrap_or_default();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n{cookie}\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|err| format!("write: {err}"))?;
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|err| format!("read: {err}"))?;
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("malformed response from the console")?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or("malformed status line from the console")?;
+    let session = head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("set-cookie")
+            .then(|| value.trim().split(';').next().unwrap_or_default().to_string())
+    });
+    Ok((status, session, body.to_string()))
+}
+
+/// Asks the running server to take a snapshot: log in to the console as an
+/// admin, then `POST /api/snapshots`. Returns the new snapshot's manifest.
+fn snapshot_via_console(
+    config: &AppConfig,
+    user: &str,
+    password: &str,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    if !config.ui.enabled {
+        return Err("the data directory is in use by a running server and its console is \
+                    disabled; stop the server or enable the console to take a snapshot"
+            .into());
+    }
+    let (port, tls) = (config.ui.bind_port, config.ui.tls.enabled);
+    let login = serde_json::json!({ "username": user, "password": password }).to_string();
+    let (mut status, mut session, mut body) =
+        console_request(port, tls, "POST", "/api/login", None, &login)?;
+    if status == 200 && session.is_none() {
+        // The account has two-factor login: the password step returned a
+        // ticket instead of a session.
+        let step: serde_json::Value = serde_json::from_str(&body)?;
+        if step["mfa"] != "verify" {
+            return Err(format!(
+                "{user} must set up two-factor authentication in the console before using it here"
+            )
+            .into());
+        }
+        let code = prompt_line("Authentication code: ")?;
+        let second = serde_json::json!({ "ticket": step["ticket"], "code": code }).to_string();
+        (status, session, body) =
+            console_request(port, tls, "POST", "/api/login/mfa", None, &second)?;
+    }
+    let Some(session) = session.filter(|_| status == 200) else {
+        return Err(format!("console login failed ({status}): {body}").into());
+    };
+    let (status, _, body) =
+        console_request(port, tls, "POST", "/api/snapshots", Some(&session), "")?;
+    let _ = console_request(port, tls, "POST", "/api/logout", Some(&session), "");
+    if status != 200 {
+        return Err(format!("snapshot failed ({status}): {body}").into());
+    }
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// The console login for commands that go through a running server,
+/// prompting for whatever was not given on the command line.
+fn console_credentials(
+    user: Option<String>,
+    password: Option<String>,
+) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let user = match user {
+        Some(user) => user,
+        None => prompt_line("Console admin user: ")?,
+    };
+    let password = match password {
+        Some(value) => value,
+        None => rpassword::prompt_password("Password: ")?,
+    };
+    Ok((user, password))
+}
+
+async fn run_backup(
+    config: &str,
+    out: &str,
+    since: Option<&str>,
+    user: Option<String>,
+    password: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use rust_s3_server::server::{backup, snapshot};
+
+    let config = AppConfig::from_file(config)?;
+    let (out, since) = (std::path::Path::new(out), since.map(std::path::Path::new));
+    let (_, summary) = if snapshot::data_dir_in_use(&config) {
+        let (user, password) = console_credentials(user, password)?;
+        let manifest = snapshot_via_console(&config, &user, &password)?;
+        let id = manifest["id"].as_str().unwrap_or_default();
+        backup::write_from_snapshot(&config, id, since, out).await?
+    } else {
+        backup::write_offline(&config, since, out).await?
+    };
+    println!(
+        "backup written to {}: {} buckets ({} in full), {} objects, {} deletes, {} bytes",
+        out.display(),
+        summary.buckets,
+        summary.full_buckets.len(),
+        summary.objects,
+        summary.deletes,
+        summary.bytes
+    );
+    println!("next checkpoint: {}", backup::checkpoint_path(out).display());
+    Ok(())
+}
+
+async fn run_snapshot(action: SnapshotAction) -> Result<(), Box<dyn std::error::Error>> {
+    use rust_s3_server::server::snapshot;
+    use rust_s3_server::storage::snapshot::{list_snapshots, prune_snapshots};
+
+    match action {
+        SnapshotAction::Create {
+            config,
+            user,
+            password,
+        } => {
+            let config = AppConfig::from_file(&config)?;
+            let (id, objects) = if snapshot::data_dir_in_use(&config) {
+                let (user, password) = console_credentials(user, password)?;
+                let manifest = snapshot_via_console(&config, &user, &password)?;
+                let objects = manifest["buckets"]
+                    .as_array()
+                    .map(|b| b.iter().filter_map(|b| b["objects"].as_u64()).sum())
+                    .unwrap_or(0u64);
+                (manifest["id"].as_str().unwrap_or_default().to_string(), objects)
+            } else {
+                let manifest = snapshot::take_offline(&config).await?;
+                let objects = manifest.objects();
+                (manifest.id, objects)
+            };
+            let dir = snapshot::snapshots_dir(&config).join(&id);
+            println!("snapshot {id} complete: {objects} objects in {}", dir.display());
+        }
+        SnapshotAction::List { config } => {
+            let config = AppConfig::from_file(&config)?;
+            for manifest in list_snapshots(&snapshot::snapshots_dir(&config)).await? {
+                println!(
+                    "{}  buckets={} objects={} bytes={}",
+                    manifest.id,
+                    manifest.buckets.len(),
+                    manifest.objects(),
+                    manifest.bytes()
+                );
+            }
+        }
+        SnapshotAction::Prune { config, keep } => {
+            let config = AppConfig::from_file(&config)?;
+            for id in prune_snapshots(&snapshot::snapshots_dir(&config), keep).await? {
+                println!("deleted {id}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `rusts3 admin`'s connection to the console API.
+struct AdminClient {
+    http: reqwest::Client,
+    base: reqwest::Url,
+    token: Option<String>,
+}
+
+impl AdminClient {
+    fn new(args: &AdminArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        let url = args
+            .url
+            .clone()
+            .or_else(|| std::env::var("RUSTS3_ADMIN_URL").ok())
+            .unwrap_or_else(|| "http://127.0.0.1:8003".to_string());
+        let token = args
+            .token
+            .clone()
+            .or_else(|| std::env::var("RUSTS3_ADMIN_TOKEN").ok())
+            .filter(|token| !token.is_empty());
+        let http = reqwest::Client::builder()
+            .danger_accept_invalid_certs(args.insecure)
+            .build()?;
+        let base = reqwest::Url::parse(&url).map_err(|err| format!("--url {url}: {err}"))?;
+        Ok(Self { http, base, token })
+    }
+
+    /// `segments` appended to the console URL, each percent-encoded.
+    fn endpoint(&self, segments: &[&str]) -> Result<reqwest::Url, Box<dyn std::error::Error>> {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|()| format!("{} cannot be a console URL", self.base))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// One request with the API token. Returns the reply's JSON.
+    async fn call(
+        &self,
+        method: reqwest::Method,
+        segments: &[&str],
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or("no API token: set RUSTS3_ADMIN_TOKEN or pass --token")?;
+        let bearer = format!("Bearer {token}");
+        let (reply, _) = self
+            .send(method, segments, body, Some((reqwest::header::AUTHORIZATION, &bearer)))
+            .await?;
+        Ok(reply)
+    }
+
+    /// Returns the reply's JSON and the session cookie, if one was set. A
+    /// failed request becomes an error carrying the console's message.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        segments: &[&str],
+        body: Option<serde_json::Value>,
+        credential: Option<(reqwest::header::HeaderName, &str)>,
+    ) -> Result<(serde_json::Value, Option<String>), Box<dyn std::error::Error>> {
+        let mut request = self.http.request(method, self.endpoint(segments)?);
+        if let Some((name, value)) = credential {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let cookie = response
+            .headers()
+            .get(reqwest::header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(str::to_string);
+        let text = response.text().await?;
+        let reply = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+        if !status.is_success() {
+            let message = reply["error"].as_str().map_or_else(|| reply.to_string(), str::to_string);
+            return Err(format!("{status}: {message}").into());
+        }
+        Ok((reply, cookie))
+    }
+
+    /// Signs in to the console, asking for an authentication code when the
+    /// account has two-factor login. Returns the session cookie.
+    async fn login(
+        &self,
+        user: &str,
+        password: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let body = serde_json::json!({ "username": user, "password": password });
+        let (step, cookie) = self
+            .send(reqwest::Method::POST, &["api", "login"], Some(body), None)
+            .await?;
+        if let Some(cookie) = cookie {
+            return Ok(cookie);
+        }
+        if step["mfa"] != "verify" {
+            return Err(format!(
+                "{user} must set up two-factor authentication in the console before using it here"
+            )
+            .into());
+        }
+        let code = prompt_line("Authentication code: ")?;
+        let body = serde_json::json!({ "ticket": step["ticket"], "code": code });
+        let (_, cookie) = self
+            .send(reqwest::Method::POST, &["api", "login", "mfa"], Some(body), None)
+            .await?;
+        cookie.ok_or_else(|| "the console did not start a session".into())
+    }
+}
+
+/// Reads a policy document from `path`, checking only that it is JSON; the
+/// server validates the rest.
+fn read_policy(path: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    Ok(serde_json::from_str(&text).map_err(|err| format!("{path}: {err}"))?)
+}
+
+async fn run_admin(args: AdminArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use reqwest::Method;
+    use serde_json::json;
+
+    let client = AdminClient::new(&args)?;
+    let reply = match args.command {
+        AdminCommand::User { action } => match action {
+            UserAction::List => client.call(Method::GET, &["api", "users"], None).await?,
+            UserAction::Create { name, password } => {
+                let body = json!({ "username": name, "password": new_password(password)? });
+                client.call(Method::POST, &["api", "users"], Some(body)).await?
+            }
+            UserAction::Delete { name } => {
+                client.call(Method::DELETE, &["api", "users", &name], None).await?
+            }
+            UserAction::Password { name, password } => {
+                let body = json!({ "password": new_password(password)? });
+                client.call(Method::PUT, &["api", "users", &name, "password"], Some(body)).await?
+            }
+            UserAction::Groups { name, groups } => {
+                let body = json!({ "groups": groups });
+                client.call(Method::PUT, &["api", "users", &name, "groups"], Some(body)).await?
+            }
+        },
+        AdminCommand::Group { action } => match action {
+            GroupAction::List => client.call(Method::GET, &["api", "groups"], None).await?,
+            GroupAction::Create { name, policy } => {
+                let policy = policy.as_deref().map(read_policy).transpose()?;
+                let body = json!({ "name": name, "policy": policy });
+                client.call(Method::POST, &["api", "groups"], Some(body)).await?
+            }
+            GroupAction::Delete { name } => {
+                client.call(Method::DELETE, &["api", "groups", &name], None).await?
+            }
+        },
+        AdminCommand::Policy { action } => {
+            let (target, policy) = match action {
+                PolicyAction::Set { target, file } => (target, read_policy(&file)?),
+                PolicyAction::Clear { target } => (target, serde_json::Value::Null),
+            };
+            let (kind, name) = match (target.user, target.group) {
+                (Some(user), _) => ("users", user),
+                (None, Some(group)) => ("groups", group),
+                (None, None) => unreachable!("clap requires --user or --group"),
+            };
+            client.call(Method::PUT, &["api", kind, &name, "policy"], Some(policy)).await?
+        }
+        AdminCommand::Key { action } => match action {
+            KeyAction::List { user } => {
+                client.call(Method::GET, &["api", "users", &user, "keys"], None).await?
+            }
+            KeyAction::Create { user } => {
+                client.call(Method::POST, &["api", "users", &user, "keys"], None).await?
+            }
+            KeyAction::Delete { access_key } => {
+                client.call(Method::DELETE, &["api", "keys", &access_key], None).await?
+            }
+            KeyAction::Activate { access_key } => {
+                let body = json!({ "active": true });
+                client.call(Method::PUT, &["api", "keys", &access_key], Some(body)).await?
+            }
+            KeyAction::Deactivate { access_key } => {
+                let body = json!({ "active": false });
+                client.call(Method::PUT, &["api", "keys", &access_key], Some(body)).await?
+            }
+            KeyAction::Rotate { access_key } => {
+                client.call(Method::POST, &["api", "keys", &access_key, "rotate"], None).await?
+            }
+        },
+        AdminCommand::Bucket { action } => match action {
+            BucketAction::List => client.call(Method::GET, &["api", "buckets"], None).await?,
+            BucketAction::Create { name, root } => {
+                let body = json!({ "name": name, "root": root });
+                client.call(Method::POST, &["api", "buckets"], Some(body)).await?
+            }
+            BucketAction::Delete { name } => {
+                client.call(Method::DELETE, &["api", "buckets", &name], None).await?
+            }
+        },
+        AdminCommand::Scan { action } => match action {
+            ScanAction::Start { buckets } => {
+                let body = json!({ "buckets": buckets });
+                client.call(Method::POST, &["api", "perf", "scan"], Some(body)).await?
+            }
+            ScanAction::Status => client.call(Method::GET, &["api", "perf", "scan"], None).await?,
+        },
+        AdminCommand::Snapshot { action } => match action {
+            AdminSnapshotAction::List => {
+                client.call(Method::GET, &["api", "snapshots"], None).await?
+            }
+            AdminSnapshotAction::Create => {
+                client.call(Method::POST, &["api", "snapshots"], None).await?
+            }
+            AdminSnapshotAction::Delete { id } => {
+                client.call(Method::DELETE, &["api", "snapshots", &id], None).await?
+            }
+        },
+        AdminCommand::Token { action } => match action {
+            TokenAction::List => client.call(Method::GET, &["api", "tokens"], None).await?,
+            TokenAction::Create {
+                name,
+                scopes,
+                days,
+                user,
+                password,
+            } => {
+                let (user, password) = console_credentials(user, password)?;
+                let cookie = client.login(&user, &password).await?;
+                let body = json!({ "name": name, "scopes": scopes, "expires_in_days": days });
+                let credential = Some((reqwest::header::COOKIE, cookie.as_str()));
+                let created = client
+                    .send(Method::POST, &["api", "tokens"], Some(body), credential.clone())
+                    .await;
+                let _ = client.send(Method::POST, &["api", "logout"], None, credential).await;
+                created?.0
+            }
+            TokenAction::Revoke { id } => {
+                client.call(Method::DELETE, &["api", "tokens", &id], None).await?
+            }
+        },
+    };
+    println!("{}", serde_json::to_string_pretty(&reply)?);
+    Ok(())
+}
+
+/// A new password: `given`, or entered twice at the prompt.
+fn new_password(given: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(value) = given {
+        return Ok(value);
+    }
+    let first = rpassword::prompt_password("Password: ")?;
+    let second = rpassword::prompt_password("Confirm password: ")?;
+    if first != second {
+        return Err("passwords do not match".into());
+    }
+    Ok(first)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
     match cli.command {
-        Some(Command::Run { config }) => run_server(AppConfig::from_file(&config)?).await,
+        Some(Command::Run { config }) => {
+            run_server(AppConfig::from_file(&config)?, Some(config)).await
+        }
         Some(Command::GenPassword { cost, password }) => {
-            let password = match password {
-                Some(value) => value,
-                None => {
-                    let first = rpassword::prompt_password("Password: ")?;
-                    let second = rpassword::prompt_password("Confirm password: ")?;
-                    if first != second {
-                        return Err("passwords do not match".into());
-                    }
-                    first
-                }
-            };
-            println!("{}", bcrypt::hash(password, cost)?);
+            println!("{}", bcrypt::hash(new_password(password)?, cost)?);
+            Ok(())
+        }
+        Some(Command::GenTotp { user }) => {
+            use rust_s3_server::server::totp;
+            let secret = totp::generate_secret();
+            let uri = totp::provisioning_uri("RustS3", &user, &secret);
+            let qr = qrcode::QrCode::new(uri.as_bytes())?
+                .render::<qrcode::render::unicode::Dense1x2>()
+                .quiet_zone(true)
+                .build();
+            println!("{qr}");
+            println!("totp_secret: \"{}\"", totp::base32_encode(&secret));
+            println!("{uri}");
             Ok(())
         }
         Some(Command::VerifyPassword { hash, cleartext }) => {
@@ -172,7 +936,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Some(Command::HealthCheck { config, timeout }) => {
             let config = AppConfig::from_file(&config)?;
-            match health_probe(config.server.bind_port, timeout) {
+            match health_probe(config.server.bind_port, timeout, config.server.tls.enabled) {
                 Ok(()) => {
                     println!("ok");
                     Ok(())
@@ -189,13 +953,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
         Some(Command::Init) => write_default_config(),
+        Some(Command::Snapshot { action }) => run_snapshot(action).await,
+        Some(Command::Restore { config, from }) => {
+            let config = AppConfig::from_file(&config)?;
+            let manifest = rust_s3_server::server::snapshot::restore(
+                &config,
+                std::path::Path::new(&from),
+            )
+            .await?;
+            println!(
+                "restored snapshot {} into {}: {} buckets, {} objects",
+                manifest.id,
+                config.server.base_dir,
+                manifest.buckets.len(),
+                manifest.objects()
+            );
+            Ok(())
+        }
+        Some(Command::Backup {
+            config,
+            out,
+            since,
+            user,
+            password,
+        }) => run_backup(&config, &out, since.as_deref(), user, password).await,
+        Some(Command::BackupApply { config, from }) => {
+            let config = AppConfig::from_file(&config)?;
+            let (_, summary) =
+                rust_s3_server::server::backup::apply(&config, std::path::Path::new(&from)).await?;
+            println!(
+                "applied {from} into {}: {} buckets, {} objects ({} already current), {} deletes",
+                config.server.base_dir,
+                summary.buckets,
+                summary.objects,
+                summary.unchanged,
+                summary.deletes
+            );
+            Ok(())
+        }
+        Some(Command::Admin(args)) => run_admin(args).await,
         None if cli.init => write_default_config(),
         None => {
-            let cfg = match cli.config {
-                Some(path) => AppConfig::from_file(&path)?,
+            let cfg = match &cli.config {
+                Some(path) => AppConfig::from_file(path)?,
                 None => AppConfig::default(),
             };
-            run_server(cfg).await
+            run_server(cfg, cli.config).await
         }
     }
 }
@@ -223,7 +1026,12 @@ fn prompt_line(prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
     Ok(value.trim_end().to_string())
 }
 
-async fn run_server(cfg: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+/// Starts the server. `config_path` is the file `cfg` came from, re-read when
+/// the configuration is reloaded (SIGHUP or the console).
+async fn run_server(
+    cfg: AppConfig,
+    config_path: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
 
     init_logging(&cfg.logging)?;
 
@@ -264,13 +1072,14 @@ async fn run_server(cfg: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
         address,
         root: cfg.server.base_dir.clone(),
         app_config: Arc::new(cfg),
+        config_path,
     })
     .await
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Cli, Command};
+    use super::{AdminCommand, Cli, Command, KeyAction, PolicyAction, SnapshotAction, TokenAction};
     use clap::Parser;
 
     #[test]
@@ -306,4 +1115,85 @@ mod tests {
         ])
         .is_ok());
     }
+
+    #[test]
+    fn snapshot_and_restore_parse() {
+        let prune = Cli::try_parse_from(["rusts3", "snapshot", "prune", "--keep", "3"]).unwrap();
+        assert!(matches!(
+            prune.command,
+            Some(Command::Snapshot { action: SnapshotAction::Prune { config, keep: 3 } })
+                if config == "config.yaml"
+        ));
+        let create = Cli::try_parse_from(["rusts3", "snapshot", "create", "--user", "admin"]).unwrap();
+        assert!(matches!(
+            create.command,
+            Some(Command::Snapshot { action: SnapshotAction::Create { user: Some(_), password: None, .. } })
+        ));
+        // Restoring needs to be told what from.
+        assert!(Cli::try_parse_from(["rusts3", "restore"]).is_err());
+        assert!(Cli::try_parse_from(["rusts3", "restore", "--from", "/snap/x"]).is_ok());
+    }
+
+    #[test]
+    fn backup_commands_parse() {
+        let backup =
+            Cli::try_parse_from(["rusts3", "backup", "--out", "b.rs3b", "--since", "a.rs3b.checkpoint"])
+                .unwrap();
+        assert!(matches!(
+            backup.command,
+            Some(Command::Backup { out, since: Some(since), .. })
+                if out == "b.rs3b" && since == "a.rs3b.checkpoint"
+        ));
+        assert!(Cli::try_parse_from(["rusts3", "backup"]).is_err());
+        let apply = Cli::try_parse_from(["rusts3", "backup-apply", "--from", "b.rs3b"]).unwrap();
+        assert!(matches!(apply.command, Some(Command::BackupApply { from, .. }) if from == "b.rs3b"));
+    }
+
+    #[test]
+    fn admin_commands_parse() {
+        let rotate = Cli::try_parse_from(["rusts3", "admin", "key", "rotate", "AKIA1"]).unwrap();
+        assert!(matches!(
+            rotate.command,
+            Some(Command::Admin(args)) if matches!(
+                &args.command,
+                AdminCommand::Key { action: KeyAction::Rotate { access_key } }
+                    if access_key == "AKIA1"
+            ) && args.url.is_none()
+        ));
+        let policy = Cli::try_parse_from([
+            "rusts3", "admin", "--url", "https://console:8003", "policy", "set", "--group", "ops",
+            "ops.json",
+        ])
+        .unwrap();
+        assert!(matches!(
+            policy.command,
+            Some(Command::Admin(args)) if matches!(
+                &args.command,
+                AdminCommand::Policy { action: PolicyAction::Set { target, file } }
+                    if target.group.as_deref() == Some("ops") && file == "ops.json"
+            ) && args.url.as_deref() == Some("https://console:8003")
+        ));
+        // A policy targets exactly one of a user or a group.
+        assert!(Cli::try_parse_from(["rusts3", "admin", "policy", "clear"]).is_err());
+        assert!(Cli::try_parse_from([
+            "rusts3", "admin", "policy", "clear", "--user", "a", "--group", "b"
+        ])
+        .is_err());
+        // A token needs at least one scope.
+        let unscoped = Cli::try_parse_from(["rusts3", "admin", "token", "create", "--name", "ci"]);
+        assert!(unscoped.is_err());
+        let token = Cli::try_parse_from([
+            "rusts3", "admin", "token", "create", "--name", "ci", "--scope", "admin:ManageUsers",
+            "--scope", "s3:*",
+        ])
+        .unwrap();
+        assert!(matches!(
+            token.command,
+            Some(Command::Admin(args)) if matches!(
+                &args.command,
+                AdminCommand::Token { action: TokenAction::Create { scopes, days: 30, .. } }
+                    if scopes.len() == 2
+            )
+        ));
+    }
 }
@@ -0,0 +1,530 @@
+//! S3 server access logging (`PUT /{bucket}?logging`).
+//!
+//! [`access_log_middleware`] turns every request against a bucket with logging
+//! enabled into one line of AWS's server access log format and parks it in an
+//! [`AccessLogBuffer`], keyed by source bucket. The `access_log` job drains the
+//! buffer on an interval and writes each bucket's lines as one object into its
+//! configured target bucket. Lines follow the documented AWS field order so
+//! existing S3 log tooling parses them unchanged; fields this server has no
+//! equivalent for (bucket owner, host id, TLS details, …) are `-`.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{header, HeaderMap, Method, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::{TimeZone, Utc};
+use futures::TryStreamExt;
+
+use super::{
+    header_value, parse_s3_query, percent_decode, HostStyleRewrite, OperationActor, RequestId,
+    S3ErrorCode,
+};
+use crate::storage::store::LocalObjectStore;
+use crate::storage::time::now_ms;
+
+/// Upper bound on undelivered lines across all buckets. Past it new records
+/// are counted and dropped rather than growing memory without limit while a
+/// target bucket is unavailable.
+const MAX_PENDING_RECORDS: usize = 100_000;
+
+#[derive(Default)]
+struct Pending {
+    lines: HashMap<String, Vec<String>>,
+    count: usize,
+}
+
+/// Formatted log lines waiting for delivery, grouped by source bucket.
+#[derive(Default)]
+pub(crate) struct AccessLogBuffer {
+    pending: Mutex<Pending>,
+    dropped: AtomicU64,
+}
+
+impl AccessLogBuffer {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub(crate) fn push(&self, bucket: &str, line: String) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.count >= MAX_PENDING_RECORDS {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        pending.count += 1;
+        pending
+            .lines
+            .entry(bucket.to_string())
+            .or_default()
+            .push(line);
+    }
+
+    /// Takes everything pending, leaving the buffer empty.
+    pub(crate) fn drain(&self) -> HashMap<String, Vec<String>> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.count = 0;
+        std::mem::take(&mut pending.lines)
+    }
+
+    /// Puts undelivered lines back ahead of anything recorded since the drain,
+    /// so a retried delivery keeps chronological order.
+    pub(crate) fn requeue(&self, bucket: &str, mut lines: Vec<String>) {
+        let mut pending = self.pending.lock().unwrap();
+        let room = MAX_PENDING_RECORDS.saturating_sub(pending.count);
+        if lines.len() > room {
+            self.dropped
+                .fetch_add((lines.len() - room) as u64, Ordering::Relaxed);
+            lines.truncate(room);
+        }
+        pending.count += lines.len();
+        let entry = pending.lines.entry(bucket.to_string()).or_default();
+        lines.append(entry);
+        *entry = lines;
+    }
+
+    /// Records dropped at the cap since the last call.
+    pub(crate) fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// One request, as the fields of an AWS server access log line.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AccessRecord {
+    pub bucket: String,
+    pub time_ms: i64,
+    pub remote_ip: Option<IpAddr>,
+    pub requester: Option<String>,
+    pub request_id: String,
+    pub operation: String,
+    pub key: Option<String>,
+    pub request_uri: String,
+    pub status: u16,
+    pub error_code: Option<String>,
+    pub bytes_sent: u64,
+    pub object_size: Option<u64>,
+    pub total_time_ms: u64,
+    pub turn_around_ms: u64,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+    pub signature_version: Option<&'static str>,
+    pub auth_type: Option<&'static str>,
+    pub host_header: Option<String>,
+}
+
+impl AccessRecord {
+    /// The record as one line (no trailing newline), fields in AWS order:
+    /// owner, bucket, time, remote IP, requester, request id, operation, key,
+    /// request-URI, status, error code, bytes sent, object size, total time,
+    /// turn-around time, referer, user agent, version id, host id, signature
+    /// version, cipher suite, auth type, host header, TLS version, access point
+    /// ARN, ACL required.
+    pub(crate) fn to_line(&self) -> String {
+        let dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        let time = Utc
+            .timestamp_millis_opt(self.time_ms)
+            .single()
+            .unwrap_or_else(Utc::now)
+            .format("[%d/%b/%Y:%H:%M:%S %z]");
+        let fields = [
+            "-".to_string(),
+            self.bucket.clone(),
+            time.to_string(),
+            dash(self.remote_ip.map(|ip| ip.to_string())),
+            dash(self.requester.as_deref().map(token)),
+            token(&self.request_id),
+            self.operation.clone(),
+            dash(self.key.as_deref().map(encode_key)),
+            quoted(Some(&self.request_uri)),
+            self.status.to_string(),
+            dash(self.error_code.as_deref().map(token)),
+            dash((self.bytes_sent > 0).then(|| self.bytes_sent.to_string())),
+            dash(self.object_size.map(|size| size.to_string())),
+            self.total_time_ms.to_string(),
+            self.turn_around_ms.to_string(),
+            quoted(self.referer.as_deref()),
+            quoted(self.user_agent.as_deref()),
+            "-".to_string(),
+            "-".to_string(),
+            dash(self.signature_version.map(str::to_string)),
+            "-".to_string(),
+            dash(self.auth_type.map(str::to_string)),
+            dash(self.host_header.as_deref().map(token)),
+            "-".to_string(),
+            "-".to_string(),
+            "-".to_string(),
+        ];
+        fields.join(" ")
+    }
+}
+
+/// A space-free field: whitespace and quotes would shift every later column.
+fn token(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_whitespace() || ch == '"' || ch.is_control() {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect()
+}
+
+/// A double-quoted field (`"-"` when absent). Embedded quotes are
+/// percent-encoded so the field always ends at the next quote.
+fn quoted(value: Option<&str>) -> String {
+    let value = value.unwrap_or("-");
+    let clean = value
+        .chars()
+        .filter(|ch| !ch.is_control())
+        .collect::<String>()
+        .replace('"', "%22");
+    format!("\"{clean}\"")
+}
+
+/// Object keys are logged URL-encoded with `/` kept literal, as S3 does.
+fn encode_key(key: &str) -> String {
+    urlencoding::encode(key).replace("%2F", "/")
+}
+
+/// Sub-resources that give a bucket-level request its operation name, checked
+/// in order.
+const BUCKET_SUBRESOURCES: [(&str, &str); 12] = [
+    ("cors", "CORS"),
+    ("logging", "LOGGING_STATUS"),
+    ("location", "LOCATION"),
+    ("uploads", "UPLOADS"),
+    ("versions", "BUCKETVERSIONS"),
+    ("versioning", "VERSIONING"),
+    ("acl", "ACL"),
+    ("policy", "BUCKETPOLICY"),
+    ("tagging", "TAGGING"),
+    ("lifecycle", "LIFECYCLE"),
+    ("website", "WEBSITE"),
+    ("delete", "MULTI_OBJECT_DELETE"),
+];
+
+/// The AWS operation name, `REST.<METHOD>.<RESOURCE>`.
+pub(crate) fn aws_operation(
+    method: &Method,
+    has_key: bool,
+    query: &HashMap<String, String>,
+    is_copy: bool,
+) -> String {
+    let resource = if !has_key {
+        if *method == Method::POST && query.contains_key("rebuildIndex") {
+            "REBUILD_INDEX"
+        } else if *method == Method::POST && !query.contains_key("delete") {
+            "OBJECT"
+        } else {
+            BUCKET_SUBRESOURCES
+                .iter()
+                .find(|(name, _)| query.contains_key(*name))
+                .map(|(_, resource)| *resource)
+                .unwrap_or("BUCKET")
+        }
+    } else {
+        let multipart = query.contains_key("uploadId");
+        match *method {
+            Method::PUT if is_copy && query.contains_key("partNumber") => {
+                return "REST.COPY.PART".to_string()
+            }
+            Method::PUT if is_copy => return "REST.COPY.OBJECT".to_string(),
+            Method::PUT if query.contains_key("partNumber") => "PART",
+            Method::POST if query.contains_key("uploads") => "UPLOADS",
+            Method::GET | Method::POST | Method::DELETE if multipart => "UPLOAD",
+            _ => "OBJECT",
+        }
+    };
+    format!("REST.{}.{resource}", method.as_str())
+}
+
+/// `(signature version, auth type)` as AWS reports them.
+fn signature_details(
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> (Option<&'static str>, Option<&'static str>) {
+    if let Some(auth) = header_value(headers, header::AUTHORIZATION.as_str()) {
+        if auth.starts_with("AWS4-HMAC-SHA256 ") {
+            return (Some("SigV4"), Some("AuthHeader"));
+        }
+        if auth.starts_with("AWS ") {
+            return (Some("SigV2"), Some("AuthHeader"));
+        }
+    }
+    if query.contains_key("X-Amz-Algorithm") {
+        return (Some("SigV4"), Some("QueryString"));
+    }
+    if query.contains_key("AWSAccessKeyId") && query.contains_key("Signature") {
+        return (Some("SigV2"), Some("QueryString"));
+    }
+    (None, None)
+}
+
+/// Health and metrics endpoints live under `/minio/…` and are never
+/// attributed to a bucket of that name.
+fn is_service_path(path: &str) -> bool {
+    path.starts_with("/minio/health/")
+        || path.starts_with("/minio/v2/metrics/")
+        || path == "/minio/prometheus/metrics"
+}
+
+/// The full object size for a GET/HEAD: the `Content-Range` total on a partial
+/// response, `Content-Length` otherwise.
+fn response_object_size(headers: &HeaderMap) -> Option<u64> {
+    if let Some(range) = header_value(headers, header::CONTENT_RANGE.as_str()) {
+        return range
+            .rsplit_once('/')
+            .and_then(|(_, total)| total.parse().ok());
+    }
+    header_value(headers, header::CONTENT_LENGTH.as_str()).and_then(|v| v.parse().ok())
+}
+
+/// Whether the request's bucket has access logging enabled, as read by the
+/// CORS layer from the same cached bucket config it already looks up, so a
+/// request costs one bucket-config lookup rather than one per layer.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BucketLoggingEnabled(pub bool);
+
+#[derive(Clone)]
+pub(crate) struct AccessLogState {
+    pub store: LocalObjectStore,
+    pub buffer: Arc<AccessLogBuffer>,
+}
+
+/// Holds a finished request's record until its response body has been sent
+/// (or abandoned), so bytes sent and total time cover the whole transfer.
+struct PendingRecord {
+    record: AccessRecord,
+    buffer: Arc<AccessLogBuffer>,
+    bytes_sent: Arc<AtomicU64>,
+    start: Instant,
+}
+
+impl Drop for PendingRecord {
+    fn drop(&mut self) {
+        self.record.bytes_sent = self.bytes_sent.load(Ordering::Relaxed);
+        self.record.total_time_ms = self.start.elapsed().as_millis() as u64;
+        self.buffer.push(&self.record.bucket, self.record.to_line());
+    }
+}
+
+/// Records requests against buckets with access logging enabled. Sits inside
+/// the request-id layer and outside auth, so denied requests are logged too.
+pub(crate) async fn access_log_middleware(
+    State(state): State<AccessLogState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let decoded = percent_decode(&path);
+    let trimmed = decoded.trim_start_matches('/');
+    let (bucket, key) = match trimmed.split_once('/') {
+        Some((bucket, key)) => (
+            bucket.to_string(),
+            Some(key.to_string()).filter(|k| !k.is_empty()),
+        ),
+        None => (trimmed.to_string(), None),
+    };
+    if bucket.is_empty() || is_service_path(&path) {
+        return next.run(request).await;
+    }
+    let enabled = match request.extensions().get::<BucketLoggingEnabled>() {
+        Some(BucketLoggingEnabled(enabled)) => *enabled,
+        None => state
+            .store
+            .bucket_meta(&bucket)
+            .await
+            .map(|meta| meta.logging.is_some())
+            .unwrap_or(false),
+    };
+    if !enabled {
+        return next.run(request).await;
+    }
+
+    let start = Instant::now();
+    let headers = request.headers();
+    let query = parse_s3_query(request.uri().query().unwrap_or(""));
+    let is_copy = headers.contains_key("x-amz-copy-source");
+    let (signature_version, auth_type) = signature_details(headers, &query);
+    let signed_path = request
+        .extensions()
+        .get::<HostStyleRewrite>()
+        .map(|rewrite| rewrite.original_path.clone())
+        .unwrap_or(path);
+    let request_uri = format!(
+        "{} {}{} {:?}",
+        request.method(),
+        signed_path,
+        request
+            .uri()
+            .query()
+            .map(|q| format!("?{q}"))
+            .unwrap_or_default(),
+        request.version(),
+    );
+    let upload_size = (request.method() == Method::PUT && !is_copy)
+        .then(|| {
+            header_value(headers, "x-amz-decoded-content-length")
+                .or_else(|| header_value(headers, header::CONTENT_LENGTH.as_str()))
+                .and_then(|v| v.parse::<u64>().ok())
+        })
+        .flatten();
+    let mut record = AccessRecord {
+        bucket,
+        time_ms: now_ms(),
+        remote_ip: request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip()),
+        request_id: request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone())
+            .unwrap_or_default(),
+        operation: aws_operation(request.method(), key.is_some(), &query, is_copy),
+        key,
+        request_uri,
+        referer: header_value(headers, header::REFERER.as_str()),
+        user_agent: header_value(headers, header::USER_AGENT.as_str()),
+        signature_version,
+        auth_type,
+        host_header: header_value(headers, header::HOST.as_str()),
+        ..AccessRecord::default()
+    };
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD);
+
+    let response = next.run(request).await;
+    record.turn_around_ms = start.elapsed().as_millis() as u64;
+    record.status = response.status().as_u16();
+    record.error_code = response
+        .extensions()
+        .get::<S3ErrorCode>()
+        .map(|code| code.0.clone());
+    record.requester = response
+        .extensions()
+        .get::<OperationActor>()
+        .and_then(|actor| actor.username.clone().or_else(|| actor.access_key.clone()));
+    record.object_size = if is_read && record.key.is_some() && response.status().is_success() {
+        response_object_size(response.headers())
+    } else {
+        upload_size
+    };
+
+    let bytes_sent = Arc::new(AtomicU64::new(0));
+    let pending = PendingRecord {
+        record,
+        buffer: state.buffer.clone(),
+        bytes_sent: bytes_sent.clone(),
+        start,
+    };
+    let (parts, body) = response.into_parts();
+    let counted = body.into_data_stream().inspect_ok(move |bytes| {
+        // `pending` rides in the stream and pushes the record when it drops.
+        let _hold = &pending;
+        bytes_sent.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+    });
+    Response::from_parts(parts, Body::from_stream(counted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(raw: &str) -> HashMap<String, String> {
+        parse_s3_query(raw)
+    }
+
+    #[test]
+    fn operations_use_aws_names() {
+        let op = |method: Method, has_key: bool, raw: &str, copy: bool| {
+            aws_operation(&method, has_key, &query(raw), copy)
+        };
+        assert_eq!(op(Method::GET, true, "", false), "REST.GET.OBJECT");
+        assert_eq!(op(Method::PUT, true, "", true), "REST.COPY.OBJECT");
+        assert_eq!(
+            op(Method::PUT, true, "partNumber=2&uploadId=u", false),
+            "REST.PUT.PART"
+        );
+        assert_eq!(
+            op(Method::POST, true, "uploads", false),
+            "REST.POST.UPLOADS"
+        );
+        assert_eq!(
+            op(Method::POST, true, "uploadId=u", false),
+            "REST.POST.UPLOAD"
+        );
+        assert_eq!(
+            op(Method::GET, false, "list-type=2", false),
+            "REST.GET.BUCKET"
+        );
+        assert_eq!(
+            op(Method::PUT, false, "logging", false),
+            "REST.PUT.LOGGING_STATUS"
+        );
+        assert_eq!(
+            op(Method::POST, false, "delete", false),
+            "REST.POST.MULTI_OBJECT_DELETE"
+        );
+        assert_eq!(op(Method::HEAD, false, "", false), "REST.HEAD.BUCKET");
+    }
+
+    #[test]
+    fn lines_follow_the_aws_field_layout() {
+        let record = AccessRecord {
+            bucket: "photos".to_string(),
+            time_ms: 1_549_411_238_000,
+            remote_ip: Some("192.0.2.3".parse().unwrap()),
+            requester: Some("alice".to_string()),
+            request_id: "3E57427F3EXAMPLE".to_string(),
+            operation: "REST.GET.OBJECT".to_string(),
+            key: Some("2019/my photo.jpg".to_string()),
+            request_uri: "GET /photos/2019/my%20photo.jpg HTTP/1.1".to_string(),
+            status: 200,
+            bytes_sent: 113,
+            object_size: Some(113),
+            total_time_ms: 7,
+            turn_around_ms: 3,
+            user_agent: Some("aws-cli/2 \"quoted\"".to_string()),
+            signature_version: Some("SigV4"),
+            auth_type: Some("AuthHeader"),
+            host_header: Some("s3.example.com".to_string()),
+            ..AccessRecord::default()
+        };
+        assert_eq!(
+            record.to_line(),
+            "- photos [06/Feb/2019:00:00:38 +0000] 192.0.2.3 alice 3E57427F3EXAMPLE \
+             REST.GET.OBJECT 2019/my%20photo.jpg \"GET /photos/2019/my%20photo.jpg HTTP/1.1\" \
+             200 - 113 113 7 3 \"-\" \"aws-cli/2 %22quoted%22\" - - SigV4 - AuthHeader \
+             s3.example.com - - -"
+        );
+    }
+
+    #[test]
+    fn buffer_caps_pending_records_and_requeues_in_order() {
+        let buffer = AccessLogBuffer::new();
+        buffer.push("b", "second".to_string());
+        buffer.requeue("b", vec!["first".to_string()]);
+        let drained = buffer.drain();
+        assert_eq!(
+            drained["b"],
+            vec!["first".to_string(), "second".to_string()]
+        );
+        assert!(buffer.drain().is_empty());
+
+        for _ in 0..MAX_PENDING_RECORDS + 3 {
+            buffer.push("b", String::new());
+        }
+        assert_eq!(buffer.take_dropped(), 3);
+        assert_eq!(buffer.drain()["b"].len(), MAX_PENDING_RECORDS);
+    }
+}
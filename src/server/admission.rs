@@ -0,0 +1,446 @@
+//! Upload admission control and the low-disk guard.
+//!
+//! Every staging write — PUT, UploadPart, CopyObject, UploadPartCopy and
+//! browser POST uploads — takes a slot before its handler runs and holds it
+//! until the response is ready. Slots are bounded globally
+//! (`admission.max_concurrent_writes`) and by the declared bytes each bucket
+//! has in flight (`max_inflight_bytes_per_bucket`). A write that finds no
+//! slot waits in a bounded queue; one that still has none after
+//! `queue_timeout_ms`, or finds the queue full, gets `503 SlowDown`.
+//!
+//! Before queueing, the guard compares what the filesystem of the bucket's
+//! data root has left against the configured floor, counting the bytes
+//! already promised to admitted uploads on that root and the new upload's own
+//! length. Crossing it answers
+//! `507 XMinioStorageFull`, which clients do not retry — the point is to stop
+//! while RocksDB can still write its WAL and deletes can still free space.
+//! Reads, deletes and multipart completion (a rename) are never held back.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderValue, Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use tokio::sync::Notify;
+
+use crate::storage::layout::{disk_space, DiskSpace, StorageLayout};
+
+use super::config::AdmissionConfig;
+use super::reload::LiveConfig;
+use super::{header_value, operation_and_target, percent_decode, s3_error, RequestId};
+
+/// How long a `statvfs` result is reused. Uploads arrive far faster than free
+/// space moves, and the in-flight reservation covers the gap.
+const DISK_SPACE_TTL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Slots {
+    writes: usize,
+    queued: usize,
+    bucket_bytes: HashMap<String, u64>,
+    /// Declared bytes of every admitted upload, held against free space.
+    reserved: u64,
+}
+
+impl Slots {
+    fn fits(&self, config: &AdmissionConfig, bucket: &str, bytes: u64) -> bool {
+        if config.max_concurrent_writes > 0 && self.writes >= config.max_concurrent_writes {
+            return false;
+        }
+        let cap = config.max_inflight_bytes_per_bucket;
+        let in_flight = self.bucket_bytes.get(bucket).copied().unwrap_or(0);
+        cap == 0 || in_flight == 0 || in_flight.saturating_add(bytes) <= cap
+    }
+}
+
+pub(crate) struct Admission {
+    config: LiveConfig,
+    layout: StorageLayout,
+    slots: Mutex<Slots>,
+    released: Notify,
+    /// Last `statvfs` per data root, by index into the layout's roots.
+    disk: Mutex<HashMap<usize, (Instant, Option<DiskSpace>)>>,
+}
+
+/// An admitted write; dropping it frees the slot and wakes the queue.
+pub(crate) struct WritePermit {
+    admission: Arc<Admission>,
+    bucket: String,
+    bytes: u64,
+}
+
+impl Drop for WritePermit {
+    fn drop(&mut self) {
+        let mut slots = self.admission.slots.lock().unwrap();
+        slots.writes -= 1;
+        slots.reserved -= self.bytes;
+        if let Some(in_flight) = slots.bucket_bytes.get_mut(&self.bucket) {
+            *in_flight -= self.bytes;
+            if *in_flight == 0 {
+                slots.bucket_bytes.remove(&self.bucket);
+            }
+        }
+        drop(slots);
+        self.admission.released.notify_waiters();
+    }
+}
+
+struct Queued<'a>(&'a Mutex<Slots>);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().queued -= 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Refusal {
+    /// The queue is full, or the wait ran past the deadline.
+    Busy(&'static str),
+    /// The write would take its data root below the free-space floor.
+    DiskFull { available: u64, floor: u64 },
+}
+
+/// The free-space floor for a filesystem of `total` bytes.
+fn free_floor(config: &AdmissionConfig, total: u64) -> u64 {
+    let percent = (total as f64 * config.min_free_percent / 100.0) as u64;
+    config.min_free_bytes.max(percent)
+}
+
+impl Admission {
+    pub(crate) fn new(config: LiveConfig, layout: StorageLayout) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            layout,
+            slots: Mutex::new(Slots::default()),
+            released: Notify::new(),
+            disk: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn disk_space(&self, root: usize) -> Option<DiskSpace> {
+        let mut cached = self.disk.lock().unwrap();
+        let previous = cached.get(&root).copied();
+        if let Some((at, space)) = previous {
+            if at.elapsed() < DISK_SPACE_TTL {
+                return space;
+            }
+        }
+        let path = &self.layout.roots()[root];
+        let space = disk_space(path);
+        if space.is_none() && previous.is_none() {
+            log::warn!(
+                "admission: cannot stat {}; the low-disk guard is inactive there",
+                path.display()
+            );
+        }
+        cached.insert(root, (Instant::now(), space));
+        space
+    }
+
+    fn check_disk(
+        &self,
+        config: &AdmissionConfig,
+        bucket: &str,
+        bytes: u64,
+    ) -> Result<(), Refusal> {
+        if config.min_free_bytes == 0 && config.min_free_percent <= 0.0 {
+            return Ok(());
+        }
+        let root = self.layout.bucket_root(bucket).unwrap_or(0);
+        let Some(space) = self.disk_space(root) else {
+            return Ok(());
+        };
+        let reserved = {
+            let slots = self.slots.lock().unwrap();
+            if self.layout.roots().len() == 1 {
+                slots.reserved
+            } else {
+                slots
+                    .bucket_bytes
+                    .iter()
+                    .filter(|(other, _)| self.layout.bucket_root(other).unwrap_or(0) == root)
+                    .map(|(_, bytes)| bytes)
+                    .sum()
+            }
+        };
+        let floor = free_floor(config, space.total);
+        let available = space.available.saturating_sub(reserved);
+        if available.saturating_sub(bytes) < floor {
+            return Err(Refusal::DiskFull { available, floor });
+        }
+        Ok(())
+    }
+
+    fn take(self: &Arc<Self>, slots: &mut Slots, bucket: &str, bytes: u64) -> WritePermit {
+        slots.writes += 1;
+        slots.reserved += bytes;
+        *slots.bucket_bytes.entry(bucket.to_string()).or_default() += bytes;
+        WritePermit {
+            admission: Arc::clone(self),
+            bucket: bucket.to_string(),
+            bytes,
+        }
+    }
+
+    /// Admits a write of `bytes` declared bytes into `bucket`, waiting in the
+    /// queue for up to `queue_timeout_ms` when every slot is taken.
+    pub(crate) async fn admit(
+        self: &Arc<Self>,
+        config: &AdmissionConfig,
+        bucket: &str,
+        bytes: u64,
+    ) -> Result<WritePermit, Refusal> {
+        self.check_disk(config, bucket, bytes)?;
+        {
+            let mut slots = self.slots.lock().unwrap();
+            if slots.fits(config, bucket, bytes) {
+                return Ok(self.take(&mut slots, bucket, bytes));
+            }
+            if slots.queued >= config.max_queued_writes {
+                return Err(Refusal::Busy("upload queue is full"));
+            }
+            slots.queued += 1;
+        }
+        // Leaves the queue however the wait ends, including the client
+        // hanging up and the future being dropped.
+        let _queued = Queued(&self.slots);
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(config.queue_timeout_ms);
+        loop {
+            let released = self.released.notified();
+            tokio::pin!(released);
+            // Register before re-checking, so a release between the check and
+            // the await still wakes us.
+            released.as_mut().enable();
+            {
+                let mut slots = self.slots.lock().unwrap();
+                if slots.fits(config, bucket, bytes) {
+                    return Ok(self.take(&mut slots, bucket, bytes));
+                }
+            }
+            if tokio::time::timeout_at(deadline, released).await.is_err() {
+                return Err(Refusal::Busy("timed out waiting for an upload slot"));
+            }
+        }
+    }
+}
+
+/// The bucket and declared length of a request that stages a write, or `None`
+/// for everything admission leaves alone.
+fn staged_write(request: &Request<Body>) -> Option<(String, u64)> {
+    let method = request.method();
+    if !matches!(*method, Method::PUT | Method::POST) {
+        return None;
+    }
+    let is_copy = request.headers().contains_key("x-amz-copy-source");
+    let (op, _) = operation_and_target(method, request.uri(), is_copy);
+    if !matches!(op, "UPLOAD" | "UPLOAD_PART" | "COPY" | "COPY_PART" | "POST") {
+        return None;
+    }
+    let path = percent_decode(request.uri().path());
+    let bucket = path.trim_start_matches('/').split('/').next()?.to_string();
+    // Copies declare no body; their size is only known once the handler
+    // reads the source, so they hold a slot but reserve nothing.
+    let bytes = header_value(request.headers(), "x-amz-decoded-content-length")
+        .or_else(|| header_value(request.headers(), header::CONTENT_LENGTH.as_str()))
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    Some((bucket, bytes))
+}
+
+pub(crate) async fn admission_middleware(
+    State(admission): State<Arc<Admission>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some((bucket, bytes)) = staged_write(&request) else {
+        return next.run(request).await;
+    };
+    let config = admission.config.current().admission.clone();
+    let permit = match admission.admit(&config, &bucket, bytes).await {
+        Ok(permit) => permit,
+        Err(refusal) => {
+            let rid = request
+                .extensions()
+                .get::<RequestId>()
+                .map(|id| id.0.as_str())
+                .unwrap_or("-");
+            return refused(rid, &refusal, request.uri().path());
+        }
+    };
+    let response = next.run(request).await;
+    drop(permit);
+    response
+}
+
+fn refused(rid: &str, refusal: &Refusal, resource: &str) -> Response {
+    match refusal {
+        Refusal::Busy(reason) => {
+            log::warn!("[{rid}] admission refused {resource}: {reason}");
+            let mut response = s3_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "SlowDown",
+                format!("Server is busy ({reason}); retry shortly"),
+                resource,
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("2"));
+            response
+        }
+        Refusal::DiskFull { available, floor } => {
+            log::warn!(
+                "[{rid}] admission refused {resource}: low disk space available={available} floor={floor}"
+            );
+            s3_error(
+                StatusCode::INSUFFICIENT_STORAGE,
+                "XMinioStorageFull",
+                format!(
+                    "Storage backend has reached its minimum free drive threshold \
+                     ({available} bytes free, {floor} kept in reserve). Please delete a few \
+                     objects to proceed."
+                ),
+                resource,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::config::AppConfig;
+
+    fn admission(tmp: &tempfile::TempDir) -> Arc<Admission> {
+        Admission::new(
+            LiveConfig::new(Arc::new(AppConfig::default())),
+            StorageLayout::new(tmp.path()),
+        )
+    }
+
+    fn config() -> AdmissionConfig {
+        AdmissionConfig {
+            max_concurrent_writes: 1,
+            max_queued_writes: 1,
+            queue_timeout_ms: 50,
+            min_free_bytes: 0,
+            ..AdmissionConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_write_takes_the_slot_when_it_frees() {
+        let tmp = tempfile::tempdir().unwrap();
+        let admission = admission(&tmp);
+        let config = AdmissionConfig {
+            queue_timeout_ms: 5_000,
+            ..config()
+        };
+        let first = admission.admit(&config, "b", 10).await.unwrap();
+        let waiter = {
+            let admission = admission.clone();
+            let config = config.clone();
+            tokio::spawn(async move { admission.admit(&config, "b", 10).await.map(|_| ()) })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // Queue holds one; the next is turned away immediately.
+        assert_eq!(
+            admission.admit(&config, "b", 10).await.err(),
+            Some(Refusal::Busy("upload queue is full"))
+        );
+        drop(first);
+        assert_eq!(waiter.await.unwrap(), Ok(()));
+        assert_eq!(admission.slots.lock().unwrap().writes, 0);
+    }
+
+    #[tokio::test]
+    async fn queue_deadline_turns_into_busy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let admission = admission(&tmp);
+        let _held = admission.admit(&config(), "b", 0).await.unwrap();
+        assert_eq!(
+            admission.admit(&config(), "b", 0).await.err(),
+            Some(Refusal::Busy("timed out waiting for an upload slot"))
+        );
+        assert_eq!(admission.slots.lock().unwrap().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn bucket_byte_cap_is_per_bucket_and_admits_one_oversized_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        let admission = admission(&tmp);
+        let config = AdmissionConfig {
+            max_concurrent_writes: 0,
+            max_inflight_bytes_per_bucket: 100,
+            ..config()
+        };
+        let big = admission.admit(&config, "a", 500).await.unwrap();
+        assert!(admission.admit(&config, "a", 1).await.is_err());
+        let other = admission.admit(&config, "b", 60).await.unwrap();
+        assert!(admission.admit(&config, "b", 40).await.is_ok());
+        drop((big, other));
+        let slots = admission.slots.lock().unwrap();
+        assert_eq!((slots.writes, slots.reserved), (0, 0));
+        assert!(slots.bucket_bytes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn low_disk_guard_counts_reserved_and_declared_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let admission = admission(&tmp);
+        let space = disk_space(tmp.path()).unwrap();
+        let config = AdmissionConfig {
+            max_concurrent_writes: 0,
+            min_free_bytes: space.available / 2,
+            ..config()
+        };
+        let held = admission
+            .admit(&config, "b", space.available / 4)
+            .await
+            .unwrap();
+        match admission.admit(&config, "b", space.available / 3).await {
+            Err(Refusal::DiskFull { floor, .. }) => assert_eq!(floor, space.available / 2),
+            other => panic!("expected DiskFull, got {:?}", other.map(|_| ())),
+        }
+        drop(held);
+        assert!(admission
+            .admit(&config, "b", space.available / 4)
+            .await
+            .is_ok());
+        let percent = AdmissionConfig {
+            min_free_bytes: 0,
+            min_free_percent: 99.99,
+            ..config
+        };
+        assert!(free_floor(&percent, space.total) > space.total / 2);
+    }
+
+    #[test]
+    fn only_staged_writes_are_admitted() {
+        let request = |method: Method, uri: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_LENGTH, "42")
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(
+            staged_write(&request(Method::PUT, "/bkt/a%20b")),
+            Some(("bkt".to_string(), 42))
+        );
+        assert!(staged_write(&request(Method::PUT, "/bkt/k?partNumber=1&uploadId=u")).is_some());
+        assert!(staged_write(&request(Method::POST, "/bkt")).is_some());
+        assert_eq!(staged_write(&request(Method::PUT, "/bkt")), None);
+        assert_eq!(
+            staged_write(&request(Method::POST, "/bkt/k?uploadId=u")),
+            None
+        );
+        assert_eq!(staged_write(&request(Method::POST, "/bkt?delete")), None);
+        assert_eq!(staged_write(&request(Method::GET, "/bkt/k")), None);
+    }
+}
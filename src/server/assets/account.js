@@ -0,0 +1,51 @@
+// ── my account ─────────────────────────────────────────────────────────────
+// Self-service for whoever is signed in: change the password (the current
+// one is checked), the access keys in keys.js, admin API tokens for scripts,
+// the policies that decide what they may do — shown as rules where the rule
+// builder can express them — and their own recent entries in the audit trail.
+
+function loadAccount(){
+  $('accountSignIn').textContent=me.is_builtin?`${me.username} is a built-in user; its password and second factor are set in the server's config file.`:`Signed in as ${me.username}.`;
+  $('changePasswordBtn').classList.toggle('hidden',!!me.is_builtin);
+  loadMyKeys();loadApiTokens();loadMyPolicy();loadMyActivity();
+}
+
+function openOwnPasswordDialog(){for(const id of ['op_current','op_password','op_confirm'])$(id).value='';setInlineError('ownPasswordError');$('ownPasswordDlg').showModal();setTimeout(()=>$('op_current').focus(),50);}
+async function changeOwnPassword(event){event.preventDefault();const current_password=$('op_current').value,new_password=$('op_password').value;if(new_password.length<8){setInlineError('ownPasswordError','Password must be at least 8 characters.');return;}if(new_password!==$('op_confirm').value){setInlineError('ownPasswordError','Passwords do not match.');return;}try{await api('PUT','/api/me/password',{current_password,new_password});$('ownPasswordDlg').close();toast('Password changed','Your other sessions have been signed out.');}catch(e){setInlineError('ownPasswordError',e.message);}}
+
+let apiTokens=[],tokenMaxDays=365;
+async function loadApiTokens(){try{const data=await api('GET','/api/tokens');const tokens=apiTokens=data.tokens||[],all=!!data.all;tokenMaxDays=data.max_days||tokenMaxDays;$('tokenOwnerHead').classList.toggle('hidden',!all);$('tokenRows').innerHTML=tokens.map((t,i)=>`<tr><td><strong>${esc(t.name)}</strong><div class="muted" style="font-size:11.5px"><code>${esc(t.id)}</code> · created ${fmtTime(t.created_at_ms)}</div></td>${all?`<td>${esc(t.username)}${t.is_builtin?' <span class="badge amber">Built-in</span>':''}</td>`:''}<td>${t.scopes.map(s=>`<code>${esc(s)}</code>`).join(' ')}</td><td class="muted">${fmtTime(t.expires_at_ms)}</td><td class="actions"><button class="row-action danger" title="Revoke token" onclick="confirmRevokeToken(${i})">${icons.trash}</button></td></tr>`).join('');const empty=!tokens.length;$('tokenRows').closest('table').classList.toggle('hidden',empty);$('tokenEmpty').classList.toggle('hidden',!empty);if(empty)$('tokenEmpty').innerHTML=`<div class="empty-icon">${icons.key}</div><h3>No API tokens</h3><p>Create one to automate administration without signing in.</p>`;}catch(e){toast('Could not load API tokens',e.message,false);}}
+function openTokenDialog(){$('tk_name').value='';$('tk_scopes').value='';$('tk_days').value=30;$('tk_days').max=tokenMaxDays;setInlineError('tokenError');$('tokenDlg').showModal();setTimeout(()=>$('tk_name').focus(),50);}
+async function createApiToken(event){event.preventDefault();const name=$('tk_name').value.trim(),scopes=$('tk_scopes').value.split(/[\s,]+/).filter(Boolean),expires_in_days=Number($('tk_days').value);if(!name){setInlineError('tokenError','Give the token a name.');return;}if(!scopes.length){setInlineError('tokenError','Add at least one scope.');return;}try{const data=await api('POST','/api/tokens',{name,scopes,expires_in_days});$('tokenDlg').close();$('tk_secret').textContent=data.token;$('tokenSecretDlg').showModal();loadApiTokens();}catch(e){setInlineError('tokenError',e.message);}}
+async function copyApiToken(){const ok=await copyText($('tk_secret').textContent);toast(ok?'Token copied':'Copy failed',ok?'':'Select the text and press Ctrl+C',ok);}
+function confirmRevokeToken(i){const t=apiTokens[i];showConfirm('Revoke API token?',t.name,'Scripts using this token will immediately lose access.',async()=>{await api('DELETE','/api/tokens/'+encodeURIComponent(t.id));toast('API token revoked',t.name);await loadApiTokens();},{confirmLabel:'Revoke',busyLabel:'Revoking…'});}
+
+const ACCESS_LABELS={read:'Read',write:'Write + delete',readwrite:'Read + write + delete'};
+function renderPolicySource(src){
+  const title=src.kind==='user'?'Your policy':`Group ${esc(src.name)}`;
+  let body;
+  if(!src.policy)body='<p class="muted">No policy attached.</p>';
+  else if(src.rules&&src.rules.length)body=`<div class="table-wrap"><table><thead><tr><th>Effect</th><th>Access</th><th>Bucket</th><th>Prefix</th></tr></thead><tbody>${src.rules.map(r=>`<tr><td><span class="badge ${r.effect==='Deny'?'red':'green'}">${esc(r.effect)}</span></td><td>${esc(ACCESS_LABELS[r.access]||r.access)}</td><td><code>${esc(r.bucket)}</code></td><td>${r.prefix?`<code>${esc(r.prefix)}</code>`:'<span class="muted">any</span>'}</td></tr>`).join('')}</tbody></table></div>`;
+  else body=`<p class="muted">Written as policy JSON; the rule view cannot show it.</p><div class="secret-box">${esc(JSON.stringify(src.policy,null,2))}</div>`;
+  return `<h4 style="margin:16px 0 8px">${title}</h4>${body}`;
+}
+async function loadMyPolicy(){
+  try{
+    const data=await api('GET','/api/me/policy');
+    if(data.builtin){$('myPolicy').innerHTML='<p class="muted">Built-in users are unrestricted: no policy applies to them.</p>';return;}
+    const sources=data.sources||[];
+    const none=!sources.some(s=>s.policy);
+    $('myPolicy').innerHTML=(none?'<div class="danger-callout" style="margin-top:16px">No policy grants you anything yet, so every request is denied. Ask an administrator for access.</div>':'')+sources.map(renderPolicySource).join('');
+  }catch(e){$('myPolicy').innerHTML=`<p class="muted">${esc(e.message)}</p>`;}
+}
+
+async function loadMyActivity(){
+  try{
+    const data=await api('GET','/api/me/activity');
+    const events=data.events||[],empty=!events.length;
+    $('activitySummary').textContent=data.enabled===false?'The audit trail is disabled on this server, so no activity is recorded.':data.truncated?'Your latest console actions and S3 requests, newest first. Older ones are not shown.':'Your latest console actions and S3 requests, newest first.';
+    $('activityRows').innerHTML=events.map(e=>`<tr><td>${esc(new Date(e.at_ms).toLocaleString())}</td><td>${esc(e.action)}</td><td class="audit-target">${esc(e.target)}</td><td><span class="badge ${e.allowed?'green':'red'}">${e.allowed?'Allowed':'Denied'}</span></td></tr>`).join('');
+    $('activityRows').closest('table').classList.toggle('hidden',empty);$('activityEmpty').classList.toggle('hidden',!empty);
+    if(empty)$('activityEmpty').innerHTML=`<div class="empty-icon">${icons.activity}</div><h3>No recent activity</h3><p>${data.enabled===false?'Enable <code>audit.enabled</code> to record it.':'Actions you take appear here.'}</p>`;
+  }catch(e){toast('Could not load activity',e.message,false);}
+}
@@ -0,0 +1,49 @@
+// ── audit trail ────────────────────────────────────────────────────────────
+// Admin-only tab over the persisted audit events. Filters map one-to-one onto
+// /api/audit query parameters; the server enforces admin and caps the page, so
+// this only renders what it is given and offers the same filters as an export.
+
+function auditParams(){
+  const params=new URLSearchParams();
+  const text={actor:'auditActor',action:'auditAction',bucket:'auditBucket',prefix:'auditPrefix'};
+  for(const [name,id] of Object.entries(text)){const v=$(id).value.trim();if(v)params.set(name,v);}
+  if($('auditOutcome').value)params.set('outcome',$('auditOutcome').value);
+  // datetime-local is the browser's local time; the server wants epoch ms.
+  for(const [name,id] of [['from','auditFrom'],['to','auditTo']]){const v=$(id).value;if(v)params.set(name,String(new Date(v).getTime()));}
+  return params;
+}
+
+async function loadAudit(){
+  try{
+    const params=auditParams();params.set('limit','500');
+    const data=await api('GET','/api/audit?'+params);
+    const enabled=data.enabled!==false;
+    $('auditDisabled').classList.toggle('hidden',enabled);
+    $('auditPanel').classList.toggle('hidden',!enabled);
+    $('auditCsvBtn').disabled=$('auditJsonBtn').disabled=!enabled;
+    if(!enabled)return;
+    const events=data.events||[];
+    $('auditCount').textContent=events.length.toLocaleString()+(data.truncated?'+':'');
+    $('auditSummary').textContent=data.truncated?'Newest first. More events match — narrow the filters or export to see them all.':'Newest first.';
+    $('auditRows').innerHTML=events.map(e=>`<tr><td>${esc(new Date(e.at_ms).toLocaleString())}</td><td><strong>${esc(e.actor)}</strong></td><td>${esc(e.action)}</td><td class="audit-target">${esc(e.target)}</td><td><span class="badge ${e.allowed?'green':'red'}">${e.allowed?'Allowed':'Denied'}</span></td><td class="hide-tablet muted">${esc(e.request_id)}</td></tr>`).join('');
+    const empty=!events.length;$('auditRows').closest('table').classList.toggle('hidden',empty);$('auditEmpty').classList.toggle('hidden',!empty);
+    if(empty)$('auditEmpty').innerHTML=`<div class="empty-icon">${icons.shield}</div><h3>No matching events</h3><p>Widen the time range or clear a filter.</p>`;
+  }catch(e){toast('Could not load audit trail',e.message,false);}
+}
+
+async function exportAudit(format,btn){
+  setBusy(btn,true,'Exporting…');
+  try{
+    const params=auditParams();params.set('format',format);
+    const resp=await fetch('/api/audit/export?'+params,{cache:'no-store'});
+    if(!resp.ok){ let msg=resp.statusText; try{ msg=(await resp.json()).error||msg; }catch{} throw new Error(msg); }
+    const blob=await resp.blob();
+    const stamp=new Date().toISOString().replace(/[:.]/g,'-').slice(0,19);
+    const name=`rusts3-audit-${stamp}.${format}`;
+    const url=URL.createObjectURL(blob);
+    const a=document.createElement('a'); a.href=url; a.download=name; document.body.append(a); a.click(); a.remove();
+    URL.revokeObjectURL(url);
+    toast('Export ready',`Downloaded ${name} · ${fmtBytes(blob.size)}`,true);
+  }catch(e){ toast('Export failed',e.message,false); }
+  finally{ setBusy(btn,false); }
+}
@@ -0,0 +1,38 @@
+// ── configuration reload ───────────────────────────────────────────────────
+// Admin-only tab. Reloading re-reads the file the server started from, exactly
+// as SIGHUP does; the server answers with the changed field paths (never their
+// values) and which of them only take effect after a restart.
+
+async function loadConfigStatus(){
+  try{
+    const data=await api('GET','/api/config');
+    const reloadable=!!data.source;
+    $('configSource').textContent=data.source||'built-in defaults';
+    $('configReloadBtn').disabled=!reloadable;
+    $('configNoSource').classList.toggle('hidden',reloadable);
+  }catch(e){toast('Could not load configuration status',e.message,false);}
+}
+
+async function reloadConfig(btn){
+  setBusy(btn,true,'Reloading…');
+  try{
+    const report=await api('POST','/api/config/reload');
+    renderConfigReport(report);
+    const pending=report.restart_required.length;
+    const applied=report.changed.length-pending;
+    toast('Configuration reloaded',report.changed.length?`${applied} applied · ${pending} need a restart`:'No changes',true);
+  }catch(e){
+    $('configResult').innerHTML=`<div class="panel-title"><div><h3>Reload rejected</h3><p>The running configuration is unchanged.</p></div><span class="spacer"></span><span class="badge red">Error</span></div><pre class="config-error">${esc(e.message)}</pre>`;
+    $('configResult').classList.remove('hidden');
+    toast('Reload failed',e.message,false);
+  }finally{setBusy(btn,false);}
+}
+
+function renderConfigReport(report){
+  const pending=new Set(report.restart_required);
+  const rows=report.changed.map(path=>`<tr><td><code>${esc(path)}</code></td><td>${pending.has(path)?'<span class="badge amber">Restart required</span>':'<span class="badge green">Applied</span>'}</td></tr>`).join('');
+  const warnings=report.warnings.map(w=>`<p class="config-warning">${esc(w)}</p>`).join('');
+  $('configResult').innerHTML=`<div class="panel-title"><div><h3>Last reload</h3><p>${esc(new Date().toLocaleString())} · ${esc(report.source)}</p></div><span class="spacer"></span><span class="badge">${report.changed.length} changed</span></div>${warnings}`+
+    (report.changed.length?`<div class="table-wrap"><table><thead><tr><th>Field</th><th style="width:170px">Status</th></tr></thead><tbody>${rows}</tbody></table></div>`:'<p class="stats-note">The file matches the running configuration.</p>');
+  $('configResult').classList.remove('hidden');
+}
@@ -19,6 +19,7 @@ const icons = {
   refresh:'<svg viewBox="0 0 24 24" class="icon"><path d="M20 6v5h-5M4 18v-5h5"/><path d="M18.5 9A7 7 0 0 0 6 6.5L4 11m16 2-2 4.5A7 7 0 0 1 5.5 15"/></svg>',
   file:'<svg viewBox="0 0 24 24" class="icon"><path d="M14 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V8Z"/><path d="M14 2v6h6"/></svg>',
   share:'<svg viewBox="0 0 24 24" class="icon"><circle cx="18" cy="5" r="3"/><circle cx="6" cy="12" r="3"/><circle cx="18" cy="19" r="3"/><path d="m8.6 10.5 6.8-4M8.6 13.5l6.8 4"/></svg>',
+  edit:'<svg viewBox="0 0 24 24" class="icon"><path d="M12 20h9"/><path d="M16.5 3.5a2.1 2.1 0 0 1 3 3L7 19l-4 1 1-4Z"/></svg>',
   trash:'<svg viewBox="0 0 24 24" class="icon"><path d="M3 6h18M8 6V4h8v2m3 0-1 15H6L5 6M10 11v6M14 11v6"/></svg>',
   info:'<svg viewBox="0 0 24 24" class="icon"><circle cx="12" cy="12" r="9"/><path d="M12 11v5M12 8h.01"/></svg>',
   eye:'<svg viewBox="0 0 24 24" class="icon"><path d="M2 12s3.5-7 10-7 10 7 10 7-3.5 7-10 7S2 12 2 12Z"/><circle cx="12" cy="12" r="3"/></svg>',
@@ -94,23 +95,27 @@ function showConfirm(title,subtitle,message,action,opts){opts=opts||{};$('confir
 // signed-out branch and the shell never has to be revealed. If the session has
 // gone in the meantime, reloading lands on the login page.
 async function boot(){try{me=await api('GET','/api/me');onLoggedIn();}catch{location.reload();}}
+// True when the session holds any of the space-separated admin:* actions. The
+// server checks every request; this only decides what to show.
+function can(actions){return (actions||'').split(' ').some(a=>(me?.admin_actions||[]).includes(a));}
+const TAB_ACTIONS={users:'admin:ManageUsers admin:ManagePolicies',groups:'admin:ManageUsers admin:ManagePolicies',sessions:'admin:ManageSessions',backup:'admin:ManageSnapshots admin:ExportIam admin:ImportIam',config:'admin:ViewConfig',perf:'admin:ViewScans',stats:'admin:ViewStats',audit:'admin:ViewAudit'};
 async function logout(){try{await api('POST','/api/logout');}finally{location.reload();}}
 function onLoggedIn(){
   document.body.classList.toggle('sidebar-collapsed',localStorage.getItem('sidebarCollapsed')==='1');
-  $('whoami').textContent=me.username;$('userRole').textContent=me.is_admin?'Administrator':'IAM user';$('avatar').textContent=(me.username[0]||'U').toUpperCase();document.querySelectorAll('[data-admin-only]').forEach(el=>el.classList.toggle('hidden',!me.is_admin));
+  $('whoami').textContent=me.username;$('userRole').textContent=me.is_admin?'Administrator':me.admin_actions?.length?'Delegated administrator':'IAM user';$('avatar').textContent=(me.username[0]||'U').toUpperCase();document.querySelectorAll('[data-admin-only]').forEach(el=>el.classList.toggle('hidden',!can(el.dataset.adminOnly)));
   showTab('objects');loadBuckets();startBucketBadgePolling();pingServer();pingTimer=setInterval(pingServer,5000);
-  // Active-task panel: admins only (the backend enforces this too). Live over a
-  // WebSocket; falls back to polling if the socket can't be established.
-  if(me.is_admin){refreshTasks();connectTasksWs();}
+  // Active-task panel: admin:ViewTasks only (the backend enforces this too). Live
+  // over a WebSocket; falls back to polling if the socket can't be established.
+  if(can('admin:ViewTasks')){refreshTasks();connectTasksWs();}
 }
 function toggleSidebar(){const c=document.body.classList.toggle('sidebar-collapsed');localStorage.setItem('sidebarCollapsed',c?'1':'0');}
 function toggleProfile(){$('profilePopover').classList.toggle('hidden');}
-const pageMeta={objects:['Object Browser','Manage buckets and objects'],users:['IAM Users','Manage users and policies'],groups:['IAM Groups','Reuse policies and assign administrative access'],keys:['My Access Keys','Manage your application credentials'],backup:['Backup & Restore','Export and import the global IAM database'],perf:['Storage Scan','Audit disk usage and object health, and repair what it finds'],stats:['Runtime Stats','System & process health over time']};
+const pageMeta={objects:['Object Browser','Manage buckets and objects'],users:['IAM Users','Manage users and policies'],groups:['IAM Groups','Reuse policies and assign administrative access'],shares:['Share Links','See and revoke the download links you have made'],account:['My Account','Your password, access keys, permissions and activity'],sessions:['Sessions','See who is signed in and sign sessions out'],backup:['Backup & Restore','Export and import the global IAM database'],config:['Configuration','Reload the configuration file without a restart'],perf:['Storage Scan','Audit disk usage and object health, and repair what it finds'],stats:['Runtime Stats','System & process health over time'],audit:['Audit Trail','Search and export the record of actions and policy decisions']};
 function showTab(tab){
-  if((tab==='users'||tab==='groups'||tab==='backup'||tab==='perf'||tab==='stats')&&!me?.is_admin)return;
+  if(TAB_ACTIONS[tab]&&!can(TAB_ACTIONS[tab]))return;
   document.querySelectorAll('.nav-item').forEach(b=>b.classList.toggle('active',b.dataset.tab===tab));
-  ['objects','users','groups','keys','backup','perf','stats'].forEach(t=>$('tab_'+t).classList.toggle('hidden',t!==tab));
+  ['objects','users','groups','sessions','shares','account','backup','config','perf','stats','audit'].forEach(t=>$('tab_'+t).classList.toggle('hidden',t!==tab));
   $('pageTitle').textContent=pageMeta[tab][0];$('pageSubtitle').textContent=pageMeta[tab][1];closeDetails();
-  if(tab==='users')loadUsers();if(tab==='groups')loadGroups();if(tab==='keys')loadMyKeys();if(tab==='backup')resetBackupPanel();if(tab==='perf')initPerf();if(tab==='stats')initStats();
+  if(tab==='users')loadUsers();if(tab==='groups')loadGroups();if(tab==='sessions')loadSessions();if(tab==='shares')loadShares();if(tab==='account')loadAccount();if(tab==='backup')resetBackupPanel();if(tab==='config')loadConfigStatus();if(tab==='perf')initPerf();if(tab==='stats')initStats();if(tab==='audit')loadAudit();
 }
 async function pingServer(){try{const resp=await fetch('/api/ping',{cache:'no-store'});const data=await resp.json().catch(()=>({}));if(!resp.ok){if(resp.status===401&&me)location.reload();throw new Error('ping failed');}$('serverState').classList.remove('offline');$('serverStateText').textContent='Server connected';$('serverVersion').textContent='RustS3 v'+data.version;}catch{$('serverState').classList.add('offline');$('serverStateText').textContent='Connection interrupted';}}
@@ -1,6 +1,6 @@
 // ── backup ─────────────────────────────────────────────────────────────────
-// Export / import of the global IAM database. Admin-only (the tab and its
-// endpoints are gated). Export downloads a binary dump; import uploads one and
+// Whole-server snapshots, and export / import of the global IAM database.
+// Admin-only (the tab and its endpoints are gated). Export downloads a binary dump; import uploads one and
 // renders a per-family summary so the result is easy to read at a glance.
 
 function fmtBytes(n){
@@ -11,12 +11,51 @@ function fmtBytes(n){
 }
 
 function resetBackupPanel(){
+  if(can('admin:ManageSnapshots'))loadSnapshots();
   stagedImport=null;
   const f=$('importFile'); if(f){ f.value=''; importFileChosen(); }
   const r=$('importResult'); if(r){ r.classList.add('hidden'); r.innerHTML=''; }
   document.querySelectorAll('input[name="importMode"]').forEach((el,i)=>el.checked=(i===0));
 }
 
+// ── snapshots ──────────────────────────────────────────────────────────────
+// Whole-server snapshots live on the server's disk, not in the browser: this
+// panel only takes, lists and deletes them. Restore is offline (CLI) because
+// it needs the server stopped.
+async function loadSnapshots(){
+  const host=$('snapshotList');if(!host)return;
+  try{
+    const data=await api('GET','/api/snapshots');
+    const keep=data.retain?`keeping the newest ${data.retain}`:'kept until deleted';
+    $('snapshotDir').innerHTML=`One consistent cut of every bucket, its objects and the IAM database, taken while the server runs. Stored in <code>${esc(data.dir)}</code>, ${keep}. Restore one with <code>rusts3 restore</code>.`;
+    const snaps=(data.snapshots||[]).slice().reverse();
+    if(!snaps.length){ host.innerHTML='<div class="muted" style="padding:18px 20px">No snapshots yet.</div>'; return; }
+    host.innerHTML=`<div class="table-wrap"><table><thead><tr><th>Snapshot</th><th>Taken</th><th>Buckets</th><th>Objects</th><th>Size</th><th style="width:60px"></th></tr></thead><tbody>${snaps.map(s=>{
+      const objects=s.buckets.reduce((n,b)=>n+b.objects,0), bytes=s.buckets.reduce((n,b)=>n+b.bytes,0);
+      return `<tr><td><code>${esc(s.id)}</code></td><td>${esc(new Date(s.created_at_ms).toLocaleString())}</td><td>${s.buckets.length}</td><td>${objects.toLocaleString()}</td><td>${fmtBytes(bytes)}</td><td class="actions"><button class="row-action danger" title="Delete snapshot" onclick="confirmDeleteSnapshot('${esc(s.id)}')">${icons.trash}</button></td></tr>`;
+    }).join('')}</tbody></table></div>`;
+  }catch(e){ host.innerHTML=`<div class="muted" style="padding:18px 20px">${esc(e.message)}</div>`; }
+}
+
+async function takeSnapshot(){
+  const btn=$('snapshotBtn'); setBusy(btn,true,'Taking snapshot…');
+  try{
+    const s=await api('POST','/api/snapshots');
+    const objects=s.buckets.reduce((n,b)=>n+b.objects,0);
+    toast('Snapshot complete',`${s.id} · ${objects.toLocaleString()} objects`,true);
+    await loadSnapshots();
+  }catch(e){ toast('Snapshot failed',e.message,false); }
+  finally{ setBusy(btn,false); }
+}
+
+function confirmDeleteSnapshot(id){
+  showConfirm('Delete snapshot?',id,'The snapshot directory is removed from the server. Objects in the live store are not affected.',async()=>{
+    await api('DELETE','/api/snapshots/'+encodeURIComponent(id));
+    toast('Snapshot deleted',id);
+    await loadSnapshots();
+  });
+}
+
 // ── file picker ────────────────────────────────────────────────────────────
 function importFileChosen(){
   const file=$('importFile').files[0];
@@ -1,8 +1,12 @@
 // ── my access keys ────────────────────────────────────────────────────────
-// The signed-in user's own application credentials, plus the one-time
+// The signed-in user's own application credentials on the account page —
+// create up to the server's limit, rotate, deactivate — plus the one-time
 // secret-reveal dialog (also used when an admin issues a key for a user).
-async function loadMyKeys(){try{const data=await api('GET','/api/users/'+encodeURIComponent(me.username)+'/keys');const keys=data.keys||[];$('keyCount').textContent=`${keys.length} key${keys.length===1?'':'s'}`;$('keyRows').innerHTML=keys.map(k=>`<tr><td><code class="key">${esc(k.access_key)}</code> ${k.builtin?'<span class="badge amber">Built-in</span>':''}</td><td class="muted">${k.builtin?'Config managed':fmtTime(k.created_at_ms)}</td><td class="actions">${k.builtin?'':`<button class="row-action danger" title="Delete key" onclick="confirmDeleteKey(decodeURIComponent('${enc(k.access_key)}'))">${icons.trash}</button>`}</td></tr>`).join('');const empty=!keys.length;$('keyRows').closest('table').classList.toggle('hidden',empty);$('keyEmpty').classList.toggle('hidden',!empty);if(empty)$('keyEmpty').innerHTML=`<div class="empty-icon">${icons.key}</div><h3>No access keys</h3><p>Create a key to connect an S3-compatible application.</p><button class="btn primary" onclick="createMyKey()">Create access key</button>`;}catch(e){toast('Could not load access keys',e.message,false);}}
+let myKeys=[];
+async function loadMyKeys(){try{const data=await api('GET','/api/users/'+encodeURIComponent(me.username)+'/keys');const keys=myKeys=data.keys||[],limit=data.limit;$('keyCount').textContent=limit==null?`${keys.length} key${keys.length===1?'':'s'}`:`${keys.length} of ${limit}`;$('keyLimitNote').textContent=limit===0?'Access keys are issued by an administrator on this server.':'Secrets are only shown once, when a key is created or rotated.'+(limit?` You can hold up to ${limit}.`:'');$('createKeyBtn').disabled=limit!=null&&keys.length>=limit;$('keyRows').innerHTML=keys.map((k,i)=>`<tr><td><code class="key">${esc(k.access_key)}</code></td><td>${k.builtin?'<span class="badge amber">Built-in</span>':k.active?'<span class="badge green">Active</span>':'<span class="badge">Inactive</span>'}</td><td class="muted">${k.builtin?'Config managed':fmtTime(k.created_at_ms)}</td><td class="actions">${k.builtin?'':`<button class="row-action" title="Rotate key" onclick="confirmRotateKey(${i})">${icons.refresh}</button><button class="row-action" title="${k.active?'Deactivate key':'Activate key'}" onclick="toggleMyKey(${i})">${k.active?icons.x:icons.check}</button><button class="row-action danger" title="Delete key" onclick="confirmDeleteKey(decodeURIComponent('${enc(k.access_key)}'))">${icons.trash}</button>`}</td></tr>`).join('');const empty=!keys.length;$('keyRows').closest('table').classList.toggle('hidden',empty);$('keyEmpty').classList.toggle('hidden',!empty);if(empty)$('keyEmpty').innerHTML=`<div class="empty-icon">${icons.key}</div><h3>No access keys</h3><p>${limit===0?'Ask an administrator for a key to connect an S3-compatible application.':'Create a key to connect an S3-compatible application.'}</p>${limit===0?'':'<button class="btn primary" onclick="createMyKey()">Create access key</button>'}`;}catch(e){toast('Could not load access keys',e.message,false);}}
 async function createMyKey(){try{const key=await api('POST','/api/users/'+encodeURIComponent(me.username)+'/keys');showSecret(key);loadMyKeys();}catch(e){toast('Could not create access key',e.message,false);}}
+function confirmRotateKey(i){const ak=myKeys[i].access_key;showConfirm('Rotate access key?',ak,'A new key replaces this one. The old key stops working immediately, so update your applications with the new secret.',async()=>{const key=await api('POST','/api/keys/'+encodeURIComponent(ak)+'/rotate');showSecret(key);await loadMyKeys();},{confirmLabel:'Rotate',busyLabel:'Rotating…'});}
+async function toggleMyKey(i){const k=myKeys[i];try{await api('PUT','/api/keys/'+encodeURIComponent(k.access_key),{active:!k.active});toast(k.active?'Access key deactivated':'Access key activated',k.access_key);await loadMyKeys();}catch(e){toast('Could not update access key',e.message,false);}}
 function showSecret(key){$('sd_text').textContent=`access_key: ${key.access_key}\nsecret_key: ${key.secret_key}`;$('secretDlg').showModal();}
 async function copySecret(){const ok=await copyText($('sd_text').textContent);toast(ok?'Credentials copied':'Copy failed',ok?'':'Select the text and press Ctrl+C',ok);}
 function confirmDeleteKey(ak){showConfirm('Delete access key?',ak,'Applications using this key will immediately lose access.',async()=>{await api('DELETE','/api/keys/'+encodeURIComponent(ak));toast('Access key deleted');await loadMyKeys();});}
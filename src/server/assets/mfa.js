@@ -0,0 +1,55 @@
+// ── two-factor authentication ──────────────────────────────────────────────
+// The signed-in user's own TOTP setup, opened from the profile menu: enroll
+// with a QR code, see and replace recovery codes, or turn it off. Changes to
+// an active second factor need a current code. Built-in users' secrets live
+// in the config file, so for them this is read-only.
+let mfaState=null,mfaMode='status';
+
+async function openMfaDialog(){
+  $('profilePopover').classList.add('hidden');
+  setInlineError('mfaError');$('mfaDlg').showModal();
+  try{mfaState=await api('GET','/api/me/mfa');renderMfa('status');}catch(e){setInlineError('mfaError',e.message);}
+}
+
+function renderMfa(mode,data={}){
+  mfaMode=mode;setInlineError('mfaError');
+  const s=mfaState||{},codeField=`<div class="field"><label for="mfa_code">Authentication code</label><input class="input" id="mfa_code" placeholder="123456" autocomplete="one-time-code" inputmode="numeric"></div>`;
+  let body='',actions='<button class="btn" onclick="mfaDlg.close()">Close</button>';
+  if(mode==='codes'){
+    body=`<div class="danger-callout">Each recovery code signs you in once if you lose your authenticator. They will not be shown again.</div><div class="secret-box">${esc(data.recovery_codes.join('\n'))}</div>`;
+    actions='<button class="btn primary" onclick="mfaDlg.close()">I have saved them</button>';
+  }else if(mode==='enroll'){
+    body=`<p class="muted" style="margin-top:0">Scan the QR code with an authenticator app, or enter the key by hand, then type the code it shows.</p><div class="mfa-qr">${data.qr_svg||''}</div><div class="secret-box">${esc(data.secret)}</div>${codeField}`;
+    actions+='<button class="btn primary" id="mfaConfirm" onclick="confirmMfa()">Turn on</button>';
+  }else if(s.config_managed){
+    body=`<p>Two-factor authentication is <strong>${s.enabled?'on':'off'}</strong> for this account.</p><p class="muted">Built-in users are configured in the server’s config file: set <code>totp_secret</code> on the user (generate one with <code>rusts3 gentotp</code>).</p>`;
+  }else if(s.enabled){
+    body=`<p>Two-factor authentication is <strong>on</strong> since ${esc(fmtTime(s.enabled_at_ms))}. ${s.recovery_codes_left} recovery code${s.recovery_codes_left===1?'':'s'} left.</p>${codeField}${s.required?'<div class="muted" style="font-size:11.5px">This server requires two-factor authentication, so it cannot be turned off.</div>':''}`;
+    actions+=`<button class="btn" id="mfaRegenerate" onclick="regenerateRecoveryCodes()">New recovery codes</button>${s.required?'':'<button class="btn danger" id="mfaDisable" onclick="disableMfa()">Turn off</button>'}`;
+  }else{
+    body='<p>Two-factor authentication is <strong>off</strong>. Turn it on to ask for a code from an authenticator app each time you sign in.</p>';
+    actions+='<button class="btn primary" id="mfaEnroll" onclick="beginMfa()">Set up</button>';
+  }
+  $('mfaBody').innerHTML=body;$('mfaActions').innerHTML=actions;
+  $('mfa_code')?.focus();
+}
+
+async function mfaStep(button,label,action){
+  setBusy($(button),true,label);
+  try{await action();}catch(e){setBusy($(button),false);setInlineError('mfaError',e.message);}
+}
+function mfaCode(){return $('mfa_code').value.trim();}
+
+function beginMfa(){mfaStep('mfaEnroll','Starting…',async()=>renderMfa('enroll',await api('POST','/api/me/mfa/enroll')));}
+function confirmMfa(){mfaStep('mfaConfirm','Verifying…',async()=>{
+  const data=await api('POST','/api/me/mfa/confirm',{code:mfaCode()});
+  mfaState=await api('GET','/api/me/mfa');renderMfa('codes',data);toast('Two-factor authentication on');
+});}
+function regenerateRecoveryCodes(){mfaStep('mfaRegenerate','Generating…',async()=>{
+  const data=await api('POST','/api/me/mfa/recovery-codes',{code:mfaCode()});
+  mfaState=await api('GET','/api/me/mfa');renderMfa('codes',data);toast('Recovery codes replaced');
+});}
+function disableMfa(){mfaStep('mfaDisable','Turning off…',async()=>{
+  await api('DELETE','/api/me/mfa',{code:mfaCode()});
+  mfaState=await api('GET','/api/me/mfa');renderMfa('status');toast('Two-factor authentication off');
+});}
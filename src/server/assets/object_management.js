@@ -1,7 +1,8 @@
 // ── objects ───────────────────────────────────────────────────────────────
 // The object browser: bucket rail, breadcrumb navigation, per-folder filter
 // memory, object listing, the drag-and-drop upload queue with its transfer
-// centre, the object details panel, and share-link (presign) creation.
+// centre, the object details panel (its preview and metadata live in
+// object_preview.js), and share-link (presign) creation.
 let buckets=[],bucket=null,prefix='',nextAfter=null,objectItems=[],shareKey=null,detailObject=null,filterMemory={};
 let selectedKeys=new Set(),visibleItems=[],bulkCancel=false,bulkRunning=false;
 
@@ -51,13 +52,31 @@ function renderObjects(){
   const empty=!visible.length;$('objectTableWrap').querySelector('table').classList.toggle('hidden',empty);$('objectEmpty').classList.toggle('hidden',!empty);if(empty)$('objectEmpty').innerHTML=`<div class="empty-icon">${icons[bucket?'folder':'database']}</div><h3>${query?'No matching objects':bucket?'This location is empty':'No buckets yet'}</h3><p>${query?'Try a different filter.':bucket?'Upload files by using the button above or dragging them into this window.':'Create your first bucket to begin storing objects.'}</p>${!bucket&&me?.is_admin?'<button class="btn primary" onclick="openBucketDialog()">Create bucket</button>':''}`;
 }
 function objectUrl(key){return `/api/object?bucket=${encodeURIComponent(bucket)}&key=${encodeURIComponent(key)}`;}
-function openBucketDialog(){$('newBucketName').value='';setInlineError('bucketError');$('bucketDlg').showModal();setTimeout(()=>$('newBucketName').focus(),50);}
-async function createBucket(event){event.preventDefault();const name=$('newBucketName').value.trim();if(!name){setInlineError('bucketError','Enter a bucket name.');return;}try{await api('POST','/api/buckets',{name});$('bucketDlg').close();toast('Bucket created',name);await loadBuckets();const index=buckets.findIndex(b=>b.name===name);if(index>=0)selectBucket(index);}catch(e){setInlineError('bucketError',e.message);}}
+function openBucketDialog(){$('newBucketName').value='';setInlineError('bucketError');fillRootSelect('newBucketRoot','newBucketRootField',null);$('bucketDlg').showModal();setTimeout(()=>$('newBucketName').focus(),50);}
+async function createBucket(event){event.preventDefault();const name=$('newBucketName').value.trim();if(!name){setInlineError('bucketError','Enter a bucket name.');return;}try{await api('POST','/api/buckets',{name,root:$('newBucketRoot').value||undefined});$('bucketDlg').close();toast('Bucket created',name);await loadBuckets();const index=buckets.findIndex(b=>b.name===name);if(index>=0)selectBucket(index);}catch(e){setInlineError('bucketError',e.message);}}
 let bucketSettingsTarget=null;
 const CORS_SAMPLE=JSON.stringify([{allowed_origins:['https://app.example.com'],allowed_methods:['GET','PUT'],allowed_headers:['content-type','x-amz-*'],expose_headers:['ETag','x-amz-request-id'],max_age_seconds:3600}],null,2);
-async function openBucketSettings(){if(!bucket)return;bucketSettingsTarget=bucket;$('bucketSettingsName').textContent=bucket;setInlineError('bucketSettingsError');try{const data=await api('GET','/api/buckets/'+encodeURIComponent(bucket)+'/cors');$('bucketConsoleOrigin').value=data.console_origin||'Not configured';const rules=data.rules||[];$('bucketCorsJson').value=rules.length?JSON.stringify(rules,null,2):'';$('bucketCorsJson').placeholder=CORS_SAMPLE;$('bucketSettingsDlg').showModal();}catch(e){toast('Could not load bucket settings',e.message,false);}}
+async function openBucketSettings(){if(!bucket)return;bucketSettingsTarget=bucket;$('bucketSettingsName').textContent=bucket;setInlineError('bucketSettingsError');try{const path='/api/buckets/'+encodeURIComponent(bucket);const [data,stats]=await Promise.all([api('GET',path+'/cors'),api('GET',path+'/stats')]);$('bucketConsoleOrigin').value=data.console_origin||'Not configured';const rules=data.rules||[];$('bucketCorsJson').value=rules.length?JSON.stringify(rules,null,2):'';$('bucketCorsJson').placeholder=CORS_SAMPLE;renderQuota(stats);fillRootSelect('bucketRoot','bucketRootField',stats.root);$('bucketSettingsDlg').showModal();}catch(e){toast('Could not load bucket settings',e.message,false);}}
+// ── quota ──
+// Usage bars fill toward the hard limit (or the soft one when that is all
+// there is); amber past a soft limit, red at a hard one. Only administrators
+// may edit the limits, so everyone else sees the inputs read-only.
+const QUOTA_FIELDS=[['quotaHardBytes','hard_bytes',true],['quotaSoftBytes','soft_bytes',true],['quotaHardObjects','hard_objects',false],['quotaSoftObjects','soft_objects',false]];
+function fmtCount(n){return Number(n).toLocaleString();}
+function quotaBar(label,used,soft,hard,fmt){const limit=hard??soft;const pct=limit?Math.min(100,used/limit*100):0;const state=hard!=null&&used>=hard?'red':soft!=null&&used>soft?'amber':'';return `<div><div class="quota-label"><span>${label}${state==='red'?' <span class="badge red">At hard limit</span>':state==='amber'?' <span class="badge amber">Over soft limit</span>':''}</span><span class="muted">${esc(fmt(used))}${limit!=null?' of '+esc(fmt(limit)):' · unlimited'}</span></div><div class="progress quota-bar ${state}"><span style="width:${pct.toFixed(1)}%"></span></div></div>`;}
+function renderQuota(stats){const q=stats.quota||{};$('bucketQuotaUsage').innerHTML=quotaBar('Size',stats.bytes,q.soft_bytes,q.hard_bytes,fmtSize)+quotaBar('Objects',stats.objects,q.soft_objects,q.hard_objects,fmtCount);for(const [id,field,bytes] of QUOTA_FIELDS){const v=q[field];$(id).value=v==null?'':bytes?fmtQuotaSize(v):String(v);$(id).disabled=!can('admin:ManageBuckets');}$('bucketCompression').value=stats.compression||'';$('bucketCompression').disabled=!can('admin:ManageBuckets');$('bucketDedup').value=stats.dedup?'on':'';$('bucketDedup').disabled=!can('admin:ManageBuckets');$('bucketDedupSaved').textContent=stats.dedup_chunks?`${fmtCount(stats.dedup_chunks)} shared chunks, ${fmtSize(stats.dedup_saved_bytes||0)} saved.`:'';}
+// Limits round-trip through the inputs, so show them exactly: the largest
+// binary unit that divides the value, else plain bytes.
+function fmtQuotaSize(v){for(const [u,scale] of [['PiB',1024**5],['TiB',1024**4],['GiB',1024**3],['MiB',1024**2],['KiB',1024]])if(v>=scale&&v%scale===0)return `${v/scale} ${u}`;return `${v} B`;}
+function parseQuotaValue(text,bytes,label){const t=text.trim().replace(/,/g,'');if(!t)return null;const m=/^(\d+(?:\.\d+)?)\s*([KMGTP]i?B?|B)?$/i.exec(t);if(!m||(!bytes&&m[2]))throw new Error(`${label}: enter ${bytes?'a size such as 500 MiB':'a whole number'}.`);const unit=(m[2]||'B').toUpperCase()[0];const scale={B:1,K:1024,M:1024**2,G:1024**3,T:1024**4,P:1024**5}[unit];return Math.round(Number(m[1])*scale);}
+function readQuota(){const q={};for(const [id,field,bytes] of QUOTA_FIELDS)q[field]=parseQuotaValue($(id).value,bytes,document.querySelector(`label[for="${id}"]`).textContent);for(const kind of ['bytes','objects'])if(q['soft_'+kind]!=null&&q['hard_'+kind]!=null&&q['soft_'+kind]>q['hard_'+kind])throw new Error('A soft limit cannot be above its hard limit.');return q;}
 function validateCorsRules(rules){if(!Array.isArray(rules))throw new Error('CORS rules must be a JSON array.');if(rules.length>100)throw new Error('A bucket can have at most 100 CORS rules.');const methods=new Set(['GET','PUT','POST','DELETE','HEAD']);for(let i=0;i<rules.length;i++){const r=rules[i],at=`Rule ${i+1}`;if(!r||typeof r!=='object'||Array.isArray(r))throw new Error(`${at} must be an object.`);for(const field of ['allowed_origins','allowed_methods','allowed_headers','expose_headers']){if(!Array.isArray(r[field])||r[field].some(v=>typeof v!=='string'||!v.trim()))throw new Error(`${at}: ${field} must be an array of non-empty strings.`);}if(!r.allowed_origins.length)throw new Error(`${at} needs at least one allowed origin.`);if(!r.allowed_methods.length)throw new Error(`${at} needs at least one allowed method.`);if(r.allowed_methods.some(v=>!methods.has(v.toUpperCase())))throw new Error(`${at} has an unsupported method.`);if([...r.allowed_origins,...r.allowed_headers].some(v=>(v.match(/\*/g)||[]).length>1))throw new Error(`${at}: origins and headers may contain at most one wildcard.`);if(r.max_age_seconds!==undefined&&(!Number.isInteger(r.max_age_seconds)||r.max_age_seconds<0||r.max_age_seconds>4294967295))throw new Error(`${at}: max_age_seconds must be an integer from 0 to 4294967295.`);}}
-async function saveBucketSettings(){let rules;try{rules=JSON.parse($('bucketCorsJson').value||'[]');validateCorsRules(rules);}catch(e){setInlineError('bucketSettingsError',e.message);return;}try{await api('PUT','/api/buckets/'+encodeURIComponent(bucketSettingsTarget)+'/cors',{rules});$('bucketSettingsDlg').close();toast('Bucket settings saved',bucketSettingsTarget);}catch(e){setInlineError('bucketSettingsError',e.message);}}
+async function saveBucketSettings(){let rules,quota;try{rules=JSON.parse($('bucketCorsJson').value||'[]');validateCorsRules(rules);if(can('admin:ManageBuckets'))quota=readQuota();}catch(e){setInlineError('bucketSettingsError',e.message);return;}try{const path='/api/buckets/'+encodeURIComponent(bucketSettingsTarget);await api('PUT',path+'/cors',{rules});if(quota){await api('PUT',path+'/quota',quota);await api('PUT',path+'/compression',{compression:$('bucketCompression').value||null});await api('PUT',path+'/dedup',{dedup:$('bucketDedup').value==='on'});}$('bucketSettingsDlg').close();toast('Bucket settings saved',bucketSettingsTarget);}catch(e){setInlineError('bucketSettingsError',e.message);}}
+// ── data roots ──
+// Only administrators see where buckets live, and only when there is more
+// than one place to put them. `current` null offers automatic placement.
+async function fillRootSelect(selectId,fieldId,current){const field=$(fieldId);field.classList.add('hidden');if(!can('admin:ManageBuckets'))return;let roots;try{roots=(await api('GET','/api/roots')).roots;}catch(e){return;}const select=$(selectId);select.innerHTML=(current==null?'<option value="">Most free space</option>':'')+roots.map(r=>`<option value="${esc(r.path)}">${esc(r.path)}${r.available_bytes!=null?' — '+esc(fmtSize(r.available_bytes))+' free of '+esc(fmtSize(r.total_bytes)):''}</option>`).join('');if(current!=null)select.value=current;field.classList.toggle('hidden',roots.length<2);}
+function moveBucket(){const target=bucketSettingsTarget,root=$('bucketRoot').value;if(!target||!root)return;showConfirm('Move bucket?',target,`Copies “${target}” to ${root} and switches over when the copy is complete. The bucket stays available except for a moment at the end.`,async()=>{await api('POST','/api/buckets/'+encodeURIComponent(target)+'/move',{root});$('bucketSettingsDlg').close();toast('Move started',target);refreshTasks();},{confirmLabel:'Move bucket',busyLabel:'Starting…',danger:false});}
 function openCorsSample(){$('corsSampleJson').value=CORS_SAMPLE;$('corsSampleDlg').showModal();}
 async function copyCorsSample(){const ok=await copyText(CORS_SAMPLE);toast(ok?'Sample copied':'Copy failed',ok?'Paste it into the CORS editor and change the origin.':'Select the sample and press Ctrl+C',ok);}
 let deletingBucket=null,deletingObjectCount=0;
@@ -78,11 +97,11 @@ async function emptyAndDeleteBucket(event){
   }catch(e){setInlineError('emptyBucketError',e.message);$('emptyBucketAction').disabled=false;$('emptyBucketCancel').disabled=false;$('emptyBucketClose').disabled=false;}
 }
 
-function openDetails(index){const o=objectItems[index];if(!o||o.type!=='object')return;detailObject=o;$('detailList').innerHTML=`<div class="detail-row"><dt>Object name</dt><dd>${esc(o.name)}</dd></div><div class="detail-row"><dt>Full path</dt><dd>${esc(o.key)}</dd></div><div class="detail-row"><dt>Bucket</dt><dd>${esc(bucket)}</dd></div><div class="detail-row"><dt>Size</dt><dd>${fmtSize(o.size)}</dd></div><div class="detail-row"><dt>Modified</dt><dd>${fmtTime(o.last_modified_ms)}</dd></div><div class="detail-row"><dt>ETag</dt><dd><code>${esc(o.etag||'—')}</code></dd></div>`;$('detailDownload').href=objectUrl(o.key);$('detailDownload').setAttribute('download','');$('detailsPanel').classList.add('open');}
-function closeDetails(){$('detailsPanel')?.classList.remove('open');detailObject=null;}
+function openDetails(index){const o=objectItems[index];if(!o||o.type!=='object')return;detailObject=o;$('detailList').innerHTML=`<div class="detail-row"><dt>Object name</dt><dd>${esc(o.name)}</dd></div><div class="detail-row"><dt>Full path</dt><dd>${esc(o.key)}</dd></div><div class="detail-row"><dt>Bucket</dt><dd>${esc(bucket)}</dd></div><div class="detail-row"><dt>Size</dt><dd>${fmtSize(o.size)}</dd></div><div class="detail-row"><dt>Modified</dt><dd>${fmtTime(o.last_modified_ms)}</dd></div><div class="detail-row"><dt>ETag</dt><dd><code>${esc(o.etag||'—')}</code></dd></div>`;$('detailDownload').href=objectUrl(o.key);$('detailDownload').setAttribute('download','');$('detailsPanel').classList.add('open');loadObjectDetails(o);}
+function closeDetails(){$('detailsPanel')?.classList.remove('open');detailObject=null;const preview=$('detailPreview');if(preview){preview.className='details-preview';preview.innerHTML=icons.file;}}
 function openShareFromDetails(){if(detailObject)openShare(detailObject.key);}
-function openShare(key){shareKey=key;$('shareObjectName').textContent=key;$('shareResult').classList.add('hidden');$('sh_url').value='';$('shareAction').innerHTML=icons.link+' Generate link';$('shareAction').onclick=doPresign;setInlineError('shareError');$('shareDlg').showModal();}
-async function doPresign(){try{const data=await api('POST','/api/presign',{bucket,key:shareKey,expires_secs:+$('sh_exp').value});$('sh_url').value=data.url;$('shareResult').classList.remove('hidden');$('shareAction').innerHTML=icons.copy+' Copy link';$('shareAction').onclick=copyShare;}catch(e){setInlineError('shareError',e.message);}}
+function openShare(key){shareKey=key;$('shareObjectName').textContent=key;$('shareResult').classList.add('hidden');$('sh_url').value='';$('sh_password').value='';$('sh_max').value='';$('shareAction').innerHTML=icons.link+' Generate link';$('shareAction').onclick=doPresign;setInlineError('shareError');$('shareDlg').showModal();}
+async function doPresign(){try{const max=$('sh_max').value.trim();const data=await api('POST','/api/presign',{bucket,key:shareKey,expires_secs:+$('sh_exp').value,password:$('sh_password').value||null,max_downloads:max?Number(max):null});$('sh_url').value=data.url;$('shareResult').classList.remove('hidden');$('shareAction').innerHTML=icons.copy+' Copy link';$('shareAction').onclick=copyShare;}catch(e){setInlineError('shareError',e.message);}}
 async function copyShare(){const ok=await copyText($('sh_url').value);toast(ok?'Link copied':'Copy failed',ok?'':'Select the link and press Ctrl+C',ok);}
 function confirmDeleteObject(key){showConfirm('Delete object?',key,`This permanently deletes “${key}”. This action cannot be undone.`,async()=>{await api('DELETE',objectUrl(key));closeDetails();toast('Object deleted',key);await loadObjects();});}
 
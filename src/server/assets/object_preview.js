@@ -0,0 +1,96 @@
+// ── object preview & metadata ──────────────────────────────────────────────
+// The details panel's preview (images, text with JSON/CSV highlighting, PDF,
+// audio and video), its metadata view, and the metadata editor. Media stream
+// from /api/object with Range requests; text previews read only the first
+// PREVIEW_TEXT_BYTES. Saving rewrites the object through a metadata-replace
+// copy, so the bytes stay as they are.
+const PREVIEW_TEXT_BYTES=256*1024;
+const TEXT_EXTENSIONS=new Set(['txt','md','log','json','csv','tsv','yaml','yml','xml','toml','ini','conf','cfg','js','ts','rs','py','sh','go','java','c','h','css','sql']);
+let detailMeta=null;
+
+function previewKind(type,key){
+  const t=(type||'').split(';')[0].trim().toLowerCase(),ext=(key.split('/').pop().split('.').slice(1).pop()||'').toLowerCase();
+  if(t.startsWith('image/'))return 'image';
+  if(t.startsWith('video/'))return 'video';
+  if(t.startsWith('audio/'))return 'audio';
+  if(t==='application/pdf')return 'pdf';
+  if(t==='application/json'||t.endsWith('+json')||(t==='application/octet-stream'||!t)&&ext==='json')return 'json';
+  if(t==='text/csv'||t==='text/tab-separated-values'||(t==='application/octet-stream'||!t)&&(ext==='csv'||ext==='tsv'))return 'csv';
+  if(t.startsWith('text/')||/^application\/(xml|javascript|x-yaml|yaml|toml|x-sh)$/.test(t)||(t==='application/octet-stream'||!t)&&TEXT_EXTENSIONS.has(ext))return 'text';
+  return null;
+}
+
+async function loadObjectDetails(o){
+  detailMeta=null;$('detailMeta').innerHTML='<div class="muted" style="padding:11px 0;font-size:12.5px">Loading metadata…</div>';$('detailEditMeta').disabled=true;
+  const preview=$('detailPreview');preview.className='details-preview';preview.innerHTML=icons.file;
+  let meta;
+  try{meta=await api('GET',`/api/object/meta?bucket=${encodeURIComponent(bucket)}&key=${encodeURIComponent(o.key)}`);}
+  catch(e){if(detailObject===o)$('detailMeta').innerHTML=`<div class="inline-error show">${esc(e.message)}</div>`;return;}
+  if(detailObject!==o)return;
+  detailMeta=meta;renderDetailMeta(meta);$('detailEditMeta').disabled=false;renderPreview(o,meta);
+}
+
+function renderDetailMeta(m){
+  const row=(k,v)=>`<div class="detail-row"><dt>${esc(k)}</dt><dd>${v}</dd></div>`,none='<span class="muted">—</span>';
+  const system=[['Content-Type',m.content_type],['Content-Encoding',m.content_encoding],['Content-Language',m.content_language],['Cache-Control',m.cache_control],['Storage class',m.storage_class]];
+  const user=Object.entries(m.user_meta||{});
+  $('detailMeta').innerHTML=system.map(([k,v])=>row(k,v?`<code>${esc(v)}</code>`:none)).join('')+row('Layout',`${m.parts>1?fmtCount(m.parts)+' parts':'Single part'}${m.compressed?' · compressed':''}`)
+    +`<h4 class="details-subhead">User metadata</h4>`+(user.length?user.map(([k,v])=>row('x-amz-meta-'+k,esc(v))).join(''):'<div class="muted" style="padding:11px 0;font-size:12.5px">None.</div>');
+}
+
+async function renderPreview(o,meta){
+  const kind=previewKind(meta.content_type,o.key),preview=$('detailPreview'),url=objectUrl(o.key)+'&inline=true';
+  if(!kind||!meta.size)return;
+  preview.classList.add('media');
+  if(kind==='image'){preview.innerHTML=`<img alt="${esc(o.name)}" src="${url}">`;preview.querySelector('img').onerror=()=>{preview.className='details-preview';preview.innerHTML=icons.file;};return;}
+  if(kind==='video'){preview.innerHTML=`<video controls preload="metadata" src="${url}"></video>`;return;}
+  if(kind==='audio'){preview.innerHTML=`<audio controls preload="metadata" src="${url}"></audio>`;return;}
+  if(kind==='pdf'){preview.innerHTML=`<iframe title="${esc(o.name)}" src="${url}"></iframe>`;return;}
+  preview.innerHTML='<div class="muted">Loading preview…</div>';
+  try{
+    const resp=await fetch(objectUrl(o.key),{headers:{range:`bytes=0-${PREVIEW_TEXT_BYTES-1}`}});
+    if(!resp.ok)throw new Error((await resp.json().catch(()=>({}))).error||resp.statusText);
+    const text=new TextDecoder().decode(await resp.arrayBuffer()),truncated=meta.size>PREVIEW_TEXT_BYTES;
+    if(detailObject!==o)return;
+    preview.innerHTML=`<pre class="code-preview">${kind==='json'?highlightJson(text,truncated):kind==='csv'?highlightCsv(text,o.key.toLowerCase().endsWith('.tsv')||meta.content_type.startsWith('text/tab-separated')?'\t':','):esc(text)}</pre>${truncated?`<div class="muted preview-note">First ${fmtSize(PREVIEW_TEXT_BYTES)} of ${fmtSize(meta.size)} shown.</div>`:''}`;
+  }catch(e){if(detailObject===o){preview.className='details-preview';preview.innerHTML=`<span class="muted">Preview unavailable: ${esc(e.message)}</span>`;}}
+}
+
+// Pretty-prints complete JSON; a truncated or invalid document, or one with
+// numbers JavaScript would round, is highlighted as it is.
+function highlightJson(text,truncated){
+  if(!truncated&&!/\d{16}/.test(text)){try{text=JSON.stringify(JSON.parse(text),null,2);}catch(e){}}
+  return text.replace(/("(?:\\.|[^"\\])*")(\s*:)?|\b(true|false|null)\b|(-?\d+(?:\.\d+)?(?:[eE][+-]?\d+)?)|[^"\w-]+|[\w-]+|"/g,(m,str,colon,lit,num)=>
+    str?`<span class="${colon?'tok-key':'tok-str'}">${esc(str)}</span>${colon?esc(colon):''}`:lit?`<span class="tok-lit">${m}</span>`:num?`<span class="tok-num">${m}</span>`:esc(m));
+}
+
+// Colours each column so fields line up by eye; quoted fields keep their separators.
+function highlightCsv(text,sep){
+  return text.split('\n').map(line=>{let out='',col=0,field='',quoted=false;
+    const flush=()=>{out+=`<span class="tok-col${col%4}">${esc(field)}</span>`;field='';};
+    for(const c of line){if(c==='"')quoted=!quoted;if(c===sep&&!quoted){flush();out+=`<span class="tok-sep">${esc(sep==='\t'?'→\t':sep)}</span>`;col++;}else field+=c;}
+    flush();return out;}).join('\n');
+}
+
+function openMetaEditor(){
+  const m=detailMeta;if(!m)return;
+  $('me_object').textContent=m.key;$('me_type').value=m.content_type||'';$('me_cache').value=m.cache_control||'';setInlineError('metaError');
+  $('metaRows').innerHTML='';for(const [k,v] of Object.entries(m.user_meta||{}))addMetaRow(k,v);
+  $('metaDlg').showModal();
+}
+function addMetaRow(k='',v=''){
+  const row=document.createElement('div');row.className='meta-row';
+  row.innerHTML=`<span class="muted">x-amz-meta-</span><input class="input" placeholder="name" value="${esc(k)}" autocomplete="off"><input class="input" placeholder="value" value="${esc(v)}" autocomplete="off"><button type="button" class="row-action danger" title="Remove" onclick="this.parentElement.remove()">${icons.trash}</button>`;
+  $('metaRows').append(row);
+}
+async function saveMeta(){
+  const m=detailMeta,user_meta={};
+  for(const row of $('metaRows').children){const [k,v]=[...row.querySelectorAll('input')].map(i=>i.value.trim());if(!k&&!v)continue;if(!k){setInlineError('metaError','Every metadata value needs a name.');return;}const name=k.toLowerCase().replace(/^x-amz-meta-/,'');if(name in user_meta){setInlineError('metaError',`“${name}” is listed twice.`);return;}user_meta[name]=v;}
+  const content_type=$('me_type').value.trim();if(!content_type){setInlineError('metaError','Enter a content type.');return;}
+  try{
+    await api('PUT','/api/object/meta',{bucket,key:m.key,content_type,cache_control:$('me_cache').value.trim()||null,user_meta,last_modified_ms:m.last_modified_ms});
+    $('metaDlg').close();toast('Metadata saved',m.key);
+    if(detailObject?.key===m.key)await loadObjectDetails(detailObject);
+    await loadObjects();
+  }catch(e){setInlineError('metaError',e.message);}
+}
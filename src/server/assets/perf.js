@@ -22,6 +22,7 @@ const findingMeta={
   unreadable_blob:['Unreadable blob directories','No usable meta.json, so there is no object key to be had — only the path. Nothing can be looked up or resynced for these.'],
   corrupt_object:['Corrupt objects','Parts are missing, shorter than recorded, or do not add up to the declared size.'],
   index_drift:['Index drift','The row and meta.json disagree on size, etag, or last-modified. Listings are describing bytes a download would not return.'],
+  corrupt_inline:['Corrupt inline objects','Small objects stored in their index row whose bytes no longer match the recorded size or ETag. There is no other copy.'],
   empty_fanout:['Empty fanout directories','Empty directories under objects/ — wasted inodes, no data at risk.'],
 };
 const actionLabels={
@@ -35,7 +36,7 @@ const actionLabels={
 // a record of what was found *and* what was done about it. These are how each
 // outcome reads once that has happened.
 const stateLabels={repaired:'Repaired',stale:'No longer applicable',failed:'Repair failed'};
-const kindOrder=['corrupt_object','missing_blob','unreadable_blob','orphan_blob','superseded_blob','index_drift','empty_fanout'];
+const kindOrder=['corrupt_object','corrupt_inline','missing_blob','unreadable_blob','orphan_blob','superseded_blob','index_drift','empty_fanout'];
 // Not everything the scan reports is damage. Empty directories are pure
 // housekeeping — nothing is at risk and nothing is unreadable — so they are
 // styled as routine rather than alarming, and kept out of the problem count.
@@ -63,14 +64,14 @@ function initPerf(){loadScanHistory();connectScanWs();}
 
 // ── live progress ──
 function connectScanWs(){
-  if(!me?.is_admin||scanWs)return;
+  if(!can('admin:ViewScans')||scanWs)return;
   let ws;
   try{ws=new WebSocket((location.protocol==='https:'?'wss:':'ws:')+'//'+location.host+'/api/perf/scan/ws');}catch{return;}
   scanWs=ws;
   ws.onmessage=e=>{let env;try{env=JSON.parse(e.data);}catch{return;}handleScanEvent(env);};
   ws.onerror=()=>{try{ws.close();}catch{}};
   // Reconnect while the page is open — a scan can outlive a proxy's idle timeout.
-  ws.onclose=()=>{scanWs=null;if(me?.is_admin)setTimeout(connectScanWs,2000);};
+  ws.onclose=()=>{scanWs=null;if(can('admin:ViewScans'))setTimeout(connectScanWs,2000);};
 }
 function handleScanEvent(env){
   if(env.type==='finished'){
@@ -273,6 +274,8 @@ function reportHtml(report){
     <td>${(b.objects_indexed||0).toLocaleString()}</td>
     <td>${esc(fmtSize(b.logical_bytes||0))}</td>
     <td>${esc(fmtSize(b.objects_bytes||0))}</td>
+    <td>${b.compressed_logical_bytes?esc(fmtSize(b.compressed_logical_bytes)+' → '+fmtSize(b.compressed_stored_bytes||0)):'<span class="muted">—</span>'}</td>
+    <td>${b.inline_objects?esc(`${b.inline_objects.toLocaleString()} · ${fmtSize(b.inline_bytes||0)}`):'<span class="muted">—</span>'}</td>
     <td>${esc(fmtSize(b.trash_bytes||0))}</td>
     <td>${esc(fmtSize(b.staging_bytes||0))}</td>
     <td>${esc(fmtSize(b.index_bytes||0))}</td>
@@ -288,6 +291,7 @@ function reportHtml(report){
       ${statTile('Objects',(report.objects||0).toLocaleString())}
       ${statTile('Logical size',fmtSize(report.logical_bytes||0))}
       ${statTile('On disk',fmtSize(report.disk_bytes||0))}
+      ${report.compressed_logical_bytes?statTile('Saved by compression',fmtSize(Math.max(0,report.compressed_logical_bytes-(report.compressed_stored_bytes||0)))):''}
       ${statTile('Problems',problems.toLocaleString(),problems>0)}
       ${housekeeping?statTile('To tidy up',housekeeping.toLocaleString()):''}
       ${statTile('Still open',outstanding.toLocaleString(),problems>0&&outstanding>0)}
@@ -296,7 +300,7 @@ function reportHtml(report){
     </div>
     ${deferredNote}
     <div style="overflow-x:auto"><table>
-      <thead><tr><th>Bucket</th><th>Objects</th><th>Logical</th><th>Objects dir</th><th>Trash</th><th>Staging</th><th>Index</th><th>Empty dirs</th><th>Stale intents</th></tr></thead>
+      <thead><tr><th>Bucket</th><th>Objects</th><th>Logical</th><th>Objects dir</th><th>Compressed</th><th>Inline</th><th>Trash</th><th>Staging</th><th>Index</th><th>Empty dirs</th><th>Stale intents</th></tr></thead>
       <tbody>${buckets}</tbody>
     </table></div>
   </div>
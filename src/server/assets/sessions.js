@@ -0,0 +1,36 @@
+// ── console sessions ───────────────────────────────────────────────────────
+// Admin-only list of everyone logged into the console, with per-session and
+// per-user sign-out. Revocation is immediate server-side; a revoked browser
+// finds out on its next request. Revoking your own session is a logout.
+let sessions=[],currentSession=null;
+
+async function loadSessions(){
+  try{
+    const data=await api('GET','/api/sessions');sessions=data.sessions||[];currentSession=data.current;
+    $('sessionCount').textContent=`${sessions.length} session${sessions.length===1?'':'s'}`;
+    $('sessionRows').innerHTML=sessions.map((s,i)=>{
+      const mine=s.id===currentSession;
+      return `<tr><td><div class="identity"><span class="identity-icon">${esc((s.username[0]||'U').toUpperCase())}</span><div><strong>${esc(s.username)}</strong><small>${s.is_builtin?'Server administrator':'IAM user'}${mine?' · This session':''}</small></div></div></td><td>${esc(s.ip||'—')}</td><td class="hide-tablet muted" title="${esc(s.user_agent||'')}" style="max-width:260px;overflow:hidden;text-overflow:ellipsis;white-space:nowrap">${esc(s.user_agent||'—')}</td><td>${esc(fmtTime(s.created_at_ms))}</td><td>${esc(fmtTime(s.last_seen_ms))}</td><td>${esc(fmtTime(s.expires_at_ms))}</td><td class="actions"><button class="row-action danger" title="Sign out this session" onclick="confirmRevokeSession(${i})">${icons.logout}</button><button class="row-action danger" title="Sign out all of this user's sessions" onclick="confirmRevokeUserSessions(${i})">${icons.users}</button></td></tr>`;
+    }).join('');
+    const empty=!sessions.length;$('sessionRows').closest('table').classList.toggle('hidden',empty);$('sessionEmpty').classList.toggle('hidden',!empty);
+    if(empty)$('sessionEmpty').innerHTML=`<div class="empty-icon">${icons.users}</div><h3>No active sessions</h3><p>Sessions appear here as people log into the console.</p>`;
+  }catch(e){toast('Could not load sessions',e.message,false);}
+}
+
+function confirmRevokeSession(i){
+  const s=sessions[i],mine=s.id===currentSession;
+  showConfirm('Sign out session?',s.username,mine?'This is your own session; you will be returned to the login page.':`The browser at ${s.ip||'an unknown address'} is signed out at once and must log in again.`,async()=>{
+    await api('DELETE','/api/sessions/'+encodeURIComponent(s.id));
+    if(mine){location.reload();return;}
+    toast('Session signed out',s.username);await loadSessions();
+  },{confirmLabel:'Sign out',busyLabel:'Signing out…'});
+}
+
+function confirmRevokeUserSessions(i){
+  const name=sessions[i].username,mine=name===me.username;
+  showConfirm('Sign out everywhere?',name,`Every console session of “${name}” ends at once${mine?', including yours':''}.`,async()=>{
+    const r=await api('DELETE','/api/users/'+encodeURIComponent(name)+'/sessions');
+    if(mine){location.reload();return;}
+    toast(`${r.revoked} session${r.revoked===1?'':'s'} signed out`,name);await loadSessions();
+  },{confirmLabel:'Sign out all',busyLabel:'Signing out…'});
+}
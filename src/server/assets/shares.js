@@ -0,0 +1,44 @@
+// ── share links ────────────────────────────────────────────────────────────
+// Every link made from the share dialog, with its limits and use. Everyone
+// sees their own links; admin:ManageShares sees everyone's. Revoking a link
+// refuses its next request; the rest keep working.
+let shares=[];
+const SHARE_OUTCOMES={ok:['green','Downloaded'],revoked:['red','Revoked'],expired:['','Expired'],limit_reached:['amber','Limit reached'],password_required:['amber','Wrong or missing password'],unknown:['red','Not valid']};
+
+function shareStatus(s,now){
+  if(s.revoked_at_ms)return '<span class="badge red">Revoked</span>';
+  if(s.expires_at_ms<=now)return '<span class="badge">Expired</span>';
+  if(s.max_downloads!=null&&s.downloads>=s.max_downloads)return '<span class="badge amber">Used up</span>';
+  return '<span class="badge green">Active</span>';
+}
+
+async function loadShares(){
+  try{
+    const data=await api('GET','/api/shares');shares=data.shares||[];const all=!!data.all,now=data.now_ms||Date.now();
+    $('shareCount').textContent=`${shares.length} link${shares.length===1?'':'s'}`;$('shareOwnerHead').classList.toggle('hidden',!all);
+    $('shareRows').innerHTML=shares.map((s,i)=>{
+      const live=!s.revoked_at_ms&&s.expires_at_ms>now;
+      return `<tr><td><div class="object-name"><span class="file-icon">${icons.file}</span><div><strong title="${esc(s.bucket+'/'+s.key)}">${esc(s.key)}</strong><div class="muted" style="font-size:11.5px">${esc(s.bucket)}${s.has_password?' · password':''}</div></div></div></td>${all?`<td>${esc(s.username)}${s.is_builtin?' <span class="badge amber">Built-in</span>':''}</td>`:''}<td>${shareStatus(s,now)}</td><td>${fmtCount(s.downloads)}${s.max_downloads!=null?` / ${fmtCount(s.max_downloads)}`:''}<div class="muted" style="font-size:11.5px">${s.last_access_ms?'last '+fmtTime(s.last_access_ms):'never used'}</div></td><td class="hide-tablet muted">${fmtTime(s.created_at_ms)}</td><td class="muted">${fmtTime(s.expires_at_ms)}</td><td class="actions"><button class="row-action" title="Show uses" onclick="openShareAccess(${i})">${icons.activity}</button>${live?`<button class="row-action danger" title="Revoke link" onclick="confirmRevokeShare(${i})">${icons.trash}</button>`:''}</td></tr>`;
+    }).join('');
+    const empty=!shares.length;$('shareRows').closest('table').classList.toggle('hidden',empty);$('shareEmpty').classList.toggle('hidden',!empty);
+    if(empty)$('shareEmpty').innerHTML=`<div class="empty-icon">${icons.share}</div><h3>No share links</h3><p>Links you make with Share in the object browser appear here.</p>`;
+  }catch(e){toast('Could not load share links',e.message,false);}
+}
+
+async function openShareAccess(i){
+  const s=shares[i];$('sa_object').textContent=s.bucket+'/'+s.key;$('shareAccessRows').innerHTML='';$('shareAccessEmpty').classList.add('hidden');$('shareAccessDlg').showModal();
+  try{
+    const accesses=(await api('GET','/api/shares/'+encodeURIComponent(s.id)+'/access')).accesses||[];
+    $('shareAccessRows').innerHTML=accesses.map(a=>{const [tone,label]=a.outcome==='ok'&&a.status>=300?['red',`Failed (${a.status})`]:SHARE_OUTCOMES[a.outcome]||['',a.outcome];return `<tr><td>${fmtTime(a.at_ms)}</td><td>${esc(a.ip||'—')}</td><td class="hide-tablet muted" title="${esc(a.user_agent||'')}" style="max-width:260px;overflow:hidden;text-overflow:ellipsis;white-space:nowrap">${esc(a.user_agent||'—')}</td><td><span class="badge ${tone}">${esc(label)}</span></td></tr>`;}).join('');
+    const empty=!accesses.length;$('shareAccessRows').closest('table').classList.toggle('hidden',empty);$('shareAccessEmpty').classList.toggle('hidden',!empty);
+    if(empty)$('shareAccessEmpty').innerHTML=`<div class="empty-icon">${icons.activity}</div><h3>Not used yet</h3><p>Each request on this link is recorded here.</p>`;
+  }catch(e){$('shareAccessDlg').close();toast('Could not load link uses',e.message,false);}
+}
+
+function confirmRevokeShare(i){
+  const s=shares[i];
+  showConfirm('Revoke share link?',s.bucket+'/'+s.key,'Anyone holding this link is refused from now on. Other links to the same object keep working.',async()=>{
+    await api('DELETE','/api/shares/'+encodeURIComponent(s.id));
+    toast('Share link revoked',s.key);await loadShares();
+  },{confirmLabel:'Revoke',busyLabel:'Revoking…'});
+}
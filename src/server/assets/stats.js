@@ -3,7 +3,7 @@
 // Admin-only tab; the /api/stats endpoint enforces that server-side. The server
 // downsamples to <=120 points and clamps the window to where data exists, so
 // this only ever draws a small, dense set — no empty expanses.
-let statsRange='5m', statsCharts=[], statsTimer=null, statsInited=false;
+let statsRange='5m', statsCharts=[], statsTimer=null, statsInited=false, statsRoots=null;
 
 // Categorical palette (light surface), validated colorblind-safe for adjacent
 // line pairs. Assigned in fixed order, never cycled.
@@ -104,6 +104,20 @@ function initStats(){
   },5000);
 }
 
+function statsRootsChart(roots){
+  const key=roots.map(root=>root.path).join('\n');
+  if(key===statsRoots)return;
+  statsRoots=key;
+  statsCharts.filter(c=>c.now==='nowRoots').forEach(c=>c.c.u.destroy());
+  statsCharts=statsCharts.filter(c=>c.now!=='nowRoots');
+  const el=$('chartRoots');
+  el.innerHTML='';
+  $('statsRootsCard').classList.toggle('hidden',!roots.length);
+  if(!roots.length)return;
+  statsCharts.push({c:statsChart(el,roots.map((root,i)=>({label:root.path,idx:13+i})),'pct'),
+    now:'nowRoots', head:v=>statsFmt('pct')(Math.max(...v.map(x=>x||0)))+' max'});
+}
+
 function statsResize(){
   if(!statsInited||$('tab_stats').classList.contains('hidden'))return;
   statsCharts.forEach(c=>c.c.u.setSize({width:c.c.el.clientWidth||520,height:168}));
@@ -123,6 +137,12 @@ async function loadStats(){
     empty.classList.toggle('hidden',hasData);
     grid.classList.toggle('hidden',!hasData);
     if(!hasData)return;
+    // Data roots come as used/total pairs; chart them as % used, appended as
+    // extra columns after the fixed ones. The set of roots is configuration, so
+    // the chart is rebuilt only when it changes.
+    const roots=r.roots||[];
+    roots.forEach(root=>d.push(root.used.map((u,i)=>u==null||!root.total[i]?null:u/root.total[i]*100)));
+    statsRootsChart(roots);
     statsCharts.forEach(c=>{
       c.c.u.setData([d[0],...c.c.defs.map(def=>d[def.idx])]);
       c.c.setLegend(null); // refresh legend to latest (cursor hook only fires on hover)
@@ -27,14 +27,14 @@ function opIcon(op,kind){
 
 // ── live task stream (WebSocket, enveloped) ──
 function connectTasksWs(){
-  if(!me?.is_admin||tasksWs)return;
+  if(!can('admin:ViewTasks')||tasksWs)return;
   let opened=false,ws;
   try{ws=new WebSocket((location.protocol==='https:'?'wss:':'ws:')+'//'+location.host+'/api/tasks/ws');}catch{startTaskPolling();return;}
   tasksWs=ws;
   ws.onopen=()=>{opened=true;lastMsgAt=Date.now();stopTaskPolling();startWatchdog();};
   ws.onmessage=e=>{lastMsgAt=Date.now();let env;try{env=JSON.parse(e.data);}catch{return;}if(env.type==='snapshot')renderTasksData(env);else if(env.type==='reply')handleProbeReply(env);};
   ws.onerror=()=>{try{ws.close();}catch{}};
-  ws.onclose=()=>{tasksWs=null;stopWatchdog();if(!me?.is_admin)return;if(opened){setTimeout(connectTasksWs,1500);}else{startTaskPolling();setTimeout(connectTasksWs,5000);}};
+  ws.onclose=()=>{tasksWs=null;stopWatchdog();if(!can('admin:ViewTasks'))return;if(opened){setTimeout(connectTasksWs,1500);}else{startTaskPolling();setTimeout(connectTasksWs,5000);}};
 }
 function startWatchdog(){stopWatchdog();tasksWatchdogTimer=setInterval(()=>{if(!tasksWs)return;if(Date.now()-lastMsgAt>PROBE_STALE_MS){sendProbe(Object.keys(taskSeen));setTimeout(()=>{if(tasksWs&&Date.now()-lastMsgAt>PROBE_STALE_MS){try{tasksWs.close();}catch{}}},4000);}},5000);}
 function stopWatchdog(){clearInterval(tasksWatchdogTimer);tasksWatchdogTimer=null;}
@@ -42,7 +42,7 @@ function sendProbe(ids){if(!tasksWs||tasksWs.readyState!==1)return;try{tasksWs.s
 function handleProbeReply(env){const t=env.tasks||{};let gone=false;for(const id in t){if(t[id]===null&&taskSeen[id]!==undefined){delete taskSeen[id];gone=true;}}if(gone&&lastTasksData){lastTasksData.tasks=(lastTasksData.tasks||[]).filter(x=>t[x.id]!==null);renderTasksData(lastTasksData);}}
 function startTaskPolling(){if(badgeTimer)return;refreshTasks();badgeTimer=setInterval(refreshTasks,3000);}
 function stopTaskPolling(){clearInterval(badgeTimer);badgeTimer=null;}
-async function refreshTasks(){if(!me?.is_admin)return;try{renderTasksData(await api('GET','/api/tasks'));}catch{}}
+async function refreshTasks(){if(!can('admin:ViewTasks'))return;try{renderTasksData(await api('GET','/api/tasks'));}catch{}}
 function renderTasksData(data){
   lastTasksData=data;const tasks=data.tasks||[];const active=tasks.filter(t=>!t.completed);
   const badge=$('taskBadge');badge.classList.remove('hidden');badge.classList.toggle('idle',active.length===0);$('taskBadgeLabel').textContent=active.length===0?'Idle':`${active.length} running`;
@@ -54,7 +54,17 @@ function renderTasksData(data){
   const completedNow=new Set();for(const t of tasks)if(t.completed){completedNow.add(t.id);showCompleted(t,now);}
   for(const id of [...dismissedTasks])if(!completedNow.has(id))dismissedTasks.delete(id);
   updateTasksEmpty(active.length);
+  renderLimits(data.limits||[]);
 }
+// Rate-limit buckets the server has touched in the last minute. The bar is the
+// emptiest of the subject's buckets; a byte bucket below zero is pacing.
+const LIMIT_SCOPES={access_key:'key',user:'user',group:'group',ip:'ip'};
+function renderLimits(limits){const el=$('tasksLimits');if(!el)return;el.innerHTML=limits.length?'<div class="limits-head">Rate limits</div>'+limits.map(limitRowHtml).join(''):'';}
+function limitFill(b){return b?Math.max(0,Math.min(100,Math.round(b.tokens/b.capacity*100))):100;}
+function limitRowHtml(l){const parts=[];const r=l.requests;if(r)parts.push(`${Math.max(0,Math.floor(r.tokens))}/${Math.round(r.capacity)} req · ${+r.rate.toFixed(2)}/s`);
+  for(const [b,arrow] of [[l.bytes_in,'↑'],[l.bytes_out,'↓']])if(b)parts.push(`${arrow} ${fmtSize(Math.round(b.rate))}/s${b.tokens<0?' (pacing)':''}`);
+  if(l.throttled)parts.push(`${l.throttled} throttled`);const status=parts.join(' · ');const fill=Math.min(limitFill(r),limitFill(l.bytes_in),limitFill(l.bytes_out));
+  return `<div class="task-row limit-row${l.limited?' limited':''}"><span class="limit-scope">${esc(LIMIT_SCOPES[l.scope]||l.scope)}</span><div class="task-main"><span class="task-target" title="${esc(l.subject)}">${esc(l.subject)}</span><span class="task-status" title="${esc(status)}">${esc(status)}</span><div class="task-bar"><span style="width:${fill}%"></span></div></div></div>`;}
 function taskRowHtml(t,now,done){const up=fmtDur(now-t.started_at_ms);const status=t.status||'';const cancel=(!done&&t.cancellable)?`<button class="task-cancel" title="Cancel task" onclick="cancelTask('${esc(t.id)}')"><span class="stop"></span></button>`:'<span class="task-cancel-spacer"></span>';const bar=done?'':(t.total>0?`<div class="task-bar"><span style="width:${Math.min(100,Math.round(t.done/t.total*100))}%"></span></div>`:(t.kind==='job'?`<div class="task-bar indet"><span></span></div>`:''));const glyph=icons[done?'check':opIcon(t.op,t.kind)]||icons.activity;return `<div class="task-row${done?' done':''}" data-task-id="${esc(t.id)}" data-sig="${esc(taskRowSig(t,done))}"><span class="task-op ${done?'done':esc(t.kind)}" title="${esc(t.op)}">${glyph}</span><div class="task-main"><span class="task-target" title="${esc(t.target)}">${esc(t.target)}</span><span class="task-status" title="${esc(status)}">${esc(status)}</span>${bar}</div><span class="task-up" title="running for ${esc(up)}">${esc(up)}</span>${cancel}</div>`;}
 
 // Everything about a row that can't be patched in place — if any of it changes
@@ -8,8 +8,10 @@ async function loadUsers(){
     $('userCount').textContent=`${users.length} user${users.length===1?'':'s'}`;
     $('userRows').innerHTML=users.map((u,i)=>{
       const memberships=(u.groups||[]).map(name=>`<span class="badge ${name.toLowerCase()==='admin'?'green':''}">${esc(name)}</span>`).join('')||'<span class="muted">None</span>';
-      const policy=u.has_policy?'<span class="badge green">Attached</span>':'<span class="badge">None</span>';
-      return `<tr><td><div class="identity"><span class="identity-icon">${esc((u.username[0]||'U').toUpperCase())}</span><div><strong>${esc(u.username)}</strong><small>${u.builtin?'Server administrator':'IAM user'}</small></div></div></td><td><span class="badge ${u.builtin?'amber':''}">${u.builtin?'Built-in':'Managed'}</span></td><td><div class="badge-list">${memberships}</div></td><td>${policy}</td><td class="actions"><button class="row-action" title="View access keys" onclick="manageKeys(${i})">${icons.key}</button>${u.builtin?'':`<button class="row-action" title="Manage groups" onclick="editMemberships(${i})">${icons.users}</button><button class="row-action" title="Reset password" onclick="openPasswordDialog(${i})">${icons.refresh}</button><button class="row-action" title="Edit explicit policy" onclick="editPolicy(${i})">${icons.shield}</button>${u.username===me.username?'':`<button class="row-action danger" title="Delete user" onclick="confirmDeleteUser(${i})">${icons.trash}</button>`}`}</td></tr>`;
+      const policy=u.policy_names&&u.policy_names.length?`<span class="badge green">${esc(u.policy_names.join(', '))}</span>`:u.has_policy?'<span class="badge green">Attached</span>':'<span class="badge">None</span>';
+      const mfa=u.mfa?'<span class="badge green">2FA</span>':'';
+      const disabled=u.disabled?'<span class="badge red">Disabled</span>':'';
+      return `<tr><td><div class="identity"><span class="identity-icon">${esc((u.username[0]||'U').toUpperCase())}</span><div><strong>${esc(u.username)}</strong><small>${u.builtin?'Server administrator':'IAM user'}</small></div></div></td><td><div class="badge-list"><span class="badge ${u.builtin?'amber':''}">${u.builtin?'Built-in':'Managed'}</span>${mfa}${disabled}</div></td><td><div class="badge-list">${memberships}</div></td><td>${policy}</td><td class="actions"><button class="row-action" title="View access keys" onclick="manageKeys(${i})">${icons.key}</button>${u.builtin?'':`<button class="row-action" title="Manage groups" onclick="editMemberships(${i})">${icons.users}</button><button class="row-action" title="Reset password" onclick="openPasswordDialog(${i})">${icons.refresh}</button>${u.mfa?`<button class="row-action danger" title="Reset two-factor authentication" onclick="confirmResetMfa(${i})">${icons.zap}</button>`:''}<button class="row-action" title="Edit explicit policy" onclick="editPolicy(${i})">${icons.shield}</button>${u.username===me.username?'':`<button class="row-action danger" title="Delete user" onclick="confirmDeleteUser(${i})">${icons.trash}</button>`}`}</td></tr>`;
     }).join('');
     const empty=!users.length;$('userRows').closest('table').classList.toggle('hidden',empty);$('userEmpty').classList.toggle('hidden',!empty);
     if(empty)$('userEmpty').innerHTML=`<div class="empty-icon">${icons.users}</div><h3>No users yet</h3><p>Create a user, attach a policy, and issue an access key.</p>`;
@@ -18,6 +20,7 @@ async function loadUsers(){
 function openUserDialog(){$('nu_name').value='';$('nu_pass').value='';setInlineError('userError');$('userDlg').showModal();setTimeout(()=>$('nu_name').focus(),50);}
 async function createUser(event){event.preventDefault();try{await api('POST','/api/users',{username:$('nu_name').value.trim(),password:$('nu_pass').value});$('userDlg').close();toast('User created',$('nu_name').value.trim());loadUsers();}catch(e){setInlineError('userError',e.message);}}
 function confirmDeleteUser(i){const name=users[i].username;showConfirm('Delete user?',name,`This permanently deletes “${name}” and all access keys owned by this user.`,async()=>{await api('DELETE','/api/users/'+encodeURIComponent(name));toast('User deleted',name);await loadUsers();});}
+function confirmResetMfa(i){const name=users[i].username;showConfirm('Reset two-factor authentication?',name,`“${name}” will sign in with their password alone, or set up a new authenticator if the server requires one. Use this when a user has lost their device and recovery codes.`,async()=>{await api('DELETE','/api/users/'+encodeURIComponent(name)+'/mfa');toast('Two-factor authentication reset',name);await loadUsers();},{confirmLabel:'Reset',busyLabel:'Resetting…',danger:true});}
 let passwordUser=null;
 function openPasswordDialog(i){passwordUser=users[i].username;$('rp_user').textContent=passwordUser;$('rp_password').value='';$('rp_confirm').value='';setInlineError('passwordError');$('passwordDlg').showModal();setTimeout(()=>$('rp_password').focus(),50);}
 async function resetPassword(event){event.preventDefault();const password=$('rp_password').value;if(password.length<8){setInlineError('passwordError','Password must be at least 8 characters.');return;}if(password!==$('rp_confirm').value){setInlineError('passwordError','Passwords do not match.');return;}try{await api('PUT','/api/users/'+encodeURIComponent(passwordUser)+'/password',{password});$('passwordDlg').close();toast('Password reset',passwordUser);}catch(e){setInlineError('passwordError',e.message);}}
@@ -26,6 +29,7 @@ async function editMemberships(i){membershipUser=users[i].username;$('mg_user').
 async function saveMemberships(){const selected=[...$('membershipList').querySelectorAll('input:checked')].map(input=>input.value);try{await api('PUT','/api/users/'+encodeURIComponent(membershipUser)+'/groups',{groups:selected});$('membershipDlg').close();toast('Groups updated',membershipUser);await loadUsers();}catch(e){setInlineError('membershipError',e.message);}}
 let managedKeyUser=null,managedKeyBuiltin=false,managedKeys=[];
 async function manageKeys(i){managedKeyUser=users[i].username;managedKeyBuiltin=!!users[i].builtin;$('uk_user').textContent=managedKeyUser;$('managedKeyCreate').classList.toggle('hidden',managedKeyBuiltin);setInlineError('managedKeyError');$('userKeysDlg').showModal();await loadManagedKeys();}
-async function loadManagedKeys(){try{const data=await api('GET','/api/users/'+encodeURIComponent(managedKeyUser)+'/keys');managedKeys=data.keys||[];$('managedKeyList').innerHTML=managedKeys.length?managedKeys.map((k,i)=>`<div style="display:flex;align-items:center;gap:10px;padding:10px 0;border-bottom:1px solid var(--line)"><span class="file-icon">${icons.key}</span><div style="min-width:0"><code class="key">${esc(k.access_key)}</code><div class="muted" style="font-size:11px;margin-top:3px">${k.builtin?'Built-in · Config managed':'Created '+fmtTime(k.created_at_ms)}</div></div><span class="spacer"></span>${k.builtin?'':`<button class="row-action danger" title="Delete key" onclick="deleteManagedKey(${i})">${icons.trash}</button>`}</div>`).join(''):`<div class="empty" style="padding:30px 10px"><div class="empty-icon">${icons.key}</div><h3>No access keys</h3><p>${managedKeyBuiltin?'Add API keys in the server configuration.':'Create a key for this user to connect an application.'}</p></div>`;}catch(e){setInlineError('managedKeyError',e.message);}}
+async function loadManagedKeys(){try{const data=await api('GET','/api/users/'+encodeURIComponent(managedKeyUser)+'/keys');managedKeys=data.keys||[];$('managedKeyList').innerHTML=managedKeys.length?managedKeys.map((k,i)=>`<div style="display:flex;align-items:center;gap:10px;padding:10px 0;border-bottom:1px solid var(--line)"><span class="file-icon">${icons.key}</span><div style="min-width:0"><code class="key">${esc(k.access_key)}</code><div class="muted" style="font-size:11px;margin-top:3px">${k.builtin?'Built-in · Config managed':'Created '+fmtTime(k.created_at_ms)+(k.active?'':' · Inactive')}</div></div><span class="spacer"></span>${k.builtin?'':`<button class="row-action" title="${k.active?'Deactivate key':'Activate key'}" onclick="toggleManagedKey(${i})">${k.active?icons.x:icons.check}</button><button class="row-action danger" title="Delete key" onclick="deleteManagedKey(${i})">${icons.trash}</button>`}</div>`).join(''):`<div class="empty" style="padding:30px 10px"><div class="empty-icon">${icons.key}</div><h3>No access keys</h3><p>${managedKeyBuiltin?'Add API keys in the server configuration.':'Create a key for this user to connect an application.'}</p></div>`;}catch(e){setInlineError('managedKeyError',e.message);}}
 async function createManagedKey(){try{const key=await api('POST','/api/users/'+encodeURIComponent(managedKeyUser)+'/keys');$('userKeysDlg').close();showSecret(key);}catch(e){setInlineError('managedKeyError',e.message);}}
+async function toggleManagedKey(i){const k=managedKeys[i];try{await api('PUT','/api/keys/'+encodeURIComponent(k.access_key),{active:!k.active});await loadManagedKeys();}catch(e){setInlineError('managedKeyError',e.message);}}
 function deleteManagedKey(i){const ak=managedKeys[i].access_key;showConfirm('Delete access key?',ak,'Applications using this key will immediately lose access.',async()=>{await api('DELETE','/api/keys/'+encodeURIComponent(ak));toast('Access key deleted');await loadManagedKeys();});}
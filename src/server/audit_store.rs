@@ -0,0 +1,526 @@
+//! Persistence for the audit trail — `<data_root>/audit.rocksdb`.
+//!
+//! A recorder fed by the task registry's [`EventHub`] stores every
+//! [`Event::Audit`] — S3 requests with their allow/deny outcome, console
+//! authorization decisions, and IAM/bucket management actions — so incident
+//! response can filter and export them instead of grepping rotated logs. Its
+//! own database for the same reasons as [`stats_store`](super::stats_store).
+//!
+//! | family   | key                         | value                 |
+//! |----------|-----------------------------|-----------------------|
+//! | `events` | `{epoch_ms:013}{seq:06}`    | [`AuditRecord`] JSON  |
+//!
+//! The zero-padded timestamp makes bytewise order chronological: a time range
+//! is one seek and retention is one ranged delete. The sequence suffix keeps
+//! events recorded in the same millisecond apart.
+//!
+//! [`EventHub`]: super::event_hub::EventHub
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use rocksdb::{
+    ColumnFamilyDescriptor, DBWithThreadMode, Direction, IteratorMode, MultiThreaded, Options,
+    WriteBatch, WriteOptions,
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use super::event_hub::Event;
+use super::registry::TaskRegistry;
+use super::reload::LiveConfig;
+use crate::storage::errors::{Result, StorageError};
+use crate::storage::time::{iso_utc_ms, now_ms};
+
+type Db = DBWithThreadMode<MultiThreaded>;
+
+const CF_EVENTS: &str = "events";
+
+/// Events written per batch by the recorder.
+const RECORD_BATCH: usize = 512;
+
+/// Audit events queued for the recorder before publishers wait for it.
+const AUDIT_FEED_CAPACITY: usize = 4096;
+
+/// How often the recorder drops events older than the retention window.
+const PRUNE_EVERY: Duration = Duration::from_secs(3600);
+
+fn key_for(ts_ms: i64, seq: u64) -> String {
+    format!("{:013}{:06}", ts_ms.max(0), seq % 1_000_000)
+}
+
+fn ts_bound(ts_ms: i64) -> String {
+    format!("{:013}", ts_ms.max(0))
+}
+
+/// One stored audit event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub at_ms: i64,
+    pub actor: String,
+    pub action: String,
+    pub target: String,
+    pub allowed: bool,
+    pub request_id: String,
+}
+
+/// Query filters; every set field must match. Actor and action match
+/// case-insensitively anywhere in the value; `bucket`/`prefix` match the
+/// target path `/<bucket>/<prefix>…`; the time range is `[from_ms, to_ms)`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    /// Exactly this user: the console records a bare username, the S3 API
+    /// `<username>/<access key>`.
+    pub principal: Option<String>,
+    pub action: Option<String>,
+    pub bucket: Option<String>,
+    pub prefix: Option<String>,
+    pub allowed: Option<bool>,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        let contains = |value: &str, needle: &Option<String>| {
+            needle
+                .as_deref()
+                .map(|needle| value.to_lowercase().contains(&needle.to_lowercase()))
+                .unwrap_or(true)
+        };
+        if !contains(&record.actor, &self.actor) || !contains(&record.action, &self.action) {
+            return false;
+        }
+        if let Some(principal) = self.principal.as_deref() {
+            let (user, _) = record.actor.split_once('/').unwrap_or((&record.actor, ""));
+            if user != principal {
+                return false;
+            }
+        }
+        if self
+            .allowed
+            .is_some_and(|allowed| allowed != record.allowed)
+        {
+            return false;
+        }
+        if let Some(bucket) = self.bucket.as_deref().filter(|b| !b.is_empty()) {
+            let root = format!("/{bucket}");
+            let prefix = self.prefix.as_deref().unwrap_or("");
+            let in_bucket = if prefix.is_empty() {
+                record.target == root || record.target.starts_with(&format!("{root}/"))
+            } else {
+                record.target.starts_with(&format!("{root}/{prefix}"))
+            };
+            if !in_bucket {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditStore {
+    db: Arc<Db>,
+    seq: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for AuditStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditStore").finish_non_exhaustive()
+    }
+}
+
+fn cf<'a>(db: &'a Db, name: &str) -> Result<Arc<rocksdb::BoundColumnFamily<'a>>> {
+    db.cf_handle(name)
+        .ok_or_else(|| StorageError::Db(format!("missing column family {name}")))
+}
+
+/// The trail duplicates what `audit.log` already has on disk; an unsynced WAL
+/// only risks the last moments before a power loss, never the object data.
+fn write_opts() -> WriteOptions {
+    let mut opts = WriteOptions::default();
+    opts.set_sync(false);
+    opts
+}
+
+async fn blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => Err(StorageError::Db(format!(
+            "audit store task panicked: {err}"
+        ))),
+    }
+}
+
+impl AuditStore {
+    pub async fn open(data_root: &Path) -> Result<Self> {
+        tokio::fs::create_dir_all(data_root).await?;
+        let db_path = data_root.join("audit.rocksdb");
+        let db = blocking(move || {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            opts.create_missing_column_families(true);
+            let cfs = [CF_EVENTS].map(|name| ColumnFamilyDescriptor::new(name, Options::default()));
+            Ok(Db::open_cf_descriptors(&opts, &db_path, cfs)?)
+        })
+        .await?;
+        Ok(Self {
+            db: Arc::new(db),
+            seq: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Stores a batch of records in one write.
+    pub async fn append(&self, records: Vec<AuditRecord>) -> Result<()> {
+        let store = self.clone();
+        blocking(move || store.append_blocking(&records)).await
+    }
+
+    /// [`append`](Self::append) on the calling thread, for the recorder.
+    fn append_blocking(&self, records: &[AuditRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let cf = cf(&self.db, CF_EVENTS)?;
+        let mut batch = WriteBatch::default();
+        for record in records {
+            let key = key_for(record.at_ms, self.seq.fetch_add(1, Ordering::Relaxed));
+            batch.put_cf(&cf, key.as_bytes(), serde_json::to_vec(record)?);
+        }
+        self.db.write_opt(batch, &write_opts())?;
+        Ok(())
+    }
+
+    /// Newest-first matches, at most `limit`. The boolean reports whether more
+    /// matches exist beyond the limit.
+    pub async fn query(
+        &self,
+        filter: AuditFilter,
+        limit: usize,
+    ) -> Result<(Vec<AuditRecord>, bool)> {
+        let db = self.db.clone();
+        blocking(move || {
+            let cf = cf(&db, CF_EVENTS)?;
+            let upper = ts_bound(filter.to_ms.unwrap_or(i64::MAX / 1_000_000));
+            let from = filter.from_ms.unwrap_or(0);
+            let mut out = Vec::new();
+            for item in db.iterator_cf(
+                &cf,
+                IteratorMode::From(upper.as_bytes(), Direction::Reverse),
+            ) {
+                let (key, value) = item?;
+                // The seek key itself is a bare timestamp, so anything at or
+                // above it (same millisecond plus a sequence) is out of range.
+                if key.as_ref() >= upper.as_bytes() {
+                    continue;
+                }
+                let Ok(record) = serde_json::from_slice::<AuditRecord>(&value) else {
+                    continue;
+                };
+                if record.at_ms < from {
+                    break;
+                }
+                if !filter.matches(&record) {
+                    continue;
+                }
+                if out.len() == limit {
+                    return Ok((out, true));
+                }
+                out.push(record);
+            }
+            Ok((out, false))
+        })
+        .await
+    }
+
+    /// Removes every event older than `before_ms` in a single ranged delete.
+    pub async fn prune(&self, before_ms: i64) -> Result<()> {
+        let store = self.clone();
+        blocking(move || store.prune_blocking(before_ms)).await
+    }
+
+    fn prune_blocking(&self, before_ms: i64) -> Result<()> {
+        let cf = cf(&self.db, CF_EVENTS)?;
+        let from = ts_bound(0);
+        let to = ts_bound(before_ms); // exclusive upper bound
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(&cf, from.as_bytes(), to.as_bytes());
+        self.db.write_opt(batch, &write_opts())?;
+        Ok(())
+    }
+}
+
+/// Renders records as CSV with a header row, quoting every text field.
+pub fn records_to_csv(records: &[AuditRecord]) -> String {
+    let quote = |value: &str| format!("\"{}\"", value.replace('"', "\"\""));
+    let mut out = String::from("time,actor,action,target,outcome,request_id\n");
+    for record in records {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            iso_utc_ms(record.at_ms),
+            quote(&record.actor),
+            quote(&record.action),
+            quote(&record.target),
+            if record.allowed { "allowed" } else { "denied" },
+            quote(&record.request_id),
+        ));
+    }
+    out
+}
+
+/// Spawns the recorder: it drains [`Event::Audit`]s off the registry's audit
+/// feed in batches into `store`, and about hourly prunes events older than the
+/// retention window (as currently configured, so a reload applies to the next
+/// prune). Stops on `shutdown` after writing what it already holds.
+///
+/// The feed is bounded and publishers wait when it is full, so a slow disk
+/// slows requests down instead of losing audit records. The recorder runs on
+/// its own thread so that wait never depends on a free runtime worker.
+pub(crate) fn spawn_recorder(
+    store: AuditStore,
+    tasks: Arc<TaskRegistry>,
+    config: LiveConfig,
+    shutdown: CancellationToken,
+) {
+    // Open the feed before spawning so nothing published after startup is missed.
+    let Some(rx) = tasks.audit_feed(AUDIT_FEED_CAPACITY) else {
+        log::warn!("audit recorder already running for this registry");
+        return;
+    };
+    let spawned = std::thread::Builder::new()
+        .name("audit-recorder".into())
+        .spawn(move || record_loop(store, rx, config, shutdown));
+    if let Err(err) = spawned {
+        log::error!("audit recorder: failed to start: {err}");
+    }
+}
+
+fn record_loop(
+    store: AuditStore,
+    rx: Receiver<Event>,
+    config: LiveConfig,
+    shutdown: CancellationToken,
+) {
+    log::info!(
+        "audit recorder started retention_days={}",
+        config.current().audit.retention_days
+    );
+    let mut last_prune: Option<Instant> = None;
+    let mut batch = Vec::new();
+    let write = |batch: &mut Vec<AuditRecord>| {
+        if let Err(err) = store.append_blocking(&std::mem::take(batch)) {
+            log::warn!("audit recorder: failed to store events: {err}");
+        }
+    };
+    loop {
+        if last_prune.is_none_or(|at| at.elapsed() >= PRUNE_EVERY) {
+            last_prune = Some(Instant::now());
+            let retention_ms = (config.current().audit.retention_days.max(1))
+                .saturating_mul(86_400)
+                .saturating_mul(1000) as i64;
+            if let Err(err) = store.prune_blocking(now_ms() - retention_ms) {
+                log::warn!("audit recorder: retention prune failed: {err}");
+            }
+        }
+        if shutdown.is_cancelled() {
+            while let Ok(event) = rx.try_recv() {
+                batch.extend(to_record(event));
+            }
+            write(&mut batch);
+            log::info!("audit recorder stopping");
+            break;
+        }
+        // Wake up now and then to notice shutdown and the prune schedule.
+        match rx.recv_timeout(Duration::from_millis(250)) {
+            Ok(event) => batch.extend(to_record(event)),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        while batch.len() < RECORD_BATCH {
+            match rx.try_recv() {
+                Ok(event) => batch.extend(to_record(event)),
+                Err(_) => break,
+            }
+        }
+        write(&mut batch);
+    }
+}
+
+fn to_record(event: Event) -> Option<AuditRecord> {
+    match event {
+        Event::Audit {
+            actor,
+            action,
+            target,
+            allowed,
+            request_id,
+        } => Some(AuditRecord {
+            at_ms: now_ms(),
+            actor,
+            action,
+            target,
+            allowed,
+            request_id,
+        }),
+        Event::TasksChanged => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store() -> (tempfile::TempDir, AuditStore) {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = AuditStore::open(tmp.path()).await.unwrap();
+        (tmp, store)
+    }
+
+    fn record(at_ms: i64, actor: &str, action: &str, target: &str, allowed: bool) -> AuditRecord {
+        AuditRecord {
+            at_ms,
+            actor: actor.to_string(),
+            action: action.to_string(),
+            target: target.to_string(),
+            allowed,
+            request_id: format!("rid-{at_ms}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn query_filters_and_returns_newest_first() {
+        let (_tmp, store) = store().await;
+        store
+            .append(vec![
+                record(10, "alice", "UPLOAD", "/docs/reports/q1.pdf", true),
+                record(20, "bob", "DOWNLOAD", "/docs/reports/q1.pdf", false),
+                record(20, "alice", "DELETE", "/photos/cat.jpg", true),
+                record(30, "alice/AKIA1", "DOWNLOAD", "/docs-archive/x", true),
+                record(40, "root", "create_bucket", "/docs", true),
+            ])
+            .await
+            .unwrap();
+
+        let all = |filter: AuditFilter| {
+            let store = store.clone();
+            async move { store.query(filter, 100).await.unwrap().0 }
+        };
+        let times = |records: Vec<AuditRecord>| records.iter().map(|r| r.at_ms).collect::<Vec<_>>();
+
+        assert_eq!(
+            times(all(AuditFilter::default()).await),
+            vec![40, 30, 20, 20, 10]
+        );
+        let alice = AuditFilter {
+            actor: Some("ALICE".into()),
+            ..Default::default()
+        };
+        assert_eq!(all(alice).await.len(), 3);
+        let principal = |name: &str| AuditFilter {
+            principal: Some(name.into()),
+            ..Default::default()
+        };
+        assert_eq!(times(all(principal("alice")).await), vec![30, 20, 10]);
+        assert!(all(principal("ali")).await.is_empty(), "whole usernames only");
+        let docs = AuditFilter {
+            bucket: Some("docs".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            times(all(docs).await),
+            vec![40, 20, 10],
+            "docs-archive is another bucket"
+        );
+        let reports = AuditFilter {
+            bucket: Some("docs".into()),
+            prefix: Some("reports/".into()),
+            allowed: Some(false),
+            ..Default::default()
+        };
+        let denied = all(reports).await;
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].actor, "bob");
+        let window = AuditFilter {
+            from_ms: Some(20),
+            to_ms: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(times(all(window).await), vec![30, 20, 20]);
+
+        let (page, more) = store.query(AuditFilter::default(), 2).await.unwrap();
+        assert_eq!(times(page), vec![40, 30]);
+        assert!(more);
+    }
+
+    #[tokio::test]
+    async fn prune_removes_only_old_events() {
+        let (_tmp, store) = store().await;
+        store
+            .append(
+                (1..=5)
+                    .map(|t| record(t * 10, "a", "x", "/b", true))
+                    .collect(),
+            )
+            .await
+            .unwrap();
+        store.prune(30).await.unwrap();
+        let (left, _) = store.query(AuditFilter::default(), 10).await.unwrap();
+        assert_eq!(
+            left.iter().map(|r| r.at_ms).collect::<Vec<_>>(),
+            vec![50, 40, 30]
+        );
+    }
+
+    #[test]
+    fn csv_quotes_text_fields() {
+        let csv = records_to_csv(&[record(0, "eve \"x\"", "UPLOAD", "/b/a,b", false)]);
+        assert_eq!(
+            csv,
+            "time,actor,action,target,outcome,request_id\n\
+             1970-01-01T00:00:00.000Z,\"eve \"\"x\"\"\",\"UPLOAD\",\"/b/a,b\",denied,\"rid-0\"\n"
+        );
+    }
+    #[tokio::test]
+    async fn recorder_keeps_every_event_of_a_burst() {
+        let (_tmp, store) = store().await;
+        let tasks = TaskRegistry::new();
+        let shutdown = CancellationToken::new();
+        spawn_recorder(
+            store.clone(),
+            tasks.clone(),
+            LiveConfig::new(Arc::new(crate::server::config::AppConfig::default())),
+            shutdown.clone(),
+        );
+        // Well past both the feed and the console broadcast capacity, published
+        // without yielding so the recorder cannot keep pace on its own.
+        let total = AUDIT_FEED_CAPACITY * 3;
+        for i in 0..total {
+            tasks.publish(Event::Audit {
+                actor: "alice".into(),
+                action: "s3:GetObject".into(),
+                target: format!("/docs/{i}"),
+                allowed: true,
+                request_id: format!("rid-{i}"),
+            });
+        }
+        shutdown.cancel();
+        let mut stored = 0;
+        for _ in 0..200 {
+            stored = store.query(AuditFilter::default(), total).await.unwrap().0.len();
+            if stored == total {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(stored, total);
+    }
+}
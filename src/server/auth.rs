@@ -3,12 +3,16 @@
 //! Validates both regular `Authorization: AWS4-HMAC-SHA256 …` requests and
 //! pre-signed URLs (`?X-Amz-Signature=…`).  When `auth.enabled` is false in
 //! the config the middleware is a no-op.
-
-use std::sync::Arc;
+//!
+//! Share links minted by the console are presigned URLs that also carry
+//! [`SHARE_ID_PARAM`]. Once the signature checks out, the share registry in
+//! [`IamStore`] decides whether that one link may still be used; a password,
+//! if the link has one, arrives as the password of HTTP Basic auth, which
+//! browsers prompt for on the `401` such a link answers with.
 
 use axum::body::Body;
 use axum::extract::State;
-use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode};
 use axum::middleware::Next;
 use axum::response::Response;
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
@@ -17,24 +21,27 @@ use hmac::{Hmac, Mac};
 use sha1::Sha1;
 use sha2::{Digest, Sha256};
 
-use super::config::AppConfig;
-use super::iam::{IamStore, Principal};
+use super::reload::LiveConfig;
+use super::iam::{IamStore, Principal, ShareAccess, ShareDenial};
 use super::identity::Identity;
 use super::logging::{TARGET_AUTH, TARGET_AUTHZ};
-use super::policy::{is_authorized, requirements_for_request, PolicyDocument};
+use super::policy::requirements_for_request;
 use super::xml::{error_xml, S3ErrorXml};
-use super::OperationActor;
+use super::{OperationActor, S3ErrorCode};
 
 type HmacSha256 = Hmac<Sha256>;
 type HmacSha1 = Hmac<Sha1>;
 
 const MAX_SIGNATURE_CLOCK_SKEW_SECS: i64 = 15 * 60;
 
+/// Query parameter naming a share link's registry entry.
+pub(crate) const SHARE_ID_PARAM: &str = "X-Rusts3-Share-Id";
+
 /// Shared state for the auth middleware: static config credentials (root,
 /// unrestricted) plus the IAM store (policy-bound access keys).
 #[derive(Clone)]
 pub struct AuthState {
-    pub config: Arc<AppConfig>,
+    pub config: LiveConfig,
     pub iam: Option<IamStore>,
 }
 
@@ -42,9 +49,14 @@ impl AuthState {
     /// Resolves an access key to `(secret, principal)`. Config credentials
     /// are root; IAM keys carry their owning user; hidden `RSWEB_…` signing
     /// keys resolve to their owner's access (so console-generated share links
-    /// are authorized exactly as the user who created them).
-    fn lookup(&self, access_key: &str) -> Option<(String, Principal)> {
-        if let Some(secret) = self.config.find_secret(access_key) {
+    /// are authorized exactly as the user who created them). Temporary keys
+    /// from the STS endpoint resolve only alongside their session token.
+    pub(crate) fn lookup(
+        &self,
+        access_key: &str,
+        session_token: Option<&str>,
+    ) -> Option<(String, Principal)> {
+        if let Some(secret) = self.config.current().find_secret(access_key) {
             return Some((secret.to_string(), Principal::Root));
         }
         let iam = self.iam.as_ref()?;
@@ -57,13 +69,37 @@ impl AuthState {
                 // still exists in config (removing them revokes their shares).
                 return self
                     .config
+                    .current()
                     .find_builtin_user(&username)
                     .map(|_| (secret, Principal::Root));
             }
             return Some((secret, Principal::IamUser(username)));
         }
+        if let Some((secret, username, token)) = iam.find_temporary_key(access_key) {
+            return session_token
+                .filter(|presented| constant_time_eq(presented, &token))
+                .map(|_| (secret, Principal::IamUser(username)));
+        }
         None
     }
+
+    /// Narrows `identity` to the scope of the access key it signed with, for
+    /// keys that carry one.
+    fn with_key_scope(&self, identity: Identity, access_key: &str) -> Identity {
+        match self.iam.as_ref().and_then(|iam| iam.key_scope(access_key)) {
+            Some(scope) => identity.scoped(scope),
+            None => identity,
+        }
+    }
+}
+
+/// The session token that temporary credentials travel with: the
+/// `x-amz-security-token` header, or its query parameter on presigned URLs.
+fn session_token(request: &Request<Body>) -> Option<String> {
+    if let Some(token) = header_str(request.headers(), "x-amz-security-token") {
+        return Some(token.to_string());
+    }
+    query_param(request.uri().query()?, "X-Amz-Security-Token")
 }
 
 // ─── Public middleware ────────────────────────────────────────────────────────
@@ -76,7 +112,7 @@ pub async fn auth_middleware(
     next: Next,
 ) -> Response {
     let rid = request_id(&request);
-    if !state.config.auth.enabled {
+    if !state.config.current().auth.enabled {
         log::debug!(target: TARGET_AUTH, "[{rid}] authn skipped (auth disabled)");
         return next.run(request).await;
     }
@@ -99,6 +135,13 @@ pub async fn auth_middleware(
         request.extensions_mut().insert(state.clone());
         return next.run(request).await;
     }
+    // STS calls are unsigned by design: the caller is trading a directory
+    // password for credentials, and the STS handler checks that password.
+    if super::sts::is_sts_request(&request) {
+        log::debug!(target: TARGET_AUTH, "[{rid}] authn deferred to the STS endpoint");
+        request.extensions_mut().insert(state.clone());
+        return next.run(request).await;
+    }
 
     // Phase 1 — authentication: prove the caller holds a valid credential.
     let authn_start = std::time::Instant::now();
@@ -116,7 +159,8 @@ pub async fn auth_middleware(
             let access_key = claimed_access_key(&request);
             let resolved = access_key
                 .as_deref()
-                .and_then(|key| state.lookup(key).map(|(_, principal)| principal));
+                .and_then(|key| state.lookup(key, session_token(&request).as_deref()))
+                .map(|(_, principal)| principal);
             let actor = operation_actor(&state, resolved.as_ref(), access_key);
             return with_operation_actor(deny(msg), actor);
         }
@@ -127,6 +171,32 @@ pub async fn auth_middleware(
         authn_start.elapsed().as_micros()
     );
     let actor = operation_actor(&state, Some(&principal), claimed_access_key(&request));
+    let access_key = actor.access_key.clone().unwrap_or_default();
+    let secure = request.extensions().get::<super::tls::TlsConnection>().is_some();
+
+    // The MinIO admin API authorizes each call against `admin:` actions
+    // itself, and needs the caller's secret to seal its payloads.
+    if super::madmin::is_admin_request(&request) {
+        let identity = match &principal {
+            Principal::Root => Identity::root(actor.username.clone(), actor.access_key.clone()),
+            Principal::IamUser(username) => {
+                let policy = state.iam.as_ref().and_then(|iam| iam.policy_for(username));
+                Identity::iam(username.clone(), policy).over_tls(secure)
+            }
+        };
+        let identity = state.with_key_scope(identity, &access_key);
+        let secret_key = state
+            .lookup(&access_key, session_token(&request).as_deref())
+            .map(|(secret, _)| secret)
+            .unwrap_or_default();
+        request.extensions_mut().insert(super::madmin::AdminCaller {
+            identity: identity.clone(),
+            secret_key,
+        });
+        request.extensions_mut().insert(identity);
+        request.extensions_mut().insert(actor.clone());
+        return with_operation_actor(next.run(request).await, actor);
+    }
 
     // Phase 2 — authorization: enforce the IAM policy bound to the caller
     // (root config credentials are unrestricted and skip this), then attach the
@@ -140,7 +210,9 @@ pub async fn auth_middleware(
                 log::warn!(target: TARGET_AUTHZ, "[{rid}] authz DENY user={username} reason=no_policy_attached");
                 return with_operation_actor(access_denied(), actor);
             };
-            if !authorize_iam(&policy, &request) {
+            let identity = Identity::iam(username.clone(), Some(policy)).over_tls(secure);
+            let identity = state.with_key_scope(identity, &access_key);
+            if !authorize_iam(&identity, &request) {
                 log::warn!(
                     target: TARGET_AUTHZ,
                     "[{rid}] authz DENY user={username} method={} uri={} ({}µs)",
@@ -155,11 +227,113 @@ pub async fn auth_middleware(
                 "[{rid}] authz ok user={username} ({}µs)",
                 authz_start.elapsed().as_micros()
             );
-            Identity::iam(username.clone(), Some(policy))
+            identity
         }
     };
+
+    // Phase 3 — share links: the signature is the link's owner's, so it
+    // only proves the link is genuine; the registry says whether it is
+    // still live, and counts and records each use.
+    let share = share_link_id(&request).map(|id| (id, share_access(&request)));
+    // Every GET that returns data spends a download, ranged or not: the
+    // bytes of a limited link cannot be pulled a range at a time for free.
+    // A HEAD returns none, so it is only checked.
+    let counted = request.method() == Method::GET;
+    if let Some((id, access)) = &share {
+        let password = basic_auth_password(request.headers());
+        let opened = match state.iam.as_ref() {
+            Some(iam) => iam.open_share(id, &access_key, password.as_deref(), counted).await,
+            None => Err(ShareDenial::Unknown),
+        };
+        if let Err(denial) = opened {
+            log::warn!(
+                target: TARGET_AUTHZ,
+                "[{rid}] share DENY link={id} reason={}",
+                denial.as_str()
+            );
+            let response = share_denied(denial);
+            if let Some(iam) = state.iam.as_ref() {
+                iam.record_share_access(
+                    id,
+                    ShareAccess {
+                        status: response.status().as_u16(),
+                        outcome: denial.as_str().to_string(),
+                        ..access.clone()
+                    },
+                );
+            }
+            return with_operation_actor(response, actor);
+        }
+    }
     request.extensions_mut().insert(identity);
-    with_operation_actor(next.run(request).await, actor)
+    // The rate limiter inside this layer keys on the verified caller.
+    request.extensions_mut().insert(actor.clone());
+    let response = next.run(request).await;
+    if let (Some((id, access)), Some(iam)) = (share, state.iam.as_ref()) {
+        if counted && !response.status().is_success() {
+            iam.refund_share_download(&id);
+        }
+        let status = response.status().as_u16();
+        iam.record_share_access(&id, ShareAccess { status, outcome: "ok".into(), ..access });
+    }
+    with_operation_actor(response, actor)
+}
+
+/// The share link id of a presigned request, if it carries one.
+fn share_link_id(request: &Request<Body>) -> Option<String> {
+    let query = request.uri().query()?;
+    if !query.contains("X-Amz-Signature=") {
+        return None;
+    }
+    parse_query_params(query).remove(SHARE_ID_PARAM)
+}
+
+/// Where a share link use came from, for its access record.
+fn share_access(request: &Request<Body>) -> ShareAccess {
+    ShareAccess {
+        at_ms: Utc::now().timestamp_millis(),
+        ip: request
+            .extensions()
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|info| info.0.ip().to_string()),
+        user_agent: header_str(request.headers(), header::USER_AGENT.as_str())
+            .map(|agent| agent.chars().take(256).collect()),
+        ..ShareAccess::default()
+    }
+}
+
+/// The password half of `Authorization: Basic …`; the user name is ignored.
+fn basic_auth_password(headers: &HeaderMap) -> Option<String> {
+    let encoded = header_str(headers, header::AUTHORIZATION.as_str())?.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(BASE64_STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    decoded.split_once(':').map(|(_, password)| password.to_string())
+}
+
+fn share_denied(denial: ShareDenial) -> Response {
+    let message = match denial {
+        ShareDenial::Unknown => "This share link is not valid",
+        ShareDenial::Revoked => "This share link has been revoked",
+        ShareDenial::Expired => "This share link has expired",
+        ShareDenial::LimitReached => "This share link has reached its download limit",
+        ShareDenial::PasswordRequired => "This share link needs its password",
+    };
+    let body = error_xml(&S3ErrorXml {
+        code: "AccessDenied".to_string(),
+        message: message.to_string(),
+        resource: "/".to_string(),
+        request_id: "rust-s3-server".to_string(),
+    });
+    let mut response = axum::response::Response::builder()
+        .header("content-type", "application/xml")
+        .header("x-amz-request-id", "rust-s3-server")
+        .extension(S3ErrorCode("AccessDenied".to_string()));
+    response = match denial {
+        ShareDenial::PasswordRequired => response
+            .status(StatusCode::UNAUTHORIZED)
+            .header(header::WWW_AUTHENTICATE, r#"Basic realm="share link", charset="UTF-8""#),
+        _ => response.status(StatusCode::FORBIDDEN),
+    };
+    response.body(Body::from(body)).unwrap()
 }
 
 /// The correlation id injected by the outer logging layer, or `"-"` if this
@@ -188,6 +362,7 @@ fn operation_actor(
         Some(Principal::Root) => access_key.as_deref().and_then(|access_key| {
             state
                 .config
+                .current()
                 .auth
                 .users
                 .iter()
@@ -229,9 +404,9 @@ fn claimed_access_key(request: &Request<Body>) -> Option<String> {
     })
 }
 
-/// Evaluates the IAM user's policy against the request. No policy attached
-/// means deny-everything; admin-only operations are never IAM-authorized.
-fn authorize_iam(policy: &PolicyDocument, request: &Request<Body>) -> bool {
+/// Evaluates the IAM identity's policy against the request. Admin-only
+/// operations are never IAM-authorized.
+fn authorize_iam(identity: &Identity, request: &Request<Body>) -> bool {
     let copy_source = request
         .headers()
         .get("x-amz-copy-source")
@@ -244,7 +419,7 @@ fn authorize_iam(policy: &PolicyDocument, request: &Request<Body>) -> bool {
     ) else {
         return false; // admin-only operation
     };
-    is_authorized(policy, &requirements)
+    identity.authorize(&requirements)
 }
 
 /// True when a POST's query string selects a concrete S3 operation whose
@@ -294,8 +469,9 @@ pub(crate) fn authorize_browser_post(
     fields: &std::collections::BTreeMap<String, String>,
     bucket: &str,
     key: &str,
+    secure_transport: bool,
 ) -> Result<OperationActor, Response> {
-    if !state.config.auth.enabled {
+    if !state.config.current().auth.enabled {
         return Ok(OperationActor::default());
     }
     let field = |name: &str| {
@@ -321,7 +497,7 @@ pub(crate) fn authorize_browser_post(
     let _terminator = parts.next().ok_or_else(|| deny("Invalid x-amz-credential"))?;
 
     let (secret, principal) = state
-        .lookup(access_key)
+        .lookup(access_key, field("x-amz-security-token"))
         .ok_or_else(|| deny("Unknown access key"))?;
 
     // The POST string-to-sign is the base64 policy document verbatim.
@@ -341,10 +517,9 @@ pub(crate) fn authorize_browser_post(
         let Some(policy) = state.iam.as_ref().and_then(|iam| iam.policy_for(username)) else {
             return Err(access_denied());
         };
-        if !is_authorized(
-            &policy,
-            &[super::policy::Requirement::object("s3:PutObject", bucket, key)],
-        ) {
+        let identity = Identity::iam(username.clone(), Some(policy)).over_tls(secure_transport);
+        let identity = state.with_key_scope(identity, access_key);
+        if !identity.authorize(&[super::policy::Requirement::object("s3:PutObject", bucket, key)]) {
             log::warn!(target: TARGET_AUTHZ, "s3 browser POST denied by policy user={username} bucket={bucket} key={key}");
             return Err(access_denied());
         }
@@ -354,6 +529,7 @@ pub(crate) fn authorize_browser_post(
         Principal::IamUser(username) => Some(username.clone()),
         Principal::Root => state
             .config
+            .current()
             .auth
             .users
             .iter()
@@ -479,7 +655,7 @@ fn validate_request(state: &AuthState, request: &Request<Body>) -> Result<Princi
 
     let parsed = parse_auth_header(auth).ok_or("Malformed Authorization header")?;
     let (secret, principal) = state
-        .lookup(&parsed.access_key)
+        .lookup(&parsed.access_key, session_token(request).as_deref())
         .ok_or("Unknown access key")?;
 
     let date = request
@@ -526,7 +702,9 @@ fn validate_signature_v2(
     let (access_key, signature) = value
         .split_once(':')
         .ok_or("Malformed Authorization header")?;
-    let (secret, principal) = state.lookup(access_key).ok_or("Unknown access key")?;
+    let (secret, principal) = state
+        .lookup(access_key, session_token(request).as_deref())
+        .ok_or("Unknown access key")?;
 
     // Freshness: bound the replay window using the signed date header (the same
     // value that goes into the string-to-sign). Without this, a captured SigV2
@@ -569,7 +747,9 @@ fn validate_signature_v2_query(
     if Utc::now().timestamp() > expires_epoch {
         return Err("Presigned URL expired");
     }
-    let (secret, principal) = state.lookup(&access_key).ok_or("Unknown access key")?;
+    let (secret, principal) = state
+        .lookup(&access_key, session_token(request).as_deref())
+        .ok_or("Unknown access key")?;
     let string_to_sign = signature_v2_query_string_to_sign(request, &expires);
     let mut mac = HmacSha1::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
     mac.update(string_to_sign.as_bytes());
@@ -758,7 +938,9 @@ fn validate_presigned(state: &AuthState, request: &Request<Body>) -> Result<Prin
     }
     let credential_scope = format!("{date}/{region}/{service}/{terminator}");
 
-    let (secret, principal) = state.lookup(access_key).ok_or("Unknown access key")?;
+    let (secret, principal) = state
+        .lookup(access_key, session_token(request).as_deref())
+        .ok_or("Unknown access key")?;
 
     // ── Expiry check ──────────────────────────────────────────────────────
     let signed_at = NaiveDateTime::parse_from_str(date_time_str, "%Y%m%dT%H%M%SZ")
@@ -798,7 +980,7 @@ fn validate_presigned(state: &AuthState, request: &Request<Body>) -> Result<Prin
     let signed_headers: Vec<String> = signed_headers_str.split(';').map(str::to_string).collect();
     let host_style = request.extensions().get::<super::HostStyleRewrite>().is_some();
     let host_override: Option<HeaderMap> = if host_style { None } else {
-        state.config.auth.public_hostname.as_deref().and_then(|hostname| {
+        state.config.current().auth.public_hostname.as_deref().and_then(|hostname| {
             if signed_headers
                 .iter()
                 .any(|h| h.eq_ignore_ascii_case("host"))
@@ -1134,6 +1316,7 @@ fn access_denied() -> Response {
         .status(StatusCode::FORBIDDEN)
         .header("content-type", "application/xml")
         .header("x-amz-request-id", "rust-s3-server")
+        .extension(S3ErrorCode("AccessDenied".to_string()))
         .body(Body::from(body))
         .unwrap()
 }
@@ -1149,6 +1332,7 @@ fn deny(message: &'static str) -> Response {
         .status(StatusCode::FORBIDDEN)
         .header("content-type", "application/xml")
         .header("x-amz-request-id", "rust-s3-server")
+        .extension(S3ErrorCode("SignatureDoesNotMatch".to_string()))
         .body(Body::from(body))
         .unwrap()
 }
@@ -1353,7 +1537,10 @@ pub(crate) fn compute_auth_header(
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use crate::server::config::AppConfig;
 
     #[test]
     fn canonical_uri_decodes_then_reencodes_each_segment() {
@@ -1447,7 +1634,7 @@ mod tests {
             .header("authorization", format!("AWS AKID:{signature}"))
             .body(Body::empty())
             .unwrap();
-        let state = AuthState { config: Arc::new(config), iam: None };
+        let state = AuthState { config: LiveConfig::new(Arc::new(config)), iam: None };
         assert_eq!(validate_request(&state, &request), Ok(Principal::Root));
     }
 
@@ -1480,7 +1667,7 @@ mod tests {
             ))
             .body(Body::empty())
             .unwrap();
-        let state = AuthState { config: Arc::new(config), iam: None };
+        let state = AuthState { config: LiveConfig::new(Arc::new(config)), iam: None };
         assert_eq!(validate_request(&state, &request), Ok(Principal::Root));
     }
 
@@ -1488,7 +1675,7 @@ mod tests {
     fn minio_health_and_metrics_paths_bypass_auth() {
         let mut config = AppConfig::default();
         config.auth.enabled = true;
-        let state = AuthState { config: Arc::new(config), iam: None };
+        let state = AuthState { config: LiveConfig::new(Arc::new(config)), iam: None };
         for path in [
             "/minio/health/live",
             "/minio/health/ready",
@@ -1525,7 +1712,7 @@ mod tests {
             .header("authorization", "AWS4-HMAC-SHA256 Credential=AKID/1/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-date, Signature=abc123")
             .body(Body::empty())
             .unwrap();
-        let state = AuthState { config: Arc::new(config), iam: None };
+        let state = AuthState { config: LiveConfig::new(Arc::new(config)), iam: None };
         assert_eq!(
             validate_request(&state, &request),
             Err("Invalid x-amz-date header")
@@ -1596,7 +1783,7 @@ mod tests {
             secret_key: secret.to_string(),
         });
         AuthState {
-            config: Arc::new(config),
+            config: LiveConfig::new(Arc::new(config)),
             iam: None,
         }
     }
@@ -1610,13 +1797,13 @@ mod tests {
         let fields = signed_post_fields("AKID", "secret", bucket, "uploads/", &expiration);
 
         // Correctly signed and scoped: authorized.
-        assert!(authorize_browser_post(&state, &fields, bucket, key).is_ok());
+        assert!(authorize_browser_post(&state, &fields, bucket, key, false).is_ok());
 
         // Missing signature: rejected.
         let mut no_sig = fields.clone();
         no_sig.remove("x-amz-signature");
         assert_eq!(
-            authorize_browser_post(&state, &no_sig, bucket, key)
+            authorize_browser_post(&state, &no_sig, bucket, key, false)
                 .unwrap_err()
                 .status(),
             StatusCode::FORBIDDEN
@@ -1625,11 +1812,11 @@ mod tests {
         // Tampered signature: rejected.
         let mut bad_sig = fields.clone();
         bad_sig.insert("x-amz-signature".to_string(), "deadbeef".to_string());
-        assert!(authorize_browser_post(&state, &bad_sig, bucket, key).is_err());
+        assert!(authorize_browser_post(&state, &bad_sig, bucket, key, false).is_err());
 
         // Unknown access key: rejected.
         let wrong_key = signed_post_fields("NOPE", "secret", bucket, "uploads/", &expiration);
-        assert!(authorize_browser_post(&state, &wrong_key, bucket, key).is_err());
+        assert!(authorize_browser_post(&state, &wrong_key, bucket, key, false).is_err());
     }
 
     #[test]
@@ -1639,14 +1826,14 @@ mod tests {
         let fields = signed_post_fields("AKID", "secret", "b", "uploads/", &expiration);
 
         // Same (validly signed) form, different bucket than the policy allows.
-        assert!(authorize_browser_post(&state, &fields, "other", "uploads/x").is_err());
+        assert!(authorize_browser_post(&state, &fields, "other", "uploads/x", false).is_err());
         // Key outside the signed prefix.
-        assert!(authorize_browser_post(&state, &fields, "b", "secret/x").is_err());
+        assert!(authorize_browser_post(&state, &fields, "b", "secret/x", false).is_err());
 
         // Expired policy is rejected even with a valid signature.
         let past = (Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
         let expired = signed_post_fields("AKID", "secret", "b", "uploads/", &past);
-        assert!(authorize_browser_post(&state, &expired, "b", "uploads/x").is_err());
+        assert!(authorize_browser_post(&state, &expired, "b", "uploads/x", false).is_err());
     }
 
     #[test]
@@ -1757,4 +1944,46 @@ mod tests {
             Some("AKPRESIGNED")
         );
     }
+
+    #[tokio::test]
+    async fn temporary_keys_need_their_session_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        iam.create_user("alice", "password123").await.unwrap();
+        let creds = iam.issue_temporary_credentials("alice", 60_000).await.unwrap();
+        let mut config = AppConfig::default();
+        config.auth.enabled = true;
+        let state = AuthState { config: LiveConfig::new(Arc::new(config)), iam: Some(iam) };
+
+        let datetime = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let auth = compute_auth_header(
+            "GET",
+            "/",
+            "",
+            "localhost",
+            &creds.access_key,
+            &creds.secret_key,
+            "us-east-1",
+            &datetime,
+        );
+        let request = |token: Option<&str>| {
+            let mut builder = Request::builder()
+                .method("GET")
+                .uri("/")
+                .header("host", "localhost")
+                .header("x-amz-date", &datetime)
+                .header("x-amz-content-sha256", "UNSIGNED-PAYLOAD")
+                .header("authorization", &auth);
+            if let Some(token) = token {
+                builder = builder.header("x-amz-security-token", token);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        assert_eq!(
+            validate_request(&state, &request(Some(&creds.session_token))),
+            Ok(Principal::IamUser("alice".into()))
+        );
+        assert!(validate_request(&state, &request(None)).is_err());
+        assert!(validate_request(&state, &request(Some("forged"))).is_err());
+    }
 }
@@ -0,0 +1,101 @@
+//! `rusts3 backup` and `rusts3 backup-apply`: which data an incremental
+//! archive is cut from, and where it and its checkpoint are written. The
+//! archive itself — format, what goes in, replay — is `storage::backup`.
+
+use std::path::{Path, PathBuf};
+
+use super::config::AppConfig;
+use super::snapshot::snapshots_dir;
+use crate::storage::backup::{ApplySummary, BackupCheckpoint, BackupSummary};
+use crate::storage::snapshot::{delete_snapshot, snapshot_path};
+use crate::storage::store::LocalObjectStore;
+
+/// The checkpoint written next to the archive at `out`: `<out>.checkpoint`.
+pub fn checkpoint_path(out: &Path) -> PathBuf {
+    let mut name = out.as_os_str().to_owned();
+    name.push(".checkpoint");
+    PathBuf::from(name)
+}
+
+/// Archives a data directory no server is running on.
+pub async fn write_offline(
+    config: &AppConfig,
+    since: Option<&Path>,
+    out: &Path,
+) -> std::result::Result<(BackupCheckpoint, BackupSummary), Box<dyn std::error::Error>> {
+    let root = Path::new(&config.server.base_dir);
+    let _process_lock = super::acquire_process_lock(root)?;
+    let store = LocalObjectStore::from_storage_config(root, &config.storage);
+    write_archive(&store, since, out).await
+}
+
+/// Archives the completed snapshot `id`, then deletes it — the snapshot only
+/// exists to give a running server's data one consistent cut.
+pub async fn write_from_snapshot(
+    config: &AppConfig,
+    id: &str,
+    since: Option<&Path>,
+    out: &Path,
+) -> std::result::Result<(BackupCheckpoint, BackupSummary), Box<dyn std::error::Error>> {
+    let dir = snapshots_dir(config);
+    let result = async {
+        let root = snapshot_path(&dir, id).await?;
+        let store = LocalObjectStore::from_storage_config(root, &config.storage);
+        write_archive(&store, since, out).await
+    }
+    .await;
+    if let Err(err) = delete_snapshot(&dir, id).await {
+        log::warn!("backup could not remove snapshot {id}: {err}");
+    }
+    result
+}
+
+/// Writes the archive under a temporary name and renames it into place once
+/// complete, then writes the checkpoint beside it — so a checkpoint never
+/// refers to an archive that does not exist.
+async fn write_archive(
+    store: &LocalObjectStore,
+    since: Option<&Path>,
+    out: &Path,
+) -> std::result::Result<(BackupCheckpoint, BackupSummary), Box<dyn std::error::Error>> {
+    use tokio::io::AsyncWriteExt;
+
+    let since: Option<BackupCheckpoint> = match since {
+        Some(path) => Some(serde_json::from_slice(&tokio::fs::read(path).await?)?),
+        None => None,
+    };
+    let mut partial = out.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    let file = tokio::fs::File::create(&partial).await?;
+    let mut writer = tokio::io::BufWriter::new(file);
+    let written = store.write_backup(since.as_ref(), &mut writer).await;
+    let (checkpoint, summary) = match written {
+        Ok(written) => written,
+        Err(err) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(err.into());
+        }
+    };
+    writer.flush().await?;
+    writer.get_ref().sync_all().await?;
+    tokio::fs::rename(&partial, out).await?;
+    tokio::fs::write(
+        checkpoint_path(out),
+        serde_json::to_vec_pretty(&checkpoint)?,
+    )
+    .await?;
+    Ok((checkpoint, summary))
+}
+
+/// Replays the archive at `from` into the configured data directory. No
+/// server may be running on it.
+pub async fn apply(
+    config: &AppConfig,
+    from: &Path,
+) -> std::result::Result<(BackupCheckpoint, ApplySummary), Box<dyn std::error::Error>> {
+    let root = Path::new(&config.server.base_dir);
+    let _process_lock = super::acquire_process_lock(root)?;
+    let store = LocalObjectStore::from_storage_config(root, &config.storage);
+    Ok(store.apply_backup(from).await?)
+}
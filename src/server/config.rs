@@ -4,6 +4,8 @@
 //! Use [`AppConfig::from_file`] to load from disk, or [`AppConfig::default`]
 //! for in-process use (tests, embeddings).
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 pub const MIN_TRASH_RETENTION_SECS: u64 = 3 * 60 * 60;
@@ -19,6 +21,9 @@ pub struct ServerConfig {
     pub bind_port: u16,
     #[serde(default = "default_base_dir")]
     pub base_dir: String,
+    /// Serve the S3 API over HTTPS instead of plain HTTP.
+    #[serde(default)]
+    pub tls: TlsConfig,
 }
 
 /// Log rotation and output settings.
@@ -47,6 +52,10 @@ pub struct LoggingConfig {
     /// Compress archived log files with gzip.
     #[serde(default)]
     pub compress: bool,
+    /// How often buffered S3 server access log records (buckets with
+    /// `?logging` enabled) are written out to their target buckets.
+    #[serde(default = "default_access_log_flush_secs")]
+    pub access_log_flush_secs: u64,
 }
 
 /// A single access-key / secret-key credential pair.
@@ -77,6 +86,10 @@ pub struct BuiltinUser {
     pub password: Option<String>,
     #[serde(default)]
     pub api_keys: Vec<ApiKeyPair>,
+    /// Base32 TOTP secret. When set, a console login also asks for a code
+    /// from an authenticator holding this secret.
+    #[serde(default)]
+    pub totp_secret: Option<String>,
 }
 
 /// Scheme used when constructing externally reachable S3 URLs.
@@ -182,6 +195,11 @@ pub struct SweeperConfig {
     /// idles for this interval before checking again.
     #[serde(default = "default_reclaim_interval_secs")]
     pub reclaim_interval_secs: u64,
+    /// How long (seconds) each bucket's change log keeps an entry. An
+    /// incremental backup whose checkpoint is older than this falls back to
+    /// sending the bucket whole; `0` keeps the log forever.
+    #[serde(default = "default_change_log_retention_secs")]
+    pub change_log_retention_secs: u64,
 }
 
 impl SweeperConfig {
@@ -209,6 +227,7 @@ impl Default for SweeperConfig {
             multipart_upload_expiry_secs: default_multipart_upload_expiry_secs(),
             trash_expiry_secs: default_trash_expiry_secs(),
             reclaim_interval_secs: default_reclaim_interval_secs(),
+            change_log_retention_secs: default_change_log_retention_secs(),
         }
     }
 }
@@ -231,6 +250,27 @@ pub struct UiConfig {
     pub public_hostname: Option<String>,
     #[serde(default)]
     pub public_scheme: PublicScheme,
+    /// Serve the console over HTTPS instead of plain HTTP.
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// Minutes without a request after which a console session ends; `0`
+    /// keeps idle sessions until `session_max_hours`.
+    #[serde(default = "default_session_idle_minutes")]
+    pub session_idle_minutes: u64,
+    /// Hours after login at which a console session ends regardless.
+    #[serde(default = "default_session_max_hours")]
+    pub session_max_hours: u64,
+    /// Every console login needs a second factor. IAM users without one are
+    /// walked through enrollment at their next login.
+    #[serde(default)]
+    pub require_totp: bool,
+    /// How many access keys an IAM user may hold when creating their own on
+    /// the account page; `0` leaves key issuance to administrators.
+    #[serde(default = "default_max_access_keys_per_user")]
+    pub max_access_keys_per_user: usize,
+    /// Single sign-on through an OpenID Connect provider.
+    #[serde(default)]
+    pub oidc: OidcConfig,
 }
 
 impl Default for UiConfig {
@@ -241,6 +281,12 @@ impl Default for UiConfig {
             bind_port: default_ui_port(),
             public_hostname: None,
             public_scheme: PublicScheme::default(),
+            tls: TlsConfig::default(),
+            session_idle_minutes: default_session_idle_minutes(),
+            session_max_hours: default_session_max_hours(),
+            require_totp: false,
+            max_access_keys_per_user: default_max_access_keys_per_user(),
+            oidc: OidcConfig::default(),
         }
     }
 }
@@ -251,6 +297,164 @@ fn default_ui_enabled() -> bool {
 fn default_ui_port() -> u16 {
     8003
 }
+fn default_session_idle_minutes() -> u64 {
+    60
+}
+fn default_session_max_hours() -> u64 {
+    12
+}
+fn default_max_access_keys_per_user() -> usize {
+    2
+}
+
+/// Console sign-in through an OpenID Connect provider: the authorization-code
+/// flow with PKCE. The ID token's claims pick the IAM user and, through
+/// `group_map`, its groups; the IAM user is what is authorized, exactly as if
+/// it had signed in with a password.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OidcConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Issuer URL; `<issuer>/.well-known/openid-configuration` must answer.
+    /// HTTPS, except on a loopback address.
+    #[serde(default)]
+    pub issuer: String,
+    #[serde(default)]
+    pub client_id: String,
+    /// Absent for a public client, which PKCE alone protects.
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// Where the provider sends the browser back to. Defaults to
+    /// `/api/oidc/callback` on the console's public origin.
+    #[serde(default)]
+    pub redirect_url: Option<String>,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+    /// Claim holding the IAM user name. Its value must already be a valid
+    /// user name; nothing is rewritten.
+    #[serde(default = "default_oidc_username_claim")]
+    pub username_claim: String,
+    /// Claim holding the user's groups at the provider: a list of strings,
+    /// or one string.
+    #[serde(default = "default_oidc_groups_claim")]
+    pub groups_claim: String,
+    /// Provider group → IAM group. When non-empty, an SSO login replaces the
+    /// user's IAM groups with the mapped ones, so the provider stays the
+    /// source of truth; provider groups not listed here are ignored.
+    #[serde(default)]
+    pub group_map: BTreeMap<String, String>,
+    /// When non-empty, only members of one of these provider groups may
+    /// sign in.
+    #[serde(default)]
+    pub allowed_groups: Vec<String>,
+    /// Create the IAM user on first sign-in. Otherwise an administrator has
+    /// to create it first.
+    #[serde(default = "default_oidc_auto_provision")]
+    pub auto_provision: bool,
+    /// Text of the sign-in button.
+    #[serde(default = "default_oidc_button_label")]
+    pub button_label: String,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            issuer: String::new(),
+            client_id: String::new(),
+            client_secret: None,
+            redirect_url: None,
+            scopes: default_oidc_scopes(),
+            username_claim: default_oidc_username_claim(),
+            groups_claim: default_oidc_groups_claim(),
+            group_map: BTreeMap::new(),
+            allowed_groups: Vec::new(),
+            auto_provision: default_oidc_auto_provision(),
+            button_label: default_oidc_button_label(),
+        }
+    }
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    ["openid", "profile", "email"].map(String::from).to_vec()
+}
+fn default_oidc_username_claim() -> String {
+    "preferred_username".to_string()
+}
+fn default_oidc_groups_claim() -> String {
+    "groups".to_string()
+}
+fn default_oidc_auto_provision() -> bool {
+    true
+}
+fn default_oidc_button_label() -> String {
+    "Sign in with SSO".to_string()
+}
+
+/// HTTPS termination for one listener. `cert_path`/`key_path` are the PEM
+/// chain and private key served by default; `sni` adds certificates selected
+/// by the name the client asks for (`*.example.com` matches one label, which
+/// is what virtual-hosted bucket names need). The files are checked every
+/// `reload_secs` and swapped in without a restart once they change; a broken
+/// replacement is logged and the previous certificates stay in service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub cert_path: String,
+    #[serde(default)]
+    pub key_path: String,
+    #[serde(default)]
+    pub sni: Vec<SniCertConfig>,
+    /// PEM bundle of the CAs whose client certificates are accepted. Required
+    /// unless `client_auth` is `none`.
+    #[serde(default)]
+    pub client_ca_path: Option<String>,
+    #[serde(default)]
+    pub client_auth: ClientAuth,
+    #[serde(default = "default_tls_reload_secs")]
+    pub reload_secs: u64,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: String::new(),
+            key_path: String::new(),
+            sni: Vec::new(),
+            client_ca_path: None,
+            client_auth: ClientAuth::default(),
+            reload_secs: default_tls_reload_secs(),
+        }
+    }
+}
+
+/// An additional certificate served when the client's SNI matches
+/// `server_name`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SniCertConfig {
+    pub server_name: String,
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// Whether a TLS listener asks for client certificates (mutual TLS).
+/// `optional` verifies a certificate when one is presented but lets clients
+/// without one connect; `required` refuses the handshake without one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientAuth {
+    #[default]
+    None,
+    Optional,
+    Required,
+}
+
+fn default_tls_reload_secs() -> u64 {
+    30
+}
 
 /// Runtime-stats sampling. The sampler snapshots CPU / memory / disk-IO (from
 /// `/proc`) and S3 throughput every `sample_secs` into a small RocksDB, pruned
@@ -288,6 +492,249 @@ fn default_stats_retention_days() -> u64 {
     7
 }
 
+/// Structured audit trail. Every audit event and authorization decision is
+/// kept in `audit.rocksdb` for `retention_days`, queryable and exportable from
+/// the console. All optional, like `stats:`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default = "default_audit_enabled")]
+    pub enabled: bool,
+    /// How long events are kept before the retention prune removes them.
+    #[serde(default = "default_audit_retention_days")]
+    pub retention_days: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_audit_enabled(),
+            retention_days: default_audit_retention_days(),
+        }
+    }
+}
+
+fn default_audit_enabled() -> bool {
+    true
+}
+fn default_audit_retention_days() -> u64 {
+    90
+}
+
+/// Whole-server snapshots (`rusts3 snapshot`, the console Backup tab).
+/// `dir` should sit on the same filesystem as `base_dir` so blob files can be
+/// hardlinked rather than copied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Where snapshots are written. Defaults to `<base_dir>/snapshots`.
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Snapshots kept after a new one completes, oldest pruned first; 0 keeps
+    /// them all.
+    #[serde(default)]
+    pub retain: usize,
+}
+
+/// Admission control for uploads: how many staging writes may run at once,
+/// how many bytes one bucket may have in flight, and how much free space
+/// `base_dir` must keep. Writes over a cap wait in a queue until
+/// `queue_timeout_ms`, then get `503 SlowDown`. Hot-reloadable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdmissionConfig {
+    /// Concurrent PUT / UploadPart / CopyObject / browser POST uploads; 0
+    /// means unlimited.
+    #[serde(default = "default_max_concurrent_writes")]
+    pub max_concurrent_writes: usize,
+    /// Declared upload bytes one bucket may have in flight; 0 means
+    /// unlimited. A single larger upload is still admitted once the bucket
+    /// has nothing else in flight.
+    #[serde(default)]
+    pub max_inflight_bytes_per_bucket: u64,
+    /// Writes allowed to wait for a slot; beyond this they are refused at once.
+    #[serde(default = "default_max_queued_writes")]
+    pub max_queued_writes: usize,
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+    /// New uploads are refused when they would leave less than this free
+    /// under `base_dir`; 0 disables the byte floor.
+    #[serde(default = "default_min_free_bytes")]
+    pub min_free_bytes: u64,
+    /// The same floor as a percentage of the filesystem; the larger of the
+    /// two applies.
+    #[serde(default)]
+    pub min_free_percent: f64,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_writes: default_max_concurrent_writes(),
+            max_inflight_bytes_per_bucket: 0,
+            max_queued_writes: default_max_queued_writes(),
+            queue_timeout_ms: default_queue_timeout_ms(),
+            min_free_bytes: default_min_free_bytes(),
+            min_free_percent: 0.0,
+        }
+    }
+}
+
+fn default_max_concurrent_writes() -> usize {
+    64
+}
+fn default_max_queued_writes() -> usize {
+    256
+}
+fn default_queue_timeout_ms() -> u64 {
+    10_000
+}
+fn default_min_free_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+
+/// Token-bucket limits on the S3 API, enforced per caller. Off unless
+/// `enabled`; rules take effect on reload.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<RateLimitRule>,
+}
+
+/// What a rate-limit rule keys its buckets on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitScope {
+    AccessKey,
+    User,
+    Group,
+    Ip,
+}
+
+/// One rate-limit rule. `subject: "*"` gives every distinct access key, user,
+/// group or address its own buckets; any other subject names one of them (or
+/// a CIDR range for `ip`) and everything it matches shares one set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitRule {
+    pub scope: RateLimitScope,
+    #[serde(default = "default_rate_limit_subject")]
+    pub subject: String,
+    #[serde(default)]
+    pub requests_per_sec: Option<f64>,
+    /// Upload bandwidth (PUT/POST bodies).
+    #[serde(default)]
+    pub bytes_in_per_sec: Option<u64>,
+    /// Download bandwidth (response bodies).
+    #[serde(default)]
+    pub bytes_out_per_sec: Option<u64>,
+    /// Bucket capacity, in seconds of the rate.
+    #[serde(default = "default_rate_limit_burst_secs")]
+    pub burst_secs: f64,
+}
+
+fn default_rate_limit_subject() -> String {
+    "*".to_string()
+}
+fn default_rate_limit_burst_secs() -> f64 {
+    1.0
+}
+
+/// An LDAP directory that authenticates console logins and S3 STS requests
+/// (`AssumeRoleWithLDAPIdentity`). A directory user signs in as the IAM user
+/// of the same name, created on first login when `auto_provision` is on;
+/// `group_map` turns directory groups into that user's IAM groups, and with
+/// them the group policies.
+///
+/// The user is found by searching `user_base_dn` with `user_filter` as the
+/// service account (`bind_dn`), then proven by binding as the entry found
+/// with the password given.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LdapConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `ldaps://host:636`, or `ldap://host:389` (with `start_tls`, or on a
+    /// trusted network only: simple binds send the password as given).
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub start_tls: bool,
+    /// Service account for searches. Absent for an anonymous bind.
+    #[serde(default)]
+    pub bind_dn: Option<String>,
+    #[serde(default)]
+    pub bind_password: Option<String>,
+    #[serde(default)]
+    pub user_base_dn: String,
+    /// `{username}` is replaced by the escaped login name. Must match
+    /// exactly one entry.
+    #[serde(default = "default_ldap_user_filter")]
+    pub user_filter: String,
+    /// Where groups are searched; absent to skip the group lookup.
+    #[serde(default)]
+    pub group_base_dn: Option<String>,
+    /// `{dn}` is replaced by the user's escaped DN and `{username}` by the
+    /// escaped login name.
+    #[serde(default = "default_ldap_group_filter")]
+    pub group_filter: String,
+    /// Attribute of a group entry holding the name `group_map` matches on.
+    #[serde(default = "default_ldap_group_name_attribute")]
+    pub group_name_attribute: String,
+    /// Directory group → IAM group. When non-empty, every LDAP login sets
+    /// the user's IAM groups to the mapped ones.
+    #[serde(default)]
+    pub group_map: BTreeMap<String, String>,
+    /// Create the IAM user on first login. Otherwise an administrator has
+    /// to create it first.
+    #[serde(default = "default_ldap_auto_provision")]
+    pub auto_provision: bool,
+    /// Connect and per-operation timeout.
+    #[serde(default = "default_ldap_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Longest lifetime an STS request may ask for; requests without
+    /// `DurationSeconds` get an hour, or this if it is shorter.
+    #[serde(default = "default_ldap_sts_max_duration_secs")]
+    pub sts_max_duration_secs: u64,
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            start_tls: false,
+            bind_dn: None,
+            bind_password: None,
+            user_base_dn: String::new(),
+            user_filter: default_ldap_user_filter(),
+            group_base_dn: None,
+            group_filter: default_ldap_group_filter(),
+            group_name_attribute: default_ldap_group_name_attribute(),
+            group_map: BTreeMap::new(),
+            auto_provision: default_ldap_auto_provision(),
+            timeout_secs: default_ldap_timeout_secs(),
+            sts_max_duration_secs: default_ldap_sts_max_duration_secs(),
+        }
+    }
+}
+
+fn default_ldap_user_filter() -> String {
+    "(uid={username})".to_string()
+}
+fn default_ldap_group_filter() -> String {
+    "(|(member={dn})(uniqueMember={dn}))".to_string()
+}
+fn default_ldap_group_name_attribute() -> String {
+    "cn".to_string()
+}
+fn default_ldap_auto_provision() -> bool {
+    true
+}
+fn default_ldap_timeout_secs() -> u64 {
+    10
+}
+fn default_ldap_sts_max_duration_secs() -> u64 {
+    12 * 60 * 60
+}
+
 /// Root configuration object, deserialised from `config.yaml`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -305,6 +752,16 @@ pub struct AppConfig {
     pub ui: UiConfig,
     #[serde(default)]
     pub stats: StatsConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+    #[serde(default)]
+    pub admission: AdmissionConfig,
+    #[serde(default)]
+    pub snapshots: SnapshotConfig,
+    #[serde(default)]
+    pub ldap: LdapConfig,
 }
 
 /// Deserializes a list of strings, dropping entries that were never filled in.
@@ -398,12 +855,27 @@ impl AppConfig {
         blank_to_none(&mut self.auth.public_hostname);
         blank_to_none(&mut self.ui.bind_address);
         blank_to_none(&mut self.ui.public_hostname);
+        blank_to_none(&mut self.server.tls.client_ca_path);
+        blank_to_none(&mut self.ui.tls.client_ca_path);
+        blank_to_none(&mut self.ui.oidc.client_secret);
+        blank_to_none(&mut self.ui.oidc.redirect_url);
+        blank_to_none(&mut self.ldap.bind_dn);
+        blank_to_none(&mut self.ldap.bind_password);
+        blank_to_none(&mut self.ldap.group_base_dn);
+        for tls in [&mut self.server.tls, &mut self.ui.tls] {
+            let before = tls.sni.len();
+            tls.sni.retain(|cert| !blank(&cert.server_name));
+            for _ in tls.sni.len()..before {
+                dropped.push("an unfilled tls.sni entry".to_string());
+            }
+        }
 
         for user in &mut self.auth.users {
             // An empty password is not "no password" to the verifier — it is a
             // password that the empty string matches. Treat it as absent, which
             // means "this user cannot log into the console".
             blank_to_none(&mut user.password);
+            blank_to_none(&mut user.totp_secret);
             let before = user.api_keys.len();
             user.api_keys
                 .retain(|key| !blank(&key.ak) && !blank(&key.secret));
@@ -466,6 +938,107 @@ impl AppConfig {
                 );
             }
         }
+        for (listener, tls) in [("server", &self.server.tls), ("ui", &self.ui.tls)] {
+            if !tls.enabled {
+                continue;
+            }
+            if tls.cert_path.trim().is_empty() || tls.key_path.trim().is_empty() {
+                return Err(format!(
+                    "{listener}.tls.cert_path and {listener}.tls.key_path are required when TLS is enabled"
+                ));
+            }
+            for cert in &tls.sni {
+                if cert.cert_path.trim().is_empty() || cert.key_path.trim().is_empty() {
+                    return Err(format!(
+                        "{listener}.tls.sni entry {:?} needs cert_path and key_path",
+                        cert.server_name
+                    ));
+                }
+            }
+            if tls.client_auth != ClientAuth::None && tls.client_ca_path.is_none() {
+                return Err(format!(
+                    "{listener}.tls.client_ca_path is required when client_auth is not none"
+                ));
+            }
+        }
+        if self.ui.session_max_hours == 0 {
+            return Err("ui.session_max_hours must be at least 1".to_string());
+        }
+        if self.ui.oidc.enabled {
+            let oidc = &self.ui.oidc;
+            if !super::oidc::is_acceptable_issuer(&oidc.issuer) {
+                return Err(
+                    "ui.oidc.issuer must be an https URL (http only on a loopback address)"
+                        .to_string(),
+                );
+            }
+            if oidc.client_id.trim().is_empty() {
+                return Err("ui.oidc.client_id is required when OIDC is enabled".to_string());
+            }
+            if !oidc.scopes.iter().any(|scope| scope == "openid") {
+                return Err("ui.oidc.scopes must include openid".to_string());
+            }
+            if oidc.username_claim.trim().is_empty() {
+                return Err("ui.oidc.username_claim must name a claim".to_string());
+            }
+        }
+        if self.ldap.enabled {
+            let ldap = &self.ldap;
+            if !(ldap.url.starts_with("ldap://") || ldap.url.starts_with("ldaps://")) {
+                return Err("ldap.url must be an ldap:// or ldaps:// URL".to_string());
+            }
+            if ldap.start_tls && ldap.url.starts_with("ldaps://") {
+                return Err("ldap.start_tls applies to ldap:// URLs, not ldaps://".to_string());
+            }
+            if ldap.bind_password.is_some() && ldap.bind_dn.is_none() {
+                return Err("ldap.bind_password is set without ldap.bind_dn".to_string());
+            }
+            if ldap.user_base_dn.trim().is_empty() {
+                return Err("ldap.user_base_dn is required when LDAP is enabled".to_string());
+            }
+            if !ldap.user_filter.contains("{username}") {
+                return Err("ldap.user_filter must contain {username}".to_string());
+            }
+            if ldap.timeout_secs == 0 {
+                return Err("ldap.timeout_secs must be at least 1".to_string());
+            }
+            if ldap.sts_max_duration_secs < 900 {
+                return Err("ldap.sts_max_duration_secs must be at least 900".to_string());
+            }
+        }
+        if !(0.0..100.0).contains(&self.admission.min_free_percent) {
+            return Err("admission.min_free_percent must be at least 0 and below 100".to_string());
+        }
+        for root in &self.storage.data_roots {
+            if !std::path::Path::new(root).is_absolute() {
+                return Err(format!("storage.data_roots entry {root:?} must be an absolute path"));
+            }
+        }
+        for (n, rule) in self.rate_limits.rules.iter().enumerate() {
+            let rates = [
+                rule.requests_per_sec,
+                rule.bytes_in_per_sec.map(|v| v as f64),
+                rule.bytes_out_per_sec.map(|v| v as f64),
+            ];
+            if rates.iter().all(Option::is_none) {
+                return Err(format!("rate_limits.rules[{n}] sets no limit"));
+            }
+            if rates.iter().flatten().any(|rate| !(*rate > 0.0 && rate.is_finite())) {
+                return Err(format!("rate_limits.rules[{n}] limits must be positive"));
+            }
+            if !(rule.burst_secs > 0.0 && rule.burst_secs.is_finite()) {
+                return Err(format!("rate_limits.rules[{n}].burst_secs must be positive"));
+            }
+            if rule.scope == RateLimitScope::Ip
+                && rule.subject != "*"
+                && super::ratelimit::IpRange::parse(&rule.subject).is_none()
+            {
+                return Err(format!(
+                    "rate_limits.rules[{n}].subject {:?} is not an address or CIDR range",
+                    rule.subject
+                ));
+            }
+        }
         if self.sweeper.trash_expiry_secs < MIN_TRASH_RETENTION_SECS {
             return Err(format!(
                 "sweeper.trash_expiry_secs must be at least {MIN_TRASH_RETENTION_SECS} seconds (3 hours)"
@@ -486,6 +1059,24 @@ impl AppConfig {
                     return Err(format!("invalid bcrypt password for user {}", user.user));
                 }
             }
+            if let Some(secret) = user.totp_secret.as_deref() {
+                let valid = super::totp::base32_decode(secret)
+                    .is_some_and(|bytes| bytes.len() >= super::totp::MIN_SECRET_BYTES);
+                if !valid {
+                    return Err(format!(
+                        "totp_secret of user {} must be base32 of at least {} bytes",
+                        user.user,
+                        super::totp::MIN_SECRET_BYTES
+                    ));
+                }
+            } else if self.ui.require_totp && user.password.is_some() {
+                // Built-in users cannot enroll from the console, so this
+                // would lock them out.
+                return Err(format!(
+                    "ui.require_totp is on but built-in user {} has no totp_secret",
+                    user.user
+                ));
+            }
             // api_keys are optional: a built-in user may be console-only.
             for key in &user.api_keys {
                 if key.ak.is_empty() || key.secret.is_empty() {
@@ -546,6 +1137,11 @@ impl Default for AppConfig {
             sweeper: SweeperConfig::default(),
             ui: UiConfig::default(),
             stats: StatsConfig::default(),
+            audit: AuditConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            admission: AdmissionConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            ldap: LdapConfig::default(),
         }
     }
 }
@@ -556,6 +1152,7 @@ impl Default for ServerConfig {
             bind_address: default_bind_address(),
             bind_port: default_bind_port(),
             base_dir: default_base_dir(),
+            tls: TlsConfig::default(),
         }
     }
 }
@@ -570,6 +1167,7 @@ impl Default for LoggingConfig {
             rotation_size_mb: default_rotation_size_mb(),
             keep_files: default_keep_files(),
             compress: false,
+            access_log_flush_secs: default_access_log_flush_secs(),
         }
     }
 }
@@ -595,6 +1193,9 @@ fn default_rotation_size_mb() -> u64 {
 fn default_keep_files() -> u32 {
     5
 }
+fn default_access_log_flush_secs() -> u64 {
+    300
+}
 fn default_sweep_interval_secs() -> u64 {
     300
 }
@@ -620,6 +1221,10 @@ fn default_trash_expiry_secs() -> u64 {
     24 * 60 * 60
 }
 
+fn default_change_log_retention_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -773,6 +1378,61 @@ mod tests {
         assert!(config.logging.enable_bandwidth_report);
     }
 
+    #[test]
+    fn access_log_flush_interval_defaults_to_five_minutes() {
+        assert_eq!(AppConfig::default().logging.access_log_flush_secs, 300);
+        let config: AppConfig =
+            serde_yaml::from_str("logging:\n  access_log_flush_secs: 30\n").unwrap();
+        assert_eq!(config.logging.access_log_flush_secs, 30);
+    }
+
+    #[test]
+    fn audit_trail_defaults_to_enabled_with_ninety_day_retention() {
+        let config: AppConfig = serde_yaml::from_str("audit: {}\n").unwrap();
+        assert!(config.audit.enabled);
+        assert_eq!(config.audit.retention_days, 90);
+        let config: AppConfig =
+            serde_yaml::from_str("audit:\n  enabled: false\n  retention_days: 7\n").unwrap();
+        assert!(!config.audit.enabled);
+        assert_eq!(config.audit.retention_days, 7);
+    }
+
+    #[test]
+    fn snapshots_default_under_base_dir_and_keep_everything() {
+        let config: AppConfig =
+            serde_yaml::from_str("server:\n  base_dir: /srv/s3\nsnapshots: {}\n").unwrap();
+        assert_eq!(config.snapshots.retain, 0);
+        assert_eq!(
+            crate::server::snapshot::snapshots_dir(&config),
+            std::path::Path::new("/srv/s3/snapshots")
+        );
+        // A container config leaves an unset dir as the empty string.
+        let config: AppConfig =
+            serde_yaml::from_str("snapshots:\n  dir: \"\"\n  retain: 3\n").unwrap();
+        assert_eq!(config.snapshots.retain, 3);
+        assert!(crate::server::snapshot::snapshots_dir(&config).ends_with("snapshots"));
+    }
+
+    #[test]
+    fn tls_requires_key_material_and_a_client_ca_for_mtls() {
+        let config: AppConfig = serde_yaml::from_str("server:\n  tls: {}\n").unwrap();
+        assert!(!config.server.tls.enabled);
+        assert_eq!(config.server.tls.reload_secs, 30);
+        config.validate().unwrap();
+
+        let missing_key: AppConfig = serde_yaml::from_str(
+            "server:\n  tls:\n    enabled: true\n    cert_path: /c.pem\n",
+        )
+        .unwrap();
+        assert!(missing_key.validate().unwrap_err().contains("server.tls"));
+
+        let mtls_without_ca: AppConfig = serde_yaml::from_str(
+            "ui:\n  tls:\n    enabled: true\n    cert_path: /c.pem\n    key_path: /k.pem\n    client_auth: required\n",
+        )
+        .unwrap();
+        assert!(mtls_without_ca.validate().unwrap_err().contains("ui.tls.client_ca_path"));
+    }
+
     #[test]
     fn trash_retention_defaults_to_one_day() {
         assert_eq!(AppConfig::default().sweeper.trash_expiry_secs, 86_400);
@@ -812,6 +1472,124 @@ mod tests {
         assert_eq!(config.storage.meta_cache_capacity, 200_000);
     }
 
+    #[test]
+    fn session_lifetime_must_be_positive() {
+        let config: AppConfig = serde_yaml::from_str("ui:\n  session_max_hours: 0\n").unwrap();
+        assert_eq!(config.validate().unwrap_err(), "ui.session_max_hours must be at least 1");
+        let config: AppConfig = serde_yaml::from_str("ui:\n  session_idle_minutes: 0\n").unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.ui.session_max_hours, 12);
+    }
+
+    #[test]
+    fn self_service_key_limit_defaults_to_two() {
+        let config: AppConfig = serde_yaml::from_str("ui:\n  enabled: true\n").unwrap();
+        assert_eq!(config.ui.max_access_keys_per_user, 2);
+        assert_eq!(AppConfig::default().ui.max_access_keys_per_user, 2);
+        let config: AppConfig =
+            serde_yaml::from_str("ui:\n  max_access_keys_per_user: 0\n").unwrap();
+        assert_eq!(config.ui.max_access_keys_per_user, 0);
+    }
+
+    #[test]
+    fn builtin_totp_secrets_are_validated() {
+        let load = |secret: &str, require: bool| {
+            let mut config: AppConfig = serde_yaml::from_str(&format!(
+                "ui:\n  require_totp: {require}\nauth:\n  users:\n    - user: admin\n      \
+                 password: secret\n      totp_secret: \"{secret}\"\n"
+            ))
+            .unwrap();
+            config.normalize();
+            config
+        };
+        let config = load("JBSW Y3DP EHPK 3PXP", true);
+        assert_eq!(config.auth.users[0].totp_secret.as_deref(), Some("JBSW Y3DP EHPK 3PXP"));
+        assert!(config.validate().is_ok());
+        assert!(load("JBSWY3DP", false).validate().unwrap_err().contains("at least 10 bytes"));
+        assert!(load("not-base32!", false).validate().is_err());
+
+        // An unfilled template slot means "no secret", which `require_totp`
+        // refuses for a user who can log in.
+        let unfilled = load("", true);
+        assert_eq!(unfilled.auth.users[0].totp_secret, None);
+        assert_eq!(
+            unfilled.validate().unwrap_err(),
+            "ui.require_totp is on but built-in user admin has no totp_secret"
+        );
+        assert!(!AppConfig::default().ui.require_totp);
+    }
+
+    #[test]
+    fn oidc_needs_a_usable_issuer_and_client() {
+        let load = |yaml: &str| {
+            let mut config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+            config.normalize();
+            config
+        };
+        let config = load(
+            "ui:\n  oidc:\n    enabled: true\n    issuer: https://id.example.com/realms/corp\n    \
+             client_id: rusts3\n    client_secret: \"\"\n    group_map: {s3-admins: admin}\n",
+        );
+        assert!(config.validate().is_ok());
+        let oidc = &config.ui.oidc;
+        assert_eq!(oidc.client_secret, None, "an unfilled secret means a public client");
+        assert_eq!(oidc.username_claim, "preferred_username");
+        assert_eq!(oidc.group_map["s3-admins"], "admin");
+        assert!(oidc.auto_provision);
+
+        let plain = load("ui:\n  oidc:\n    enabled: true\n    issuer: http://id.example.com\n    \
+                          client_id: rusts3\n");
+        assert!(plain.validate().unwrap_err().contains("https"));
+        let local = load("ui:\n  oidc:\n    enabled: true\n    issuer: http://127.0.0.1:9000\n    \
+                          client_id: rusts3\n");
+        assert!(local.validate().is_ok());
+        let no_client =
+            load("ui:\n  oidc:\n    enabled: true\n    issuer: https://id.example.com\n");
+        assert!(no_client.validate().unwrap_err().contains("client_id"));
+        // Disabled, nothing is checked.
+        assert!(load("ui:\n  oidc:\n    issuer: nonsense\n").validate().is_ok());
+    }
+
+    #[test]
+    fn ldap_settings_are_validated() {
+        let load = |yaml: &str| {
+            let mut config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+            config.normalize();
+            config
+        };
+        let base = "ldap:\n  enabled: true\n  url: ldaps://ldap.example.com\n  \
+                    user_base_dn: ou=people,dc=example,dc=com\n";
+        let config = load(&format!("{base}  bind_dn: \"\"\n  group_base_dn: \"\"\n"));
+        assert!(config.validate().is_ok());
+        assert_eq!(config.ldap.bind_dn, None);
+        assert_eq!(config.ldap.group_base_dn, None);
+        assert_eq!(config.ldap.user_filter, "(uid={username})");
+        assert_eq!(config.ldap.sts_max_duration_secs, 43_200);
+
+        let err = |extra: &str| load(&format!("{base}{extra}")).validate().unwrap_err();
+        assert!(err("  start_tls: true\n").contains("start_tls"));
+        assert!(err("  bind_password: pw\n").contains("bind_dn"));
+        assert!(err("  user_filter: (uid=alice)\n").contains("{username}"));
+        assert!(err("  sts_max_duration_secs: 60\n").contains("900"));
+        let http = load("ldap:\n  enabled: true\n  url: http://x\n  user_base_dn: dc=x\n");
+        assert!(http.validate().unwrap_err().contains("ldap.url"));
+        // Disabled, nothing is checked.
+        assert!(load("ldap:\n  url: nonsense\n").validate().is_ok());
+    }
+
+    #[test]
+    fn data_roots_must_be_absolute() {
+        let config: AppConfig =
+            serde_yaml::from_str("storage:\n  data_roots: [/mnt/disk2, disk3]\n").unwrap();
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "storage.data_roots entry \"disk3\" must be an absolute path"
+        );
+        let config: AppConfig =
+            serde_yaml::from_str("storage:\n  data_roots: [/mnt/disk2]\n").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn default_config_round_trips_through_yaml() {
         // `rusts3 init` writes exactly this; it must parse and validate.
@@ -892,6 +1670,10 @@ mod tests {
         assert_eq!(config.server.bind_port, 8002);
         assert_eq!(config.ui.bind_port, 8003);
         assert_eq!(config.ui.public_scheme, PublicScheme::Http);
+        assert!(config.storage.data_roots.is_empty());
+        // LDAP filters carry their own braces through the expander.
+        assert_eq!(config.ldap.user_filter, LdapConfig::default().user_filter);
+        assert_eq!(config.ldap.group_filter, LdapConfig::default().group_filter);
     }
 
     /// Placeholder expansion is textual and runs *before* the YAML parser (see
@@ -5,22 +5,27 @@
 //! any transport — the registry just says "tasks changed," and whoever cares
 //! listens. Backed by a `tokio::sync::broadcast` channel, so every subscriber
 //! (every open console session) receives every event.
+//!
+//! Broadcast drops what a slow subscriber lags past, which is fine for a
+//! console that refreshes from a snapshot but not for the audit trail. Audit
+//! events therefore also go down a dedicated bounded feed (see
+//! [`EventHub::audit_feed`]) whose publisher waits for room instead.
 
-use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, OnceLock};
 
 use tokio::sync::broadcast;
 
 /// Something worth notifying live consumers about. The console WebSocket only
-/// reacts to [`Event::TasksChanged`]; [`Event::Audit`] rides the same bus so a
-/// future external subscriber (webhooks/Kafka) can consume the full event
-/// stream from one place.
+/// reacts to [`Event::TasksChanged`]; [`Event::Audit`] rides the same bus and
+/// is persisted by the audit recorder (see [`audit_store`](super::audit_store)).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     /// The set of in-flight tasks changed (one started or finished).
     TasksChanged,
-    /// A security-relevant verb ran (or was denied). Emitted by the console
-    /// pipeline and IAM management actions so every meaningful action is
-    /// observable off one bus.
+    /// A security-relevant verb ran (or was denied). Emitted by S3 requests,
+    /// the console pipeline and IAM management actions so every meaningful
+    /// action is observable off one bus.
     Audit {
         /// Who performed the action (username).
         actor: String,
@@ -35,26 +40,47 @@ pub enum Event {
     },
 }
 
-/// Fan-out event bus. Cheap to clone (it's just the sender handle).
+/// Fan-out event bus. Cheap to clone (it's just the sender handles).
 #[derive(Clone)]
 pub struct EventHub {
     tx: broadcast::Sender<Event>,
+    audit: OnceLock<SyncSender<Event>>,
 }
 
 impl EventHub {
     pub fn new() -> Arc<Self> {
-        // A small buffer; a lagging subscriber just gets `Lagged` and refreshes
-        // from a full snapshot, so no event is ever truly "missed" in effect.
-        let (tx, _) = broadcast::channel(64);
-        Arc::new(Self { tx })
+        // A lagging console subscriber just gets `Lagged` and refreshes from a
+        // full snapshot. Every S3 request publishes an audit event, so leave
+        // room for bursts to spare consoles needless refreshes; the audit
+        // recorder reads its own feed and never depends on this.
+        let (tx, _) = broadcast::channel(1024);
+        Arc::new(Self {
+            tx,
+            audit: OnceLock::new(),
+        })
     }
 
     /// Publishes an event to all current subscribers. A no-op if nobody is
-    /// listening.
+    /// listening. An [`Event::Audit`] is first queued on the audit feed, if
+    /// one is open; when that feed is full this blocks until the recorder
+    /// catches up rather than lose the event.
     pub fn publish(&self, event: Event) {
+        if let (Event::Audit { .. }, Some(audit)) = (&event, self.audit.get()) {
+            // A closed feed means the recorder has stopped (shutdown).
+            let _ = audit.send(event.clone());
+        }
         let _ = self.tx.send(event);
     }
 
+    /// Opens the audit feed: every [`Event::Audit`] published from now on is
+    /// delivered to the returned receiver, which holds at most `capacity`
+    /// unread events before publishers wait. There is one feed per hub;
+    /// `None` if it is already open.
+    pub fn audit_feed(&self, capacity: usize) -> Option<Receiver<Event>> {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        self.audit.set(tx).ok().map(|_| rx)
+    }
+
     pub fn subscribe(&self) -> broadcast::Receiver<Event> {
         self.tx.subscribe()
     }
@@ -89,7 +89,7 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: BucketCtx, body: Body)
 
     // Authorize before touching storage.
     let actor = if let Some(state) = &ctx.auth_state {
-        match authorize_browser_post(state, &form.fields, &bucket, &key) {
+        match authorize_browser_post(state, &form.fields, &bucket, &key, ctx.secure_transport) {
             Ok(actor) => actor,
             Err(resp) => return resp,
         }
@@ -118,6 +118,11 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: BucketCtx, body: Body)
         .get("Content-Language")
         .or_else(|| form.fields.get("content-language"))
         .map(String::as_str);
+    let cache_control = form
+        .fields
+        .get("Cache-Control")
+        .or_else(|| form.fields.get("cache-control"))
+        .map(String::as_str);
     let input = match tokio::fs::File::open(file.temp.path()).await {
         Ok(file) => file,
         Err(err) => return srv::storage_error_response(err.into(), &format!("/{bucket}/{key}")),
@@ -131,6 +136,7 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: BucketCtx, body: Body)
             None,
             storage_class,
             content_language,
+            cache_control,
             &user_meta,
             None,
         )
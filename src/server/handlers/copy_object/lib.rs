@@ -65,6 +65,12 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
     } else {
         None
     };
+    // REPLACE drops a Cache-Control the request does not repeat, as on S3.
+    let replacement_cache_control = replace_metadata.then(|| {
+        ctx.headers
+            .get(header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+    });
     let src_object = match store.read_object(&src_bucket, &src_key).await {
         Ok(object) => object,
         Err(err) => return srv::storage_error_response(err, &format!("/{src_bucket}/{src_key}")),
@@ -92,6 +98,8 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
             user_meta.as_ref(),
             replacement_content_type,
             replacement_content_language,
+            replacement_cache_control,
+            None,
         )
         .await
     {
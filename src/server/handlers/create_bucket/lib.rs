@@ -26,6 +26,7 @@ mod tests {
             identity: None,
             auth_state: None,
             tasks: None,
+            secure_transport: false,
             bucket: bucket.into(),
             query: std::collections::HashMap::new(),
             headers: axum::http::HeaderMap::new(),
@@ -20,6 +20,10 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
         .headers
         .get(header::CONTENT_LANGUAGE)
         .and_then(|v| v.to_str().ok());
+    let cache_control = ctx
+        .headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok());
     let storage_class = srv::storage_class_header(&ctx.headers);
     if let Some(resp) = srv::reject_invalid_storage_class(storage_class, &resource) {
         return resp;
@@ -38,6 +42,7 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
             content_encoding.as_deref(),
             storage_class,
             content_language,
+            cache_control,
             &user_meta,
         )
         .await
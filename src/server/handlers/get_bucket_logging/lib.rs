@@ -0,0 +1,20 @@
+//! `GET /{bucket}?logging` — return the bucket's server access logging status.
+
+use axum::body::Body;
+use axum::http::StatusCode;
+use axum::response::Response;
+
+use crate::server as srv;
+use crate::server::handlers::BucketCtx;
+use crate::server::xml::bucket_logging_status_xml;
+use crate::storage::store::LocalObjectStore;
+
+pub(crate) async fn handle(store: LocalObjectStore, ctx: BucketCtx, _body: Body) -> Response {
+    match store.bucket_meta(&ctx.bucket).await {
+        Ok(meta) => srv::xml_response(
+            StatusCode::OK,
+            bucket_logging_status_xml(meta.logging.as_ref()),
+        ),
+        Err(err) => srv::storage_error_response(err, &ctx.resource()),
+    }
+}
@@ -3,7 +3,6 @@
 //! files. All read-specific logic lives here; only cross-cutting helpers
 //! (error mapping, empty responses) come from the `server` library.
 
-use std::io::SeekFrom;
 use std::path::Path as FsPath;
 
 use axum::body::Body;
@@ -13,8 +12,9 @@ use axum::response::Response;
 use crate::server as srv;
 use crate::server::handlers::ObjectCtx;
 use crate::server::range::{parse_range_header, RangeSelection};
+use crate::storage::compression::{open_part_range, PartReader};
 use crate::storage::errors::StorageError;
-use crate::storage::metadata::{quote_etag, ObjectMeta};
+use crate::storage::metadata::{quote_etag, ObjectMeta, PartMeta};
 use crate::storage::store::LocalObjectStore;
 use crate::storage::time::{http_date_ms, parse_http_date_ms};
 
@@ -147,6 +147,10 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
         .unwrap_or_else(|| object.meta.content_type.clone());
     let content_encoding = object.meta.content_encoding.clone();
     let content_language = object.meta.content_language.clone();
+    let cache_control = query
+        .get("response-cache-control")
+        .cloned()
+        .or_else(|| object.meta.cache_control.clone());
     let storage_class = object.meta.storage_class.clone();
     let user_meta = object.meta.user_meta.clone();
     let last_modified = http_date_ms(object.meta.last_modified_ms);
@@ -169,8 +173,8 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
     if let Some(value) = query.get("response-content-language") {
         builder = builder.header(header::CONTENT_LANGUAGE, value);
     }
-    if let Some(value) = query.get("response-cache-control") {
-        builder = builder.header(header::CACHE_CONTROL, value);
+    if let Some(cache_control) = cache_control {
+        builder = builder.header(header::CACHE_CONTROL, cache_control);
     }
     if let Some(value) = query.get("response-content-disposition") {
         builder = builder.header(header::CONTENT_DISPOSITION, value);
@@ -192,6 +196,10 @@ pub(crate) async fn handle(store: LocalObjectStore, ctx: ObjectCtx, _body: Body)
 
     let body = if method == Method::HEAD {
         Body::empty()
+    } else if let Some(data) = &object.inline {
+        // Inline objects arrive with their bytes; the range is a slice.
+        let start = range_start as usize;
+        Body::from(data.slice(start..start + range_len as usize))
     } else {
         match stream_object_range(
             &object.meta,
@@ -252,8 +260,9 @@ struct PartReadSegment {
 
 /// Streams `range_len` bytes starting at `range_start` from the object's part
 /// files without loading the full content into memory. Single-part objects are
-/// seeked and wrapped directly; multi-part objects are piped through an
-/// in-process duplex channel into one contiguous stream.
+/// seeked (or, when compressed, decoded from the enclosing frame) and wrapped
+/// directly; multi-part objects are piped through an in-process duplex channel
+/// into one contiguous stream.
 async fn stream_object_range(
     meta: &ObjectMeta,
     part_offsets: &[u64],
@@ -261,7 +270,6 @@ async fn stream_object_range(
     range_start: u64,
     range_len: u64,
 ) -> Result<Body, StorageError> {
-    use tokio::io::{AsyncReadExt, AsyncSeekExt};
     use tokio_util::io::ReaderStream;
 
     if range_len == 0 {
@@ -271,35 +279,18 @@ async fn stream_object_range(
     // Fast path: single-part object.
     if meta.parts.len() == 1 {
         let path = object_dir.join(&meta.parts[0].file);
-        match tokio::fs::File::open(&path).await {
-            Ok(mut file) => {
-                if range_start > 0 {
-                    if file.seek(SeekFrom::Start(range_start)).await.is_err() {
-                        return Err(StorageError::CorruptObject(format!(
-                            "failed to seek {}",
-                            path.display()
-                        )));
-                    }
-                }
-                return Ok(Body::from_stream(ReaderStream::with_capacity(
-                    file.take(range_len),
-                    STREAM_CHUNK_SIZE,
-                )));
-            }
-            Err(err) => {
-                return Err(StorageError::CorruptObject(format!(
-                    "failed to open {}: {err}",
-                    path.display()
-                )))
-            }
-        }
+        let reader = open_segment(&path, &meta.parts[0], range_start, range_len).await?;
+        return Ok(Body::from_stream(ReaderStream::with_capacity(
+            reader,
+            STREAM_CHUNK_SIZE,
+        )));
     }
 
     let mut segments = multipart_range_segments(meta, part_offsets, range_start, range_len)?
         .into_iter()
         .map(|segment| {
             (
-                meta.parts[segment.index].file.clone(),
+                meta.parts[segment.index].clone(),
                 segment.skip,
                 segment.take,
             )
@@ -307,28 +298,12 @@ async fn stream_object_range(
         .collect::<Vec<_>>();
 
     if segments.len() == 1 {
-        let (name, skip, take) = segments.pop().unwrap();
-        let path = object_dir.join(name);
-        match tokio::fs::File::open(&path).await {
-            Ok(mut file) => {
-                if skip > 0 && file.seek(SeekFrom::Start(skip)).await.is_err() {
-                    return Err(StorageError::CorruptObject(format!(
-                        "failed to seek {}",
-                        path.display()
-                    )));
-                }
-                return Ok(Body::from_stream(ReaderStream::with_capacity(
-                    file.take(take),
-                    STREAM_CHUNK_SIZE,
-                )));
-            }
-            Err(err) => {
-                return Err(StorageError::CorruptObject(format!(
-                    "failed to open {}: {err}",
-                    path.display()
-                )))
-            }
-        }
+        let (part, skip, take) = segments.pop().unwrap();
+        let reader = open_segment(&object_dir.join(&part.file), &part, skip, take).await?;
+        return Ok(Body::from_stream(ReaderStream::with_capacity(
+            reader,
+            STREAM_CHUNK_SIZE,
+        )));
     }
 
     let stable_dir = StableObjectDir::open(object_dir).map_err(|err| {
@@ -338,31 +313,30 @@ async fn stream_object_range(
         ))
     })?;
     let mut segments = segments.into_iter();
-    let (first_name, first_skip, first_take) = segments.next().unwrap();
-    let mut first_file = stable_dir.open_part(&first_name).map_err(|err| {
-        StorageError::CorruptObject(format!("failed to open part {first_name}: {err}"))
+    let (first_part, first_skip, first_take) = segments.next().unwrap();
+    let first_file = stable_dir.open_part(&first_part.file).map_err(|err| {
+        StorageError::CorruptObject(format!("failed to open part {}: {err}", first_part.file))
     })?;
-    if first_skip > 0 {
-        first_file
-            .seek(SeekFrom::Start(first_skip))
-            .await
-            .map_err(|_| StorageError::CorruptObject(format!("failed to seek part {first_name}")))?;
-    }
+    let mut first_reader = open_part_range(first_file, &first_part, first_skip, first_take)
+        .await
+        .map_err(|err| {
+            StorageError::CorruptObject(format!("failed to read part {}: {err}", first_part.file))
+        })?;
     let (mut writer, reader) = tokio::io::duplex(STREAM_CHUNK_SIZE);
     tokio::spawn(async move {
-        use tokio::io::AsyncReadExt;
-        let mut limited = first_file.take(first_take);
-        if tokio::io::copy(&mut limited, &mut writer).await.is_err() {
+        if tokio::io::copy(&mut first_reader, &mut writer)
+            .await
+            .is_err()
+        {
             return;
         }
-        for (name, skip, take) in segments {
-            let Ok(mut file) = stable_dir.open_part(&name) else {
+        for (part, skip, take) in segments {
+            let Ok(file) = stable_dir.open_part(&part.file) else {
                 return;
             };
-            if skip > 0 && file.seek(SeekFrom::Start(skip)).await.is_err() {
+            let Ok(mut limited) = open_part_range(file, &part, skip, take).await else {
                 return;
-            }
-            let mut limited = file.take(take);
+            };
             if tokio::io::copy(&mut limited, &mut writer).await.is_err() {
                 return;
             }
@@ -375,6 +349,23 @@ async fn stream_object_range(
     )))
 }
 
+/// Opens `take` logical bytes of one part at `skip`.
+async fn open_segment(
+    path: &FsPath,
+    part: &PartMeta,
+    skip: u64,
+    take: u64,
+) -> Result<PartReader, StorageError> {
+    let file = tokio::fs::File::open(path).await.map_err(|err| {
+        StorageError::CorruptObject(format!("failed to open {}: {err}", path.display()))
+    })?;
+    open_part_range(file, part, skip, take)
+        .await
+        .map_err(|err| {
+            StorageError::CorruptObject(format!("failed to read {}: {err}", path.display()))
+        })
+}
+
 fn multipart_range_segments(
     meta: &ObjectMeta,
     part_offsets: &[u64],
@@ -445,6 +436,7 @@ mod tests {
             content_type: "application/octet-stream".to_string(),
             content_encoding: None,
             content_language: None,
+            cache_control: None,
             storage_class: "STANDARD".to_string(),
             user_meta: std::collections::BTreeMap::new(),
             parts: sizes
@@ -455,6 +447,8 @@ mod tests {
                     file: format!("part.{}", i + 1),
                     size: *size,
                     etag: format!("etag{}", i + 1),
+                    compression: None,
+                    chunk: None,
                 })
                 .collect(),
         }
@@ -53,6 +53,8 @@ pub(crate) mod delete_bucket;
 pub(crate) mod delete_objects;
 #[path = "get_bucket_location/lib.rs"]
 pub(crate) mod get_bucket_location;
+#[path = "get_bucket_logging/lib.rs"]
+pub(crate) mod get_bucket_logging;
 #[path = "head_bucket/lib.rs"]
 pub(crate) mod head_bucket;
 #[path = "list_objects/lib.rs"]
@@ -61,6 +63,8 @@ pub(crate) mod list_objects;
 pub(crate) mod list_uploads;
 #[path = "list_versions/lib.rs"]
 pub(crate) mod list_versions;
+#[path = "put_bucket_logging/lib.rs"]
+pub(crate) mod put_bucket_logging;
 #[path = "rebuild_index/lib.rs"]
 pub(crate) mod rebuild_index;
 
@@ -94,6 +98,8 @@ pub(crate) struct BucketCtx {
     /// The in-flight task registry, for verbs that launch their own jobs (e.g.
     /// rebuild-index). `None` only on code paths without a running server.
     pub tasks: Option<Arc<TaskRegistry>>,
+    /// The request arrived over TLS (feeds `aws:SecureTransport`).
+    pub secure_transport: bool,
     pub bucket: String,
     pub query: HashMap<String, String>,
     pub headers: HeaderMap,
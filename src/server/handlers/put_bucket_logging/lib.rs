@@ -0,0 +1,112 @@
+//! `PUT /{bucket}?logging` — enable or disable server access logging. A
+//! `BucketLoggingStatus` with a `LoggingEnabled` element names the target
+//! bucket and key prefix; an empty one turns logging off. Delivery itself is
+//! the `access_log` job's business.
+
+use std::sync::OnceLock;
+
+use axum::body::{to_bytes, Body};
+use axum::http::StatusCode;
+use axum::response::Response;
+use regex::Regex;
+
+use crate::server as srv;
+use crate::server::handlers::BucketCtx;
+use crate::server::policy::Requirement;
+use crate::storage::metadata::BucketLogging;
+use crate::storage::store::LocalObjectStore;
+
+pub(crate) async fn handle(store: LocalObjectStore, ctx: BucketCtx, body: Body) -> Response {
+    let resource = ctx.resource();
+    let raw = match to_bytes(body, 64 * 1024).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return srv::s3_error(
+                StatusCode::BAD_REQUEST,
+                "MalformedXML",
+                "Invalid BucketLoggingStatus document",
+                &resource,
+            )
+        }
+    };
+    let xml = String::from_utf8_lossy(&raw);
+    if !xml.contains("BucketLoggingStatus") {
+        return srv::s3_error(
+            StatusCode::BAD_REQUEST,
+            "MalformedXML",
+            "Invalid BucketLoggingStatus document",
+            &resource,
+        );
+    }
+    let logging = if xml.contains("<LoggingEnabled>") {
+        let Some(target_bucket) = element(&xml, target_bucket_re()).filter(|v| !v.is_empty()) else {
+            return srv::s3_error(
+                StatusCode::BAD_REQUEST,
+                "MalformedXML",
+                "LoggingEnabled requires a TargetBucket",
+                &resource,
+            );
+        };
+        let target_prefix = element(&xml, target_prefix_re()).unwrap_or_default();
+        if !store.bucket_exists(&target_bucket).await {
+            return srv::s3_error(
+                StatusCode::BAD_REQUEST,
+                "InvalidTargetBucketForLogging",
+                "The target bucket for logging does not exist",
+                &resource,
+            );
+        }
+        // Log objects are written by the server, not the caller, so without
+        // this check PutBucketLogging on one's own bucket would be a way to
+        // write into any other bucket.
+        let may_write = ctx
+            .identity
+            .as_ref()
+            .map(|identity| {
+                identity.authorize(&[Requirement::object(
+                    "s3:PutObject",
+                    &target_bucket,
+                    &format!("{target_prefix}*"),
+                )])
+            })
+            .unwrap_or(true);
+        if !may_write {
+            return srv::s3_error(
+                StatusCode::FORBIDDEN,
+                "AccessDenied",
+                "Access Denied: no write permission on the logging target",
+                &resource,
+            );
+        }
+        Some(BucketLogging {
+            target_bucket,
+            target_prefix,
+        })
+    } else {
+        None
+    };
+    match store.set_bucket_logging(&ctx.bucket, logging).await {
+        Ok(()) => srv::empty_response(StatusCode::OK),
+        Err(err) => srv::storage_error_response(err, &resource),
+    }
+}
+
+fn target_bucket_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?s)<TargetBucket>\s*(.*?)\s*</TargetBucket>").expect("static regex")
+    })
+}
+
+fn target_prefix_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?s)<TargetPrefix>\s*(.*?)\s*</TargetPrefix>").expect("static regex")
+    })
+}
+
+fn element(xml: &str, re: &Regex) -> Option<String> {
+    re.captures(xml)
+        .and_then(|capture| capture.get(1))
+        .map(|value| srv::unescape_xml(value.as_str()))
+}
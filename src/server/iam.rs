@@ -20,12 +20,61 @@
 //! Every value is JSON tagged with a `"v"` version. Only **V1** exists today;
 //! V1 parsing is lenient (missing fields default, unknown fields ignored) and a
 //! value tagged with a newer version is rejected rather than misread.
+//!
+//! ## Console sessions
+//!
+//! Console sessions live in the `sessions` family so a restart does not log
+//! every operator out. A row is keyed by the SHA-256 of the cookie token, never
+//! the token itself, so a copy of the database (a snapshot, a backup) cannot
+//! be replayed as a login; the hash doubles as the session id the console
+//! lists and revokes by. Sessions are also held in memory and resolved from
+//! there, like every other auth lookup. Sessions are not IAM data: export and
+//! import leave the family alone.
+//!
+//! ## Two-factor authentication
+//!
+//! The `mfa` family holds one row per enrolled user: the TOTP secret, the
+//! SHA-256 of each unused recovery code, and the last time step that was
+//! accepted (see [`super::totp`]). A row starts out unconfirmed and only
+//! counts once the user has proven their authenticator with a code. Unlike
+//! sessions this is IAM data; it travels with export and import and goes
+//! when the user does. Built-in users keep their secret in the config file.
+//!
+//! ## Temporary credentials
+//!
+//! The `sts` family holds S3 credentials issued by STS
+//! (`AssumeRoleWithLDAPIdentity`), keyed by access key. They act as their
+//! user, are honored only with their session token and until they expire,
+//! and are swept once expired. Like sessions they are not IAM data and are
+//! left out of export and import.
+//!
+//! ## Named policies
+//!
+//! The `policies` family holds reusable policy documents by name, the
+//! "canned" policies MinIO clients manage (see [`super::madmin`]); a few
+//! built-in ones exist without a row. A user or group that has named policies
+//! attached keeps their names and, as its own policy, their merged statements,
+//! which are rewritten whenever one of those policies changes. Setting a
+//! policy document directly replaces the attachment.
+//!
+//! ## Share links
+//!
+//! The `shares` family records every share link the console mints, keyed by
+//! a random link id that is signed into the link itself: who made it, for
+//! which object, until when, an optional password (PBKDF2, like user
+//! passwords) and download limit, and how often it has been used. A link is
+//! honored only while its row says so, which is what makes one link
+//! revocable without the others. Each use is appended to `share_access`
+//! under the link id. Rows are held in memory like sessions, outlive their
+//! link for [`SHARE_RETENTION_MS`] so the console can still show them, go
+//! with their user, and are not IAM data: export and import leave them alone.
 
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::{Arc, Mutex, RwLock};
 
 use hmac::{Hmac, Mac};
+use sha2::Digest;
 use rand::RngCore;
 use rocksdb::{
     ColumnFamilyDescriptor, DBWithThreadMode, Direction, IteratorMode, MultiThreaded, Options,
@@ -37,14 +86,23 @@ use sha2::Sha256;
 /// Multi-threaded RocksDB handle (see the same alias in `storage::index`).
 type Db = DBWithThreadMode<MultiThreaded>;
 
-use super::policy::{Effect, OneOrMany, PolicyDocument, Statement};
+use super::policy::{Effect, OneOrMany, PolicyDocument, Statement, ADMIN_RESOURCE};
+use super::totp;
 use crate::storage::errors::{Result, StorageError};
+use crate::storage::snapshot::CheckpointSource;
 use crate::storage::time::now_ms;
 
 type HmacSha256 = Hmac<Sha256>;
 
 const PBKDF2_ITERATIONS: u32 = 100_000;
-const SESSION_TTL_MS: i64 = 12 * 60 * 60 * 1000;
+/// `last_seen_ms` is written back at most this often per session; the idle
+/// timeout is only as precise as this.
+const SESSION_TOUCH_MS: i64 = 60_000;
+
+/// How long a share link stays listed after it expires or is revoked.
+pub const SHARE_RETENTION_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+/// Newest accesses listed per share link.
+const SHARE_ACCESS_LISTED: usize = 100;
 
 /// Highest entity value version this build understands. See module docs.
 const ENTITY_VERSION: u32 = 1;
@@ -54,6 +112,23 @@ const CF_GROUPS: &str = "groups";
 const CF_ACCESS_KEYS: &str = "access_keys";
 const CF_WEB_KEYS: &str = "web_keys";
 const CF_USER_GROUPS: &str = "user_groups";
+const CF_SESSIONS: &str = "sessions";
+const CF_MFA: &str = "mfa";
+const CF_STS: &str = "sts";
+const CF_API_TOKENS: &str = "api_tokens";
+const CF_POLICIES: &str = "policies";
+const CF_SHARES: &str = "shares";
+const CF_SHARE_ACCESS: &str = "share_access";
+/// Every family that export and import carry.
+const CF_IAM: [&str; 7] = [
+    CF_USERS,
+    CF_GROUPS,
+    CF_ACCESS_KEYS,
+    CF_WEB_KEYS,
+    CF_USER_GROUPS,
+    CF_MFA,
+    CF_POLICIES,
+];
 
 /// Separator between the two components of a `user_groups` key. Both usernames
 /// and group names are validated to a restricted charset that excludes NUL, so
@@ -116,13 +191,22 @@ impl Group {
         match self {
             Self::Admin => Some(PolicyDocument {
                 version: "2012-10-17".to_string(),
-                statement: vec![Statement {
-                    sid: Some("RustS3SystemAdmin".to_string()),
-                    effect: Effect::Allow,
-                    action: OneOrMany::One("s3:*".to_string()),
-                    resource: OneOrMany::One("arn:aws:s3:::*".to_string()),
-                    condition: None,
-                }],
+                statement: vec![
+                    Statement {
+                        sid: Some("RustS3SystemAdmin".to_string()),
+                        effect: Effect::Allow,
+                        action: OneOrMany::One("s3:*".to_string()),
+                        resource: OneOrMany::One("arn:aws:s3:::*".to_string()),
+                        condition: None,
+                    },
+                    Statement {
+                        sid: Some("RustS3SystemAdminConsole".to_string()),
+                        effect: Effect::Allow,
+                        action: OneOrMany::One("admin:*".to_string()),
+                        resource: OneOrMany::One(ADMIN_RESOURCE.to_string()),
+                        condition: None,
+                    },
+                ],
             }),
             Self::Named(name) => named
                 .get(&name.as_str().to_ascii_lowercase())
@@ -136,6 +220,10 @@ impl Group {
 pub struct IamUser {
     pub username: String,
     pub policy: Option<PolicyDocument>,
+    /// Named policies the policy was made from; empty when it was set directly.
+    pub policy_names: Vec<String>,
+    /// A disabled user keeps its rows but can neither sign in nor sign requests.
+    pub disabled: bool,
     pub created_at_ms: i64,
 }
 
@@ -143,6 +231,7 @@ pub struct IamUser {
 pub struct IamGroup {
     pub group: Group,
     pub policy: Option<PolicyDocument>,
+    pub policy_names: Vec<String>,
     pub created_at_ms: i64,
     pub members: u64,
 }
@@ -153,6 +242,95 @@ pub struct AccessKey {
     pub secret_key: String,
     pub username: String,
     pub created_at_ms: i64,
+    /// Inactive keys stay listed but no longer sign requests.
+    pub active: bool,
+    pub name: String,
+    pub description: String,
+    /// Narrows what the key may do below its user's own policy, as a MinIO
+    /// service account's policy does.
+    pub scope: Option<PolicyDocument>,
+}
+
+impl AccessKey {
+    fn generate(username: &str) -> Self {
+        Self {
+            access_key: format!("RSAK{}", random_hex(8).to_uppercase()),
+            secret_key: random_hex(20),
+            username: username.to_string(),
+            created_at_ms: now_ms(),
+            active: true,
+            name: String::new(),
+            description: String::new(),
+            scope: None,
+        }
+    }
+}
+
+/// An access key asked for with more than [`IamStore::create_access_key`]
+/// offers. Unset id and secret are generated.
+#[derive(Debug, Clone, Default)]
+pub struct NewAccessKey {
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub scope: Option<PolicyDocument>,
+}
+
+/// Changes to an access key; `None` leaves a field alone.
+#[derive(Debug, Clone, Default)]
+pub struct AccessKeyUpdate {
+    pub secret_key: Option<String>,
+    pub active: Option<bool>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `Some(None)` removes the scope.
+    pub scope: Option<Option<PolicyDocument>>,
+}
+
+/// A reusable policy document with a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedPolicy {
+    pub name: String,
+    pub policy: PolicyDocument,
+    /// Built-in policies cannot be changed or removed.
+    pub builtin: bool,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// Names of the policies that exist without being stored, as MinIO has them.
+pub const BUILTIN_POLICIES: [&str; 4] = ["consoleAdmin", "readonly", "readwrite", "writeonly"];
+
+fn builtin_policy(name: &str) -> Option<PolicyDocument> {
+    let statement = |sid: &str, actions: &[&str], resource: &str| Statement {
+        sid: Some(sid.to_string()),
+        effect: Effect::Allow,
+        action: match actions {
+            [action] => OneOrMany::One(action.to_string()),
+            _ => OneOrMany::Many(actions.iter().map(|a| a.to_string()).collect()),
+        },
+        resource: OneOrMany::One(resource.to_string()),
+        condition: None,
+    };
+    let statements = match name {
+        "consoleAdmin" => vec![
+            statement("ConsoleAdminS3", &["s3:*"], "arn:aws:s3:::*"),
+            statement("ConsoleAdmin", &["admin:*"], ADMIN_RESOURCE),
+        ],
+        "readonly" => vec![statement(
+            "ReadOnly",
+            &["s3:GetBucketLocation", "s3:GetObject"],
+            "arn:aws:s3:::*",
+        )],
+        "readwrite" => vec![statement("ReadWrite", &["s3:*"], "arn:aws:s3:::*")],
+        "writeonly" => vec![statement("WriteOnly", &["s3:PutObject"], "arn:aws:s3:::*")],
+        _ => return None,
+    };
+    Some(PolicyDocument {
+        version: "2012-10-17".to_string(),
+        statement: statements,
+    })
 }
 
 /// The resolved caller of an S3 API request.
@@ -189,6 +367,11 @@ struct UserV1 {
     salt: String,
     #[serde(default)]
     policy_json: Option<String>,
+    /// Named policies `policy_json` was merged from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    policy_names: Vec<String>,
+    #[serde(default)]
+    disabled: bool,
     #[serde(default)]
     created_at_ms: i64,
 }
@@ -202,6 +385,8 @@ struct GroupV1 {
     name: String,
     #[serde(default)]
     policy_json: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    policy_names: Vec<String>,
     #[serde(default)]
     is_system: bool,
     #[serde(default)]
@@ -222,6 +407,58 @@ struct AccessKeyV1 {
     username: String,
     #[serde(default)]
     created_at_ms: i64,
+    /// Deactivated by its owner or an administrator; absent on older rows.
+    #[serde(default)]
+    inactive: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope_json: Option<String>,
+}
+
+impl From<&AccessKey> for AccessKeyV1 {
+    fn from(key: &AccessKey) -> Self {
+        Self {
+            v: ENTITY_VERSION,
+            access_key: key.access_key.clone(),
+            secret_key: key.secret_key.clone(),
+            username: key.username.clone(),
+            created_at_ms: key.created_at_ms,
+            inactive: !key.active,
+            name: key.name.clone(),
+            description: key.description.clone(),
+            scope_json: key.scope.as_ref().map(to_json),
+        }
+    }
+}
+
+impl AccessKeyV1 {
+    fn into_key(self, access_key: String) -> AccessKey {
+        AccessKey {
+            access_key,
+            secret_key: self.secret_key,
+            username: self.username,
+            created_at_ms: self.created_at_ms,
+            active: !self.inactive,
+            name: self.name,
+            description: self.description,
+            scope: self.scope_json.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NamedPolicyV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
+    policy_json: String,
+    #[serde(default)]
+    created_at_ms: i64,
+    #[serde(default)]
+    updated_at_ms: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -238,6 +475,222 @@ struct WebKeyV1 {
     created_at_ms: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct TempCredentialV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
+    secret_key: String,
+    #[serde(default)]
+    session_token: String,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    expires_at_ms: i64,
+    #[serde(default)]
+    created_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ApiTokenV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    is_builtin: bool,
+    #[serde(default)]
+    name: String,
+    /// Action patterns the token may use, e.g. `admin:ManageUsers`, `s3:*`.
+    #[serde(default)]
+    scopes: Vec<String>,
+    /// Hex SHA-256 of the token's secret half; the secret itself is not kept.
+    #[serde(default)]
+    secret_hash: String,
+    #[serde(default)]
+    created_at_ms: i64,
+    #[serde(default)]
+    expires_at_ms: i64,
+}
+
+/// An admin API token, as listed. The secret is only ever returned by
+/// [`IamStore::create_api_token`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub username: String,
+    pub is_builtin: bool,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+impl ApiTokenInfo {
+    fn new(id: String, row: &ApiTokenV1) -> Self {
+        Self {
+            id,
+            username: row.username.clone(),
+            is_builtin: row.is_builtin,
+            name: row.name.clone(),
+            scopes: row.scopes.clone(),
+            created_at_ms: row.created_at_ms,
+            expires_at_ms: row.expires_at_ms,
+        }
+    }
+}
+
+/// Prefix of every admin API token: `rs3t_<id>_<secret>`.
+pub const API_TOKEN_PREFIX: &str = "rs3t_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ShareV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    is_builtin: bool,
+    /// The hidden web key the link is signed with; only it may present the id.
+    #[serde(default)]
+    signer: String,
+    #[serde(default)]
+    bucket: String,
+    #[serde(default)]
+    key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password_hash: Option<String>,
+    #[serde(default)]
+    salt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_downloads: Option<u64>,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    created_at_ms: i64,
+    #[serde(default)]
+    expires_at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revoked_at_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_access_ms: Option<i64>,
+}
+
+impl ShareV1 {
+    /// When the link stopped (or will stop) working, for the retention sweep.
+    fn ended_at_ms(&self) -> i64 {
+        self.revoked_at_ms.map_or(self.expires_at_ms, |at| at.min(self.expires_at_ms))
+    }
+}
+
+/// A share link to mint, as the console asks for it.
+#[derive(Debug, Clone, Default)]
+pub struct NewShare {
+    pub bucket: String,
+    pub key: String,
+    /// The web key the link is signed with (see [`IamStore::web_key_for`]).
+    pub signer: String,
+    pub ttl_ms: i64,
+    pub password: Option<String>,
+    pub max_downloads: Option<u64>,
+}
+
+/// A share link, as listed. Neither the URL nor the password is kept.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareLink {
+    pub id: String,
+    pub username: String,
+    pub is_builtin: bool,
+    pub bucket: String,
+    pub key: String,
+    pub has_password: bool,
+    pub max_downloads: Option<u64>,
+    pub downloads: u64,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+    pub last_access_ms: Option<i64>,
+}
+
+impl ShareLink {
+    fn new(id: String, row: &ShareV1) -> Self {
+        Self {
+            id,
+            username: row.username.clone(),
+            is_builtin: row.is_builtin,
+            bucket: row.bucket.clone(),
+            key: row.key.clone(),
+            has_password: row.password_hash.is_some(),
+            max_downloads: row.max_downloads,
+            downloads: row.downloads,
+            created_at_ms: row.created_at_ms,
+            expires_at_ms: row.expires_at_ms,
+            revoked_at_ms: row.revoked_at_ms,
+            last_access_ms: row.last_access_ms,
+        }
+    }
+}
+
+/// Why a share link was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareDenial {
+    /// No such link, or it was presented with another key than it was signed with.
+    Unknown,
+    Revoked,
+    Expired,
+    LimitReached,
+    /// The link has a password and none (or the wrong one) came with it.
+    PasswordRequired,
+}
+
+impl ShareDenial {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+            Self::LimitReached => "limit_reached",
+            Self::PasswordRequired => "password_required",
+        }
+    }
+}
+
+/// One use of a share link: where from, and how it went (`ok`, or a
+/// [`ShareDenial`]).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShareAccess {
+    #[serde(default)]
+    pub at_ms: i64,
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub outcome: String,
+}
+
+/// S3 credentials issued by STS. Sign like any key pair, and send
+/// `session_token` as `X-Amz-Security-Token`.
+#[derive(Debug, Clone)]
+pub struct TemporaryCredentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: String,
+    pub expires_at_ms: i64,
+}
+
+/// What [`IamStore::sync_external_user`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExternalSync {
+    pub created: bool,
+    /// The user's new groups, when they changed.
+    pub groups: Option<Vec<String>>,
+    /// Groups asked for that are not IAM groups, and were skipped.
+    pub missing_groups: Vec<String>,
+}
+
 fn to_vec<T: Serialize>(value: &T) -> Vec<u8> {
     serde_json::to_vec(value).expect("IAM value serializes")
 }
@@ -246,10 +699,19 @@ fn from_slice<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
     Ok(serde_json::from_slice(bytes)?)
 }
 
+fn to_json(policy: &PolicyDocument) -> String {
+    serde_json::to_string(policy).expect("policy serializes")
+}
+
 #[derive(Debug, Default)]
 struct Snapshot {
-    /// access_key → (secret_key, username)
+    /// access_key → (secret_key, username), active keys only.
     keys: HashMap<String, (String, String)>,
+    /// Deactivated access keys, and those of disabled users → username.
+    inactive_keys: HashMap<String, String>,
+    /// access_key → the scope narrowing it, for keys that have one.
+    key_scopes: HashMap<String, PolicyDocument>,
+    disabled_users: HashSet<String>,
     /// Hidden internal signing keys (`RSWEB_…`) used by the console to presign
     /// share links, keyed by access_key → (secret, username, is_builtin). Never
     /// listed or exposed; resolve to their owner's access on verification.
@@ -258,6 +720,10 @@ struct Snapshot {
     policies: HashMap<String, Option<PolicyDocument>>,
     /// username → assigned group names.
     memberships: HashMap<String, Vec<Group>>,
+    /// Unexpired STS credentials, by access key.
+    temp_keys: HashMap<String, TempCredentialV1>,
+    /// Admin API tokens, by id. Expired ones are skipped by the lookups.
+    api_tokens: HashMap<String, ApiTokenV1>,
 }
 
 /// Raw rows scanned out of RocksDB, before the (pure) snapshot assembly.
@@ -270,13 +736,108 @@ struct RawTables {
     access_keys: Vec<AccessKeyV1>,
     /// (username, web key). Username is the RocksDB key of the family.
     web_keys: Vec<(String, WebKeyV1)>,
+    /// (access key, credential), unexpired ones only.
+    temp_keys: Vec<(String, TempCredentialV1)>,
+    /// (token id, token), unexpired ones only.
+    api_tokens: Vec<(String, ApiTokenV1)>,
 }
 
-#[derive(Debug, Clone)]
-struct Session {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
     username: String,
+    #[serde(default)]
     is_root: bool,
-    expires_at_ms: i64,
+    #[serde(default)]
+    ip: Option<String>,
+    #[serde(default)]
+    user_agent: Option<String>,
+    #[serde(default)]
+    created_at_ms: i64,
+    #[serde(default)]
+    last_seen_ms: i64,
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    row: SessionV1,
+    /// `last_seen_ms` as last written to RocksDB.
+    stored_seen_ms: i64,
+}
+
+/// How long console sessions last. Passed in on every lookup rather than
+/// fixed at login, so a config reload applies to sessions already open.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionTimeouts {
+    /// Inactivity after which a session ends; `0` never ends one for idling.
+    pub idle_ms: i64,
+    /// Age after which a session ends however active it is.
+    pub max_age_ms: i64,
+}
+
+impl SessionTimeouts {
+    fn expired(&self, row: &SessionV1, now: i64) -> bool {
+        now - row.created_at_ms >= self.max_age_ms
+            || (self.idle_ms > 0 && now - row.last_seen_ms >= self.idle_ms)
+    }
+
+    fn expires_at_ms(&self, row: &SessionV1) -> i64 {
+        let absolute = row.created_at_ms + self.max_age_ms;
+        if self.idle_ms > 0 {
+            absolute.min(row.last_seen_ms + self.idle_ms)
+        } else {
+            absolute
+        }
+    }
+}
+
+/// Where a login came from, recorded with its session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// A live console session, as listed to administrators.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub username: String,
+    pub is_builtin: bool,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at_ms: i64,
+    pub last_seen_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MfaV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    /// Base32, as the user's authenticator holds it.
+    #[serde(default)]
+    secret: String,
+    /// False until the first code is confirmed.
+    #[serde(default)]
+    enabled: bool,
+    /// SHA-256 of each recovery code not yet used.
+    #[serde(default)]
+    recovery_hashes: Vec<String>,
+    #[serde(default)]
+    last_step: u64,
+    #[serde(default)]
+    created_at_ms: i64,
+}
+
+/// A user's two-factor state, as the console shows it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MfaStatus {
+    pub enabled: bool,
+    pub recovery_codes_left: usize,
+    pub enabled_at_ms: Option<i64>,
 }
 
 #[derive(Clone)]
@@ -284,6 +845,11 @@ pub struct IamStore {
     db: Arc<Db>,
     snapshot: Arc<RwLock<Snapshot>>,
     sessions: Arc<Mutex<HashMap<String, Session>>>,
+    /// Share links by id. Download counts change under this lock.
+    shares: Arc<Mutex<HashMap<String, ShareV1>>>,
+    /// Serializes two-factor read-modify-writes, so one code cannot be
+    /// accepted twice by racing logins.
+    mfa_lock: Arc<Mutex<()>>,
 }
 
 fn cf<'a>(db: &'a Db, name: &str) -> Result<Arc<rocksdb::BoundColumnFamily<'a>>> {
@@ -335,8 +901,21 @@ impl IamStore {
             let mut opts = Options::default();
             opts.create_if_missing(true);
             opts.create_missing_column_families(true);
-            let cfs = [CF_USERS, CF_GROUPS, CF_ACCESS_KEYS, CF_WEB_KEYS, CF_USER_GROUPS]
-                .into_iter()
+            let cfs = [
+                CF_USERS,
+                CF_GROUPS,
+                CF_ACCESS_KEYS,
+                CF_WEB_KEYS,
+                CF_USER_GROUPS,
+                CF_SESSIONS,
+                CF_MFA,
+                CF_STS,
+                CF_API_TOKENS,
+                CF_POLICIES,
+                CF_SHARES,
+                CF_SHARE_ACCESS,
+            ]
+            .into_iter()
                 .map(|name| ColumnFamilyDescriptor::new(name, Options::default()));
             let db = Db::open_cf_descriptors(&opts, &db_path, cfs)?;
             // Seed the built-in admin group if absent. Scoped so the CF handle
@@ -348,6 +927,7 @@ impl IamStore {
                         v: ENTITY_VERSION,
                         name: Group::ADMIN_NAME.to_string(),
                         policy_json: None,
+                        policy_names: Vec::new(),
                         is_system: true,
                         created_at_ms: now_ms(),
                     };
@@ -357,10 +937,18 @@ impl IamStore {
             Ok(db)
         })
         .await?;
+        let db = Arc::new(db);
+        let (sessions, shares) = blocking({
+            let db = db.clone();
+            move || Ok((load_sessions(&db)?, load_shares(&db)?))
+        })
+        .await?;
         let store = Self {
-            db: Arc::new(db),
+            db,
             snapshot: Arc::new(RwLock::new(Snapshot::default())),
-            sessions: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(sessions)),
+            shares: Arc::new(Mutex::new(shares)),
+            mfa_lock: Arc::new(Mutex::new(())),
         };
         store.reload().await?;
         Ok(store)
@@ -408,6 +996,25 @@ impl IamStore {
                 reject_newer(wk.v, "web key")?;
                 raw.web_keys.push((String::from_utf8_lossy(&key).into_owned(), wk));
             }
+            let sts = cf(&db, CF_STS)?;
+            let now = now_ms();
+            for item in db.iterator_cf(&sts, IteratorMode::Start) {
+                let (key, value) = item?;
+                let temp: TempCredentialV1 = from_slice(&value)?;
+                reject_newer(temp.v, "temporary credential")?;
+                if temp.expires_at_ms > now {
+                    raw.temp_keys.push((String::from_utf8_lossy(&key).into_owned(), temp));
+                }
+            }
+            let tokens = cf(&db, CF_API_TOKENS)?;
+            for item in db.iterator_cf(&tokens, IteratorMode::Start) {
+                let (key, value) = item?;
+                let token: ApiTokenV1 = from_slice(&value)?;
+                reject_newer(token.v, "api token")?;
+                if token.expires_at_ms > now {
+                    raw.api_tokens.push((String::from_utf8_lossy(&key).into_owned(), token));
+                }
+            }
             Ok(raw)
         })
         .await
@@ -421,6 +1028,9 @@ impl IamStore {
 
         let mut direct_policies: HashMap<String, Option<PolicyDocument>> = HashMap::new();
         for (username, user) in &raw.users {
+            if user.disabled {
+                snapshot.disabled_users.insert(username.clone());
+            }
             let policy = user
                 .policy_json
                 .as_ref()
@@ -476,15 +1086,36 @@ impl IamStore {
 
         for ak in raw.access_keys {
             // The access-key id is the family key; carried in `access_key`.
-            snapshot
-                .keys
-                .insert(ak.access_key.clone(), (ak.secret_key, ak.username));
+            if let Some(scope) = ak.scope_json.as_deref() {
+                // An unreadable scope must not widen the key: deny everything.
+                let scope = serde_json::from_str(scope).unwrap_or_else(|_| PolicyDocument {
+                    version: "2012-10-17".to_string(),
+                    statement: Vec::new(),
+                });
+                snapshot.key_scopes.insert(ak.access_key.clone(), scope);
+            }
+            if ak.inactive || snapshot.disabled_users.contains(&ak.username) {
+                snapshot.inactive_keys.insert(ak.access_key, ak.username);
+            } else {
+                snapshot
+                    .keys
+                    .insert(ak.access_key.clone(), (ak.secret_key, ak.username));
+            }
         }
         for (username, wk) in raw.web_keys {
+            if snapshot.disabled_users.contains(&username) {
+                continue;
+            }
             snapshot
                 .web_keys
                 .insert(wk.access_key, (wk.secret_key, username, wk.is_builtin));
         }
+        snapshot.temp_keys = raw
+            .temp_keys
+            .into_iter()
+            .filter(|(_, temp)| !snapshot.disabled_users.contains(&temp.username))
+            .collect();
+        snapshot.api_tokens = raw.api_tokens.into_iter().collect();
 
         *self.snapshot.write().unwrap() = snapshot;
         Ok(())
@@ -493,17 +1124,47 @@ impl IamStore {
     // ── synchronous auth-path lookups (in-memory) ─────────────────────────────
 
     /// Resolves an access key to its secret and owning user. Sync — used inside
-    /// SigV4 validation.
+    /// SigV4 validation. Inactive keys do not resolve.
     pub fn find_key(&self, access_key: &str) -> Option<(String, String)> {
         self.snapshot.read().unwrap().keys.get(access_key).cloned()
     }
 
+    /// The user an access key belongs to, whether or not it is active.
+    pub fn key_owner(&self, access_key: &str) -> Option<String> {
+        let snapshot = self.snapshot.read().unwrap();
+        snapshot
+            .keys
+            .get(access_key)
+            .map(|(_, username)| username.clone())
+            .or_else(|| snapshot.inactive_keys.get(access_key).cloned())
+    }
+
+    /// The scope narrowing an access key, if it has one. Sync — applied to the
+    /// identity of every request the key signs.
+    pub fn key_scope(&self, access_key: &str) -> Option<PolicyDocument> {
+        self.snapshot.read().unwrap().key_scopes.get(access_key).cloned()
+    }
+
+    pub fn is_disabled(&self, username: &str) -> bool {
+        self.snapshot.read().unwrap().disabled_users.contains(username)
+    }
+
     /// Resolves a hidden `RSWEB_…` signing key to `(secret, username,
     /// is_builtin)`. Sync — used inside presigned-URL verification.
     pub fn find_web_key(&self, access_key: &str) -> Option<(String, String, bool)> {
         self.snapshot.read().unwrap().web_keys.get(access_key).cloned()
     }
 
+    /// Resolves an unexpired STS access key to `(secret, username,
+    /// session_token)`. Sync — used inside SigV4 validation.
+    pub fn find_temporary_key(&self, access_key: &str) -> Option<(String, String, String)> {
+        let snapshot = self.snapshot.read().unwrap();
+        let temp = snapshot.temp_keys.get(access_key)?;
+        (temp.expires_at_ms > now_ms()).then(|| {
+            (temp.secret_key.clone(), temp.username.clone(), temp.session_token.clone())
+        })
+    }
+
     /// Returns the caller's hidden web-signing key `(access_key, secret)`,
     /// creating it on first use. Used by the console to presign share links so a
     /// user never has to configure a real access key just to share. The key is
@@ -613,6 +1274,8 @@ impl IamStore {
                 password_hash: hash,
                 salt,
                 policy_json: None,
+                policy_names: Vec::new(),
+                disabled: false,
                 created_at_ms: now_ms(),
             };
             db.put_cf_opt(&users, username_owned.as_bytes(), to_vec(&value), &sync_write())?;
@@ -645,53 +1308,175 @@ impl IamStore {
         .await?;
         // A password reset is a security boundary: existing console sessions for
         // the user must authenticate again with the new value.
-        self.sessions
-            .lock()
-            .unwrap()
-            .retain(|_, s| s.is_root || s.username != username);
+        self.revoke_sessions_where(|s| !s.is_root && s.username == username)
+            .await?;
         Ok(())
     }
 
-    pub async fn delete_user(&self, username: &str) -> Result<()> {
+    /// Creates or updates the user behind a MinIO-style credential: an IAM
+    /// user whose password is `secret` and who signs with an access key of
+    /// the same id and secret. See [`super::madmin`], where a user is its key.
+    pub async fn set_user_credentials(&self, username: &str, secret: &str) -> Result<()> {
+        validate_username(username)?;
+        if secret.len() < 8 {
+            return Err(StorageError::Io("secret key must be at least 8 characters".into()));
+        }
+        if self.find_web_key(username).is_some() || self.find_temporary_key(username).is_some() {
+            return Err(StorageError::Io(format!("access key {username} is taken")));
+        }
+        let salt = random_hex(16);
+        let hash = pbkdf2_hex(secret, &salt);
         let db = self.db.clone();
         let username_owned = username.to_string();
-        blocking(move || {
+        let secret = secret.to_string();
+        let existed = blocking(move || {
             let users = cf(&db, CF_USERS)?;
             let access = cf(&db, CF_ACCESS_KEYS)?;
-            let web = cf(&db, CF_WEB_KEYS)?;
-            let user_groups = cf(&db, CF_USER_GROUPS)?;
-            let mut batch = WriteBatch::default();
-
-            // Group memberships: all keys prefixed by `username\0`.
-            let prefix = membership_key(&username_owned, "");
-            for item in db.iterator_cf(&user_groups, IteratorMode::From(&prefix, Direction::Forward)) {
-                let (key, _) = item?;
-                if !key.starts_with(&prefix) {
-                    break;
-                }
-                batch.delete_cf(&user_groups, &key);
-            }
-            // Access keys owned by the user (value carries username).
-            for item in db.iterator_cf(&access, IteratorMode::Start) {
-                let (key, value) = item?;
-                let ak: AccessKeyV1 = from_slice(&value)?;
-                if ak.username == username_owned {
-                    batch.delete_cf(&access, &key);
+            let key = username_owned.as_bytes();
+            let mut ak = match db.get_cf(&access, key)? {
+                Some(value) => {
+                    let ak: AccessKeyV1 = from_slice(&value)?;
+                    reject_newer(ak.v, "access key")?;
+                    if ak.username != username_owned {
+                        return Err(StorageError::Io(format!(
+                            "access key {username_owned} is taken"
+                        )));
+                    }
+                    ak
                 }
-            }
-            // Their hidden signing key goes too, so any share links they made
-            // stop working — deleting a user vanishes their shares.
-            batch.delete_cf(&web, username_owned.as_bytes());
-            batch.delete_cf(&users, username_owned.as_bytes());
+                None => AccessKeyV1::from(&AccessKey {
+                    access_key: username_owned.clone(),
+                    ..AccessKey::generate(&username_owned)
+                }),
+            };
+            ak.v = ENTITY_VERSION;
+            ak.secret_key = secret;
+            let existing = db.get_cf(&users, key)?;
+            let mut user = match &existing {
+                Some(value) => from_slice::<UserV1>(value)?,
+                None => UserV1 {
+                    v: ENTITY_VERSION,
+                    password_hash: String::new(),
+                    salt: String::new(),
+                    policy_json: None,
+                    policy_names: Vec::new(),
+                    disabled: false,
+                    created_at_ms: now_ms(),
+                },
+            };
+            reject_newer(user.v, "iam user")?;
+            user.v = ENTITY_VERSION;
+            user.password_hash = hash;
+            user.salt = salt;
+            let mut batch = WriteBatch::default();
+            batch.put_cf(&users, key, to_vec(&user));
+            batch.put_cf(&access, key, to_vec(&ak));
             db.write_opt(batch, &sync_write())?;
+            Ok(existing.is_some())
+        })
+        .await?;
+        if existed {
+            // The secret is the console password too; see `set_password`.
+            self.revoke_sessions_where(|s| !s.is_root && s.username == username)
+                .await?;
+        }
+        self.reload().await
+    }
+
+    /// Disables or re-enables a user. Disabling signs the user out, and until
+    /// re-enabled none of their keys, temporary credentials or share links work.
+    pub async fn set_user_disabled(&self, username: &str, disabled: bool) -> Result<()> {
+        let db = self.db.clone();
+        let username_owned = username.to_string();
+        blocking(move || {
+            let users = cf(&db, CF_USERS)?;
+            let Some(value) = db.get_cf(&users, username_owned.as_bytes())? else {
+                return Err(StorageError::Io(format!("no such user {username_owned}")));
+            };
+            let mut user: UserV1 = from_slice(&value)?;
+            reject_newer(user.v, "iam user")?;
+            user.v = ENTITY_VERSION;
+            user.disabled = disabled;
+            db.put_cf_opt(&users, username_owned.as_bytes(), to_vec(&user), &sync_write())?;
             Ok(())
         })
         .await?;
-        // Invalidate any live sessions for the deleted user.
-        self.sessions
+        if disabled {
+            self.revoke_sessions_where(|s| !s.is_root && s.username == username)
+                .await?;
+        }
+        self.reload().await
+    }
+
+    pub async fn delete_user(&self, username: &str) -> Result<()> {
+        let db = self.db.clone();
+        let username_owned = username.to_string();
+        blocking(move || {
+            let users = cf(&db, CF_USERS)?;
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let web = cf(&db, CF_WEB_KEYS)?;
+            let user_groups = cf(&db, CF_USER_GROUPS)?;
+            let mfa = cf(&db, CF_MFA)?;
+            let sts = cf(&db, CF_STS)?;
+            let tokens = cf(&db, CF_API_TOKENS)?;
+            let shares = cf(&db, CF_SHARES)?;
+            let share_access = cf(&db, CF_SHARE_ACCESS)?;
+            let mut batch = WriteBatch::default();
+
+            // Group memberships: all keys prefixed by `username\0`.
+            let prefix = membership_key(&username_owned, "");
+            for item in db.iterator_cf(&user_groups, IteratorMode::From(&prefix, Direction::Forward)) {
+                let (key, _) = item?;
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                batch.delete_cf(&user_groups, &key);
+            }
+            // Access keys owned by the user (value carries username).
+            for item in db.iterator_cf(&access, IteratorMode::Start) {
+                let (key, value) = item?;
+                let ak: AccessKeyV1 = from_slice(&value)?;
+                if ak.username == username_owned {
+                    batch.delete_cf(&access, &key);
+                }
+            }
+            for item in db.iterator_cf(&sts, IteratorMode::Start) {
+                let (key, value) = item?;
+                let temp: TempCredentialV1 = from_slice(&value)?;
+                if temp.username == username_owned {
+                    batch.delete_cf(&sts, &key);
+                }
+            }
+            for item in db.iterator_cf(&tokens, IteratorMode::Start) {
+                let (key, value) = item?;
+                let token: ApiTokenV1 = from_slice(&value)?;
+                if !token.is_builtin && token.username == username_owned {
+                    batch.delete_cf(&tokens, &key);
+                }
+            }
+            for item in db.iterator_cf(&shares, IteratorMode::Start) {
+                let (key, value) = item?;
+                let share: ShareV1 = from_slice(&value)?;
+                if !share.is_builtin && share.username == username_owned {
+                    delete_share_rows(&mut batch, &shares, &share_access, &key);
+                }
+            }
+            // Their hidden signing key goes too, so any share links they made
+            // stop working — deleting a user vanishes their shares.
+            batch.delete_cf(&web, username_owned.as_bytes());
+            batch.delete_cf(&mfa, username_owned.as_bytes());
+            batch.delete_cf(&users, username_owned.as_bytes());
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.shares
             .lock()
             .unwrap()
-            .retain(|_, s| s.is_root || s.username != username);
+            .retain(|_, share| share.is_builtin || share.username != username);
+        // Invalidate any live sessions for the deleted user.
+        self.revoke_sessions_where(|s| !s.is_root && s.username == username)
+            .await?;
         self.reload().await
     }
 
@@ -713,6 +1498,7 @@ impl IamStore {
             let mut user: UserV1 = from_slice(&value)?;
             user.v = ENTITY_VERSION;
             user.policy_json = json;
+            user.policy_names.clear();
             db.put_cf_opt(&users, username_owned.as_bytes(), to_vec(&user), &sync_write())?;
             Ok(())
         })
@@ -731,6 +1517,8 @@ impl IamStore {
                     .policy_json
                     .as_ref()
                     .and_then(|json| serde_json::from_str(json).ok()),
+                policy_names: user.policy_names,
+                disabled: user.disabled,
                 created_at_ms: user.created_at_ms,
             })
             .collect::<Vec<_>>();
@@ -758,6 +1546,7 @@ impl IamStore {
                         .policy_json
                         .as_ref()
                         .and_then(|json| serde_json::from_str(json).ok()),
+                    policy_names: group.policy_names,
                     created_at_ms: group.created_at_ms,
                     members,
                 })
@@ -793,6 +1582,7 @@ impl IamStore {
                 v: ENTITY_VERSION,
                 name: stored_name,
                 policy_json: json,
+                policy_names: Vec::new(),
                 is_system: false,
                 created_at_ms: now_ms(),
             };
@@ -827,6 +1617,7 @@ impl IamStore {
             }
             record.v = ENTITY_VERSION;
             record.policy_json = json;
+            record.policy_names.clear();
             db.put_cf_opt(&groups, key.as_bytes(), to_vec(&record), &sync_write())?;
             Ok(())
         })
@@ -922,170 +1713,1268 @@ impl IamStore {
         self.reload().await
     }
 
-    pub async fn verify_password(&self, username: &str, password: &str) -> Result<bool> {
+    // ── named policies ────────────────────────────────────────────────────────
+
+    /// Every named policy, the built-in ones first.
+    pub async fn list_named_policies(&self) -> Result<Vec<NamedPolicy>> {
+        let db = self.db.clone();
+        let stored = blocking(move || {
+            let policies = cf(&db, CF_POLICIES)?;
+            let mut stored = Vec::new();
+            for item in db.iterator_cf(&policies, IteratorMode::Start) {
+                let (key, value) = item?;
+                let row: NamedPolicyV1 = from_slice(&value)?;
+                reject_newer(row.v, "named policy")?;
+                stored.push(NamedPolicy {
+                    name: String::from_utf8_lossy(&key).into_owned(),
+                    policy: serde_json::from_str(&row.policy_json)?,
+                    builtin: false,
+                    created_at_ms: row.created_at_ms,
+                    updated_at_ms: row.updated_at_ms,
+                });
+            }
+            Ok(stored)
+        })
+        .await?;
+        let builtin = BUILTIN_POLICIES.iter().filter_map(|name| {
+            Some(NamedPolicy {
+                name: name.to_string(),
+                policy: builtin_policy(name)?,
+                builtin: true,
+                created_at_ms: 0,
+                updated_at_ms: 0,
+            })
+        });
+        Ok(builtin.chain(stored).collect())
+    }
+
+    pub async fn named_policy(&self, name: &str) -> Result<Option<NamedPolicy>> {
+        let policies = self.list_named_policies().await?;
+        Ok(policies.into_iter().find(|policy| policy.name == name))
+    }
+
+    /// Creates or replaces a named policy. Users and groups it is attached to
+    /// get the new statements in the same write.
+    pub async fn put_named_policy(&self, name: &str, policy: &PolicyDocument) -> Result<()> {
+        validate_policy_name(name)?;
+        if builtin_policy(name).is_some() {
+            return Err(StorageError::Io(format!("{name} is a built-in policy")));
+        }
+        policy.validate().map_err(StorageError::Io)?;
+        let db = self.db.clone();
+        let name = name.to_string();
+        let policy = policy.clone();
+        blocking(move || {
+            let policies = cf(&db, CF_POLICIES)?;
+            let now = now_ms();
+            let created_at_ms = match db.get_cf(&policies, name.as_bytes())? {
+                Some(value) => from_slice::<NamedPolicyV1>(&value)?.created_at_ms,
+                None => now,
+            };
+            let row = NamedPolicyV1 {
+                v: ENTITY_VERSION,
+                policy_json: to_json(&policy),
+                created_at_ms,
+                updated_at_ms: now,
+            };
+            let mut batch = WriteBatch::default();
+            batch.put_cf(&policies, name.as_bytes(), to_vec(&row));
+            let pending = (name.as_str(), &policy);
+            let users = cf(&db, CF_USERS)?;
+            for item in db.iterator_cf(&users, IteratorMode::Start) {
+                let (key, value) = item?;
+                let mut user: UserV1 = from_slice(&value)?;
+                if user.policy_names.contains(&name) {
+                    user.v = ENTITY_VERSION;
+                    user.policy_json = merge_named(&db, &user.policy_names, Some(pending))?;
+                    batch.put_cf(&users, &key, to_vec(&user));
+                }
+            }
+            let groups = cf(&db, CF_GROUPS)?;
+            for item in db.iterator_cf(&groups, IteratorMode::Start) {
+                let (key, value) = item?;
+                let mut group: GroupV1 = from_slice(&value)?;
+                if group.policy_names.contains(&name) {
+                    group.v = ENTITY_VERSION;
+                    group.policy_json = merge_named(&db, &group.policy_names, Some(pending))?;
+                    batch.put_cf(&groups, &key, to_vec(&group));
+                }
+            }
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    /// Removes a named policy. One still attached to a user or group stays.
+    pub async fn delete_named_policy(&self, name: &str) -> Result<()> {
+        if builtin_policy(name).is_some() {
+            return Err(StorageError::Io(format!("{name} is a built-in policy")));
+        }
+        let db = self.db.clone();
+        let name = name.to_string();
+        blocking(move || {
+            let policies = cf(&db, CF_POLICIES)?;
+            if db.get_cf(&policies, name.as_bytes())?.is_none() {
+                return Err(StorageError::Io(format!("no such policy {name}")));
+            }
+            let users = cf(&db, CF_USERS)?;
+            for item in db.iterator_cf(&users, IteratorMode::Start) {
+                let (key, value) = item?;
+                if from_slice::<UserV1>(&value)?.policy_names.contains(&name) {
+                    let user = String::from_utf8_lossy(&key);
+                    return Err(StorageError::Io(format!(
+                        "policy {name} is attached to user {user}"
+                    )));
+                }
+            }
+            let groups = cf(&db, CF_GROUPS)?;
+            for item in db.iterator_cf(&groups, IteratorMode::Start) {
+                let (_, value) = item?;
+                let group: GroupV1 = from_slice(&value)?;
+                if group.policy_names.contains(&name) {
+                    return Err(StorageError::Io(format!(
+                        "policy {name} is attached to group {}",
+                        group.name
+                    )));
+                }
+            }
+            db.delete_cf_opt(&policies, name.as_bytes(), &sync_write())?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Attaches exactly `names` to a user: its policy becomes their merged
+    /// statements, or none when `names` is empty.
+    pub async fn set_user_policy_names(&self, username: &str, names: &[String]) -> Result<()> {
+        let db = self.db.clone();
+        let username_owned = username.to_string();
+        let names = dedup_names(names);
+        blocking(move || {
+            let users = cf(&db, CF_USERS)?;
+            let Some(value) = db.get_cf(&users, username_owned.as_bytes())? else {
+                return Err(StorageError::Io(format!("no such user {username_owned}")));
+            };
+            let mut user: UserV1 = from_slice(&value)?;
+            reject_newer(user.v, "iam user")?;
+            user.v = ENTITY_VERSION;
+            user.policy_json = merge_named(&db, &names, None)?;
+            user.policy_names = names;
+            db.put_cf_opt(&users, username_owned.as_bytes(), to_vec(&user), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    /// [`set_user_policy_names`](Self::set_user_policy_names) for a group.
+    pub async fn set_group_policy_names(&self, name: &str, names: &[String]) -> Result<()> {
+        let group = self.resolve_groups(&[name.to_string()]).await?.remove(0);
+        let Group::Named(group) = group else {
+            return Err(StorageError::Io("admin group is not editable".into()));
+        };
+        let db = self.db.clone();
+        let key = group.as_str().to_ascii_lowercase();
+        let names = dedup_names(names);
+        blocking(move || {
+            let groups = cf(&db, CF_GROUPS)?;
+            let Some(value) = db.get_cf(&groups, key.as_bytes())? else {
+                return Err(StorageError::Io(format!("no such editable group {key}")));
+            };
+            let mut record: GroupV1 = from_slice(&value)?;
+            reject_newer(record.v, "iam group")?;
+            record.v = ENTITY_VERSION;
+            record.policy_json = merge_named(&db, &names, None)?;
+            record.policy_names = names;
+            db.put_cf_opt(&groups, key.as_bytes(), to_vec(&record), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    // ── externally authenticated users ────────────────────────────────────────
+
+    /// Brings the IAM user behind an SSO or LDAP login in line with the
+    /// directory that vouched for it: creates it if `create` allows (with a
+    /// random password nobody is told) and, when `groups` is given, sets its
+    /// groups to those of them that exist.
+    pub async fn sync_external_user(
+        &self,
+        username: &str,
+        create: bool,
+        groups: Option<&[String]>,
+    ) -> Result<ExternalSync> {
+        let mut sync = ExternalSync::default();
+        if self.is_disabled(username) {
+            return Err(StorageError::Io(format!("IAM user {username} is disabled")));
+        }
+        if !self.user_exists(username) {
+            if !create {
+                return Err(StorageError::Io(format!(
+                    "there is no IAM user {username}; an administrator has to create it"
+                )));
+            }
+            self.create_user(username, &random_hex(32)).await?;
+            sync.created = true;
+        }
+        let Some(wanted) = groups else {
+            return Ok(sync);
+        };
+        let existing = self.list_groups().await?;
+        let mut names = Vec::new();
+        for name in wanted {
+            if existing.iter().any(|g| g.group.name().eq_ignore_ascii_case(name)) {
+                names.push(name.clone());
+            } else {
+                sync.missing_groups.push(name.clone());
+            }
+        }
+        let lower = |names: &[String]| {
+            let mut names: Vec<String> = names.iter().map(|n| n.to_ascii_lowercase()).collect();
+            names.sort();
+            names.dedup();
+            names
+        };
+        if lower(&self.groups_for(username)) != lower(&names) {
+            let resolved = self.resolve_groups(&names).await?;
+            self.set_user_groups(username, &resolved).await?;
+            sync.groups = Some(names);
+        }
+        Ok(sync)
+    }
+
+    /// Issues STS credentials acting as `username` for `duration_ms`, and
+    /// sweeps out any that have expired.
+    pub async fn issue_temporary_credentials(
+        &self,
+        username: &str,
+        duration_ms: i64,
+    ) -> Result<TemporaryCredentials> {
+        if !self.user_exists(username) {
+            return Err(StorageError::Io(format!("no such user {username}")));
+        }
+        let now = now_ms();
+        let issued = TemporaryCredentials {
+            access_key: format!("RSSTS{}", random_hex(8).to_ascii_uppercase()),
+            secret_key: random_hex(20),
+            session_token: random_hex(32),
+            expires_at_ms: now + duration_ms,
+        };
+        let row = TempCredentialV1 {
+            v: ENTITY_VERSION,
+            secret_key: issued.secret_key.clone(),
+            session_token: issued.session_token.clone(),
+            username: username.to_string(),
+            expires_at_ms: issued.expires_at_ms,
+            created_at_ms: now,
+        };
+        let db = self.db.clone();
+        let access_key = issued.access_key.clone();
+        blocking(move || {
+            let sts = cf(&db, CF_STS)?;
+            let mut batch = WriteBatch::default();
+            for item in db.iterator_cf(&sts, IteratorMode::Start) {
+                let (key, value) = item?;
+                let expired = from_slice::<TempCredentialV1>(&value)
+                    .map_or(true, |temp| temp.expires_at_ms <= now);
+                if expired {
+                    batch.delete_cf(&sts, &key);
+                }
+            }
+            batch.put_cf(&sts, access_key.as_bytes(), to_vec(&row));
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await?;
+        Ok(issued)
+    }
+
+    pub async fn verify_password(&self, username: &str, password: &str) -> Result<bool> {
+        let db = self.db.clone();
+        let username_owned = username.to_string();
+        let stored = blocking(move || {
+            let users = cf(&db, CF_USERS)?;
+            match db.get_cf(&users, username_owned.as_bytes())? {
+                Some(value) => {
+                    let user: UserV1 = from_slice(&value)?;
+                    reject_newer(user.v, "iam user")?;
+                    Ok((!user.disabled).then_some((user.password_hash, user.salt)))
+                }
+                None => Ok(None),
+            }
+        })
+        .await?;
+        let Some((hash, salt)) = stored else {
+            return Ok(false);
+        };
+        let candidate = pbkdf2_hex(password, &salt);
+        Ok(constant_time_eq(&candidate, &hash))
+    }
+
+    // ── access keys ───────────────────────────────────────────────────────────
+
+    pub async fn create_access_key(&self, username: &str) -> Result<AccessKey> {
+        self.create_access_key_with(username, NewAccessKey::default()).await
+    }
+
+    /// Creates an access key with a chosen id, secret, name or scope.
+    pub async fn create_access_key_with(
+        &self,
+        username: &str,
+        spec: NewAccessKey,
+    ) -> Result<AccessKey> {
+        if !self.user_exists(username) {
+            return Err(StorageError::Io(format!("no such user {username}")));
+        }
+        let mut key = AccessKey::generate(username);
+        if let Some(access_key) = spec.access_key {
+            validate_access_key(&access_key)?;
+            if self.find_web_key(&access_key).is_some()
+                || self.find_temporary_key(&access_key).is_some()
+            {
+                return Err(StorageError::Io(format!("access key {access_key} is taken")));
+            }
+            key.access_key = access_key;
+        }
+        if let Some(secret_key) = spec.secret_key {
+            validate_secret_key(&secret_key)?;
+            key.secret_key = secret_key;
+        }
+        if let Some(scope) = &spec.scope {
+            scope.validate().map_err(StorageError::Io)?;
+        }
+        key.name = spec.name;
+        key.description = spec.description;
+        key.scope = spec.scope;
+        let db = self.db.clone();
+        let stored = key.clone();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            if db.get_cf(&access, stored.access_key.as_bytes())?.is_some() {
+                let taken = &stored.access_key;
+                return Err(StorageError::Io(format!("access key {taken} is taken")));
+            }
+            let value = AccessKeyV1::from(&stored);
+            db.put_cf_opt(&access, stored.access_key.as_bytes(), to_vec(&value), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await?;
+        Ok(key)
+    }
+
+    /// One access key, active or not.
+    pub async fn access_key(&self, access_key: &str) -> Result<Option<AccessKey>> {
+        let db = self.db.clone();
+        let access_key = access_key.to_string();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let Some(value) = db.get_cf(&access, access_key.as_bytes())? else {
+                return Ok(None);
+            };
+            let ak: AccessKeyV1 = from_slice(&value)?;
+            reject_newer(ak.v, "access key")?;
+            Ok(Some(ak.into_key(access_key)))
+        })
+        .await
+    }
+
+    pub async fn update_access_key(&self, access_key: &str, update: AccessKeyUpdate) -> Result<()> {
+        if let Some(secret_key) = &update.secret_key {
+            validate_secret_key(secret_key)?;
+        }
+        if let Some(Some(scope)) = &update.scope {
+            scope.validate().map_err(StorageError::Io)?;
+        }
+        let db = self.db.clone();
+        let access_key_owned = access_key.to_string();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let Some(value) = db.get_cf(&access, access_key_owned.as_bytes())? else {
+                return Err(StorageError::Io(format!("no such access key {access_key_owned}")));
+            };
+            let mut ak: AccessKeyV1 = from_slice(&value)?;
+            reject_newer(ak.v, "access key")?;
+            ak.v = ENTITY_VERSION;
+            if let Some(secret_key) = update.secret_key {
+                ak.secret_key = secret_key;
+            }
+            if let Some(active) = update.active {
+                ak.inactive = !active;
+            }
+            if let Some(name) = update.name {
+                ak.name = name;
+            }
+            if let Some(description) = update.description {
+                ak.description = description;
+            }
+            if let Some(scope) = update.scope {
+                ak.scope_json = scope.as_ref().map(to_json);
+            }
+            db.put_cf_opt(&access, access_key_owned.as_bytes(), to_vec(&ak), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    /// Replaces `access_key` with a new key for the same user in one write,
+    /// so the old secret stops working the moment the new one exists.
+    pub async fn rotate_access_key(&self, access_key: &str) -> Result<AccessKey> {
+        let Some(username) = self.key_owner(access_key) else {
+            return Err(StorageError::Io(format!("no such access key {access_key}")));
+        };
+        let key = AccessKey::generate(&username);
+        let db = self.db.clone();
+        let old = access_key.to_string();
+        let stored = key.clone();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let mut batch = WriteBatch::default();
+            batch.delete_cf(&access, old.as_bytes());
+            let value = AccessKeyV1::from(&stored);
+            batch.put_cf(&access, stored.access_key.as_bytes(), to_vec(&value));
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await?;
+        Ok(key)
+    }
+
+    /// Turns an access key off or back on. An inactive key keeps its secret
+    /// but fails every signature check until it is reactivated.
+    pub async fn set_access_key_active(&self, access_key: &str, active: bool) -> Result<()> {
+        let db = self.db.clone();
+        let access_key_owned = access_key.to_string();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let Some(value) = db.get_cf(&access, access_key_owned.as_bytes())? else {
+                return Err(StorageError::Io(format!("no such access key {access_key_owned}")));
+            };
+            let mut ak: AccessKeyV1 = from_slice(&value)?;
+            reject_newer(ak.v, "access key")?;
+            ak.v = ENTITY_VERSION;
+            ak.inactive = !active;
+            db.put_cf_opt(&access, access_key_owned.as_bytes(), to_vec(&ak), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    pub async fn delete_access_key(&self, access_key: &str) -> Result<()> {
+        let db = self.db.clone();
+        let access_key_owned = access_key.to_string();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            db.delete_cf_opt(&access, access_key_owned.as_bytes(), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await
+    }
+
+    pub async fn list_access_keys(&self, username: &str) -> Result<Vec<AccessKey>> {
+        let db = self.db.clone();
+        let username_owned = username.to_string();
+        blocking(move || {
+            let access = cf(&db, CF_ACCESS_KEYS)?;
+            let mut keys = Vec::new();
+            for item in db.iterator_cf(&access, IteratorMode::Start) {
+                let (key, value) = item?;
+                let ak: AccessKeyV1 = from_slice(&value)?;
+                reject_newer(ak.v, "access key")?;
+                if ak.username == username_owned {
+                    keys.push(ak.into_key(String::from_utf8_lossy(&key).into_owned()));
+                }
+            }
+            keys.sort_by_key(|k| k.created_at_ms);
+            Ok(keys)
+        })
+        .await
+    }
+
+    // ── raw export / import (backup, restore, migrate) ────────────────────────
+
+    /// Dumps the entire global IAM database (all column families) to an
+    /// in-memory buffer as a length-delimited protobuf stream of `(cf, key,
+    /// value)` rows. See [`crate::storage::rawdb`]. Read-only and consistent
+    /// (single snapshot); safe against the live store, so the console serves it
+    /// straight into an HTTP download.
+    pub async fn export_raw(&self) -> Result<Vec<u8>> {
+        let db = self.db.clone();
+        blocking(move || {
+            let mut buf = Vec::new();
+            crate::storage::rawdb::export(
+                &db,
+                &CF_IAM,
+                &mut buf,
+            )?;
+            Ok(buf)
+        })
+        .await
+    }
+
+    /// Imports a dump produced by [`export_raw`](Self::export_raw), upserting
+    /// every row (`put`) in one atomic batch. No family is cleared, so importing
+    /// onto a fresh data directory reproduces the source exactly and onto a
+    /// populated one merges. `Replace` erases the IAM families first for an exact
+    /// restore. The apply is one atomic batch. Refreshes the in-memory snapshot
+    /// afterward. Returns a per-family
+    /// [`ImportReport`](crate::storage::rawdb::ImportReport) so the caller can
+    /// show exactly what landed.
+    pub async fn import_raw(
+        &self,
+        dump: Vec<u8>,
+        mode: crate::storage::rawdb::ImportMode,
+    ) -> Result<crate::storage::rawdb::ImportReport> {
+        let db = self.db.clone();
+        let report = blocking(move || {
+            crate::storage::rawdb::import(
+                &db,
+                dump.as_slice(),
+                mode,
+                &CF_IAM,
+            )
+        })
+        .await?;
+        self.reload().await?;
+        Ok(report)
+    }
+
+    // ── web sessions ──────────────────────────────────────────────────────────
+
+    /// Starts a session and returns its cookie token. The row is on disk
+    /// before the token is handed out.
+    pub async fn create_session(
+        &self,
+        username: &str,
+        is_root: bool,
+        client: SessionClient,
+    ) -> Result<String> {
+        let token = random_hex(32);
+        let id = session_id(&token);
+        let now = now_ms();
+        let row = SessionV1 {
+            v: ENTITY_VERSION,
+            username: username.to_string(),
+            is_root,
+            ip: client.ip,
+            user_agent: client.user_agent,
+            created_at_ms: now,
+            last_seen_ms: now,
+        };
+        let db = self.db.clone();
+        let (key, value) = (id.clone(), to_vec(&row));
+        blocking(move || {
+            let sessions = cf(&db, CF_SESSIONS)?;
+            db.put_cf_opt(&sessions, key.as_bytes(), value, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.sessions.lock().unwrap().insert(
+            id,
+            Session {
+                row,
+                stored_seen_ms: now,
+            },
+        );
+        Ok(token)
+    }
+
+    /// Returns `(username, is_root)` for a live session token, and marks the
+    /// session as seen. An expired session is removed on the way.
+    pub fn resolve_session(
+        &self,
+        token: &str,
+        timeouts: SessionTimeouts,
+    ) -> Option<(String, bool)> {
+        let id = session_id(token);
+        let now = now_ms();
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(&id)?;
+        if timeouts.expired(&session.row, now) {
+            sessions.remove(&id);
+            drop(sessions);
+            self.forget_sessions(&[id]);
+            return None;
+        }
+        session.row.last_seen_ms = now;
+        let resolved = (session.row.username.clone(), session.row.is_root);
+        if now - session.stored_seen_ms >= SESSION_TOUCH_MS {
+            session.stored_seen_ms = now;
+            let value = to_vec(&session.row);
+            drop(sessions);
+            // Unsynced: a lost touch only makes the session look a little
+            // older after a crash. This runs on the request path, and an
+            // unsynced put is a memtable insert.
+            let written = cf(&self.db, CF_SESSIONS)
+                .and_then(|cf| Ok(self.db.put_cf(&cf, id.as_bytes(), value)?));
+            if let Err(err) = written {
+                log::warn!("console session touch failed: {err}");
+            }
+        }
+        Some(resolved)
+    }
+
+    /// Ends the session holding `token` (logout).
+    pub async fn destroy_session(&self, token: &str) -> Result<()> {
+        let id = session_id(token);
+        self.revoke_session(&id).await.map(|_| ())
+    }
+
+    /// Live sessions, most recently active first. Expired ones are dropped.
+    pub async fn list_sessions(&self, timeouts: SessionTimeouts) -> Result<Vec<SessionInfo>> {
+        let now = now_ms();
+        let mut expired = Vec::new();
+        let mut live = Vec::new();
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            sessions.retain(|id, session| {
+                if timeouts.expired(&session.row, now) {
+                    expired.push(id.clone());
+                    return false;
+                }
+                live.push(SessionInfo {
+                    id: id.clone(),
+                    username: session.row.username.clone(),
+                    is_builtin: session.row.is_root,
+                    ip: session.row.ip.clone(),
+                    user_agent: session.row.user_agent.clone(),
+                    created_at_ms: session.row.created_at_ms,
+                    last_seen_ms: session.row.last_seen_ms,
+                    expires_at_ms: timeouts.expires_at_ms(&session.row),
+                });
+                true
+            });
+        }
+        if !expired.is_empty() {
+            self.delete_sessions(expired).await?;
+        }
+        live.sort_by_key(|s| std::cmp::Reverse(s.last_seen_ms));
+        Ok(live)
+    }
+
+    /// Ends one session by id. `false` when there was no such session.
+    pub async fn revoke_session(&self, id: &str) -> Result<bool> {
+        let found = self.sessions.lock().unwrap().remove(id).is_some();
+        if found {
+            self.delete_sessions(vec![id.to_string()]).await?;
+        }
+        Ok(found)
+    }
+
+    /// Ends every session of `username`, built-in and IAM alike. Returns how
+    /// many there were.
+    pub async fn revoke_user_sessions(&self, username: &str) -> Result<usize> {
+        self.revoke_sessions_where(|s| s.username == username).await
+    }
+
+    /// Removes matching sessions from memory first, so they stop resolving
+    /// before the disk catches up.
+    async fn revoke_sessions_where(&self, matches: impl Fn(&SessionV1) -> bool) -> Result<usize> {
+        let mut ids = Vec::new();
+        self.sessions.lock().unwrap().retain(|id, session| {
+            let hit = matches(&session.row);
+            if hit {
+                ids.push(id.clone());
+            }
+            !hit
+        });
+        let count = ids.len();
+        if count > 0 {
+            self.delete_sessions(ids).await?;
+        }
+        Ok(count)
+    }
+
+    async fn delete_sessions(&self, ids: Vec<String>) -> Result<()> {
+        let db = self.db.clone();
+        blocking(move || {
+            let sessions = cf(&db, CF_SESSIONS)?;
+            let mut batch = WriteBatch::default();
+            for id in ids {
+                batch.delete_cf(&sessions, id.as_bytes());
+            }
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Best-effort delete from a synchronous caller. The rows are already out
+    /// of memory; should this fail they come back at the next open, already
+    /// expired, and go the first time they are looked at.
+    fn forget_sessions(&self, ids: &[String]) {
+        let deleted = cf(&self.db, CF_SESSIONS).and_then(|cf| {
+            for id in ids {
+                self.db.delete_cf(&cf, id.as_bytes())?;
+            }
+            Ok(())
+        });
+        if let Err(err) = deleted {
+            log::warn!("console session cleanup failed: {err}");
+        }
+    }
+
+    // ── admin API tokens ──────────────────────────────────────────────────────
+
+    /// Issues a bearer token acting as `username`, limited to `scopes`, for
+    /// `ttl_ms`, and sweeps out any that have expired. Returns the token, which
+    /// cannot be recovered later, with its listing.
+    pub async fn create_api_token(
+        &self,
+        username: &str,
+        is_builtin: bool,
+        name: &str,
+        scopes: Vec<String>,
+        ttl_ms: i64,
+    ) -> Result<(String, ApiTokenInfo)> {
+        if !is_builtin && !self.user_exists(username) {
+            return Err(StorageError::Io(format!("no such user {username}")));
+        }
+        let now = now_ms();
+        let id = random_hex(8);
+        let secret = random_hex(32);
+        let row = ApiTokenV1 {
+            v: ENTITY_VERSION,
+            username: username.to_string(),
+            is_builtin,
+            name: name.to_string(),
+            scopes,
+            secret_hash: session_id(&secret),
+            created_at_ms: now,
+            expires_at_ms: now + ttl_ms,
+        };
+        let info = ApiTokenInfo::new(id.clone(), &row);
+        let db = self.db.clone();
+        let key = id.clone();
+        blocking(move || {
+            let tokens = cf(&db, CF_API_TOKENS)?;
+            let mut batch = WriteBatch::default();
+            for item in db.iterator_cf(&tokens, IteratorMode::Start) {
+                let (key, value) = item?;
+                let expired = from_slice::<ApiTokenV1>(&value)
+                    .map_or(true, |token| token.expires_at_ms <= now);
+                if expired {
+                    batch.delete_cf(&tokens, &key);
+                }
+            }
+            batch.put_cf(&tokens, key.as_bytes(), to_vec(&row));
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await?;
+        Ok((format!("{API_TOKEN_PREFIX}{id}_{secret}"), info))
+    }
+
+    /// Resolves a live bearer token. Sync — used on the request path.
+    pub fn resolve_api_token(&self, token: &str) -> Option<ApiTokenInfo> {
+        let (id, secret) = token.strip_prefix(API_TOKEN_PREFIX)?.split_once('_')?;
+        let snapshot = self.snapshot.read().unwrap();
+        let row = snapshot.api_tokens.get(id)?;
+        // A disabled user's tokens pause with their keys and resume with them.
+        let live = row.expires_at_ms > now_ms()
+            && (row.is_builtin || !snapshot.disabled_users.contains(&row.username))
+            && constant_time_eq(&session_id(secret), &row.secret_hash);
+        live.then(|| ApiTokenInfo::new(id.to_string(), row))
+    }
+
+    /// Unexpired tokens, newest first.
+    pub fn list_api_tokens(&self) -> Vec<ApiTokenInfo> {
+        let now = now_ms();
+        let snapshot = self.snapshot.read().unwrap();
+        let mut tokens: Vec<ApiTokenInfo> = snapshot
+            .api_tokens
+            .iter()
+            .filter(|(_, row)| row.expires_at_ms > now)
+            .map(|(id, row)| ApiTokenInfo::new(id.clone(), row))
+            .collect();
+        tokens.sort_by_key(|t| std::cmp::Reverse(t.created_at_ms));
+        tokens
+    }
+
+    /// Revokes one token by id. `false` when there was no such token.
+    pub async fn revoke_api_token(&self, id: &str) -> Result<bool> {
+        if !self.snapshot.read().unwrap().api_tokens.contains_key(id) {
+            return Ok(false);
+        }
+        let db = self.db.clone();
+        let key = id.to_string();
+        blocking(move || {
+            let tokens = cf(&db, CF_API_TOKENS)?;
+            db.delete_cf_opt(&tokens, key.as_bytes(), &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        self.reload().await?;
+        Ok(true)
+    }
+
+    // ── share links ───────────────────────────────────────────────────────────
+
+    /// Records a new share link and returns it; the caller signs its id into
+    /// the URL. Links that ended more than [`SHARE_RETENTION_MS`] ago are swept
+    /// on the way, with their access records.
+    pub async fn create_share(
+        &self,
+        username: &str,
+        is_builtin: bool,
+        share: NewShare,
+    ) -> Result<ShareLink> {
+        if !is_builtin && !self.user_exists(username) {
+            return Err(StorageError::Io(format!("no such user {username}")));
+        }
+        let now = now_ms();
+        let id = random_hex(12);
+        let salt = random_hex(16);
+        let password_hash = share
+            .password
+            .filter(|password| !password.is_empty())
+            .map(|password| (password, salt.clone()));
+        let password_hash = match password_hash {
+            Some((password, salt)) => {
+                Some(blocking(move || Ok(pbkdf2_hex(&password, &salt))).await?)
+            }
+            None => None,
+        };
+        let row = ShareV1 {
+            v: ENTITY_VERSION,
+            username: username.to_string(),
+            is_builtin,
+            signer: share.signer,
+            bucket: share.bucket,
+            key: share.key,
+            password_hash,
+            salt,
+            max_downloads: share.max_downloads,
+            downloads: 0,
+            created_at_ms: now,
+            expires_at_ms: now + share.ttl_ms,
+            revoked_at_ms: None,
+            last_access_ms: None,
+        };
+        let stale: Vec<String> = self
+            .shares
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, share)| share.ended_at_ms() + SHARE_RETENTION_MS <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let db = self.db.clone();
+        let (key, value, swept) = (id.clone(), to_vec(&row), stale.clone());
+        blocking(move || {
+            let shares = cf(&db, CF_SHARES)?;
+            let share_access = cf(&db, CF_SHARE_ACCESS)?;
+            let mut batch = WriteBatch::default();
+            for id in swept {
+                delete_share_rows(&mut batch, &shares, &share_access, id.as_bytes());
+            }
+            batch.put_cf(&shares, key.as_bytes(), value);
+            db.write_opt(batch, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        let link = ShareLink::new(id.clone(), &row);
+        let mut shares = self.shares.lock().unwrap();
+        for id in &stale {
+            shares.remove(id);
+        }
+        shares.insert(id, row);
+        Ok(link)
+    }
+
+    /// Every recorded share link, newest first.
+    pub fn list_shares(&self) -> Vec<ShareLink> {
+        let mut links: Vec<ShareLink> = self
+            .shares
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, row)| ShareLink::new(id.clone(), row))
+            .collect();
+        links.sort_by_key(|link| std::cmp::Reverse(link.created_at_ms));
+        links
+    }
+
+    pub fn share(&self, id: &str) -> Option<ShareLink> {
+        let shares = self.shares.lock().unwrap();
+        shares.get(id).map(|row| ShareLink::new(id.to_string(), row))
+    }
+
+    /// Stops one link from working; it stays listed until swept. `false` when
+    /// there was no such link or it was already revoked.
+    pub async fn revoke_share(&self, id: &str) -> Result<bool> {
+        let row = {
+            let mut shares = self.shares.lock().unwrap();
+            match shares.get_mut(id) {
+                Some(row) if row.revoked_at_ms.is_none() => {
+                    row.revoked_at_ms = Some(now_ms());
+                    row.clone()
+                }
+                _ => return Ok(false),
+            }
+        };
+        let db = self.db.clone();
+        let (key, value) = (id.to_string(), to_vec(&row));
+        blocking(move || {
+            let shares = cf(&db, CF_SHARES)?;
+            db.put_cf_opt(&shares, key.as_bytes(), value, &sync_write())?;
+            Ok(())
+        })
+        .await?;
+        Ok(true)
+    }
+
+    /// Decides whether link `id`, presented signed by `signer`, may be used
+    /// now, and if so and `count` is set counts a download against it. Give
+    /// the download back with [`Self::refund_share_download`] if it then
+    /// fails. Uses that are not counted still end with the link's limit.
+    pub async fn open_share(
+        &self,
+        id: &str,
+        signer: &str,
+        password: Option<&str>,
+        count: bool,
+    ) -> std::result::Result<(), ShareDenial> {
+        let now = now_ms();
+        let row = self.shares.lock().unwrap().get(id).cloned();
+        let row = row.filter(|row| row.signer == signer).ok_or(ShareDenial::Unknown)?;
+        if row.revoked_at_ms.is_some() {
+            return Err(ShareDenial::Revoked);
+        }
+        if row.expires_at_ms <= now {
+            return Err(ShareDenial::Expired);
+        }
+        if let Some(hash) = row.password_hash {
+            let password = password.ok_or(ShareDenial::PasswordRequired)?.to_string();
+            let salt = row.salt;
+            let candidate = blocking(move || Ok(pbkdf2_hex(&password, &salt)))
+                .await
+                .map_err(|_| ShareDenial::PasswordRequired)?;
+            if !constant_time_eq(&candidate, &hash) {
+                return Err(ShareDenial::PasswordRequired);
+            }
+        }
+        // Checked again under the lock: a revocation or the last allowed
+        // download may have landed while the password was being hashed.
+        let mut shares = self.shares.lock().unwrap();
+        let row = shares.get_mut(id).ok_or(ShareDenial::Unknown)?;
+        if row.revoked_at_ms.is_some() {
+            return Err(ShareDenial::Revoked);
+        }
+        if row.max_downloads.is_some_and(|max| row.downloads >= max) {
+            return Err(ShareDenial::LimitReached);
+        }
+        if count {
+            row.downloads += 1;
+        }
+        row.last_access_ms = Some(now);
+        self.store_share(id, row);
+        Ok(())
+    }
+
+    /// Takes back the download [`Self::open_share`] counted, for a request
+    /// that did not succeed.
+    pub fn refund_share_download(&self, id: &str) {
+        let mut shares = self.shares.lock().unwrap();
+        if let Some(row) = shares.get_mut(id) {
+            row.downloads = row.downloads.saturating_sub(1);
+            self.store_share(id, row);
+        }
+    }
+
+    /// Writes a changed row from the request path, under the shares lock so
+    /// counts land in order. Unsynced, like a session touch: a crash can
+    /// only lose the last few counts.
+    fn store_share(&self, id: &str, row: &ShareV1) {
+        let written = cf(&self.db, CF_SHARES)
+            .and_then(|cf| Ok(self.db.put_cf(&cf, id.as_bytes(), to_vec(row))?));
+        if let Err(err) = written {
+            log::warn!("share link update failed: {err}");
+        }
+    }
+
+    /// Appends one use of link `id` to its access record. Best-effort and
+    /// unsynced; it runs after the response is already decided.
+    pub fn record_share_access(&self, id: &str, access: ShareAccess) {
+        if !self.shares.lock().unwrap().contains_key(id) {
+            return;
+        }
+        let mut key = id.as_bytes().to_vec();
+        key.push(SEP);
+        key.extend_from_slice(&access.at_ms.to_be_bytes());
+        // Orders records made within the same millisecond.
+        static SEQ: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+        let seq = SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        key.extend_from_slice(&seq.to_be_bytes());
+        let written = cf(&self.db, CF_SHARE_ACCESS)
+            .and_then(|cf| Ok(self.db.put_cf(&cf, key, to_vec(&access))?));
+        if let Err(err) = written {
+            log::warn!("share link access record failed: {err}");
+        }
+    }
+
+    /// The newest uses of link `id`, newest first.
+    pub async fn share_accesses(&self, id: &str) -> Result<Vec<ShareAccess>> {
         let db = self.db.clone();
-        let username_owned = username.to_string();
-        let stored = blocking(move || {
-            let users = cf(&db, CF_USERS)?;
-            match db.get_cf(&users, username_owned.as_bytes())? {
-                Some(value) => {
-                    let user: UserV1 = from_slice(&value)?;
-                    reject_newer(user.v, "iam user")?;
-                    Ok(Some((user.password_hash, user.salt)))
+        let mut prefix = id.as_bytes().to_vec();
+        prefix.push(SEP);
+        blocking(move || {
+            let family = cf(&db, CF_SHARE_ACCESS)?;
+            let mut end = prefix.clone();
+            *end.last_mut().unwrap() += 1;
+            let mut accesses = Vec::new();
+            for item in db.iterator_cf(&family, IteratorMode::From(&end, Direction::Reverse)) {
+                let (key, value) = item?;
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                if let Ok(access) = from_slice::<ShareAccess>(&value) {
+                    accesses.push(access);
+                }
+                if accesses.len() == SHARE_ACCESS_LISTED {
+                    break;
                 }
-                None => Ok(None),
             }
+            Ok(accesses)
         })
-        .await?;
-        let Some((hash, salt)) = stored else {
-            return Ok(false);
-        };
-        let candidate = pbkdf2_hex(password, &salt);
-        Ok(constant_time_eq(&candidate, &hash))
+        .await
     }
 
-    // ── access keys ───────────────────────────────────────────────────────────
+    // ── two-factor authentication ─────────────────────────────────────────────
+
+    pub async fn mfa_status(&self, username: &str) -> Result<MfaStatus> {
+        self.with_mfa(username, |row| {
+            Ok(match row {
+                Some(mfa) if mfa.enabled => MfaStatus {
+                    enabled: true,
+                    recovery_codes_left: mfa.recovery_hashes.len(),
+                    enabled_at_ms: Some(mfa.created_at_ms),
+                },
+                _ => MfaStatus::default(),
+            })
+        })
+        .await
+    }
 
-    pub async fn create_access_key(&self, username: &str) -> Result<AccessKey> {
+    /// Starts enrollment with a fresh secret, replacing any earlier attempt
+    /// that was never confirmed, and returns the secret as base32. Fails when
+    /// two-factor is already on: it has to be turned off first.
+    pub async fn begin_mfa_enrollment(&self, username: &str) -> Result<String> {
         if !self.user_exists(username) {
             return Err(StorageError::Io(format!("no such user {username}")));
         }
-        let key = AccessKey {
-            access_key: format!("RSAK{}", random_hex(8).to_uppercase()),
-            secret_key: random_hex(20),
-            username: username.to_string(),
-            created_at_ms: now_ms(),
-        };
-        let db = self.db.clone();
-        let stored = key.clone();
-        blocking(move || {
-            let access = cf(&db, CF_ACCESS_KEYS)?;
-            let value = AccessKeyV1 {
+        let username_owned = username.to_string();
+        self.with_mfa(username, move |row| {
+            if row.as_ref().is_some_and(|mfa| mfa.enabled) {
+                return Err(StorageError::Io(format!(
+                    "two-factor authentication is already enabled for {username_owned}"
+                )));
+            }
+            let secret = totp::base32_encode(&totp::generate_secret());
+            *row = Some(MfaV1 {
                 v: ENTITY_VERSION,
-                access_key: stored.access_key.clone(),
-                secret_key: stored.secret_key,
-                username: stored.username,
-                created_at_ms: stored.created_at_ms,
+                secret: secret.clone(),
+                enabled: false,
+                recovery_hashes: Vec::new(),
+                last_step: 0,
+                created_at_ms: now_ms(),
+            });
+            Ok(secret)
+        })
+        .await
+    }
+
+    /// Finishes enrollment if `code` matches the pending secret, returning
+    /// the new recovery codes (the only time they exist in clear). `None`
+    /// for a wrong code, or when no enrollment is pending.
+    pub async fn confirm_mfa(&self, username: &str, code: &str) -> Result<Option<Vec<String>>> {
+        let code = code.to_string();
+        self.with_mfa(username, move |row| {
+            let Some(mfa) = row.as_mut().filter(|mfa| !mfa.enabled) else {
+                return Ok(None);
             };
-            db.put_cf_opt(&access, stored.access_key.as_bytes(), to_vec(&value), &sync_write())?;
-            Ok(())
+            let secret = totp::base32_decode(&mfa.secret).unwrap_or_default();
+            let Some(step) = totp::verify(&secret, &code, totp::now_secs(), 0) else {
+                return Ok(None);
+            };
+            let codes = totp::recovery_codes();
+            mfa.v = ENTITY_VERSION;
+            mfa.enabled = true;
+            mfa.last_step = step;
+            mfa.created_at_ms = now_ms();
+            mfa.recovery_hashes = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+            Ok(Some(codes))
         })
-        .await?;
-        self.reload().await?;
-        Ok(key)
+        .await
     }
 
-    pub async fn delete_access_key(&self, access_key: &str) -> Result<()> {
-        let db = self.db.clone();
-        let access_key_owned = access_key.to_string();
-        blocking(move || {
-            let access = cf(&db, CF_ACCESS_KEYS)?;
-            db.delete_cf_opt(&access, access_key_owned.as_bytes(), &sync_write())?;
-            Ok(())
+    /// Checks a login's second factor: a current authenticator code, or one
+    /// of the recovery codes, which matching uses up. False when the user
+    /// has no confirmed enrollment.
+    pub async fn verify_mfa(&self, username: &str, code: &str) -> Result<bool> {
+        let code = code.to_string();
+        self.with_mfa(username, move |row| {
+            let Some(mfa) = row.as_mut().filter(|mfa| mfa.enabled) else {
+                return Ok(false);
+            };
+            reject_newer(mfa.v, "mfa enrollment")?;
+            let secret = totp::base32_decode(&mfa.secret).unwrap_or_default();
+            let now = totp::now_secs();
+            if let Some(step) = totp::verify(&secret, &code, now, mfa.last_step) {
+                mfa.last_step = step;
+                return Ok(true);
+            }
+            let hash = totp::hash_recovery_code(&code);
+            let before = mfa.recovery_hashes.len();
+            mfa.recovery_hashes.retain(|stored| !constant_time_eq(stored, &hash));
+            Ok(mfa.recovery_hashes.len() < before)
         })
-        .await?;
-        self.reload().await
+        .await
     }
 
-    pub async fn list_access_keys(&self, username: &str) -> Result<Vec<AccessKey>> {
+    /// Users with confirmed two-factor enrollment, for the user list.
+    pub async fn mfa_enrolled_users(&self) -> Result<HashSet<String>> {
         let db = self.db.clone();
-        let username_owned = username.to_string();
         blocking(move || {
-            let access = cf(&db, CF_ACCESS_KEYS)?;
-            let mut keys = Vec::new();
-            for item in db.iterator_cf(&access, IteratorMode::Start) {
+            let family = cf(&db, CF_MFA)?;
+            let mut enrolled = HashSet::new();
+            for item in db.iterator_cf(&family, IteratorMode::Start) {
                 let (key, value) = item?;
-                let ak: AccessKeyV1 = from_slice(&value)?;
-                reject_newer(ak.v, "access key")?;
-                if ak.username == username_owned {
-                    keys.push(AccessKey {
-                        access_key: String::from_utf8_lossy(&key).into_owned(),
-                        secret_key: ak.secret_key,
-                        username: ak.username,
-                        created_at_ms: ak.created_at_ms,
-                    });
+                if from_slice::<MfaV1>(&value)?.enabled {
+                    enrolled.insert(String::from_utf8_lossy(&key).into_owned());
                 }
             }
-            keys.sort_by_key(|k| k.created_at_ms);
-            Ok(keys)
+            Ok(enrolled)
         })
         .await
     }
 
-    // ── raw export / import (backup, restore, migrate) ────────────────────────
-
-    /// Dumps the entire global IAM database (all column families) to an
-    /// in-memory buffer as a length-delimited protobuf stream of `(cf, key,
-    /// value)` rows. See [`crate::storage::rawdb`]. Read-only and consistent
-    /// (single snapshot); safe against the live store, so the console serves it
-    /// straight into an HTTP download.
-    pub async fn export_raw(&self) -> Result<Vec<u8>> {
-        let db = self.db.clone();
-        blocking(move || {
-            let mut buf = Vec::new();
-            crate::storage::rawdb::export(
-                &db,
-                &[CF_USERS, CF_GROUPS, CF_ACCESS_KEYS, CF_WEB_KEYS, CF_USER_GROUPS],
-                &mut buf,
-            )?;
-            Ok(buf)
+    /// Replaces the recovery codes, invalidating the old ones.
+    pub async fn regenerate_recovery_codes(&self, username: &str) -> Result<Vec<String>> {
+        let username_owned = username.to_string();
+        self.with_mfa(username, move |row| {
+            let Some(mfa) = row.as_mut().filter(|mfa| mfa.enabled) else {
+                return Err(StorageError::Io(format!(
+                    "two-factor authentication is not enabled for {username_owned}"
+                )));
+            };
+            let codes = totp::recovery_codes();
+            mfa.recovery_hashes = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+            Ok(codes)
         })
         .await
     }
 
-    /// Imports a dump produced by [`export_raw`](Self::export_raw), upserting
-    /// every row (`put`) in one atomic batch. No family is cleared, so importing
-    /// onto a fresh data directory reproduces the source exactly and onto a
-    /// populated one merges. `Replace` erases the IAM families first for an exact
-    /// restore. The apply is one atomic batch. Refreshes the in-memory snapshot
-    /// afterward. Returns a per-family
-    /// [`ImportReport`](crate::storage::rawdb::ImportReport) so the caller can
-    /// show exactly what landed.
-    pub async fn import_raw(
-        &self,
-        dump: Vec<u8>,
-        mode: crate::storage::rawdb::ImportMode,
-    ) -> Result<crate::storage::rawdb::ImportReport> {
+    /// Removes the user's enrollment, confirmed or not. Returns whether there
+    /// was one.
+    pub async fn disable_mfa(&self, username: &str) -> Result<bool> {
+        self.with_mfa(username, |row| Ok(row.take().is_some())).await
+    }
+
+    /// Runs `update` on the user's `mfa` row under the lock and writes back
+    /// whatever it leaves: a changed row is put, a removed one deleted.
+    async fn with_mfa<T, F>(&self, username: &str, update: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Option<MfaV1>) -> Result<T> + Send + 'static,
+    {
         let db = self.db.clone();
-        let report = blocking(move || {
-            crate::storage::rawdb::import(
-                &db,
-                dump.as_slice(),
-                mode,
-                &[CF_USERS, CF_GROUPS, CF_ACCESS_KEYS, CF_WEB_KEYS, CF_USER_GROUPS],
-            )
+        let lock = self.mfa_lock.clone();
+        let username = username.to_string();
+        blocking(move || {
+            let _guard = lock.lock().unwrap();
+            let family = cf(&db, CF_MFA)?;
+            let stored = db.get_cf(&family, username.as_bytes())?;
+            let mut row = match &stored {
+                Some(value) => Some(from_slice::<MfaV1>(value)?),
+                None => None,
+            };
+            let out = update(&mut row)?;
+            match row {
+                Some(row) => {
+                    let value = to_vec(&row);
+                    if stored.as_deref() != Some(value.as_slice()) {
+                        db.put_cf_opt(&family, username.as_bytes(), value, &sync_write())?;
+                    }
+                }
+                None if stored.is_some() => {
+                    db.delete_cf_opt(&family, username.as_bytes(), &sync_write())?;
+                }
+                None => {}
+            }
+            Ok(out)
         })
-        .await?;
-        self.reload().await?;
-        Ok(report)
+        .await
     }
+}
 
-    // ── web sessions ──────────────────────────────────────────────────────────
+/// The id a session is stored and listed under: the hex SHA-256 of its token.
+pub fn session_id(token: &str) -> String {
+    sha2::Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
 
-    pub fn create_session(&self, username: &str, is_root: bool) -> String {
-        let token = random_hex(32);
-        self.sessions.lock().unwrap().insert(
-            token.clone(),
-            Session {
-                username: username.to_string(),
-                is_root,
-                expires_at_ms: now_ms() + SESSION_TTL_MS,
-            },
-        );
-        token
+/// Reads the `sessions` family at startup. Rows from a newer build are
+/// skipped rather than failing the open: losing a login is cheap.
+fn load_sessions(db: &Db) -> Result<HashMap<String, Session>> {
+    let family = cf(db, CF_SESSIONS)?;
+    let mut sessions = HashMap::new();
+    for item in db.iterator_cf(&family, IteratorMode::Start) {
+        let (key, value) = item?;
+        let Ok(row) = from_slice::<SessionV1>(&value) else {
+            continue;
+        };
+        if row.v > ENTITY_VERSION {
+            continue;
+        }
+        let id = String::from_utf8_lossy(&key).into_owned();
+        let stored_seen_ms = row.last_seen_ms;
+        sessions.insert(id, Session { row, stored_seen_ms });
     }
+    Ok(sessions)
+}
 
-    /// Returns `(username, is_root)` for a live session token.
-    pub fn resolve_session(&self, token: &str) -> Option<(String, bool)> {
-        let mut sessions = self.sessions.lock().unwrap();
-        let now = now_ms();
-        sessions.retain(|_, s| s.expires_at_ms > now);
-        sessions.get(token).map(|s| (s.username.clone(), s.is_root))
+/// Reads the `shares` family at startup, skipping rows from a newer build.
+fn load_shares(db: &Db) -> Result<HashMap<String, ShareV1>> {
+    let family = cf(db, CF_SHARES)?;
+    let mut shares = HashMap::new();
+    for item in db.iterator_cf(&family, IteratorMode::Start) {
+        let (key, value) = item?;
+        let Ok(row) = from_slice::<ShareV1>(&value) else {
+            continue;
+        };
+        if row.v > ENTITY_VERSION {
+            continue;
+        }
+        shares.insert(String::from_utf8_lossy(&key).into_owned(), row);
     }
+    Ok(shares)
+}
 
-    pub fn destroy_session(&self, token: &str) {
-        self.sessions.lock().unwrap().remove(token);
-    }
+/// Queues the deletion of share link `id` and its whole access record.
+fn delete_share_rows(
+    batch: &mut WriteBatch,
+    shares: &Arc<rocksdb::BoundColumnFamily<'_>>,
+    share_access: &Arc<rocksdb::BoundColumnFamily<'_>>,
+    id: &[u8],
+) {
+    let mut from = id.to_vec();
+    from.push(SEP);
+    let mut to = id.to_vec();
+    to.push(SEP + 1);
+    batch.delete_range_cf(share_access, from, to);
+    batch.delete_cf(shares, id);
 }
 
 impl std::fmt::Debug for IamStore {
@@ -1094,6 +2983,18 @@ impl std::fmt::Debug for IamStore {
     }
 }
 
+/// Server snapshots carry `admin.rocksdb` alongside the bucket indexes.
+impl CheckpointSource for IamStore {
+    fn name(&self) -> &str {
+        "admin.rocksdb"
+    }
+
+    fn checkpoint(&self, dest: &Path) -> Result<()> {
+        rocksdb::checkpoint::Checkpoint::new(&self.db)?.create_checkpoint(dest)?;
+        Ok(())
+    }
+}
+
 fn validate_username(username: &str) -> Result<()> {
     let ok = !username.is_empty()
         && username.len() <= 64
@@ -1107,6 +3008,81 @@ fn validate_username(username: &str) -> Result<()> {
     }
 }
 
+fn validate_policy_name(name: &str) -> Result<()> {
+    let ok = !name.is_empty()
+        && name.len() <= 128
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+=,.@_-".contains(c));
+    if ok {
+        Ok(())
+    } else {
+        Err(StorageError::Io(format!("invalid policy name {name:?}")))
+    }
+}
+
+fn validate_access_key(access_key: &str) -> Result<()> {
+    let ok = (3..=128).contains(&access_key.len())
+        && access_key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if ok {
+        Ok(())
+    } else {
+        Err(StorageError::Io(format!("invalid access key {access_key:?}")))
+    }
+}
+
+fn validate_secret_key(secret_key: &str) -> Result<()> {
+    if (8..=128).contains(&secret_key.len()) {
+        Ok(())
+    } else {
+        Err(StorageError::Io("secret key must be 8 to 128 characters".into()))
+    }
+}
+
+fn dedup_names(names: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .iter()
+        .filter(|name| seen.insert(name.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// The merged statements of the named policies `names`, as a user's or
+/// group's `policy_json`; `pending` stands in for a policy being written in
+/// the same batch. Runs on the blocking pool.
+fn merge_named(
+    db: &Db,
+    names: &[String],
+    pending: Option<(&str, &PolicyDocument)>,
+) -> Result<Option<String>> {
+    let policies = cf(db, CF_POLICIES)?;
+    let mut statements = Vec::new();
+    for name in names {
+        let policy = match (builtin_policy(name), pending) {
+            (Some(policy), _) => policy,
+            (None, Some((pending_name, policy))) if pending_name == name => policy.clone(),
+            _ => {
+                let Some(value) = db.get_cf(&policies, name.as_bytes())? else {
+                    return Err(StorageError::Io(format!("no such policy {name}")));
+                };
+                let row: NamedPolicyV1 = from_slice(&value)?;
+                reject_newer(row.v, "named policy")?;
+                serde_json::from_str(&row.policy_json)?
+            }
+        };
+        statements.extend(policy.statement);
+    }
+    Ok((!statements.is_empty()).then(|| {
+        to_json(&PolicyDocument {
+            version: "2012-10-17".to_string(),
+            statement: statements,
+        })
+    }))
+}
+
 fn validate_group_name(name: &str) -> Result<()> {
     let ok = !name.is_empty()
         && name.len() <= 64
@@ -1120,7 +3096,7 @@ fn validate_group_name(name: &str) -> Result<()> {
     }
 }
 
-fn random_hex(bytes: usize) -> String {
+pub(crate) fn random_hex(bytes: usize) -> String {
     let mut buf = vec![0u8; bytes];
     rand::thread_rng().fill_bytes(&mut buf);
     buf.iter().map(|b| format!("{b:02x}")).collect()
@@ -1161,6 +3137,17 @@ mod tests {
     use super::*;
     use crate::server::policy::{is_authorized, Requirement};
 
+    const TIMEOUTS: SessionTimeouts = SessionTimeouts {
+        idle_ms: 60 * 60 * 1000,
+        max_age_ms: 12 * 60 * 60 * 1000,
+    };
+
+    async fn login(iam: &IamStore, username: &str, is_root: bool) -> String {
+        iam.create_session(username, is_root, SessionClient::default())
+            .await
+            .unwrap()
+    }
+
     async fn open_tmp() -> (tempfile::TempDir, IamStore) {
         let tmp = tempfile::tempdir().unwrap();
         let store = IamStore::open(tmp.path()).await.unwrap();
@@ -1219,6 +3206,28 @@ mod tests {
         assert!(iam.find_key(&key.access_key).is_none());
     }
 
+    #[tokio::test]
+    async fn inactive_keys_stop_signing_and_rotation_replaces_the_key() {
+        let (_tmp, iam) = open_tmp().await;
+        iam.create_user("erin", "password123").await.unwrap();
+        let key = iam.create_access_key("erin").await.unwrap();
+
+        iam.set_access_key_active(&key.access_key, false).await.unwrap();
+        assert!(iam.find_key(&key.access_key).is_none());
+        assert_eq!(iam.key_owner(&key.access_key).as_deref(), Some("erin"));
+        assert!(!iam.list_access_keys("erin").await.unwrap()[0].active);
+        iam.set_access_key_active(&key.access_key, true).await.unwrap();
+        assert_eq!(iam.find_key(&key.access_key).unwrap().0, key.secret_key);
+
+        let rotated = iam.rotate_access_key(&key.access_key).await.unwrap();
+        assert_ne!(rotated.access_key, key.access_key);
+        assert!(iam.key_owner(&key.access_key).is_none());
+        assert_eq!(iam.find_key(&rotated.access_key).unwrap(), (rotated.secret_key, "erin".into()));
+        let keys = iam.list_access_keys("erin").await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert!(iam.rotate_access_key("RSAKMISSING").await.is_err());
+    }
+
     #[tokio::test]
     async fn state_survives_reopen() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1236,23 +3245,76 @@ mod tests {
     async fn sessions_expire_and_are_destroyed_with_user() {
         let (_tmp, iam) = open_tmp().await;
         iam.create_user("dave", "password123").await.unwrap();
-        let token = iam.create_session("dave", false);
-        assert_eq!(iam.resolve_session(&token), Some(("dave".to_string(), false)));
+        let token = login(&iam, "dave", false).await;
+        assert_eq!(iam.resolve_session(&token, TIMEOUTS), Some(("dave".to_string(), false)));
+        let aged = SessionTimeouts { max_age_ms: 0, ..TIMEOUTS };
+        assert_eq!(iam.resolve_session(&token, aged), None);
+        // An expired session is gone, not merely refused.
+        assert_eq!(iam.resolve_session(&token, TIMEOUTS), None);
+
+        let token = login(&iam, "dave", false).await;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let idle = SessionTimeouts { idle_ms: 10, ..TIMEOUTS };
+        assert_eq!(iam.resolve_session(&token, idle), None);
+
+        let token = login(&iam, "dave", false).await;
         iam.delete_user("dave").await.unwrap();
-        assert_eq!(iam.resolve_session(&token), None);
+        assert_eq!(iam.resolve_session(&token, TIMEOUTS), None);
     }
 
     #[tokio::test]
     async fn password_reset_replaces_credentials_and_invalidates_sessions() {
         let (_tmp, iam) = open_tmp().await;
         iam.create_user("erin", "old-password").await.unwrap();
-        let token = iam.create_session("erin", false);
+        let token = login(&iam, "erin", false).await;
 
         iam.set_password("erin", "new-password").await.unwrap();
 
         assert!(!iam.verify_password("erin", "old-password").await.unwrap());
         assert!(iam.verify_password("erin", "new-password").await.unwrap());
-        assert_eq!(iam.resolve_session(&token), None);
+        assert_eq!(iam.resolve_session(&token, TIMEOUTS), None);
+    }
+
+    #[tokio::test]
+    async fn sessions_survive_reopen_until_revoked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let client = SessionClient {
+            ip: Some("192.0.2.7".to_string()),
+            user_agent: Some("curl/8".to_string()),
+        };
+        let (kept, revoked, other) = {
+            let iam = IamStore::open(tmp.path()).await.unwrap();
+            let kept = iam.create_session("admin", true, client).await.unwrap();
+            (kept, login(&iam, "admin", true).await, login(&iam, "gail", false).await)
+        };
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        assert_eq!(iam.resolve_session(&kept, TIMEOUTS), Some(("admin".to_string(), true)));
+
+        let listed = iam.list_sessions(TIMEOUTS).await.unwrap();
+        assert_eq!(listed.len(), 3);
+        let first = listed.iter().find(|s| s.id == session_id(&kept)).unwrap();
+        assert_eq!(first.ip.as_deref(), Some("192.0.2.7"));
+        assert_eq!(first.user_agent.as_deref(), Some("curl/8"));
+        assert!(first.is_builtin);
+        // Only the hash is stored; the token itself is never listed.
+        assert!(listed.iter().all(|s| s.id != kept && s.id.len() == 64));
+
+        assert!(iam.revoke_session(&session_id(&revoked)).await.unwrap());
+        assert!(!iam.revoke_session(&session_id(&revoked)).await.unwrap());
+        assert_eq!(iam.resolve_session(&revoked, TIMEOUTS), None);
+        assert_eq!(iam.revoke_user_sessions("gail").await.unwrap(), 1);
+        assert_eq!(iam.resolve_session(&other, TIMEOUTS), None);
+        drop(iam);
+
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        let ids: Vec<String> = iam
+            .list_sessions(TIMEOUTS)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, [session_id(&kept)]);
     }
 
     #[test]
@@ -1298,6 +3360,7 @@ mod tests {
         assert!(iam.is_admin("grace"));
         let effective = iam.policy_for("grace").unwrap();
         assert!(is_authorized(&effective, &[Requirement::object("s3:DeleteObject", "any", "key")]));
+        assert!(is_authorized(&effective, &[Requirement::admin("admin:ManageUsers")]));
         assert!(iam.set_group_policy(Group::Admin.name(), None).await.is_err());
         assert!(iam.delete_group(Group::Admin.name()).await.is_err());
     }
@@ -1370,6 +3433,310 @@ mod tests {
         assert!(target.import_raw(truncated, ImportMode::Merge).await.is_err());
     }
 
+    #[tokio::test]
+    async fn mfa_enrollment_codes_and_recovery() {
+        let (tmp, iam) = open_tmp().await;
+        iam.create_user("alice", "password123").await.unwrap();
+        assert!(!iam.mfa_status("alice").await.unwrap().enabled);
+        assert!(iam.begin_mfa_enrollment("nobody").await.is_err());
+
+        let secret = iam.begin_mfa_enrollment("alice").await.unwrap();
+        let secret = totp::base32_decode(&secret).unwrap();
+        let code = |step: u64| format!("{:06}", totp::code_at(&secret, step));
+        let step = totp::now_secs() / 30;
+        // Nothing counts until enrollment is confirmed.
+        assert!(!iam.verify_mfa("alice", &code(step)).await.unwrap());
+        assert_eq!(iam.confirm_mfa("alice", "000000x").await.unwrap(), None);
+        let recovery = iam.confirm_mfa("alice", &code(step)).await.unwrap().unwrap();
+        assert_eq!(recovery.len(), totp::RECOVERY_CODES);
+        assert!(iam.begin_mfa_enrollment("alice").await.is_err(), "already enabled");
+
+        // The confirming code, and anything before it, cannot be replayed.
+        assert!(!iam.verify_mfa("alice", &code(step)).await.unwrap());
+        assert!(iam.verify_mfa("alice", &code(step + 1)).await.unwrap());
+        assert!(!iam.verify_mfa("alice", &code(step + 1)).await.unwrap());
+
+        // Recovery codes work once each, and the state survives a reopen.
+        assert!(iam.verify_mfa("alice", &recovery[0].to_uppercase()).await.unwrap());
+        assert!(!iam.verify_mfa("alice", &recovery[0]).await.unwrap());
+        drop(iam);
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        let status = iam.mfa_status("alice").await.unwrap();
+        assert!(status.enabled);
+        assert_eq!(iam.mfa_enrolled_users().await.unwrap(), HashSet::from(["alice".into()]));
+        assert_eq!(status.recovery_codes_left, totp::RECOVERY_CODES - 1);
+        let fresh = iam.regenerate_recovery_codes("alice").await.unwrap();
+        assert!(!iam.verify_mfa("alice", &recovery[1]).await.unwrap());
+        assert!(iam.verify_mfa("alice", &fresh[1]).await.unwrap());
+
+        // Enrollment is IAM data: it is exported, and it dies with the user.
+        let dst = tempfile::tempdir().unwrap();
+        let restored = IamStore::open(dst.path()).await.unwrap();
+        let dump = iam.export_raw().await.unwrap();
+        restored.import_raw(dump, crate::storage::rawdb::ImportMode::Merge).await.unwrap();
+        assert!(restored.mfa_status("alice").await.unwrap().enabled);
+        iam.delete_user("alice").await.unwrap();
+        assert!(!iam.mfa_status("alice").await.unwrap().enabled);
+        assert!(!iam.disable_mfa("alice").await.unwrap());
+        assert!(restored.disable_mfa("alice").await.unwrap());
+        assert!(!restored.verify_mfa("alice", &fresh[2]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn external_users_are_provisioned_and_follow_their_directory_groups() {
+        let (_tmp, iam) = open_tmp().await;
+        iam.create_group("readers", None).await.unwrap();
+        let groups = ["admin".to_string(), "ghosts".to_string()];
+        assert!(iam.sync_external_user("dana", false, None).await.is_err());
+        let sync = iam.sync_external_user("dana", true, Some(&groups)).await.unwrap();
+        assert!(sync.created);
+        assert_eq!(sync.groups, Some(vec!["admin".to_string()]));
+        assert_eq!(sync.missing_groups, vec!["ghosts".to_string()]);
+        assert!(iam.is_admin("dana"));
+
+        // Unchanged groups are not rewritten; `None` leaves them alone.
+        let again = iam.sync_external_user("dana", true, Some(&groups)).await.unwrap();
+        assert_eq!(again.groups, None);
+        assert!(!again.created);
+        assert_eq!(iam.sync_external_user("dana", false, None).await.unwrap().groups, None);
+        let sync = iam.sync_external_user("dana", false, Some(&["Readers".into()])).await.unwrap();
+        assert_eq!(sync.groups, Some(vec!["Readers".to_string()]));
+        assert_eq!(iam.groups_for("dana"), vec!["readers".to_string()]);
+        assert!(!iam.is_admin("dana"));
+    }
+
+    #[tokio::test]
+    async fn temporary_credentials_expire_and_die_with_their_user() {
+        let (tmp, iam) = open_tmp().await;
+        assert!(iam.issue_temporary_credentials("nobody", 60_000).await.is_err());
+        iam.create_user("erin", "password123").await.unwrap();
+        let creds = iam.issue_temporary_credentials("erin", 60_000).await.unwrap();
+        let (secret, owner, token) = iam.find_temporary_key(&creds.access_key).unwrap();
+        assert_eq!((secret, owner, token), (creds.secret_key, "erin".into(), creds.session_token));
+        assert!(iam.find_key(&creds.access_key).is_none(), "not a long-term key");
+
+        // Expired credentials stop resolving, and the next issue sweeps them.
+        let stale = iam.issue_temporary_credentials("erin", -1).await.unwrap();
+        assert!(iam.find_temporary_key(&stale.access_key).is_none());
+        iam.issue_temporary_credentials("erin", 60_000).await.unwrap();
+        let sts = cf(&iam.db, CF_STS).unwrap();
+        assert!(iam.db.get_cf(&sts, stale.access_key.as_bytes()).unwrap().is_none());
+        drop(sts);
+
+        // They survive a reopen, are not exported, and go with the user.
+        drop(iam);
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        assert!(iam.find_temporary_key(&creds.access_key).is_some());
+        let dst = tempfile::tempdir().unwrap();
+        let restored = IamStore::open(dst.path()).await.unwrap();
+        let dump = iam.export_raw().await.unwrap();
+        restored.import_raw(dump, crate::storage::rawdb::ImportMode::Merge).await.unwrap();
+        assert!(restored.find_temporary_key(&creds.access_key).is_none());
+        iam.delete_user("erin").await.unwrap();
+        assert!(iam.find_temporary_key(&creds.access_key).is_none());
+    }
+
+    #[tokio::test]
+    async fn api_tokens_resolve_until_revoked_expired_or_orphaned() {
+        let (tmp, iam) = open_tmp().await;
+        let scopes = vec!["admin:ManageUsers".to_string()];
+        assert!(iam.create_api_token("nobody", false, "ci", vec![], 60_000).await.is_err());
+        iam.create_user("fay", "password123").await.unwrap();
+        let (token, info) =
+            iam.create_api_token("fay", false, "ci", scopes.clone(), 60_000).await.unwrap();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        let resolved = iam.resolve_api_token(&token).unwrap();
+        assert_eq!((resolved.id.as_str(), resolved.username.as_str()), (info.id.as_str(), "fay"));
+        assert_eq!(resolved.scopes, scopes);
+        assert!(iam.resolve_api_token(&format!("{token}0")).is_none(), "wrong secret");
+        assert!(iam.resolve_api_token(&format!("{API_TOKEN_PREFIX}{}_x", info.id)).is_none());
+        iam.set_user_disabled("fay", true).await.unwrap();
+        assert!(iam.resolve_api_token(&token).is_none(), "owner disabled");
+        iam.set_user_disabled("fay", false).await.unwrap();
+        assert!(iam.resolve_api_token(&token).is_some());
+
+        // Expired tokens stop resolving and are not listed.
+        let (stale, _) = iam.create_api_token("admin", true, "old", vec![], -1).await.unwrap();
+        assert!(iam.resolve_api_token(&stale).is_none());
+        assert_eq!(iam.list_api_tokens().len(), 1);
+
+        // Tokens survive a reopen, are not exported, and go with their user.
+        drop(iam);
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        assert!(iam.resolve_api_token(&token).is_some());
+        let dst = tempfile::tempdir().unwrap();
+        let restored = IamStore::open(dst.path()).await.unwrap();
+        let dump = iam.export_raw().await.unwrap();
+        restored.import_raw(dump, crate::storage::rawdb::ImportMode::Merge).await.unwrap();
+        assert!(restored.resolve_api_token(&token).is_none());
+        let (builtin, root) =
+            iam.create_api_token("admin", true, "ops", vec![], 60_000).await.unwrap();
+        iam.delete_user("fay").await.unwrap();
+        assert!(iam.resolve_api_token(&token).is_none());
+        assert!(iam.resolve_api_token(&builtin).is_some());
+        assert!(iam.revoke_api_token(&root.id).await.unwrap());
+        assert!(!iam.revoke_api_token(&root.id).await.unwrap());
+        assert!(iam.resolve_api_token(&builtin).is_none());
+    }
+
+    #[tokio::test]
+    async fn share_links_count_downloads_until_limited_or_revoked() {
+        let (tmp, iam) = open_tmp().await;
+        iam.create_user("sid", "password123").await.unwrap();
+        let (signer, _) = iam.web_key_for("sid", false).await.unwrap();
+        let new_share = |password: Option<&str>, max_downloads| NewShare {
+            bucket: "reports".into(),
+            key: "q3.pdf".into(),
+            signer: signer.clone(),
+            ttl_ms: 60_000,
+            password: password.map(str::to_string),
+            max_downloads,
+        };
+        let limited = iam.create_share("sid", false, new_share(None, Some(2))).await.unwrap();
+        let id = limited.id.as_str();
+        assert_eq!(iam.open_share(id, "RSWEB_other", None, true).await, Err(ShareDenial::Unknown));
+        assert_eq!(iam.open_share("nope", &signer, None, true).await, Err(ShareDenial::Unknown));
+        assert_eq!(iam.open_share(id, &signer, None, true).await, Ok(()));
+        // An uncounted use passes the checks without spending a download.
+        assert_eq!(iam.open_share(id, &signer, None, false).await, Ok(()));
+        assert_eq!(iam.share(id).unwrap().downloads, 1);
+        // A failed download is given back.
+        assert_eq!(iam.open_share(id, &signer, None, true).await, Ok(()));
+        iam.refund_share_download(id);
+        assert_eq!(iam.open_share(id, &signer, None, true).await, Ok(()));
+        assert_eq!(iam.open_share(id, &signer, None, true).await, Err(ShareDenial::LimitReached));
+        assert_eq!(iam.open_share(id, &signer, None, false).await, Err(ShareDenial::LimitReached));
+        assert_eq!(iam.share(id).unwrap().downloads, 2);
+
+        let locked = iam.create_share("sid", false, new_share(Some("s3cret"), None)).await.unwrap();
+        assert!(locked.has_password);
+        let locked_id = locked.id.as_str();
+        let denied = Err(ShareDenial::PasswordRequired);
+        assert_eq!(iam.open_share(locked_id, &signer, None, true).await, denied);
+        assert_eq!(iam.open_share(locked_id, &signer, Some("guess"), true).await, denied);
+        assert_eq!(iam.open_share(locked_id, &signer, Some("s3cret"), true).await, Ok(()));
+        assert!(iam.revoke_share(locked_id).await.unwrap());
+        assert!(!iam.revoke_share(locked_id).await.unwrap());
+        let revoked = Err(ShareDenial::Revoked);
+        assert_eq!(iam.open_share(locked_id, &signer, Some("s3cret"), true).await, revoked);
+
+        let expired = NewShare { ttl_ms: -1, ..new_share(None, None) };
+        let expired = iam.create_share("sid", false, expired).await.unwrap();
+        let opened = iam.open_share(&expired.id, &signer, None, true).await;
+        assert_eq!(opened, Err(ShareDenial::Expired));
+
+        iam.record_share_access(id, ShareAccess { at_ms: 1, status: 200, ..Default::default() });
+        iam.record_share_access(id, ShareAccess { at_ms: 2, status: 403, ..Default::default() });
+        let accesses = iam.share_accesses(id).await.unwrap();
+        assert_eq!(accesses.iter().map(|a| a.status).collect::<Vec<_>>(), vec![403, 200]);
+        assert!(iam.share_accesses(locked_id).await.unwrap().is_empty());
+
+        // Links survive a reopen, and go with their user.
+        drop(iam);
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        assert_eq!(iam.list_shares().len(), 3);
+        assert_eq!(iam.share(id).unwrap().downloads, 2);
+        assert!(iam.share(locked_id).unwrap().revoked_at_ms.is_some());
+        iam.delete_user("sid").await.unwrap();
+        assert!(iam.list_shares().is_empty());
+        assert!(iam.share_accesses(id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn named_policies_follow_their_edits_and_stay_while_attached() {
+        let (_tmp, iam) = open_tmp().await;
+        iam.create_user("hank", "password123").await.unwrap();
+        iam.create_group("readers", None).await.unwrap();
+        let get = |bucket: &str| [Requirement::object("s3:GetObject", bucket, "k")];
+        let reports: PolicyDocument = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::reports/*"}]}"#,
+        )
+        .unwrap();
+        assert!(iam.put_named_policy("readonly", &reports).await.is_err(), "built-in");
+        assert!(iam.set_user_policy_names("hank", &["missing".into()]).await.is_err());
+        iam.put_named_policy("reports", &reports).await.unwrap();
+        let names = ["reports".to_string(), "writeonly".to_string()];
+        iam.set_user_policy_names("hank", &names).await.unwrap();
+        iam.set_group_policy_names("readers", &names[..1]).await.unwrap();
+        let effective = iam.policy_for("hank").unwrap();
+        assert!(is_authorized(&effective, &get("reports")));
+        assert!(!is_authorized(&effective, &get("other")));
+        assert!(is_authorized(&effective, &[Requirement::object("s3:PutObject", "x", "k")]));
+
+        // Editing the policy rewrites everyone it is attached to.
+        let widened: PolicyDocument = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::*"}]}"#,
+        )
+        .unwrap();
+        iam.put_named_policy("reports", &widened).await.unwrap();
+        assert!(is_authorized(&iam.policy_for("hank").unwrap(), &get("other")));
+        let groups = iam.list_groups().await.unwrap();
+        let readers = groups.iter().find(|g| g.group.name() == "readers").unwrap();
+        assert_eq!(readers.policy, Some(widened.clone()));
+        assert_eq!(readers.policy_names, vec!["reports".to_string()]);
+
+        assert!(iam.delete_named_policy("reports").await.is_err(), "attached");
+        iam.set_user_policy_names("hank", &[]).await.unwrap();
+        iam.set_group_policy("readers", Some(&widened)).await.unwrap();
+        let users = iam.list_users().await.unwrap();
+        assert_eq!((users[0].policy.clone(), users[0].policy_names.len()), (None, 0));
+        iam.delete_named_policy("reports").await.unwrap();
+        let names: Vec<String> =
+            iam.list_named_policies().await.unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, BUILTIN_POLICIES.to_vec());
+    }
+
+    #[tokio::test]
+    async fn minio_style_users_sign_as_themselves_and_keys_can_be_scoped() {
+        let (_tmp, iam) = open_tmp().await;
+        iam.set_user_credentials("ivy", "first-secret").await.unwrap();
+        assert_eq!(iam.find_key("ivy"), Some(("first-secret".into(), "ivy".into())));
+        assert!(iam.verify_password("ivy", "first-secret").await.unwrap());
+        iam.set_user_credentials("ivy", "second-secret").await.unwrap();
+        assert_eq!(iam.find_key("ivy").unwrap().0, "second-secret");
+        assert!(iam.verify_password("ivy", "second-secret").await.unwrap());
+
+        // Another user's key id is not up for grabs.
+        iam.create_user("jon", "password123").await.unwrap();
+        let jons = iam.create_access_key("jon").await.unwrap();
+        assert!(iam.set_user_credentials(&jons.access_key, "password123").await.is_err());
+
+        let scope: PolicyDocument = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::*"}]}"#,
+        )
+        .unwrap();
+        let spec = NewAccessKey {
+            access_key: Some("ivy-ci".into()),
+            secret_key: Some("ci-secret-1".into()),
+            name: "ci".into(),
+            scope: Some(scope.clone()),
+            ..NewAccessKey::default()
+        };
+        let key = iam.create_access_key_with("ivy", spec.clone()).await.unwrap();
+        assert_eq!((key.access_key.as_str(), key.name.as_str()), ("ivy-ci", "ci"));
+        assert!(iam.create_access_key_with("ivy", spec).await.is_err(), "taken");
+        assert_eq!(iam.key_scope("ivy-ci"), Some(scope));
+        assert_eq!(iam.key_scope("ivy"), None);
+        let update = AccessKeyUpdate {
+            scope: Some(None),
+            active: Some(false),
+            ..AccessKeyUpdate::default()
+        };
+        iam.update_access_key("ivy-ci", update).await.unwrap();
+        assert_eq!(iam.key_scope("ivy-ci"), None);
+        assert!(iam.find_key("ivy-ci").is_none());
+        assert!(!iam.access_key("ivy-ci").await.unwrap().unwrap().active);
+
+        // A disabled user can neither sign in nor sign requests.
+        iam.set_user_disabled("ivy", true).await.unwrap();
+        assert!(iam.find_key("ivy").is_none());
+        assert_eq!(iam.key_owner("ivy"), Some("ivy".into()));
+        assert!(!iam.verify_password("ivy", "second-secret").await.unwrap());
+        assert!(iam.list_users().await.unwrap().iter().any(|u| u.username == "ivy" && u.disabled));
+        iam.set_user_disabled("ivy", false).await.unwrap();
+        assert!(iam.find_key("ivy").is_some());
+    }
+
     #[tokio::test]
     async fn newer_value_version_is_rejected() {
         let json = br#"{"v":2,"password_hash":"h","salt":"s","created_at_ms":1}"#;
@@ -7,7 +7,9 @@
 //! Keeping this the single choke point means a policy-enforcement fix lands in
 //! exactly one place.
 
-use super::policy::{is_authorized, PolicyDocument, Requirement};
+use super::policy::{
+    grants_within, is_authorized, PolicyDocument, Requirement, SECURE_TRANSPORT_KEY,
+};
 
 /// A resolved caller, independent of how it authenticated.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,10 +21,19 @@ pub enum Identity {
         access_key: Option<String>,
     },
     /// A runtime IAM user, bound by its effective policy. `policy: None` means
-    /// no policy is attached, which denies everything.
+    /// no policy is attached, which denies everything. `secure_transport`
+    /// feeds the `aws:SecureTransport` condition key.
     Iam {
         username: String,
         policy: Option<PolicyDocument>,
+        secure_transport: bool,
+    },
+    /// Another identity acting through a credential that carries only part of
+    /// its authority, such as an admin API token. A request must pass both the
+    /// identity's own check and `scope`.
+    Scoped {
+        identity: Box<Identity>,
+        scope: PolicyDocument,
     },
 }
 
@@ -38,7 +49,33 @@ impl Identity {
 
     /// A policy-bound IAM identity.
     pub fn iam(username: String, policy: Option<PolicyDocument>) -> Self {
-        Identity::Iam { username, policy }
+        Identity::Iam {
+            username,
+            policy,
+            secure_transport: false,
+        }
+    }
+
+    /// Narrows this identity to what `scope` allows.
+    pub fn scoped(self, scope: PolicyDocument) -> Self {
+        Identity::Scoped {
+            identity: Box::new(self),
+            scope,
+        }
+    }
+
+    /// Records whether the caller reached us over TLS.
+    pub fn over_tls(mut self, secure: bool) -> Self {
+        match &mut self {
+            Identity::Iam {
+                secure_transport, ..
+            } => *secure_transport = secure,
+            Identity::Scoped { identity, .. } => {
+                **identity = identity.as_ref().clone().over_tls(secure);
+            }
+            Identity::Unrestricted { .. } => {}
+        }
+        self
     }
 
     /// The single authorization decision point. Unrestricted identities always
@@ -49,9 +86,36 @@ impl Identity {
             Identity::Unrestricted { .. } => true,
             Identity::Iam {
                 policy: Some(policy),
+                secure_transport,
                 ..
-            } => is_authorized(policy, requirements),
+            } => {
+                let requirements: Vec<Requirement> = requirements
+                    .iter()
+                    .cloned()
+                    .map(|mut r| {
+                        r.context
+                            .insert(SECURE_TRANSPORT_KEY.to_string(), secure_transport.to_string());
+                        r
+                    })
+                    .collect();
+                is_authorized(policy, &requirements)
+            }
             Identity::Iam { policy: None, .. } => false,
+            Identity::Scoped { identity, scope } => {
+                identity.authorize(requirements) && is_authorized(scope, requirements)
+            }
+        }
+    }
+
+    /// Whether this identity already holds everything `granted` allows, so
+    /// handing it to someone else gives away nothing new.
+    pub fn holds(&self, granted: &PolicyDocument) -> bool {
+        match self {
+            Identity::Unrestricted { .. } => true,
+            Identity::Iam { policy, .. } => grants_within(policy.as_ref(), granted),
+            Identity::Scoped { identity, scope } => {
+                identity.holds(granted) && grants_within(Some(scope), granted)
+            }
         }
     }
 
@@ -64,6 +128,7 @@ impl Identity {
         match self {
             Identity::Unrestricted { username, .. } => username.as_deref(),
             Identity::Iam { username, .. } => Some(username),
+            Identity::Scoped { identity, .. } => identity.username(),
         }
     }
 
@@ -71,16 +136,18 @@ impl Identity {
         match self {
             Identity::Unrestricted { access_key, .. } => access_key.as_deref(),
             Identity::Iam { .. } => None,
+            Identity::Scoped { identity, .. } => identity.access_key(),
         }
     }
 
     /// The IAM policy for a policy-bound identity, if any. Used by handlers
     /// that must authorize per-item after parsing a request body (e.g. S3
-    /// multi-object delete).
+    /// multi-object delete). A scoped identity answers `None`: no single
+    /// document describes it, so it can only go through [`Identity::authorize`].
     pub fn policy(&self) -> Option<&PolicyDocument> {
         match self {
             Identity::Iam { policy, .. } => policy.as_ref(),
-            Identity::Unrestricted { .. } => None,
+            Identity::Unrestricted { .. } | Identity::Scoped { .. } => None,
         }
     }
 }
@@ -110,6 +177,43 @@ mod tests {
         assert_eq!(id.username(), Some("bob"));
     }
 
+    #[test]
+    fn secure_transport_condition_follows_the_connection() {
+        let policy: PolicyDocument = serde_json::from_str(
+            r#"{"Statement":[
+                {"Effect":"Allow","Action":"s3:*","Resource":"arn:aws:s3:::*"},
+                {"Effect":"Deny","Action":"s3:*","Resource":"arn:aws:s3:::*",
+                 "Condition":{"Bool":{"aws:SecureTransport":"false"}}}
+            ]}"#,
+        )
+        .unwrap();
+        policy.validate().unwrap();
+        let reqs = [Requirement::object("s3:GetObject", "b", "k")];
+        let plain = Identity::iam("carol".into(), Some(policy));
+        assert!(!plain.authorize(&reqs), "plain HTTP must hit the deny");
+        assert!(plain.over_tls(true).authorize(&reqs));
+    }
+
+    #[test]
+    fn scoped_identities_need_both_their_own_grant_and_the_scope() {
+        let scope: PolicyDocument = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":["s3:Get*","admin:ViewStats"],
+                "Resource":"*"}]}"#,
+        )
+        .unwrap();
+        let get = [Requirement::object("s3:GetObject", "b", "k")];
+        let put = [Requirement::object("s3:PutObject", "b", "k")];
+        let root = Identity::root(Some("admin".into()), None).scoped(scope.clone());
+        assert!(root.authorize(&get));
+        assert!(!root.authorize(&put), "outside the scope");
+        assert!(root.authorize(&[Requirement::admin("admin:ViewStats")]));
+        assert!(!root.is_unrestricted());
+        assert_eq!(root.username(), Some("admin"));
+
+        let bob = Identity::iam("bob".into(), None).scoped(scope);
+        assert!(!bob.authorize(&get), "the scope grants nothing bob lacks");
+    }
+
     #[test]
     fn iam_with_policy_is_evaluated() {
         let id = Identity::iam("alice".into(), Some(allow_all()));
@@ -33,11 +33,21 @@
             router_with_metrics(
                 LocalObjectStore::new(tmp.path()),
                 super::auth::AuthState {
-                    config: std::sync::Arc::new(super::config::AppConfig::default()),
+                    config: super::reload::LiveConfig::new(std::sync::Arc::new(
+                        super::config::AppConfig::default(),
+                    )),
                     iam: None,
                 },
                 metrics.clone(),
                 super::registry::TaskRegistry::new(),
+                super::access_log::AccessLogBuffer::new(),
+                super::ratelimit::RateLimiter::new(
+                    super::reload::LiveConfig::new(std::sync::Arc::new(
+                        super::config::AppConfig::default(),
+                    )),
+                    None,
+                ),
+                None,
             ),
             metrics,
         )
@@ -1753,6 +1763,51 @@
         );
     }
 
+    #[tokio::test]
+    async fn cache_control_is_stored_copied_and_replaced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = make_app(&tmp);
+        let send = |method: &str, uri: &str, headers: &[(&str, &str)], body: &'static str| {
+            let mut builder = Request::builder().method(method).uri(uri);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            app.clone().oneshot(builder.body(Body::from(body)).unwrap())
+        };
+        let cache_control = |res: &axum::response::Response| {
+            res.headers()
+                .get("cache-control")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        send("PUT", "/cc-bucket", &[], "").await.unwrap();
+        let res = send("PUT", "/cc-bucket/a.txt", &[("cache-control", "max-age=60")], "abc")
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = send("HEAD", "/cc-bucket/a.txt", &[], "").await.unwrap();
+        assert_eq!(cache_control(&res).as_deref(), Some("max-age=60"));
+        let res = send("GET", "/cc-bucket/a.txt?response-cache-control=no-cache", &[], "")
+            .await
+            .unwrap();
+        assert_eq!(cache_control(&res).as_deref(), Some("no-cache"));
+
+        // COPY keeps it; REPLACE takes the request's, and none means none.
+        let copy = [("x-amz-copy-source", "/cc-bucket/a.txt")];
+        send("PUT", "/cc-bucket/kept.txt", &copy, "").await.unwrap();
+        let res = send("HEAD", "/cc-bucket/kept.txt", &[], "").await.unwrap();
+        assert_eq!(cache_control(&res).as_deref(), Some("max-age=60"));
+        let replace = [
+            ("x-amz-copy-source", "/cc-bucket/a.txt"),
+            ("x-amz-metadata-directive", "REPLACE"),
+        ];
+        send("PUT", "/cc-bucket/a.txt", &replace, "").await.unwrap();
+        let res = send("HEAD", "/cc-bucket/a.txt", &[], "").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(cache_control(&res), None);
+    }
+
     #[tokio::test]
     async fn browser_style_post_upload_creates_object() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2600,6 +2655,7 @@
             Some(axum::Extension(authority.clone())),
             None,
             None,
+            None,
             Body::from(delete_xml),
         )
         .await;
@@ -2622,6 +2678,7 @@
             Some(axum::Extension(authority)),
             None,
             None,
+            None,
             Body::from(r#"<Delete><Object><Key>private/b.txt</Key></Object></Delete>"#),
         )
         .await;
@@ -3558,6 +3615,115 @@
         assert_eq!(missing.status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn bucket_logging_delivers_aws_format_records_to_the_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = LocalObjectStore::new(tmp.path());
+        let buffer = super::access_log::AccessLogBuffer::new();
+        let tasks = super::registry::TaskRegistry::new();
+        let app = router_with_metrics(
+            store.clone(),
+            super::auth::AuthState {
+                config: super::reload::LiveConfig::new(std::sync::Arc::new(
+                    super::config::AppConfig::default(),
+                )),
+                iam: None,
+            },
+            std::sync::Arc::new(TrafficMetrics::default()),
+            tasks.clone(),
+            buffer.clone(),
+            super::ratelimit::RateLimiter::new(
+                super::reload::LiveConfig::new(std::sync::Arc::new(
+                    super::config::AppConfig::default(),
+                )),
+                None,
+            ),
+            None,
+        );
+        let send = |method: &str, uri: &str, body: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("user-agent", "test-agent/1.0")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+        };
+        for bucket in ["/source", "/logs"] {
+            assert_eq!(send("PUT", bucket, "").await.unwrap().status(), StatusCode::OK);
+        }
+
+        let disabled = body_text(send("GET", "/source?logging", "").await.unwrap()).await;
+        assert!(!disabled.contains("LoggingEnabled"), "{disabled}");
+        let missing_target = send(
+            "PUT",
+            "/source?logging",
+            "<BucketLoggingStatus><LoggingEnabled><TargetBucket>nope</TargetBucket><TargetPrefix>x/</TargetPrefix></LoggingEnabled></BucketLoggingStatus>",
+        )
+        .await
+        .unwrap();
+        assert_eq!(missing_target.status(), StatusCode::BAD_REQUEST);
+        assert!(body_text(missing_target).await.contains("InvalidTargetBucketForLogging"));
+
+        let enable = send(
+            "PUT",
+            "/source?logging",
+            "<BucketLoggingStatus><LoggingEnabled><TargetBucket>logs</TargetBucket><TargetPrefix>access/</TargetPrefix></LoggingEnabled></BucketLoggingStatus>",
+        )
+        .await
+        .unwrap();
+        assert_eq!(enable.status(), StatusCode::OK);
+        let status = body_text(send("GET", "/source?logging", "").await.unwrap()).await;
+        assert!(status.contains("<TargetBucket>logs</TargetBucket><TargetPrefix>access/</TargetPrefix>"));
+
+        assert_eq!(send("PUT", "/source/a%20b.txt", "hello").await.unwrap().status(), StatusCode::OK);
+        let download = send("GET", "/source/a%20b.txt", "").await.unwrap();
+        assert_eq!(body_text(download).await, "hello");
+        assert_eq!(send("GET", "/source/absent", "").await.unwrap().status(), StatusCode::NOT_FOUND);
+
+        let delivered = super::jobs::access_log::run_once(
+            &store,
+            &buffer,
+            &tokio_util::sync::CancellationToken::new(),
+            &tasks,
+            "test-run",
+        )
+        .await;
+        // GET ?logging, PUT object, GET object, failed GET.
+        assert_eq!(delivered, 4);
+
+        let listing = body_text(send("GET", "/logs?list-type=2&prefix=access/", "").await.unwrap()).await;
+        let key = regex::Regex::new(r"<Key>(access/\d{4}-\d{2}-\d{2}-\d{2}-\d{2}-\d{2}-[0-9A-F]{16})</Key>")
+            .unwrap()
+            .captures(&listing)
+            .unwrap_or_else(|| panic!("no log object in {listing}"))[1]
+            .to_string();
+        let log = body_text(send("GET", &format!("/logs/{key}"), "").await.unwrap()).await;
+        let lines = log.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4, "{log}");
+        let get = lines.iter().find(|line| line.contains("REST.GET.OBJECT a%20b.txt")).unwrap();
+        assert!(get.starts_with("- source ["), "{get}");
+        assert!(get.contains(r#""GET /source/a%20b.txt HTTP/1.1" 200 - 5 5 "#), "{get}");
+        assert!(get.contains(r#""-" "test-agent/1.0""#), "{get}");
+        let failed = lines.iter().find(|line| line.contains("REST.GET.OBJECT absent")).unwrap();
+        assert!(failed.contains(" 404 NoSuchKey "), "{failed}");
+        assert!(lines.iter().any(|line| line.contains("REST.PUT.OBJECT a%20b.txt")));
+
+        // Requests against the log bucket itself are not recorded.
+        assert!(buffer.drain().is_empty());
+
+        // Turning logging off takes effect on the very next request.
+        let disable = send("PUT", "/source?logging", "<BucketLoggingStatus/>").await.unwrap();
+        assert_eq!(disable.status(), StatusCode::OK);
+        drop(disable);
+        assert_eq!(send("GET", "/source/a%20b.txt", "").await.unwrap().status(), StatusCode::OK);
+        let pending = buffer.drain();
+        let lines = pending.get("source").cloned().unwrap_or_default();
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        assert!(lines[0].contains("REST.PUT.LOGGING_STATUS"), "{}", lines[0]);
+    }
+
     fn now_datetime() -> String {
         chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string()
     }
@@ -3648,6 +3814,202 @@
         assert_eq!(body_text(res).await, "topsecret");
     }
 
+    #[tokio::test]
+    async fn admin_api_sees_whether_the_connection_is_tls() {
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = super::iam::IamStore::open(&tmp.path().join("admin")).await.unwrap();
+        iam.create_user("ops", "password123").await.unwrap();
+        let policy = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"admin:ViewStats","Resource":"*",
+                "Condition":{"Bool":{"aws:SecureTransport":"true"}}}]}"#,
+        )
+        .unwrap();
+        iam.set_policy("ops", Some(&policy)).await.unwrap();
+        let key = iam.create_access_key("ops").await.unwrap();
+        let mut config = super::config::AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.public_hostname = Some(TEST_HOST.to_string());
+        let config = super::reload::LiveConfig::new(std::sync::Arc::new(config));
+        let scans = super::scan_store::ScanStore::open(tmp.path()).await.unwrap();
+        let tasks = super::registry::TaskRegistry::new();
+        let admin = super::madmin::AdminApi {
+            store: LocalObjectStore::new(tmp.path()),
+            iam: iam.clone(),
+            config: config.clone(),
+            scans: super::jobs::perf_scan::ScanService::new(scans),
+            tasks: tasks.clone(),
+        };
+        let app = router_with_metrics(
+            LocalObjectStore::new(tmp.path()),
+            super::auth::AuthState { config: config.clone(), iam: Some(iam) },
+            std::sync::Arc::new(TrafficMetrics::default()),
+            tasks,
+            super::access_log::AccessLogBuffer::new(),
+            super::ratelimit::RateLimiter::new(config, None),
+            Some(admin),
+        );
+        let info = |tls: bool| {
+            let datetime = now_datetime();
+            let path = "/minio/admin/v3/info";
+            let auth = crate::server::auth::compute_auth_header(
+                "GET",
+                path,
+                "",
+                TEST_HOST,
+                &key.access_key,
+                &key.secret_key,
+                TEST_REGION,
+                &datetime,
+            );
+            let mut request = Request::builder()
+                .uri(path)
+                .header("host", TEST_HOST)
+                .header("x-amz-date", &datetime)
+                .header("x-amz-content-sha256", "UNSIGNED-PAYLOAD")
+                .header("authorization", auth);
+            if tls {
+                let marker = super::tls::TlsConnection { client_certificate: false };
+                request = request.extension(marker);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        assert_eq!(info(false).await.unwrap().status(), StatusCode::FORBIDDEN);
+        assert_eq!(info(true).await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn share_links_answer_to_their_registry_entry() {
+        use super::iam::{IamStore, NewShare};
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = IamStore::open(&tmp.path().join("admin")).await.unwrap();
+        iam.create_user("sid", "password123").await.unwrap();
+        let policy = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"*"}]}"#,
+        )
+        .unwrap();
+        iam.set_policy("sid", Some(&policy)).await.unwrap();
+        let mut config = super::config::AppConfig::default();
+        config.auth.enabled = true;
+        config.auth.credentials.push(super::config::Credential {
+            access_key: TEST_ACCESS_KEY.to_string(),
+            secret_key: TEST_SECRET_KEY.to_string(),
+        });
+        config.auth.public_hostname = Some(TEST_HOST.to_string());
+        let config = super::reload::LiveConfig::new(std::sync::Arc::new(config));
+        let app = router_with_metrics(
+            LocalObjectStore::new(tmp.path()),
+            super::auth::AuthState { config: config.clone(), iam: Some(iam.clone()) },
+            std::sync::Arc::new(TrafficMetrics::default()),
+            super::registry::TaskRegistry::new(),
+            super::access_log::AccessLogBuffer::new(),
+            super::ratelimit::RateLimiter::new(config, None),
+            None,
+        );
+        let res = signed_request(app.clone(), "PUT", "/shared", "", Body::empty()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = Body::from("quarterly");
+        let res = signed_request(app.clone(), "PUT", "/shared/q3.txt", "", body).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let (signer, secret) = iam.web_key_for("sid", false).await.unwrap();
+        let share = |password: Option<&str>, max_downloads| NewShare {
+            bucket: "shared".into(),
+            key: "q3.txt".into(),
+            signer: signer.clone(),
+            ttl_ms: 3_600_000,
+            password: password.map(str::to_string),
+            max_downloads,
+        };
+        let signed_url = |method: &str, id: &str| {
+            let qs = crate::server::auth::presign_query(
+                method,
+                "/shared/q3.txt",
+                TEST_HOST,
+                &signer,
+                &secret,
+                TEST_REGION,
+                &now_datetime(),
+                3600,
+                &[(super::auth::SHARE_ID_PARAM, id)],
+            );
+            format!("/shared/q3.txt?{qs}")
+        };
+        let url = |id: &str| signed_url("GET", id);
+        let get = |uri: String, authorization: Option<&'static str>| {
+            let mut request = Request::builder().method("GET").uri(uri);
+            if let Some(value) = authorization {
+                request = request.header("authorization", value);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let once = iam.create_share("sid", false, share(None, Some(1))).await.unwrap();
+        let res = get(url(&once.id), None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body_text(res).await, "quarterly");
+        let res = get(url(&once.id), None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(body_text(res).await.contains("download limit"));
+        assert_eq!(get(url("unregistered"), None).await.unwrap().status(), StatusCode::FORBIDDEN);
+
+        // A HEAD probe is only checked; any ranged GET spends a download, so
+        // a limited link cannot be read a range at a time past its limit.
+        let probe = |method: &'static str, id: &str, range: Option<&'static str>| {
+            let mut request = Request::builder().method(method).uri(signed_url(method, id));
+            if let Some(range) = range {
+                request = request.header("range", range);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let ranged = iam.create_share("sid", false, share(None, Some(1))).await.unwrap();
+        assert_eq!(probe("HEAD", &ranged.id, None).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(iam.share(&ranged.id).unwrap().downloads, 0);
+        let res = probe("GET", &ranged.id, Some("bytes=1-")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body_text(res).await, "uarterly");
+        assert_eq!(iam.share(&ranged.id).unwrap().downloads, 1);
+        let res = probe("GET", &ranged.id, Some("bytes=0-0")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(body_text(res).await.contains("download limit"));
+        let res = probe("GET", &ranged.id, Some("bytes=1-")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(probe("HEAD", &ranged.id, None).await.unwrap().status(), StatusCode::FORBIDDEN);
+
+        // "user:s3cret" and "user:wrong", as browsers send them.
+        let locked = iam.create_share("sid", false, share(Some("s3cret"), None)).await.unwrap();
+        let res = get(url(&locked.id), None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert!(res.headers().contains_key("www-authenticate"));
+        let wrong = get(url(&locked.id), Some("Basic dXNlcjp3cm9uZw==")).await.unwrap();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        let res = get(url(&locked.id), Some("Basic dXNlcjpzM2NyZXQ=")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Revoking one link leaves the others working.
+        let other = iam.create_share("sid", false, share(None, None)).await.unwrap();
+        assert!(iam.revoke_share(&locked.id).await.unwrap());
+        let res = get(url(&locked.id), Some("Basic dXNlcjpzM2NyZXQ=")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(get(url(&other.id), None).await.unwrap().status(), StatusCode::OK);
+
+        let outcomes: Vec<(u16, String)> = iam
+            .share_accesses(&locked.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|access| (access.status, access.outcome))
+            .collect();
+        let expected = [
+            (403, "revoked"),
+            (200, "ok"),
+            (401, "password_required"),
+            (401, "password_required"),
+        ];
+        let expected: Vec<(u16, String)> =
+            expected.iter().map(|(status, outcome)| (*status, outcome.to_string())).collect();
+        assert_eq!(outcomes, expected);
+    }
+
     #[tokio::test]
     async fn presigned_put_valid_signature_uploads_object() {
         let tmp = tempfile::tempdir().unwrap();
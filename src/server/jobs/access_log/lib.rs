@@ -0,0 +1,102 @@
+//! Scheduled job: deliver buffered server access log records. Each source
+//! bucket's pending lines become one object in its logging target bucket,
+//! named `<prefix>YYYY-mm-dd-HH-MM-SS-<unique>` as S3 names them. Registers in
+//! the task registry only when there is something to deliver.
+
+use std::sync::Arc;
+
+use rand::RngCore;
+use tokio_util::sync::CancellationToken;
+
+use crate::server::access_log::AccessLogBuffer;
+use crate::server::registry::{TaskKind, TaskRegistry};
+use crate::storage::errors::StorageError;
+use crate::storage::store::LocalObjectStore;
+use crate::storage::time::now_ms;
+
+pub(crate) const JOB: &str = "access_log";
+
+pub(crate) async fn run_once(
+    store: &LocalObjectStore,
+    buffer: &AccessLogBuffer,
+    cancel: &CancellationToken,
+    tasks: &Arc<TaskRegistry>,
+    run_id: &str,
+) -> usize {
+    let dropped = buffer.take_dropped();
+    if dropped > 0 {
+        log::warn!("[{run_id}] {JOB} dropped={dropped} records over the pending limit");
+    }
+    let pending = buffer.drain();
+    if pending.is_empty() {
+        return 0;
+    }
+    let guard = tasks.register(run_id, TaskKind::Job, JOB, "all-buckets");
+    let progress = guard.progress();
+    let mut delivered = 0;
+    for (bucket, lines) in pending {
+        if cancel.is_cancelled() || guard.is_cancelled() {
+            buffer.requeue(&bucket, lines);
+            continue;
+        }
+        // Re-read the configuration: logging may have been switched off (or
+        // the bucket deleted) since these requests were recorded.
+        let Some(logging) = store
+            .bucket_meta(&bucket)
+            .await
+            .ok()
+            .and_then(|meta| meta.logging)
+        else {
+            continue;
+        };
+        let key = log_object_key(&logging.target_prefix, now_ms());
+        let mut body = lines.join("\n");
+        body.push('\n');
+        match store
+            .put_object(
+                &logging.target_bucket,
+                &key,
+                body.as_bytes(),
+                Some("text/plain"),
+                None,
+                false,
+            )
+            .await
+        {
+            Ok(_) => delivered += lines.len(),
+            Err(StorageError::BucketNotFound(_)) => log::warn!(
+                "[{run_id}] {JOB} bucket={bucket} target={} missing; discarded {} records",
+                logging.target_bucket,
+                lines.len()
+            ),
+            Err(err) => {
+                log::warn!(
+                    "[{run_id}] {JOB} bucket={bucket} target={} error={err}; will retry",
+                    logging.target_bucket
+                );
+                buffer.requeue(&bucket, lines);
+            }
+        }
+        progress.set_note(format!("delivered {delivered} records"));
+    }
+    if delivered > 0 {
+        log::info!("[{run_id}] {JOB} complete delivered={delivered}");
+    }
+    delivered
+}
+
+fn log_object_key(prefix: &str, epoch_ms: i64) -> String {
+    use chrono::TimeZone;
+    let stamp = chrono::Utc
+        .timestamp_millis_opt(epoch_ms)
+        .single()
+        .unwrap_or_else(chrono::Utc::now)
+        .format("%Y-%m-%d-%H-%M-%S");
+    let mut unique = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut unique);
+    let unique = unique
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<String>();
+    format!("{prefix}{stamp}-{unique}")
+}
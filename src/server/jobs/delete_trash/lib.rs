@@ -1,6 +1,6 @@
 //! Scheduled job: delete expired trash directories (retired/overwritten/deleted
-//! blobs past their grace window) for every bucket. Registers in the task
-//! registry.
+//! blobs past their grace window) for every bucket, then the dedup chunks that
+//! purge left unreferenced. Registers in the task registry.
 
 use std::sync::Arc;
 
@@ -9,7 +9,7 @@ use tokio_util::sync::CancellationToken;
 use crate::server::config::SweeperConfig;
 use crate::server::registry::{TaskKind, TaskRegistry};
 use crate::storage::store::LocalObjectStore;
-use crate::storage::sweeper::delete_trash_bucket;
+use crate::storage::sweeper::{delete_chunks_bucket, delete_trash_bucket};
 use crate::storage::time::now_ms;
 
 pub(crate) const JOB: &str = "delete_trash";
@@ -32,6 +32,7 @@ pub(crate) async fn run_once(
     };
     let pass = cfg.sweep_pass();
     let mut removed = 0;
+    let mut chunks = 0;
     for (bucket, _) in &buckets {
         // Stop on scheduler shutdown or an operator cancel of this run.
         if cancel.is_cancelled() || guard.is_cancelled() {
@@ -41,10 +42,14 @@ pub(crate) async fn run_once(
             Ok(n) => removed += n,
             Err(err) => log::warn!("[{run_id}] {JOB} bucket={bucket} error={err}"),
         }
-        progress.set_note(format!("deleted {removed} trash dirs"));
+        match delete_chunks_bucket(store, bucket, &pass, now_ms()).await {
+            Ok(n) => chunks += n,
+            Err(err) => log::warn!("[{run_id}] {JOB} bucket={bucket} chunks error={err}"),
+        }
+        progress.set_note(format!("deleted {removed} trash dirs, {chunks} chunks"));
     }
-    if removed > 0 {
-        log::info!("[{run_id}] {JOB} complete removed={removed}");
+    if removed > 0 || chunks > 0 {
+        log::info!("[{run_id}] {JOB} complete removed={removed} chunks={chunks}");
     }
     removed
 }
@@ -12,14 +12,20 @@
 //! asked for it or the scheduler did. Each maintenance purpose is its own job
 //! so they can run at independent frequencies without overlapping.
 
+#[path = "access_log/lib.rs"]
+pub(crate) mod access_log;
 #[path = "delete_staging/lib.rs"]
 pub(crate) mod delete_staging;
 #[path = "delete_trash/lib.rs"]
 pub(crate) mod delete_trash;
 #[path = "migrate_layout/lib.rs"]
 pub(crate) mod migrate_layout;
+#[path = "move_bucket/lib.rs"]
+pub(crate) mod move_bucket;
 #[path = "perf_scan/lib.rs"]
 pub(crate) mod perf_scan;
+#[path = "prune_changes/lib.rs"]
+pub(crate) mod prune_changes;
 #[path = "reclaim/lib.rs"]
 pub(crate) mod reclaim;
 #[path = "rebuild_index/lib.rs"]
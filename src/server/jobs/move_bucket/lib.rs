@@ -0,0 +1,46 @@
+//! Job: move a bucket to another data root.
+//!
+//! The copy runs while the bucket stays in service, then a short switch-over
+//! makes the new copy live (see `storage::placement`). Started from the
+//! console; the task panel shows bytes copied against the bucket's size, then
+//! "switching over" for the moment the bucket answers 503. Cancelling stops
+//! the copy and discards it; the switch-over itself always runs to the end.
+
+use std::sync::Arc;
+
+use crate::server::registry::{TaskKind, TaskRegistry};
+use crate::storage::placement::MoveProgress;
+use crate::storage::store::LocalObjectStore;
+
+pub(crate) const JOB: &str = "move_bucket";
+
+/// Launches the move of `bucket` to data root `target` as a registry task.
+pub(crate) fn spawn(
+    store: LocalObjectStore,
+    bucket: String,
+    target: usize,
+    tasks: Arc<TaskRegistry>,
+) {
+    let run_id = crate::server::new_request_id();
+    let destination = store.layout().roots()[target].display().to_string();
+    tokio::spawn(async move {
+        let guard = tasks.register(&run_id, TaskKind::Job, JOB, format!("/{bucket}"));
+        let progress = guard.progress();
+        let cancel = guard.cancel_token();
+        log::info!("[{run_id}] {JOB} started bucket={bucket} to={destination}");
+        let report = |step: MoveProgress| match step {
+            MoveProgress::Measured(total) => progress.set_total(total),
+            MoveProgress::Copied(bytes) => progress.add_done(bytes),
+            MoveProgress::Switching => {
+                progress.set_note(format!("switching over to {destination}"))
+            }
+        };
+        match store.move_bucket(&bucket, target, &cancel, &report).await {
+            Ok(()) => {
+                progress.set_note(format!("moved to {destination}"));
+                log::info!("[{run_id}] {JOB} complete bucket={bucket} to={destination}");
+            }
+            Err(err) => log::warn!("[{run_id}] {JOB} failed bucket={bucket} error={err}"),
+        }
+    });
+}
@@ -0,0 +1,56 @@
+//! Scheduled job: trim every bucket's change log to
+//! `sweeper.change_log_retention_secs`. The log only feeds incremental
+//! backups; a checkpoint older than what is left just makes the next backup
+//! send that bucket whole. Registers in the task registry.
+
+use std::sync::Arc;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::server::config::SweeperConfig;
+use crate::server::registry::{TaskKind, TaskRegistry};
+use crate::storage::store::LocalObjectStore;
+use crate::storage::time::now_ms;
+
+pub(crate) const JOB: &str = "prune_changes";
+
+pub(crate) async fn run_once(
+    store: &LocalObjectStore,
+    cfg: &SweeperConfig,
+    cancel: &CancellationToken,
+    tasks: &Arc<TaskRegistry>,
+    run_id: &str,
+) -> usize {
+    if cfg.change_log_retention_secs == 0 {
+        return 0;
+    }
+    let guard = tasks.register(run_id, TaskKind::Job, JOB, "all-buckets");
+    let progress = guard.progress();
+    let buckets = match store.list_buckets().await {
+        Ok(buckets) => buckets,
+        Err(err) => {
+            log::warn!("[{run_id}] {JOB} failed to list buckets error={err}");
+            return 0;
+        }
+    };
+    let before_ms = now_ms().saturating_sub(cfg.change_log_retention_secs as i64 * 1000);
+    let mut pruned = 0;
+    for (bucket, _) in &buckets {
+        if cancel.is_cancelled() || guard.is_cancelled() {
+            break;
+        }
+        let result = match store.index(bucket).await {
+            Ok(index) => index.prune_changes(before_ms).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(n) => pruned += n,
+            Err(err) => log::warn!("[{run_id}] {JOB} bucket={bucket} error={err}"),
+        }
+        progress.set_note(format!("pruned {pruned} change-log entries"));
+    }
+    if pruned > 0 {
+        log::info!("[{run_id}] {JOB} complete pruned={pruned}");
+    }
+    pruned
+}
@@ -75,6 +75,7 @@ mod tests {
             multipart_upload_expiry_secs: 0,
             trash_expiry_secs: 0,
             reclaim_interval_secs: 300,
+            change_log_retention_secs: 0,
         };
         let n = run_once(
             &store,
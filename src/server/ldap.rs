@@ -0,0 +1,467 @@
+//! LDAP directory authentication, for console logins and STS.
+//!
+//! A login searches for the user as the service account, binds as the entry
+//! it found with the password given, and then reads the user's groups. Each
+//! attempt opens its own connection: logins are rare next to S3 traffic, and
+//! nothing is left holding a connection a directory restart has broken.
+
+use std::time::Duration;
+
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+
+use super::config::LdapConfig;
+
+/// `invalidCredentials` (RFC 4511): a wrong password, or no such DN.
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// A directory user whose password checked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapUser {
+    pub dn: String,
+    /// Names of the directory groups the user is in.
+    pub groups: Vec<String>,
+}
+
+impl LdapUser {
+    /// The user's IAM groups under `ldap.group_map`, or `None` when the map
+    /// is empty and group membership is managed in the console instead.
+    pub fn iam_groups(&self, config: &LdapConfig) -> Option<Vec<String>> {
+        if config.group_map.is_empty() {
+            return None;
+        }
+        let mut groups: Vec<String> = self
+            .groups
+            .iter()
+            .filter_map(|group| config.group_map.get(group).cloned())
+            .collect();
+        groups.sort();
+        groups.dedup();
+        Some(groups)
+    }
+}
+
+/// Checks `password` for `username`. `Ok(None)` when the directory says no —
+/// unknown user, ambiguous user, wrong password — and `Err` when it could
+/// not be asked.
+pub async fn authenticate(
+    config: &LdapConfig,
+    username: &str,
+    password: &str,
+) -> Result<Option<LdapUser>, String> {
+    // A simple bind with an empty password is an "unauthenticated" bind
+    // (RFC 4513 §5.1.2), which many servers report as a success.
+    if username.is_empty() || password.is_empty() {
+        return Ok(None);
+    }
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let settings = LdapConnSettings::new()
+        .set_conn_timeout(timeout)
+        .set_starttls(config.start_tls);
+    let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &config.url)
+        .await
+        .map_err(|err| format!("cannot reach {}: {err}", config.url))?;
+    ldap3::drive!(conn);
+    let result = check(&mut ldap, config, timeout, username, password).await;
+    let _ = ldap.unbind().await;
+    result
+}
+
+fn failed(what: &'static str) -> impl Fn(LdapError) -> String {
+    move |err| format!("LDAP {what} failed: {err}")
+}
+
+async fn check(
+    ldap: &mut Ldap,
+    config: &LdapConfig,
+    timeout: Duration,
+    username: &str,
+    password: &str,
+) -> Result<Option<LdapUser>, String> {
+    service_bind(ldap, config, timeout).await?;
+    let filter = config
+        .user_filter
+        .replace("{username}", &ldap_escape(username));
+    let (entries, _) = ldap
+        .with_timeout(timeout)
+        .search(&config.user_base_dn, Scope::Subtree, &filter, vec!["1.1"])
+        .await
+        .and_then(|result| result.success())
+        .map_err(failed("user search"))?;
+    if entries.len() > 1 {
+        log::warn!(
+            target: super::logging::TARGET_AUTH,
+            "ldap user filter {filter} matches {} entries; refusing the login",
+            entries.len()
+        );
+    }
+    let Ok([entry]) = <[_; 1]>::try_from(entries) else {
+        return Ok(None);
+    };
+    let dn = SearchEntry::construct(entry).dn;
+
+    let bind = ldap
+        .with_timeout(timeout)
+        .simple_bind(&dn, password)
+        .await
+        .map_err(failed("user bind"))?;
+    if bind.rc == INVALID_CREDENTIALS {
+        return Ok(None);
+    }
+    bind.success().map_err(failed("user bind"))?;
+
+    let mut groups = Vec::new();
+    if let Some(base) = &config.group_base_dn {
+        // Back to the service account: a user may not be allowed to read
+        // group entries.
+        service_bind(ldap, config, timeout).await?;
+        let filter = config
+            .group_filter
+            .replace("{dn}", &ldap_escape(dn.as_str()))
+            .replace("{username}", &ldap_escape(username));
+        let attr = config.group_name_attribute.as_str();
+        let (entries, _) = ldap
+            .with_timeout(timeout)
+            .search(base, Scope::Subtree, &filter, vec![attr])
+            .await
+            .and_then(|result| result.success())
+            .map_err(failed("group search"))?;
+        for entry in entries {
+            let entry = SearchEntry::construct(entry);
+            for (name, values) in entry.attrs {
+                if name.eq_ignore_ascii_case(attr) {
+                    groups.extend(values);
+                }
+            }
+        }
+        groups.sort();
+        groups.dedup();
+    }
+    Ok(Some(LdapUser { dn, groups }))
+}
+
+async fn service_bind(
+    ldap: &mut Ldap,
+    config: &LdapConfig,
+    timeout: Duration,
+) -> Result<(), String> {
+    let Some(bind_dn) = &config.bind_dn else {
+        return Ok(());
+    };
+    let password = config.bind_password.as_deref().unwrap_or_default();
+    ldap.with_timeout(timeout)
+        .simple_bind(bind_dn, password)
+        .await
+        .and_then(|result| result.success())
+        .map(drop)
+        .map_err(failed("service account bind"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// One BER element: its tag and contents. Enough of BER for LDAPv3 as
+    /// `ldap3` speaks it, which is all the mock directory needs.
+    fn element(buf: &[u8]) -> Option<(u8, &[u8], usize)> {
+        let (&tag, rest) = buf.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (len, header) = if first < 0x80 {
+            (first as usize, 2)
+        } else {
+            let n = (first & 0x7f) as usize;
+            let bytes = rest.get(..n)?;
+            (
+                bytes.iter().fold(0, |len, &b| (len << 8) | b as usize),
+                2 + n,
+            )
+        };
+        let content = buf.get(header..header + len)?;
+        Some((tag, content, header + len))
+    }
+
+    fn elements(mut buf: &[u8]) -> Vec<(u8, &[u8])> {
+        let mut out = Vec::new();
+        while let Some((tag, content, used)) = element(buf) {
+            out.push((tag, content));
+            buf = &buf[used..];
+        }
+        out
+    }
+
+    fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match content.len() {
+            len @ 0..=0x7f => out.push(len as u8),
+            len => {
+                let bytes = (len as u32).to_be_bytes();
+                let skip = bytes.iter().take_while(|&&b| b == 0).count();
+                out.push(0x80 | (4 - skip) as u8);
+                out.extend_from_slice(&bytes[skip..]);
+            }
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn text(bytes: &[u8]) -> String {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    /// An in-memory directory served over LDAPv3 on a loopback port: simple
+    /// binds, subtree searches with `&`, `|`, `!`, equality and presence
+    /// filters, and unbind.
+    pub(crate) struct MockDirectory {
+        entries: Vec<Entry>,
+    }
+
+    /// (dn, attributes, password)
+    type Entry = (String, Vec<(String, Vec<String>)>, Option<String>);
+
+    impl MockDirectory {
+        pub(crate) fn example() -> Self {
+            let person = |uid: &str, password: &str| {
+                (
+                    format!("uid={uid},ou=people,dc=example,dc=com"),
+                    vec![("uid".to_string(), vec![uid.to_string()])],
+                    Some(password.to_string()),
+                )
+            };
+            let group = |cn: &str, attr: &str, members: &[&str]| {
+                let members = members
+                    .iter()
+                    .map(|uid| format!("uid={uid},ou=people,dc=example,dc=com"))
+                    .collect();
+                (
+                    format!("cn={cn},ou=groups,dc=example,dc=com"),
+                    vec![
+                        ("cn".to_string(), vec![cn.to_string()]),
+                        (attr.to_string(), members),
+                    ],
+                    None,
+                )
+            };
+            Self {
+                entries: vec![
+                    (
+                        "cn=reader,dc=example,dc=com".to_string(),
+                        Vec::new(),
+                        Some("readerpw".to_string()),
+                    ),
+                    person("alice", "alicepw"),
+                    person("bob", "bobpw"),
+                    group("storage-admins", "member", &["alice"]),
+                    group("staff", "uniqueMember", &["alice", "bob"]),
+                ],
+            }
+        }
+
+        pub(crate) async fn serve(self) -> SocketAddr {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let directory = Arc::new(self);
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(directory.clone().session(socket));
+                }
+            });
+            addr
+        }
+
+        async fn session(self: Arc<Self>, mut socket: tokio::net::TcpStream) {
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                while let Some((_, message, used)) = element(&buf) {
+                    let parts = elements(message);
+                    let (id, (op, body)) = (encode(0x02, parts[0].1), parts[1]);
+                    let reply = |op: u8, content: Vec<u8>| {
+                        encode(0x30, &[id.clone(), encode(op, &content)].concat())
+                    };
+                    let result = |code: u8| {
+                        [encode(0x0a, &[code]), encode(0x04, b""), encode(0x04, b"")].concat()
+                    };
+                    let out = match op {
+                        0x60 => {
+                            let fields = elements(body);
+                            let code = self.bind(&text(fields[1].1), &text(fields[2].1));
+                            reply(0x61, result(code))
+                        }
+                        0x63 => {
+                            let fields = elements(body);
+                            let base = text(fields[0].1).to_ascii_lowercase();
+                            let mut out = Vec::new();
+                            for (dn, attrs, _) in &self.entries {
+                                if dn.to_ascii_lowercase().ends_with(&base)
+                                    && Self::matches(attrs, fields[6].0, fields[6].1)
+                                {
+                                    out.extend(reply(0x64, Self::entry(dn, attrs)));
+                                }
+                            }
+                            out.extend(reply(0x65, result(0)));
+                            out
+                        }
+                        // Unbind, and anything else: hang up.
+                        _ => return,
+                    };
+                    if socket.write_all(&out).await.is_err() {
+                        return;
+                    }
+                    buf.drain(..used);
+                }
+                match socket.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }
+
+        fn bind(&self, dn: &str, password: &str) -> u8 {
+            if dn.is_empty() {
+                return 0;
+            }
+            let known = self
+                .entries
+                .iter()
+                .find(|(entry, ..)| entry.eq_ignore_ascii_case(dn));
+            match known {
+                Some((_, _, Some(expected))) if expected == password => 0,
+                _ => INVALID_CREDENTIALS as u8,
+            }
+        }
+
+        fn matches(attrs: &[(String, Vec<String>)], tag: u8, filter: &[u8]) -> bool {
+            let values = |name: String| {
+                attrs
+                    .iter()
+                    .filter(move |(attr, _)| attr.eq_ignore_ascii_case(&name))
+                    .flat_map(|(_, values)| values)
+            };
+            match tag {
+                0xa0 => elements(filter)
+                    .iter()
+                    .all(|&(t, f)| Self::matches(attrs, t, f)),
+                0xa1 => elements(filter)
+                    .iter()
+                    .any(|&(t, f)| Self::matches(attrs, t, f)),
+                0xa2 => elements(filter)
+                    .iter()
+                    .all(|&(t, f)| !Self::matches(attrs, t, f)),
+                0xa3 => {
+                    let parts = elements(filter);
+                    let wanted = text(parts[1].1);
+                    values(text(parts[0].1)).any(|value| value.eq_ignore_ascii_case(&wanted))
+                }
+                0x87 => values(text(filter)).next().is_some(),
+                _ => false,
+            }
+        }
+
+        fn entry(dn: &str, attrs: &[(String, Vec<String>)]) -> Vec<u8> {
+            let attributes: Vec<u8> = attrs
+                .iter()
+                .flat_map(|(name, values)| {
+                    let values: Vec<u8> = values
+                        .iter()
+                        .flat_map(|v| encode(0x04, v.as_bytes()))
+                        .collect();
+                    encode(
+                        0x30,
+                        &[encode(0x04, name.as_bytes()), encode(0x31, &values)].concat(),
+                    )
+                })
+                .collect();
+            [encode(0x04, dn.as_bytes()), encode(0x30, &attributes)].concat()
+        }
+    }
+
+    pub(crate) fn config_for(addr: SocketAddr) -> LdapConfig {
+        LdapConfig {
+            enabled: true,
+            url: format!("ldap://{addr}"),
+            bind_dn: Some("cn=reader,dc=example,dc=com".to_string()),
+            bind_password: Some("readerpw".to_string()),
+            user_base_dn: "ou=people,dc=example,dc=com".to_string(),
+            group_base_dn: Some("ou=groups,dc=example,dc=com".to_string()),
+            group_map: BTreeMap::from([("storage-admins".to_string(), "admin".to_string())]),
+            ..LdapConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn users_bind_with_their_password_and_bring_their_groups() {
+        let config = config_for(MockDirectory::example().serve().await);
+        let alice = authenticate(&config, "alice", "alicepw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(alice.dn, "uid=alice,ou=people,dc=example,dc=com");
+        assert_eq!(
+            alice.groups,
+            vec!["staff".to_string(), "storage-admins".to_string()]
+        );
+        assert_eq!(alice.iam_groups(&config), Some(vec!["admin".to_string()]));
+        let bob = authenticate(&config, "bob", "bobpw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bob.groups, vec!["staff".to_string()]);
+        assert_eq!(bob.iam_groups(&config), Some(Vec::new()));
+        let unmapped = LdapConfig {
+            group_map: BTreeMap::new(),
+            ..config.clone()
+        };
+        assert_eq!(bob.iam_groups(&unmapped), None);
+
+        let denied = |user: &'static str, password: &'static str| {
+            let config = config.clone();
+            async move { authenticate(&config, user, password).await.unwrap() }
+        };
+        assert_eq!(denied("alice", "bobpw").await, None);
+        assert_eq!(denied("carol", "carolpw").await, None);
+        assert_eq!(denied("alice", "").await, None, "no unauthenticated binds");
+        // Filter metacharacters in a login name are escaped, not obeyed.
+        assert_eq!(denied("*", "alicepw").await, None);
+        assert_eq!(denied("alice)(uid=*", "alicepw").await, None);
+    }
+
+    #[tokio::test]
+    async fn directory_trouble_is_an_error_not_a_denial() {
+        let addr = MockDirectory::example().serve().await;
+        let wrong_service = LdapConfig {
+            bind_password: Some("nope".to_string()),
+            ..config_for(addr)
+        };
+        let err = authenticate(&wrong_service, "alice", "alicepw")
+            .await
+            .unwrap_err();
+        assert!(err.contains("service account bind"), "{err}");
+
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err = authenticate(&config_for(closed), "alice", "alicepw")
+            .await
+            .unwrap_err();
+        assert!(err.contains("cannot reach"), "{err}");
+
+        // Without a group base no group lookup happens.
+        let no_groups = LdapConfig {
+            group_base_dn: None,
+            ..config_for(addr)
+        };
+        let alice = authenticate(&no_groups, "alice", "alicepw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(alice.groups.is_empty());
+    }
+}
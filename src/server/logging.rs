@@ -12,8 +12,13 @@
 //! lands in `auth.log`. The category targets are non-additive, so their lines
 //! do not also duplicate into `server.log` — but they still echo to the
 //! console, so stdout remains a complete stream.
+//!
+//! [`reconfigure`] rebuilds the same setup from a reloaded config and swaps it
+//! in place, so the level, destinations and rotation settings change without a
+//! restart.
 
 use std::path::Path;
+use std::sync::OnceLock;
 
 use log::LevelFilter;
 use log4rs::{
@@ -28,6 +33,7 @@ use log4rs::{
     },
     config::{Appender, Config, Logger, Root},
     encode::pattern::PatternEncoder,
+    Handle,
 };
 
 use super::config::LoggingConfig;
@@ -39,6 +45,9 @@ pub const TARGET_AUTHZ: &str = "rusts3::authz";
 /// Log target for the operation audit trail (who did what, success or failure).
 pub const TARGET_AUDIT: &str = "rusts3::audit";
 
+/// The installed logger, kept so a config reload can replace its setup.
+static HANDLE: OnceLock<Handle> = OnceLock::new();
+
 const PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S%.3f)} {l:<5} {m}{n}";
 
 fn encoder() -> Box<PatternEncoder> {
@@ -69,6 +78,21 @@ fn rolling_appender(
 }
 
 pub fn init_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let handle = log4rs::init_config(build(config)?)?;
+    let _ = HANDLE.set(handle);
+    Ok(())
+}
+
+/// Replaces the running log setup with one built from `config`. A no-op when
+/// logging was never initialized through [`init_logging`] (tests, embeddings).
+pub fn reconfigure(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(handle) = HANDLE.get() {
+        handle.set_config(build(config)?);
+    }
+    Ok(())
+}
+
+fn build(config: &LoggingConfig) -> Result<Config, Box<dyn std::error::Error>> {
     let level: LevelFilter = config.level.parse().unwrap_or(LevelFilter::Info);
     // Clamp to at least 1 MiB (0 would rotate on every write) and use a checked
     // multiply so an absurd config value can't overflow and panic.
@@ -112,7 +136,7 @@ pub fn init_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Er
             .appender("server")
             .appender("console")
             .build(level);
-        log4rs::init_config(builder.build(root)?)?;
+        Ok(builder.build(root)?)
     } else if let Some(log_file) = &config.file {
         // Single combined file (legacy).
         let appender = rolling_appender(
@@ -126,11 +150,9 @@ pub fn init_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Er
             .appender("console")
             .appender("file")
             .build(level);
-        log4rs::init_config(builder.build(root)?)?;
+        Ok(builder.build(root)?)
     } else {
         let root = Root::builder().appender("console").build(level);
-        log4rs::init_config(builder.build(root)?)?;
+        Ok(builder.build(root)?)
     }
-
-    Ok(())
 }
@@ -0,0 +1,1481 @@
+//! A subset of the MinIO admin API (`/minio/admin/v3/…`), so `mc admin` and
+//! MinIO's Terraform provider can manage users, policies, groups and service
+//! accounts here, read server info and run heals.
+//!
+//! Calls are SigV4-signed on the S3 port like any S3 request; the auth layer
+//! resolves the caller and hands over its [`AdminCaller`], and each call is
+//! then authorized against the console's `admin:` actions — users, groups and
+//! service accounts need `admin:ManageUsers`, policies `admin:ManagePolicies`,
+//! info `admin:ViewStats`, heal status `admin:ViewScans` and starting a heal
+//! `admin:RunScan`. Config credentials are unrestricted as everywhere else.
+//!
+//! The mapping onto this server:
+//!
+//! - A MinIO user is an IAM user whose password is its secret key and who
+//!   signs with an access key of the same id (see
+//!   [`IamStore::set_user_credentials`]). Disabling it disables the user.
+//! - Canned policies are the IAM store's named policies; attaching them sets
+//!   the user's or group's policy to their merged statements.
+//! - Service accounts are the user's other access keys. One created with a
+//!   policy is scoped to it, on top of its user's own policy.
+//! - A heal is a storage scan; its client token is the scan report id.
+//!
+//! Payloads MinIO encrypts travel encrypted here too, sealed with the caller's
+//! secret key in MinIO's format: `salt(32) | id(1) | nonce(8) | stream`, where
+//! the key comes from Argon2id or PBKDF2 and the stream is `sio` — AES-256-GCM
+//! or ChaCha20-Poly1305 over 16 KiB fragments.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, RawQuery};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use chrono::{DateTime, SecondsFormat};
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, CHACHA20_POLY1305};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::event_hub::Event;
+use super::iam::{AccessKeyUpdate, Group, IamStore, IamUser, NewAccessKey};
+use super::identity::Identity;
+use super::jobs::perf_scan::ScanService;
+use super::logging::{TARGET_AUDIT, TARGET_AUTHZ};
+use super::policy::{PolicyDocument, Requirement, ADMIN_ACTIONS};
+use super::registry::TaskRegistry;
+use super::reload::LiveConfig;
+use super::scan_store::ScanStatus;
+use super::RequestId;
+use crate::storage::errors::StorageError;
+use crate::storage::store::LocalObjectStore;
+
+const PREFIX: &str = "/minio/admin/v3/";
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// True for a request to the admin API, which authorizes per call rather than
+/// through the S3 action map.
+pub(crate) fn is_admin_request(request: &Request<Body>) -> bool {
+    request.uri().path().starts_with(PREFIX)
+}
+
+/// The authenticated caller of an admin API request, attached by the auth
+/// layer: who it is, and the secret key its payloads are sealed with.
+#[derive(Clone)]
+pub(crate) struct AdminCaller {
+    pub identity: Identity,
+    pub secret_key: String,
+}
+
+/// What the admin API works on. Absent from routers built without IAM.
+#[derive(Clone)]
+pub(crate) struct AdminApi {
+    pub store: LocalObjectStore,
+    pub iam: IamStore,
+    pub config: LiveConfig,
+    pub scans: Arc<ScanService>,
+    pub tasks: Arc<TaskRegistry>,
+}
+
+pub(crate) async fn handle(
+    request_id: Option<Extension<RequestId>>,
+    api: Option<Extension<AdminApi>>,
+    caller: Option<Extension<AdminCaller>>,
+    method: Method,
+    Path(op): Path<String>,
+    RawQuery(raw_query): RawQuery,
+    body: Body,
+) -> Response {
+    let rid = request_id.map(|Extension(id)| id.0).unwrap_or_default();
+    let resource = format!("{PREFIX}{op}");
+    let (Some(Extension(api)), Some(Extension(caller))) = (api, caller) else {
+        let message = "the admin API needs auth.enabled";
+        return AdminError::new(StatusCode::NOT_IMPLEMENTED, "NotImplemented", message)
+            .into_response(&rid, &resource);
+    };
+    let Ok(body) = axum::body::to_bytes(body, MAX_BODY_BYTES).await else {
+        return AdminError::invalid("request body too large").into_response(&rid, &resource);
+    };
+    let call = Call {
+        api: &api,
+        caller: &caller,
+        rid: &rid,
+        query: super::sts::parse_form(raw_query.as_deref().unwrap_or("")),
+        body,
+    };
+    let result = match (method.as_str(), op.as_str()) {
+        ("GET", "info") => server_info(&call).await,
+        ("PUT", "add-user") => add_user(&call).await,
+        ("DELETE", "remove-user") => remove_user(&call).await,
+        ("GET", "list-users") => list_users(&call).await,
+        ("GET", "user-info") => user_info(&call).await,
+        ("PUT", "set-user-status") => set_user_status(&call).await,
+        ("GET", "list-canned-policies") => list_policies(&call).await,
+        ("GET", "info-canned-policy") => policy_info(&call).await,
+        ("PUT", "add-canned-policy") => add_policy(&call).await,
+        ("DELETE", "remove-canned-policy") => remove_policy(&call).await,
+        ("PUT", "set-user-or-group-policy") => set_policy(&call).await,
+        ("POST", "idp/builtin/policy/attach") => change_policies(&call, true).await,
+        ("POST", "idp/builtin/policy/detach") => change_policies(&call, false).await,
+        ("PUT", "update-group-members") => update_group_members(&call).await,
+        ("GET", "group") => group_info(&call).await,
+        ("GET", "groups") => list_groups(&call).await,
+        ("PUT", "set-group-status") => set_group_status(&call).await,
+        ("PUT", "add-service-account") => add_service_account(&call).await,
+        ("GET", "list-service-accounts") => list_service_accounts(&call).await,
+        ("GET", "info-service-account") => service_account_info(&call).await,
+        ("POST", "update-service-account") => update_service_account(&call).await,
+        ("DELETE", "delete-service-account") => delete_service_account(&call).await,
+        ("POST", "background-heal/status") => background_heal_status(&call).await,
+        ("POST", heal_path) if heal_path == "heal" || heal_path.starts_with("heal/") => {
+            heal(&call, heal_path).await
+        }
+        _ => Err(AdminError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "NotImplemented",
+            format!("{method} {resource} is not supported by this server"),
+        )),
+    };
+    result.unwrap_or_else(|err| err.into_response(&rid, &resource))
+}
+
+// ── request plumbing ──────────────────────────────────────────────────────────
+
+struct Call<'a> {
+    api: &'a AdminApi,
+    caller: &'a AdminCaller,
+    rid: &'a str,
+    query: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl Call<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, AdminError> {
+        self.param(name)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| AdminError::invalid(format!("{name} is required")))
+    }
+
+    /// Who the caller is, for logs and the audit trail.
+    fn actor(&self) -> &str {
+        let identity = &self.caller.identity;
+        identity.username().or(identity.access_key()).unwrap_or("-")
+    }
+
+    /// The caller's IAM user; config credentials have none.
+    fn own_user(&self) -> Option<&str> {
+        fn iam_user(identity: &Identity) -> Option<&str> {
+            match identity {
+                Identity::Unrestricted { .. } => None,
+                Identity::Iam { username, .. } => Some(username),
+                Identity::Scoped { identity, .. } => iam_user(identity),
+            }
+        }
+        iam_user(&self.caller.identity)
+    }
+
+    fn allowed(&self, action: &'static str) -> bool {
+        self.caller.identity.authorize(&[Requirement::admin(action)])
+    }
+
+    fn require(&self, action: &'static str) -> Result<(), AdminError> {
+        self.require_any(&[action])
+    }
+
+    fn require_any(&self, actions: &[&'static str]) -> Result<(), AdminError> {
+        if actions.iter().any(|action| self.allowed(action)) {
+            return Ok(());
+        }
+        log::warn!(
+            target: TARGET_AUTHZ,
+            "[{}] authz DENY admin api user={} needs={}",
+            self.rid,
+            self.actor(),
+            actions.join("|"),
+        );
+        Err(AdminError::new(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            format!("{} is required", actions.join(" or ")),
+        ))
+    }
+
+    /// Service accounts are the caller's own to manage; anyone else's need
+    /// `admin:ManageUsers`, and an administrator's need an administrator.
+    fn require_owner(&self, owner: &str) -> Result<(), AdminError> {
+        match self.own_user() == Some(owner) {
+            true => Ok(()),
+            false => {
+                self.require("admin:ManageUsers")?;
+                self.guard_admin_target(owner)
+            }
+        }
+    }
+
+    /// Config credentials or an admin-group member, not scoped below any
+    /// `admin:` action — what the console calls an administrator.
+    fn is_full_admin(&self) -> bool {
+        let admin = self.own_user().is_none_or(|user| self.api.iam.is_admin(user));
+        admin && ADMIN_ACTIONS.iter().all(|(action, _)| self.allowed(action))
+    }
+
+    /// Delegated administration stops at administrators and at the caller's
+    /// own account: changing either's keys, policies or groups takes an
+    /// administrator, as in the console.
+    fn guard_admin_target(&self, username: &str) -> Result<(), AdminError> {
+        if self.is_full_admin() {
+            return Ok(());
+        }
+        if self.own_user() == Some(username) {
+            return self.deny("only an administrator can change your own account".to_string());
+        }
+        if !self.api.iam.is_admin(username) {
+            return Ok(());
+        }
+        self.deny(format!(
+            "{username} is an administrator; only an administrator can change that account"
+        ))
+    }
+
+    /// Nor does it reach the groups the caller belongs to.
+    fn guard_own_group(&self, group: &str) -> Result<(), AdminError> {
+        let groups = self.own_user().map(|user| self.api.iam.groups_for(user)).unwrap_or_default();
+        if self.is_full_admin() || !groups.iter().any(|name| name.eq_ignore_ascii_case(group)) {
+            return Ok(());
+        }
+        self.deny(format!("you belong to {group}; only an administrator can change it"))
+    }
+
+    /// A delegate hands out only what it holds itself.
+    fn guard_grant(&self, policy: Option<&PolicyDocument>) -> Result<(), AdminError> {
+        match policy {
+            Some(policy) if !self.is_full_admin() && !self.caller.identity.holds(policy) => {
+                self.deny("that policy grants more than you hold".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A secret lets whoever holds it act as its user, so a delegate sets one
+    /// only for users who hold nothing the delegate does not.
+    fn guard_act_as(&self, username: &str) -> Result<(), AdminError> {
+        match self.api.iam.policy_for(username) {
+            Some(policy) if !self.is_full_admin() && !self.caller.identity.holds(&policy) => {
+                self.deny(format!("{username} holds permissions you do not; ask an administrator"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Service account secrets for someone else take an administrator.
+    fn guard_key_issue(&self, owner: &str) -> Result<(), AdminError> {
+        if self.is_full_admin() || self.own_user() == Some(owner) {
+            return Ok(());
+        }
+        self.deny("only an administrator can issue keys for another user".to_string())
+    }
+
+    fn deny(&self, message: String) -> Result<(), AdminError> {
+        log::warn!(
+            target: TARGET_AUTHZ,
+            "[{}] authz DENY admin api user={} {message}",
+            self.rid,
+            self.actor(),
+        );
+        Err(AdminError::new(StatusCode::FORBIDDEN, "AccessDenied", message))
+    }
+
+    fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, AdminError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|err| AdminError::invalid(format!("malformed request body: {err}")))
+    }
+
+    /// The body, decrypted with the caller's secret key.
+    async fn sealed_json<T: serde::de::DeserializeOwned>(&self) -> Result<T, AdminError> {
+        let secret = self.caller.secret_key.clone();
+        let body = self.body.clone();
+        let plain = tokio::task::spawn_blocking(move || decrypt_data(&secret, &body))
+            .await
+            .map_err(|err| AdminError::internal(err.to_string()))?
+            .map_err(|err| {
+                AdminError::new(StatusCode::BAD_REQUEST, "XMinioAdminConfigBadJSON", err)
+            })?;
+        serde_json::from_slice(&plain)
+            .map_err(|err| AdminError::invalid(format!("malformed request body: {err}")))
+    }
+
+    fn audit(&self, action: &str, target: impl Into<String>) {
+        let target = target.into();
+        let actor = self.actor();
+        log::info!(target: TARGET_AUDIT, "[{}] {actor} {action} {target} OK", self.rid);
+        self.api.tasks.publish(Event::Audit {
+            actor: actor.to_string(),
+            action: action.to_string(),
+            target,
+            allowed: true,
+            request_id: self.rid.to_string(),
+        });
+    }
+}
+
+fn ok() -> Result<Response, AdminError> {
+    Ok(StatusCode::OK.into_response())
+}
+
+fn plain(value: &impl Serialize) -> Result<Response, AdminError> {
+    let body = serde_json::to_vec(value).map_err(|err| AdminError::internal(err.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// `value` as JSON sealed with the caller's secret key.
+async fn sealed(call: &Call<'_>, value: &impl Serialize) -> Result<Response, AdminError> {
+    let data = serde_json::to_vec(value).map_err(|err| AdminError::internal(err.to_string()))?;
+    let secret = call.caller.secret_key.clone();
+    let body = tokio::task::spawn_blocking(move || encrypt_data(&secret, &data))
+        .await
+        .map_err(|err| AdminError::internal(err.to_string()))?
+        .map_err(AdminError::internal)?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response())
+}
+
+fn rfc3339(ms: i64) -> String {
+    DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn status_name(enabled: bool) -> &'static str {
+    if enabled {
+        "enabled"
+    } else {
+        "disabled"
+    }
+}
+
+/// A comma-separated list, as MinIO passes policy names.
+fn split_names(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// ── errors ────────────────────────────────────────────────────────────────────
+
+/// A MinIO-shaped error: `{"Code", "Message", "Resource", "RequestId"}`.
+#[derive(Debug)]
+struct AdminError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl AdminError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "XMinioAdminInvalidArgument", message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalError", message)
+    }
+
+    fn no_such_user(username: &str) -> Self {
+        let message = format!("the specified user {username} does not exist");
+        Self::new(StatusCode::NOT_FOUND, "XMinioAdminNoSuchUser", message)
+    }
+
+    fn no_such_service_account(access_key: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "XMinioAdminServiceAccountNotFound",
+            format!("the specified service account {access_key} does not exist"),
+        )
+    }
+
+    fn into_response(self, request_id: &str, resource: &str) -> Response {
+        let body = json!({
+            "Code": self.code,
+            "Message": self.message,
+            "Resource": resource,
+            "RequestId": request_id,
+        });
+        (self.status, axum::Json(body)).into_response()
+    }
+}
+
+/// IAM store errors carry their cause in the message; the common ones get
+/// the code MinIO clients look for.
+impl From<StorageError> for AdminError {
+    fn from(err: StorageError) -> Self {
+        let StorageError::Io(message) = err else {
+            return Self::internal(err.to_string());
+        };
+        let code = [
+            ("no such user", "XMinioAdminNoSuchUser"),
+            ("no such group", "XMinioAdminNoSuchGroup"),
+            ("no such editable group", "XMinioAdminNoSuchGroup"),
+            ("no such policy", "XMinioAdminNoSuchPolicy"),
+            ("no such access key", "XMinioAdminServiceAccountNotFound"),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| message.starts_with(prefix))
+        .map(|(_, code)| code);
+        match code {
+            Some(code) => Self::new(StatusCode::NOT_FOUND, code, message),
+            None => Self::invalid(message),
+        }
+    }
+}
+
+// ── server info ───────────────────────────────────────────────────────────────
+
+async fn server_info(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ViewStats")?;
+    let buckets = call.api.store.list_buckets().await?;
+    let (mut objects, mut bytes) = (0u64, 0u64);
+    for (name, _) in &buckets {
+        let usage = call.api.store.bucket_usage(name).await?;
+        objects += usage.objects;
+        bytes += usage.bytes;
+    }
+    let config = call.api.config.current();
+    plain(&json!({
+        "mode": "online",
+        "deploymentID": "",
+        "buckets": { "count": buckets.len() },
+        "objects": { "count": objects },
+        "usage": { "size": bytes },
+        "backend": { "backendType": "FS" },
+        "servers": [{
+            "state": "online",
+            "endpoint": config.auth.public_hostname.clone().unwrap_or_default(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "drives": [],
+        }],
+    }))
+}
+
+// ── users ─────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddUserRequest {
+    secret_key: String,
+    #[serde(default)]
+    status: String,
+}
+
+/// The `UserInfo` MinIO lists and describes users with.
+fn user_entry(iam: &IamStore, user: &IamUser) -> Value {
+    json!({
+        "policyName": user.policy_names.join(","),
+        "status": status_name(!user.disabled),
+        "memberOf": iam.groups_for(&user.username),
+        "updatedAt": rfc3339(user.created_at_ms),
+    })
+}
+
+async fn find_user(iam: &IamStore, username: &str) -> Result<IamUser, AdminError> {
+    let users = iam.list_users().await?;
+    users
+        .into_iter()
+        .find(|user| user.username == username)
+        .ok_or_else(|| AdminError::no_such_user(username))
+}
+
+async fn add_user(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManageUsers")?;
+    let username = call.required("accessKey")?;
+    let req: AddUserRequest = call.sealed_json().await?;
+    let config = call.api.config.current();
+    if config.find_secret(username).is_some() || config.find_builtin_user(username).is_some() {
+        let message = format!("{username} is a built-in credential and cannot be changed here");
+        return Err(AdminError::invalid(message));
+    }
+    if call.own_user() == Some(username) {
+        let message = "change your own password in the console instead";
+        return Err(AdminError::new(StatusCode::FORBIDDEN, "AccessDenied", message));
+    }
+    call.guard_admin_target(username)?;
+    call.guard_act_as(username)?;
+    call.api.iam.set_user_credentials(username, &req.secret_key).await?;
+    if !req.status.is_empty() {
+        call.api.iam.set_user_disabled(username, req.status == "disabled").await?;
+    }
+    call.audit("create_user", username);
+    ok()
+}
+
+async fn remove_user(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManageUsers")?;
+    let username = call.required("accessKey")?;
+    if !call.api.iam.user_exists(username) {
+        return Err(AdminError::no_such_user(username));
+    }
+    call.guard_admin_target(username)?;
+    call.api.iam.delete_user(username).await?;
+    call.audit("delete_user", username);
+    ok()
+}
+
+async fn list_users(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require_any(&["admin:ManageUsers", "admin:ManagePolicies"])?;
+    let iam = &call.api.iam;
+    let users: BTreeMap<String, Value> = iam
+        .list_users()
+        .await?
+        .iter()
+        .map(|user| (user.username.clone(), user_entry(iam, user)))
+        .collect();
+    sealed(call, &users).await
+}
+
+async fn user_info(call: &Call<'_>) -> Result<Response, AdminError> {
+    let username = call.required("accessKey")?;
+    if call.own_user() != Some(username) {
+        call.require_any(&["admin:ManageUsers", "admin:ManagePolicies"])?;
+    }
+    let user = find_user(&call.api.iam, username).await?;
+    plain(&user_entry(&call.api.iam, &user))
+}
+
+async fn set_user_status(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManageUsers")?;
+    let username = call.required("accessKey")?;
+    let disabled = match call.required("status")? {
+        "enabled" => false,
+        "disabled" => true,
+        other => return Err(AdminError::invalid(format!("unknown status {other:?}"))),
+    };
+    if !call.api.iam.user_exists(username) {
+        return Err(AdminError::no_such_user(username));
+    }
+    call.guard_admin_target(username)?;
+    call.api.iam.set_user_disabled(username, disabled).await?;
+    call.audit("set_user_status", format!("{username} {}", status_name(!disabled)));
+    ok()
+}
+
+// ── canned policies ───────────────────────────────────────────────────────────
+
+async fn list_policies(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require_any(&["admin:ManagePolicies", "admin:ManageUsers"])?;
+    let policies: BTreeMap<String, PolicyDocument> = call
+        .api
+        .iam
+        .list_named_policies()
+        .await?
+        .into_iter()
+        .map(|named| (named.name, named.policy))
+        .collect();
+    plain(&policies)
+}
+
+async fn policy_info(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require_any(&["admin:ManagePolicies", "admin:ManageUsers"])?;
+    let name = call.required("name")?;
+    let Some(named) = call.api.iam.named_policy(name).await? else {
+        let message = format!("the canned policy {name} does not exist");
+        return Err(AdminError::new(StatusCode::NOT_FOUND, "XMinioAdminNoSuchPolicy", message));
+    };
+    if call.param("v") != Some("2") {
+        return plain(&named.policy);
+    }
+    plain(&json!({
+        "PolicyName": named.name,
+        "Policy": named.policy,
+        "CreateDate": rfc3339(named.created_at_ms),
+        "UpdateDate": rfc3339(named.updated_at_ms),
+    }))
+}
+
+async fn add_policy(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManagePolicies")?;
+    let name = call.required("name")?;
+    let policy: PolicyDocument = call.json()?;
+    // Editing a named policy changes everyone it is attached to.
+    call.guard_grant(Some(&policy))?;
+    call.api.iam.put_named_policy(name, &policy).await?;
+    call.audit("put_policy", name);
+    ok()
+}
+
+async fn remove_policy(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManagePolicies")?;
+    let name = call.required("name")?;
+    call.api.iam.delete_named_policy(name).await?;
+    call.audit("delete_policy", name);
+    ok()
+}
+
+/// The named policies attached to a user or group right now.
+async fn attached_names(
+    iam: &IamStore,
+    target: &str,
+    is_group: bool,
+) -> Result<Vec<String>, AdminError> {
+    if !is_group {
+        return Ok(find_user(iam, target).await?.policy_names);
+    }
+    let groups = iam.list_groups().await?;
+    groups
+        .into_iter()
+        .find(|group| group.group.name().eq_ignore_ascii_case(target))
+        .map(|group| group.policy_names)
+        .ok_or_else(|| {
+            let message = format!("the specified group {target} does not exist");
+            AdminError::new(StatusCode::NOT_FOUND, "XMinioAdminNoSuchGroup", message)
+        })
+}
+
+async fn attach(
+    call: &Call<'_>,
+    target: &str,
+    is_group: bool,
+    names: &[String],
+) -> Result<(), AdminError> {
+    let iam = &call.api.iam;
+    if is_group {
+        call.guard_own_group(target)?;
+    } else {
+        call.guard_admin_target(target)?;
+    }
+    let current = attached_names(iam, target, is_group).await?;
+    for name in names.iter().filter(|name| !current.contains(name)) {
+        let named = iam.named_policy(name).await?;
+        call.guard_grant(named.as_ref().map(|named| &named.policy))?;
+    }
+    if is_group {
+        iam.set_group_policy_names(target, names).await?;
+        call.audit("set_group_policy", format!("{target} policies={names:?}"));
+    } else {
+        iam.set_user_policy_names(target, names).await?;
+        call.audit("set_policy", format!("{target} policies={names:?}"));
+    }
+    Ok(())
+}
+
+async fn set_policy(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManagePolicies")?;
+    let target = call.required("userOrGroup")?;
+    let is_group = call.param("isGroup") == Some("true");
+    let names = split_names(call.param("policyName").unwrap_or(""));
+    attach(call, target, is_group, &names).await?;
+    ok()
+}
+
+#[derive(Deserialize)]
+struct PolicyAssociation {
+    #[serde(default)]
+    policies: Vec<String>,
+    #[serde(default)]
+    user: String,
+    #[serde(default)]
+    group: String,
+}
+
+async fn change_policies(call: &Call<'_>, attaching: bool) -> Result<Response, AdminError> {
+    call.require("admin:ManagePolicies")?;
+    let req: PolicyAssociation = call.sealed_json().await?;
+    let (target, is_group) = match (req.user.is_empty(), req.group.is_empty()) {
+        (false, true) => (req.user.as_str(), false),
+        (true, false) => (req.group.as_str(), true),
+        _ => return Err(AdminError::invalid("exactly one of user and group is required")),
+    };
+    let current = attached_names(&call.api.iam, target, is_group).await?;
+    let (mut names, mut changed) = (current.clone(), Vec::new());
+    for policy in &req.policies {
+        if attaching && !names.contains(policy) {
+            names.push(policy.clone());
+            changed.push(policy.clone());
+        } else if !attaching && names.contains(policy) {
+            names.retain(|name| name != policy);
+            changed.push(policy.clone());
+        }
+    }
+    if !changed.is_empty() {
+        attach(call, target, is_group, &names).await?;
+    }
+    let (attached, detached) = match attaching {
+        true => (changed, Vec::new()),
+        false => (Vec::new(), changed),
+    };
+    sealed(
+        call,
+        &json!({
+            "policiesAttached": attached,
+            "policiesDetached": detached,
+            "updatedAt": rfc3339(crate::storage::time::now_ms()),
+        }),
+    )
+    .await
+}
+
+// ── groups ────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupMembers {
+    group: String,
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    is_remove: bool,
+}
+
+/// Users whose groups include `group`.
+async fn members_of(iam: &IamStore, group: &str) -> Result<Vec<String>, AdminError> {
+    Ok(iam
+        .list_users()
+        .await?
+        .into_iter()
+        .filter(|user| {
+            let groups = iam.groups_for(&user.username);
+            groups.iter().any(|name| name.eq_ignore_ascii_case(group))
+        })
+        .map(|user| user.username)
+        .collect())
+}
+
+async fn update_group_members(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManageUsers")?;
+    let req: GroupMembers = call.json()?;
+    let iam = &call.api.iam;
+    // Membership of the admin group makes an administrator; only one may
+    // grant or take it away.
+    if req.group.eq_ignore_ascii_case(Group::Admin.name()) && !call.is_full_admin() {
+        call.deny("only an administrator can change the admin group's members".to_string())?;
+    }
+    let exists = attached_names(iam, &req.group, true).await.is_ok();
+    if req.is_remove && req.members.is_empty() {
+        // MinIO's "remove the group": only an empty one goes.
+        if !exists {
+            return Err(AdminError::from(StorageError::Io(format!("no such group {}", req.group))));
+        }
+        if !members_of(iam, &req.group).await?.is_empty() {
+            let message = format!("group {} is not empty", req.group);
+            return Err(AdminError::new(StatusCode::CONFLICT, "XMinioAdminGroupNotEmpty", message));
+        }
+        iam.delete_group(&req.group).await?;
+        call.audit("delete_group", &req.group);
+        return ok();
+    }
+    for member in &req.members {
+        if !iam.user_exists(member) {
+            return Err(AdminError::no_such_user(member));
+        }
+        call.guard_admin_target(member)?;
+    }
+    // Joining a group is being granted its policy.
+    if exists && !req.is_remove && !req.members.is_empty() {
+        let groups = iam.list_groups().await?;
+        let group = groups.iter().find(|group| group.group.name().eq_ignore_ascii_case(&req.group));
+        call.guard_grant(group.and_then(|group| group.policy.as_ref()))?;
+    }
+    if !exists && !req.is_remove {
+        iam.create_group(&req.group, None).await?;
+        call.audit("create_group", &req.group);
+    }
+    for member in &req.members {
+        let mut names = iam.groups_for(member);
+        names.retain(|name| !name.eq_ignore_ascii_case(&req.group));
+        if !req.is_remove {
+            names.push(req.group.clone());
+        }
+        let groups = iam.resolve_groups(&names).await?;
+        iam.set_user_groups(member, &groups).await?;
+        call.audit("set_user_groups", format!("{member} groups={names:?}"));
+    }
+    ok()
+}
+
+async fn group_info(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require_any(&["admin:ManageUsers", "admin:ManagePolicies"])?;
+    let name = call.required("group")?;
+    let policies = attached_names(&call.api.iam, name, true).await?;
+    plain(&json!({
+        "name": name,
+        "status": "enabled",
+        "members": members_of(&call.api.iam, name).await?,
+        "policy": policies.join(","),
+    }))
+}
+
+async fn list_groups(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require_any(&["admin:ManageUsers", "admin:ManagePolicies"])?;
+    let groups = call.api.iam.list_groups().await?;
+    let names: Vec<&str> = groups.iter().map(|group| group.group.name()).collect();
+    plain(&names)
+}
+
+async fn set_group_status(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ManageUsers")?;
+    let name = call.required("group")?;
+    attached_names(&call.api.iam, name, true).await?;
+    match call.required("status")? {
+        "enabled" => ok(),
+        _ => Err(AdminError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "NotImplemented",
+            "groups cannot be disabled on this server",
+        )),
+    }
+}
+
+// ── service accounts ──────────────────────────────────────────────────────────
+
+/// A policy as MinIO clients send it: a JSON document, or a string holding
+/// one. Empty means none.
+fn scope_of(raw: Option<&Value>) -> Result<Option<PolicyDocument>, AdminError> {
+    let value = match raw {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(text)) if text.trim().is_empty() => return Ok(None),
+        Some(Value::String(text)) => serde_json::from_str(text)
+            .map_err(|err| AdminError::invalid(format!("malformed policy: {err}")))?,
+        Some(value) => value.clone(),
+    };
+    if value.as_object().is_some_and(|object| object.is_empty()) {
+        return Ok(None);
+    }
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|err| AdminError::invalid(format!("malformed policy: {err}")))
+}
+
+/// MinIO's "no expiry" is the zero time or the epoch; anything else would
+/// need expiring keys, which this server does not have.
+fn check_no_expiry(expiration: Option<&str>) -> Result<(), AdminError> {
+    match expiration {
+        None => Ok(()),
+        Some(value) if value.starts_with("0001-01-01") || value.starts_with("1970-01-01") => {
+            Ok(())
+        }
+        Some(_) => Err(AdminError::invalid("service accounts cannot expire on this server")),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddServiceAccount {
+    #[serde(default)]
+    policy: Option<Value>,
+    #[serde(default)]
+    target_user: String,
+    #[serde(default)]
+    access_key: String,
+    #[serde(default)]
+    secret_key: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    expiration: Option<String>,
+}
+
+async fn add_service_account(call: &Call<'_>) -> Result<Response, AdminError> {
+    let req: AddServiceAccount = call.sealed_json().await?;
+    let owner = match (req.target_user.as_str(), call.own_user()) {
+        ("", Some(own)) => own.to_string(),
+        ("", None) => {
+            let message = "service accounts belong to IAM users; set targetUser";
+            return Err(AdminError::invalid(message));
+        }
+        (target, _) => target.to_string(),
+    };
+    call.require_owner(&owner)?;
+    call.guard_key_issue(&owner)?;
+    check_no_expiry(req.expiration.as_deref())?;
+    if !req.access_key.is_empty() && call.api.config.current().find_secret(&req.access_key).is_some()
+    {
+        return Err(AdminError::invalid(format!("access key {} is taken", req.access_key)));
+    }
+    let spec = NewAccessKey {
+        access_key: Some(req.access_key).filter(|key| !key.is_empty()),
+        secret_key: Some(req.secret_key).filter(|key| !key.is_empty()),
+        name: req.name,
+        description: req.description,
+        scope: scope_of(req.policy.as_ref())?,
+    };
+    let key = call.api.iam.create_access_key_with(&owner, spec).await?;
+    call.audit("create_access_key", format!("{owner} ak={}", key.access_key));
+    sealed(
+        call,
+        &json!({
+            "credentials": {
+                "accessKey": key.access_key,
+                "secretKey": key.secret_key,
+                "expiration": rfc3339(0),
+            }
+        }),
+    )
+    .await
+}
+
+/// A service account the caller may manage. The key a MinIO user signs with
+/// as itself is not one.
+async fn service_account(
+    call: &Call<'_>,
+    access_key: &str,
+) -> Result<super::iam::AccessKey, AdminError> {
+    let key = call
+        .api
+        .iam
+        .access_key(access_key)
+        .await?
+        .filter(|key| key.access_key != key.username)
+        .ok_or_else(|| AdminError::no_such_service_account(access_key))?;
+    call.require_owner(&key.username)?;
+    Ok(key)
+}
+
+fn account_status(active: bool) -> &'static str {
+    if active {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+async fn list_service_accounts(call: &Call<'_>) -> Result<Response, AdminError> {
+    let owner = match (call.param("user").filter(|user| !user.is_empty()), call.own_user()) {
+        (Some(user), _) => user.to_string(),
+        (None, Some(own)) => own.to_string(),
+        (None, None) => return sealed(call, &json!({ "accounts": [] })).await,
+    };
+    call.require_owner(&owner)?;
+    let keys = call.api.iam.list_access_keys(&owner).await?;
+    let accounts: Vec<Value> = keys
+        .iter()
+        .filter(|key| key.access_key != key.username)
+        .map(|key| {
+            json!({
+                "parentUser": key.username,
+                "accountStatus": account_status(key.active),
+                "impliedPolicy": key.scope.is_none(),
+                "accessKey": key.access_key,
+                "name": key.name,
+                "description": key.description,
+            })
+        })
+        .collect();
+    sealed(call, &json!({ "accounts": accounts })).await
+}
+
+async fn service_account_info(call: &Call<'_>) -> Result<Response, AdminError> {
+    let key = service_account(call, call.required("accessKey")?).await?;
+    // An unscoped key acts with its user's policy, which is what MinIO shows.
+    let policy = key
+        .scope
+        .clone()
+        .or_else(|| call.api.iam.policy_for(&key.username));
+    let policy = policy
+        .map(|policy| serde_json::to_string(&policy).unwrap_or_default())
+        .unwrap_or_default();
+    sealed(
+        call,
+        &json!({
+            "parentUser": key.username,
+            "accountStatus": account_status(key.active),
+            "impliedPolicy": key.scope.is_none(),
+            "policy": policy,
+            "name": key.name,
+            "description": key.description,
+        }),
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateServiceAccount {
+    #[serde(default)]
+    new_policy: Option<Value>,
+    #[serde(default)]
+    new_secret_key: String,
+    #[serde(default)]
+    new_status: String,
+    #[serde(default)]
+    new_name: String,
+    #[serde(default)]
+    new_description: String,
+    #[serde(default)]
+    new_expiration: Option<String>,
+}
+
+async fn update_service_account(call: &Call<'_>) -> Result<Response, AdminError> {
+    let key = service_account(call, call.required("accessKey")?).await?;
+    let req: UpdateServiceAccount = call.sealed_json().await?;
+    if !req.new_secret_key.is_empty() {
+        call.guard_key_issue(&key.username)?;
+    }
+    check_no_expiry(req.new_expiration.as_deref())?;
+    let active = match req.new_status.as_str() {
+        "" => None,
+        "on" | "enabled" => Some(true),
+        "off" | "disabled" => Some(false),
+        other => return Err(AdminError::invalid(format!("unknown status {other:?}"))),
+    };
+    let update = AccessKeyUpdate {
+        secret_key: Some(req.new_secret_key).filter(|secret| !secret.is_empty()),
+        active,
+        name: Some(req.new_name).filter(|name| !name.is_empty()),
+        description: Some(req.new_description).filter(|text| !text.is_empty()),
+        scope: match &req.new_policy {
+            None | Some(Value::Null) => None,
+            raw => Some(scope_of(raw.as_ref())?),
+        },
+    };
+    call.api.iam.update_access_key(&key.access_key, update).await?;
+    call.audit("update_access_key", format!("{} ak={}", key.username, key.access_key));
+    ok()
+}
+
+async fn delete_service_account(call: &Call<'_>) -> Result<Response, AdminError> {
+    let key = service_account(call, call.required("accessKey")?).await?;
+    call.api.iam.delete_access_key(&key.access_key).await?;
+    call.audit("delete_access_key", format!("{} ak={}", key.username, key.access_key));
+    ok()
+}
+
+// ── heal (storage scans) ──────────────────────────────────────────────────────
+
+async fn background_heal_status(call: &Call<'_>) -> Result<Response, AdminError> {
+    call.require("admin:ViewScans")?;
+    let last = call.api.scans.store().list_reports(1).await?.into_iter().next();
+    let (scanned, last_activity) = last
+        .map(|report| (report.objects(), report.finished_at_ms.max(report.started_at_ms)))
+        .unwrap_or_default();
+    plain(&json!({
+        "offline_nodes": 0,
+        "heal_disks": [],
+        "scanned_items_count": scanned,
+        "last_heal_activity": rfc3339(last_activity),
+        "running": call.api.scans.busy().is_some(),
+    }))
+}
+
+/// `POST heal/[bucket[/prefix]]`: starts a scan, or with `clientToken`
+/// reports on one; `forceStop` cancels the running one.
+async fn heal(call: &Call<'_>, path: &str) -> Result<Response, AdminError> {
+    let bucket = path
+        .strip_prefix("heal")
+        .unwrap_or("")
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("");
+    if let Some(token) = call.param("clientToken").filter(|token| !token.is_empty()) {
+        call.require("admin:ViewScans")?;
+        return heal_status(call, token).await;
+    }
+    call.require("admin:RunScan")?;
+    if call.param("forceStop") == Some("true") {
+        let snapshot = call.api.scans.snapshot();
+        if let Some(task_id) = snapshot["task_id"].as_str() {
+            call.api.tasks.cancel(task_id);
+            call.audit("cancel_task", task_id);
+        }
+        return ok();
+    }
+    let existing: Vec<String> = call
+        .api
+        .store
+        .list_buckets()
+        .await?
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    let buckets = match bucket {
+        "" => existing,
+        bucket if existing.iter().any(|name| name == bucket) => vec![bucket.to_string()],
+        bucket => {
+            let message = format!("the specified bucket {bucket} does not exist");
+            return Err(AdminError::new(StatusCode::NOT_FOUND, "NoSuchBucket", message));
+        }
+    };
+    if buckets.is_empty() {
+        return Err(AdminError::invalid("there are no buckets to heal"));
+    }
+    let Some(report_id) = super::jobs::perf_scan::spawn(
+        call.api.store.clone(),
+        call.api.scans.clone(),
+        call.api.tasks.clone(),
+        buckets.clone(),
+        call.actor().to_string(),
+    ) else {
+        let message = "a storage scan or repair is already running";
+        return Err(AdminError::new(StatusCode::BAD_REQUEST, "XMinioHealAlreadyRunning", message));
+    };
+    call.audit("storage_scan", format!("{} bucket(s)", buckets.len()));
+    plain(&json!({
+        "clientToken": report_id,
+        "clientAddress": "",
+        "startTime": rfc3339(crate::storage::time::now_ms()),
+    }))
+}
+
+async fn heal_status(call: &Call<'_>, token: &str) -> Result<Response, AdminError> {
+    let Some(report) = call.api.scans.store().get_report(token).await? else {
+        let message = format!("no heal sequence with client token {token}");
+        return Err(AdminError::new(StatusCode::NOT_FOUND, "XMinioHealNoSuchProcess", message));
+    };
+    let summary = match report.status {
+        ScanStatus::Running => "running",
+        ScanStatus::Completed => "finished",
+        ScanStatus::Cancelled | ScanStatus::Failed => "stopped",
+    };
+    let items: Vec<Value> = report
+        .buckets
+        .iter()
+        .enumerate()
+        .map(|(index, bucket)| {
+            json!({
+                "resultId": index + 1,
+                "type": "bucket",
+                "bucket": bucket.bucket,
+                "objectSize": bucket.logical_bytes,
+                "detail": format!("{} finding(s)", bucket.findings.values().sum::<u64>()),
+            })
+        })
+        .collect();
+    plain(&json!({
+        "summary": summary,
+        "detail": report.error.clone().unwrap_or_default(),
+        "startTime": rfc3339(report.started_at_ms),
+        "settings": {},
+        "items": items,
+    }))
+}
+
+// ── payload encryption ────────────────────────────────────────────────────────
+
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 8;
+const TAG_LEN: usize = 16;
+/// `sio`'s fragment size: plaintext is sealed 16 KiB at a time.
+const FRAGMENT_LEN: usize = 16 * 1024;
+/// Key derivations and ciphers, by the id byte MinIO writes after the salt.
+const ARGON2ID_AES_GCM: u8 = 0x00;
+const ARGON2ID_CHACHA20_POLY1305: u8 = 0x01;
+const PBKDF2_AES_GCM: u8 = 0x02;
+const PBKDF2_ITERATIONS: u32 = 8192;
+
+fn derive_key(id: u8, secret: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    let algorithm = match id {
+        ARGON2ID_AES_GCM | ARGON2ID_CHACHA20_POLY1305 => {
+            let params = Params::new(64 * 1024, 1, 4, Some(key.len()))
+                .map_err(|err| err.to_string())?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(secret.as_bytes(), salt, &mut key)
+                .map_err(|err| err.to_string())?;
+            match id {
+                ARGON2ID_AES_GCM => &AES_256_GCM,
+                _ => &CHACHA20_POLY1305,
+            }
+        }
+        PBKDF2_AES_GCM => {
+            let iterations = std::num::NonZeroU32::new(PBKDF2_ITERATIONS).expect("non-zero");
+            ring::pbkdf2::derive(
+                ring::pbkdf2::PBKDF2_HMAC_SHA256,
+                iterations,
+                salt,
+                secret.as_bytes(),
+                &mut key,
+            );
+            &AES_256_GCM
+        }
+        other => return Err(format!("unknown encryption scheme {other:#04x}")),
+    };
+    let key = UnboundKey::new(algorithm, &key).map_err(|_| "invalid key".to_string())?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// The nonce of fragment `seq`: the stream nonce, then `seq` little-endian.
+fn fragment_nonce(nonce: &[u8; NONCE_LEN], seq: u32) -> Nonce {
+    let mut bytes = [0u8; 12];
+    bytes[..NONCE_LEN].copy_from_slice(nonce);
+    bytes[NONCE_LEN..].copy_from_slice(&seq.to_le_bytes());
+    Nonce::assume_unique_for_key(bytes)
+}
+
+/// The associated data of every fragment: a flag byte, set on the last one,
+/// then the tag sealing nothing under sequence number 0.
+fn stream_ad(key: &LessSafeKey, nonce: &[u8; NONCE_LEN]) -> Result<[u8; 1 + TAG_LEN], String> {
+    let tag = key
+        .seal_in_place_separate_tag(fragment_nonce(nonce, 0), Aad::empty(), &mut [])
+        .map_err(|_| "seal failed".to_string())?;
+    let mut ad = [0u8; 1 + TAG_LEN];
+    ad[1..].copy_from_slice(tag.as_ref());
+    Ok(ad)
+}
+
+fn encrypt_with(
+    id: u8,
+    secret: &str,
+    salt: &[u8; SALT_LEN],
+    nonce: &[u8; NONCE_LEN],
+    data: &[u8],
+) -> Result<Vec<u8>, String> {
+    let key = derive_key(id, secret, salt)?;
+    let mut ad = stream_ad(&key, nonce)?;
+    let fragments = data.len().div_ceil(FRAGMENT_LEN).max(1);
+    let mut out = Vec::with_capacity(SALT_LEN + 1 + NONCE_LEN + data.len() + fragments * TAG_LEN);
+    out.extend_from_slice(salt);
+    out.push(id);
+    out.extend_from_slice(nonce);
+    for index in 0..fragments {
+        let start = index * FRAGMENT_LEN;
+        let mut fragment = data[start..(start + FRAGMENT_LEN).min(data.len())].to_vec();
+        if index + 1 == fragments {
+            ad[0] = 0x80;
+        }
+        key.seal_in_place_append_tag(
+            fragment_nonce(nonce, index as u32 + 1),
+            Aad::from(&ad),
+            &mut fragment,
+        )
+        .map_err(|_| "seal failed".to_string())?;
+        out.extend_from_slice(&fragment);
+    }
+    Ok(out)
+}
+
+/// Seals `data` as MinIO's admin client does: Argon2id and AES-256-GCM.
+fn encrypt_data(secret: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+    encrypt_with(ARGON2ID_AES_GCM, secret, &salt, &nonce, data)
+}
+
+/// Opens a payload sealed by any MinIO client: every key derivation and
+/// cipher it may pick.
+fn decrypt_data(secret: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    const HEADER_LEN: usize = SALT_LEN + 1 + NONCE_LEN;
+    if data.len() < HEADER_LEN + TAG_LEN {
+        return Err("the request body is not an encrypted payload".into());
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let id = rest[0];
+    let nonce: [u8; NONCE_LEN] = rest[1..1 + NONCE_LEN].try_into().expect("nonce length");
+    let stream = &rest[1 + NONCE_LEN..];
+    let key = derive_key(id, secret, salt)?;
+    let mut ad = stream_ad(&key, &nonce)?;
+    let fragments: Vec<&[u8]> = stream.chunks(FRAGMENT_LEN + TAG_LEN).collect();
+    let mut out = Vec::with_capacity(stream.len());
+    for (index, fragment) in fragments.iter().enumerate() {
+        if index + 1 == fragments.len() {
+            ad[0] = 0x80;
+        }
+        let mut buf = fragment.to_vec();
+        let plain = key
+            .open_in_place(fragment_nonce(&nonce, index as u32 + 1), Aad::from(&ad), &mut buf)
+            .map_err(|_| "the payload does not decrypt with the caller's secret key")?;
+        out.extend_from_slice(plain);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payloads_round_trip_across_fragments_and_schemes() {
+        let salt = [7u8; SALT_LEN];
+        let nonce = [9u8; NONCE_LEN];
+        for len in [0, 1, FRAGMENT_LEN - 1, FRAGMENT_LEN, FRAGMENT_LEN + 1, 3 * FRAGMENT_LEN] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let sealed = encrypt_with(PBKDF2_AES_GCM, "secret123", &salt, &nonce, &data).unwrap();
+            let fragments = len.div_ceil(FRAGMENT_LEN).max(1);
+            assert_eq!(sealed.len(), SALT_LEN + 1 + NONCE_LEN + len + fragments * TAG_LEN);
+            assert_eq!(sealed[SALT_LEN], PBKDF2_AES_GCM);
+            assert_eq!(decrypt_data("secret123", &sealed).unwrap(), data);
+            assert!(decrypt_data("wrong-secret", &sealed).is_err());
+        }
+        // Dropping the final fragment must not pass for a shorter payload.
+        let data = vec![1u8; 2 * FRAGMENT_LEN];
+        let sealed = encrypt_with(PBKDF2_AES_GCM, "secret123", &salt, &nonce, &data).unwrap();
+        let truncated = &sealed[..sealed.len() - FRAGMENT_LEN - TAG_LEN];
+        assert!(decrypt_data("secret123", truncated).is_err());
+
+        let sealed = encrypt_data("secret123", b"{\"status\":\"enabled\"}").unwrap();
+        assert_eq!(sealed[SALT_LEN], ARGON2ID_AES_GCM);
+        assert_eq!(decrypt_data("secret123", &sealed).unwrap(), b"{\"status\":\"enabled\"}");
+        let chacha =
+            encrypt_with(ARGON2ID_CHACHA20_POLY1305, "secret123", &salt, &nonce, b"x").unwrap();
+        assert_eq!(decrypt_data("secret123", &chacha).unwrap(), b"x");
+    }
+
+    fn call<'a>(api: &'a AdminApi, caller: &'a AdminCaller, query: &str, body: &[u8]) -> Call<'a> {
+        Call {
+            api,
+            caller,
+            rid: "test",
+            query: super::super::sts::parse_form(query),
+            body: Bytes::copy_from_slice(body),
+        }
+    }
+
+    fn status(result: Result<Response, AdminError>) -> StatusCode {
+        match result {
+            Ok(response) => response.status(),
+            Err(err) => err.status,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_user_manager_cannot_touch_administrators() {
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = IamStore::open(&tmp.path().join("admin")).await.unwrap();
+        for user in ["boss", "helper", "staff"] {
+            iam.create_user(user, "password123").await.unwrap();
+        }
+        let admin_group = iam.resolve_groups(&["admin".to_string()]).await.unwrap();
+        iam.set_user_groups("boss", &admin_group).await.unwrap();
+        let grant = serde_json::from_value(json!({"Statement": [{"Effect": "Allow",
+            "Action": ["admin:ManageUsers", "admin:ManagePolicies"], "Resource": "*"}]}));
+        iam.set_policy("helper", Some(&grant.unwrap())).await.unwrap();
+        let config = LiveConfig::new(Arc::new(super::super::config::AppConfig::default()));
+        let scans = super::super::scan_store::ScanStore::open(tmp.path()).await.unwrap();
+        let api = AdminApi {
+            store: LocalObjectStore::new(tmp.path()),
+            iam: iam.clone(),
+            config,
+            scans: ScanService::new(scans),
+            tasks: TaskRegistry::new(),
+        };
+        let as_user = |name: &str| AdminCaller {
+            identity: Identity::iam(name.to_string(), iam.policy_for(name)).over_tls(true),
+            secret_key: "helper-secret".to_string(),
+        };
+        let (helper, boss) = (&as_user("helper"), &as_user("boss"));
+        let call = |caller, query, body| call(&api, caller, query, body);
+        let sealed = |value: Value| encrypt_data("helper-secret", value.to_string().as_bytes());
+        let new_secret = sealed(json!({"secretKey": "taken-over-1"})).unwrap();
+
+        // Every way of changing an administrator's account is refused...
+        let denied = [
+            status(add_user(&call(helper, "accessKey=boss", &new_secret)).await),
+            status(remove_user(&call(helper, "accessKey=boss", b"")).await),
+            status(set_user_status(&call(helper, "accessKey=boss&status=disabled", b"")).await),
+            status(set_policy(&call(helper, "userOrGroup=boss&policyName=x", b"")).await),
+        ];
+        assert!(denied.iter().all(|code| *code == StatusCode::FORBIDDEN), "{denied:?}");
+        let into_admins = json!({"group": "admin", "members": ["helper"]}).to_string();
+        let res = update_group_members(&call(helper, "", into_admins.as_bytes())).await;
+        assert_eq!(status(res), StatusCode::FORBIDDEN);
+        assert!(!iam.is_admin("helper"));
+        let key = sealed(json!({"targetUser": "boss"})).unwrap();
+        let res = add_service_account(&call(helper, "", &key)).await;
+        assert_eq!(status(res), StatusCode::FORBIDDEN);
+        assert!(iam.user_exists("boss") && iam.is_admin("boss"));
+
+        // ...while other users stay the delegate's to manage, and an
+        // administrator may do all of it.
+        let res = set_user_status(&call(helper, "accessKey=staff&status=disabled", b"")).await;
+        assert_eq!(status(res), StatusCode::OK);
+        let key = sealed(json!({"targetUser": "staff"})).unwrap();
+        assert_eq!(status(add_service_account(&call(boss, "", &key)).await), StatusCode::OK);
+        let res = update_group_members(&call(boss, "", into_admins.as_bytes())).await;
+        assert_eq!(status(res), StatusCode::OK);
+        assert!(iam.is_admin("helper"));
+    }
+
+    #[tokio::test]
+    async fn a_delegate_grants_and_borrows_nothing_it_does_not_hold() {
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = IamStore::open(&tmp.path().join("admin")).await.unwrap();
+        for user in ["helper", "staff", "reader"] {
+            iam.create_user(user, "password123").await.unwrap();
+        }
+        let grant = serde_json::from_value(json!({"Statement": [{"Effect": "Allow",
+            "Action": ["admin:ManageUsers", "admin:ManagePolicies"], "Resource": "*"}]}));
+        iam.set_policy("helper", Some(&grant.unwrap())).await.unwrap();
+        let everything: PolicyDocument = serde_json::from_value(json!({"Statement": [
+            {"Effect": "Allow", "Action": "s3:*", "Resource": "*"}]}))
+        .unwrap();
+        iam.put_named_policy("everything", &everything).await.unwrap();
+        iam.set_policy("reader", Some(&everything)).await.unwrap();
+        iam.create_group("crew", Some(&everything)).await.unwrap();
+        let config = LiveConfig::new(Arc::new(super::super::config::AppConfig::default()));
+        let scans = super::super::scan_store::ScanStore::open(tmp.path()).await.unwrap();
+        let api = AdminApi {
+            store: LocalObjectStore::new(tmp.path()),
+            iam: iam.clone(),
+            config,
+            scans: ScanService::new(scans),
+            tasks: TaskRegistry::new(),
+        };
+        let helper = &AdminCaller {
+            identity: Identity::iam("helper".to_string(), iam.policy_for("helper")).over_tls(true),
+            secret_key: "helper-secret".to_string(),
+        };
+        let call = |query, body| call(&api, helper, query, body);
+        let sealed = |value: Value| encrypt_data("helper-secret", value.to_string().as_bytes());
+
+        let to_staff = "userOrGroup=staff&policyName=everything";
+        let to_self = "userOrGroup=helper&policyName=everything";
+        let widened = serde_json::to_vec(&everything).unwrap();
+        let into_crew = json!({"group": "crew", "members": ["staff"]}).to_string();
+        let key = sealed(json!({"targetUser": "staff"})).unwrap();
+        let new_secret = sealed(json!({"secretKey": "taken-over-1"})).unwrap();
+        let denied = [
+            status(set_policy(&call(to_staff, b"")).await),
+            status(set_policy(&call(to_self, b"")).await),
+            status(add_policy(&call("name=everything", &widened)).await),
+            status(update_group_members(&call("", into_crew.as_bytes())).await),
+            status(add_service_account(&call("", &key)).await),
+            status(add_user(&call("accessKey=reader", &new_secret)).await),
+        ];
+        assert!(denied.iter().all(|code| *code == StatusCode::FORBIDDEN), "{denied:?}");
+        assert_eq!(iam.policy_for("staff"), None);
+        assert!(iam.groups_for("staff").is_empty());
+        assert!(iam.list_access_keys("staff").await.unwrap().is_empty());
+        // A user who holds nothing more is still the delegate's to manage.
+        let res = add_user(&call("accessKey=staff", &new_secret)).await;
+        assert_eq!(status(res), StatusCode::OK);
+    }
+
+    #[test]
+    fn policies_arrive_as_documents_or_strings() {
+        let doc = json!({
+            "Version": "2012-10-17",
+            "Statement": [{"Effect": "Allow", "Action": "s3:GetObject", "Resource": "arn:aws:s3:::b/*"}],
+        });
+        let from_doc = scope_of(Some(&doc)).unwrap().unwrap();
+        let from_text = scope_of(Some(&Value::String(doc.to_string()))).unwrap().unwrap();
+        assert_eq!(from_doc, from_text);
+        assert_eq!(scope_of(None).unwrap(), None);
+        assert_eq!(scope_of(Some(&json!({}))).unwrap(), None);
+        assert_eq!(scope_of(Some(&Value::String(String::new()))).unwrap(), None);
+        assert!(scope_of(Some(&json!({"Statement": 1}))).is_err());
+        assert!(check_no_expiry(Some("1970-01-01T00:00:00Z")).is_ok());
+        assert!(check_no_expiry(Some("2030-01-01T00:00:00Z")).is_err());
+        assert_eq!(split_names(" readonly, ,mine "), vec!["readonly", "mine"]);
+    }
+}
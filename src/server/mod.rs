@@ -3,22 +3,35 @@
 //! Entry point: [`serve`] starts the HTTP server.  [`router`] builds the
 //! Axum [`Router`] for use in integration tests.
 
+pub(crate) mod access_log;
+pub(crate) mod admission;
+pub mod audit_store;
 pub mod auth;
+pub mod backup;
 pub mod config;
 pub mod event_hub;
 pub(crate) mod handlers;
 pub mod iam;
 pub(crate) mod jobs;
 pub mod identity;
+pub mod ldap;
 pub mod logging;
+pub(crate) mod madmin;
+pub mod oidc;
 pub(crate) mod pipeline;
 pub mod policy;
 pub mod range;
+pub(crate) mod ratelimit;
 pub mod registry;
+pub mod reload;
+pub mod snapshot;
 pub mod scan_store;
 pub mod stats_store;
+pub(crate) mod sts;
 pub mod sysstat;
 pub mod template;
+pub mod tls;
+pub mod totp;
 pub mod ui;
 pub mod xml;
 
@@ -93,11 +106,19 @@ enum OperationDisposition {
     Partial,
 }
 
+/// The S3 error code an error response carries in its XML body, for log
+/// layers that report it without re-parsing the body.
+#[derive(Debug, Clone)]
+pub(crate) struct S3ErrorCode(pub String);
+
 #[derive(Debug, Clone)]
 pub struct S3HttpConfig {
     pub address: SocketAddr,
     pub root: String,
     pub app_config: Arc<AppConfig>,
+    /// The file `app_config` was read from, re-read on SIGHUP or the
+    /// console's reload action. `None` disables reloading.
+    pub config_path: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -222,14 +243,18 @@ impl TrafficMetrics {
 
 /// Builds the Axum router.  Exported so integration tests can call it directly.
 pub fn router(store: LocalObjectStore, app_config: Arc<AppConfig>) -> Router {
+    let config = reload::LiveConfig::new(app_config);
     router_with_metrics(
         store,
         AuthState {
-            config: app_config,
+            config: config.clone(),
             iam: None,
         },
         Arc::new(TrafficMetrics::default()),
         registry::TaskRegistry::new(),
+        access_log::AccessLogBuffer::new(),
+        ratelimit::RateLimiter::new(config, None),
+        None,
     )
 }
 
@@ -238,12 +263,21 @@ fn router_with_metrics(
     auth_state: AuthState,
     metrics: Arc<TrafficMetrics>,
     tasks: Arc<registry::TaskRegistry>,
+    access_log: Arc<access_log::AccessLogBuffer>,
+    limits: Arc<ratelimit::RateLimiter>,
+    admin: Option<madmin::AdminApi>,
 ) -> Router {
     let host_style_config = auth_state.config.clone();
     let cors_state = CorsMiddlewareState {
         config: auth_state.config.clone(),
         store: store.clone(),
     };
+    let access_log_state = access_log::AccessLogState {
+        store: store.clone(),
+        buffer: access_log,
+    };
+    let events = tasks.clone();
+    let admission = admission::Admission::new(auth_state.config.clone(), store.layout().clone());
     let inner = Router::new()
         .route("/minio/health/live", get(health_live))
         .route("/minio/health/ready", get(health_live))
@@ -252,7 +286,8 @@ fn router_with_metrics(
         .route("/minio/v2/metrics/bucket", get(metrics_endpoint))
         .route("/minio/v2/metrics/resource", get(metrics_endpoint))
         .route("/minio/prometheus/metrics", get(metrics_endpoint))
-        .route("/", get(list_buckets))
+        .route("/minio/admin/v3/*op", any(madmin::handle))
+        .route("/", get(list_buckets).post(sts::handle))
         .route("/:bucket", any(bucket_route))
         .route("/:bucket/", any(bucket_route))
         .route("/:bucket/*key", any(object_route))
@@ -262,18 +297,41 @@ fn router_with_metrics(
             tasks,
             task_registry_middleware,
         ))
+        // Upload slots and the low-disk guard; a write waiting for a slot is
+        // not yet a task.
+        .layer(middleware::from_fn_with_state(
+            admission,
+            admission::admission_middleware,
+        ))
+        // Rate limits charge the authenticated caller, so they sit inside
+        // auth; the task registry inside them counts paced bytes as they flow.
+        .layer(middleware::from_fn_with_state(
+            limits,
+            ratelimit::rate_limit_middleware,
+        ))
         .layer(middleware::from_fn_with_state(auth_state, auth_middleware))
         .layer(middleware::from_fn_with_state(
             metrics,
             traffic_metrics_middleware,
         ))
-        .layer(middleware::from_fn(log_middleware))
+        // Server access logging for buckets that enabled it; needs the request
+        // id from the layer outside it.
+        .layer(middleware::from_fn_with_state(
+            access_log_state,
+            access_log::access_log_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(events, log_middleware))
         // Presigned browser uploads originate on the separate management-UI
         // port. Handle their credential-free preflight and expose the S3 ETag
         // needed by multipart clients.
         .layer(middleware::from_fn_with_state(cors_state, s3_cors_middleware))
         .layer(DefaultBodyLimit::max(5 * 1024 * 1024 * 1024))
         .with_state(store);
+    // The MinIO admin API only exists where there is an IAM store to manage.
+    let inner = match admin {
+        Some(admin) => inner.layer(Extension(admin)),
+        None => inner,
+    };
     // `Router::layer` middleware runs only after a route has been matched, so
     // the host-style URI rewrite must wrap the finished router to influence
     // routing (and the auth layer's policy path) at all.
@@ -285,7 +343,7 @@ fn router_with_metrics(
 
 #[derive(Clone)]
 struct CorsMiddlewareState {
-    config: Arc<AppConfig>,
+    config: reload::LiveConfig,
     store: LocalObjectStore,
 }
 
@@ -350,7 +408,7 @@ fn cors_rule_decision(
 
 async fn s3_cors_middleware(
     State(state): State<CorsMiddlewareState>,
-    request: Request<Body>,
+    mut request: Request<Body>,
     next: Next,
 ) -> Response {
     let origin = request
@@ -365,15 +423,18 @@ async fn s3_cors_middleware(
         .split('/')
         .next()
         .filter(|bucket| !bucket.is_empty());
-    let rules = match bucket {
-        Some(bucket) => state
-            .store
-            .bucket_meta(bucket)
-            .await
-            .map(|meta| meta.cors)
-            .unwrap_or_default(),
-        None => Vec::new(),
+    let meta = match bucket {
+        Some(bucket) => state.store.bucket_meta(bucket).await.ok(),
+        None => None,
     };
+    // The access-log layer inside needs the same bucket config; pass it on
+    // rather than look it up again.
+    request
+        .extensions_mut()
+        .insert(access_log::BucketLoggingEnabled(
+            meta.as_ref().is_some_and(|meta| meta.logging.is_some()),
+        ));
+    let rules = meta.map(|meta| meta.cors).unwrap_or_default();
     let requested_method = if request.method() == axum::http::Method::OPTIONS {
         request
             .headers()
@@ -397,7 +458,7 @@ async fn s3_cors_middleware(
         .unwrap_or_default();
     let decision = origin.as_deref().and_then(|origin| {
         cors_rule_decision(
-            &state.config,
+            &state.config.current(),
             &rules,
             origin,
             requested_method,
@@ -447,11 +508,11 @@ async fn s3_cors_middleware(
 /// is the bare public hostname (or anything else — IPs, internal names) pass
 /// through unchanged as path-style.
 async fn host_style_middleware(
-    State(config): State<Arc<AppConfig>>,
+    State(config): State<reload::LiveConfig>,
     mut request: Request<Body>,
     next: Next,
 ) -> Response {
-    if let Some(bucket) = host_style_bucket(&config, &request) {
+    if let Some(bucket) = host_style_bucket(&config.current(), &request) {
         let original_path = request.uri().path().to_string();
         let query = request
             .uri()
@@ -487,6 +548,7 @@ fn host_style_bucket(config: &AppConfig, request: &Request<Body>) -> Option<Stri
     let path = request.uri().path();
     if matches!(path, "/minio/health/live" | "/minio/health/ready" | "/minio/prometheus/metrics")
         || path.starts_with("/minio/v2/metrics/")
+        || path.starts_with("/minio/admin/")
     {
         return None;
     }
@@ -636,7 +698,11 @@ fn new_request_id() -> String {
     bytes.iter().map(|b| format!("{b:02x}")).collect()
 }
 
-async fn log_middleware(mut request: Request<Body>, next: Next) -> Response {
+async fn log_middleware(
+    State(tasks): State<Arc<registry::TaskRegistry>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
     let method = request.method().clone();
     let uri = request.uri().clone();
     let is_copy = request.headers().contains_key("x-amz-copy-source");
@@ -721,6 +787,13 @@ async fn log_middleware(mut request: Request<Body>, next: Next) -> Response {
     } else {
         log::info!(target: logging::TARGET_AUDIT, "[{request_id}] {actor} {operation} {target} {result} {suffix}");
     }
+    tasks.publish(event_hub::Event::Audit {
+        actor,
+        action: operation.to_string(),
+        target,
+        allowed: result != "Denied",
+        request_id,
+    });
     response
 }
 
@@ -737,9 +810,15 @@ fn operation_and_target(
         .unwrap_or((trimmed, None));
     let query = parse_s3_query(uri.query().unwrap_or(""));
     if bucket.is_empty() {
+        if method == Method::POST {
+            return ("ASSUME_ROLE", "/".to_string());
+        }
         return ("LIST_BUCKETS", "/".to_string());
     }
     if key.is_none() || key == Some("") {
+        if *method == Method::PUT && query.contains_key("logging") {
+            return ("PUT_BUCKET_LOGGING", format!("/{bucket}"));
+        }
         if *method == Method::PUT {
             return ("CREATE_BUCKET", format!("/{bucket}"));
         }
@@ -768,6 +847,9 @@ fn operation_and_target(
             if query.contains_key("location") {
                 return ("GET_BUCKET_LOCATION", format!("/{bucket}"));
             }
+            if query.contains_key("logging") {
+                return ("GET_BUCKET_LOGGING", format!("/{bucket}"));
+            }
             return ("LIST", target);
         }
         if *method == Method::HEAD {
@@ -937,8 +1019,17 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
     // Exactly one process may own a data directory: the per-key in-memory
     // locks are only meaningful under that assumption.
     let _process_lock = acquire_process_lock(FsPath::new(&config.root))?;
+    // Everything that may change on reload reads the config through this
+    // handle; what only applies at startup keeps reading `config.app_config`.
+    let live_config = match &config.config_path {
+        Some(path) => reload::LiveConfig::from_source(config.app_config.clone(), path.clone()),
+        None => reload::LiveConfig::new(config.app_config.clone()),
+    };
 
     let store = LocalObjectStore::from_storage_config(&config.root, &config.app_config.storage);
+    // A move between data roots cut short by a crash is finished or undone
+    // before anything looks for the bucket.
+    store.recover_bucket_moves().await?;
     let shutdown = store.shutdown_token();
     let metrics = Arc::new(TrafficMetrics::default());
     // One registry of in-flight work, shared by the HTTP handlers and the
@@ -994,6 +1085,7 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
         shutdown_sig.cancel();
         log::info!("shutdown signal watcher task completed");
     });
+    reload::spawn_sighup_listener(live_config.clone(), shutdown.clone());
 
     if config.app_config.logging.enable_bandwidth_report {
         let metrics_shutdown = shutdown.clone();
@@ -1047,11 +1139,35 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
     // the task registry while it runs.
     spawn_maintenance_jobs(
         store.clone(),
-        config.app_config.sweeper.clone(),
+        live_config.clone(),
+        shutdown.clone(),
+        tasks.clone(),
+    );
+    let access_log = access_log::AccessLogBuffer::new();
+    spawn_access_log_job(
+        store.clone(),
+        access_log.clone(),
+        live_config.clone(),
         shutdown.clone(),
         tasks.clone(),
     );
 
+    // Audit trail: recorded whether or not the console runs, since it is the
+    // S3 traffic that incident response most often needs to reconstruct.
+    let audit = if config.app_config.audit.enabled {
+        let audit_db = audit_store::AuditStore::open(FsPath::new(&config.root)).await?;
+        audit_store::spawn_recorder(
+            audit_db.clone(),
+            tasks.clone(),
+            live_config.clone(),
+            shutdown.clone(),
+        );
+        Some(audit_db)
+    } else {
+        log::info!("audit trail disabled by config");
+        None
+    };
+
     // IAM store (admin.sqlite at the data root, outside any bucket) backs
     // runtime-managed users/keys/policies; built-in admin users come from
     // the config and are immutable at runtime.
@@ -1069,19 +1185,28 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
         }
     }
     let auth_state = AuthState {
-        config: config.app_config.clone(),
+        config: live_config.clone(),
         iam: Some(iam.clone()),
     };
+    let limits = ratelimit::RateLimiter::new(live_config.clone(), Some(iam.clone()));
+    // Storage-scan history lives in its own database, deliberately not a
+    // family of admin.rocksdb: IAM is exported/imported as one unit and scan
+    // reports have no business travelling with it. Shared by the console and
+    // the MinIO admin API's heal calls.
+    let scans = jobs::perf_scan::ScanService::new(
+        scan_store::ScanStore::open(FsPath::new(&config.root)).await?,
+    );
+    let admin_api = madmin::AdminApi {
+        store: store.clone(),
+        iam: iam.clone(),
+        config: live_config.clone(),
+        scans: scans.clone(),
+        tasks: tasks.clone(),
+    };
 
     // Management UI on its own port: web logins (user/password) only —
     // completely separate from the access-key-authenticated S3 API.
     if config.app_config.ui.enabled {
-        // Storage-scan history lives in its own database, deliberately not a
-        // family of admin.rocksdb: IAM is exported/imported as one unit and
-        // scan reports have no business travelling with it.
-        let scans = jobs::perf_scan::ScanService::new(
-            scan_store::ScanStore::open(FsPath::new(&config.root)).await?,
-        );
         // Runtime stats: its own time-series database and a 5s sampler feeding
         // it. Disabled by config means no store is opened and no sampler runs;
         // the UI endpoint then reports the feature as off.
@@ -1090,7 +1215,8 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
             sysstat::spawn_sampler(
                 stats_db.clone(),
                 metrics.clone(),
-                config.app_config.stats.clone(),
+                live_config.clone(),
+                store.layout().roots().to_vec(),
                 shutdown.clone(),
             );
             Some(stats_db)
@@ -1101,11 +1227,15 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
         let ui_state = ui::UiState {
             store: store.clone(),
             iam,
-            config: config.app_config.clone(),
+            config: live_config.clone(),
             metrics: metrics.clone(),
             tasks: tasks.clone(),
             scans,
             stats,
+            audit,
+            limits: limits.clone(),
+            logins: Arc::default(),
+            oidc: Arc::default(),
         };
         let ui_bind = format!(
             "{}:{}",
@@ -1117,23 +1247,63 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
                 .unwrap_or_else(|| config.address.ip().to_string()),
             config.app_config.ui.bind_port
         );
+        let ui_tls = tls::prepare("management UI", &config.app_config.ui.tls, &shutdown)?;
         let ui_listener = tokio::net::TcpListener::bind(&ui_bind).await?;
         let ui_shutdown = shutdown.clone();
-        log::info!("management UI listening on {ui_bind}");
+        log::info!(
+            "management UI listening on {}://{ui_bind}",
+            tls::scheme(ui_tls.as_ref())
+        );
         tokio::spawn(async move {
-            if let Err(err) = axum::serve(ui_listener, ui::router(ui_state))
-                .with_graceful_shutdown(ui_shutdown.cancelled_owned())
-                .await
-            {
+            let result = match ui_tls {
+                Some(tls) => tls::serve(ui_listener, ui::router(ui_state), tls, ui_shutdown).await,
+                None => {
+                    axum::serve(
+                        ui_listener,
+                        ui::router(ui_state).into_make_service_with_connect_info::<SocketAddr>(),
+                    )
+                    .with_graceful_shutdown(ui_shutdown.cancelled_owned())
+                    .await
+                }
+            };
+            if let Err(err) = result {
                 log::error!("management UI server error: {err}");
             }
         });
     }
 
-    let app = router_with_metrics(store, auth_state, metrics, tasks);
+    let app = router_with_metrics(
+        store.clone(),
+        auth_state,
+        metrics,
+        tasks.clone(),
+        access_log.clone(),
+        limits,
+        Some(admin_api),
+    );
+    let s3_tls = tls::prepare("S3 API", &config.app_config.server.tls, &shutdown)?;
     let listener = tokio::net::TcpListener::bind(config.address).await?;
+    log::info!(
+        "S3 API listening on {}://{}",
+        tls::scheme(s3_tls.as_ref()),
+        config.address
+    );
     let drain = shutdown.clone();
-    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.cancelled_owned());
+    let server_shutdown = shutdown.clone();
+    // Connect info gives the access log the client address.
+    let server = async move {
+        match s3_tls {
+            Some(tls) => tls::serve(listener, app, tls, server_shutdown).await,
+            None => {
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(server_shutdown.cancelled_owned())
+                .await
+            }
+        }
+    };
 
     // Graceful shutdown waits for in-flight connections, and an idle keep-alive
     // connection is indistinguishable from a busy one — so a single client
@@ -1150,6 +1320,17 @@ pub async fn serve(config: S3HttpConfig) -> Result<(), Box<dyn std::error::Error
             );
         }
     }
+    // Deliver access log records for the requests served since the last
+    // scheduled flush, including those that drained during shutdown.
+    let run_id = new_request_id();
+    jobs::access_log::run_once(
+        &store,
+        &access_log,
+        &tokio_util::sync::CancellationToken::new(),
+        &tasks,
+        &run_id,
+    )
+    .await;
     log::info!("rusts3-v2 shutdown complete");
     Ok(())
 }
@@ -1194,36 +1375,45 @@ async fn wait_for_shutdown_signal() -> &'static str {
 /// interval, never overlapping (it awaits each run before the next tick), under
 /// a cancellation token that is a child of the process shutdown token — so the
 /// whole set stops at shutdown and each job can later be cancelled on its own.
+///
+/// Every run takes the sweeper settings current when it starts and schedules
+/// the next run from them, so a config reload retunes the jobs in place.
 fn spawn_maintenance_jobs(
     store: LocalObjectStore,
-    cfg: self::config::SweeperConfig,
+    config: reload::LiveConfig,
     shutdown: tokio_util::sync::CancellationToken,
     tasks: Arc<registry::TaskRegistry>,
 ) {
     macro_rules! spawn_job {
         ($name:expr, $run:path) => {{
             let store = store.clone();
-            let cfg = cfg.clone();
+            let config = config.clone();
             let cancel = shutdown.child_token();
             let tasks = tasks.clone();
             tokio::spawn(async move {
-                log::info!("job scheduler started job={} interval_secs={}", $name, cfg.interval_secs);
-                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
-                    cfg.interval_secs.max(1),
-                ));
-                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                log::info!(
+                    "job scheduler started job={} interval_secs={}",
+                    $name,
+                    config.current().sweeper.interval_secs
+                );
+                let mut next = tokio::time::Instant::now();
                 loop {
                     tokio::select! {
                         _ = cancel.cancelled() => {
                             log::info!("job scheduler stopping job={}", $name);
                             break;
                         }
-                        _ = interval.tick() => {
+                        _ = tokio::time::sleep_until(next) => {
+                            let cfg = config.current().sweeper.clone();
+                            next += tokio::time::Duration::from_secs(cfg.interval_secs.max(1));
                             let run_id = new_request_id();
                             let fut = $run(&store, &cfg, &cancel, &tasks, &run_id);
                             if futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(fut)).await.is_err() {
                                 log::error!("job {} panicked run_id={run_id}; scheduler continues", $name);
                             }
+                            // A run that overran its interval is followed by the
+                            // next one straight away, not by a burst of catch-ups.
+                            next = next.max(tokio::time::Instant::now());
                         }
                     }
                 }
@@ -1233,6 +1423,7 @@ fn spawn_maintenance_jobs(
     spawn_job!("resolve_intents", jobs::resolve_intents::run_once);
     spawn_job!("delete_staging", jobs::delete_staging::run_once);
     spawn_job!("delete_trash", jobs::delete_trash::run_once);
+    spawn_job!("prune_changes", jobs::prune_changes::run_once);
 
     // Layout migration: relocate legacy 4-level objects to the single-level
     // layout. Its own loop (starts immediately); each run drains all remaining
@@ -1241,10 +1432,11 @@ fn spawn_maintenance_jobs(
         let store = store.clone();
         let cancel = shutdown.child_token();
         let tasks = tasks.clone();
-        let idle_secs = cfg.interval_secs.max(1);
+        let config = config.clone();
         tokio::spawn(async move {
             log::info!(
-                "job scheduler started job=migrate_layout interval_secs={idle_secs} (starts immediately)"
+                "job scheduler started job=migrate_layout interval_secs={} (starts immediately)",
+                config.current().sweeper.interval_secs.max(1)
             );
             loop {
                 if cancel.is_cancelled() {
@@ -1261,7 +1453,9 @@ fn spawn_maintenance_jobs(
                         log::info!("job scheduler stopping job=migrate_layout");
                         break;
                     }
-                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(idle_secs)) => {}
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(
+                        config.current().sweeper.interval_secs.max(1),
+                    )) => {}
                 }
             }
         });
@@ -1274,10 +1468,10 @@ fn spawn_maintenance_jobs(
         let store = store.clone();
         let cancel = shutdown.child_token();
         let tasks = tasks.clone();
-        let idle_secs = cfg.reclaim_interval_secs.max(1);
         tokio::spawn(async move {
             log::info!(
-                "job scheduler started job=reclaim_dirs interval_secs={idle_secs} (starts immediately)"
+                "job scheduler started job=reclaim_dirs interval_secs={} (starts immediately)",
+                config.current().sweeper.reclaim_interval_secs.max(1)
             );
             loop {
                 if cancel.is_cancelled() {
@@ -1294,13 +1488,57 @@ fn spawn_maintenance_jobs(
                         log::info!("job scheduler stopping job=reclaim_dirs");
                         break;
                     }
-                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(idle_secs)) => {}
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(
+                        config.current().sweeper.reclaim_interval_secs.max(1),
+                    )) => {}
                 }
             }
         });
     }
 }
 
+/// Spawns the server access log delivery loop. Like the maintenance jobs it
+/// awaits each run before the next tick; the final flush at shutdown is done
+/// by [`serve`] once the listener has drained.
+fn spawn_access_log_job(
+    store: LocalObjectStore,
+    buffer: Arc<access_log::AccessLogBuffer>,
+    config: reload::LiveConfig,
+    shutdown: tokio_util::sync::CancellationToken,
+    tasks: Arc<registry::TaskRegistry>,
+) {
+    let cancel = shutdown.child_token();
+    tokio::spawn(async move {
+        log::info!(
+            "job scheduler started job={} interval_secs={}",
+            jobs::access_log::JOB,
+            config.current().logging.access_log_flush_secs
+        );
+        let mut next = tokio::time::Instant::now();
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    log::info!("job scheduler stopping job={}", jobs::access_log::JOB);
+                    break;
+                }
+                _ = tokio::time::sleep_until(next) => {
+                    let flush_secs = config.current().logging.access_log_flush_secs;
+                    next += tokio::time::Duration::from_secs(flush_secs.max(1));
+                    let run_id = new_request_id();
+                    let fut = jobs::access_log::run_once(&store, &buffer, &cancel, &tasks, &run_id);
+                    if futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(fut)).await.is_err() {
+                        log::error!(
+                            "job {} panicked run_id={run_id}; scheduler continues",
+                            jobs::access_log::JOB
+                        );
+                    }
+                    next = next.max(tokio::time::Instant::now());
+                }
+            }
+        }
+    });
+}
+
 fn human_bytes(bytes: u64) -> String {
     const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
     let mut value = bytes as f64;
@@ -1355,6 +1593,7 @@ async fn bucket_route(
     identity: Option<Extension<Identity>>,
     auth_state: Option<Extension<AuthState>>,
     tasks: Option<Extension<Arc<registry::TaskRegistry>>>,
+    tls: Option<Extension<tls::TlsConnection>>,
     body: Body,
 ) -> Response {
     let query = parse_s3_query(raw_query.as_deref().unwrap_or(""));
@@ -1363,6 +1602,7 @@ async fn bucket_route(
         identity: identity.map(|Extension(id)| id),
         auth_state: auth_state.map(|Extension(state)| state),
         tasks: tasks.map(|Extension(tasks)| tasks),
+        secure_transport: tls.is_some(),
         bucket,
         query,
         headers,
@@ -1379,6 +1619,12 @@ async fn bucket_route(
         Method::GET if ctx.query.contains_key("cors") => get_bucket_cors_s3(store, ctx).await,
         Method::PUT if ctx.query.contains_key("cors") => put_bucket_cors_s3(store, ctx, body).await,
         Method::DELETE if ctx.query.contains_key("cors") => delete_bucket_cors_s3(store, ctx).await,
+        Method::GET if ctx.query.contains_key("logging") => {
+            handlers::get_bucket_logging::handle(store, ctx, body).await
+        }
+        Method::PUT if ctx.query.contains_key("logging") => {
+            handlers::put_bucket_logging::handle(store, ctx, body).await
+        }
         // A sub-resource we do not implement must never fall through to
         // CreateBucket/DeleteBucket: `PUT /{bucket}?versioning` would be
         // answered 200 by the idempotent create (telling the client versioning
@@ -1928,7 +2174,8 @@ fn known_unimplemented_bucket_query(query: &HashMap<String, String>) -> bool {
 /// broader than [`known_unimplemented_bucket_query`], which only guards GET —
 /// answering a write with "200, applied" or with a bucket deletion is far worse
 /// than a read returning an empty listing. `cors`, `versions`, `uploads`,
-/// `location`, `delete` and `rebuildIndex` are implemented and absent here.
+/// `location`, `delete` and `rebuildIndex` are implemented and absent here;
+/// `logging` stays listed because only its GET and PUT forms exist.
 fn unimplemented_bucket_subresource(query: &HashMap<String, String>) -> bool {
     [
         "accelerate",
@@ -2014,12 +2261,30 @@ fn storage_error_response(err: StorageError, resource: &str) -> Response {
                 .insert(header::RETRY_AFTER, HeaderValue::from_static("5"));
             response
         }
+        StorageError::BucketMoving(_) => {
+            let mut response = s3_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "SlowDown",
+                "Bucket is moving to another disk; retry shortly",
+                resource,
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+            response
+        }
         StorageError::PreconditionFailed { .. } => s3_error(
             StatusCode::PRECONDITION_FAILED,
             "PreconditionFailed",
             "At least one of the preconditions you specified did not hold",
             resource,
         ),
+        StorageError::QuotaExceeded(_) => s3_error(
+            StatusCode::BAD_REQUEST,
+            "QuotaExceeded",
+            err.to_string(),
+            resource,
+        ),
         _ => s3_error(
             StatusCode::INTERNAL_SERVER_ERROR,
             "InternalError",
@@ -2041,7 +2306,9 @@ fn s3_error(
         resource: resource.to_string(),
         request_id: "rust-s3-server".to_string(),
     });
-    xml_response(status, body)
+    let mut response = xml_response(status, body);
+    response.extensions_mut().insert(S3ErrorCode(code.to_string()));
+    response
 }
 
 fn xml_response(status: StatusCode, body: String) -> Response {
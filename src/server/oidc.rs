@@ -0,0 +1,759 @@
+//! OpenID Connect sign-in for the console: the authorization-code flow with
+//! PKCE (RFC 7636, `S256`).
+//!
+//! [`OidcClient::authorize`] builds the provider URL the browser is sent to
+//! and remembers the flow's verifier and nonce under its `state`;
+//! [`OidcClient::exchange`] redeems the code the provider sends back, checks
+//! the ID token, and returns its claims. [`map_claims`] turns those into an
+//! IAM user name and groups. Only the ID token is used; the access token is
+//! thrown away, since nothing here calls the provider on the user's behalf.
+//!
+//! Discovery metadata and signing keys are fetched on first use and cached
+//! for an hour. A token signed with a key id not in the cache refetches the
+//! keys, at most once a minute, so a provider's key rotation is picked up
+//! without a restart.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::RngCore;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::config::OidcConfig;
+use crate::storage::time::now_ms;
+
+/// Path of the console's callback, which the default redirect URL points at.
+pub const CALLBACK_PATH: &str = "/api/oidc/callback";
+
+/// How long a sign-in may spend at the provider.
+const FLOW_TTL: Duration = Duration::from_secs(10 * 60);
+/// Cap on unfinished sign-ins, so abandoned ones cannot grow without bound.
+const MAX_FLOWS: usize = 10_000;
+const METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+const KEY_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+/// Clock difference tolerated between us and the provider.
+const CLOCK_SKEW_SECS: i64 = 120;
+
+/// An issuer (or endpoint) URL the client will talk to: HTTPS, or plain HTTP
+/// to a loopback address for a provider on the same machine.
+pub fn is_acceptable_issuer(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match (url.scheme(), url.host_str()) {
+        ("https", Some(_)) => true,
+        ("http", Some(host)) => matches!(host, "localhost" | "127.0.0.1" | "[::1]"),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Metadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// One key of the provider's JWKS. Only what signature checks need.
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    alg: Option<String>,
+    #[serde(default, rename = "use")]
+    usage: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+struct Provider {
+    /// The configured issuer this was discovered from; a reload that changes
+    /// it discards the cache.
+    issuer: String,
+    metadata: Metadata,
+    keys: Vec<Jwk>,
+    fetched: Instant,
+    keys_fetched: Instant,
+}
+
+/// A sign-in between [`OidcClient::authorize`] and [`OidcClient::exchange`].
+struct Flow {
+    verifier: String,
+    nonce: String,
+    redirect_uri: String,
+    started: Instant,
+}
+
+/// Where to send the browser, and the `state` naming the flow, which the
+/// console also pins to the browser in a cookie.
+#[derive(Debug, Clone)]
+pub struct Authorization {
+    pub url: String,
+    pub state: String,
+}
+
+/// The IAM identity an ID token maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsoUser {
+    pub username: String,
+    /// The user's IAM groups, or `None` when `group_map` is empty and group
+    /// membership is managed in the console instead.
+    pub groups: Option<Vec<String>>,
+}
+
+pub struct OidcClient {
+    http: reqwest::Client,
+    provider: tokio::sync::Mutex<Option<Provider>>,
+    flows: Mutex<HashMap<String, Flow>>,
+}
+
+impl Default for OidcClient {
+    fn default() -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("HTTP client with the default TLS backend");
+        Self {
+            http,
+            provider: tokio::sync::Mutex::new(None),
+            flows: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl OidcClient {
+    /// Starts a sign-in that the provider will return to `redirect_uri`.
+    pub async fn authorize(
+        &self,
+        config: &OidcConfig,
+        redirect_uri: &str,
+    ) -> Result<Authorization, String> {
+        let (metadata, _) = self.provider(config, None).await?;
+        let (state, nonce, verifier) = (random_token(), random_token(), random_token());
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        let mut url = reqwest::Url::parse(&metadata.authorization_endpoint)
+            .map_err(|err| format!("authorization endpoint: {err}"))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", &config.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        let mut flows = self.flows.lock().unwrap();
+        flows.retain(|_, flow| flow.started.elapsed() < FLOW_TTL);
+        if flows.len() >= MAX_FLOWS {
+            return Err("too many sign-ins in progress; try again shortly".to_string());
+        }
+        flows.insert(
+            state.clone(),
+            Flow {
+                verifier,
+                nonce,
+                redirect_uri: redirect_uri.to_string(),
+                started: Instant::now(),
+            },
+        );
+        Ok(Authorization {
+            url: url.into(),
+            state,
+        })
+    }
+
+    /// Redeems `code` for the flow named `state` and returns the verified
+    /// claims of its ID token. A flow can be redeemed once.
+    pub async fn exchange(
+        &self,
+        config: &OidcConfig,
+        state: &str,
+        code: &str,
+    ) -> Result<Map<String, Value>, String> {
+        let flow = self
+            .flows
+            .lock()
+            .unwrap()
+            .remove(state)
+            .filter(|flow| flow.started.elapsed() < FLOW_TTL)
+            .ok_or("this sign-in has expired or was already used; start again")?;
+        let (metadata, _) = self.provider(config, None).await?;
+        let mut request = self.http.post(&metadata.token_endpoint);
+        if let Some(secret) = &config.client_secret {
+            request = request.basic_auth(&config.client_id, Some(secret));
+        }
+        let response = request
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &flow.redirect_uri),
+                ("client_id", &config.client_id),
+                ("code_verifier", &flow.verifier),
+            ])
+            .send()
+            .await
+            .map_err(|err| format!("token request failed: {err}"))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|err| format!("token response: {err}"))?;
+        if !status.is_success() {
+            let reason = body["error_description"]
+                .as_str()
+                .or(body["error"].as_str())
+                .unwrap_or("no reason given");
+            return Err(format!(
+                "the provider refused the code ({status}): {reason}"
+            ));
+        }
+        let token = body["id_token"]
+            .as_str()
+            .ok_or("the token response has no id_token")?;
+
+        let kid = token_kid(token);
+        let (metadata, keys) = self.provider(config, kid.as_deref()).await?;
+        verify_id_token(
+            token,
+            &keys,
+            &Expected {
+                issuer: &metadata.issuer,
+                client_id: &config.client_id,
+                nonce: &flow.nonce,
+                now_secs: now_ms() / 1000,
+            },
+        )
+    }
+
+    /// The provider's metadata and keys, fetching whatever is missing, stale,
+    /// or lacks the key `kid`.
+    async fn provider(
+        &self,
+        config: &OidcConfig,
+        kid: Option<&str>,
+    ) -> Result<(Metadata, Vec<Jwk>), String> {
+        let mut cache = self.provider.lock().await;
+        let stale = cache
+            .as_ref()
+            .is_none_or(|p| p.issuer != config.issuer || p.fetched.elapsed() > METADATA_TTL);
+        if stale {
+            let metadata = self.discover(&config.issuer).await?;
+            let keys = self.get_json::<JwkSet>(&metadata.jwks_uri).await?.keys;
+            let now = Instant::now();
+            *cache = Some(Provider {
+                issuer: config.issuer.clone(),
+                metadata,
+                keys,
+                fetched: now,
+                keys_fetched: now,
+            });
+        }
+        let provider = cache.as_mut().expect("filled above");
+        if let Some(kid) = kid {
+            let known = provider
+                .keys
+                .iter()
+                .any(|key| key.kid.as_deref() == Some(kid));
+            if !known && provider.keys_fetched.elapsed() > KEY_REFETCH_INTERVAL {
+                provider.keys = self
+                    .get_json::<JwkSet>(&provider.metadata.jwks_uri)
+                    .await?
+                    .keys;
+                provider.keys_fetched = Instant::now();
+            }
+        }
+        Ok((provider.metadata.clone(), provider.keys.clone()))
+    }
+
+    async fn discover(&self, issuer: &str) -> Result<Metadata, String> {
+        let issuer = issuer.trim_end_matches('/');
+        let url = format!("{issuer}/.well-known/openid-configuration");
+        let metadata: Metadata = self.get_json(&url).await?;
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(format!(
+                "{url} describes issuer {}, not {issuer}",
+                metadata.issuer
+            ));
+        }
+        for endpoint in [&metadata.token_endpoint, &metadata.jwks_uri] {
+            if !is_acceptable_issuer(endpoint) {
+                return Err(format!("provider endpoint {endpoint} is not HTTPS"));
+            }
+        }
+        Ok(metadata)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| format!("{url}: {err}"))?;
+        if !response.status().is_success() {
+            return Err(format!("{url}: HTTP {}", response.status()));
+        }
+        response.json().await.map_err(|err| format!("{url}: {err}"))
+    }
+}
+
+/// What an ID token must say to be accepted for a flow.
+struct Expected<'a> {
+    issuer: &'a str,
+    client_id: &'a str,
+    nonce: &'a str,
+    now_secs: i64,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn decode_json<T: DeserializeOwned>(part: &str) -> Option<T> {
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(part).ok()?).ok()
+}
+
+fn token_kid(token: &str) -> Option<String> {
+    let header: Value = decode_json(token.split('.').next()?)?;
+    header["kid"].as_str().map(str::to_string)
+}
+
+/// Checks the ID token's signature against `keys` and its claims against
+/// `expected`, and returns the claims.
+fn verify_id_token(
+    token: &str,
+    keys: &[Jwk],
+    expected: &Expected,
+) -> Result<Map<String, Value>, String> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let &[header, payload, signature] = parts.as_slice() else {
+        return Err("the ID token is not a signed JWT".to_string());
+    };
+    let header: Value = decode_json(header).ok_or("the ID token header is malformed")?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| "the ID token signature is malformed")?;
+    let alg = header["alg"].as_str().unwrap_or_default();
+    let kid = header["kid"].as_str();
+    let signed = &token[..token.len() - parts[2].len() - 1];
+    let verified = keys
+        .iter()
+        .filter(|key| kid.is_none_or(|kid| key.kid.as_deref() == Some(kid)))
+        .any(|key| key.verifies(alg, signed.as_bytes(), &signature));
+    if !verified {
+        return Err(format!(
+            "the ID token signature does not verify (alg {alg}, key {})",
+            kid.unwrap_or("unnamed")
+        ));
+    }
+
+    let claims: Map<String, Value> =
+        decode_json(payload).ok_or("the ID token payload is malformed")?;
+    if claims.get("iss").and_then(Value::as_str) != Some(expected.issuer) {
+        return Err("the ID token is from another issuer".to_string());
+    }
+    let audience: Vec<&str> = match claims.get("aud") {
+        Some(Value::String(aud)) => vec![aud.as_str()],
+        Some(Value::Array(auds)) => auds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let azp = claims.get("azp").and_then(Value::as_str);
+    if !audience.contains(&expected.client_id) || azp.is_some_and(|azp| azp != expected.client_id) {
+        return Err("the ID token is for another client".to_string());
+    }
+    let exp = claims
+        .get("exp")
+        .and_then(Value::as_i64)
+        .ok_or("the ID token has no exp")?;
+    if exp + CLOCK_SKEW_SECS < expected.now_secs {
+        return Err("the ID token has expired; check the server clock".to_string());
+    }
+    if let Some(nbf) = claims.get("nbf").and_then(Value::as_i64) {
+        if nbf - CLOCK_SKEW_SECS > expected.now_secs {
+            return Err("the ID token is not valid yet; check the server clock".to_string());
+        }
+    }
+    if claims.get("nonce").and_then(Value::as_str) != Some(expected.nonce) {
+        return Err("the ID token was not issued for this sign-in".to_string());
+    }
+    Ok(claims)
+}
+
+impl Jwk {
+    /// Whether this key, used with `alg`, signed `message`. RSA and ECDSA
+    /// keys only: `none` and the HMAC algorithms are never accepted.
+    fn verifies(&self, alg: &str, message: &[u8], sig: &[u8]) -> bool {
+        if self.alg.as_deref().is_some_and(|own| own != alg)
+            || self.usage.as_deref().is_some_and(|usage| usage != "sig")
+        {
+            return false;
+        }
+        let part = |value: &Option<String>| {
+            value
+                .as_deref()
+                .and_then(|value| URL_SAFE_NO_PAD.decode(value).ok())
+        };
+        match (self.kty.as_str(), alg) {
+            ("RSA", "RS256" | "RS384" | "RS512") => {
+                let params = match alg {
+                    "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                    "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                    _ => &signature::RSA_PKCS1_2048_8192_SHA512,
+                };
+                let (Some(n), Some(e)) = (part(&self.n), part(&self.e)) else {
+                    return false;
+                };
+                RsaPublicKeyComponents { n: &n, e: &e }
+                    .verify(params, message, sig)
+                    .is_ok()
+            }
+            ("EC", "ES256" | "ES384") => {
+                let (params, curve) = match alg {
+                    "ES256" => (&signature::ECDSA_P256_SHA256_FIXED, "P-256"),
+                    _ => (&signature::ECDSA_P384_SHA384_FIXED, "P-384"),
+                };
+                let (Some(x), Some(y)) = (part(&self.x), part(&self.y)) else {
+                    return false;
+                };
+                if self.crv.as_deref() != Some(curve) {
+                    return false;
+                }
+                let point = [&[0x04][..], &x, &y].concat();
+                UnparsedPublicKey::new(params, point)
+                    .verify(message, sig)
+                    .is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The IAM user and groups for an ID token's claims, or why the holder may
+/// not sign in.
+pub fn map_claims(config: &OidcConfig, claims: &Map<String, Value>) -> Result<SsoUser, String> {
+    let username = claims
+        .get(&config.username_claim)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| format!("the ID token has no {} claim", config.username_claim))?;
+    let provider_groups: Vec<&str> = match claims.get(&config.groups_claim) {
+        Some(Value::Array(groups)) => groups.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(group)) => vec![group.as_str()],
+        _ => Vec::new(),
+    };
+    if !config.allowed_groups.is_empty()
+        && !provider_groups
+            .iter()
+            .any(|group| config.allowed_groups.iter().any(|allowed| allowed == group))
+    {
+        return Err(format!(
+            "{username} is not in a group allowed to use the console"
+        ));
+    }
+    let groups = (!config.group_map.is_empty()).then(|| {
+        let mut groups: Vec<String> = provider_groups
+            .iter()
+            .filter_map(|group| config.group_map.get(*group).cloned())
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    });
+    Ok(SsoUser {
+        username: username.to_string(),
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::extract::{Form, State};
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde_json::json;
+
+    use super::*;
+
+    /// An ES256 key generated for the test, standing in for the provider's.
+    struct TestKey {
+        pair: EcdsaKeyPair,
+        rng: SystemRandom,
+    }
+
+    impl TestKey {
+        fn generate() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self { pair, rng }
+        }
+
+        fn jwk(&self) -> Value {
+            let point = self.pair.public_key().as_ref();
+            json!({
+                "kty": "EC", "crv": "P-256", "kid": "k1", "alg": "ES256", "use": "sig",
+                "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+            })
+        }
+
+        fn sign(&self, header: Value, claims: &Value) -> String {
+            let signed = format!(
+                "{}.{}",
+                URL_SAFE_NO_PAD.encode(header.to_string()),
+                URL_SAFE_NO_PAD.encode(claims.to_string())
+            );
+            let sig = self.pair.sign(&self.rng, signed.as_bytes()).unwrap();
+            format!("{signed}.{}", URL_SAFE_NO_PAD.encode(sig.as_ref()))
+        }
+
+        fn id_token(&self, claims: &Value) -> String {
+            self.sign(json!({"alg": "ES256", "kid": "k1", "typ": "JWT"}), claims)
+        }
+    }
+
+    /// A provider on a loopback port: discovery, keys, and a token endpoint
+    /// that checks the PKCE verifier against the challenge it was given.
+    struct MockIssuer {
+        issuer: String,
+        key: TestKey,
+        /// Code → (nonce, code challenge) of the sign-in it was issued to.
+        codes: Mutex<HashMap<String, (String, String)>>,
+    }
+
+    impl MockIssuer {
+        async fn start() -> Arc<Self> {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let mock = Arc::new(Self {
+                issuer: format!("http://{}", listener.local_addr().unwrap()),
+                key: TestKey::generate(),
+                codes: Mutex::new(HashMap::new()),
+            });
+            let app = Router::new()
+                .route("/.well-known/openid-configuration", get(Self::discovery))
+                .route("/jwks", get(Self::jwks))
+                .route("/token", post(Self::token))
+                .with_state(mock.clone());
+            tokio::spawn(async move { axum::serve(listener, app).await });
+            mock
+        }
+
+        fn config(&self) -> OidcConfig {
+            OidcConfig {
+                enabled: true,
+                issuer: self.issuer.clone(),
+                client_id: "rusts3".to_string(),
+                client_secret: Some("s3cret".to_string()),
+                ..OidcConfig::default()
+            }
+        }
+
+        /// What the provider does when the user approves: a code bound to
+        /// the sign-in's nonce and challenge.
+        fn approve(&self, code: &str, authorize_url: &str) {
+            let url = reqwest::Url::parse(authorize_url).unwrap();
+            let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            assert_eq!(query["code_challenge_method"], "S256");
+            self.codes.lock().unwrap().insert(
+                code.to_string(),
+                (query["nonce"].clone(), query["code_challenge"].clone()),
+            );
+        }
+
+        async fn discovery(State(mock): State<Arc<Self>>) -> Json<Value> {
+            Json(json!({
+                "issuer": mock.issuer,
+                "authorization_endpoint": format!("{}/authorize", mock.issuer),
+                "token_endpoint": format!("{}/token", mock.issuer),
+                "jwks_uri": format!("{}/jwks", mock.issuer),
+            }))
+        }
+
+        async fn jwks(State(mock): State<Arc<Self>>) -> Json<Value> {
+            Json(json!({ "keys": [mock.key.jwk()] }))
+        }
+
+        async fn token(
+            State(mock): State<Arc<Self>>,
+            Form(form): Form<HashMap<String, String>>,
+        ) -> Response {
+            let refuse = |why: &str| {
+                let body = json!({"error": "invalid_grant", "error_description": why});
+                (StatusCode::BAD_REQUEST, Json(body)).into_response()
+            };
+            let Some((nonce, challenge)) = mock.codes.lock().unwrap().remove(&form["code"]) else {
+                return refuse("unknown code");
+            };
+            let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+            if URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) != challenge {
+                return refuse("PKCE verification failed");
+            }
+            let now = now_ms() / 1000;
+            let claims = json!({
+                "iss": mock.issuer, "aud": form["client_id"], "sub": "u-1",
+                "iat": now, "exp": now + 300, "nonce": nonce,
+                "preferred_username": "alice", "groups": ["staff", "storage-admins"],
+            });
+            Json(json!({"token_type": "Bearer", "id_token": mock.key.id_token(&claims)}))
+                .into_response()
+        }
+    }
+
+    #[tokio::test]
+    async fn code_flow_against_a_mock_issuer() {
+        let mock = MockIssuer::start().await;
+        let config = mock.config();
+        let client = OidcClient::default();
+        let callback = "http://console.test/api/oidc/callback";
+
+        let auth = client.authorize(&config, callback).await.unwrap();
+        assert!(auth.url.starts_with(&format!("{}/authorize?", mock.issuer)));
+        mock.approve("code-1", &auth.url);
+        let claims = client
+            .exchange(&config, &auth.state, "code-1")
+            .await
+            .unwrap();
+        assert_eq!(claims["preferred_username"], "alice");
+
+        // A flow is redeemed once, whatever code comes with it.
+        mock.approve("code-2", &auth.url);
+        let err = client
+            .exchange(&config, &auth.state, "code-2")
+            .await
+            .unwrap_err();
+        assert!(err.contains("expired or was already used"), "{err}");
+
+        // A code the provider did not issue to this flow is refused there.
+        let auth = client.authorize(&config, callback).await.unwrap();
+        let err = client
+            .exchange(&config, &auth.state, "code-2")
+            .await
+            .unwrap_err();
+        assert!(err.contains("PKCE verification failed"), "{err}");
+    }
+
+    #[test]
+    fn id_tokens_are_checked_before_their_claims_are_used() {
+        let key = TestKey::generate();
+        let keys: Vec<Jwk> = vec![serde_json::from_value(key.jwk()).unwrap()];
+        let expected = Expected {
+            issuer: "https://idp.example",
+            client_id: "rusts3",
+            nonce: "n-1",
+            now_secs: 1_700_000_000,
+        };
+        let claims = json!({
+            "iss": "https://idp.example", "aud": ["rusts3", "other"], "azp": "rusts3",
+            "exp": 1_700_000_300, "nonce": "n-1", "preferred_username": "alice",
+        });
+        let token = key.id_token(&claims);
+        assert_eq!(
+            verify_id_token(&token, &keys, &expected).unwrap()["nonce"],
+            "n-1"
+        );
+
+        let check = |claims: Value| verify_id_token(&key.id_token(&claims), &keys, &expected);
+        let with = |field: &str, value: Value| {
+            let mut claims = claims.clone();
+            claims[field] = value;
+            claims
+        };
+        assert!(check(with("iss", json!("https://evil.example"))).is_err());
+        assert!(check(with("aud", json!("other"))).is_err());
+        assert!(check(with("azp", json!("other"))).is_err());
+        assert!(check(with("exp", json!(1_699_999_000))).is_err());
+        assert!(check(with("nonce", json!("n-2"))).is_err());
+
+        // Tampering with the payload breaks the signature.
+        let forged = URL_SAFE_NO_PAD.encode(with("preferred_username", json!("root")).to_string());
+        let parts: Vec<&str> = token.split('.').collect();
+        let tampered = format!("{}.{forged}.{}", parts[0], parts[2]);
+        assert!(verify_id_token(&tampered, &keys, &expected).is_err());
+        // Unsigned tokens, and tokens claiming another algorithm, are refused.
+        let unsigned = format!(
+            "{}.{}.",
+            URL_SAFE_NO_PAD.encode(json!({"alg": "none"}).to_string()),
+            parts[1]
+        );
+        assert!(verify_id_token(&unsigned, &keys, &expected).is_err());
+        let hs = key.sign(json!({"alg": "HS256", "kid": "k1"}), &claims);
+        assert!(verify_id_token(&hs, &keys, &expected).is_err());
+    }
+
+    #[test]
+    fn claims_map_to_an_iam_user_and_groups() {
+        let mut config = OidcConfig {
+            enabled: true,
+            ..OidcConfig::default()
+        };
+        let claims = |value: Value| value.as_object().unwrap().clone();
+        let alice = claims(json!({"preferred_username": "alice", "groups": ["staff", "ops"]}));
+        assert_eq!(
+            map_claims(&config, &alice).unwrap(),
+            SsoUser {
+                username: "alice".to_string(),
+                groups: None
+            }
+        );
+
+        config.group_map.insert("ops".into(), "operators".into());
+        config.group_map.insert("staff".into(), "readers".into());
+        config.group_map.insert("admins".into(), "operators".into());
+        let groups = map_claims(&config, &alice).unwrap().groups;
+        assert_eq!(
+            groups,
+            Some(vec!["operators".to_string(), "readers".to_string()])
+        );
+        let single = claims(json!({"preferred_username": "bob", "groups": "admins"}));
+        assert_eq!(
+            map_claims(&config, &single).unwrap().groups,
+            Some(vec!["operators".into()])
+        );
+
+        config.allowed_groups = vec!["admins".to_string()];
+        assert!(map_claims(&config, &alice)
+            .unwrap_err()
+            .contains("not in a group"));
+        assert!(map_claims(&config, &claims(json!({"sub": "x"}))).is_err());
+
+        assert!(is_acceptable_issuer("https://login.example.com/realms/x"));
+        assert!(is_acceptable_issuer("http://127.0.0.1:9000"));
+        assert!(!is_acceptable_issuer("http://login.example.com"));
+        assert!(!is_acceptable_issuer("login.example.com"));
+    }
+}
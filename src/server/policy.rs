@@ -4,6 +4,10 @@
 //! to that user. Evaluation follows AWS semantics: explicit `Deny` wins,
 //! then explicit `Allow`, default is deny. `Action` and `Resource` support
 //! `*` and `?` wildcards; action names match case-insensitively.
+//!
+//! Besides the S3 actions, policies grant console administration through the
+//! `admin:` actions in [`ADMIN_ACTIONS`], on [`ADMIN_RESOURCE`]. They are
+//! evaluated exactly like S3 actions, so a delegated role is just a policy.
 
 use std::collections::BTreeMap;
 
@@ -37,9 +41,10 @@ pub struct Statement {
     pub condition: Option<Condition>,
 }
 
-/// Supported AWS string conditions for S3 request parameters. The condition
-/// key namespace is deliberately strict: unsupported keys fail validation
-/// instead of being silently ignored.
+/// Supported AWS conditions: string operators over the S3 list parameters
+/// and `Bool` over `aws:SecureTransport`. The condition key namespace is
+/// deliberately strict: unsupported keys fail validation instead of being
+/// silently ignored.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Condition {
@@ -51,6 +56,8 @@ pub struct Condition {
     pub string_not_equals: BTreeMap<String, OneOrMany>,
     #[serde(rename = "StringNotLike", default, skip_serializing_if = "BTreeMap::is_empty")]
     pub string_not_like: BTreeMap<String, OneOrMany>,
+    #[serde(rename = "Bool", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bool: BTreeMap<String, OneOrMany>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -306,6 +313,14 @@ impl PolicyDocument {
             if statement.resource.is_empty() {
                 return Err(format!("Statement[{index}].Resource must not be empty"));
             }
+            // A misspelled admin action would silently grant nothing.
+            if let Some(unknown) = statement.action.iter().find(|action| {
+                action.get(..6).is_some_and(|p| p.eq_ignore_ascii_case("admin:"))
+                    && !action.contains(['*', '?'])
+                    && !ADMIN_ACTIONS.iter().any(|(a, _)| a.eq_ignore_ascii_case(action))
+            }) {
+                return Err(format!("Statement[{index}].Action {unknown:?} is not an admin action"));
+            }
             if let Some(condition) = &statement.condition {
                 validate_condition(condition, statement, index)?;
             }
@@ -323,11 +338,34 @@ fn validate_condition(
         && condition.string_like.is_empty()
         && condition.string_not_equals.is_empty()
         && condition.string_not_like.is_empty()
+        && condition.bool.is_empty()
     {
         return Err(format!(
             "Statement[{statement_index}].Condition must not be empty"
         ));
     }
+    for (key, values) in &condition.bool {
+        if !key.eq_ignore_ascii_case(SECURE_TRANSPORT_KEY) {
+            return Err(format!(
+                "Statement[{statement_index}].Condition.Bool uses unsupported key {key:?}"
+            ));
+        }
+        if values.is_empty() || values.iter().any(|v| !matches!(v, "true" | "false")) {
+            return Err(format!(
+                "Statement[{statement_index}].Condition.Bool.{key} must be \"true\" or \"false\""
+            ));
+        }
+    }
+    let operators = [
+        ("StringEquals", &condition.string_equals),
+        ("StringLike", &condition.string_like),
+        ("StringNotEquals", &condition.string_not_equals),
+        ("StringNotLike", &condition.string_not_like),
+    ];
+    if operators.iter().all(|(_, entries)| entries.is_empty()) {
+        // Transport conditions apply to any action and resource.
+        return Ok(());
+    }
     const LIST_ACTIONS: &[&str] = &[
         "s3:ListBucket",
         "s3:ListBucketVersions",
@@ -351,12 +389,6 @@ fn validate_condition(
             "Statement[{statement_index}].Condition uses s3 list keys with a non-bucket Resource"
         ));
     }
-    let operators = [
-        ("StringEquals", &condition.string_equals),
-        ("StringLike", &condition.string_like),
-        ("StringNotEquals", &condition.string_not_equals),
-        ("StringNotLike", &condition.string_not_like),
-    ];
     for (operator, entries) in operators {
         for (key, values) in entries {
             if !matches!(key.to_ascii_lowercase().as_str(), "s3:prefix" | "s3:delimiter") {
@@ -374,6 +406,36 @@ fn validate_condition(
     Ok(())
 }
 
+/// Condition key carrying whether the request arrived over TLS, as `"true"`
+/// or `"false"`. Set by [`Identity::authorize`](super::identity::Identity::authorize).
+pub const SECURE_TRANSPORT_KEY: &str = "aws:SecureTransport";
+
+/// The resource every `admin:` action is checked against. `"Resource": "*"`
+/// matches it as well.
+pub const ADMIN_RESOURCE: &str = "arn:rusts3:admin:::*";
+
+/// Console administration actions and what each one unlocks. The system
+/// `admin` group holds `admin:*`; anything less is a delegated role.
+pub const ADMIN_ACTIONS: &[(&str, &str)] = &[
+    ("admin:ManageUsers", "IAM users and groups, their keys, passwords and memberships"),
+    ("admin:ManagePolicies", "attach policies to IAM users and groups"),
+    ("admin:ManageSessions", "list console sessions and sign them out"),
+    ("admin:ManageShares", "list every user's share links, their use, and revoke them"),
+    ("admin:ManageBuckets", "bucket quotas, compression, deduplication and data-root moves"),
+    ("admin:ManageSnapshots", "list, take and delete whole-server snapshots"),
+    ("admin:ExportIam", "download the IAM database"),
+    ("admin:ImportIam", "preview and apply IAM imports"),
+    ("admin:ViewTasks", "watch the task monitor"),
+    ("admin:CancelTask", "cancel running tasks"),
+    ("admin:ViewScans", "storage scan progress, reports and findings"),
+    ("admin:RunScan", "start storage scans and delete their reports"),
+    ("admin:RepairObjects", "repair what a storage scan found"),
+    ("admin:ViewStats", "the runtime stats dashboard"),
+    ("admin:ViewAudit", "browse and export the audit trail"),
+    ("admin:ViewConfig", "the configuration status page"),
+    ("admin:ReloadConfig", "reload the configuration file"),
+];
+
 /// One authorization requirement derived from an incoming request:
 /// the S3 action plus the ARN it targets.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -419,6 +481,15 @@ impl Requirement {
             context: BTreeMap::new(),
         }
     }
+
+    /// A console administration action from [`ADMIN_ACTIONS`].
+    pub fn admin(action: &'static str) -> Self {
+        Self {
+            action,
+            resource: ADMIN_RESOURCE.to_string(),
+            context: BTreeMap::new(),
+        }
+    }
 }
 
 /// Returns true when `policy` allows every requirement.
@@ -456,6 +527,76 @@ pub fn evaluate(policy: &PolicyDocument, requirement: &Requirement) -> bool {
     allowed
 }
 
+/// Whether everything `granted` allows, `holder` allows as well, so a
+/// delegated administrator cannot hand out more than they hold. `None` holds
+/// nothing. Deliberately conservative: a conditional Allow in `holder` does
+/// not count, and a Deny in `holder` that might overlap a grant refuses it.
+pub fn grants_within(holder: Option<&PolicyDocument>, granted: &PolicyDocument) -> bool {
+    let held = holder.map_or(&[][..], |policy| policy.statement.as_slice());
+    let allows = granted.statement.iter().filter(|s| s.effect == Effect::Allow);
+    allows.flat_map(|s| s.action.iter().flat_map(|a| s.resource.iter().map(move |r| (a, r))))
+        .all(|(action, resource)| {
+            let covered = held.iter().any(|s| {
+                s.effect == Effect::Allow
+                    && s.condition.is_none()
+                    && s.action.iter().any(|p| pattern_covers(p, action, true))
+                    && s.resource.iter().any(|p| pattern_covers(p, resource, false))
+            });
+            let denied = held.iter().any(|s| {
+                s.effect == Effect::Deny
+                    && s.action.iter().any(|p| patterns_may_overlap(p, action, true))
+                    && s.resource.iter().any(|p| patterns_may_overlap(p, resource, false))
+            });
+            covered && !denied
+        })
+}
+
+fn has_wildcard(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+fn glob_match(pattern: &str, value: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        wildcard_match_ci(pattern, value)
+    } else {
+        wildcard_match(pattern, value)
+    }
+}
+
+/// Whether every value `target` matches, `pattern` matches too. A `*`-only
+/// pattern that matches `target` read literally does; `?` is only trusted
+/// against a plain value.
+fn pattern_covers(pattern: &str, target: &str, ignore_case: bool) -> bool {
+    if !has_wildcard(target) || !pattern.contains('?') {
+        return glob_match(pattern, target, ignore_case);
+    }
+    if ignore_case {
+        pattern.eq_ignore_ascii_case(target)
+    } else {
+        pattern == target
+    }
+}
+
+/// Whether some value could match both patterns. Two wildcard patterns are
+/// assumed to unless their literal prefixes or suffixes disagree.
+fn patterns_may_overlap(a: &str, b: &str, ignore_case: bool) -> bool {
+    match (has_wildcard(a), has_wildcard(b)) {
+        (true, true) => {
+            let fold = |p: &str| if ignore_case { p.to_ascii_lowercase() } else { p.to_string() };
+            let (a, b) = (fold(a), fold(b));
+            // Where each literal prefix ends and literal suffix begins.
+            let head = |p: &str| p.find(['*', '?']).unwrap_or(p.len());
+            let tail = |p: &str| p.rfind(['*', '?']).map_or(0, |i| i + 1);
+            let (a_head, b_head) = (&a[..head(&a)], &b[..head(&b)]);
+            let (a_tail, b_tail) = (&a[tail(&a)..], &b[tail(&b)..]);
+            (a_head.starts_with(b_head) || b_head.starts_with(a_head))
+                && (a_tail.ends_with(b_tail) || b_tail.ends_with(a_tail))
+        }
+        (_, false) => glob_match(a, b, ignore_case),
+        (false, true) => glob_match(b, a, ignore_case),
+    }
+}
+
 fn conditions_match(
     condition: Option<&Condition>,
     context: &BTreeMap<String, String>,
@@ -476,6 +617,9 @@ fn conditions_match(
             context,
             wildcard_match,
         )
+        && condition_operator_matches(&condition.bool, context, |pattern, value| {
+            pattern.eq_ignore_ascii_case(value)
+        })
 }
 
 fn condition_operator_matches(
@@ -620,6 +764,14 @@ pub fn requirements_for_request(
         ("PUT" | "DELETE", None) if has("cors") => {
             vec![Requirement::bucket("s3:PutBucketCORS", bucket)]
         }
+        ("GET", None) if has("logging") => {
+            vec![Requirement::bucket("s3:GetBucketLogging", bucket)]
+        }
+        // Write access to the target bucket is checked by the handler once the
+        // body naming it has been parsed.
+        ("PUT", None) if has("logging") => {
+            vec![Requirement::bucket("s3:PutBucketLogging", bucket)]
+        }
         ("PUT", None) => vec![Requirement::bucket("s3:CreateBucket", bucket)],
         ("DELETE", None) => vec![Requirement::bucket("s3:DeleteBucket", bucket)],
         ("HEAD", None) => vec![Requirement::bucket("s3:ListBucket", bucket)],
@@ -900,6 +1052,8 @@ mod tests {
         assert_eq!(r("DELETE", "/b/k", "uploadId=x")[0].action, "s3:AbortMultipartUpload");
         assert_eq!(r("POST", "/b/k", "uploads")[0].action, "s3:PutObject");
         assert!(r("POST", "/b", "delete").is_empty());
+        assert_eq!(r("GET", "/b", "logging")[0].action, "s3:GetBucketLogging");
+        assert_eq!(r("PUT", "/b", "logging")[0].action, "s3:PutBucketLogging");
         assert_eq!(
             requirements_for_request("POST", "/b", "rebuildIndex", None).unwrap()[0].action,
             "s3:RebuildIndex"
@@ -926,4 +1080,56 @@ mod tests {
         assert!(reqs.contains(&Requirement::object("s3:PutObject", "dst", "key")));
         assert!(reqs.contains(&Requirement::object("s3:GetObject", "src", "orig")));
     }
+
+    #[test]
+    fn admin_actions_are_granted_like_s3_actions() {
+        let operator = policy(
+            r#"{"Statement":[
+                {"Effect":"Allow","Action":["admin:View*"],"Resource":"*"},
+                {"Effect":"Deny","Action":"admin:ViewAudit","Resource":"arn:rusts3:admin:::*"}
+            ]}"#,
+        );
+        operator.validate().unwrap();
+        assert!(is_authorized(&operator, &[Requirement::admin("admin:ViewStats")]));
+        assert!(!is_authorized(&operator, &[Requirement::admin("admin:ViewAudit")]));
+        assert!(!is_authorized(&operator, &[Requirement::admin("admin:RunScan")]));
+        // S3 grants stay S3 grants.
+        let data = policy(r#"{"Statement":[{"Effect":"Allow","Action":"s3:*","Resource":"*"}]}"#);
+        assert!(!is_authorized(&data, &[Requirement::admin("admin:ManageUsers")]));
+
+        let typo = policy(
+            r#"{"Statement":[{"Effect":"Allow","Action":"admin:RunScans","Resource":"*"}]}"#,
+        );
+        assert!(typo.validate().unwrap_err().contains("admin:RunScans"));
+    }
+
+    #[test]
+    fn grants_within_refuses_anything_the_holder_lacks() {
+        let holder = policy(
+            r#"{"Statement":[
+                {"Effect":"Allow","Action":["s3:Get*","admin:ManagePolicies"],"Resource":"*"},
+                {"Effect":"Allow","Action":"s3:PutObject","Resource":"arn:aws:s3:::docs/*"},
+                {"Effect":"Deny","Action":"s3:GetObject","Resource":"arn:aws:s3:::vault/*"}
+            ]}"#,
+        );
+        let within = |json: &str| grants_within(Some(&holder), &policy(json));
+        let allows = |action: &str, resource: &str| {
+            let statement =
+                serde_json::json!({ "Effect": "Allow", "Action": action, "Resource": resource });
+            within(&serde_json::json!({ "Statement": [statement] }).to_string())
+        };
+        assert!(allows("s3:GetObject", "arn:aws:s3:::docs/*"));
+        assert!(allows("s3:putobject", "arn:aws:s3:::docs/a/*"));
+        assert!(allows("admin:ManagePolicies", ADMIN_RESOURCE));
+        // Deny statements hand nothing out.
+        assert!(within(r#"{"Statement":[{"Effect":"Deny","Action":"*","Resource":"*"}]}"#));
+        assert!(!within(READ_ONLY_DOCS), "s3:ListBucket is not held");
+        assert!(!allows("s3:Put*", "arn:aws:s3:::docs/*"));
+        assert!(!allows("s3:*", "*"));
+        assert!(!allows("admin:*", "*"));
+        assert!(!allows("s3:GetObject", "arn:aws:s3:::v*"), "overlaps the vault deny");
+        assert!(!allows("s3:PutObject", "arn:aws:s3:::docs?/*"));
+        assert!(!grants_within(None, &policy(READ_ONLY_DOCS)));
+        assert!(grants_within(None, &policy(r#"{"Statement":[]}"#)));
+    }
 }
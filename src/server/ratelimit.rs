@@ -0,0 +1,665 @@
+//! Token-bucket rate limiting for the S3 API.
+//!
+//! `rate_limits.rules` in the config each select callers by access key, user,
+//! IAM group or source address, and every rule keeps its own buckets: one per
+//! distinct caller when the rule's `subject` is `"*"`, otherwise one shared by
+//! everything the subject matches (a whole group, a whole CIDR range). A
+//! request is subject to every rule that matches it.
+//!
+//! Request buckets gate admission — an empty one answers `503 SlowDown` with a
+//! `Retry-After` covering the wait. Byte buckets pace instead of refusing: body
+//! streams draw from them chunk by chunk and sleep off any debt, so a transfer
+//! slows to the configured rate rather than failing half-way. A new request
+//! whose byte bucket is still in debt is refused like an over-rate one, which
+//! is what stops a client that opens many parallel transfers.
+//!
+//! Limiting runs inside authentication, so only a verified access key can
+//! spend a bucket — a forged `Credential=` cannot exhaust someone else's.
+//! Rules are read from the live config on every request; a reload that changes
+//! them starts every bucket afresh.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{header, HeaderValue, Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use futures::StreamExt;
+use serde::Serialize;
+
+use super::config::{RateLimitRule, RateLimitScope};
+use super::iam::IamStore;
+use super::identity::Identity;
+use super::reload::LiveConfig;
+use super::{s3_error, OperationActor, RequestId};
+
+/// Buckets idle this long are forgotten (and drop out of the task monitor). A
+/// bucket that has been idle for a while has refilled, so dropping it loses
+/// nothing but its throttle count.
+const IDLE_FORGET: Duration = Duration::from_secs(300);
+
+/// The monitor lists buckets used within this window.
+const MONITOR_WINDOW: Duration = Duration::from_secs(60);
+
+/// An address or CIDR range from a rule's `subject`, e.g. `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(text: &str) -> Option<Self> {
+        let (addr, prefix) = match text.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (text, None),
+        };
+        let network: IpAddr = addr.trim().parse().ok()?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().ok().filter(|p| *p <= max)?,
+            None => max,
+        };
+        Some(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Compare IPv4-mapped IPv6 peers (dual-stack listeners) as IPv4.
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Who is asking, as far as the rules care.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Caller {
+    pub access_key: Option<String>,
+    pub username: Option<String>,
+    pub groups: Vec<String>,
+    pub ip: Option<IpAddr>,
+}
+
+impl RateLimitRule {
+    /// The bucket subjects this rule charges for `caller` — empty when the
+    /// rule does not apply. Only a group rule can yield more than one.
+    fn subjects(&self, caller: &Caller) -> Vec<String> {
+        let any = self.subject == "*";
+        let exact = |value: &Option<String>| {
+            value
+                .as_ref()
+                .filter(|v| any || **v == self.subject)
+                .cloned()
+                .into_iter()
+                .collect()
+        };
+        match self.scope {
+            RateLimitScope::AccessKey => exact(&caller.access_key),
+            RateLimitScope::User => exact(&caller.username),
+            RateLimitScope::Group => caller
+                .groups
+                .iter()
+                .filter(|g| any || **g == self.subject)
+                .cloned()
+                .collect(),
+            RateLimitScope::Ip => match caller.ip {
+                Some(ip) if any => vec![ip.to_string()],
+                Some(ip) if IpRange::parse(&self.subject).is_some_and(|r| r.contains(ip)) => {
+                    vec![self.subject.clone()]
+                }
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, capacity: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Seconds until the balance reaches `level`.
+    fn wait_until(&self, level: f64) -> f64 {
+        ((level - self.tokens) / self.rate).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    rule: usize,
+    subject: String,
+}
+
+struct Buckets {
+    requests: Option<TokenBucket>,
+    bytes_in: Option<TokenBucket>,
+    bytes_out: Option<TokenBucket>,
+    throttled: u64,
+    last_used: Instant,
+}
+
+impl Buckets {
+    fn new(rule: &RateLimitRule, now: Instant) -> Self {
+        let burst = rule.burst_secs;
+        Self {
+            // A request bucket always holds at least one request, or a rate
+            // under 1/s with a short burst could never admit anything.
+            requests: rule
+                .requests_per_sec
+                .map(|rate| TokenBucket::new(rate, (rate * burst).max(1.0), now)),
+            bytes_in: rule
+                .bytes_in_per_sec
+                .map(|rate| TokenBucket::new(rate as f64, rate as f64 * burst, now)),
+            bytes_out: rule
+                .bytes_out_per_sec
+                .map(|rate| TokenBucket::new(rate as f64, rate as f64 * burst, now)),
+            throttled: 0,
+            last_used: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        for bucket in [&mut self.requests, &mut self.bytes_in, &mut self.bytes_out]
+            .into_iter()
+            .flatten()
+        {
+            bucket.refill(now);
+        }
+    }
+
+    /// Seconds before this subject may start another request.
+    fn admission_wait(&self) -> f64 {
+        let requests = self.requests.as_ref().map_or(0.0, |b| b.wait_until(1.0));
+        let debt = [&self.bytes_in, &self.bytes_out]
+            .into_iter()
+            .flatten()
+            .map(|b| b.wait_until(0.0))
+            .fold(0.0, f64::max);
+        requests.max(debt)
+    }
+
+    fn bytes(&mut self, direction: Direction) -> Option<&mut TokenBucket> {
+        match direction {
+            Direction::In => self.bytes_in.as_mut(),
+            Direction::Out => self.bytes_out.as_mut(),
+        }
+    }
+}
+
+struct LimiterState {
+    /// The rules the buckets were built from; a reload that changes them
+    /// starts over (bucket keys are rule positions).
+    rules: Vec<RateLimitRule>,
+    /// Bumped on every reset so permits issued against old buckets go inert.
+    epoch: u64,
+    buckets: HashMap<BucketKey, Buckets>,
+}
+
+pub(crate) struct RateLimiter {
+    config: LiveConfig,
+    iam: Option<IamStore>,
+    state: Mutex<LimiterState>,
+}
+
+/// The buckets an admitted request charges its body bytes to.
+#[derive(Clone)]
+pub(crate) struct Permit {
+    limiter: Arc<RateLimiter>,
+    epoch: u64,
+    keys: Arc<Vec<BucketKey>>,
+    paces_in: bool,
+    paces_out: bool,
+}
+
+impl Permit {
+    fn paces(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::In => self.paces_in,
+            Direction::Out => self.paces_out,
+        }
+    }
+
+    /// Charges `bytes` to every byte bucket for `direction` and returns how
+    /// long to hold the chunk so the stream keeps to the slowest rate.
+    pub(crate) fn charge(&self, direction: Direction, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let mut state = self.limiter.state.lock().unwrap();
+        if state.epoch != self.epoch {
+            return Duration::ZERO;
+        }
+        let mut wait: f64 = 0.0;
+        for key in self.keys.iter() {
+            let Some(buckets) = state.buckets.get_mut(key) else {
+                continue;
+            };
+            buckets.last_used = now;
+            if let Some(bucket) = buckets.bytes(direction) {
+                bucket.refill(now);
+                bucket.tokens -= bytes as f64;
+                wait = wait.max(bucket.wait_until(0.0));
+            }
+        }
+        Duration::from_secs_f64(wait)
+    }
+}
+
+/// One bucket set in the task monitor.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LimitSnapshot {
+    pub scope: RateLimitScope,
+    pub subject: String,
+    /// Whether the subject would be refused right now.
+    pub limited: bool,
+    pub throttled: u64,
+    pub requests: Option<BucketSnapshot>,
+    pub bytes_in: Option<BucketSnapshot>,
+    pub bytes_out: Option<BucketSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BucketSnapshot {
+    pub rate: f64,
+    pub capacity: f64,
+    /// Negative while a byte bucket is paying off a transfer.
+    pub tokens: f64,
+}
+
+impl From<&TokenBucket> for BucketSnapshot {
+    fn from(bucket: &TokenBucket) -> Self {
+        Self {
+            rate: bucket.rate,
+            capacity: bucket.capacity,
+            tokens: bucket.tokens,
+        }
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: LiveConfig, iam: Option<IamStore>) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            iam,
+            state: Mutex::new(LimiterState {
+                rules: Vec::new(),
+                epoch: 0,
+                buckets: HashMap::new(),
+            }),
+        })
+    }
+
+    /// Builds the [`Caller`] for a request that passed authentication. Group
+    /// membership is only looked up when a group rule exists.
+    fn caller(&self, request: &Request<Body>, rules: &[RateLimitRule]) -> Caller {
+        let actor = request.extensions().get::<OperationActor>();
+        let iam_user = match request.extensions().get::<Identity>() {
+            Some(Identity::Iam { username, .. }) => Some(username.as_str()),
+            _ => None,
+        };
+        let groups = match (iam_user, &self.iam) {
+            (Some(username), Some(iam))
+                if rules.iter().any(|r| r.scope == RateLimitScope::Group) =>
+            {
+                iam.groups_for(username)
+            }
+            _ => Vec::new(),
+        };
+        Caller {
+            access_key: actor.and_then(|a| a.access_key.clone()),
+            username: actor.and_then(|a| a.username.clone()),
+            groups,
+            ip: request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+        }
+    }
+
+    /// Admits a request or says how long the caller must wait. `Ok(None)`
+    /// means no rule applies and nothing needs pacing.
+    pub(crate) fn admit(
+        self: &Arc<Self>,
+        caller: &Caller,
+        rules: &[RateLimitRule],
+    ) -> Result<Option<Permit>, Duration> {
+        let keys: Vec<BucketKey> = rules
+            .iter()
+            .enumerate()
+            .flat_map(|(rule, spec)| {
+                spec.subjects(caller)
+                    .into_iter()
+                    .map(move |subject| BucketKey { rule, subject })
+            })
+            .collect();
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.rules != rules {
+            state.rules = rules.to_vec();
+            state.epoch += 1;
+            state.buckets.clear();
+        }
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        if state.buckets.len() > 10_000 {
+            state
+                .buckets
+                .retain(|_, b| now.saturating_duration_since(b.last_used) < IDLE_FORGET);
+        }
+        let mut wait: f64 = 0.0;
+        for key in &keys {
+            let buckets = state
+                .buckets
+                .entry(key.clone())
+                .or_insert_with(|| Buckets::new(&rules[key.rule], now));
+            buckets.refill(now);
+            buckets.last_used = now;
+            let own = buckets.admission_wait();
+            if own > 0.0 {
+                buckets.throttled += 1;
+            }
+            wait = wait.max(own);
+        }
+        if wait > 0.0 {
+            return Err(Duration::from_secs_f64(wait));
+        }
+        let (mut paces_in, mut paces_out) = (false, false);
+        for key in &keys {
+            if let Some(buckets) = state.buckets.get_mut(key) {
+                if let Some(requests) = buckets.requests.as_mut() {
+                    requests.tokens -= 1.0;
+                }
+                paces_in |= buckets.bytes_in.is_some();
+                paces_out |= buckets.bytes_out.is_some();
+            }
+        }
+        Ok(Some(Permit {
+            limiter: Arc::clone(self),
+            epoch: state.epoch,
+            keys: Arc::new(keys),
+            paces_in,
+            paces_out,
+        }))
+    }
+
+    /// Buckets used within the last minute, busiest first.
+    pub(crate) fn snapshot(&self) -> Vec<LimitSnapshot> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state
+            .buckets
+            .retain(|_, b| now.saturating_duration_since(b.last_used) < IDLE_FORGET);
+        let state = &mut *state;
+        let mut out: Vec<LimitSnapshot> = state
+            .buckets
+            .iter_mut()
+            .filter(|(_, b)| now.saturating_duration_since(b.last_used) < MONITOR_WINDOW)
+            .filter_map(|(key, buckets)| {
+                let rule = state.rules.get(key.rule)?;
+                buckets.refill(now);
+                Some(LimitSnapshot {
+                    scope: rule.scope,
+                    subject: key.subject.clone(),
+                    limited: buckets.admission_wait() > 0.0,
+                    throttled: buckets.throttled,
+                    requests: buckets.requests.as_ref().map(Into::into),
+                    bytes_in: buckets.bytes_in.as_ref().map(Into::into),
+                    bytes_out: buckets.bytes_out.as_ref().map(Into::into),
+                })
+            })
+            .collect();
+        out.sort_by(|a, b| {
+            b.limited
+                .cmp(&a.limited)
+                .then(b.throttled.cmp(&a.throttled))
+                .then_with(|| a.subject.cmp(&b.subject))
+        });
+        out
+    }
+}
+
+/// Sits just inside authentication: admits or refuses the request, then
+/// paces the request and response bodies through the caller's byte buckets.
+pub(crate) async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let config = limiter.config.current();
+    if !config.rate_limits.enabled || config.rate_limits.rules.is_empty() {
+        return next.run(request).await;
+    }
+    let rules = &config.rate_limits.rules;
+    let caller = limiter.caller(&request, rules);
+    let permit = match limiter.admit(&caller, rules) {
+        Ok(Some(permit)) => permit,
+        Ok(None) => return next.run(request).await,
+        Err(wait) => {
+            let rid = request
+                .extensions()
+                .get::<RequestId>()
+                .map(|id| id.0.as_str())
+                .unwrap_or("-");
+            log::info!(
+                "[{rid}] rate limited key={} user={} ip={} retry_after={:.1}s",
+                caller.access_key.as_deref().unwrap_or("-"),
+                caller.username.as_deref().unwrap_or("-"),
+                caller
+                    .ip
+                    .map(|ip| ip.to_string())
+                    .unwrap_or_else(|| "-".into()),
+                wait.as_secs_f64(),
+            );
+            return slow_down(wait, request.uri().path());
+        }
+    };
+    drop(config);
+
+    let request =
+        if permit.paces(Direction::In) && matches!(*request.method(), Method::PUT | Method::POST) {
+            let (parts, body) = request.into_parts();
+            Request::from_parts(parts, paced(body, permit.clone(), Direction::In))
+        } else {
+            request
+        };
+    let response = next.run(request).await;
+    if permit.paces(Direction::Out) {
+        let (parts, body) = response.into_parts();
+        return Response::from_parts(parts, paced(body, permit, Direction::Out));
+    }
+    response
+}
+
+fn paced(body: Body, permit: Permit, direction: Direction) -> Body {
+    Body::from_stream(body.into_data_stream().then(move |chunk| {
+        let permit = permit.clone();
+        async move {
+            if let Ok(bytes) = &chunk {
+                let wait = permit.charge(direction, bytes.len());
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+            chunk
+        }
+    }))
+}
+
+fn slow_down(wait: Duration, resource: &str) -> Response {
+    let mut response = s3_error(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "SlowDown",
+        "Please reduce your request rate.",
+        resource,
+    );
+    let secs = wait.as_secs_f64().ceil().max(1.0) as u64;
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::config::AppConfig;
+
+    fn rule(scope: RateLimitScope, subject: &str) -> RateLimitRule {
+        RateLimitRule {
+            scope,
+            subject: subject.to_string(),
+            requests_per_sec: None,
+            bytes_in_per_sec: None,
+            bytes_out_per_sec: None,
+            burst_secs: 1.0,
+        }
+    }
+
+    fn limiter() -> Arc<RateLimiter> {
+        RateLimiter::new(LiveConfig::new(Arc::new(AppConfig::default())), None)
+    }
+
+    fn caller(key: &str, ip: &str) -> Caller {
+        Caller {
+            access_key: Some(key.to_string()),
+            username: Some("alice".to_string()),
+            groups: vec!["ci".to_string(), "dev".to_string()],
+            ip: ip.parse().ok(),
+        }
+    }
+
+    #[test]
+    fn ip_ranges_match_by_prefix_and_mapped_v4() {
+        let range = IpRange::parse("10.1.0.0/16").unwrap();
+        assert!(range.contains("10.1.200.3".parse().unwrap()));
+        assert!(!range.contains("10.2.0.1".parse().unwrap()));
+        assert!(range.contains("::ffff:10.1.0.9".parse().unwrap()));
+        assert!(IpRange::parse("0.0.0.0/0")
+            .unwrap()
+            .contains("8.8.8.8".parse().unwrap()));
+        assert!(IpRange::parse("2001:db8::/32")
+            .unwrap()
+            .contains("2001:db8::1".parse().unwrap()));
+        assert_eq!(IpRange::parse("10.0.0.0/33"), None);
+        assert_eq!(IpRange::parse("not-an-ip"), None);
+    }
+
+    #[test]
+    fn subjects_follow_scope_and_wildcards() {
+        let c = caller("AK1", "192.168.1.5");
+        assert_eq!(rule(RateLimitScope::AccessKey, "*").subjects(&c), ["AK1"]);
+        assert!(rule(RateLimitScope::AccessKey, "AK2")
+            .subjects(&c)
+            .is_empty());
+        assert_eq!(rule(RateLimitScope::Group, "*").subjects(&c), ["ci", "dev"]);
+        assert_eq!(rule(RateLimitScope::Group, "ci").subjects(&c), ["ci"]);
+        assert_eq!(rule(RateLimitScope::Ip, "*").subjects(&c), ["192.168.1.5"]);
+        assert_eq!(
+            rule(RateLimitScope::Ip, "192.168.0.0/16").subjects(&c),
+            ["192.168.0.0/16"]
+        );
+    }
+
+    #[test]
+    fn request_bucket_refuses_past_the_burst_and_counts_throttles() {
+        let limiter = limiter();
+        let rules = vec![RateLimitRule {
+            requests_per_sec: Some(2.0),
+            ..rule(RateLimitScope::AccessKey, "*")
+        }];
+        let c = caller("AK1", "127.0.0.1");
+        assert!(limiter.admit(&c, &rules).unwrap().is_some());
+        assert!(limiter.admit(&c, &rules).unwrap().is_some());
+        let wait = limiter.admit(&c, &rules).err().unwrap();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(500));
+        // Another key has its own bucket.
+        assert!(limiter.admit(&caller("AK2", "127.0.0.1"), &rules).is_ok());
+        let snap = limiter.snapshot();
+        assert_eq!(snap[0].subject, "AK1");
+        assert!(snap[0].limited);
+        assert_eq!(snap[0].throttled, 1);
+    }
+
+    #[test]
+    fn byte_debt_paces_transfers_and_blocks_new_requests() {
+        let limiter = limiter();
+        let rules = vec![RateLimitRule {
+            bytes_in_per_sec: Some(1000),
+            ..rule(RateLimitScope::Ip, "*")
+        }];
+        let c = caller("AK1", "10.0.0.1");
+        let permit = limiter.admit(&c, &rules).unwrap().unwrap();
+        assert!(permit.paces(Direction::In) && !permit.paces(Direction::Out));
+        // The burst covers the first second; the next 500 bytes are owed.
+        assert_eq!(permit.charge(Direction::In, 1000), Duration::ZERO);
+        let wait = permit.charge(Direction::In, 500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        assert!(limiter.admit(&c, &rules).is_err());
+        // Unmatched callers are not limited at all.
+        let other = Caller { ip: None, ..c };
+        assert!(limiter.admit(&other, &rules).unwrap().is_none());
+    }
+
+    #[test]
+    fn changed_rules_reset_buckets_and_disarm_old_permits() {
+        let limiter = limiter();
+        let c = caller("AK1", "127.0.0.1");
+        let strict = vec![RateLimitRule {
+            requests_per_sec: Some(1.0),
+            bytes_out_per_sec: Some(10),
+            ..rule(RateLimitScope::User, "alice")
+        }];
+        let permit = limiter.admit(&c, &strict).unwrap().unwrap();
+        assert!(limiter.admit(&c, &strict).is_err());
+        let relaxed = vec![RateLimitRule {
+            requests_per_sec: Some(100.0),
+            ..strict[0].clone()
+        }];
+        assert!(limiter.admit(&c, &relaxed).is_ok());
+        assert_eq!(permit.charge(Direction::Out, 1_000_000), Duration::ZERO);
+    }
+}
@@ -188,6 +188,11 @@ impl TaskRegistry {
         self.hub.publish(event);
     }
 
+    /// Opens the hub's lossless audit feed; see [`EventHub::audit_feed`].
+    pub fn audit_feed(&self, capacity: usize) -> Option<std::sync::mpsc::Receiver<Event>> {
+        self.hub.audit_feed(capacity)
+    }
+
     /// Registers a cancellable task (the default) and returns a guard that
     /// deregisters it on drop.
     pub fn register(
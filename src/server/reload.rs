@@ -0,0 +1,401 @@
+//! Hot configuration reload.
+//!
+//! The running configuration lives behind a [`LiveConfig`] handle shared by the
+//! S3 auth layer, the console and the background job schedulers; each takes
+//! the current snapshot when it needs one, so a reload is a single atomic
+//! pointer swap and nothing holds on to a stale copy.
+//!
+//! A reload — SIGHUP, or the console's admin action — re-reads the file the
+//! server started from through the same template expansion and validation as
+//! startup. Settings that were consumed once at startup (listeners, the data
+//! directory, storage tuning, which subsystems exist at all) cannot change
+//! underneath a running process: the new snapshot keeps the running values for
+//! those, and the [`ReloadReport`] lists them as waiting for a restart.
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use super::config::AppConfig;
+
+/// Config paths (a section, or a `section.field`) captured at startup. A
+/// change under any of them is reported but only takes effect on restart.
+const RESTART_REQUIRED: &[&str] = &[
+    "server",
+    "storage",
+    "ui.enabled",
+    "ui.bind_address",
+    "ui.bind_port",
+    "ui.tls",
+    "logging.enable_bandwidth_report",
+    "stats.enabled",
+    "audit.enabled",
+];
+
+/// Copies every [`RESTART_REQUIRED`] setting from the running config into a
+/// freshly loaded one, so the snapshot handed out always describes what the
+/// process is actually doing.
+fn keep_restart_settings(running: &AppConfig, next: &mut AppConfig) {
+    next.server = running.server.clone();
+    next.storage = running.storage.clone();
+    next.ui.enabled = running.ui.enabled;
+    next.ui.bind_address = running.ui.bind_address.clone();
+    next.ui.bind_port = running.ui.bind_port;
+    next.ui.tls = running.ui.tls.clone();
+    next.logging.enable_bandwidth_report = running.logging.enable_bandwidth_report;
+    next.stats.enabled = running.stats.enabled;
+    next.audit.enabled = running.audit.enabled;
+}
+
+fn requires_restart(path: &str) -> bool {
+    RESTART_REQUIRED.iter().any(|prefix| {
+        path == *prefix
+            || path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// What a reload did. Only field paths are reported, never values — the
+/// config holds passwords and secret keys.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadReport {
+    /// The file that was re-read.
+    pub source: String,
+    /// Every field whose value differs from the running config, sorted.
+    pub changed: Vec<String>,
+    /// The subset of `changed` that keeps its running value until a restart.
+    pub restart_required: Vec<String>,
+    /// Changes that were accepted but could not be fully applied.
+    pub warnings: Vec<String>,
+}
+
+impl ReloadReport {
+    /// One line for the server log.
+    pub fn summary(&self) -> String {
+        if self.changed.is_empty() {
+            return format!("configuration reloaded from {}: no changes", self.source);
+        }
+        let applied: Vec<&str> = self
+            .changed
+            .iter()
+            .filter(|path| !requires_restart(path))
+            .map(String::as_str)
+            .collect();
+        let mut line = format!(
+            "configuration reloaded from {}: applied=[{}]",
+            self.source,
+            applied.join(", ")
+        );
+        if !self.restart_required.is_empty() {
+            line.push_str(&format!(
+                " restart_required=[{}]",
+                self.restart_required.join(", ")
+            ));
+        }
+        line
+    }
+}
+
+/// The running configuration, swappable at runtime. Cheap to clone; every
+/// clone sees the same snapshot.
+#[derive(Clone)]
+pub struct LiveConfig {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    current: RwLock<Arc<AppConfig>>,
+    /// The file the server was started from. `None` for in-process configs
+    /// (tests, embeddings), which have nothing to reload from.
+    source: Option<String>,
+    /// Serializes reloads, so two overlapping ones cannot interleave their
+    /// diff and swap.
+    reloading: Mutex<()>,
+}
+
+impl LiveConfig {
+    /// A fixed in-process config with no file behind it.
+    pub fn new(config: Arc<AppConfig>) -> Self {
+        Self::build(config, None)
+    }
+
+    /// A config loaded from `path`, which [`reload`](Self::reload) re-reads.
+    pub fn from_source(config: Arc<AppConfig>, path: impl Into<String>) -> Self {
+        Self::build(config, Some(path.into()))
+    }
+
+    fn build(config: Arc<AppConfig>, source: Option<String>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                current: RwLock::new(config),
+                source,
+                reloading: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// The current snapshot. Hold it for the duration of one request or job
+    /// run rather than re-reading field by field.
+    pub fn current(&self) -> Arc<AppConfig> {
+        self.inner
+            .current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// The file reloads re-read, if there is one.
+    pub fn source(&self) -> Option<&str> {
+        self.inner.source.as_deref()
+    }
+
+    /// Re-reads, validates and installs the source file. On any error the
+    /// running config is left untouched.
+    pub fn reload(&self) -> Result<ReloadReport, String> {
+        let source = self.inner.source.clone().ok_or_else(|| {
+            "the server was started without a config file; there is nothing to reload".to_string()
+        })?;
+        let _guard = self
+            .inner
+            .reloading
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let next = AppConfig::from_file(&source).map_err(|err| format!("{source}: {err}"))?;
+        Ok(self.install(source, next))
+    }
+
+    /// [`reload`](Self::reload) off the async runtime, with the outcome logged
+    /// under `trigger` (what asked for it: a signal, a console user).
+    pub async fn reload_logged(&self, trigger: &str) -> Result<ReloadReport, String> {
+        let live = self.clone();
+        let result = tokio::task::spawn_blocking(move || live.reload())
+            .await
+            .map_err(|err| format!("reload task failed: {err}"))?;
+        match &result {
+            Ok(report) => {
+                log::info!("{trigger}: {}", report.summary());
+                for warning in &report.warnings {
+                    log::warn!("{trigger}: {warning}");
+                }
+            }
+            Err(err) => log::warn!(
+                "{trigger}: configuration reload failed, keeping the running config: {err}"
+            ),
+        }
+        result
+    }
+
+    fn install(&self, source: String, mut next: AppConfig) -> ReloadReport {
+        let running = self.current();
+        let changed = changed_fields(&running, &next);
+        let restart_required = changed
+            .iter()
+            .filter(|path| requires_restart(path))
+            .cloned()
+            .collect();
+        keep_restart_settings(&running, &mut next);
+
+        let mut warnings = Vec::new();
+        let logging_changed = changed
+            .iter()
+            .any(|path| path.starts_with("logging.") && !requires_restart(path));
+        if logging_changed {
+            if let Err(err) = super::logging::reconfigure(&next.logging) {
+                warnings.push(format!(
+                    "logging: {err}; the previous log setup stays active"
+                ));
+            }
+        }
+        *self
+            .inner
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(next);
+        ReloadReport {
+            source,
+            changed,
+            restart_required,
+            warnings,
+        }
+    }
+}
+
+/// Reloads the configuration on every SIGHUP until shutdown. Handling the
+/// signal at all also stops it from terminating the process, its default.
+pub(crate) fn spawn_sighup_listener(config: LiveConfig, shutdown: CancellationToken) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::warn!("cannot listen for SIGHUP ({err}); reload from the console instead");
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                received = hangup.recv() => {
+                    if received.is_none() {
+                        break;
+                    }
+                    let _ = config.reload_logged("SIGHUP").await;
+                }
+            }
+        }
+    });
+    #[cfg(not(unix))]
+    let _ = (config, shutdown);
+}
+
+/// Dotted paths of every leaf that differs between two configs. Lists are
+/// compared whole (`auth.users`), since an entry has no stable path of its own.
+fn changed_fields(old: &AppConfig, new: &AppConfig) -> Vec<String> {
+    let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    diff_values("", &old, &new, &mut out);
+    out.sort();
+    out
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, out: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys = old
+                .keys()
+                .chain(new.keys().filter(|key| !old.contains_key(*key)));
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_values(
+                    &child,
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        _ if old != new => out.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::config::BuiltinUser;
+
+    #[test]
+    fn restart_settings_are_reported_and_kept_at_their_running_values() {
+        let running = AppConfig::default();
+        let mut next = AppConfig::default();
+        next.server.bind_port = 9100;
+        next.server.tls.reload_secs = 5;
+        next.ui.bind_port = 9101;
+        next.ui.public_scheme = crate::server::config::PublicScheme::Https;
+        next.logging.level = "debug".into();
+        next.sweeper.interval_secs = 17;
+        next.auth.users.push(BuiltinUser {
+            user: "ops".into(),
+            password: Some("secret".into()),
+            api_keys: Vec::new(),
+            totp_secret: None,
+        });
+
+        let live = LiveConfig::new(Arc::new(running));
+        let report = live.install("config.yaml".into(), next);
+        assert_eq!(
+            report.changed,
+            [
+                "auth.users",
+                "logging.level",
+                "server.bind_port",
+                "server.tls.reload_secs",
+                "sweeper.interval_secs",
+                "ui.bind_port",
+                "ui.public_scheme",
+            ]
+        );
+        assert_eq!(
+            report.restart_required,
+            ["server.bind_port", "server.tls.reload_secs", "ui.bind_port"]
+        );
+        assert!(!report.summary().contains("secret"));
+
+        let current = live.current();
+        assert_eq!(current.server.bind_port, 8002);
+        assert_eq!(current.ui.bind_port, 8003);
+        assert_eq!(current.sweeper.interval_secs, 17);
+        assert_eq!(current.logging.level, "debug");
+        assert_eq!(current.auth.users.len(), 1);
+    }
+
+    #[test]
+    fn kept_settings_cover_everything_marked_restart_required() {
+        // Changes every restart-only setting; once the running values are
+        // carried over, nothing restart-only may remain different — otherwise
+        // the report would promise a restart for a change already live.
+        let running = AppConfig::default();
+        let mut next = AppConfig::default();
+        next.server.bind_address = "127.0.0.2".into();
+        next.server.base_dir = "/elsewhere".into();
+        next.server.tls.enabled = true;
+        next.storage.meta_cache_capacity += 1;
+        next.ui.enabled = !running.ui.enabled;
+        next.ui.bind_address = Some("127.0.0.3".into());
+        next.ui.bind_port += 1;
+        next.ui.tls.enabled = true;
+        next.logging.enable_bandwidth_report = !running.logging.enable_bandwidth_report;
+        next.stats.enabled = !running.stats.enabled;
+        next.audit.enabled = !running.audit.enabled;
+        assert_eq!(
+            changed_fields(&running, &next).len(),
+            11,
+            "each setting above should register as one change"
+        );
+
+        keep_restart_settings(&running, &mut next);
+        let leftover: Vec<String> = changed_fields(&running, &next)
+            .into_iter()
+            .filter(|path| requires_restart(path))
+            .collect();
+        assert!(leftover.is_empty(), "not carried over: {leftover:?}");
+    }
+
+    #[test]
+    fn reload_rereads_the_file_and_keeps_the_old_config_when_it_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "sweeper:\n  interval_secs: 60\n").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let live =
+            LiveConfig::from_source(Arc::new(AppConfig::from_file(&path).unwrap()), path.clone());
+
+        std::fs::write(&path, "sweeper:\n  interval_secs: 30\n").unwrap();
+        let report = live.reload().unwrap();
+        assert_eq!(report.changed, ["sweeper.interval_secs"]);
+        assert!(report.restart_required.is_empty());
+        assert_eq!(live.current().sweeper.interval_secs, 30);
+
+        // Fails validation (trash retention below the minimum).
+        std::fs::write(
+            &path,
+            "sweeper:\n  interval_secs: 10\n  trash_expiry_secs: 1\n",
+        )
+        .unwrap();
+        assert!(live.reload().is_err());
+        assert_eq!(live.current().sweeper.interval_secs, 30);
+
+        let unsourced = LiveConfig::new(Arc::new(AppConfig::default()));
+        assert!(unsourced.reload().is_err());
+    }
+}
@@ -137,6 +137,16 @@ impl ScanReport {
         self.buckets.iter().map(|b| b.total_bytes()).sum()
     }
 
+    /// Logical and on-disk bytes of the live parts stored compressed.
+    pub fn compressed_bytes(&self) -> (u64, u64) {
+        self.buckets.iter().fold((0, 0), |(logical, stored), b| {
+            (
+                logical + b.compressed_logical_bytes,
+                stored + b.compressed_stored_bytes,
+            )
+        })
+    }
+
     pub fn objects(&self) -> u64 {
         self.buckets.iter().map(|b| b.objects_indexed).sum()
     }
@@ -0,0 +1,117 @@
+//! Whole-server snapshots: where they live, how a running server takes one,
+//! and the offline paths `rusts3 snapshot` and `rusts3 restore` use when no
+//! server owns the data directory. The snapshot itself — one consistent cut
+//! over every bucket index and `admin.rocksdb`, blobs hardlinked — is
+//! `storage::snapshot`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::config::AppConfig;
+use super::iam::IamStore;
+use super::registry::{TaskKind, TaskRegistry};
+use crate::storage::errors::Result;
+use crate::storage::snapshot::{
+    prune_snapshots, restore_snapshot, CheckpointSource, SnapshotManifest,
+};
+use crate::storage::store::LocalObjectStore;
+
+pub(crate) const OP: &str = "snapshot";
+
+/// `snapshots.dir`, or `<base_dir>/snapshots`.
+pub fn snapshots_dir(config: &AppConfig) -> PathBuf {
+    match config.snapshots.dir.as_deref().filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(&config.server.base_dir).join("snapshots"),
+    }
+}
+
+/// Takes a snapshot on the running server as a registry task, then prunes to
+/// `snapshots.retain`. Returns when the snapshot is complete.
+pub(crate) async fn take(
+    store: &LocalObjectStore,
+    iam: &IamStore,
+    config: &AppConfig,
+    tasks: &Arc<TaskRegistry>,
+) -> Result<SnapshotManifest> {
+    let run_id = super::new_request_id();
+    let dir = snapshots_dir(config);
+    // Abandoning a snapshot half-way only wastes the work; there is nothing to
+    // gain from cancelling one, and the commit freeze must not be interrupted.
+    let guard = tasks.register_uncancellable(&run_id, TaskKind::Job, OP, dir.display().to_string());
+    let progress = guard.progress();
+    let linked = |n: u64| {
+        progress.add_done(n);
+        progress.set_note(format!("{} objects linked", progress.read().0));
+    };
+    log::info!("[{run_id}] {OP} started dir={}", dir.display());
+    let extras: Vec<Arc<dyn CheckpointSource>> = vec![Arc::new(iam.clone())];
+    let manifest = match store.snapshot(&dir, &extras, &linked).await {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            log::warn!("[{run_id}] {OP} failed error={err}");
+            return Err(err);
+        }
+    };
+    log::info!(
+        "[{run_id}] {OP} completed id={} objects={} bytes={}",
+        manifest.id,
+        manifest.objects(),
+        manifest.bytes()
+    );
+    prune(&dir, config.snapshots.retain).await;
+    Ok(manifest)
+}
+
+async fn prune(dir: &Path, retain: usize) {
+    if retain == 0 {
+        return;
+    }
+    match prune_snapshots(dir, retain).await {
+        Ok(removed) if removed.is_empty() => {}
+        Ok(removed) => log::info!("{OP} pruned {}", removed.join(", ")),
+        Err(err) => log::warn!("{OP} prune failed error={err}"),
+    }
+}
+
+/// Takes a snapshot of a data directory no server is running on. Fails if one
+/// is — ask that server instead (the console's snapshot action).
+pub async fn take_offline(
+    config: &AppConfig,
+) -> std::result::Result<SnapshotManifest, Box<dyn std::error::Error>> {
+    let root = Path::new(&config.server.base_dir);
+    let _process_lock = super::acquire_process_lock(root)?;
+    let store = LocalObjectStore::from_storage_config(root, &config.storage);
+    let iam = IamStore::open(root).await?;
+    let dir = snapshots_dir(config);
+    let extras: Vec<Arc<dyn CheckpointSource>> = vec![Arc::new(iam)];
+    let manifest = store.snapshot(&dir, &extras, &|_| {}).await?;
+    prune(&dir, config.snapshots.retain).await;
+    Ok(manifest)
+}
+
+/// Rebuilds the configured data directory from the snapshot at `from`. The
+/// directory must be empty of buckets and IAM data, and no server may be
+/// running on it.
+pub async fn restore(
+    config: &AppConfig,
+    from: &Path,
+) -> std::result::Result<SnapshotManifest, Box<dyn std::error::Error>> {
+    let root = Path::new(&config.server.base_dir);
+    let _process_lock = super::acquire_process_lock(root)?;
+    Ok(restore_snapshot(from, root).await?)
+}
+
+/// True when a server (or another command) holds the data directory's
+/// process lock. Only probes: creates nothing and keeps no lock.
+pub fn data_dir_in_use(config: &AppConfig) -> bool {
+    use std::os::unix::io::AsRawFd;
+    let lock_path = Path::new(&config.server.base_dir).join(".rusts3.lock");
+    let Ok(file) = std::fs::File::open(lock_path) else {
+        return false;
+    };
+    let fd = file.as_raw_fd();
+    // SAFETY: `fd` belongs to `file`, open for the call; dropping `file`
+    // releases any lock the probe took.
+    unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) != 0 }
+}
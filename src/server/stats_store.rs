@@ -12,6 +12,7 @@
 //! chronological: a time range is one seek, retention is one ranged delete, and
 //! the UI's downsample is `buckets` cheap seeks rather than a full scan.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -20,7 +21,7 @@ use rocksdb::{
     WriteBatch, WriteOptions,
 };
 
-use super::sysstat::Sample;
+use super::sysstat::{RootSpace, Sample};
 use crate::storage::errors::{Result, StorageError};
 
 type Db = DBWithThreadMode<MultiThreaded>;
@@ -121,6 +122,8 @@ impl StatsStore {
             let span = (end_ms - start_ms).max(1) as i128;
             let mut sums = vec![[0f64; Sample::COLS]; buckets];
             let mut counts = vec![[0u32; Sample::COLS]; buckets];
+            // Per root: (used sum, total sum, count).
+            let mut roots = vec![BTreeMap::<String, (f64, f64, u32)>::new(); buckets];
 
             let start_key = key_for(start_ms);
             for item in
@@ -143,6 +146,12 @@ impl StatsStore {
                             counts[bi][j] += 1;
                         }
                     }
+                    for (path, space) in sample.roots {
+                        let acc = roots[bi].entry(path).or_default();
+                        acc.0 += space.used;
+                        acc.1 += space.total;
+                        acc.2 += 1;
+                    }
                 }
             }
 
@@ -157,7 +166,15 @@ impl StatsStore {
                             cols[j] = Some(sums[bi][j] / counts[bi][j] as f64);
                         }
                     }
-                    Some(Sample::from_cols(cols))
+                    let mut sample = Sample::from_cols(cols);
+                    sample.roots = std::mem::take(&mut roots[bi])
+                        .into_iter()
+                        .map(|(path, (used, total, n))| {
+                            let n = n as f64;
+                            (path, RootSpace { used: used / n, total: total / n })
+                        })
+                        .collect();
+                    Some(sample)
                 })
                 .collect();
             Ok(out)
@@ -285,6 +302,7 @@ mod tests {
             net_in: Some(10.0),
             net_out: Some(11.0),
             qps: Some(12.0),
+            roots: [("/data".to_string(), RootSpace { used: 13.0, total: 14.0 })].into(),
         };
         store.put(1_700_000_000_000, &s).await.unwrap();
         let series = store
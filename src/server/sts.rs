@@ -0,0 +1,306 @@
+//! The STS endpoint: `AssumeRoleWithLDAPIdentity` trades an LDAP username and
+//! password for temporary S3 credentials.
+//!
+//! The request is the one MinIO accepts — an unsigned form `POST /` on the S3
+//! port carrying `Action`, `Version`, `LDAPUsername`, `LDAPPassword` and an
+//! optional `DurationSeconds` — so existing LDAP credential providers work
+//! unchanged. The credentials act as the IAM user of the same name, which is
+//! provisioned and kept in its mapped directory groups just as a console login
+//! through LDAP would. Requests signed with them must also present the session
+//! token (`x-amz-security-token`), and deleting the user revokes them.
+
+use axum::body::Body;
+use axum::extract::RawQuery;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use chrono::{DateTime, SecondsFormat};
+
+use super::auth::AuthState;
+use super::ldap;
+use super::logging::TARGET_AUTH;
+use super::xml::escape_xml;
+use super::{OperationActor, RequestId};
+
+pub const ACTION: &str = "AssumeRoleWithLDAPIdentity";
+const NAMESPACE: &str = "https://sts.amazonaws.com/doc/2011-06-15/";
+/// The shortest lifetime AWS allows for temporary credentials.
+pub const MIN_DURATION_SECS: u64 = 900;
+const DEFAULT_DURATION_SECS: u64 = 3600;
+const MAX_FORM_BYTES: usize = 64 * 1024;
+
+/// True for a request the auth layer lets through unauthenticated because
+/// this endpoint authenticates it: an unsigned `POST /`, which is no S3
+/// operation.
+pub(crate) fn is_sts_request(request: &Request<Body>) -> bool {
+    request.method() == Method::POST
+        && request.uri().path() == "/"
+        && !request.headers().contains_key(header::AUTHORIZATION)
+        && !request.uri().query().unwrap_or("").contains("Signature=")
+}
+
+pub(crate) async fn handle(
+    request_id: Option<Extension<RequestId>>,
+    auth_state: Option<Extension<AuthState>>,
+    RawQuery(raw_query): RawQuery,
+    body: Body,
+) -> Response {
+    let rid = request_id.map(|Extension(id)| id.0).unwrap_or_default();
+    // The auth layer only hands its state over for the unsigned requests it
+    // defers here; anything else reaching `POST /` is not an STS call.
+    let Some(Extension(state)) = auth_state else {
+        return sts_error(
+            &rid,
+            StatusCode::BAD_REQUEST,
+            "InvalidAction",
+            "STS requests are unsigned and need auth.enabled",
+        );
+    };
+    let Ok(body) = axum::body::to_bytes(body, MAX_FORM_BYTES).await else {
+        return sts_error(
+            &rid,
+            StatusCode::BAD_REQUEST,
+            "InvalidRequest",
+            "request body too large",
+        );
+    };
+    let mut params = parse_form(raw_query.as_deref().unwrap_or(""));
+    params.extend(parse_form(&String::from_utf8_lossy(&body)));
+    let param = |name: &str| {
+        params
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if param("Action") != Some(ACTION) {
+        let message = format!("the only supported action is {ACTION}");
+        return sts_error(&rid, StatusCode::BAD_REQUEST, "InvalidAction", &message);
+    }
+    let config = state.config.current();
+    let ldap_config = &config.ldap;
+    let Some(iam) = state.iam.as_ref().filter(|_| ldap_config.enabled) else {
+        let message = "LDAP is not configured on this server";
+        return sts_error(&rid, StatusCode::BAD_REQUEST, "InvalidAction", message);
+    };
+    let (Some(username), Some(password)) = (param("LDAPUsername"), param("LDAPPassword")) else {
+        let message = "LDAPUsername and LDAPPassword are required";
+        return sts_error(&rid, StatusCode::BAD_REQUEST, "MissingParameter", message);
+    };
+    let max = ldap_config.sts_max_duration_secs;
+    let duration = match param("DurationSeconds") {
+        None => DEFAULT_DURATION_SECS.min(max),
+        Some(value) => match value.parse::<u64>() {
+            Ok(secs) if (MIN_DURATION_SECS..=max).contains(&secs) => secs,
+            _ => {
+                let message =
+                    format!("DurationSeconds must be between {MIN_DURATION_SECS} and {max}");
+                return sts_error(
+                    &rid,
+                    StatusCode::BAD_REQUEST,
+                    "InvalidParameterValue",
+                    &message,
+                );
+            }
+        },
+    };
+
+    let denied = |reason: &str| {
+        log::warn!(
+            target: TARGET_AUTH,
+            "[{rid}] sts login failed user={username} kind=ldap: {reason}"
+        );
+        sts_error(
+            &rid,
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "invalid LDAP credentials",
+        )
+    };
+    if config.find_builtin_user(username).is_some() {
+        return denied("built-in users sign with their own keys");
+    }
+    let user = match ldap::authenticate(ldap_config, username, password).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return denied("wrong username or password"),
+        Err(err) => {
+            log::warn!(target: TARGET_AUTH, "[{rid}] sts login user={username} kind=ldap: {err}");
+            let message = "the LDAP directory is unavailable; try again later";
+            return sts_error(
+                &rid,
+                StatusCode::SERVICE_UNAVAILABLE,
+                "ServiceUnavailable",
+                message,
+            );
+        }
+    };
+    if !ldap_config.auto_provision && !iam.user_exists(username) {
+        return denied("no IAM user of that name and auto_provision is off");
+    }
+    let groups = user.iam_groups(ldap_config);
+    let issued = async {
+        let sync = iam
+            .sync_external_user(username, true, groups.as_deref())
+            .await?;
+        for group in &sync.missing_groups {
+            log::warn!(target: TARGET_AUTH, "ldap group_map names missing IAM group {group}");
+        }
+        if sync.created || sync.groups.is_some() {
+            log::info!(
+                target: TARGET_AUTH,
+                "[{rid}] sts synced user={username} created={} groups={:?}",
+                sync.created,
+                sync.groups,
+            );
+        }
+        iam.issue_temporary_credentials(username, duration as i64 * 1000)
+            .await
+    };
+    let credentials = match issued.await {
+        Ok(credentials) => credentials,
+        Err(err) => {
+            log::error!(target: TARGET_AUTH, "[{rid}] sts login user={username} kind=ldap: {err}");
+            let message = "could not issue credentials";
+            return sts_error(
+                &rid,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                message,
+            );
+        }
+    };
+    log::info!(
+        target: TARGET_AUTH,
+        "[{rid}] sts login user={username} kind=ldap key={} duration={duration}s",
+        credentials.access_key,
+    );
+
+    let expiration = DateTime::from_timestamp_millis(credentials.expires_at_ms)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><AssumeRoleWithLDAPIdentityResponse xmlns="{NAMESPACE}"><AssumeRoleWithLDAPIdentityResult><Credentials><AccessKeyId>{}</AccessKeyId><SecretAccessKey>{}</SecretAccessKey><SessionToken>{}</SessionToken><Expiration>{expiration}</Expiration></Credentials></AssumeRoleWithLDAPIdentityResult><ResponseMetadata><RequestId>{}</RequestId></ResponseMetadata></AssumeRoleWithLDAPIdentityResponse>"#,
+        credentials.access_key,
+        credentials.secret_key,
+        credentials.session_token,
+        escape_xml(&rid),
+    );
+    let mut response = ([(header::CONTENT_TYPE, "application/xml")], xml).into_response();
+    response.extensions_mut().insert(OperationActor {
+        username: Some(username.to_string()),
+        access_key: Some(credentials.access_key),
+    });
+    response
+}
+
+/// `application/x-www-form-urlencoded` pairs, in order.
+pub(crate) fn parse_form(text: &str) -> Vec<(String, String)> {
+    let decode = |part: &str| {
+        let part = part.replace('+', " ");
+        urlencoding::decode(&part)
+            .map(|s| s.into_owned())
+            .unwrap_or(part)
+    };
+    text.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn sts_error(request_id: &str, status: StatusCode, code: &str, message: &str) -> Response {
+    let kind = if status.is_server_error() {
+        "Receiver"
+    } else {
+        "Sender"
+    };
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><ErrorResponse xmlns="{NAMESPACE}"><Error><Type>{kind}</Type><Code>{code}</Code><Message>{}</Message></Error><RequestId>{}</RequestId></ErrorResponse>"#,
+        escape_xml(message),
+        escape_xml(request_id),
+    );
+    (status, [(header::CONTENT_TYPE, "application/xml")], xml).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::server::config::AppConfig;
+    use crate::server::iam::IamStore;
+    use crate::server::ldap::tests::{config_for, MockDirectory};
+    use crate::server::reload::LiveConfig;
+
+    fn tag<'a>(xml: &'a str, name: &str) -> &'a str {
+        let start = xml.find(&format!("<{name}>")).unwrap() + name.len() + 2;
+        let end = xml.find(&format!("</{name}>")).unwrap();
+        &xml[start..end]
+    }
+
+    async fn call(state: &AuthState, form: &str) -> (StatusCode, String) {
+        let response = handle(
+            Some(Extension(RequestId("req-1".into()))),
+            Some(Extension(state.clone())),
+            RawQuery(None),
+            Body::from(form.to_string()),
+        )
+        .await;
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn form_values_decode_plus_as_space() {
+        assert_eq!(
+            parse_form("Action=X&LDAPPassword=a+b%2Bc%26&flag"),
+            vec![
+                ("Action".to_string(), "X".to_string()),
+                ("LDAPPassword".to_string(), "a b+c&".to_string()),
+                ("flag".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn ldap_users_get_temporary_credentials_for_their_password() {
+        let addr = MockDirectory::example().serve().await;
+        let tmp = tempfile::tempdir().unwrap();
+        let iam = IamStore::open(tmp.path()).await.unwrap();
+        let config = AppConfig {
+            ldap: config_for(addr),
+            ..AppConfig::default()
+        };
+        let state = AuthState {
+            config: LiveConfig::new(Arc::new(config)),
+            iam: Some(iam.clone()),
+        };
+        let form = |password: &str, extra: &str| {
+            let action = format!("Action={ACTION}&Version=2011-06-15");
+            format!("{action}&LDAPUsername=alice&LDAPPassword={password}{extra}")
+        };
+
+        let (status, body) = call(&state, &form("nope", "")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(tag(&body, "Code"), "AccessDenied");
+        let (status, body) = call(&state, &form("alicepw", "&DurationSeconds=60")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(tag(&body, "Code"), "InvalidParameterValue");
+
+        let (status, body) = call(&state, &form("alicepw", "&DurationSeconds=900")).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let access_key = tag(&body, "AccessKeyId");
+        let (secret, owner, token) = iam.find_temporary_key(access_key).unwrap();
+        assert_eq!(secret, tag(&body, "SecretAccessKey"));
+        assert_eq!(token, tag(&body, "SessionToken"));
+        assert_eq!(owner, "alice");
+        // Provisioned on first use, in the IAM group her directory group maps to.
+        assert_eq!(iam.groups_for("alice"), vec!["admin".to_string()]);
+    }
+}
@@ -16,7 +16,13 @@
 //! gaps — the sampler never panics. **Container caveat:** `/proc/stat` and
 //! `/proc/diskstats` are host-wide, so `cpu_sys*` and `disk_sys*` reflect the
 //! whole host; the `*_proc*` series (from `/proc/self/*`) are exact.
+//!
+//! Each sample also records the capacity of every data root (a `statvfs` per
+//! root, see [`disk_space`](crate::storage::layout::disk_space)), kept per
+//! root rather than as columns since the set of roots is configuration.
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
@@ -26,9 +32,10 @@ use serde::{Deserialize, Serialize};
 use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
 
-use super::config::StatsConfig;
+use super::reload::LiveConfig;
 use super::stats_store::StatsStore;
 use super::TrafficMetrics;
+use crate::storage::layout::disk_space;
 use crate::storage::time::now_ms;
 
 /// One sampled instant. Every metric is `Option` so an unavailable source
@@ -58,6 +65,17 @@ pub struct Sample {
     pub net_out: Option<f64>,
     /// S3 requests per second across all methods.
     pub qps: Option<f64>,
+    /// Space on each data root, keyed by its path. Absent in samples taken
+    /// before roots were recorded.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub roots: BTreeMap<String, RootSpace>,
+}
+
+/// Used and total bytes of one data root's filesystem.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RootSpace {
+    pub used: f64,
+    pub total: f64,
 }
 
 impl Sample {
@@ -79,7 +97,7 @@ impl Sample {
         Self {
             cpu_sys: c[0], cpu_proc: c[1], mem_used: c[2], mem_total: c[3], mem_proc_rss: c[4],
             disk_proc_r: c[5], disk_proc_w: c[6], disk_sys_r: c[7], disk_sys_w: c[8],
-            net_in: c[9], net_out: c[10], qps: c[11],
+            net_in: c[9], net_out: c[10], qps: c[11], roots: BTreeMap::new(),
         }
     }
 }
@@ -291,38 +309,43 @@ fn diff(prev: &Raw, cur: &Raw) -> Sample {
         net_in: Some(cur.net_in.saturating_sub(prev.net_in) as f64 / dt_s),
         net_out: Some(cur.net_out.saturating_sub(prev.net_out) as f64 / dt_s),
         qps: Some(cur.reqs.saturating_sub(prev.reqs) as f64 / dt_s),
+        roots: BTreeMap::new(),
     }
 }
 
+/// Current capacity of every data root. A root that cannot be measured is
+/// left out of the sample rather than reported as empty.
+fn read_roots(roots: &[PathBuf]) -> BTreeMap<String, RootSpace> {
+    roots
+        .iter()
+        .filter_map(|root| {
+            let space = disk_space(root)?;
+            let used = space.total.saturating_sub(space.available) as f64;
+            Some((root.display().to_string(), RootSpace { used, total: space.total as f64 }))
+        })
+        .collect()
+}
+
 /// Spawns the sampler task: every `sample_secs` it snapshots the raw counters,
 /// stores the diff against the previous snapshot, and on a slow (~hourly)
-/// cadence prunes samples older than the retention window. Stops on `shutdown`.
+/// cadence prunes samples older than the retention window. Both settings are
+/// read from the live config, so a reload changes them in place. Stops on
+/// `shutdown`.
 pub(crate) fn spawn_sampler(
     store: StatsStore,
     metrics: Arc<TrafficMetrics>,
-    cfg: StatsConfig,
+    config: LiveConfig,
+    roots: Vec<PathBuf>,
     shutdown: CancellationToken,
 ) {
-    let period = cfg.sample_secs.max(1);
-    let retention_ms = (cfg.retention_days.max(1)).saturating_mul(86_400).saturating_mul(1000) as i64;
-    // Prune roughly hourly, but at least once every tick if the period is huge.
-    let prune_every = (3600 / period).max(1);
-
     tokio::spawn(async move {
+        let initial = config.current().stats.clone();
+        let mut period = initial.sample_secs.max(1);
         log::info!(
             "runtime stats sampler started sample_secs={period} retention_days={}",
-            cfg.retention_days
+            initial.retention_days
         );
-        // Align the first tick to the next wall-clock multiple of the period, so
-        // samples land on stable boundaries (:00/:05/:10…) across restarts.
-        let period_ms = (period * 1000) as i64;
-        let delay_ms = (period_ms - now_ms().rem_euclid(period_ms)) as u64;
-        let start = tokio::time::Instant::now() + Duration::from_millis(delay_ms);
-        let mut interval = tokio::time::interval_at(start, Duration::from_secs(period));
-        // Fixed-rate: keep ticks on the original aligned grid, skipping any the
-        // task was too busy to service — never bunching (Burst) or drifting the
-        // phase (Delay).
-        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut interval = aligned_interval(period);
 
         // The first tick establishes the baseline counters; every tick after it
         // stores the diff against the previous one.
@@ -335,27 +358,55 @@ pub(crate) fn spawn_sampler(
                     break;
                 }
                 _ = interval.tick() => {
+                    let cfg = config.current().stats.clone();
                     let cur = read_raw(&metrics);
                     if let Some(prev) = &prev {
-                        let sample = diff(prev, &cur);
+                        let mut sample = diff(prev, &cur);
+                        sample.roots = read_roots(&roots);
                         if let Err(err) = store.put(cur.at_ms, &sample).await {
                             log::warn!("runtime stats: failed to store sample: {err}");
                         }
                         samples += 1;
-                        if samples % prune_every == 0 {
-                            let before = now_ms() - retention_ms;
-                            if let Err(err) = store.prune(before).await {
+                        // Prune roughly hourly, but at least once every tick if
+                        // the period is huge.
+                        if samples % (3600 / period).max(1) == 0 {
+                            let retention_ms = (cfg.retention_days.max(1))
+                                .saturating_mul(86_400)
+                                .saturating_mul(1000) as i64;
+                            if let Err(err) = store.prune(now_ms() - retention_ms).await {
                                 log::warn!("runtime stats: retention prune failed: {err}");
                             }
                         }
                     }
                     prev = Some(cur);
+                    // A reloaded period moves the sampler onto the new grid; the
+                    // next sample's rates still cover the real elapsed time.
+                    if cfg.sample_secs.max(1) != period {
+                        period = cfg.sample_secs.max(1);
+                        log::info!("runtime stats sampler now sampling every {period}s");
+                        interval = aligned_interval(period);
+                    }
                 }
             }
         }
     });
 }
 
+/// A tick every `period` seconds, aligned to the next wall-clock multiple of
+/// the period, so samples land on stable boundaries (:00/:05/:10…) across
+/// restarts.
+fn aligned_interval(period: u64) -> tokio::time::Interval {
+    let period_ms = (period * 1000) as i64;
+    let delay_ms = (period_ms - now_ms().rem_euclid(period_ms)) as u64;
+    let start = tokio::time::Instant::now() + Duration::from_millis(delay_ms);
+    let mut interval = tokio::time::interval_at(start, Duration::from_secs(period));
+    // Fixed-rate: keep ticks on the original aligned grid, skipping any the
+    // task was too busy to service — never bunching (Burst) or drifting the
+    // phase (Delay).
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
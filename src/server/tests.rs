@@ -97,6 +97,15 @@
             operation_and_target(&Method::DELETE, &bucket, false).0,
             "DELETE_BUCKET"
         );
+        let logging: axum::http::Uri = "/bucket?logging".parse().unwrap();
+        assert_eq!(
+            operation_and_target(&Method::PUT, &logging, false).0,
+            "PUT_BUCKET_LOGGING"
+        );
+        assert_eq!(
+            operation_and_target(&Method::GET, &logging, false).0,
+            "GET_BUCKET_LOGGING"
+        );
     }
 
     #[test]
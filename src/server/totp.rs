@@ -0,0 +1,309 @@
+//! Time-based one-time passwords (RFC 6238) for console two-factor login.
+//!
+//! Codes are the authenticator-app default: HMAC-SHA1, six digits, a 30-second
+//! step. A code is accepted for the step before and after the current one to
+//! absorb clock drift, and never for a step at or before the last one that
+//! succeeded, so a code read over someone's shoulder cannot be replayed.
+//!
+//! Secrets are shown to people and carried in configuration as unpadded
+//! RFC 4648 base32, which is what authenticator apps expect. Recovery codes
+//! are random, shown once, and only their SHA-256 is kept.
+//!
+//! [`PendingLogins`] holds the gap between the two login steps: a password
+//! that checked out earns a short-lived ticket, and only the ticket plus a
+//! valid code earns a session.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::storage::time::now_ms;
+
+const STEP_SECS: u64 = 30;
+const DIGITS: u32 = 6;
+/// Bytes of a generated secret: 160 bits, the RFC 4226 recommendation.
+pub const SECRET_BYTES: usize = 20;
+/// Shortest secret accepted from configuration: 80 bits, the RFC 4226 floor.
+pub const MIN_SECRET_BYTES: usize = 10;
+pub const RECOVERY_CODES: usize = 10;
+
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut secret);
+    secret
+}
+
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = u64::from_be_bytes([0, 0, 0, buf[0], buf[1], buf[2], buf[3], buf[4]]);
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            out.push(BASE32[((bits >> (35 - i * 5)) & 31) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decodes base32, ignoring case, spaces, dashes and padding — the forms a
+/// secret takes when someone copies it by hand. `None` on any other character.
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut bits, mut held) = (0u32, 0u32);
+    for c in text.chars() {
+        if matches!(c, ' ' | '-' | '=') {
+            continue;
+        }
+        let value = BASE32.iter().position(|&b| b as char == c.to_ascii_uppercase())?;
+        bits = (bits << 5) | value as u32;
+        held += 5;
+        if held >= 8 {
+            held -= 8;
+            out.push((bits >> held) as u8);
+            bits &= (1 << held) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// The code for time step `step` (RFC 4226 dynamic truncation).
+pub(crate) fn code_at(secret: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC takes any key length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[19] & 0x0f) as usize;
+    let word = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    word % 10u32.pow(DIGITS)
+}
+
+/// Checks `code` against the steps around `now_secs`. Returns the matching
+/// step, which the caller records as `last_step` for the next check.
+pub fn verify(secret: &[u8], code: &str, now_secs: u64, last_step: u64) -> Option<u64> {
+    let code = code.trim().replace(' ', "");
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let current = now_secs / STEP_SECS;
+    [current.saturating_sub(1), current, current + 1]
+        .into_iter()
+        .filter(|&step| step > last_step)
+        .find(|&step| code_at(secret, step) == code)
+}
+
+pub fn now_secs() -> u64 {
+    (now_ms().max(0) / 1000) as u64
+}
+
+/// The `otpauth://` URI authenticator apps import, usually as a QR code.
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &[u8]) -> String {
+    let label = urlencoding::encode(&format!("{issuer}:{account}")).into_owned();
+    format!(
+        "otpauth://totp/{label}?secret={}&issuer={}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECS}",
+        base32_encode(secret),
+        urlencoding::encode(issuer),
+    )
+}
+
+/// `uri` as an SVG QR code, for the console to show inline.
+pub fn qr_svg(uri: &str) -> String {
+    match qrcode::QrCode::new(uri.as_bytes()) {
+        Ok(code) => code
+            .render::<qrcode::render::svg::Color>()
+            .min_dimensions(180, 180)
+            .quiet_zone(true)
+            .build(),
+        // Only a URI longer than any QR version holds fails; the console then
+        // falls back to the secret and the URI as text.
+        Err(_) => String::new(),
+    }
+}
+
+/// Fresh recovery codes, `xxxxx-xxxxx` in an alphabet without look-alikes.
+pub fn recovery_codes() -> Vec<String> {
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODES)
+        .map(|_| {
+            let mut code: String = (0..10)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect();
+            code.insert(5, '-');
+            code
+        })
+        .collect()
+}
+
+/// What is stored for a recovery code. Dashes, spaces and case do not count,
+/// so a code typed back in any of those forms still matches.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normal: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    Sha256::digest(normal.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+// ── the second login step ────────────────────────────────────────────────────
+
+/// How long a password-verified login waits for its code.
+const TICKET_TTL_MS: i64 = 5 * 60 * 1000;
+/// Wrong codes a ticket tolerates before the password must be entered again.
+const TICKET_ATTEMPTS: u32 = 5;
+
+/// A login that passed its password check and still owes a code.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub username: String,
+    pub is_builtin: bool,
+    /// Set when the user is enrolling as part of this login, because the
+    /// server requires two-factor and they had not set it up yet.
+    pub enrolling: bool,
+    expires_at_ms: i64,
+    attempts: u32,
+}
+
+/// Tickets for logins between their two steps, plus the last accepted step
+/// of built-in users, whose secrets live in configuration rather than in
+/// `admin.rocksdb`. Both are in memory: a restart just means signing in again.
+#[derive(Debug, Default)]
+pub struct PendingLogins {
+    tickets: Mutex<HashMap<String, PendingLogin>>,
+    builtin_steps: Mutex<HashMap<String, u64>>,
+}
+
+impl PendingLogins {
+    pub fn start(&self, username: &str, is_builtin: bool, enrolling: bool) -> String {
+        let mut bytes = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let ticket: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let now = now_ms();
+        let mut tickets = self.tickets.lock().unwrap();
+        tickets.retain(|_, pending| pending.expires_at_ms > now);
+        tickets.insert(
+            ticket.clone(),
+            PendingLogin {
+                username: username.to_string(),
+                is_builtin,
+                enrolling,
+                expires_at_ms: now + TICKET_TTL_MS,
+                attempts: 0,
+            },
+        );
+        ticket
+    }
+
+    /// The login behind `ticket`, counting one attempt against it. A ticket
+    /// that is expired or out of attempts is gone.
+    pub fn attempt(&self, ticket: &str) -> Option<PendingLogin> {
+        let mut tickets = self.tickets.lock().unwrap();
+        let pending = tickets.get_mut(ticket)?;
+        if pending.expires_at_ms <= now_ms() || pending.attempts >= TICKET_ATTEMPTS {
+            tickets.remove(ticket);
+            return None;
+        }
+        pending.attempts += 1;
+        Some(pending.clone())
+    }
+
+    /// Ends a ticket once it has been redeemed for a session.
+    pub fn finish(&self, ticket: &str) {
+        self.tickets.lock().unwrap().remove(ticket);
+    }
+
+    /// Verifies a built-in user's code against their configured secret.
+    pub fn verify_builtin(&self, username: &str, secret: &[u8], code: &str) -> bool {
+        let mut steps = self.builtin_steps.lock().unwrap();
+        let last = steps.get(username).copied().unwrap_or(0);
+        match verify(secret, code, now_secs(), last) {
+            Some(step) => {
+                steps.insert(username.to_string(), step);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 6238 appendix B, SHA-1 column, truncated to six digits.
+    #[test]
+    fn codes_match_the_rfc_test_vectors() {
+        let secret = b"12345678901234567890";
+        for (time, code) in [
+            (59, 287082),
+            (1111111109, 81804),
+            (1111111111, 50471),
+            (1234567890, 5924),
+            (2000000000, 279037),
+        ] {
+            assert_eq!(code_at(secret, time / STEP_SECS), code, "t={time}");
+        }
+        assert_eq!(verify(secret, "287 082", 59, 0), Some(1));
+        // Neighbouring steps are accepted; anything already used is not.
+        assert_eq!(verify(secret, "287082", 59 + STEP_SECS, 0), Some(1));
+        assert_eq!(verify(secret, "287082", 59, 1), None);
+        assert_eq!(verify(secret, "287082", 59 + 3 * STEP_SECS, 0), None);
+        assert_eq!(verify(secret, "28708", 59, 0), None);
+    }
+
+    #[test]
+    fn base32_round_trips_rfc_4648_vectors() {
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ] {
+            assert_eq!(base32_encode(plain.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base32_decode("mzxw 6ytb-oi======").unwrap(), b"foobar");
+        assert_eq!(base32_decode("MZXW1"), None);
+    }
+
+    #[test]
+    fn recovery_codes_match_however_they_are_typed() {
+        let codes = recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODES);
+        let hash = hash_recovery_code(&codes[0]);
+        assert_eq!(hash_recovery_code(&codes[0].to_uppercase().replace('-', " ")), hash);
+        assert_ne!(hash_recovery_code(&codes[1]), hash);
+    }
+
+    #[test]
+    fn tickets_run_out_of_attempts() {
+        let logins = PendingLogins::default();
+        let ticket = logins.start("alice", false, false);
+        for _ in 0..TICKET_ATTEMPTS {
+            assert_eq!(logins.attempt(&ticket).unwrap().username, "alice");
+        }
+        assert!(logins.attempt(&ticket).is_none());
+        let ticket = logins.start("alice", false, false);
+        logins.finish(&ticket);
+        assert!(logins.attempt(&ticket).is_none());
+    }
+}
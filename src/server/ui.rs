@@ -4,32 +4,39 @@
 //! access keys only. IAM users' policies are enforced on every UI object
 //! operation exactly as they are on the S3 API.
 
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::body::Body;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::extract::{DefaultBodyLimit, Extension, Path, Query, State};
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Extension, Path, Query, State};
 use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
 use axum::middleware::{self, Next};
-use axum::response::{Html, IntoResponse, Response};
+use axum::response::{AppendHeaders, Html, IntoResponse, Response};
 use axum::routing::{delete, get, post, put};
 use axum::{Json, Router};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use super::auth::presign_query;
+use super::auth::{presign_query, SHARE_ID_PARAM};
 use super::config::AppConfig;
+use super::reload::LiveConfig;
 use super::identity::Identity;
 use super::logging::{TARGET_AUDIT, TARGET_AUTH, TARGET_AUTHZ};
 use super::TrafficMetrics;
-use super::iam::{Group, IamStore};
+use super::iam::{
+    ApiTokenInfo, Group, IamStore, NewShare, SessionClient, SessionTimeouts, ShareLink,
+};
 use super::policy::{
-    compile_rules, decompile_rules, PolicyDocument, PolicyRule, Requirement,
+    compile_rules, decompile_rules, is_authorized, Effect, OneOrMany, PolicyDocument, PolicyRule,
+    Requirement, Statement, ADMIN_ACTIONS,
 };
 use crate::storage::errors::StorageError;
 use crate::storage::rawdb;
-use crate::storage::metadata::CorsRule;
-use crate::storage::store::{CompletePartRequest, LocalObjectStore};
+use crate::storage::metadata::{quote_etag, BucketQuota, CompressionCodec, CorsRule};
+use crate::storage::store::{CompletePartRequest, LocalObjectStore, Precondition};
+use crate::storage::time::http_date_ms;
 
 const SESSION_COOKIE: &str = "rusts3_ui_session";
 
@@ -42,22 +49,35 @@ const IAM_IMPORT_LIMIT_BYTES: usize = 128 * 1024 * 1024;
 pub struct UiState {
     pub store: LocalObjectStore,
     pub iam: IamStore,
-    pub config: Arc<AppConfig>,
+    pub config: LiveConfig,
     pub(crate) metrics: Arc<TrafficMetrics>,
     pub(crate) tasks: Arc<super::registry::TaskRegistry>,
     /// Storage health scans: their history, and the live-progress channel.
     pub(crate) scans: Arc<super::jobs::perf_scan::ScanService>,
     /// Runtime stats time-series. `None` when the feature is disabled by config.
     pub(crate) stats: Option<super::stats_store::StatsStore>,
+    /// Persisted audit trail. `None` when the feature is disabled by config.
+    pub(crate) audit: Option<super::audit_store::AuditStore>,
+    /// S3 API rate limiter, for its live bucket state in the task monitor.
+    pub(crate) limits: Arc<super::ratelimit::RateLimiter>,
+    /// Logins that passed the password check and await a two-factor code.
+    pub(crate) logins: Arc<super::totp::PendingLogins>,
+    /// OpenID Connect client: provider metadata and sign-ins in flight.
+    pub(crate) oidc: Arc<super::oidc::OidcClient>,
 }
 
 /// A resolved UI session. Managed admin membership is resolved on every
 /// request so promotions and demotions take effect immediately.
 #[derive(Debug, Clone)]
 struct UiSession {
+    /// Id of the session (not its token), as `/api/sessions` lists it. For an
+    /// admin API token, the token's id.
+    id: String,
     username: String,
     is_builtin: bool,
     is_admin: bool,
+    /// What an admin API token is limited to; `None` for a console sign-in.
+    scope: Option<PolicyDocument>,
 }
 
 pub fn router(state: UiState) -> Router {
@@ -67,8 +87,19 @@ pub fn router(state: UiState) -> Router {
         .route("/assets/:file", get(ui_asset))
         .route("/favicon.ico", get(favicon))
         .route("/api/login", post(login))
+        .route("/api/login/mfa", post(login_mfa))
+        .route("/api/login/methods", get(login_methods))
+        .route("/api/oidc/login", get(oidc_login))
+        .route("/api/oidc/callback", get(oidc_callback))
         .route("/api/logout", post(logout))
         .route("/api/me", get(me))
+        .route("/api/me/mfa", get(mfa_status).delete(disable_mfa))
+        .route("/api/me/mfa/enroll", post(begin_mfa_enrollment))
+        .route("/api/me/mfa/confirm", post(confirm_mfa))
+        .route("/api/me/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/api/me/password", put(change_own_password))
+        .route("/api/me/policy", get(my_policy))
+        .route("/api/me/activity", get(my_activity))
         .route("/api/ping", get(server_ping))
         .route("/api/users", get(list_users).post(create_user))
         .route("/api/users/:name", delete(delete_user))
@@ -77,19 +108,36 @@ pub fn router(state: UiState) -> Router {
         .route("/api/users/:name/policy/rules", put(set_user_policy_rules))
         .route("/api/users/:name/groups", get(list_user_groups).put(set_user_groups))
         .route("/api/users/:name/keys", get(list_keys).post(create_key))
+        .route("/api/users/:name/sessions", delete(revoke_user_sessions))
+        .route("/api/users/:name/mfa", delete(reset_user_mfa))
         .route("/api/groups", get(list_groups).post(create_group))
         .route("/api/groups/:name", delete(delete_group))
         .route("/api/groups/:name/policy", put(set_group_policy))
         .route("/api/groups/:name/policy/rules", put(set_group_policy_rules))
         .route("/api/policies/compile", post(compile_policy_rules))
         .route("/api/policies/decompile", post(decompile_policy_rules))
-        .route("/api/keys/:ak", delete(delete_key))
+        .route("/api/keys/:ak", put(set_key_status).delete(delete_key))
+        .route("/api/keys/:ak/rotate", post(rotate_key))
+        // Console sessions (admin only): who is logged in, and revocation.
+        .route("/api/sessions", get(list_sessions))
+        .route("/api/sessions/:id", delete(revoke_session))
+        .route("/api/tokens", get(list_api_tokens).post(create_api_token))
+        .route("/api/tokens/:id", delete(revoke_api_token))
         .route("/api/buckets", get(list_buckets).post(create_bucket))
         .route("/api/buckets/:name", delete(delete_bucket))
         .route("/api/buckets/:name/stats", get(bucket_stats))
         .route("/api/buckets/:name/rebuild", post(rebuild_bucket))
         .route("/api/buckets/:name/cors", get(get_bucket_cors).put(set_bucket_cors))
+        .route("/api/buckets/:name/quota", put(set_bucket_quota))
+        .route("/api/buckets/:name/compression", put(set_bucket_compression))
+        .route("/api/buckets/:name/dedup", put(set_bucket_dedup))
+        .route("/api/buckets/:name/move", post(move_bucket))
+        .route("/api/roots", get(list_roots))
         .route("/api/admin/export", get(export_iam))
+        // Whole-server snapshots (admin only). Creation waits for the snapshot
+        // to finish; it is also a registry task, visible in the task monitor.
+        .route("/api/snapshots", get(list_snapshots).post(create_snapshot))
+        .route("/api/snapshots/:id", delete(delete_snapshot))
         // Import buffers and fully validates the dump before an atomic apply, so
         // the body is held in memory. The global IAM database is small by nature
         // (users/keys/policies, not object data), but we never *assume* that: a
@@ -107,6 +155,10 @@ pub fn router(state: UiState) -> Router {
             get(download_object)
                 .delete(delete_object),
         )
+        .route(
+            "/api/object/meta",
+            get(object_metadata).put(replace_object_metadata),
+        )
         .route("/api/folders", post(create_folder))
         // Upload control plane for the console. File bytes never cross the UI
         // listener: the browser PUTs them to short-lived, RSWEB-signed standard
@@ -120,6 +172,9 @@ pub fn router(state: UiState) -> Router {
         .route("/api/multipart/abort", delete(multipart_abort))
         .route("/api/objects", get(list_objects))
         .route("/api/presign", post(presign))
+        .route("/api/shares", get(list_shares))
+        .route("/api/shares/:id", delete(revoke_share))
+        .route("/api/shares/:id/access", get(share_accesses))
         .route("/api/tasks", get(list_tasks))
         .route("/api/tasks/ws", get(tasks_ws))
         .route("/api/tasks/:id/cancel", post(cancel_task))
@@ -140,6 +195,12 @@ pub fn router(state: UiState) -> Router {
         .route("/api/perf/scans/:id/repair", post(repair_findings))
         // Runtime stats (admin only): a single read-only, downsampled series.
         .route("/api/stats/series", get(stats_series))
+        // Audit trail (admin only): filtered view and export.
+        .route("/api/audit", get(audit_events))
+        .route("/api/audit/export", get(export_audit))
+        // Configuration reload (admin only): same effect as SIGHUP.
+        .route("/api/config", get(config_status))
+        .route("/api/config/reload", post(reload_config))
         .layer(DefaultBodyLimit::max(5 * 1024 * 1024 * 1024))
         .layer(middleware::from_fn_with_state(
             state.config.clone(),
@@ -157,17 +218,20 @@ pub fn router(state: UiState) -> Router {
 /// independent request-side check for modern browsers: cross-site unsafe
 /// requests are rejected, and an Origin header must match the configured
 /// public console origin when one is set. Requests without browser fetch
-/// metadata remain usable by non-browser administration clients.
+/// metadata remain usable by non-browser administration clients, and requests
+/// with an admin API token skip the check: a browser never attaches one on
+/// its own, and such a request is authorized by the token, not the cookie.
 async fn ui_csrf_middleware(
-    State(config): State<Arc<AppConfig>>,
+    State(config): State<LiveConfig>,
     request: Request<Body>,
     next: Next,
 ) -> Response {
+    let config = config.current();
     let unsafe_method = !matches!(
         *request.method(),
         axum::http::Method::GET | axum::http::Method::HEAD | axum::http::Method::OPTIONS
     );
-    if unsafe_method {
+    if unsafe_method && bearer_token(request.headers()).is_none() {
         let cross_site = request
             .headers()
             .get("sec-fetch-site")
@@ -249,16 +313,23 @@ async fn ui_asset(Path(file): Path<String>) -> Response {
         "core.js" => include_str!("assets/core.js"),
         "tasks.js" => include_str!("assets/tasks.js"),
         "object_management.js" => include_str!("assets/object_management.js"),
+        "object_preview.js" => include_str!("assets/object_preview.js"),
         "uploads.js" => include_str!("assets/uploads.js"),
         "users.js" => include_str!("assets/users.js"),
         "groups.js" => include_str!("assets/groups.js"),
         "policy.js" => include_str!("assets/policy.js"),
         "keys.js" => include_str!("assets/keys.js"),
+        "sessions.js" => include_str!("assets/sessions.js"),
+        "shares.js" => include_str!("assets/shares.js"),
+        "mfa.js" => include_str!("assets/mfa.js"),
+        "account.js" => include_str!("assets/account.js"),
         "export_import.js" => include_str!("assets/export_import.js"),
         "perf.js" => include_str!("assets/perf.js"),
         // Vendored charting library (single IIFE build) + the stats tab logic.
         "uPlot.iife.min.js" => include_str!("assets/uPlot.iife.min.js"),
         "stats.js" => include_str!("assets/stats.js"),
+        "audit.js" => include_str!("assets/audit.js"),
+        "config.js" => include_str!("assets/config.js"),
         "main.js" => include_str!("assets/main.js"),
         _ => return error_response(StatusCode::NOT_FOUND, "asset not found"),
     };
@@ -286,7 +357,7 @@ async fn stats_series(
     headers: HeaderMap,
     Query(query): Query<StatsQuery>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewStats") {
         return resp;
     }
     let Some(store) = state.stats.clone() else {
@@ -324,7 +395,7 @@ async fn stats_series(
     // finer than the sample interval just yields null gaps between lone points.
     // Cap the count at roughly one bucket per sample so a short window still
     // draws a continuous line.
-    let sample_ms = (state.config.stats.sample_secs.max(1) * 1000) as i64;
+    let sample_ms = (state.config.current().stats.sample_secs.max(1) * 1000) as i64;
     let points = (((end - start) / sample_ms) + 1).clamp(1, points as i64) as usize;
 
     let samples = match store.sample_series(start, end, points).await {
@@ -367,15 +438,35 @@ async fn stats_series(
         col!(net_out),
         col!(qps),
     ]);
+    // Data roots ride alongside the fixed columns, one used/total pair per
+    // root seen anywhere in the window.
+    let mut root_paths = std::collections::BTreeSet::new();
+    for sample in samples.iter().flatten() {
+        root_paths.extend(sample.roots.keys().cloned());
+    }
+    let roots: Vec<Value> = root_paths
+        .into_iter()
+        .map(|path| {
+            let space = |s: &Option<super::sysstat::Sample>| {
+                s.as_ref().and_then(|s| s.roots.get(&path).copied())
+            };
+            let used: Vec<Value> =
+                samples.iter().map(|s| json!(space(s).map(|r| r.used))).collect();
+            let total: Vec<Value> =
+                samples.iter().map(|s| json!(space(s).map(|r| r.total))).collect();
+            json!({ "path": path, "used": used, "total": total })
+        })
+        .collect();
     let mut response = Json(json!({
         "enabled": true,
         "range": range,
-        "sample_secs": state.config.stats.sample_secs,
+        "sample_secs": state.config.current().stats.sample_secs,
         "labels": [
             "time", "cpu_sys", "cpu_proc", "mem_used", "mem_total", "mem_proc_rss",
             "disk_proc_r", "disk_proc_w", "disk_sys_r", "disk_sys_w", "net_in", "net_out", "qps"
         ],
         "data": data,
+        "roots": roots,
     }))
     .into_response();
     response.headers_mut().insert(
@@ -385,6 +476,150 @@ async fn stats_series(
     response
 }
 
+// ── audit trail ──────────────────────────────────────────────────────────────
+
+/// Most events one console page shows; exports go further.
+const AUDIT_PAGE_LIMIT: usize = 500;
+const AUDIT_EXPORT_LIMIT: usize = 100_000;
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    actor: Option<String>,
+    action: Option<String>,
+    bucket: Option<String>,
+    prefix: Option<String>,
+    /// `allowed` or `denied`; anything else means both.
+    outcome: Option<String>,
+    /// Epoch milliseconds, inclusive.
+    from: Option<i64>,
+    /// Epoch milliseconds, exclusive.
+    to: Option<i64>,
+    limit: Option<usize>,
+    /// Export only: `csv` (default) or `json`.
+    format: Option<String>,
+}
+
+impl AuditQuery {
+    fn filter(&self) -> super::audit_store::AuditFilter {
+        let text = |v: &Option<String>| v.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+        super::audit_store::AuditFilter {
+            actor: text(&self.actor),
+            principal: None,
+            action: text(&self.action),
+            bucket: text(&self.bucket),
+            prefix: text(&self.prefix),
+            allowed: match self.outcome.as_deref() {
+                Some("allowed") => Some(true),
+                Some("denied") => Some(false),
+                _ => None,
+            },
+            from_ms: self.from,
+            to_ms: self.to,
+        }
+    }
+}
+
+/// Filtered audit events, newest first. **Admin only.** `truncated` tells the
+/// page that more matches exist than it was sent.
+async fn audit_events(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewAudit") {
+        return resp;
+    }
+    let Some(store) = state.audit.clone() else {
+        return Json(json!({ "enabled": false })).into_response();
+    };
+    let limit = query.limit.unwrap_or(200).clamp(1, AUDIT_PAGE_LIMIT);
+    match store.query(query.filter(), limit).await {
+        Ok((events, truncated)) => {
+            Json(json!({ "enabled": true, "events": events, "truncated": truncated })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Downloads the filtered audit events as CSV or JSON. **Admin only.** The
+/// export itself is audited, so the trail shows who took a copy.
+async fn export_audit(
+    State(state): State<UiState>,
+    Extension(rid): Extension<super::RequestId>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ViewAudit") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let Some(store) = state.audit.clone() else {
+        return error_response(StatusCode::NOT_FOUND, "audit trail is disabled");
+    };
+    let json_format = match query.format.as_deref().unwrap_or("csv") {
+        "csv" => false,
+        "json" => true,
+        _ => return error_response(StatusCode::BAD_REQUEST, "format must be csv or json"),
+    };
+    let events = match store.query(query.filter(), AUDIT_EXPORT_LIMIT).await {
+        Ok((events, _)) => events,
+        Err(err) => return storage_error(err),
+    };
+    audit(&state, &rid.0, &actor.username, "export_audit", format!("{} events", events.len()));
+    let (body, content_type, filename) = if json_format {
+        (
+            serde_json::to_string_pretty(&events).unwrap_or_else(|_| "[]".to_string()),
+            "application/json",
+            "attachment; filename=\"rusts3-audit.json\"",
+        )
+    } else {
+        (
+            super::audit_store::records_to_csv(&events),
+            "text/csv; charset=utf-8",
+            "attachment; filename=\"rusts3-audit.csv\"",
+        )
+    };
+    let mut response = (StatusCode::OK, body).into_response();
+    let out = response.headers_mut();
+    out.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    out.insert(header::CONTENT_DISPOSITION, HeaderValue::from_static(filename));
+    out.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+// ── configuration reload ─────────────────────────────────────────────────────
+
+/// Where the running configuration came from. **Admin only.** A server started
+/// without a file reports `null` and cannot reload.
+async fn config_status(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewConfig") {
+        return resp;
+    }
+    Json(json!({ "source": state.config.source() })).into_response()
+}
+
+/// Re-reads and applies the configuration file, exactly as SIGHUP does, and
+/// returns the [`ReloadReport`](super::reload::ReloadReport). **Admin only.**
+/// A file that fails to parse or validate changes nothing.
+async fn reload_config(
+    State(state): State<UiState>,
+    Extension(rid): Extension<super::RequestId>,
+    headers: HeaderMap,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ReloadConfig") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let trigger = format!("[{}] console reload by {}", rid.0, actor.username);
+    match state.config.reload_logged(&trigger).await {
+        Ok(report) => {
+            audit(&state, &rid.0, &actor.username, "reload_config", report.source.clone());
+            Json(report).into_response()
+        }
+        Err(err) => error_response(StatusCode::UNPROCESSABLE_ENTITY, err),
+    }
+}
+
 // ── request tracing ──────────────────────────────────────────────────────────
 
 /// Paths that are pure polling / health / static and would only flood the audit
@@ -397,6 +632,9 @@ fn is_audit_noise(path: &str) -> bool {
             "/" | "/tasks"
                 | "/api/ping"
                 | "/api/me"
+                | "/api/me/policy"
+                | "/api/me/activity"
+                | "/api/login/methods"
                 | "/api/tasks"
                 | "/api/tasks/ws"
                 | "/api/perf/scan"
@@ -404,6 +642,14 @@ fn is_audit_noise(path: &str) -> bool {
         )
 }
 
+tokio::task_local! {
+    /// Whether the console request being handled arrived over TLS, as the
+    /// listener marked its connection. Handlers see only headers, so
+    /// [`ui_log_middleware`] sets this around each request for
+    /// [`identity_of`]; outside one it reads as plain HTTP.
+    static OVER_TLS: bool;
+}
+
 /// The console counterpart to the S3 `log_middleware`: injects a correlation id
 /// early (so handlers/pipeline share it), echoes it back as `x-amz-request-id`,
 /// and writes one audit line per meaningful request — the same tracing/audit
@@ -423,8 +669,9 @@ async fn ui_log_middleware(
     let actor = session_of(&state, request.headers())
         .map(|s| s.username)
         .unwrap_or_else(|| "-".to_string());
+    let secure = request.extensions().get::<super::tls::TlsConnection>().is_some();
     let start = std::time::Instant::now();
-    let mut response = next.run(request).await;
+    let mut response = OVER_TLS.scope(secure, next.run(request)).await;
     let elapsed_ms = start.elapsed().as_millis();
     let status = response.status();
     if let Ok(value) = HeaderValue::from_str(&request_id) {
@@ -454,45 +701,234 @@ async fn ui_log_middleware(
 
 // ── session helpers ──────────────────────────────────────────────────────────
 
-fn session_of(state: &UiState, headers: &HeaderMap) -> Option<UiSession> {
+fn session_token(headers: &HeaderMap) -> Option<String> {
+    cookie_value(headers, SESSION_COOKIE)
+}
+
+fn cookie_value(headers: &HeaderMap, cookie: &str) -> Option<String> {
     let cookies = headers.get(header::COOKIE)?.to_str().ok()?;
-    let token = cookies.split(';').find_map(|c| {
+    cookies.split(';').find_map(|c| {
         let (name, value) = c.trim().split_once('=')?;
-        (name == SESSION_COOKIE).then(|| value.to_string())
-    })?;
-    let (username, is_builtin) = state.iam.resolve_session(&token)?;
+        (name == cookie).then(|| value.to_string())
+    })
+}
+
+/// `ui.session_*` as the IAM store takes them. Read per request, so a reload
+/// shortens or lengthens sessions that are already open.
+fn session_timeouts(state: &UiState) -> SessionTimeouts {
+    let ui = &state.config.current().ui;
+    SessionTimeouts {
+        idle_ms: ui.session_idle_minutes.saturating_mul(60_000).min(i64::MAX as u64) as i64,
+        max_age_ms: ui.session_max_hours.saturating_mul(3_600_000).min(i64::MAX as u64) as i64,
+    }
+}
+
+/// The admin API token in `Authorization: Bearer …`, if any.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// A bearer token wins over the cookie, so a script run from a signed-in
+/// browser profile still gets only what its token allows.
+fn session_of(state: &UiState, headers: &HeaderMap) -> Option<UiSession> {
+    if let Some(token) = bearer_token(headers) {
+        return token_session(state, token);
+    }
+    let token = session_token(headers)?;
+    let (username, is_builtin) = state.iam.resolve_session(&token, session_timeouts(state))?;
     let is_admin = is_builtin || state.iam.is_admin(&username);
     Some(UiSession {
+        id: super::iam::session_id(&token),
         username,
         is_builtin,
         is_admin,
+        scope: None,
+    })
+}
+
+/// An admin API token acts as its owner, narrowed to its scopes. It counts as
+/// a full administrator only when its owner is one and it is scoped to every
+/// `admin:` action. A built-in owner must still be in the config.
+fn token_session(state: &UiState, token: &str) -> Option<UiSession> {
+    let token = state.iam.resolve_api_token(token)?;
+    if token.is_builtin && state.config.current().find_builtin_user(&token.username).is_none() {
+        return None;
+    }
+    let scope = token_scope(&token.scopes);
+    let is_admin = (token.is_builtin || state.iam.is_admin(&token.username))
+        && ADMIN_ACTIONS
+            .iter()
+            .all(|(action, _)| is_authorized(&scope, &[Requirement::admin(action)]));
+    Some(UiSession {
+        id: token.id,
+        username: token.username,
+        is_builtin: token.is_builtin,
+        is_admin,
+        scope: Some(scope),
     })
 }
 
+/// A token's scopes as a policy: allow those actions on everything.
+fn token_scope(scopes: &[String]) -> PolicyDocument {
+    PolicyDocument {
+        version: "2012-10-17".to_string(),
+        statement: vec![Statement {
+            sid: None,
+            effect: Effect::Allow,
+            action: OneOrMany::Many(scopes.to_vec()),
+            resource: OneOrMany::One("*".to_string()),
+            condition: None,
+        }],
+    }
+}
+
 fn require_session(state: &UiState, headers: &HeaderMap) -> Result<UiSession, Response> {
     session_of(state, headers).ok_or_else(|| error_response(StatusCode::UNAUTHORIZED, "not logged in"))
 }
 
-fn require_root(state: &UiState, headers: &HeaderMap) -> Result<UiSession, Response> {
+/// A console sign-in, refusing admin API tokens. New tokens, the password and
+/// the second factor stay with the person, not a token they gave a script.
+fn require_console_session(state: &UiState, headers: &HeaderMap) -> Result<UiSession, Response> {
     let session = require_session(state, headers)?;
-    if !session.is_admin {
-        return Err(error_response(StatusCode::FORBIDDEN, "admin only"));
+    if session.scope.is_some() {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "not available to API tokens; sign in to the console",
+        ));
     }
     Ok(session)
 }
 
+/// Requires a console administration action from [`ADMIN_ACTIONS`]. Built-in
+/// users and the `admin` group hold them all; anyone else needs a policy that
+/// grants it, checked like any S3 action.
+fn require_admin(
+    state: &UiState,
+    headers: &HeaderMap,
+    action: &'static str,
+) -> Result<UiSession, Response> {
+    let session = require_session(state, headers)?;
+    if !authorize(state, &session, &[Requirement::admin(action)]) {
+        return Err(error_response(StatusCode::FORBIDDEN, format!("{action} is required")));
+    }
+    Ok(session)
+}
+
+/// Roles that need to read users and groups to do their job.
+const IAM_READERS: &[&str] = &["admin:ManageUsers", "admin:ManagePolicies"];
+
+/// [`require_admin`] for the IAM reads that more than one role needs.
+fn require_any_admin(
+    state: &UiState,
+    headers: &HeaderMap,
+    actions: &[&'static str],
+) -> Result<UiSession, Response> {
+    let session = require_session(state, headers)?;
+    let identity = identity_of(state, &session);
+    if actions.iter().any(|action| identity.authorize(&[Requirement::admin(action)])) {
+        return Ok(session);
+    }
+    // Logs and audits the denial.
+    authorize(state, &session, &[Requirement::admin(actions[0])]);
+    Err(error_response(StatusCode::FORBIDDEN, format!("{} is required", actions.join(" or "))))
+}
+
+/// Delegated administrators manage everyone but full administrators and
+/// themselves, or resetting a password or widening their own policy would be
+/// a way to become one.
+fn guard_admin_target(state: &UiState, session: &UiSession, username: &str) -> Result<(), Response> {
+    if session.is_admin || (session.username != username && !state.iam.is_admin(username)) {
+        return Ok(());
+    }
+    if session.username == username {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "only an administrator can change your own account here",
+        ));
+    }
+    Err(error_response(
+        StatusCode::FORBIDDEN,
+        format!("{username} is an administrator; only an administrator can change that account"),
+    ))
+}
+
+/// Delegated administrators leave the groups they belong to alone, for the
+/// same reason.
+fn guard_own_group(state: &UiState, session: &UiSession, group: &str) -> Result<(), Response> {
+    let groups = state.iam.groups_for(&session.username);
+    let member = groups.iter().any(|g| g.eq_ignore_ascii_case(group));
+    if session.is_admin || !member {
+        return Ok(());
+    }
+    Err(error_response(
+        StatusCode::FORBIDDEN,
+        format!("you belong to {group}; only an administrator can change it"),
+    ))
+}
+
+/// A new password lets the caller sign in as its user, so a delegate resets
+/// only those who hold nothing the delegate does not.
+fn guard_act_as(state: &UiState, session: &UiSession, username: &str) -> Result<(), Response> {
+    match state.iam.policy_for(username) {
+        Some(policy) if !session.is_admin && !identity_of(state, session).holds(&policy) => {
+            Err(error_response(
+                StatusCode::FORBIDDEN,
+                format!("{username} holds permissions you do not; ask an administrator"),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A delegated administrator hands out only what they hold: attaching
+/// `s3:*` to someone else would be reading the data by proxy.
+fn guard_grant(
+    state: &UiState,
+    session: &UiSession,
+    policy: Option<&PolicyDocument>,
+) -> Result<(), Response> {
+    match policy {
+        Some(policy) if !session.is_admin && !identity_of(state, session).holds(policy) => {
+            Err(error_response(StatusCode::FORBIDDEN, "that policy grants more than you hold"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The `admin:` actions a session holds, so the console shows the pages they
+/// unlock. Every request is still checked on its own.
+fn admin_actions(state: &UiState, session: &UiSession) -> Vec<&'static str> {
+    let identity = identity_of(state, session);
+    ADMIN_ACTIONS
+        .iter()
+        .map(|(action, _)| *action)
+        .filter(|action| identity.authorize(&[Requirement::admin(action)]))
+        .collect()
+}
+
 
 /// Resolves the console session to the same [`Identity`] the S3 API uses, so
 /// both front doors authorize through one code path. Built-in sessions are
-/// unrestricted; IAM sessions carry their effective policy.
+/// unrestricted; IAM sessions carry their effective policy; admin API tokens
+/// are scoped on top of either.
 fn identity_of(state: &UiState, session: &UiSession) -> Identity {
-    if session.is_builtin {
+    let identity = if session.is_builtin {
         Identity::root(Some(session.username.clone()), None)
     } else {
         Identity::iam(
             session.username.clone(),
             state.iam.policy_for(&session.username),
         )
+        .over_tls(OVER_TLS.try_with(|secure| *secure).unwrap_or(false))
+    };
+    match &session.scope {
+        Some(scope) => identity.scoped(scope.clone()),
+        None => identity,
     }
 }
 
@@ -508,6 +944,19 @@ fn authorize(state: &UiState, session: &UiSession, requirements: &[Requirement])
                 session.username, r.action, r.resource,
             );
         }
+        if let Some(r) = requirements.first() {
+            // No request id reaches this far; the audit trail still wants the deny.
+            state.tasks.publish(super::event_hub::Event::Audit {
+                actor: session.username.clone(),
+                action: r.action.to_string(),
+                target: match r.resource.strip_prefix("arn:aws:s3:::") {
+                    Some(path) => format!("/{path}"),
+                    None => r.resource.clone(),
+                },
+                allowed: false,
+                request_id: "-".to_string(),
+            });
+        }
     }
     allowed
 }
@@ -564,11 +1013,14 @@ fn storage_error(err: StorageError) -> Response {
         StorageError::ObjectNotFound { .. } | StorageError::BucketNotFound(_) => {
             StatusCode::NOT_FOUND
         }
-        StorageError::BucketRebuilding(_) => StatusCode::SERVICE_UNAVAILABLE,
-        StorageError::InvalidMultipartUpload(_) => StatusCode::CONFLICT,
-        StorageError::InvalidBucketName(_) | StorageError::InvalidObjectKey(_) => {
-            StatusCode::BAD_REQUEST
+        StorageError::BucketRebuilding(_) | StorageError::BucketMoving(_) => {
+            StatusCode::SERVICE_UNAVAILABLE
         }
+        StorageError::InvalidMultipartUpload(_) => StatusCode::CONFLICT,
+        StorageError::InvalidBucketName(_)
+        | StorageError::InvalidObjectKey(_)
+        | StorageError::QuotaExceeded(_)
+        | StorageError::InvalidPlacement(_) => StatusCode::BAD_REQUEST,
         _ => StatusCode::INTERNAL_SERVER_ERROR,
     };
     error_response(status, err.to_string())
@@ -582,10 +1034,17 @@ struct LoginRequest {
     password: String,
 }
 
-async fn login(State(state): State<UiState>, Json(req): Json<LoginRequest>) -> Response {
+async fn login(
+    State(state): State<UiState>,
+    Extension(rid): Extension<super::RequestId>,
+    connect: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<LoginRequest>,
+) -> Response {
+    let client = session_client(connect, &headers);
     // Built-in admin users (config file) first — they always win over any
     // same-named sqlite user and are unrestricted.
-    if let Some(builtin) = state.config.find_builtin_user(&req.username) {
+    if let Some(builtin) = state.config.current().find_builtin_user(&req.username) {
         let ok = builtin
             .password
             .as_deref()
@@ -595,40 +1054,378 @@ async fn login(State(state): State<UiState>, Json(req): Json<LoginRequest>) -> R
             log::warn!(target: TARGET_AUTH, "ui login failed user={} kind=builtin", req.username);
             return error_response(StatusCode::UNAUTHORIZED, "invalid credentials");
         }
+        if builtin.totp_secret.is_some() {
+            let ticket = state.logins.start(&req.username, true, false);
+            return Json(json!({ "mfa": "verify", "ticket": ticket })).into_response();
+        }
         log::info!(target: TARGET_AUTH, "ui login user={} kind=builtin root=true", req.username);
-        return session_response(&state, &req.username, true);
+        return session_response(&state, &req.username, true, client, None).await;
     }
-    match state.iam.verify_password(&req.username, &req.password).await {
-        Ok(true) => {
-            log::info!(target: TARGET_AUTH, "ui login user={} kind=iam root=false", req.username);
-            session_response(&state, &req.username, false)
+    // IAM passwords next, then the directory when there is one. Either way
+    // the session is the IAM user's, second factor included.
+    let kind = match state.iam.verify_password(&req.username, &req.password).await {
+        Ok(true) => "iam",
+        Ok(false) => match ldap_login(&state, &rid.0, &req.username, &req.password).await {
+            Ok(true) => "ldap",
+            Ok(false) => {
+                log::warn!(target: TARGET_AUTH, "ui login failed user={} kind=iam", req.username);
+                return error_response(StatusCode::UNAUTHORIZED, "invalid credentials");
+            }
+            Err(resp) => return resp,
+        },
+        Err(err) => return storage_error(err),
+    };
+    let enrolled = match state.iam.mfa_status(&req.username).await {
+        Ok(status) => status.enabled,
+        Err(err) => return storage_error(err),
+    };
+    if enrolled {
+        let ticket = state.logins.start(&req.username, false, false);
+        return Json(json!({ "mfa": "verify", "ticket": ticket })).into_response();
+    }
+    if state.config.current().ui.require_totp {
+        // No session until the user has a second factor: they enroll now,
+        // and confirming the first code completes the login.
+        let secret = match state.iam.begin_mfa_enrollment(&req.username).await {
+            Ok(secret) => secret,
+            Err(err) => return storage_error(err),
+        };
+        let ticket = state.logins.start(&req.username, false, true);
+        let mut body = enrollment_json(&req.username, &secret);
+        body["mfa"] = json!("enroll");
+        body["ticket"] = json!(ticket);
+        return Json(body).into_response();
+    }
+    log::info!(target: TARGET_AUTH, "ui login user={} kind={kind} root=false", req.username);
+    session_response(&state, &req.username, false, client, None).await
+}
+
+/// Asks the LDAP directory about a login the IAM password did not match.
+/// True when the directory vouched for it and the IAM user is in place.
+async fn ldap_login(
+    state: &UiState,
+    request_id: &str,
+    username: &str,
+    password: &str,
+) -> Result<bool, Response> {
+    let config = state.config.current();
+    let ldap = &config.ldap;
+    if !ldap.enabled {
+        return Ok(false);
+    }
+    let user = match super::ldap::authenticate(ldap, username, password).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Ok(false),
+        Err(err) => {
+            log::warn!(target: TARGET_AUTH, "ui login user={username} kind=ldap: {err}");
+            return Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "the LDAP directory is unavailable; try again later",
+            ));
         }
-        Ok(false) => {
-            log::warn!(target: TARGET_AUTH, "ui login failed user={} kind=iam", req.username);
-            error_response(StatusCode::UNAUTHORIZED, "invalid credentials")
+    };
+    let groups = user.iam_groups(ldap);
+    let provisioned = provision_external_user(
+        state,
+        request_id,
+        &config,
+        "ldap",
+        username,
+        groups.as_deref(),
+        ldap.auto_provision,
+    );
+    if let Err(err) = provisioned.await {
+        log::warn!(target: TARGET_AUTH, "ui login failed user={username} kind=ldap: {err}");
+        return Err(error_response(StatusCode::FORBIDDEN, err));
+    }
+    Ok(true)
+}
+
+fn session_client(connect: Option<ConnectInfo<SocketAddr>>, headers: &HeaderMap) -> SessionClient {
+    SessionClient {
+        ip: connect.map(|ConnectInfo(addr)| addr.ip().to_string()),
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.chars().take(256).collect()),
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginMfaRequest {
+    ticket: String,
+    code: String,
+}
+
+/// The second login step: a ticket from `/api/login` plus an authenticator
+/// or recovery code. A ticket from a login that enrolled confirms the new
+/// secret instead, and the response carries the recovery codes.
+async fn login_mfa(
+    State(state): State<UiState>,
+    connect: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<LoginMfaRequest>,
+) -> Response {
+    let client = session_client(connect, &headers);
+    let Some(pending) = state.logins.attempt(&req.ticket) else {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            "sign-in expired; enter your password again",
+        );
+    };
+    let (username, kind) = (&pending.username, if pending.is_builtin { "builtin" } else { "iam" });
+    let mut recovery_codes = None;
+    let verified = if pending.is_builtin {
+        let config = state.config.current();
+        config
+            .find_builtin_user(username)
+            .and_then(|user| user.totp_secret.as_deref())
+            .and_then(super::totp::base32_decode)
+            .is_some_and(|secret| state.logins.verify_builtin(username, &secret, &req.code))
+    } else if pending.enrolling {
+        match state.iam.confirm_mfa(username, &req.code).await {
+            Ok(codes) => {
+                recovery_codes = codes;
+                recovery_codes.is_some()
+            }
+            Err(err) => return storage_error(err),
         }
-        Err(err) => storage_error(err),
+    } else {
+        match state.iam.verify_mfa(username, &req.code).await {
+            Ok(verified) => verified,
+            Err(err) => return storage_error(err),
+        }
+    };
+    if !verified {
+        log::warn!(target: TARGET_AUTH, "ui login failed user={username} kind={kind} factor=totp");
+        return error_response(StatusCode::UNAUTHORIZED, "invalid authentication code");
     }
+    state.logins.finish(&req.ticket);
+    log::info!(
+        target: TARGET_AUTH,
+        "ui login user={username} kind={kind} root={} factor=totp",
+        pending.is_builtin
+    );
+    session_response(&state, username, pending.is_builtin, client, recovery_codes).await
+}
+
+/// What an authenticator app needs to add an account: the secret, its
+/// `otpauth://` URI and that URI as a QR code.
+fn enrollment_json(username: &str, secret: &str) -> serde_json::Value {
+    let bytes = super::totp::base32_decode(secret).unwrap_or_default();
+    let uri = super::totp::provisioning_uri("RustS3", username, &bytes);
+    json!({ "secret": secret, "uri": uri, "qr_svg": super::totp::qr_svg(&uri) })
 }
 
-fn session_response(state: &UiState, username: &str, is_builtin: bool) -> Response {
-    let token = state.iam.create_session(username, is_builtin);
+async fn session_response(
+    state: &UiState,
+    username: &str,
+    is_builtin: bool,
+    client: SessionClient,
+    recovery_codes: Option<Vec<String>>,
+) -> Response {
+    let token = match state.iam.create_session(username, is_builtin, client).await {
+        Ok(token) => token,
+        Err(err) => return storage_error(err),
+    };
     let is_admin = is_builtin || state.iam.is_admin(username);
     let cookie = format!("{SESSION_COOKIE}={token}; HttpOnly; SameSite=Strict; Path=/");
+    let mut body = json!({ "username": username, "is_admin": is_admin, "is_builtin": is_builtin });
+    if let Some(codes) = recovery_codes {
+        body["recovery_codes"] = json!(codes);
+    }
+    ([(header::SET_COOKIE, cookie)], Json(body)).into_response()
+}
+
+// ── single sign-on ───────────────────────────────────────────────────────────
+
+/// Pins an SSO sign-in's `state` to the browser that started it. `Lax`, not
+/// `Strict`: it has to come back on the provider's redirect.
+const OIDC_STATE_COOKIE: &str = "rusts3_oidc";
+
+/// What the login page offers besides a password.
+async fn login_methods(State(state): State<UiState>) -> Json<Value> {
+    let config = state.config.current();
+    let oidc = &config.ui.oidc;
+    Json(json!({ "sso": oidc.enabled.then(|| oidc.button_label.clone()) }))
+}
+
+/// Where the provider sends the browser back to: `ui.oidc.redirect_url`, or
+/// the callback on the console's public origin, or on the host the browser
+/// asked for.
+fn oidc_redirect_uri(config: &AppConfig, headers: &HeaderMap) -> Option<String> {
+    if let Some(url) = &config.ui.oidc.redirect_url {
+        return Some(url.clone());
+    }
+    let (scheme, host) = match &config.ui.public_hostname {
+        Some(host) => (config.ui.public_scheme.as_str(), host.clone()),
+        None => {
+            let host = headers.get(header::HOST)?.to_str().ok()?.to_string();
+            (if config.ui.tls.enabled { "https" } else { "http" }, host)
+        }
+    };
+    Some(format!("{scheme}://{host}{}", super::oidc::CALLBACK_PATH))
+}
+
+/// Back to the login page, which shows `message`.
+fn sso_failure(message: &str) -> Response {
+    let location = format!("/?sso_error={}", urlencoding::encode(message));
+    (StatusCode::FOUND, [(header::LOCATION, location)]).into_response()
+}
+
+/// Starts an SSO sign-in by sending the browser to the provider.
+async fn oidc_login(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let config = state.config.current();
+    let oidc = &config.ui.oidc;
+    if !oidc.enabled {
+        return error_response(StatusCode::NOT_FOUND, "single sign-on is not configured");
+    }
+    let Some(redirect_uri) = oidc_redirect_uri(&config, &headers) else {
+        return sso_failure("cannot tell the console's address; set ui.oidc.redirect_url");
+    };
+    match state.oidc.authorize(oidc, &redirect_uri).await {
+        Ok(auth) => {
+            let cookie = format!(
+                "{OIDC_STATE_COOKIE}={}; HttpOnly; SameSite=Lax; Path=/api/oidc; Max-Age=600",
+                auth.state
+            );
+            (
+                StatusCode::FOUND,
+                [(header::LOCATION, auth.url), (header::SET_COOKIE, cookie)],
+            )
+                .into_response()
+        }
+        Err(err) => {
+            log::warn!(target: TARGET_AUTH, "ui sso unavailable: {err}");
+            sso_failure(&format!("the identity provider is unavailable: {err}"))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// The provider's redirect back. A verified ID token becomes a session for
+/// the IAM user its claims name, created first if `auto_provision` allows.
+/// SSO logins skip the console's own two-factor step; the provider is
+/// expected to enforce its own.
+async fn oidc_callback(
+    State(state): State<UiState>,
+    Extension(rid): Extension<super::RequestId>,
+    connect: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Response {
+    let config = state.config.current();
+    let oidc = &config.ui.oidc;
+    if !oidc.enabled {
+        return error_response(StatusCode::NOT_FOUND, "single sign-on is not configured");
+    }
+    if let Some(error) = query.error {
+        let reason = query.error_description.unwrap_or(error);
+        log::warn!(target: TARGET_AUTH, "ui login failed kind=oidc provider said: {reason}");
+        return sso_failure(&format!("the identity provider refused the sign-in: {reason}"));
+    }
+    let (Some(code), Some(flow)) = (query.code, query.state) else {
+        return sso_failure("the identity provider's reply is missing its code");
+    };
+    if cookie_value(&headers, OIDC_STATE_COOKIE).as_deref() != Some(flow.as_str()) {
+        return sso_failure("this sign-in was started in another browser; start again");
+    }
+    let user = match state.oidc.exchange(oidc, &flow, &code).await {
+        Ok(claims) => super::oidc::map_claims(oidc, &claims),
+        Err(err) => Err(err),
+    };
+    let user = match user {
+        Ok(user) => user,
+        Err(err) => {
+            log::warn!(target: TARGET_AUTH, "ui login failed kind=oidc: {err}");
+            return sso_failure(&err);
+        }
+    };
+    let provisioned = provision_external_user(
+        &state,
+        &rid.0,
+        &config,
+        "oidc",
+        &user.username,
+        user.groups.as_deref(),
+        oidc.auto_provision,
+    );
+    if let Err(err) = provisioned.await {
+        log::warn!(target: TARGET_AUTH, "ui login failed user={} kind=oidc: {err}", user.username);
+        return sso_failure(&err);
+    }
+    let client = session_client(connect, &headers);
+    let token = match state.iam.create_session(&user.username, false, client).await {
+        Ok(token) => token,
+        Err(err) => return sso_failure(&err.to_string()),
+    };
+    log::info!(target: TARGET_AUTH, "ui login user={} kind=oidc root=false", user.username);
+    audit(&state, &rid.0, &user.username, "sso_login", &config.ui.oidc.issuer);
+    // The browser got here by a cross-site redirect chain, on which it holds
+    // back `SameSite=Strict` cookies, so a redirect to the console would land
+    // on the login page. A page that navigates itself is same-site.
     (
-        [(header::SET_COOKIE, cookie)],
-        Json(json!({ "username": username, "is_admin": is_admin, "is_builtin": is_builtin })),
+        AppendHeaders([
+            (
+                header::SET_COOKIE,
+                format!("{SESSION_COOKIE}={token}; HttpOnly; SameSite=Strict; Path=/"),
+            ),
+            (
+                header::SET_COOKIE,
+                format!("{OIDC_STATE_COOKIE}=; Max-Age=0; HttpOnly; SameSite=Lax; Path=/api/oidc"),
+            ),
+        ]),
+        Html(concat!(
+            "<!doctype html><meta http-equiv=\"refresh\" content=\"0;url=/\">",
+            "<script>location.replace('/')</script>",
+        )),
     )
         .into_response()
 }
 
+/// Makes sure the IAM user behind an SSO or LDAP login exists and, when its
+/// source maps groups, is in exactly the groups they map to. `kind` names
+/// the source, as in the auth log's `kind=`.
+async fn provision_external_user(
+    state: &UiState,
+    request_id: &str,
+    config: &AppConfig,
+    kind: &str,
+    username: &str,
+    groups: Option<&[String]>,
+    auto_provision: bool,
+) -> Result<(), String> {
+    if config.find_builtin_user(username).is_some() {
+        return Err(format!("{username} is a built-in user and signs in with its password"));
+    }
+    let sync = state
+        .iam
+        .sync_external_user(username, auto_provision, groups)
+        .await
+        .map_err(|err| err.to_string())?;
+    for group in &sync.missing_groups {
+        log::warn!(target: TARGET_AUTH, "{kind} group_map names missing IAM group {group}");
+    }
+    if sync.created {
+        audit(state, request_id, kind, "create_user", username);
+    }
+    if let Some(groups) = sync.groups {
+        audit(state, request_id, kind, "set_user_groups", format!("{username} groups={groups:?}"));
+    }
+    Ok(())
+}
+
 async fn logout(State(state): State<UiState>, headers: HeaderMap) -> Response {
-    if let Some(cookies) = headers.get(header::COOKIE).and_then(|v| v.to_str().ok()) {
-        if let Some(token) = cookies.split(';').find_map(|c| {
-            let (name, value) = c.trim().split_once('=')?;
-            (name == SESSION_COOKIE).then(|| value.to_string())
-        }) {
-            state.iam.destroy_session(&token);
+    if let Some(token) = session_token(&headers) {
+        if let Err(err) = state.iam.destroy_session(&token).await {
+            return storage_error(err);
         }
     }
     let clear = format!("{SESSION_COOKIE}=; Max-Age=0; HttpOnly; SameSite=Strict; Path=/");
@@ -641,6 +1438,7 @@ async fn me(State(state): State<UiState>, headers: HeaderMap) -> Response {
             "username": s.username,
             "is_admin": s.is_admin,
             "is_builtin": s.is_builtin,
+            "admin_actions": admin_actions(&state, &s),
         }))
         .into_response(),
         Err(resp) => resp,
@@ -667,11 +1465,12 @@ async fn server_ping(State(state): State<UiState>, headers: HeaderMap) -> Respon
 // ── user management (root only, sqlite-backed IAM users) ─────────────────────
 
 async fn list_users(State(state): State<UiState>, headers: HeaderMap) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_any_admin(&state, &headers, IAM_READERS) {
         return resp;
     }
     let builtin: Vec<_> = state
         .config
+        .current()
         .auth
         .users
         .iter()
@@ -681,8 +1480,13 @@ async fn list_users(State(state): State<UiState>, headers: HeaderMap) -> Respons
             "is_admin": true,
             "groups": [Group::Admin.name()],
             "keys": u.api_keys.len(),
+            "mfa": u.totp_secret.is_some(),
         }))
         .collect();
+    let enrolled = match state.iam.mfa_enrolled_users().await {
+        Ok(enrolled) => enrolled,
+        Err(err) => return storage_error(err),
+    };
     match state.iam.list_users().await {
         Ok(users) => {
             let mut out = builtin;
@@ -695,7 +1499,10 @@ async fn list_users(State(state): State<UiState>, headers: HeaderMap) -> Respons
                     "groups": groups,
                     "has_policy": u.policy.is_some(),
                     "policy": u.policy,
+                    "policy_names": u.policy_names,
+                    "disabled": u.disabled,
                     "created_at_ms": u.created_at_ms,
+                    "mfa": enrolled.contains(&u.username),
                 })
             }));
             Json(json!({ "users": out })).into_response()
@@ -716,11 +1523,11 @@ async fn create_user(
     Extension(rid): Extension<super::RequestId>,
     Json(req): Json<CreateUserRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&req.username).is_some() {
+    if state.config.current().find_builtin_user(&req.username).is_some() {
         return error_response(
             StatusCode::CONFLICT,
             "name is reserved by a built-in config user",
@@ -741,11 +1548,14 @@ async fn delete_user(
     Extension(rid): Extension<super::RequestId>,
     axum::extract::Path(name): axum::extract::Path<String>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&name).is_some() {
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
         return error_response(
             StatusCode::CONFLICT,
             "built-in users are config-managed and cannot be deleted at runtime",
@@ -778,11 +1588,17 @@ async fn reset_password(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(req): Json<ResetPasswordRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&name).is_some() {
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    if let Err(resp) = guard_act_as(&state, &actor, &name) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
         return error_response(
             StatusCode::CONFLICT,
             "built-in user passwords are config-managed",
@@ -804,11 +1620,17 @@ async fn set_policy(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(policy): Json<Option<PolicyDocument>>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManagePolicies") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&name).is_some() {
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    if let Err(resp) = guard_grant(&state, &actor, policy.as_ref()) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
         return error_response(
             StatusCode::CONFLICT,
             "built-in users are unrestricted and config-managed; policies cannot be attached",
@@ -834,7 +1656,7 @@ async fn compile_policy_rules(
     headers: HeaderMap,
     Json(req): Json<PolicyRulesRequest>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ManagePolicies") {
         return resp;
     }
     match compile_rules(&req.rules) {
@@ -848,7 +1670,7 @@ async fn decompile_policy_rules(
     headers: HeaderMap,
     Json(policy): Json<PolicyDocument>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ManagePolicies") {
         return resp;
     }
     if let Err(message) = policy.validate() {
@@ -867,12 +1689,15 @@ async fn set_user_policy_rules(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(req): Json<PolicyRulesRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManagePolicies") {
         Ok(session) => session,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&name).is_some() {
-        return error_response(
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
+        return error_response(
             StatusCode::CONFLICT,
             "built-in users are config-managed",
         );
@@ -881,6 +1706,9 @@ async fn set_user_policy_rules(
         Ok(policy) => policy,
         Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
     };
+    if let Err(resp) = guard_grant(&state, &actor, Some(&policy)) {
+        return resp;
+    }
     match state.iam.set_policy(&name, Some(&policy)).await {
         Ok(()) => {
             audit(&state, &rid.0, &actor.username, "set_user_policy_rules", &name);
@@ -893,17 +1721,18 @@ async fn set_user_policy_rules(
 // ── IAM groups (administrators only) ─────────────────────────────────────────
 
 async fn list_groups(State(state): State<UiState>, headers: HeaderMap) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_any_admin(&state, &headers, IAM_READERS) {
         return resp;
     }
     match state.iam.list_groups().await {
         Ok(groups) => {
-            let builtin_admins = state.config.auth.users.len() as u64;
+            let builtin_admins = state.config.current().auth.users.len() as u64;
             Json(json!({
                 "groups": groups.iter().map(|group| json!({
                     "name": group.group.name(),
                     "policy": group.policy,
                     "has_policy": group.policy.is_some(),
+                    "policy_names": group.policy_names,
                     "is_system": matches!(group.group, Group::Admin),
                     "members": group.members + match group.group {
                         Group::Admin => builtin_admins,
@@ -931,10 +1760,13 @@ async fn create_group(
     Extension(rid): Extension<super::RequestId>,
     Json(req): Json<CreateGroupRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
+    if let Err(resp) = guard_grant(&state, &actor, req.policy.as_ref()) {
+        return resp;
+    }
     match state.iam.create_group(&req.name, req.policy.as_ref()).await {
         Ok(()) => {
             audit(&state, &rid.0, &actor.username, "create_group", &req.name);
@@ -951,10 +1783,16 @@ async fn set_group_policy(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(policy): Json<Option<PolicyDocument>>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManagePolicies") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
+    if let Err(resp) = guard_own_group(&state, &actor, &name) {
+        return resp;
+    }
+    if let Err(resp) = guard_grant(&state, &actor, policy.as_ref()) {
+        return resp;
+    }
     match state.iam.set_group_policy(&name, policy.as_ref()).await {
         Ok(()) => {
             audit(&state, &rid.0, &actor.username, "set_group_policy", &name);
@@ -971,14 +1809,20 @@ async fn set_group_policy_rules(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(req): Json<PolicyRulesRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManagePolicies") {
         Ok(session) => session,
         Err(resp) => return resp,
     };
+    if let Err(resp) = guard_own_group(&state, &actor, &name) {
+        return resp;
+    }
     let policy = match compile_rules(&req.rules) {
         Ok(policy) => policy,
         Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
     };
+    if let Err(resp) = guard_grant(&state, &actor, Some(&policy)) {
+        return resp;
+    }
     match state.iam.set_group_policy(&name, Some(&policy)).await {
         Ok(()) => {
             audit(&state, &rid.0, &actor.username, "set_group_policy_rules", &name);
@@ -994,7 +1838,7 @@ async fn delete_group(
     Extension(rid): Extension<super::RequestId>,
     axum::extract::Path(name): axum::extract::Path<String>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -1012,10 +1856,10 @@ async fn list_user_groups(
     headers: HeaderMap,
     axum::extract::Path(name): axum::extract::Path<String>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_any_admin(&state, &headers, IAM_READERS) {
         return resp;
     }
-    if state.config.find_builtin_user(&name).is_some() {
+    if state.config.current().find_builtin_user(&name).is_some() {
         return Json(json!({"groups": [Group::Admin.name()]})).into_response();
     }
     if !state.iam.user_exists(&name) {
@@ -1036,17 +1880,44 @@ async fn set_user_groups(
     axum::extract::Path(name): axum::extract::Path<String>,
     Json(req): Json<SetUserGroupsRequest>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if state.config.find_builtin_user(&name).is_some() {
+    if state.config.current().find_builtin_user(&name).is_some() {
         return error_response(StatusCode::CONFLICT, "built-in admin membership is immutable");
     }
     let parsed = match state.iam.resolve_groups(&req.groups).await {
         Ok(groups) => groups,
         Err(err) => return error_response(StatusCode::BAD_REQUEST, err.to_string()),
     };
+    // Only an administrator makes or unmakes one.
+    if parsed.iter().any(|group| matches!(group, Group::Admin)) && !actor.is_admin {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "only an administrator can add users to the admin group",
+        );
+    }
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    // Joining a group is being granted its policy.
+    if !actor.is_admin {
+        let groups = match state.iam.list_groups().await {
+            Ok(groups) => groups,
+            Err(err) => return storage_error(err),
+        };
+        let current = state.iam.groups_for(&name);
+        let joined = groups.iter().filter(|group| {
+            let named = |other: &str| other.eq_ignore_ascii_case(group.group.name());
+            parsed.iter().any(|g| named(g.name())) && !current.iter().any(|g| named(g))
+        });
+        for group in joined {
+            if let Err(resp) = guard_grant(&state, &actor, group.policy.as_ref()) {
+                return resp;
+            }
+        }
+    }
     if actor.username == name
         && !actor.is_builtin
         && !parsed.iter().any(|group| matches!(group, Group::Admin))
@@ -1067,8 +1938,23 @@ async fn set_user_groups(
 
 // ── access keys ──────────────────────────────────────────────────────────────
 
-fn may_manage_keys(session: &UiSession, target_user: &str) -> bool {
-    session.is_admin || session.username == target_user
+/// Everyone manages their own keys; `admin:ManageUsers` covers other people's.
+fn may_manage_keys(state: &UiState, session: &UiSession, target_user: &str) -> bool {
+    session.username == target_user
+        || (authorize(state, session, &[Requirement::admin("admin:ManageUsers")])
+            && guard_admin_target(state, session, target_user).is_ok())
+}
+
+/// A new secret lets whoever holds it act as the key's owner, so a delegate
+/// can list, pause and delete other people's keys but only issues their own.
+fn guard_key_issue(session: &UiSession, owner: &str) -> Result<(), Response> {
+    if session.is_admin || session.username == owner {
+        return Ok(());
+    }
+    Err(error_response(
+        StatusCode::FORBIDDEN,
+        "only an administrator can issue keys for another user",
+    ))
 }
 
 async fn list_keys(
@@ -1080,11 +1966,11 @@ async fn list_keys(
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if !may_manage_keys(&session, &name) {
+    if !may_manage_keys(&state, &session, &name) {
         return error_response(StatusCode::FORBIDDEN, "not your keys");
     }
     // Built-in users' keys are visible (access key only) but config-managed.
-    if let Some(builtin) = state.config.find_builtin_user(&name) {
+    if let Some(builtin) = state.config.current().find_builtin_user(&name) {
         return Json(json!({
             "keys": builtin.api_keys.iter().map(|k| json!({
                 "access_key": k.ak,
@@ -1098,7 +1984,9 @@ async fn list_keys(
             "keys": keys.iter().map(|k| json!({
                 "access_key": k.access_key,
                 "created_at_ms": k.created_at_ms,
-            })).collect::<Vec<_>>()
+                "active": k.active,
+            })).collect::<Vec<_>>(),
+            "limit": self_service_key_limit(&state, &session, &name),
         }))
         .into_response(),
         Err(err) => storage_error(err),
@@ -1115,15 +2003,36 @@ async fn create_key(
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    if !may_manage_keys(&session, &name) {
+    if !may_manage_keys(&state, &session, &name) {
         return error_response(StatusCode::FORBIDDEN, "not your keys");
     }
-    if state.config.find_builtin_user(&name).is_some() {
+    if let Err(resp) = guard_key_issue(&session, &name) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
         return error_response(
             StatusCode::CONFLICT,
             "built-in users are config-managed; add api_keys in the config file",
         );
     }
+    if let Some(limit) = self_service_key_limit(&state, &session, &name) {
+        if limit == 0 {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                "access keys are issued by an administrator on this server",
+            );
+        }
+        match state.iam.list_access_keys(&name).await {
+            Ok(keys) if keys.len() >= limit => {
+                return error_response(
+                    StatusCode::CONFLICT,
+                    format!("you already have {limit} access keys; delete one first"),
+                );
+            }
+            Ok(_) => {}
+            Err(err) => return storage_error(err),
+        }
+    }
     match state.iam.create_access_key(&name).await {
         // The secret is returned exactly once, at creation.
         Ok(key) => {
@@ -1144,35 +2053,584 @@ async fn delete_key(
     Extension(rid): Extension<super::RequestId>,
     axum::extract::Path(ak): axum::extract::Path<String>,
 ) -> Response {
-    let session = match require_session(&state, &headers) {
-        Ok(s) => s,
+    let (session, owner) = match key_of(&state, &headers, &ak) {
+        Ok(found) => found,
         Err(resp) => return resp,
     };
+    match state.iam.delete_access_key(&ak).await {
+        Ok(()) => {
+            audit(&state, &rid.0, &session.username, "delete_access_key", format!("{owner} ak={ak}"));
+            Json(json!({"ok": true})).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// The session and the owner of an IAM access key the session may manage.
+fn key_of(
+    state: &UiState,
+    headers: &HeaderMap,
+    ak: &str,
+) -> Result<(UiSession, String), Response> {
+    let session = require_session(state, headers)?;
     // Hidden console signing keys (`RSWEB_…`) are never listed and cannot be
-    // deleted individually — they live in a separate table and only die when
+    // managed individually — they live in a separate table and only die when
     // the owning user is deleted. Reject any attempt so the invariant is
-    // explicit (defense in depth; `find_key` would already miss them).
+    // explicit (defense in depth; `key_owner` would already miss them).
     if ak.starts_with("RSWEB_") {
-        return error_response(StatusCode::NOT_FOUND, "no such access key");
+        return Err(error_response(StatusCode::NOT_FOUND, "no such access key"));
     }
-    if state.config.find_secret(&ak).is_some() {
-        return error_response(
+    if state.config.current().find_secret(ak).is_some() {
+        return Err(error_response(
             StatusCode::CONFLICT,
-            "built-in api keys are config-managed and cannot be deleted at runtime",
-        );
+            "built-in api keys are config-managed and cannot be changed at runtime",
+        ));
     }
-    let owner = state.iam.find_key(&ak).map(|(_, user)| user);
-    let Some(owner) = owner else {
-        return error_response(StatusCode::NOT_FOUND, "no such access key");
+    let Some(owner) = state.iam.key_owner(ak) else {
+        return Err(error_response(StatusCode::NOT_FOUND, "no such access key"));
     };
-    if !may_manage_keys(&session, &owner) {
-        return error_response(StatusCode::FORBIDDEN, "not your key");
+    if !may_manage_keys(state, &session, &owner) {
+        return Err(error_response(StatusCode::FORBIDDEN, "not your key"));
     }
-    match state.iam.delete_access_key(&ak).await {
+    Ok((session, owner))
+}
+
+/// How many keys `target_user` may hold when this session creates one:
+/// `ui.max_access_keys_per_user` for a user's own keys, no cap for anyone
+/// holding `admin:ManageUsers`, who could issue them anyway.
+fn self_service_key_limit(
+    state: &UiState,
+    session: &UiSession,
+    target_user: &str,
+) -> Option<usize> {
+    let manages_users =
+        identity_of(state, session).authorize(&[Requirement::admin("admin:ManageUsers")]);
+    (session.username == target_user && !manages_users)
+        .then(|| state.config.current().ui.max_access_keys_per_user)
+}
+
+#[derive(Deserialize)]
+struct KeyStatusRequest {
+    active: bool,
+}
+
+/// Deactivates or reactivates an access key. Requests signed with an
+/// inactive key are refused as if it did not exist.
+async fn set_key_status(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(ak): axum::extract::Path<String>,
+    Json(req): Json<KeyStatusRequest>,
+) -> Response {
+    let (session, owner) = match key_of(&state, &headers, &ak) {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+    match state.iam.set_access_key_active(&ak, req.active).await {
         Ok(()) => {
-            audit(&state, &rid.0, &session.username, "delete_access_key", format!("{owner} ak={ak}"));
+            let action = if req.active { "activate_access_key" } else { "deactivate_access_key" };
+            audit(&state, &rid.0, &session.username, action, format!("{owner} ak={ak}"));
+            Json(json!({"ok": true})).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Swaps an access key for a new one. The old key stops working at once;
+/// the new secret is returned this one time.
+async fn rotate_key(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(ak): axum::extract::Path<String>,
+) -> Response {
+    let (session, owner) = match key_of(&state, &headers, &ak) {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = guard_key_issue(&session, &owner) {
+        return resp;
+    }
+    match state.iam.rotate_access_key(&ak).await {
+        Ok(key) => {
+            let target = format!("{owner} ak={ak} new={}", key.access_key);
+            audit(&state, &rid.0, &session.username, "rotate_access_key", target);
+            Json(json!({
+                "access_key": key.access_key,
+                "secret_key": key.secret_key,
+            }))
+            .into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+// ── console sessions ─────────────────────────────────────────────────────────
+
+async fn list_sessions(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_admin(&state, &headers, "admin:ManageSessions") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    match state.iam.list_sessions(session_timeouts(&state)).await {
+        Ok(sessions) => Json(json!({ "sessions": sessions, "current": session.id })).into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Revocation is immediate: the session stops resolving before this returns,
+/// so the holder's next request is answered 401.
+async fn revoke_session(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageSessions") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    match state.iam.revoke_session(&id).await {
+        Ok(true) => {
+            let short = &id[..id.len().min(12)];
+            audit(&state, &rid.0, &actor.username, "revoke_session", format!("session={short}"));
+            Json(json!({"ok": true})).into_response()
+        }
+        Ok(false) => error_response(StatusCode::NOT_FOUND, "no such session"),
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn revoke_user_sessions(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageSessions") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    match state.iam.revoke_user_sessions(&name).await {
+        Ok(revoked) => {
+            audit(&state, &rid.0, &actor.username, "revoke_user_sessions", &name);
+            Json(json!({ "ok": true, "revoked": revoked })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+// ── admin API tokens ─────────────────────────────────────────────────────────
+
+/// Longest lifetime a token can be issued for.
+const API_TOKEN_MAX_DAYS: u32 = 365;
+
+#[derive(Deserialize)]
+struct CreateApiTokenRequest {
+    name: String,
+    /// Action patterns, e.g. `admin:ManageUsers`, `admin:*`, `s3:Get*`.
+    scopes: Vec<String>,
+    #[serde(default = "default_token_days")]
+    expires_in_days: u32,
+}
+
+fn default_token_days() -> u32 {
+    30
+}
+
+/// Whether `token` is the caller's own.
+fn owns_token(session: &UiSession, token: &ApiTokenInfo) -> bool {
+    token.username == session.username && token.is_builtin == session.is_builtin
+}
+
+/// The caller's tokens, or everyone's for `admin:ManageSessions`.
+async fn list_api_tokens(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let all = identity_of(&state, &session)
+        .authorize(&[Requirement::admin("admin:ManageSessions")]);
+    let tokens: Vec<ApiTokenInfo> = state
+        .iam
+        .list_api_tokens()
+        .into_iter()
+        .filter(|token| all || owns_token(&session, token))
+        .collect();
+    Json(json!({ "tokens": tokens, "all": all, "max_days": API_TOKEN_MAX_DAYS })).into_response()
+}
+
+/// Issues a token acting as the caller. It is returned once; only its hash is
+/// kept. A token never holds more than its owner: each request must pass both.
+async fn create_api_token(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> Response {
+    let session = match require_console_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let name = req.name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return error_response(StatusCode::BAD_REQUEST, "a token needs a name of 1-64 characters");
+    }
+    let scopes: Vec<String> = req
+        .scopes
+        .iter()
+        .map(|scope| scope.trim().to_string())
+        .filter(|scope| !scope.is_empty())
+        .collect();
+    if scopes.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "a token needs at least one scope");
+    }
+    if let Some(bad) = scopes.iter().find(|scope| {
+        let lower = scope.to_ascii_lowercase();
+        *scope != "*" && !lower.starts_with("s3:") && !lower.starts_with("admin:")
+    }) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("scope {bad:?} is not an s3: or admin: action"),
+        );
+    }
+    if let Err(err) = token_scope(&scopes).validate() {
+        return error_response(StatusCode::BAD_REQUEST, err);
+    }
+    if req.expires_in_days == 0 || req.expires_in_days > API_TOKEN_MAX_DAYS {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("expires_in_days must be 1-{API_TOKEN_MAX_DAYS}"),
+        );
+    }
+    let ttl_ms = i64::from(req.expires_in_days) * 86_400_000;
+    match state
+        .iam
+        .create_api_token(&session.username, session.is_builtin, name, scopes, ttl_ms)
+        .await
+    {
+        Ok((token, info)) => {
+            audit(
+                &state,
+                &rid.0,
+                &session.username,
+                "create_api_token",
+                format!("token={} name={name}", info.id),
+            );
+            Json(json!({ "token": token, "info": info })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Revocation is immediate, like a session's.
+async fn revoke_api_token(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let Some(token) = state.iam.list_api_tokens().into_iter().find(|t| t.id == id) else {
+        return error_response(StatusCode::NOT_FOUND, "no such token");
+    };
+    if !owns_token(&session, &token)
+        && !authorize(&state, &session, &[Requirement::admin("admin:ManageSessions")])
+    {
+        return error_response(StatusCode::FORBIDDEN, "admin:ManageSessions is required");
+    }
+    match state.iam.revoke_api_token(&id).await {
+        Ok(true) => {
+            audit(&state, &rid.0, &session.username, "revoke_api_token", format!("token={id}"));
             Json(json!({"ok": true})).into_response()
         }
+        Ok(false) => error_response(StatusCode::NOT_FOUND, "no such token"),
+        Err(err) => storage_error(err),
+    }
+}
+
+// ── two-factor authentication (the caller's own) ────────────────────────────
+
+async fn mfa_status(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let config = state.config.current();
+    let required = config.ui.require_totp;
+    if session.is_builtin {
+        let enabled = config
+            .find_builtin_user(&session.username)
+            .is_some_and(|user| user.totp_secret.is_some());
+        return Json(json!({ "enabled": enabled, "required": required, "config_managed": true }))
+            .into_response();
+    }
+    match state.iam.mfa_status(&session.username).await {
+        Ok(status) => Json(json!({
+            "enabled": status.enabled,
+            "recovery_codes_left": status.recovery_codes_left,
+            "enabled_at_ms": status.enabled_at_ms,
+            "required": required,
+            "config_managed": false,
+        }))
+        .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// The session's IAM user, refusing built-in users, whose second factor is
+/// the `totp_secret` in the config file.
+fn require_iam_session(state: &UiState, headers: &HeaderMap) -> Result<UiSession, Response> {
+    let session = require_console_session(state, headers)?;
+    if session.is_builtin {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            "built-in users set totp_secret in the config file",
+        ));
+    }
+    Ok(session)
+}
+
+async fn begin_mfa_enrollment(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_iam_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    match state.iam.begin_mfa_enrollment(&session.username).await {
+        Ok(secret) => Json(enrollment_json(&session.username, &secret)).into_response(),
+        Err(err) => error_response(StatusCode::CONFLICT, err.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct MfaCodeRequest {
+    code: String,
+}
+
+async fn confirm_mfa(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Response {
+    let session = match require_iam_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    match state.iam.confirm_mfa(&session.username, &req.code).await {
+        Ok(Some(codes)) => {
+            audit(&state, &rid.0, &session.username, "enable_mfa", &session.username);
+            Json(json!({ "recovery_codes": codes })).into_response()
+        }
+        Ok(None) => error_response(StatusCode::BAD_REQUEST, "invalid authentication code"),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Checks the caller's current second factor before a change to it. A wrong
+/// code is a 400, not a 401: the session itself is fine.
+async fn check_own_code(state: &UiState, username: &str, code: &str) -> Result<(), Response> {
+    match state.iam.verify_mfa(username, code).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(error_response(StatusCode::BAD_REQUEST, "invalid authentication code")),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
+async fn regenerate_recovery_codes(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Response {
+    let session = match require_iam_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = check_own_code(&state, &session.username, &req.code).await {
+        return resp;
+    }
+    match state.iam.regenerate_recovery_codes(&session.username).await {
+        Ok(codes) => {
+            let user = &session.username;
+            audit(&state, &rid.0, user, "regenerate_recovery_codes", user);
+            Json(json!({ "recovery_codes": codes })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn disable_mfa(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Response {
+    let session = match require_iam_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if state.config.current().ui.require_totp {
+        return error_response(
+            StatusCode::CONFLICT,
+            "two-factor authentication is required on this server",
+        );
+    }
+    if let Err(resp) = check_own_code(&state, &session.username, &req.code).await {
+        return resp;
+    }
+    match state.iam.disable_mfa(&session.username).await {
+        Ok(_) => {
+            audit(&state, &rid.0, &session.username, "disable_mfa", &session.username);
+            Json(json!({ "ok": true })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// An administrator removing a user's second factor, for a lost device. The
+/// user signs in with their password alone next time, or enrolls again if
+/// the server requires it.
+async fn reset_user_mfa(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageUsers") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = guard_admin_target(&state, &actor, &name) {
+        return resp;
+    }
+    if state.config.current().find_builtin_user(&name).is_some() {
+        return error_response(
+            StatusCode::CONFLICT,
+            "built-in users' two-factor secrets are config-managed",
+        );
+    }
+    match state.iam.disable_mfa(&name).await {
+        Ok(true) => {
+            audit(&state, &rid.0, &actor.username, "reset_mfa", &name);
+            Json(json!({ "ok": true })).into_response()
+        }
+        Ok(false) => {
+            error_response(StatusCode::NOT_FOUND, "two-factor authentication is not set up")
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+// ── my account (the caller's own) ────────────────────────────────────────────
+
+/// Most events the account page lists.
+const MY_ACTIVITY_LIMIT: usize = 50;
+
+#[derive(Deserialize)]
+struct ChangePasswordRequest {
+    current_password: String,
+    new_password: String,
+}
+
+/// Changes the caller's password after checking the current one. Their other
+/// sessions end, as after an administrator's reset; this one is replaced so
+/// the browser that made the change stays signed in.
+async fn change_own_password(
+    State(state): State<UiState>,
+    Extension(rid): Extension<super::RequestId>,
+    connect: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Response {
+    let session = match require_console_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let username = &session.username;
+    if session.is_builtin {
+        return error_response(
+            StatusCode::CONFLICT,
+            "built-in user passwords are config-managed",
+        );
+    }
+    match state.iam.verify_password(username, &req.current_password).await {
+        Ok(true) => {}
+        Ok(false) => {
+            log::warn!(
+                target: TARGET_AUTH,
+                "[{}] password change failed user={username}: wrong current password",
+                rid.0
+            );
+            return error_response(StatusCode::FORBIDDEN, "the current password is wrong");
+        }
+        Err(err) => return storage_error(err),
+    }
+    if let Err(err) = state.iam.set_password(username, &req.new_password).await {
+        return error_response(StatusCode::BAD_REQUEST, err.to_string());
+    }
+    log::info!(target: TARGET_AUTH, "[{}] password changed user={username}", rid.0);
+    audit(&state, &rid.0, username, "change_password", username);
+    session_response(&state, username, false, session_client(connect, &headers), None).await
+}
+
+/// The policies that make up the caller's permissions: their own and each
+/// group's, in the rule-builder form where the policy has one.
+async fn my_policy(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if session.is_builtin {
+        return Json(json!({ "builtin": true, "sources": [] })).into_response();
+    }
+    let own = match state.iam.list_users().await {
+        Ok(users) => users
+            .into_iter()
+            .find(|u| u.username == session.username)
+            .and_then(|u| u.policy),
+        Err(err) => return storage_error(err),
+    };
+    let groups = match state.iam.list_groups().await {
+        Ok(groups) => groups,
+        Err(err) => return storage_error(err),
+    };
+    let member_of = state.iam.groups_for(&session.username);
+    let source = |kind: &str, name: &str, policy: Option<PolicyDocument>| {
+        let rules = policy.as_ref().and_then(|p| decompile_rules(p).ok());
+        json!({ "kind": kind, "name": name, "policy": policy, "rules": rules })
+    };
+    let mut sources = vec![source("user", &session.username, own)];
+    sources.extend(
+        groups
+            .into_iter()
+            .filter(|g| member_of.iter().any(|m| m.eq_ignore_ascii_case(g.group.name())))
+            .map(|g| source("group", g.group.name(), g.policy)),
+    );
+    Json(json!({ "builtin": false, "sources": sources })).into_response()
+}
+
+/// The caller's own recent console actions and S3 requests, newest first.
+async fn my_activity(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let Some(store) = state.audit.clone() else {
+        return Json(json!({ "enabled": false })).into_response();
+    };
+    let filter = super::audit_store::AuditFilter {
+        principal: Some(session.username),
+        ..Default::default()
+    };
+    match store.query(filter, MY_ACTIVITY_LIMIT).await {
+        Ok((events, truncated)) => {
+            Json(json!({ "enabled": true, "events": events, "truncated": truncated })).into_response()
+        }
         Err(err) => storage_error(err),
     }
 }
@@ -1213,6 +2671,7 @@ async fn list_buckets(
                     "name": name,
                     "created_at_ms": meta.created_at_ms,
                     "multipart_uploads": multipart_uploads,
+                    "root": root_path(&state, &name),
                 }));
             }
             Json(json!({ "buckets": visible })).into_response()
@@ -1224,6 +2683,27 @@ async fn list_buckets(
 #[derive(Deserialize)]
 struct CreateBucketRequest {
     name: String,
+    /// A data root path from `/api/roots`; absent places by free space.
+    #[serde(default)]
+    root: Option<String>,
+}
+
+/// Index of the configured data root whose path is `path`.
+fn root_index(state: &UiState, path: &str) -> Result<usize, Response> {
+    state
+        .store
+        .layout()
+        .roots()
+        .iter()
+        .position(|root| root.display().to_string() == path)
+        .ok_or_else(|| error_response(StatusCode::BAD_REQUEST, format!("no data root {path}")))
+}
+
+/// The data root `bucket` lives on, as `/api/roots` names it.
+fn root_path(state: &UiState, bucket: &str) -> Option<String> {
+    let layout = state.store.layout();
+    let root = layout.bucket_root(bucket).ok()?;
+    Some(layout.roots()[root].display().to_string())
 }
 
 async fn create_bucket(
@@ -1247,7 +2727,11 @@ async fn create_bucket(
         Ok(g) => g,
         Err(resp) => return resp,
     };
-    match state.store.create_bucket(&req.name).await {
+    let root = match req.root.as_deref().map(|path| root_index(&state, path)).transpose() {
+        Ok(root) => root,
+        Err(resp) => return resp,
+    };
+    match state.store.create_bucket_on(&req.name, root).await {
         Ok(()) => {
             audit(&state, &rid.0, &session.username, "create_bucket", format!("/{}", req.name));
             Json(json!({"ok": true})).into_response()
@@ -1296,21 +2780,173 @@ async fn bucket_stats(
         Ok(s) => s,
         Err(resp) => return resp,
     };
-    let _guard = match begin_verb(
-        &state,
-        &session,
-        &rid.0,
-        "BUCKET_STATS",
-        format!("/{name}"),
-        &[Requirement::bucket("s3:ListBucket", &name)],
-    ) {
-        Ok(g) => g,
+    let _guard = match begin_verb(
+        &state,
+        &session,
+        &rid.0,
+        "BUCKET_STATS",
+        format!("/{name}"),
+        &[Requirement::bucket("s3:ListBucket", &name)],
+    ) {
+        Ok(g) => g,
+        Err(resp) => return resp,
+    };
+    let meta = match state.store.bucket_meta(&name).await {
+        Ok(meta) => meta,
+        Err(err) => return storage_error(err),
+    };
+    let dedup = match state.store.bucket_dedup_usage(&name).await {
+        Ok(dedup) => dedup,
+        Err(err) => return storage_error(err),
+    };
+    match state.store.bucket_usage(&name).await {
+        Ok(usage) => Json(json!({
+            "objects": usage.objects,
+            "bytes": usage.bytes,
+            "quota": meta.quota,
+            "compression": meta.compression,
+            "dedup": meta.dedup,
+            "dedup_chunks": dedup.chunks,
+            "dedup_stored_bytes": dedup.stored_bytes,
+            "dedup_saved_bytes": dedup.saved_bytes(),
+            "root": root_path(&state, &name),
+        }))
+        .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Admin-only: quotas exist to keep teams sharing a disk apart, so a bucket's
+/// own users may see theirs (through the stats endpoint) but not raise it.
+/// An all-empty body removes the quota.
+async fn set_bucket_quota(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Path(name): Path<String>,
+    Json(quota): Json<BucketQuota>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageBuckets") {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    let inverted = |soft: Option<u64>, hard: Option<u64>| {
+        matches!((soft, hard), (Some(soft), Some(hard)) if soft > hard)
+    };
+    if inverted(quota.soft_bytes, quota.hard_bytes)
+        || inverted(quota.soft_objects, quota.hard_objects)
+    {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "a soft limit cannot be above its hard limit",
+        );
+    }
+    match state.store.set_bucket_quota(&name, Some(quota)).await {
+        Ok(()) => {
+            audit(&state, &rid.0, &actor.username, "set_bucket_quota", format!("/{name}"));
+            Json(json!({ "ok": true })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+#[derive(Deserialize)]
+struct CompressionBody {
+    #[serde(default)]
+    compression: Option<CompressionCodec>,
+}
+
+/// Admin-only, like quotas: compression trades the server's CPU for its disk.
+/// Applies to writes from now on; a `null` codec turns it off.
+async fn set_bucket_compression(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Path(name): Path<String>,
+    Json(body): Json<CompressionBody>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageBuckets") {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    match state.store.set_bucket_compression(&name, body.compression).await {
+        Ok(()) => {
+            audit(&state, &rid.0, &actor.username, "set_bucket_compression", format!("/{name}"));
+            Json(json!({ "ok": true })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+#[derive(Deserialize)]
+struct DedupBody {
+    dedup: bool,
+}
+
+/// Admin-only: a dedup bucket shares part files between objects, so its
+/// storage no longer splits cleanly per object.
+async fn set_bucket_dedup(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Path(name): Path<String>,
+    Json(body): Json<DedupBody>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageBuckets") {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    match state.store.set_bucket_dedup(&name, body.dedup).await {
+        Ok(()) => {
+            audit(&state, &rid.0, &actor.username, "set_bucket_dedup", format!("/{name}"));
+            Json(json!({ "ok": true })).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Admin-only. Every data root with its capacity and the buckets on it.
+async fn list_roots(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ManageBuckets") {
+        return resp;
+    }
+    match state.store.root_usage().await {
+        Ok(roots) => Json(json!({ "roots": roots })).into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+#[derive(Deserialize)]
+struct MoveBucketBody {
+    root: String,
+}
+
+/// Admin-only. Starts moving a bucket to another data root as a background
+/// `move_bucket` job; progress shows in the task panel.
+async fn move_bucket(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Path(name): Path<String>,
+    Json(body): Json<MoveBucketBody>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageBuckets") {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    let target = match root_index(&state, &body.root) {
+        Ok(target) => target,
         Err(resp) => return resp,
     };
-    match state.store.object_count(&name).await {
-        Ok(objects) => Json(json!({"objects": objects})).into_response(),
-        Err(err) => storage_error(err),
+    if !state.store.bucket_exists(&name).await {
+        return storage_error(StorageError::BucketNotFound(name));
+    }
+    if state.store.layout().bucket_root(&name).ok() == Some(target) {
+        return error_response(StatusCode::BAD_REQUEST, "the bucket is already on that root");
     }
+    super::jobs::move_bucket::spawn(state.store.clone(), name.clone(), target, state.tasks.clone());
+    audit(&state, &rid.0, &actor.username, "move_bucket", format!("/{name}"));
+    (StatusCode::ACCEPTED, Json(json!({ "ok": true }))).into_response()
 }
 
 async fn get_bucket_cors(
@@ -1336,8 +2972,9 @@ async fn get_bucket_cors(
     };
     match state.store.bucket_meta(&name).await {
         Ok(meta) => {
-            let console_origin = state.config.ui.public_hostname.as_deref().map(|host| {
-                format!("{}://{host}", state.config.ui.public_scheme.as_str())
+            let config = state.config.current();
+            let console_origin = config.ui.public_hostname.as_deref().map(|host| {
+                format!("{}://{host}", config.ui.public_scheme.as_str())
             });
             Json(json!({ "rules": meta.cors, "console_origin": console_origin })).into_response()
         }
@@ -1450,7 +3087,7 @@ async fn export_iam(
     headers: HeaderMap,
     Extension(rid): Extension<super::RequestId>,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ExportIam") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -1473,6 +3110,69 @@ async fn export_iam(
     }
 }
 
+/// Admin-only. Completed snapshots, oldest first, and where they are kept.
+async fn list_snapshots(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ManageSnapshots") {
+        return resp;
+    }
+    let config = state.config.current();
+    let dir = super::snapshot::snapshots_dir(&config);
+    match crate::storage::snapshot::list_snapshots(&dir).await {
+        Ok(snapshots) => Json(json!({
+            "dir": dir.display().to_string(),
+            "retain": config.snapshots.retain,
+            "snapshots": snapshots,
+        }))
+        .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Admin-only. Takes a snapshot of the whole server and answers with its
+/// manifest once it is complete. Only one runs at a time.
+async fn create_snapshot(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageSnapshots") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if state.store.snapshot_lock().try_lock().is_err() {
+        return error_response(StatusCode::CONFLICT, "a snapshot is already running");
+    }
+    let config = state.config.current();
+    match super::snapshot::take(&state.store, &state.iam, &config, &state.tasks).await {
+        Ok(manifest) => {
+            audit(&state, &rid.0, &actor.username, "create_snapshot", manifest.id.clone());
+            Json(manifest).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Admin-only. Deletes one snapshot.
+async fn delete_snapshot(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Path(id): Path<String>,
+) -> Response {
+    let actor = match require_admin(&state, &headers, "admin:ManageSnapshots") {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let dir = super::snapshot::snapshots_dir(&state.config.current());
+    match crate::storage::snapshot::delete_snapshot(&dir, &id).await {
+        Ok(()) => {
+            audit(&state, &rid.0, &actor.username, "delete_snapshot", id);
+            Json(json!({ "ok": true })).into_response()
+        }
+        Err(err) => error_response(StatusCode::NOT_FOUND, err.to_string()),
+    }
+}
+
 #[derive(Deserialize)]
 struct ImportQuery {
     /// `merge` (default) upserts; `replace` erases the IAM families first.
@@ -1491,7 +3191,7 @@ async fn import_iam(
     Query(query): Query<ImportQuery>,
     body: axum::body::Bytes,
 ) -> Response {
-    let actor = match require_root(&state, &headers) {
+    let actor = match require_admin(&state, &headers, "admin:ImportIam") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -1546,7 +3246,7 @@ async fn preview_import_iam(
     headers: HeaderMap,
     body: axum::body::Bytes,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ImportIam") {
         return resp;
     }
     if body.is_empty() {
@@ -1706,11 +3406,36 @@ struct ObjectQuery {
     key: String,
 }
 
+#[derive(Deserialize)]
+struct DownloadQuery {
+    bucket: String,
+    key: String,
+    /// Ask for `Content-Disposition: inline`, for the preview panel. Honoured
+    /// only for types in [`previews_inline`]; anything else still downloads.
+    #[serde(default)]
+    inline: bool,
+}
+
+/// Content types a browser may render in place from the console's own origin.
+/// Markup (HTML, SVG, XML) could run script with the session cookie, so it is
+/// always sent as an attachment.
+fn previews_inline(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    match essence.split_once('/') {
+        Some(("image", sub)) => sub != "svg+xml",
+        Some(("audio" | "video", _)) => true,
+        _ => essence == "application/pdf" || essence == "text/plain",
+    }
+}
+
+/// Streams an object to the browser. A single `Range` is answered with 206 so
+/// audio and video previews can seek; a ranged read is audited only when it
+/// starts at the first byte, so seeking through a video is one download event.
 async fn download_object(
     State(state): State<UiState>,
     headers: HeaderMap,
     Extension(rid): Extension<super::RequestId>,
-    Query(q): Query<ObjectQuery>,
+    Query(q): Query<DownloadQuery>,
 ) -> Response {
     let session = match require_session(&state, &headers) {
         Ok(s) => s,
@@ -1731,15 +3456,39 @@ async fn download_object(
         Ok(read) => read,
         Err(err) => return storage_error(err),
     };
-    audit(&state, &rid.0, &session.username, "download", format!("/{}/{}", q.bucket, q.key));
-    // Open every part before responding: open FDs survive a concurrent
-    // overwrite retiring the dir mid-download.
+    let total = read.meta.size;
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let (status, start, len) = match super::range::parse_range_header(range, total) {
+        super::range::RangeSelection::Full => (StatusCode::OK, 0, total),
+        super::range::RangeSelection::Single { start, end_inclusive } => {
+            (StatusCode::PARTIAL_CONTENT, start, end_inclusive - start + 1)
+        }
+        super::range::RangeSelection::Unsatisfiable { total_size } => {
+            let mut response = error_response(StatusCode::RANGE_NOT_SATISFIABLE, "bad range");
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{total_size}")) {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+            return response;
+        }
+    };
+    if start == 0 {
+        audit(&state, &rid.0, &session.username, "download", format!("/{}/{}", q.bucket, q.key));
+    }
+    // Open every part the range touches before responding: open FDs survive a
+    // concurrent overwrite retiring the dir mid-download.
     let mut files = Vec::with_capacity(read.meta.parts.len());
+    let (end, mut part_start) = (start + len, 0u64);
     for part in &read.meta.parts {
-        match tokio::fs::File::open(read.object_dir.join(&part.file)).await {
-            Ok(file) => files.push(file),
-            Err(err) => return storage_error(err.into()),
+        let part_end = part_start + part.size;
+        if part_end > start && part_start < end {
+            let skip = start.saturating_sub(part_start);
+            let take = end.min(part_end) - part_start - skip;
+            match read.open_part(part, skip, take).await {
+                Ok(file) => files.push(file),
+                Err(err) => return storage_error(err.into()),
+            }
         }
+        part_start = part_end;
     }
     use tokio::io::AsyncReadExt;
     let mut reader: Box<dyn tokio::io::AsyncRead + Send + Unpin> = Box::new(tokio::io::empty());
@@ -1750,7 +3499,7 @@ async fn download_object(
     // so the task stays visible for the whole transfer and a cancel stops
     // sending.
     let progress = guard.progress();
-    progress.set_total(read.meta.size);
+    progress.set_total(len);
     let cancel = guard.cancel_token();
     let active = state.metrics.begin_get();
     let metrics = state.metrics.clone();
@@ -1788,16 +3537,28 @@ async fn download_object(
         .chars()
         .filter(|c| !c.is_control() && *c != '"' && *c != '\\')
         .collect::<String>();
-    match Response::builder()
-        .status(StatusCode::OK)
+    let disposition = if q.inline && previews_inline(&read.meta.content_type) {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let mut builder = Response::builder()
+        .status(status)
         .header(header::CONTENT_TYPE, read.meta.content_type.clone())
-        .header(header::CONTENT_LENGTH, read.meta.size)
+        .header(header::CONTENT_LENGTH, len)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, quote_etag(&read.meta.etag))
+        .header(header::LAST_MODIFIED, http_date_ms(read.meta.last_modified_ms))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
         .header(
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{filename}\""),
-        )
-        .body(Body::from_stream(stream))
-    {
+            format!("{disposition}; filename=\"{filename}\""),
+        );
+    if status == StatusCode::PARTIAL_CONTENT {
+        let last = start + len - 1;
+        builder = builder.header(header::CONTENT_RANGE, format!("bytes {start}-{last}/{total}"));
+    }
+    match builder.body(Body::from_stream(stream)) {
         Ok(resp) => resp,
         Err(err) => {
             log::error!("download response build failed key={}: {err}", q.key);
@@ -1809,6 +3570,173 @@ async fn download_object(
     }
 }
 
+/// System headers and user metadata of one object, for the details panel.
+/// Needs `s3:GetObject`, as a HEAD does.
+async fn object_metadata(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Query(q): Query<ObjectQuery>,
+) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let _guard = match begin_verb(
+        &state,
+        &session,
+        &rid.0,
+        "HEAD",
+        format!("/{}/{}", q.bucket, q.key),
+        &[Requirement::object("s3:GetObject", &q.bucket, &q.key)],
+    ) {
+        Ok(g) => g,
+        Err(resp) => return resp,
+    };
+    let meta = match state.store.read_object(&q.bucket, &q.key).await {
+        Ok(read) => read.meta,
+        Err(err) => return storage_error(err),
+    };
+    let compressed = meta.parts.iter().any(|p| p.compression.is_some());
+    Json(json!({
+        "bucket": meta.bucket,
+        "key": meta.object_key,
+        "size": meta.size,
+        "etag": meta.etag,
+        "last_modified_ms": meta.last_modified_ms,
+        "content_type": meta.content_type,
+        "content_encoding": meta.content_encoding,
+        "content_language": meta.content_language,
+        "cache_control": meta.cache_control,
+        "storage_class": meta.storage_class,
+        "user_meta": meta.user_meta,
+        "parts": meta.parts.len(),
+        "compressed": compressed,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct ReplaceMetadataRequest {
+    bucket: String,
+    key: String,
+    content_type: String,
+    #[serde(default)]
+    cache_control: Option<String>,
+    #[serde(default)]
+    user_meta: BTreeMap<String, String>,
+    /// Last-Modified of the object the editor was opened on. Any write since,
+    /// metadata-only ones included, moves it, so a newer object is left alone.
+    #[serde(default)]
+    last_modified_ms: Option<i64>,
+}
+
+/// Rewrites Content-Type, Cache-Control and user metadata the way S3 does: a
+/// copy of the object onto itself with the metadata directive `REPLACE`. The
+/// bytes are untouched (part files are shared where the filesystem allows),
+/// but the object gets a new Last-Modified, so it needs `s3:GetObject` and
+/// `s3:PutObject` both.
+async fn replace_object_metadata(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    Json(req): Json<ReplaceMetadataRequest>,
+) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let target = format!("/{}/{}", req.bucket, req.key);
+    let _guard = match begin_verb(
+        &state,
+        &session,
+        &rid.0,
+        "REPLACE_METADATA",
+        target.clone(),
+        &[
+            Requirement::object("s3:GetObject", &req.bucket, &req.key),
+            Requirement::object("s3:PutObject", &req.bucket, &req.key),
+        ],
+    ) {
+        Ok(g) => g,
+        Err(resp) => return resp,
+    };
+    let content_type = req.content_type.trim();
+    let cache_control = req.cache_control.as_deref().map(str::trim).filter(|v| !v.is_empty());
+    if content_type.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "Content-Type must not be empty");
+    }
+    if HeaderValue::from_str(content_type).is_err()
+        || cache_control.is_some_and(|v| HeaderValue::from_str(v).is_err())
+    {
+        return error_response(StatusCode::BAD_REQUEST, "header values must be printable text");
+    }
+    let user_meta = match validate_user_meta(req.user_meta) {
+        Ok(meta) => meta,
+        Err(msg) => return error_response(StatusCode::BAD_REQUEST, msg),
+    };
+    // Checked where the copy commits, under the key's lock, so a write that
+    // lands while the copy is in flight is caught too.
+    let precondition = req.last_modified_ms.map(Precondition::IfLastModified);
+    match state
+        .store
+        .copy_object_with_metadata(
+            &req.bucket,
+            &req.key,
+            &req.bucket,
+            &req.key,
+            None,
+            Some(&user_meta),
+            Some(content_type),
+            None,
+            Some(cache_control),
+            precondition,
+        )
+        .await
+    {
+        Ok(result) => {
+            audit(&state, &rid.0, &session.username, "replace_metadata", target);
+            Json(json!({ "etag": result.etag })).into_response()
+        }
+        Err(StorageError::PreconditionFailed { .. }) => error_response(
+            StatusCode::CONFLICT,
+            "the object changed since it was opened; reload it and try again",
+        ),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// Applies the rules `x-amz-meta-*` headers meet on a PUT: names become
+/// lowercase header tokens, values header-safe text, and the whole set stays
+/// within the S3 size limit.
+fn validate_user_meta(
+    meta: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut out = BTreeMap::new();
+    for (name, value) in meta {
+        let name = name.trim().to_ascii_lowercase();
+        let name = name.strip_prefix("x-amz-meta-").unwrap_or(&name).to_string();
+        if name.is_empty() {
+            return Err("User metadata key must not be empty".to_string());
+        }
+        if axum::http::HeaderName::from_bytes(format!("x-amz-meta-{name}").as_bytes()).is_err() {
+            return Err(format!("User metadata key {name:?} is not a valid header name"));
+        }
+        if HeaderValue::from_str(&value).is_err() {
+            return Err(format!("User metadata value for {name:?} must be printable text"));
+        }
+        out.insert(name, value);
+    }
+    let total = out.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>();
+    if total > super::MAX_USER_META_BYTES {
+        return Err(format!(
+            "User metadata size exceeds {} bytes",
+            super::MAX_USER_META_BYTES
+        ));
+    }
+    Ok(out)
+}
+
 #[derive(Deserialize)]
 struct UploadPresignRequest {
     bucket: String,
@@ -2013,7 +3941,8 @@ async fn presigned_upload_response(
     key: &str,
     extra_query: &[(&str, &str)],
 ) -> Response {
-    let Some(host) = state.config.auth.public_hostname.as_deref() else {
+    let config = state.config.current();
+    let Some(host) = config.auth.public_hostname.as_deref() else {
         return error_response(
             StatusCode::PRECONDITION_FAILED,
             "set auth.public_hostname in the config to enable direct console uploads",
@@ -2048,7 +3977,7 @@ async fn presigned_upload_response(
     );
     let base_url = format!(
         "{}://{}",
-        state.config.auth.public_scheme.as_str(),
+        config.auth.public_scheme.as_str(),
         host.trim_end_matches('/'),
     );
     audit(
@@ -2192,6 +4121,7 @@ fn tasks_payload(state: &UiState) -> serde_json::Value {
         "tasks": tasks,
         "bytes_in": bytes_in,
         "bytes_out": bytes_out,
+        "limits": state.limits.snapshot(),
     })
 }
 
@@ -2212,7 +4142,7 @@ fn probe_reply(state: &UiState, req_id: &str, ids: &[String]) -> serde_json::Val
 }
 
 async fn list_tasks(State(state): State<UiState>, headers: HeaderMap) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewTasks") {
         return resp;
     }
     Json(tasks_payload(&state)).into_response()
@@ -2228,7 +4158,7 @@ async fn tasks_ws(
     headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    if require_root(&state, &headers).is_err() {
+    if require_admin(&state, &headers, "admin:ViewTasks").is_err() {
         return error_response(StatusCode::UNAUTHORIZED, "admin only");
     }
     ws.on_upgrade(move |socket| tasks_socket(socket, state))
@@ -2297,7 +4227,7 @@ async fn cancel_task(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:CancelTask") {
         return resp;
     }
     match state.tasks.cancel(&id) {
@@ -2332,7 +4262,7 @@ async fn start_scan(
     Extension(rid): Extension<super::RequestId>,
     Json(req): Json<StartScanRequest>,
 ) -> Response {
-    let session = match require_root(&state, &headers) {
+    let session = match require_admin(&state, &headers, "admin:RunScan") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -2391,7 +4321,7 @@ async fn start_scan(
 /// Live state without a WebSocket — the console's fallback, and a plain way to
 /// ask "is anything running right now".
 async fn scan_state(State(state): State<UiState>, headers: HeaderMap) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewScans") {
         return resp;
     }
     Json(state.scans.snapshot()).into_response()
@@ -2405,7 +4335,7 @@ async fn scan_ws(
     headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    if require_root(&state, &headers).is_err() {
+    if require_admin(&state, &headers, "admin:ViewScans").is_err() {
         return error_response(StatusCode::UNAUTHORIZED, "admin only");
     }
     ws.on_upgrade(move |socket| scan_socket(socket, state))
@@ -2465,7 +4395,7 @@ async fn list_scan_reports(
     headers: HeaderMap,
     Query(query): Query<ScanListQuery>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewScans") {
         return resp;
     }
     match state.scans.store().list_reports(query.limit.clamp(1, 200)).await {
@@ -2484,7 +4414,7 @@ async fn get_scan_report(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewScans") {
         return resp;
     }
     let report = match state.scans.store().get_report(&id).await {
@@ -2513,7 +4443,7 @@ async fn delete_scan_report(
     Extension(rid): Extension<super::RequestId>,
     Path(id): Path<String>,
 ) -> Response {
-    let session = match require_root(&state, &headers) {
+    let session = match require_admin(&state, &headers, "admin:RunScan") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -2545,7 +4475,7 @@ async fn delete_scan_reports(
     Extension(rid): Extension<super::RequestId>,
     Json(req): Json<DeleteReportsRequest>,
 ) -> Response {
-    let session = match require_root(&state, &headers) {
+    let session = match require_admin(&state, &headers, "admin:RunScan") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -2620,7 +4550,7 @@ async fn list_scan_findings(
     Path(id): Path<String>,
     Query(query): Query<FindingsQuery>,
 ) -> Response {
-    if let Err(resp) = require_root(&state, &headers) {
+    if let Err(resp) = require_admin(&state, &headers, "admin:ViewScans") {
         return resp;
     }
     let kind = query.kind.as_deref().filter(|v| !v.is_empty());
@@ -2666,7 +4596,7 @@ async fn repair_findings(
     Path(id): Path<String>,
     Json(req): Json<RepairRequest>,
 ) -> Response {
-    let session = match require_root(&state, &headers) {
+    let session = match require_admin(&state, &headers, "admin:RepairObjects") {
         Ok(s) => s,
         Err(resp) => return resp,
     };
@@ -2746,6 +4676,8 @@ fn scan_report_json(report: &super::scan_store::ScanReport) -> Value {
         "objects": report.objects(),
         "logical_bytes": report.logical_bytes(),
         "disk_bytes": report.disk_bytes(),
+        "compressed_logical_bytes": report.compressed_bytes().0,
+        "compressed_stored_bytes": report.compressed_bytes().1,
         "findings": report.findings,
         "findings_total": report.findings_total,
         "deferred_recent": report.deferred_recent(),
@@ -2783,6 +4715,11 @@ struct PresignRequest {
     key: String,
     #[serde(default = "default_presign_expiry")]
     expires_secs: u64,
+    /// Asked for as the password of HTTP Basic auth when the link is opened.
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    max_downloads: Option<u64>,
 }
 
 fn default_presign_expiry() -> u64 {
@@ -2811,7 +4748,8 @@ async fn presign(
         Ok(g) => g,
         Err(resp) => return resp,
     };
-    let Some(host) = state.config.auth.public_hostname.as_deref() else {
+    let config = state.config.current();
+    let Some(host) = config.auth.public_hostname.as_deref() else {
         return error_response(
             StatusCode::PRECONDITION_FAILED,
             "set auth.public_hostname in the config to enable share links",
@@ -2819,7 +4757,7 @@ async fn presign(
     };
     let base_url = format!(
         "{}://{}",
-        state.config.auth.public_scheme.as_str(),
+        config.auth.public_scheme.as_str(),
         host.trim_end_matches('/'),
     );
 
@@ -2838,6 +4776,14 @@ async fn presign(
         Err(err) => return storage_error(err),
     };
 
+    let password = req.password.filter(|password| !password.is_empty());
+    if password.as_ref().is_some_and(|password| password.len() > 256) {
+        return error_response(StatusCode::BAD_REQUEST, "the password is longer than 256 bytes");
+    }
+    if req.max_downloads == Some(0) {
+        return error_response(StatusCode::BAD_REQUEST, "max_downloads must be at least 1");
+    }
+
     let encoded_key = req
         .key
         .split('/')
@@ -2847,6 +4793,24 @@ async fn presign(
     let path = format!("/{}/{}", req.bucket, encoded_key);
     let datetime = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
     let expires = req.expires_secs.clamp(1, 7 * 24 * 3600);
+    // Every link gets its own registry row, and its id is signed into the
+    // URL, so it can be limited, watched and revoked on its own.
+    let new_share = NewShare {
+        bucket: req.bucket.clone(),
+        key: req.key.clone(),
+        signer: access_key.clone(),
+        ttl_ms: expires as i64 * 1000,
+        password,
+        max_downloads: req.max_downloads,
+    };
+    let share = match state
+        .iam
+        .create_share(&session.username, session.is_builtin, new_share)
+        .await
+    {
+        Ok(share) => share,
+        Err(err) => return storage_error(err),
+    };
     let query = presign_query(
         "GET",
         &path,
@@ -2856,10 +4820,103 @@ async fn presign(
         "us-east-1",
         &datetime,
         expires,
-        &[],
+        &[(SHARE_ID_PARAM, &share.id)],
+    );
+    audit(
+        &state,
+        &rid.0,
+        &session.username,
+        "presign",
+        format!("/{}/{} (share={} expires_secs={expires})", req.bucket, req.key, share.id),
     );
-    audit(&state, &rid.0, &session.username, "presign", format!("/{}/{} (expires_secs={expires})", req.bucket, req.key));
-    Json(json!({ "url": format!("{base_url}{path}?{query}") })).into_response()
+    Json(json!({ "url": format!("{base_url}{path}?{query}"), "share": share })).into_response()
+}
+
+// ── share link registry ──────────────────────────────────────────────────────
+
+fn owns_share(session: &UiSession, share: &ShareLink) -> bool {
+    share.username == session.username && share.is_builtin == session.is_builtin
+}
+
+/// The share link `id`, if the caller made it or holds `admin:ManageShares`.
+fn visible_share(state: &UiState, session: &UiSession, id: &str) -> Result<ShareLink, Response> {
+    let Some(share) = state.iam.share(id) else {
+        return Err(error_response(StatusCode::NOT_FOUND, "no such share link"));
+    };
+    if !owns_share(session, &share)
+        && !authorize(state, session, &[Requirement::admin("admin:ManageShares")])
+    {
+        return Err(error_response(StatusCode::FORBIDDEN, "admin:ManageShares is required"));
+    }
+    Ok(share)
+}
+
+/// The caller's share links, or everyone's for `admin:ManageShares`.
+async fn list_shares(State(state): State<UiState>, headers: HeaderMap) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let all = identity_of(&state, &session)
+        .authorize(&[Requirement::admin("admin:ManageShares")]);
+    let shares: Vec<ShareLink> = state
+        .iam
+        .list_shares()
+        .into_iter()
+        .filter(|share| all || owns_share(&session, share))
+        .collect();
+    let now_ms = crate::storage::time::now_ms();
+    Json(json!({ "shares": shares, "all": all, "now_ms": now_ms })).into_response()
+}
+
+/// Revocation is immediate: the next request on the link is refused.
+async fn revoke_share(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    Extension(rid): Extension<super::RequestId>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    let share = match visible_share(&state, &session, &id) {
+        Ok(share) => share,
+        Err(resp) => return resp,
+    };
+    match state.iam.revoke_share(&id).await {
+        Ok(true) => {
+            audit(
+                &state,
+                &rid.0,
+                &session.username,
+                "revoke_share",
+                format!("/{}/{} (share={id})", share.bucket, share.key),
+            );
+            Json(json!({"ok": true})).into_response()
+        }
+        Ok(false) => error_response(StatusCode::CONFLICT, "the share link is already revoked"),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// The newest uses of one share link.
+async fn share_accesses(
+    State(state): State<UiState>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    let session = match require_session(&state, &headers) {
+        Ok(s) => s,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = visible_share(&state, &session, &id) {
+        return resp;
+    }
+    match state.iam.share_accesses(&id).await {
+        Ok(accesses) => Json(json!({ "accesses": accesses })).into_response(),
+        Err(err) => storage_error(err),
+    }
 }
 
 fn constant_time_eq(a: &str, b: &str) -> bool {
@@ -2942,3 +4999,249 @@ mod folder_tests {
         assert!(folder_object_key("", "a////b").is_err());
     }
 }
+
+#[cfg(test)]
+mod delegation_tests {
+    use super::{router, UiState};
+    use crate::server::iam::IamStore;
+    use crate::storage::store::LocalObjectStore;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::Router;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn console(tmp: &tempfile::TempDir) -> (Router, IamStore) {
+        let iam = IamStore::open(&tmp.path().join("admin")).await.unwrap();
+        let config = crate::server::reload::LiveConfig::new(Arc::new(Default::default()));
+        let scans = crate::server::scan_store::ScanStore::open(tmp.path()).await.unwrap();
+        let state = UiState {
+            store: LocalObjectStore::new(tmp.path()),
+            iam: iam.clone(),
+            config: config.clone(),
+            metrics: Arc::default(),
+            tasks: crate::server::registry::TaskRegistry::new(),
+            scans: crate::server::jobs::perf_scan::ScanService::new(scans),
+            stats: None,
+            audit: None,
+            limits: crate::server::ratelimit::RateLimiter::new(config, None),
+            logins: Arc::default(),
+            oidc: Arc::default(),
+        };
+        (router(state), iam)
+    }
+
+    async fn send(app: &Router, cookie: &str, method: &str, uri: &str, body: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::COOKIE, cookie)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    async fn login(app: &Router, username: &str) -> String {
+        let body = format!(r#"{{"username":"{username}","password":"password123"}}"#);
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/login")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "login {username}");
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn a_delegated_role_stays_inside_its_grant() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, iam) = console(&tmp).await;
+        for user in ["boss", "helper", "staff"] {
+            iam.create_user(user, "password123").await.unwrap();
+        }
+        let admin_group = iam.resolve_groups(&["admin".to_string()]).await.unwrap();
+        iam.set_user_groups("boss", &admin_group).await.unwrap();
+        let grant = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"admin:ManageUsers","Resource":"*"}]}"#,
+        );
+        iam.set_policy("helper", Some(&grant.unwrap())).await.unwrap();
+        let (helper, boss) = (login(&app, "helper").await, login(&app, "boss").await);
+
+        // Outside the grant.
+        assert_eq!(send(&app, &helper, "GET", "/api/audit", "").await, StatusCode::FORBIDDEN);
+        let res = send(&app, &helper, "POST", "/api/policies/compile", r#"{"rules":[]}"#).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        // Inside it, for ordinary users.
+        let password = r#"{"password":"password456"}"#;
+        let res = send(&app, &helper, "PUT", "/api/users/staff/password", password).await;
+        assert_eq!(res, StatusCode::OK);
+
+        // Administrators are out of reach...
+        let res = send(&app, &helper, "PUT", "/api/users/boss/password", password).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let res = send(&app, &helper, "DELETE", "/api/users/boss", "").await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let res = send(&app, &helper, "PUT", "/api/users/boss/groups", r#"{"groups":[]}"#).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        assert!(iam.is_admin("boss"));
+
+        // ...and so is the admin group, for anyone, the delegate included.
+        let into_admins = r#"{"groups":["admin"]}"#;
+        for target in ["staff", "helper"] {
+            let uri = format!("/api/users/{target}/groups");
+            let res = send(&app, &helper, "PUT", &uri, into_admins).await;
+            assert_eq!(res, StatusCode::FORBIDDEN, "{target}");
+            assert!(!iam.is_admin(target));
+        }
+        let res = send(&app, &boss, "PUT", "/api/users/staff/groups", into_admins).await;
+        assert_eq!(res, StatusCode::OK);
+        assert!(iam.is_admin("staff"));
+    }
+
+    #[tokio::test]
+    async fn a_delegate_grants_and_borrows_nothing_it_does_not_hold() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, iam) = console(&tmp).await;
+        for user in ["polly", "helper", "staff", "reader"] {
+            iam.create_user(user, "password123").await.unwrap();
+        }
+        let policy = |json: &str| serde_json::from_str(json).unwrap();
+        let docs = r#"{"Statement":[
+            {"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::docs/*"}
+        ]}"#;
+        let everything = r#"{"Statement":[{"Effect":"Allow","Action":"s3:*","Resource":"*"}]}"#;
+        let polly_grant = policy(
+            r#"{"Statement":[
+                {"Effect":"Allow","Action":"admin:ManagePolicies","Resource":"*"},
+                {"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::docs/*"}
+            ]}"#,
+        );
+        iam.set_policy("polly", Some(&polly_grant)).await.unwrap();
+        let helper_grant = policy(
+            r#"{"Statement":[{"Effect":"Allow","Action":"admin:ManageUsers","Resource":"*"}]}"#,
+        );
+        iam.set_policy("helper", Some(&helper_grant)).await.unwrap();
+        iam.set_policy("reader", Some(&policy(everything))).await.unwrap();
+        iam.create_group("team", None).await.unwrap();
+        iam.create_group("crew", Some(&policy(everything))).await.unwrap();
+        let team = iam.resolve_groups(&["team".to_string()]).await.unwrap();
+        iam.set_user_groups("polly", &team).await.unwrap();
+        let (polly, helper) = (login(&app, "polly").await, login(&app, "helper").await);
+
+        // A policy delegate hands out what it holds, to others only.
+        let res = send(&app, &polly, "PUT", "/api/users/staff/policy", everything).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let res = send(&app, &polly, "PUT", "/api/users/staff/policy", docs).await;
+        assert_eq!(res, StatusCode::OK);
+        let res = send(&app, &polly, "PUT", "/api/users/polly/policy", docs).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let res = send(&app, &polly, "PUT", "/api/groups/team/policy", docs).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let admin_all = r#"{"Statement":[{"Effect":"Allow","Action":"admin:*","Resource":"*"}]}"#;
+        let res = send(&app, &polly, "PUT", "/api/groups/crew/policy", admin_all).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        assert_eq!(iam.policy_for("polly"), Some(polly_grant));
+
+        // A user delegate manages other people's keys but issues only its own.
+        assert_eq!(send(&app, &helper, "GET", "/api/users/staff/keys", "").await, StatusCode::OK);
+        let res = send(&app, &helper, "POST", "/api/users/staff/keys", "").await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        assert!(iam.list_access_keys("staff").await.unwrap().is_empty());
+        let res = send(&app, &helper, "POST", "/api/users/helper/keys", "").await;
+        assert_eq!(res, StatusCode::OK);
+        // Nor does it sign in as, or enrol into, more than it holds.
+        let password = r#"{"password":"password456"}"#;
+        let res = send(&app, &helper, "PUT", "/api/users/reader/password", password).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let into_crew = r#"{"groups":["crew"]}"#;
+        let res = send(&app, &helper, "PUT", "/api/users/staff/groups", into_crew).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        let res = send(&app, &helper, "PUT", "/api/users/helper/groups", into_crew).await;
+        assert_eq!(res, StatusCode::FORBIDDEN);
+        assert!(iam.groups_for("staff").is_empty());
+    }
+
+    #[tokio::test]
+    async fn secure_transport_follows_the_console_connection() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, iam) = console(&tmp).await;
+        iam.create_user("ops", "password123").await.unwrap();
+        let grant = serde_json::from_str(
+            r#"{"Statement":[{"Effect":"Allow","Action":"admin:ViewTasks","Resource":"*",
+                "Condition":{"Bool":{"aws:SecureTransport":"true"}}}]}"#,
+        );
+        iam.set_policy("ops", Some(&grant.unwrap())).await.unwrap();
+        let ops = login(&app, "ops").await;
+        assert_eq!(send(&app, &ops, "GET", "/api/tasks", "").await, StatusCode::FORBIDDEN);
+        let request = Request::builder()
+            .uri("/api/tasks")
+            .header(header::COOKIE, &ops)
+            .extension(crate::server::tls::TlsConnection { client_certificate: false })
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[cfg(test)]
+mod preview_tests {
+    use super::{previews_inline, validate_user_meta};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn only_inert_types_render_inline() {
+        assert!(previews_inline("image/png"));
+        assert!(previews_inline("Video/MP4"));
+        assert!(previews_inline("application/pdf"));
+        assert!(previews_inline("text/plain; charset=utf-8"));
+        assert!(!previews_inline("image/svg+xml"));
+        assert!(!previews_inline("text/html"));
+        assert!(!previews_inline("application/xhtml+xml"));
+    }
+
+    #[test]
+    fn user_meta_is_held_to_header_rules() {
+        let meta = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>()
+        };
+        let ok = validate_user_meta(meta(&[("X-Amz-Meta-Owner", "ops"), ("Team", "a")])).unwrap();
+        assert_eq!(ok, meta(&[("owner", "ops"), ("team", "a")]));
+        assert!(validate_user_meta(meta(&[("", "x")])).is_err());
+        assert!(validate_user_meta(meta(&[("has space", "x")])).is_err());
+        assert!(validate_user_meta(meta(&[("k", "line\nbreak")])).is_err());
+        assert!(validate_user_meta(meta(&[("k", &"x".repeat(4096))])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod token_tests {
+    use super::{bearer_token, token_scope};
+    use crate::server::policy::{is_authorized, Requirement};
+    use axum::http::{header, HeaderMap, HeaderValue};
+
+    #[test]
+    fn token_scope_allows_only_its_actions_everywhere() {
+        let scope = token_scope(&["admin:ManageUsers".into(), "s3:Get*".into()]);
+        assert!(scope.validate().is_ok());
+        assert!(is_authorized(&scope, &[Requirement::admin("admin:ManageUsers")]));
+        assert!(!is_authorized(&scope, &[Requirement::admin("admin:ManageBuckets")]));
+        assert!(is_authorized(&scope, &[Requirement::object("s3:GetObject", "b", "k")]));
+        assert!(!is_authorized(&scope, &[Requirement::object("s3:PutObject", "b", "k")]));
+        assert!(token_scope(&["admin:ManageUser".into()]).validate().is_err());
+    }
+
+    #[test]
+    fn bearer_token_is_read_from_the_authorization_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer rs3t_a_b"));
+        assert_eq!(bearer_token(&headers), Some("rs3t_a_b"));
+    }
+}
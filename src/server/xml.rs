@@ -1,5 +1,5 @@
 use crate::storage::index::ListPage;
-use crate::storage::metadata::{quote_etag, PartMeta, UploadMeta};
+use crate::storage::metadata::{quote_etag, BucketLogging, PartMeta, UploadMeta};
 use crate::storage::store::ObjectVersionEntry;
 use crate::storage::time::iso_utc_ms;
 
@@ -369,6 +369,23 @@ pub fn delete_objects_xml(results: &[DeleteObjectResult], quiet: bool) -> String
     )
 }
 
+/// `GetBucketLogging` body. An empty `BucketLoggingStatus` means logging is
+/// disabled, exactly as S3 reports it.
+pub fn bucket_logging_status_xml(logging: Option<&BucketLogging>) -> String {
+    let enabled = logging
+        .map(|logging| {
+            format!(
+                "<LoggingEnabled><TargetBucket>{}</TargetBucket><TargetPrefix>{}</TargetPrefix></LoggingEnabled>",
+                escape_xml(&logging.target_bucket),
+                escape_xml(&logging.target_prefix),
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><BucketLoggingStatus xmlns="http://s3.amazonaws.com/doc/2006-03-01/">{enabled}</BucketLoggingStatus>"#
+    )
+}
+
 pub(crate) fn escape_xml(value: &str) -> String {
     let mut out = String::with_capacity(value.len());
     for ch in value.chars() {
@@ -422,6 +439,8 @@ mod tests {
                     size: 3,
                     etag: "abc".to_string(),
                     last_modified_ms: 0,
+                    chunks: Vec::new(),
+                    inline: None,
                 }],
                 common_prefixes: vec!["a/b/".to_string()],
                 is_truncated: true,
@@ -453,6 +472,8 @@ mod tests {
                     size: 3,
                     etag: "abc".to_string(),
                     last_modified_ms: 0,
+                    chunks: Vec::new(),
+                    inline: None,
                 }],
                 common_prefixes: vec!["a+b/".to_string()],
                 is_truncated: true,
@@ -484,6 +505,7 @@ mod tests {
             content_type: "text/plain".to_string(),
             content_encoding: None,
             content_language: None,
+            cache_control: None,
             storage_class: "STANDARD".to_string(),
             user_meta: std::collections::BTreeMap::new(),
             parts: vec![],
@@ -511,6 +533,7 @@ mod tests {
             content_type: "application/octet-stream".to_string(),
             content_encoding: None,
             content_language: None,
+            cache_control: None,
             storage_class: "STANDARD".to_string(),
             user_meta: std::collections::BTreeMap::new(),
         }];
@@ -0,0 +1,785 @@
+//! Incremental backup archives: the objects committed or deleted in each
+//! bucket since a previous checkpoint, as one self-describing stream, and the
+//! replay that applies such a stream to another data root.
+//!
+//! The framing is the raw-dump one ([`rawdb`](super::rawdb)) with its own
+//! magic:
+//!
+//! ```text
+//! [ "RS3IB\x01" ]                          (6-byte magic header)
+//! [ varint len ][ Record ]                  (length-delimited protobuf)
+//! ...
+//! [ 32-byte TRAILER ]                       (end-of-stream sentinel)
+//! ```
+//!
+//! Records, in stream order:
+//!
+//! | kind         | carries                                                  |
+//! |--------------|----------------------------------------------------------|
+//! | `HEADER`     | JSON: format version, creation time, the `since` cursor  |
+//! | `BUCKET`     | bucket name and its `bucket.json`; `full` if dumped whole|
+//! | `OBJECT`     | key and the object's `meta.json`                         |
+//! | `CHUNK`      | part number and up to 1 MiB of that part file, in order  |
+//! | `DELETE`     | a key that no longer exists                              |
+//! | `CHECKPOINT` | JSON [`BackupCheckpoint`] to pass as the next `since`    |
+//!
+//! ## What goes in
+//!
+//! Every commit appends the key to its bucket's change log (see
+//! [`ObjectIndex`](super::index::ObjectIndex)). A bucket whose cursor in
+//! `since` is from the same log epoch and not below the pruned floor is sent
+//! incrementally: each key changed after the cursor, once, in its current
+//! state — an `OBJECT` if it exists, a `DELETE` if not. Any other bucket is
+//! sent whole and marked `full`, and applying it also removes target keys the
+//! archive does not mention. Cursors are read before the objects, so a write
+//! racing the backup at worst appears in two archives, never in none.
+//!
+//! Part files travel as stored: a compressed part stays compressed, and the
+//! `meta.json` that precedes it says so.
+//!
+//! Bucket deletions are not carried: a bucket that disappears from the source
+//! simply stops appearing in archives.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use super::errors::{Result, StorageError};
+use super::index::ChangeCursor;
+use super::metadata::{BucketMeta, ObjectMeta};
+use super::rawdb::TRAILER;
+use super::staging::new_staging_id;
+use super::store::LocalObjectStore;
+use super::time::now_ms;
+
+/// File-type marker: `RS3IB` + format version byte.
+const MAGIC: &[u8] = b"RS3IB\x01";
+
+const FORMAT_VERSION: u32 = 1;
+
+const KIND_HEADER: u32 = 1;
+const KIND_BUCKET: u32 = 2;
+const KIND_OBJECT: u32 = 3;
+const KIND_CHUNK: u32 = 4;
+const KIND_DELETE: u32 = 5;
+const KIND_CHECKPOINT: u32 = 6;
+
+const CHUNK: u64 = 1024 * 1024;
+
+/// Largest record a reader accepts; a chunk plus generous framing.
+const MAX_RECORD: usize = 4 * 1024 * 1024;
+
+/// Rows and change-log entries read per pass.
+const PAGE: usize = 1000;
+
+#[derive(Clone, PartialEq, Message)]
+struct Record {
+    #[prost(uint32, tag = "1")]
+    kind: u32,
+    #[prost(string, tag = "2")]
+    bucket: String,
+    #[prost(string, tag = "3")]
+    key: String,
+    #[prost(bytes = "vec", tag = "4")]
+    body: Vec<u8>,
+    #[prost(uint32, tag = "5")]
+    part: u32,
+    #[prost(bool, tag = "6")]
+    full: bool,
+}
+
+/// Where each bucket's change log stood when an archive was cut. Written at
+/// the end of every archive, and passed back as `since` to get the next one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupCheckpoint {
+    #[serde(default)]
+    pub v: u32,
+    #[serde(default)]
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub buckets: BTreeMap<String, ChangeCursor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    v: u32,
+    created_at_ms: i64,
+    #[serde(default)]
+    since: Option<BackupCheckpoint>,
+}
+
+/// What a backup wrote.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BackupSummary {
+    pub buckets: usize,
+    /// Buckets sent whole: new, or their cursor could not be continued.
+    pub full_buckets: Vec<String>,
+    pub objects: u64,
+    pub deletes: u64,
+    pub bytes: u64,
+}
+
+/// What applying an archive did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ApplySummary {
+    pub buckets: usize,
+    pub objects: u64,
+    /// Objects the target already held at the archived version.
+    pub unchanged: u64,
+    pub deletes: u64,
+    pub bytes: u64,
+}
+
+async fn write_record<W: AsyncWrite + Unpin>(w: &mut W, record: &Record) -> Result<()> {
+    let buf = record.encode_length_delimited_to_vec();
+    w.write_all(&buf).await?;
+    Ok(())
+}
+
+/// Reads the next record, or `None` at the end of the body.
+async fn read_record<R: AsyncRead + Unpin>(r: &mut R) -> Result<Option<Record>> {
+    let mut prefix = Vec::with_capacity(10);
+    loop {
+        let mut byte = [0u8; 1];
+        if r.read(&mut byte).await? == 0 {
+            if prefix.is_empty() {
+                return Ok(None);
+            }
+            return Err(corrupt("record length cut short"));
+        }
+        prefix.push(byte[0]);
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        if prefix.len() >= 10 {
+            return Err(corrupt("bad record length"));
+        }
+    }
+    let len = prost::decode_length_delimiter(prefix.as_slice())
+        .map_err(|e| corrupt(&format!("bad record length: {e}")))?;
+    if len > MAX_RECORD {
+        return Err(corrupt(&format!(
+            "record of {len} bytes exceeds the {MAX_RECORD}-byte limit"
+        )));
+    }
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)
+        .await
+        .map_err(|e| corrupt(&format!("record cut short: {e}")))?;
+    Record::decode(body.as_slice())
+        .map(Some)
+        .map_err(|e| corrupt(&format!("protobuf decode: {e}")))
+}
+
+fn corrupt(detail: &str) -> StorageError {
+    StorageError::Io(format!("invalid backup archive: {detail}"))
+}
+
+impl LocalObjectStore {
+    /// Writes an archive of every bucket's changes since `since` (everything,
+    /// when `None`) to `w`, and returns the checkpoint to pass next time. The
+    /// checkpoint is also the archive's last record.
+    pub async fn write_backup<W: AsyncWrite + Unpin>(
+        &self,
+        since: Option<&BackupCheckpoint>,
+        w: &mut W,
+    ) -> Result<(BackupCheckpoint, BackupSummary)> {
+        let header = Header {
+            v: FORMAT_VERSION,
+            created_at_ms: now_ms(),
+            since: since.cloned(),
+        };
+        w.write_all(MAGIC).await?;
+        write_record(
+            w,
+            &Record {
+                kind: KIND_HEADER,
+                body: serde_json::to_vec(&header)?,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let mut checkpoint = BackupCheckpoint {
+            v: FORMAT_VERSION,
+            created_at_ms: header.created_at_ms,
+            buckets: BTreeMap::new(),
+        };
+        let mut summary = BackupSummary::default();
+        for (bucket, meta) in self.list_buckets().await? {
+            let index = self.index(&bucket).await?;
+            let cursor = index.change_cursor().await?;
+            let floor = index.change_floor().await?;
+            let prior = since
+                .and_then(|s| s.buckets.get(&bucket))
+                .filter(|p| p.epoch == cursor.epoch && p.seq >= floor && p.seq <= cursor.seq);
+            write_record(
+                w,
+                &Record {
+                    kind: KIND_BUCKET,
+                    bucket: bucket.clone(),
+                    body: serde_json::to_vec(&meta)?,
+                    full: prior.is_none(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            summary.buckets += 1;
+            match prior {
+                Some(prior) => {
+                    let mut keys = BTreeSet::new();
+                    let mut after = prior.seq;
+                    while after < cursor.seq {
+                        let page = index.changes_after(after, PAGE).await?;
+                        let Some(&(last, _)) = page.last() else {
+                            break;
+                        };
+                        keys.extend(
+                            page.into_iter()
+                                .take_while(|(seq, _)| *seq <= cursor.seq)
+                                .map(|(_, key)| key),
+                        );
+                        after = last;
+                    }
+                    for key in keys {
+                        self.backup_key(&bucket, &key, w, &mut summary).await?;
+                    }
+                }
+                None => {
+                    summary.full_buckets.push(bucket.clone());
+                    let mut after: Option<String> = None;
+                    loop {
+                        let page = index
+                            .all_entries_after(after.as_deref(), PAGE as i64)
+                            .await?;
+                        let Some(last) = page.last() else {
+                            break;
+                        };
+                        after = Some(last.object_key.clone());
+                        for row in &page {
+                            self.backup_key(&bucket, &row.object_key, w, &mut summary)
+                                .await?;
+                        }
+                    }
+                }
+            }
+            checkpoint.buckets.insert(bucket, cursor);
+        }
+
+        write_record(
+            w,
+            &Record {
+                kind: KIND_CHECKPOINT,
+                body: serde_json::to_vec(&checkpoint)?,
+                ..Default::default()
+            },
+        )
+        .await?;
+        w.write_all(&TRAILER).await?;
+        w.flush().await?;
+        Ok((checkpoint, summary))
+    }
+
+    /// Sends `key` as it is now: its metadata and part bytes, or a delete
+    /// when it no longer exists.
+    async fn backup_key<W: AsyncWrite + Unpin>(
+        &self,
+        bucket: &str,
+        key: &str,
+        w: &mut W,
+        summary: &mut BackupSummary,
+    ) -> Result<()> {
+        let object = match self.read_object(bucket, key).await {
+            Ok(object) => object,
+            Err(StorageError::ObjectNotFound { .. }) => {
+                summary.deletes += 1;
+                return write_record(
+                    w,
+                    &Record {
+                        kind: KIND_DELETE,
+                        bucket: bucket.to_string(),
+                        key: key.to_string(),
+                        ..Default::default()
+                    },
+                )
+                .await;
+            }
+            Err(err) => return Err(err),
+        };
+        write_record(
+            w,
+            &Record {
+                kind: KIND_OBJECT,
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                body: serde_json::to_vec(&object.meta)?,
+                ..Default::default()
+            },
+        )
+        .await?;
+        let mut buf = Vec::new();
+        for part in &object.meta.parts {
+            let mut file = object.open_stored_part(part).await?;
+            let mut remaining = part.stored_size();
+            while remaining > 0 {
+                let n = remaining.min(CHUNK) as usize;
+                buf.resize(n, 0);
+                file.read_exact(&mut buf).await?;
+                write_record(
+                    w,
+                    &Record {
+                        kind: KIND_CHUNK,
+                        part: u32::from(part.number),
+                        body: std::mem::take(&mut buf),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                remaining -= n as u64;
+            }
+        }
+        summary.objects += 1;
+        summary.bytes += object.meta.size;
+        Ok(())
+    }
+
+    /// Replays the archive at `path` into this store: creates missing
+    /// buckets, takes over their CORS rules and quota, publishes every
+    /// archived object and removes every archived delete. Objects already
+    /// present at the archived version are skipped, so applying an archive
+    /// twice is harmless. The archive's completeness trailer is checked
+    /// before anything is touched. Returns the checkpoint the archive ends
+    /// with.
+    pub async fn apply_backup(&self, path: &Path) -> Result<(BackupCheckpoint, ApplySummary)> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let len = file.metadata().await?.len();
+        let framing = (MAGIC.len() + TRAILER.len()) as u64;
+        if len < framing {
+            return Err(corrupt("too short"));
+        }
+        let mut magic = vec![0u8; MAGIC.len()];
+        file.read_exact(&mut magic).await?;
+        if magic != MAGIC {
+            return Err(corrupt("bad magic header"));
+        }
+        let mut trailer = [0u8; 32];
+        file.seek(std::io::SeekFrom::Start(len - TRAILER.len() as u64))
+            .await?;
+        file.read_exact(&mut trailer).await?;
+        if trailer != TRAILER {
+            return Err(corrupt(
+                "end-of-stream marker missing or corrupt (truncated?)",
+            ));
+        }
+        file.seek(std::io::SeekFrom::Start(MAGIC.len() as u64))
+            .await?;
+        let mut r = tokio::io::BufReader::new(file).take(len - framing);
+
+        let header = match read_record(&mut r).await? {
+            Some(record) if record.kind == KIND_HEADER => {
+                serde_json::from_slice::<Header>(&record.body)?
+            }
+            _ => return Err(corrupt("missing header")),
+        };
+        if header.v > FORMAT_VERSION {
+            return Err(corrupt(&format!(
+                "format v{}, newer than this build understands (v{FORMAT_VERSION})",
+                header.v
+            )));
+        }
+
+        let mut replay = Replay {
+            store: self,
+            bucket: None,
+            pending: None,
+            summary: ApplySummary::default(),
+        };
+        let result = replay.run(&mut r).await;
+        // A failed replay leaves at most one staged object behind.
+        if let Some(pending) = replay.pending.take() {
+            let _ = tokio::fs::remove_dir_all(&pending.staging_dir).await;
+        }
+        let checkpoint = result?;
+        Ok((checkpoint, replay.summary))
+    }
+}
+
+/// The bucket being replayed; `seen` tracks keys when the archive holds it
+/// whole, so the rest can be removed at its end.
+struct ReplayBucket {
+    name: String,
+    seen: Option<HashSet<String>>,
+}
+
+/// An object whose chunks are being staged. `staging_id` is `None` when the
+/// target already holds this version and the chunks are only skipped over.
+struct PendingObject {
+    bucket: String,
+    key: String,
+    meta: ObjectMeta,
+    staging_id: Option<String>,
+    staging_dir: std::path::PathBuf,
+}
+
+struct Replay<'a> {
+    store: &'a LocalObjectStore,
+    bucket: Option<ReplayBucket>,
+    pending: Option<PendingObject>,
+    summary: ApplySummary,
+}
+
+impl Replay<'_> {
+    async fn run<R: AsyncRead + Unpin>(&mut self, r: &mut R) -> Result<BackupCheckpoint> {
+        while let Some(record) = read_record(r).await? {
+            if record.kind != KIND_CHUNK {
+                self.finish_object().await?;
+            }
+            match record.kind {
+                KIND_BUCKET => {
+                    self.finish_bucket().await?;
+                    self.begin_bucket(record).await?;
+                }
+                KIND_OBJECT => self.begin_object(record).await?,
+                KIND_CHUNK => self.append_chunk(record).await?,
+                KIND_DELETE => {
+                    let bucket = self.current_bucket(&record.bucket)?;
+                    if let Some(seen) = &mut bucket.seen {
+                        seen.insert(record.key.clone());
+                    }
+                    self.store
+                        .delete_object(&record.bucket, &record.key)
+                        .await?;
+                    self.summary.deletes += 1;
+                }
+                KIND_CHECKPOINT => {
+                    self.finish_bucket().await?;
+                    if read_record(r).await?.is_some() {
+                        return Err(corrupt("records after the checkpoint"));
+                    }
+                    return Ok(serde_json::from_slice(&record.body)?);
+                }
+                kind => return Err(corrupt(&format!("unknown record kind {kind}"))),
+            }
+        }
+        Err(corrupt("missing checkpoint"))
+    }
+
+    fn current_bucket(&mut self, name: &str) -> Result<&mut ReplayBucket> {
+        match &mut self.bucket {
+            Some(bucket) if bucket.name == name => Ok(bucket),
+            _ => Err(corrupt(&format!(
+                "record for bucket {name} outside its section"
+            ))),
+        }
+    }
+
+    async fn begin_bucket(&mut self, record: Record) -> Result<()> {
+        let meta: BucketMeta = serde_json::from_slice(&record.body)?;
+        let name = record.bucket;
+        if !self.store.bucket_exists(&name).await {
+            self.store.create_bucket(&name).await?;
+        }
+        self.store.set_bucket_cors(&name, meta.cors).await?;
+        self.store.set_bucket_quota(&name, meta.quota).await?;
+        self.summary.buckets += 1;
+        self.bucket = Some(ReplayBucket {
+            name,
+            seen: record.full.then(HashSet::new),
+        });
+        Ok(())
+    }
+
+    /// Ends a wholly archived bucket by removing the keys it did not list.
+    async fn finish_bucket(&mut self) -> Result<()> {
+        let Some(ReplayBucket {
+            name,
+            seen: Some(seen),
+        }) = self.bucket.take()
+        else {
+            return Ok(());
+        };
+        let index = self.store.index(&name).await?;
+        let mut after: Option<String> = None;
+        loop {
+            let page = index
+                .all_entries_after(after.as_deref(), PAGE as i64)
+                .await?;
+            let Some(last) = page.last() else {
+                return Ok(());
+            };
+            after = Some(last.object_key.clone());
+            for row in page {
+                if !seen.contains(&row.object_key) {
+                    self.store.delete_object(&name, &row.object_key).await?;
+                    self.summary.deletes += 1;
+                }
+            }
+        }
+    }
+
+    async fn begin_object(&mut self, record: Record) -> Result<()> {
+        let meta: ObjectMeta = serde_json::from_slice(&record.body)?;
+        let bucket = self.current_bucket(&record.bucket)?;
+        if let Some(seen) = &mut bucket.seen {
+            seen.insert(record.key.clone());
+        }
+        for part in &meta.parts {
+            if part.file != format!("part.{}", part.number) {
+                return Err(corrupt(&format!("bad part file name {:?}", part.file)));
+            }
+        }
+        let current = self
+            .store
+            .index(&record.bucket)
+            .await?
+            .get(&record.key)
+            .await?;
+        let unchanged = current.is_some_and(|row| {
+            row.etag == meta.etag && row.last_modified_ms == meta.last_modified_ms
+        });
+        let staging_id = new_staging_id(now_ms());
+        let staging_dir = self
+            .store
+            .layout()
+            .put_staging_dir(&record.bucket, &staging_id)?;
+        if !unchanged {
+            let object_dir = staging_dir.join("object");
+            tokio::fs::create_dir_all(&object_dir).await?;
+            for part in &meta.parts {
+                tokio::fs::File::create(object_dir.join(&part.file)).await?;
+            }
+            tokio::fs::write(
+                object_dir.join("meta.json"),
+                serde_json::to_vec_pretty(&meta)?,
+            )
+            .await?;
+        }
+        self.pending = Some(PendingObject {
+            bucket: record.bucket,
+            key: record.key,
+            meta,
+            staging_id: (!unchanged).then_some(staging_id),
+            staging_dir,
+        });
+        Ok(())
+    }
+
+    async fn append_chunk(&mut self, record: Record) -> Result<()> {
+        let Some(pending) = &self.pending else {
+            return Err(corrupt("chunk outside an object"));
+        };
+        let Some(part) = pending
+            .meta
+            .parts
+            .iter()
+            .find(|p| u32::from(p.number) == record.part)
+        else {
+            return Err(corrupt(&format!("chunk for unknown part {}", record.part)));
+        };
+        if pending.staging_id.is_none() {
+            return Ok(());
+        }
+        let path = pending.staging_dir.join("object").join(&part.file);
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(&record.body).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    /// Publishes the staged object, after checking every part arrived whole.
+    async fn finish_object(&mut self) -> Result<()> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        let Some(staging_id) = &pending.staging_id else {
+            self.summary.unchanged += 1;
+            return Ok(());
+        };
+        let object_dir = pending.staging_dir.join("object");
+        for part in &pending.meta.parts {
+            let size = tokio::fs::metadata(object_dir.join(&part.file))
+                .await?
+                .len();
+            if size != part.stored_size() {
+                let _ = tokio::fs::remove_dir_all(&pending.staging_dir).await;
+                return Err(corrupt(&format!(
+                    "{}/{} part {} has {size} bytes, expected {}",
+                    pending.bucket,
+                    pending.key,
+                    part.number,
+                    part.stored_size()
+                )));
+            }
+        }
+        let size = pending.meta.size;
+        self.store
+            .import_object(&pending.bucket, &pending.key, staging_id, pending.meta)
+            .await?;
+        self.summary.objects += 1;
+        self.summary.bytes += size;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn backup(
+        store: &LocalObjectStore,
+        since: Option<&BackupCheckpoint>,
+        path: &Path,
+    ) -> (BackupCheckpoint, BackupSummary) {
+        let mut file = tokio::fs::File::create(path).await.unwrap();
+        store.write_backup(since, &mut file).await.unwrap()
+    }
+
+    async fn body(store: &LocalObjectStore, bucket: &str, key: &str) -> Option<Vec<u8>> {
+        let object = match store.read_object(bucket, key).await {
+            Ok(object) => object,
+            Err(StorageError::ObjectNotFound { .. }) => return None,
+            Err(err) => panic!("{err}"),
+        };
+        let mut out = Vec::new();
+        for part in &object.meta.parts {
+            out.extend(
+                tokio::fs::read(object.object_dir.join(&part.file))
+                    .await
+                    .unwrap(),
+            );
+        }
+        Some(out)
+    }
+
+    #[tokio::test]
+    async fn incremental_archives_replay_changes_and_deletes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = LocalObjectStore::new(tmp.path().join("source"));
+        let target = LocalObjectStore::new(tmp.path().join("target"));
+        source.create_bucket("bucket").await.unwrap();
+        let big = vec![7u8; CHUNK as usize + 10];
+        source
+            .put_object("bucket", "a", b"one", None, None, false)
+            .await
+            .unwrap();
+        source
+            .put_object("bucket", "big", &big, None, None, false)
+            .await
+            .unwrap();
+        source
+            .put_object("bucket", "gone", b"x", None, None, false)
+            .await
+            .unwrap();
+        source
+            .put_object("bucket", "empty", b"", None, None, false)
+            .await
+            .unwrap();
+
+        let first = tmp.path().join("first.rs3b");
+        let (checkpoint, summary) = backup(&source, None, &first).await;
+        assert_eq!(summary.full_buckets, vec!["bucket".to_string()]);
+        assert_eq!(summary.objects, 4);
+        let (applied, report) = target.apply_backup(&first).await.unwrap();
+        assert_eq!(applied, checkpoint);
+        assert_eq!(report.objects, 4);
+        assert_eq!(body(&target, "bucket", "big").await.unwrap(), big);
+        assert_eq!(body(&target, "bucket", "empty").await.unwrap(), b"");
+        let src_lm = source
+            .read_object("bucket", "a")
+            .await
+            .unwrap()
+            .meta
+            .last_modified_ms;
+        let dst_lm = target
+            .read_object("bucket", "a")
+            .await
+            .unwrap()
+            .meta
+            .last_modified_ms;
+        assert_eq!(src_lm, dst_lm);
+
+        source
+            .put_object("bucket", "a", b"two", None, None, false)
+            .await
+            .unwrap();
+        source
+            .put_object("bucket", "a", b"three", None, None, false)
+            .await
+            .unwrap();
+        source.delete_object("bucket", "gone").await.unwrap();
+        source
+            .put_object("bucket", "new", b"n", None, None, false)
+            .await
+            .unwrap();
+        let second = tmp.path().join("second.rs3b");
+        let (_, summary) = backup(&source, Some(&checkpoint), &second).await;
+        assert!(summary.full_buckets.is_empty());
+        assert_eq!((summary.objects, summary.deletes), (2, 1));
+
+        let (_, report) = target.apply_backup(&second).await.unwrap();
+        assert_eq!((report.objects, report.deletes), (2, 1));
+        assert_eq!(body(&target, "bucket", "a").await.unwrap(), b"three");
+        assert_eq!(body(&target, "bucket", "new").await.unwrap(), b"n");
+        assert!(body(&target, "bucket", "gone").await.is_none());
+        // Replaying again changes nothing.
+        let (_, report) = target.apply_backup(&second).await.unwrap();
+        assert_eq!((report.objects, report.unchanged), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn pruned_log_falls_back_to_a_full_bucket() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = LocalObjectStore::new(tmp.path().join("source"));
+        let target = LocalObjectStore::new(tmp.path().join("target"));
+        source.create_bucket("bucket").await.unwrap();
+        source
+            .put_object("bucket", "keep", b"k", None, None, false)
+            .await
+            .unwrap();
+        source
+            .put_object("bucket", "drop", b"d", None, None, false)
+            .await
+            .unwrap();
+        let first = tmp.path().join("first.rs3b");
+        let (checkpoint, _) = backup(&source, None, &first).await;
+        target.apply_backup(&first).await.unwrap();
+
+        source.delete_object("bucket", "drop").await.unwrap();
+        let index = source.index("bucket").await.unwrap();
+        index.prune_changes(now_ms() + 1).await.unwrap();
+        let second = tmp.path().join("second.rs3b");
+        let (_, summary) = backup(&source, Some(&checkpoint), &second).await;
+        assert_eq!(summary.full_buckets, vec!["bucket".to_string()]);
+        assert_eq!((summary.objects, summary.deletes), (1, 0));
+
+        // The whole bucket arrives, and what it no longer holds is removed.
+        let (_, report) = target.apply_backup(&second).await.unwrap();
+        assert_eq!((report.unchanged, report.deletes), (1, 1));
+        assert!(body(&target, "bucket", "drop").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn truncated_archive_is_rejected_before_applying() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = LocalObjectStore::new(tmp.path().join("source"));
+        let target = LocalObjectStore::new(tmp.path().join("target"));
+        source.create_bucket("bucket").await.unwrap();
+        source
+            .put_object("bucket", "a", b"one", None, None, false)
+            .await
+            .unwrap();
+        let path = tmp.path().join("a.rs3b");
+        backup(&source, None, &path).await;
+        let data = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() - 5]).unwrap();
+        let err = target.apply_backup(&path).await.unwrap_err();
+        assert!(err.to_string().contains("end-of-stream"), "{err}");
+        assert!(!target.bucket_exists("bucket").await);
+    }
+}
@@ -0,0 +1,188 @@
+//! The per-bucket content-addressed chunk store behind deduplication.
+//!
+//! ```text
+//! buckets/<bucket>/chunks/<id[0..2]>/<id>
+//! ```
+//!
+//! A chunk id is the SHA-256 of a part file's bytes *as stored* (after any
+//! compression), so two parts share a chunk exactly when their files are
+//! byte-identical. Sharing is done with hardlinks: a published part file in a
+//! dedup bucket is another name for its chunk's inode. Readers therefore never
+//! know a part is shared, and removing any one name — retiring a blob dir to
+//! trash, purging the trash, collecting a chunk — can never take data away
+//! from another.
+//!
+//! Who references a chunk is recorded in the bucket index (see
+//! [`ObjectIndex`](super::index::ObjectIndex)); the store only holds the
+//! bytes. A chunk file is collected once the index holds no reference to it,
+//! no other name links its inode (so nothing in staging or trash still shares
+//! it), and its inode has not changed for a grace period — linking a new part
+//! to a chunk updates the inode's ctime, which covers the window between
+//! interning a part and committing the row that references it.
+
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::errors::{Result, StorageError};
+
+/// Where chunk `id` lives under a bucket's chunk store.
+pub(crate) fn chunk_path(chunks_dir: &Path, id: &str) -> PathBuf {
+    chunks_dir.join(&id[..2]).join(id)
+}
+
+fn is_chunk_id(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+async fn hash_file(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 256 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Makes the part file at `part_path` share the chunk matching its content:
+/// an existing chunk replaces the file (same bytes, one inode), otherwise the
+/// file becomes the chunk. Returns the chunk id, or `None` when the part
+/// cannot be linked into the store (e.g. it sits on another filesystem) and
+/// stays unshared.
+pub(crate) async fn intern_part(chunks_dir: &Path, part_path: &Path) -> Result<Option<String>> {
+    let id = hash_file(part_path).await?;
+    let chunk = chunk_path(chunks_dir, &id);
+    let part = part_path.to_path_buf();
+    let linked = tokio::task::spawn_blocking(move || link_part(&chunk, &part))
+        .await
+        .map_err(|err| StorageError::Io(format!("chunk link task panicked: {err}")))??;
+    Ok(linked.then_some(id))
+}
+
+fn link_part(chunk: &Path, part: &Path) -> Result<bool> {
+    let name = part
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let tmp = part.with_file_name(format!(".{name}.chunk"));
+    // Two rounds cover losing a race to a concurrent publish of the same
+    // content; a third would mean the store is changing under us.
+    for _ in 0..2 {
+        let _ = std::fs::remove_file(&tmp);
+        match std::fs::hard_link(chunk, &tmp) {
+            Ok(()) => {
+                std::fs::rename(&tmp, part)?;
+                // Renaming one name of an inode over another is a no-op that
+                // leaves the source in place (the part was already the chunk).
+                let _ = std::fs::remove_file(&tmp);
+                return Ok(true);
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => {
+                log::warn!("chunk link failed chunk={} error={err}", chunk.display());
+                return Ok(false);
+            }
+        }
+        if let Some(parent) = chunk.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match std::fs::hard_link(part, chunk) {
+            Ok(()) => return Ok(true),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                log::warn!("chunk store failed chunk={} error={err}", chunk.display());
+                return Ok(false);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Chunk ids under `chunks_dir` that nothing but the store links and whose
+/// inode has been unchanged for at least `grace_ms` — the candidates the
+/// index is asked about before a collection.
+pub(crate) fn idle_chunks(chunks_dir: &Path, now_ms: i64, grace_ms: i64) -> Vec<String> {
+    let mut idle = Vec::new();
+    let Ok(fanout) = std::fs::read_dir(chunks_dir) else {
+        return idle;
+    };
+    for dir in fanout.flatten() {
+        let Ok(entries) = std::fs::read_dir(dir.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !is_chunk_id(&name) {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let changed_ms = meta.ctime() * 1000 + meta.ctime_nsec() / 1_000_000;
+            if meta.nlink() == 1 && now_ms.saturating_sub(changed_ms) >= grace_ms {
+                idle.push(name);
+            }
+        }
+    }
+    idle
+}
+
+/// Removes a collected chunk's file, and its fanout dir once empty.
+pub(crate) async fn remove_chunk(chunks_dir: &Path, id: &str) -> Result<()> {
+    let path = chunk_path(chunks_dir, id);
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::remove_dir(parent).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::time::now_ms;
+
+    #[tokio::test]
+    async fn identical_parts_share_one_inode() {
+        let tmp = tempfile::tempdir().unwrap();
+        let chunks = tmp.path().join("chunks");
+        let (a, b) = (tmp.path().join("a"), tmp.path().join("b"));
+        std::fs::write(&a, b"same bytes").unwrap();
+        std::fs::write(&b, b"same bytes").unwrap();
+
+        let id_a = intern_part(&chunks, &a).await.unwrap().unwrap();
+        let id_b = intern_part(&chunks, &b).await.unwrap().unwrap();
+        assert_eq!(id_a, id_b);
+        let ino = |p: &Path| std::fs::metadata(p).unwrap().ino();
+        assert_eq!(ino(&a), ino(&b));
+        assert_eq!(ino(&a), ino(&chunk_path(&chunks, &id_a)));
+        assert_eq!(std::fs::read(&b).unwrap(), b"same bytes");
+        // Interning an already-shared part changes nothing.
+        assert_eq!(intern_part(&chunks, &b).await.unwrap(), Some(id_a.clone()));
+        assert!(!tmp.path().join(".b.chunk").exists());
+
+        // Still linked from the parts: not idle.
+        assert!(idle_chunks(&chunks, i64::MAX, 0).is_empty());
+        std::fs::remove_file(&a).unwrap();
+        std::fs::remove_file(&b).unwrap();
+        assert_eq!(idle_chunks(&chunks, i64::MAX, 0), vec![id_a.clone()]);
+        // ...but only after the grace period.
+        assert!(idle_chunks(&chunks, now_ms(), 60_000).is_empty());
+
+        remove_chunk(&chunks, &id_a).await.unwrap();
+        assert!(!chunk_path(&chunks, &id_a).exists());
+        assert!(!chunks.join(&id_a[..2]).exists());
+    }
+}
@@ -13,6 +13,11 @@ pub struct StorageConfig {
     /// power loss may drop the last acked writes (never corrupts).
     #[serde(default = "default_durability")]
     pub durability: DurabilityMode,
+    /// `full` only: how long (µs) the first of a burst of index commits waits
+    /// for others to join its synced batch. 0 never delays a lone commit but
+    /// still batches commits that queue behind an in-flight sync.
+    #[serde(default = "default_group_commit_window_us")]
+    pub group_commit_window_us: u64,
     /// Worker tasks used by the index rebuild pipeline (each traverses
     /// directories AND parses meta.json). 0 = auto: one per CPU core.
     #[serde(default = "default_rebuild_reader_threads")]
@@ -23,6 +28,24 @@ pub struct StorageConfig {
     /// Rows per transaction in the rebuild batch writer.
     #[serde(default = "default_rebuild_batch_size")]
     pub rebuild_batch_size: usize,
+    /// How CopyObject and whole-part UploadPartCopy produce the destination's
+    /// part files. Blob files never change after publish, so sharing them is
+    /// safe; anything that cannot be shared falls back to a byte copy.
+    #[serde(default)]
+    pub copy_mode: CopyMode,
+    /// Objects of at most this many bytes are kept in their index row instead
+    /// of a blob dir (no `meta.json`, no part file). 0 disables inlining.
+    #[serde(default)]
+    pub inline_threshold: u64,
+    /// Extra data directories, typically one per disk, that hold buckets
+    /// alongside `base_dir`. New buckets go to whichever root has the most
+    /// free space unless one is chosen; `base_dir` keeps the server's own
+    /// databases either way.
+    #[serde(
+        default,
+        deserialize_with = "crate::server::config::list_of_filled_strings"
+    )]
+    pub data_roots: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,14 +55,30 @@ pub enum DurabilityMode {
     Relaxed,
 }
 
+/// `link`: hardlink, else reflink, else copy bytes. `reflink`: copy-on-write
+/// clone (`FICLONE`: btrfs, XFS, bcachefs), else copy bytes — for setups where
+/// shared inodes would confuse backup or quota tooling. `copy`: always stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyMode {
+    #[default]
+    Link,
+    Reflink,
+    Copy,
+}
+
 impl Default for StorageConfig {
     fn default() -> Self {
         Self {
             meta_cache_capacity: default_meta_cache_capacity(),
             durability: default_durability(),
+            group_commit_window_us: default_group_commit_window_us(),
             rebuild_reader_threads: default_rebuild_reader_threads(),
             rebuild_queue_bound: default_rebuild_queue_bound(),
             rebuild_batch_size: default_rebuild_batch_size(),
+            copy_mode: CopyMode::default(),
+            inline_threshold: 0,
+            data_roots: Vec::new(),
         }
     }
 }
@@ -50,6 +89,9 @@ fn default_meta_cache_capacity() -> usize {
 fn default_durability() -> DurabilityMode {
     DurabilityMode::Full
 }
+fn default_group_commit_window_us() -> u64 {
+    200
+}
 fn default_rebuild_reader_threads() -> usize {
     0 // auto: one worker per CPU core
 }
@@ -70,6 +112,10 @@ mod tests {
         assert_eq!(config.meta_cache_capacity, 200_000);
         assert_eq!(config.durability, DurabilityMode::Full);
         assert_eq!(config.rebuild_reader_threads, 0); // 0 = auto (core count)
+        assert_eq!(config.copy_mode, CopyMode::Link);
+        assert_eq!(config.group_commit_window_us, 200);
+        assert_eq!(config.inline_threshold, 0);
+        assert!(config.data_roots.is_empty());
     }
 
     #[test]
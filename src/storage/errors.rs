@@ -25,6 +25,14 @@ pub enum StorageError {
     /// A conditional write's precondition (If-None-Match / If-Match) was not
     /// met against the object currently at the key. Maps to HTTP 412.
     PreconditionFailed { bucket: String, key: String },
+    /// The write would take the bucket past a hard quota. Checked before the
+    /// row flips, so nothing of the rejected write becomes visible.
+    QuotaExceeded(String),
+    /// The bucket is switching data roots; requests get 503 for the moment
+    /// the switch takes, like a rebuild.
+    BucketMoving(String),
+    /// A data root that is not configured, or a move that makes no sense.
+    InvalidPlacement(String),
 }
 
 impl fmt::Display for StorageError {
@@ -62,6 +70,11 @@ impl fmt::Display for StorageError {
             StorageError::PreconditionFailed { bucket, key } => {
                 write!(f, "precondition failed for {bucket}/{key}")
             }
+            StorageError::QuotaExceeded(v) => write!(f, "quota exceeded: {v}"),
+            StorageError::BucketMoving(v) => {
+                write!(f, "bucket is moving to another data root: {v}")
+            }
+            StorageError::InvalidPlacement(v) => write!(f, "invalid placement: {v}"),
         }
     }
 }
@@ -4,6 +4,9 @@
 //! instant its key is written, DELETE the instant its key is removed. The
 //! filesystem is an immutable blob heap addressed by `blob_dir` (stored
 //! relative to the bucket directory) and is never scanned on the request path.
+//! An object below the inline threshold has no blob dir at all: its metadata
+//! and body are carried in the row itself, so it is written, replaced and
+//! deleted by the row commit alone.
 //!
 //! The `intents` column family is a write-ahead record of planned live-tree
 //! transitions: a `publish` intent is written (and committed) before a blob is
@@ -12,6 +15,14 @@
 //! completes. Crash recovery and orphan cleanup read this family only — never
 //! the tree.
 //!
+//! The `changes` column family is the bucket's change log: every commit that
+//! creates, replaces, rewrites or deletes a row appends the key under the next
+//! change sequence number, in the same batch. Incremental backups read it to
+//! find what moved since their last checkpoint. Sequence numbers belong to a
+//! log *epoch*, a random id minted when the database is created, so a rebuilt
+//! or restored index — whose numbering starts over or forks — can never be
+//! mistaken for a continuation of the one a checkpoint was taken against.
+//!
 //! ## On-disk value format
 //!
 //! Every stored value is JSON carrying an explicit `"v"` version field so the
@@ -28,20 +39,49 @@
 //! |-------------|------------------------|-----------------------------|
 //! | `objects`   | object key (raw UTF-8) | [`ObjectValueV1`] JSON      |
 //! | `intents`   | id (8-byte big-endian) | [`IntentValueV1`] JSON      |
-//! | `counters`  | `objects`              | i64 LE, summed via merge    |
+//! | `counters`  | `objects`, `bytes`     | i64 LE, summed via merge    |
+//! | `changes`   | seq (8-byte big-endian)| [`ChangeValueV1`] JSON      |
+//! | `chunks`    | chunk id (hex hash)    | refs i64 LE + size i64 LE   |
 //! | `meta`      | `schema_version`       | i64 LE                      |
+//! |             | `change_seq`, `change_floor` | i64 LE                |
+//! |             | `change_epoch`         | hex string                  |
 //!
 //! Object keys are stored as raw UTF-8 bytes, so RocksDB's bytewise ordering is
 //! exactly S3 lexicographic listing order — prefix/`start-after`/delimiter
-//! listing is a single forward iterator seek. The `objects` counter is
-//! maintained through a summing merge operator so concurrent commits on
-//! *different* keys never lose an increment (they run under distinct per-key
-//! locks, so no transaction is needed — only the counter is contended, and the
-//! merge operator resolves that without one).
+//! listing is a single forward iterator seek. The `objects` and `bytes`
+//! counters are maintained through a summing merge operator so concurrent
+//! commits on *different* keys never lose an increment (they run under distinct
+//! per-key locks, so no transaction is needed — only the counters are
+//! contended, and the merge operator resolves that without one). The one
+//! exception is a commit checked against a hard quota: reading the totals and
+//! writing the batch must not interleave with another such commit, so those
+//! serialise on a per-index mutex.
+//!
+//! ## Chunk references
+//!
+//! In a dedup bucket a row also lists the shared chunk files its parts are
+//! hardlinks of, and the `chunks` family counts how many rows reference each
+//! one. Every commit that adds, replaces or removes a row merges the
+//! reference deltas into the same batch, so the counts move exactly when the
+//! rows do. The chunk collector drops an id only while holding the index's
+//! chunk gate exclusively, which every reference-carrying commit holds shared
+//! — a count can therefore never be read as zero and deleted while an
+//! increment for it is in flight.
+//!
+//! ## Group commit
+//!
+//! Under [`Durability::Full`] every commit must be on disk before it is acked,
+//! and a WAL sync per commit caps small-object PUTs at the device's fsync
+//! rate. Commits therefore go through a `Committer` that folds whatever is
+//! issued concurrently — within a short configurable window, plus anything
+//! that queued behind the previous sync — into one synced `WriteBatch`. Each
+//! caller's mutations stay atomic and in order within the group, and nobody
+//! returns before their group is durable.
 
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicI64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 use rocksdb::{
     ColumnFamilyDescriptor, DBWithThreadMode, Direction, IteratorMode, MergeOperands, MultiThreaded,
@@ -50,6 +90,8 @@ use rocksdb::{
 use serde::{Deserialize, Serialize};
 
 use super::errors::{Result, StorageError};
+use super::metadata::{BucketQuota, BucketUsage, DedupUsage, ObjectMeta};
+use super::time::now_ms;
 
 /// Multi-threaded RocksDB handle: `cf_handle` yields an `Arc<BoundColumnFamily>`
 /// that outlives a borrow of the DB, which suits the `Arc<DB>` +
@@ -71,10 +113,18 @@ pub const INTENT_RETIRE: &str = "retire";
 const CF_OBJECTS: &str = "objects";
 const CF_INTENTS: &str = "intents";
 const CF_COUNTERS: &str = "counters";
+const CF_CHANGES: &str = "changes";
+const CF_CHUNKS: &str = "chunks";
 const CF_META: &str = "meta";
 
 const KEY_OBJECT_COUNT: &[u8] = b"objects";
+const KEY_BYTE_TOTAL: &[u8] = b"bytes";
 const KEY_SCHEMA_VERSION: &[u8] = b"schema_version";
+/// Highest change sequence number ever assigned, written with the entry.
+const KEY_CHANGE_SEQ: &[u8] = b"change_seq";
+/// Highest sequence number pruned from the log; entries above it are intact.
+const KEY_CHANGE_FLOOR: &[u8] = b"change_floor";
+const KEY_CHANGE_EPOCH: &[u8] = b"change_epoch";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectRecord {
@@ -85,6 +135,49 @@ pub struct ObjectRecord {
     pub size: u64,
     pub etag: String,
     pub last_modified_ms: i64,
+    /// Chunk-store files the object's parts share, one entry per part that
+    /// was deduplicated. Empty outside dedup buckets.
+    pub chunks: Vec<ChunkRef>,
+    /// The whole object, for one stored in its row. Its `blob_dir` is empty.
+    pub inline: Option<Box<InlineObject>>,
+}
+
+impl ObjectRecord {
+    /// The blob dir holding the object, or `None` when it lives inline.
+    pub fn on_disk(&self) -> Option<&str> {
+        self.inline.is_none().then_some(self.blob_dir.as_str())
+    }
+}
+
+/// What `meta.json` and the part file would hold, for an object small enough
+/// to keep in its row. The body is stored uncompressed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineObject {
+    pub meta: ObjectMeta,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+}
+
+mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A part's reference to a file in the bucket's chunk store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub id: String,
+    /// Bytes the chunk file occupies on disk.
+    pub size: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -105,6 +198,15 @@ pub struct ListPage {
     pub next_after: Option<String>,
 }
 
+/// A position in a bucket's change log: everything up to and including `seq`
+/// of log `epoch`. A cursor from a different epoch says nothing about this
+/// log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeCursor {
+    pub epoch: String,
+    pub seq: i64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Durability {
     /// fsync blobs before commit and fsync the WAL on every write: acked
@@ -144,6 +246,10 @@ struct ObjectValueV1 {
     etag: String,
     #[serde(default)]
     last_modified_ms: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<ChunkRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inline: Option<Box<InlineObject>>,
 }
 
 /// The `intents` family value. The id is the RocksDB key.
@@ -163,6 +269,17 @@ struct IntentValueV1 {
     attempts: i64,
 }
 
+/// The `changes` family value. The sequence number is the RocksDB key.
+#[derive(Debug, Serialize, Deserialize)]
+struct ChangeValueV1 {
+    #[serde(default = "default_version")]
+    v: u32,
+    #[serde(default)]
+    key: String,
+    #[serde(default)]
+    at_ms: i64,
+}
+
 fn reject_newer(v: u32, what: &str) -> Result<()> {
     if v > ENTITY_VERSION {
         return Err(StorageError::Db(format!(
@@ -181,6 +298,8 @@ fn encode_object(record: &ObjectRecord) -> Vec<u8> {
         size: record.size,
         etag: record.etag.clone(),
         last_modified_ms: record.last_modified_ms,
+        chunks: record.chunks.clone(),
+        inline: record.inline.clone(),
     })
     .expect("ObjectValueV1 serializes")
 }
@@ -194,6 +313,8 @@ fn decode_object(key: &[u8], value: &[u8]) -> Result<ObjectRecord> {
         size: v.size,
         etag: v.etag,
         last_modified_ms: v.last_modified_ms,
+        chunks: v.chunks,
+        inline: v.inline,
     })
 }
 
@@ -222,6 +343,21 @@ fn decode_intent(id: i64, value: &[u8]) -> Result<IntentRecord> {
     })
 }
 
+fn encode_change(key: &str, at_ms: i64) -> Vec<u8> {
+    serde_json::to_vec(&ChangeValueV1 {
+        v: ENTITY_VERSION,
+        key: key.to_string(),
+        at_ms,
+    })
+    .expect("ChangeValueV1 serializes")
+}
+
+fn decode_change(value: &[u8]) -> Result<ChangeValueV1> {
+    let v: ChangeValueV1 = serde_json::from_slice(value)?;
+    reject_newer(v.v, "change")?;
+    Ok(v)
+}
+
 // ── key / counter byte encodings ────────────────────────────────────────────
 
 fn id_key(id: i64) -> [u8; 8] {
@@ -256,6 +392,53 @@ fn counter_merge(_key: &[u8], existing: Option<&[u8]>, operands: &MergeOperands)
     Some(sum.to_le_bytes().to_vec())
 }
 
+/// Decodes a `chunks` family value: (reference count, chunk size).
+fn decode_chunk(bytes: &[u8]) -> (i64, i64) {
+    if bytes.len() == 16 {
+        (decode_counter(&bytes[..8]), decode_counter(&bytes[8..]))
+    } else {
+        (0, 0)
+    }
+}
+
+fn encode_chunk(refs: i64, size: i64) -> Vec<u8> {
+    let mut value = refs.to_le_bytes().to_vec();
+    value.extend_from_slice(&size.to_le_bytes());
+    value
+}
+
+/// Associative merge for the `chunks` family: operands are (delta, size)
+/// pairs; deltas are summed and the size — the same for every reference to
+/// one chunk — is carried along.
+fn chunk_merge(_key: &[u8], existing: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+    let (mut refs, mut size) = existing.map(decode_chunk).unwrap_or((0, 0));
+    for op in operands.iter() {
+        let (delta, op_size) = decode_chunk(op);
+        refs += delta;
+        size = size.max(op_size);
+    }
+    Some(encode_chunk(refs, size))
+}
+
+/// Nets the chunk references a commit adds and drops into one merge per id,
+/// so replacing an object with one sharing the same chunks writes nothing.
+/// Returns whether any merge was queued.
+fn chunk_deltas(ops: &mut Ops, added: &[ChunkRef], removed: &[ChunkRef]) -> bool {
+    let mut deltas: std::collections::BTreeMap<&str, (i64, u64)> = Default::default();
+    for (chunk, delta) in added.iter().map(|c| (c, 1)).chain(removed.iter().map(|c| (c, -1))) {
+        let entry = deltas.entry(chunk.id.as_str()).or_insert((0, chunk.size));
+        entry.0 += delta;
+    }
+    let mut any = false;
+    for (id, (delta, size)) in deltas {
+        if delta != 0 {
+            ops.merge(CF_CHUNKS, id.as_bytes(), encode_chunk(delta, size as i64));
+            any = true;
+        }
+    }
+    any
+}
+
 // ── the index handle ────────────────────────────────────────────────────────
 
 #[derive(Clone)]
@@ -266,6 +449,12 @@ pub struct ObjectIndex {
     /// Single-process (guaranteed by the data-root process lock), so an atomic
     /// counter is a sound stand-in for SQLite's AUTOINCREMENT.
     intent_seq: Arc<AtomicI64>,
+    /// Held across the read-check-write of quota-checked commits.
+    quota_lock: Arc<std::sync::Mutex<()>>,
+    /// Shared by commits that move chunk references, exclusive while the
+    /// chunk collector drops unreferenced ids (see the module docs).
+    chunk_gate: Arc<RwLock<()>>,
+    committer: Arc<Committer>,
 }
 
 impl std::fmt::Debug for ObjectIndex {
@@ -290,10 +479,14 @@ fn db_options() -> Options {
 fn column_families() -> Vec<ColumnFamilyDescriptor> {
     let mut counter_opts = Options::default();
     counter_opts.set_merge_operator_associative("i64_sum", counter_merge);
+    let mut chunk_opts = Options::default();
+    chunk_opts.set_merge_operator_associative("chunk_refs", chunk_merge);
     vec![
         ColumnFamilyDescriptor::new(CF_OBJECTS, Options::default()),
         ColumnFamilyDescriptor::new(CF_INTENTS, Options::default()),
         ColumnFamilyDescriptor::new(CF_COUNTERS, counter_opts),
+        ColumnFamilyDescriptor::new(CF_CHANGES, Options::default()),
+        ColumnFamilyDescriptor::new(CF_CHUNKS, chunk_opts),
         ColumnFamilyDescriptor::new(CF_META, Options::default()),
     ]
 }
@@ -325,6 +518,27 @@ fn max_intent_id(db: &Db) -> Result<i64> {
     })
 }
 
+fn read_meta_i64(db: &Db, key: &[u8]) -> Result<i64> {
+    let meta = cf(db, CF_META)?;
+    Ok(db.get_cf(&meta, key)?.map(|b| decode_counter(&b)).unwrap_or(0))
+}
+
+/// Mints the change-log epoch of a database that has none — one just
+/// created, or one written before the log existed.
+fn ensure_change_epoch(db: &Db) -> Result<()> {
+    let meta = cf(db, CF_META)?;
+    if db.get_cf(&meta, KEY_CHANGE_EPOCH)?.is_none() {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(true);
+        db.put_cf_opt(&meta, KEY_CHANGE_EPOCH, new_change_epoch(), &opts)?;
+    }
+    Ok(())
+}
+
+fn new_change_epoch() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
 /// Returns true when the bucket needs a (blocking) rebuild before it can serve:
 /// the database is missing while blobs exist, or the schema version is not
 /// current.
@@ -352,6 +566,17 @@ pub async fn needs_rebuild(bucket_dir: &Path) -> Result<bool> {
     .await
 }
 
+fn read_usage(db: &Db) -> Result<BucketUsage> {
+    let counters = cf(db, CF_COUNTERS)?;
+    let read = |key: &[u8]| -> Result<u64> {
+        Ok(db.get_cf(&counters, key)?.map(|b| decode_counter(&b)).unwrap_or(0).max(0) as u64)
+    };
+    Ok(BucketUsage {
+        bytes: read(KEY_BYTE_TOTAL)?,
+        objects: read(KEY_OBJECT_COUNT)?,
+    })
+}
+
 async fn run_blocking<F, T>(f: F) -> Result<T>
 where
     F: FnOnce() -> Result<T> + Send + 'static,
@@ -363,6 +588,258 @@ where
     }
 }
 
+// ── group commit ────────────────────────────────────────────────────────────
+
+/// One mutation, addressed by column-family *name* so it can be queued past
+/// the borrow a `BoundColumnFamily` would pin.
+enum Op {
+    Put(&'static str, Vec<u8>, Vec<u8>),
+    Delete(&'static str, Vec<u8>),
+    Merge(&'static str, Vec<u8>, Vec<u8>),
+    /// A change-log entry for a key; its sequence number is assigned when the
+    /// batch is written.
+    Change(String, i64),
+}
+
+/// A caller's atomic unit: the mutations one commit method would otherwise
+/// have put in its own `WriteBatch`. They land, in order, inside whatever
+/// group the commit joins.
+#[derive(Default)]
+struct Ops(Vec<Op>);
+
+impl Ops {
+    fn put(&mut self, cf: &'static str, key: impl AsRef<[u8]>, value: impl Into<Vec<u8>>) {
+        self.0.push(Op::Put(cf, key.as_ref().to_vec(), value.into()));
+    }
+
+    fn delete(&mut self, cf: &'static str, key: impl AsRef<[u8]>) {
+        self.0.push(Op::Delete(cf, key.as_ref().to_vec()));
+    }
+
+    fn merge(&mut self, cf: &'static str, key: impl AsRef<[u8]>, value: impl Into<Vec<u8>>) {
+        self.0.push(Op::Merge(cf, key.as_ref().to_vec(), value.into()));
+    }
+
+    fn change(&mut self, key: &str, at_ms: i64) {
+        self.0.push(Op::Change(key.to_string(), at_ms));
+    }
+}
+
+/// Writes `ops` as one batch. Change-log entries are numbered here, under
+/// `change_seq` held until the batch is written, so the log only ever grows
+/// at its tail: a reader that sees entry N also sees every entry below it.
+/// The high-water mark travels in the same batch, and the in-memory counter
+/// only advances once the write succeeded.
+fn write_ops(db: &Db, ops: Vec<Op>, opts: &WriteOptions, change_seq: &Mutex<i64>) -> Result<()> {
+    let mut seq = ops
+        .iter()
+        .any(|op| matches!(op, Op::Change(..)))
+        .then(|| change_seq.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut next = seq.as_deref().copied().unwrap_or(0);
+    let mut batch = WriteBatch::default();
+    for op in ops {
+        match op {
+            Op::Put(name, key, value) => batch.put_cf(&cf(db, name)?, key, value),
+            Op::Delete(name, key) => batch.delete_cf(&cf(db, name)?, key),
+            Op::Merge(name, key, value) => batch.merge_cf(&cf(db, name)?, key, value),
+            Op::Change(key, at_ms) => {
+                next += 1;
+                batch.put_cf(&cf(db, CF_CHANGES)?, id_key(next), encode_change(&key, at_ms));
+            }
+        }
+    }
+    if seq.is_some() {
+        batch.put_cf(&cf(db, CF_META)?, KEY_CHANGE_SEQ, next.to_le_bytes());
+    }
+    db.write_opt(batch, opts)?;
+    if let Some(seq) = seq.as_mut() {
+        **seq = next;
+    }
+    Ok(())
+}
+
+/// Commits and synced groups since open — how well group commit is
+/// coalescing. Equal counts mean every commit paid its own fsync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupCommitStats {
+    pub commits: u64,
+    pub groups: u64,
+}
+
+/// The group being filled, and the slot its result will be published in.
+struct Group {
+    ops: Vec<Op>,
+    commits: u64,
+    outcome: Arc<OnceLock<Result<()>>>,
+}
+
+impl Group {
+    fn new() -> Self {
+        Self {
+            ops: Vec::new(),
+            commits: 0,
+            outcome: Arc::new(OnceLock::new()),
+        }
+    }
+}
+
+struct CommitState {
+    filling: Group,
+    /// A caller is writing a group right now; everyone else queues behind it.
+    leader: bool,
+    stats: GroupCommitStats,
+}
+
+/// Funnels full-durability commits into shared synced batches.
+///
+/// Classic leader/follower: a commit appends its ops to the filling group. If
+/// no write is in flight it becomes the leader — waits out the window so
+/// concurrent commits can join, takes the group, and writes it as one synced
+/// `WriteBatch`. Commits arriving meanwhile fill the next group and wait; the
+/// first of them to wake when the leader finishes leads it. Nobody returns
+/// before the group holding their ops is on disk, so an ack still means
+/// durable, and because groups are written strictly one after another in
+/// arrival order, two commits touching the same key land in the order they
+/// were issued. A group is one batch, so a crash keeps all of it or none.
+///
+/// Under `relaxed` durability there is no fsync to amortise and commits are
+/// written directly.
+struct Committer {
+    durability: Durability,
+    window: Duration,
+    gate: CommitGate,
+    /// Last change sequence number assigned; see [`write_ops`].
+    change_seq: Arc<Mutex<i64>>,
+    /// Set by [`ObjectIndex::seal`]; every commit after it fails with this.
+    sealed: OnceLock<StorageError>,
+    state: Mutex<CommitState>,
+    turn: Condvar,
+}
+
+impl Committer {
+    fn new(durability: Durability, window: Duration, gate: CommitGate, change_seq: Arc<Mutex<i64>>) -> Self {
+        Self {
+            durability,
+            window,
+            gate,
+            change_seq,
+            sealed: OnceLock::new(),
+            state: Mutex::new(CommitState {
+                filling: Group::new(),
+                leader: false,
+                stats: GroupCommitStats::default(),
+            }),
+            turn: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CommitState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stats(&self) -> GroupCommitStats {
+        self.lock().stats
+    }
+
+    /// Writes `ops` atomically and returns once they are durable. Blocking —
+    /// call from `run_blocking`.
+    fn commit(&self, db: &Db, ops: Ops) -> Result<()> {
+        if self.durability == Durability::Relaxed {
+            let _open = self.gate.open();
+            if let Some(err) = self.sealed.get() {
+                return Err(err.clone());
+            }
+            return write_ops(db, ops.0, &write_opts(self.durability), &self.change_seq);
+        }
+        let mut state = self.lock();
+        state.filling.ops.extend(ops.0);
+        state.filling.commits += 1;
+        let outcome = state.filling.outcome.clone();
+        loop {
+            if let Some(result) = outcome.get() {
+                return result.clone();
+            }
+            if state.leader {
+                state = self.turn.wait(state).unwrap_or_else(|e| e.into_inner());
+                continue;
+            }
+            state.leader = true;
+            drop(state);
+            let mut leader = LeaderGuard {
+                committer: self,
+                outcome: None,
+            };
+            if !self.window.is_zero() {
+                std::thread::sleep(self.window);
+            }
+            let group = {
+                let mut state = self.lock();
+                state.stats.commits += state.filling.commits;
+                state.stats.groups += 1;
+                std::mem::replace(&mut state.filling, Group::new())
+            };
+            leader.outcome = Some(group.outcome.clone());
+            let result = {
+                let _open = self.gate.open();
+                match self.sealed.get() {
+                    Some(err) => Err(err.clone()),
+                    None => {
+                        write_ops(db, group.ops, &write_opts(self.durability), &self.change_seq)
+                    }
+                }
+            };
+            let _ = group.outcome.set(result);
+            drop(leader);
+            state = self.lock();
+        }
+    }
+}
+
+/// Store-wide switch that holds off index commits, so every bucket's index can
+/// be checkpointed at one cut. Commits hold it shared only for the instant of
+/// their write; a snapshot holds it exclusively while it checkpoints, which
+/// stalls writers at their commit point without failing them.
+#[derive(Clone, Default)]
+pub struct CommitGate(Arc<RwLock<()>>);
+
+impl std::fmt::Debug for CommitGate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommitGate").finish_non_exhaustive()
+    }
+}
+
+impl CommitGate {
+    /// Waits out in-flight commits and holds off new ones until the guard
+    /// drops. Blocking — call from `spawn_blocking`.
+    pub fn freeze(&self) -> RwLockWriteGuard<'_, ()> {
+        self.0.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn open(&self) -> RwLockReadGuard<'_, ()> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Hands leadership on when the leader is done — or unwinds mid-write, in
+/// which case its group is failed rather than left for followers to wait on
+/// forever.
+struct LeaderGuard<'a> {
+    committer: &'a Committer,
+    outcome: Option<Arc<OnceLock<Result<()>>>>,
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(outcome) = &self.outcome {
+            let _ = outcome.set(Err(StorageError::Db(
+                "index group commit aborted".to_string(),
+            )));
+        }
+        self.committer.lock().leader = false;
+        self.committer.turn.notify_all();
+    }
+}
+
 impl ObjectIndex {
     /// Opens (creating if absent) a current-schema index. Fails with
     /// [`StorageError::IndexOutdated`] if the on-disk schema is not current —
@@ -377,7 +854,10 @@ impl ObjectIndex {
         let index = Self::open_at(&db_path, durability).await?;
         let version = index.schema_version().await?;
         match version {
-            Some(v) if v == SCHEMA_VERSION => Ok(index),
+            Some(v) if v == SCHEMA_VERSION => {
+                index.ensure_byte_total().await?;
+                Ok(index)
+            }
             Some(v) if existed => {
                 index.close().await;
                 Err(StorageError::IndexOutdated(format!(
@@ -390,6 +870,7 @@ impl ObjectIndex {
             // entirely and is handled by `needs_rebuild` (missing RocksDB dir).
             _ => {
                 index.create_schema().await?;
+                index.ensure_byte_total().await?;
                 Ok(index)
             }
         }
@@ -400,17 +881,78 @@ impl ObjectIndex {
     /// call [`create_schema`](Self::create_schema) for that.
     pub async fn open_at(db_path: &Path, durability: Durability) -> Result<Self> {
         let path = db_path.to_path_buf();
-        let (db, seed) = run_blocking(move || {
+        let (db, seed, change_seq) = run_blocking(move || {
             let db = open_db(&path)?;
             let seed = max_intent_id(&db)?;
-            Ok((db, seed))
+            ensure_change_epoch(&db)?;
+            let change_seq = read_meta_i64(&db, KEY_CHANGE_SEQ)?;
+            Ok((db, seed, change_seq))
         })
         .await?;
+        let change_seq = Arc::new(Mutex::new(change_seq));
         Ok(Self {
             db: Arc::new(db),
             durability,
             intent_seq: Arc::new(AtomicI64::new(seed)),
+            quota_lock: Arc::new(std::sync::Mutex::new(())),
+            chunk_gate: Arc::new(RwLock::new(())),
+            committer: Arc::new(Committer::new(
+                durability,
+                Duration::ZERO,
+                CommitGate::default(),
+                change_seq,
+            )),
+        })
+    }
+
+    /// Sets how long a group-commit leader waits for company before syncing.
+    /// Zero still coalesces commits that queued behind an in-flight sync; it
+    /// just never delays a lone one. Call before the index is shared.
+    pub fn with_commit_window(mut self, window: Duration) -> Self {
+        let gate = self.committer.gate.clone();
+        let change_seq = self.committer.change_seq.clone();
+        self.committer = Arc::new(Committer::new(self.durability, window, gate, change_seq));
+        self
+    }
+
+    /// Puts this index's commits behind a shared [`CommitGate`]. Call before
+    /// the index is shared.
+    pub fn with_commit_gate(mut self, gate: CommitGate) -> Self {
+        let change_seq = self.committer.change_seq.clone();
+        self.committer = Arc::new(Committer::new(
+            self.durability,
+            self.committer.window,
+            gate,
+            change_seq,
+        ));
+        self
+    }
+
+    pub fn group_commit_stats(&self) -> GroupCommitStats {
+        self.committer.stats()
+    }
+
+    /// Seeds the `bytes` counter from the rows when it is absent — an index
+    /// written before the counter existed. Runs at open, before the index is
+    /// shared, so no commit can race the scan.
+    async fn ensure_byte_total(&self) -> Result<()> {
+        let db = self.db.clone();
+        let durability = self.durability;
+        run_blocking(move || {
+            let counters = cf(&db, CF_COUNTERS)?;
+            if db.get_cf(&counters, KEY_BYTE_TOTAL)?.is_some() {
+                return Ok(());
+            }
+            let objects = cf(&db, CF_OBJECTS)?;
+            let mut total: i64 = 0;
+            for item in db.iterator_cf(&objects, IteratorMode::Start) {
+                let (key, value) = item?;
+                total += decode_object(&key, &value)?.size as i64;
+            }
+            db.put_cf_opt(&counters, KEY_BYTE_TOTAL, total.to_le_bytes(), &write_opts(durability))?;
+            Ok(())
         })
+        .await
     }
 
     async fn schema_version(&self) -> Result<Option<i64>> {
@@ -446,7 +988,7 @@ impl ObjectIndex {
         let db = self.db.clone();
         let _ = run_blocking(move || {
             let _ = db.flush();
-            for name in [CF_OBJECTS, CF_INTENTS, CF_COUNTERS, CF_META] {
+            for name in [CF_OBJECTS, CF_INTENTS, CF_COUNTERS, CF_CHANGES, CF_META] {
                 if let Ok(handle) = cf(&db, name) {
                     let _ = db.flush_cf(&handle);
                 }
@@ -457,6 +999,23 @@ impl ObjectIndex {
         .await;
     }
 
+    /// Writes a RocksDB checkpoint — a consistent, openable copy of the whole
+    /// database, hardlinking SST files where it can — to `dest`, which must not
+    /// exist. Blocking, so a caller can take several under one
+    /// [`CommitGate::freeze`].
+    pub fn checkpoint(&self, dest: &Path) -> Result<()> {
+        rocksdb::checkpoint::Checkpoint::new(&self.db)?.create_checkpoint(dest)?;
+        Ok(())
+    }
+
+    /// Fails every later commit through this index — and its clones — with
+    /// `reason`, for a handle whose database is about to be retired while
+    /// requests may still hold it. Call under [`CommitGate::freeze`] so no
+    /// commit is caught mid-write.
+    pub fn seal(&self, reason: StorageError) {
+        let _ = self.committer.sealed.set(reason);
+    }
+
     pub async fn checkpoint_truncate(&self) -> Result<()> {
         self.close().await;
         Ok(())
@@ -485,7 +1044,7 @@ impl ObjectIndex {
     /// serialized against other mutations of this key.
     pub async fn update_blob_dir(&self, key: &str, expected: &str, new_blob_dir: &str) -> Result<bool> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         let key = key.to_string();
         let expected = expected.to_string();
         let new_blob_dir = new_blob_dir.to_string();
@@ -499,7 +1058,9 @@ impl ObjectIndex {
                 return Ok(false);
             }
             record.blob_dir = new_blob_dir;
-            db.put_cf_opt(&objects, key.as_bytes(), encode_object(&record), &write_opts(durability))?;
+            let mut ops = Ops::default();
+            ops.put(CF_OBJECTS, key.as_bytes(), encode_object(&record));
+            committer.commit(&db, ops)?;
             Ok(true)
         })
         .await
@@ -520,7 +1081,7 @@ impl ObjectIndex {
         last_modified_ms: i64,
     ) -> Result<bool> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         let key = key.to_string();
         let expected = expected_blob_dir.to_string();
         let etag = etag.to_string();
@@ -533,10 +1094,17 @@ impl ObjectIndex {
             if record.blob_dir != expected {
                 return Ok(false);
             }
+            let delta = size as i64 - record.size as i64;
             record.size = size;
             record.etag = etag;
             record.last_modified_ms = last_modified_ms;
-            db.put_cf_opt(&objects, key.as_bytes(), encode_object(&record), &write_opts(durability))?;
+            let mut ops = Ops::default();
+            ops.put(CF_OBJECTS, key.as_bytes(), encode_object(&record));
+            ops.change(&key, now_ms());
+            if delta != 0 {
+                ops.merge(CF_COUNTERS, KEY_BYTE_TOTAL, delta.to_le_bytes());
+            }
+            committer.commit(&db, ops)?;
             Ok(true)
         })
         .await
@@ -591,6 +1159,179 @@ impl ObjectIndex {
         .await
     }
 
+    /// Object count and stored bytes, straight from the counters.
+    pub async fn usage(&self) -> Result<BucketUsage> {
+        let db = self.db.clone();
+        run_blocking(move || read_usage(&db)).await
+    }
+
+    // ── chunks ──────────────────────────────────────────────────────────────
+
+    /// Totals over the chunk reference counts: what the shared chunks occupy
+    /// against what their references would occupy unshared. Walks the
+    /// `chunks` family, which holds one entry per distinct chunk.
+    pub async fn dedup_usage(&self) -> Result<DedupUsage> {
+        let db = self.db.clone();
+        run_blocking(move || {
+            let chunks = cf(&db, CF_CHUNKS)?;
+            let mut usage = DedupUsage::default();
+            for item in db.iterator_cf(&chunks, IteratorMode::Start) {
+                let (_, value) = item?;
+                let (refs, size) = decode_chunk(&value);
+                if refs > 0 {
+                    usage.chunks += 1;
+                    usage.stored_bytes += size.max(0) as u64;
+                    usage.referenced_bytes += (refs * size).max(0) as u64;
+                }
+            }
+            Ok(usage)
+        })
+        .await
+    }
+
+    /// Current reference count of one chunk (0 when it has no entry).
+    pub async fn chunk_refs(&self, id: &str) -> Result<i64> {
+        let db = self.db.clone();
+        let id = id.to_string();
+        run_blocking(move || {
+            let chunks = cf(&db, CF_CHUNKS)?;
+            Ok(db.get_cf(&chunks, id.as_bytes())?.map(|v| decode_chunk(&v).0).unwrap_or(0))
+        })
+        .await
+    }
+
+    /// The chunk collector's commit point. Of `ids`, returns those no row
+    /// references — their entries removed in the same step — so the caller
+    /// may delete the files. Holds the chunk gate exclusively from the read
+    /// through the write, so no reference can land in between.
+    pub async fn drop_unreferenced_chunks(&self, ids: Vec<String>) -> Result<Vec<String>> {
+        let db = self.db.clone();
+        let committer = self.committer.clone();
+        let chunk_gate = self.chunk_gate.clone();
+        run_blocking(move || {
+            let chunks = cf(&db, CF_CHUNKS)?;
+            let _guard = chunk_gate.write().unwrap_or_else(|e| e.into_inner());
+            let mut ops = Ops::default();
+            let mut unreferenced = Vec::new();
+            for id in ids {
+                match db.get_cf(&chunks, id.as_bytes())? {
+                    Some(value) if decode_chunk(&value).0 > 0 => continue,
+                    Some(_) => ops.delete(CF_CHUNKS, id.as_bytes()),
+                    None => {}
+                }
+                unreferenced.push(id);
+            }
+            if !ops.0.is_empty() {
+                committer.commit(&db, ops)?;
+            }
+            Ok(unreferenced)
+        })
+        .await
+    }
+
+    // ── change log ──────────────────────────────────────────────────────────
+
+    /// The log's epoch and the last sequence number written to it.
+    pub async fn change_cursor(&self) -> Result<ChangeCursor> {
+        let db = self.db.clone();
+        run_blocking(move || {
+            let meta = cf(&db, CF_META)?;
+            let epoch = db
+                .get_cf(&meta, KEY_CHANGE_EPOCH)?
+                .map(|b| String::from_utf8_lossy(&b).into_owned())
+                .unwrap_or_default();
+            Ok(ChangeCursor {
+                epoch,
+                seq: read_meta_i64(&db, KEY_CHANGE_SEQ)?,
+            })
+        })
+        .await
+    }
+
+    /// Highest sequence number pruned from the log, 0 while it is whole. A
+    /// cursor below the floor can no longer be continued from.
+    pub async fn change_floor(&self) -> Result<i64> {
+        let db = self.db.clone();
+        run_blocking(move || read_meta_i64(&db, KEY_CHANGE_FLOOR)).await
+    }
+
+    /// Up to `limit` log entries after sequence number `after`, in order, as
+    /// `(seq, key)`. A key appears once per change, so a caller after the
+    /// current state of each key collapses repeats itself.
+    pub async fn changes_after(&self, after: i64, limit: usize) -> Result<Vec<(i64, String)>> {
+        let db = self.db.clone();
+        run_blocking(move || {
+            let changes = cf(&db, CF_CHANGES)?;
+            let start = id_key(after.saturating_add(1));
+            let mut out = Vec::new();
+            for item in db.iterator_cf(&changes, IteratorMode::From(&start, Direction::Forward)) {
+                if out.len() >= limit {
+                    break;
+                }
+                let (key, value) = item?;
+                out.push((id_from_key(&key), decode_change(&value)?.key));
+            }
+            Ok(out)
+        })
+        .await
+    }
+
+    /// Drops log entries recorded before `before_ms`, oldest first, and raises
+    /// the floor past them. Stops at the first younger entry, so the log stays
+    /// a contiguous tail. Returns how many entries went.
+    pub async fn prune_changes(&self, before_ms: i64) -> Result<usize> {
+        const BATCH: usize = 10_000;
+        let mut pruned = 0;
+        loop {
+            let db = self.db.clone();
+            let committer = self.committer.clone();
+            let (n, done) = run_blocking(move || {
+                let changes = cf(&db, CF_CHANGES)?;
+                let mut ops = Ops::default();
+                let mut floor = None;
+                let mut done = true;
+                for item in db.iterator_cf(&changes, IteratorMode::Start) {
+                    let (key, value) = item?;
+                    if decode_change(&value)?.at_ms >= before_ms {
+                        break;
+                    }
+                    if ops.0.len() >= BATCH {
+                        done = false;
+                        break;
+                    }
+                    ops.delete(CF_CHANGES, &key);
+                    floor = Some(id_from_key(&key));
+                }
+                let n = ops.0.len();
+                if let Some(floor) = floor {
+                    ops.put(CF_META, KEY_CHANGE_FLOOR, floor.to_le_bytes());
+                    committer.commit(&db, ops)?;
+                }
+                Ok((n, done))
+            })
+            .await?;
+            pruned += n;
+            if done {
+                return Ok(pruned);
+            }
+        }
+    }
+
+    /// Starts a new log epoch. For a database copied from another — a
+    /// restored snapshot — whose log would otherwise carry on under its
+    /// source's name while the two diverge.
+    pub async fn fork_change_log(&self) -> Result<()> {
+        let db = self.db.clone();
+        run_blocking(move || {
+            let meta = cf(&db, CF_META)?;
+            let mut opts = WriteOptions::default();
+            opts.set_sync(true);
+            db.put_cf_opt(&meta, KEY_CHANGE_EPOCH, new_change_epoch(), &opts)?;
+            Ok(())
+        })
+        .await
+    }
+
     // ── intents ─────────────────────────────────────────────────────────────
 
     fn next_intent_id(&self) -> i64 {
@@ -601,14 +1342,15 @@ impl ObjectIndex {
     /// live tree.
     pub async fn insert_publish_intent(&self, key: &str, blob_dir: &str, now_ms: i64) -> Result<i64> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         let id = self.next_intent_id();
         let key = key.to_string();
         let blob_dir = blob_dir.to_string();
         run_blocking(move || {
-            let intents = cf(&db, CF_INTENTS)?;
             let value = encode_intent(INTENT_PUBLISH, &key, &blob_dir, now_ms, 0);
-            db.put_cf_opt(&intents, id_key(id), value, &write_opts(durability))?;
+            let mut ops = Ops::default();
+            ops.put(CF_INTENTS, id_key(id), value);
+            committer.commit(&db, ops)?;
             Ok(id)
         })
         .await
@@ -619,66 +1361,150 @@ impl ObjectIndex {
     /// `retire` intent for the displaced blob dir — all in one write batch.
     /// Returns the retire intent id when an old dir was displaced.
     ///
-    /// Runs under the per-key write lock, so reading the prior existence of the
-    /// key to decide the counter delta is race-free against other mutations of
-    /// this key; cross-key contention on the counter is resolved by the merge
+    /// Runs under the per-key write lock, so reading the prior row of the key
+    /// to decide the counter deltas is race-free against other mutations of
+    /// this key; cross-key contention on the counters is resolved by the merge
     /// operator.
+    ///
+    /// With a `quota` that has hard limits, the totals are read and checked
+    /// under the index's quota mutex and the batch is only written if the
+    /// write fits; otherwise it fails with [`StorageError::QuotaExceeded`]
+    /// and nothing is written — the publish intent is left for the caller.
+    ///
+    /// The new row's chunk references are added and the displaced row's
+    /// dropped in the same batch.
     pub async fn commit_publish(
         &self,
         record: &ObjectRecord,
         publish_intent_id: i64,
         displaced_blob_dir: Option<&str>,
         now_ms: i64,
+        quota: Option<&BucketQuota>,
+    ) -> Result<Option<i64>> {
+        self.commit_row(record, Some(publish_intent_id), displaced_blob_dir, now_ms, quota)
+            .await
+    }
+
+    /// [`commit_publish`](Self::commit_publish) for a row that carries its
+    /// object inline. Nothing was staged on disk, so there is no publish
+    /// intent to clear; a displaced blob dir is still retired.
+    pub async fn commit_inline(
+        &self,
+        record: &ObjectRecord,
+        displaced_blob_dir: Option<&str>,
+        now_ms: i64,
+        quota: Option<&BucketQuota>,
+    ) -> Result<Option<i64>> {
+        self.commit_row(record, None, displaced_blob_dir, now_ms, quota)
+            .await
+    }
+
+    async fn commit_row(
+        &self,
+        record: &ObjectRecord,
+        publish_intent_id: Option<i64>,
+        displaced_blob_dir: Option<&str>,
+        now_ms: i64,
+        quota: Option<&BucketQuota>,
     ) -> Result<Option<i64>> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         let record = record.clone();
         let displaced = displaced_blob_dir.map(str::to_string);
         let retire_id = displaced.as_ref().map(|_| self.next_intent_id());
+        let quota = quota.filter(|q| q.has_hard_limits()).cloned();
+        let quota_lock = self.quota_lock.clone();
+        let chunk_gate = self.chunk_gate.clone();
         run_blocking(move || {
             let objects = cf(&db, CF_OBJECTS)?;
-            let intents = cf(&db, CF_INTENTS)?;
-            let counters = cf(&db, CF_COUNTERS)?;
-            let existed = db.get_cf(&objects, record.object_key.as_bytes())?.is_some();
+            let old = db
+                .get_cf(&objects, record.object_key.as_bytes())?
+                .map(|v| decode_object(record.object_key.as_bytes(), &v))
+                .transpose()?;
+            let old_size = old.as_ref().map(|old| old.size);
+
+            let _quota_guard = match &quota {
+                Some(quota) => {
+                    let guard = quota_lock.lock().unwrap_or_else(|e| e.into_inner());
+                    let before = read_usage(&db)?;
+                    let after = before.after_replace(old_size, record.size);
+                    if let Some(reason) = quota.hard_violation(before, after) {
+                        return Err(StorageError::QuotaExceeded(reason));
+                    }
+                    Some(guard)
+                }
+                None => None,
+            };
 
-            let mut batch = WriteBatch::default();
-            batch.put_cf(&objects, record.object_key.as_bytes(), encode_object(&record));
-            batch.delete_cf(&intents, id_key(publish_intent_id));
+            let mut ops = Ops::default();
+            ops.put(CF_OBJECTS, record.object_key.as_bytes(), encode_object(&record));
+            ops.change(&record.object_key, now_ms);
+            if let Some(publish_intent_id) = publish_intent_id {
+                ops.delete(CF_INTENTS, id_key(publish_intent_id));
+            }
             if let (Some(old_dir), Some(id)) = (&displaced, retire_id) {
                 let value = encode_intent(INTENT_RETIRE, &record.object_key, old_dir, now_ms, 0);
-                batch.put_cf(&intents, id_key(id), value);
+                ops.put(CF_INTENTS, id_key(id), value);
             }
-            if !existed {
-                batch.merge_cf(&counters, KEY_OBJECT_COUNT, 1i64.to_le_bytes());
+            if old_size.is_none() {
+                ops.merge(CF_COUNTERS, KEY_OBJECT_COUNT, 1i64.to_le_bytes());
             }
-            db.write_opt(batch, &write_opts(durability))?;
+            let delta = record.size as i64 - old_size.unwrap_or(0) as i64;
+            if delta != 0 {
+                ops.merge(CF_COUNTERS, KEY_BYTE_TOTAL, delta.to_le_bytes());
+            }
+            let dropped = old.map(|old| old.chunks).unwrap_or_default();
+            let _chunk_guard = chunk_deltas(&mut ops, &record.chunks, &dropped)
+                .then(|| chunk_gate.read().unwrap_or_else(|e| e.into_inner()));
+            // A quota-checked commit keeps the quota guard until its group is
+            // on disk, so the next check reads totals that include it.
+            committer.commit(&db, ops)?;
             Ok(retire_id)
         })
         .await
     }
 
-    /// The atomic commit point of a DELETE: removes the object and records a
-    /// `retire` intent for its blob dir in one write batch.
-    pub async fn commit_delete(&self, key: &str, blob_dir: &str, now_ms: i64) -> Result<i64> {
+    /// The atomic commit point of a DELETE: removes the object, drops its
+    /// chunk references and records a `retire` intent for its blob dir in one
+    /// write batch. An inline object (`blob_dir` of `None`) has nothing to
+    /// retire, so no intent is written and `None` is returned.
+    pub async fn commit_delete(
+        &self,
+        key: &str,
+        blob_dir: Option<&str>,
+        now_ms: i64,
+    ) -> Result<Option<i64>> {
         let db = self.db.clone();
-        let durability = self.durability;
-        let id = self.next_intent_id();
+        let committer = self.committer.clone();
+        let chunk_gate = self.chunk_gate.clone();
+        let id = blob_dir.map(|_| self.next_intent_id());
         let key = key.to_string();
-        let blob_dir = blob_dir.to_string();
+        let blob_dir = blob_dir.map(str::to_string);
         run_blocking(move || {
             let objects = cf(&db, CF_OBJECTS)?;
-            let intents = cf(&db, CF_INTENTS)?;
-            let counters = cf(&db, CF_COUNTERS)?;
-            let existed = db.get_cf(&objects, key.as_bytes())?.is_some();
-
-            let mut batch = WriteBatch::default();
-            batch.delete_cf(&objects, key.as_bytes());
-            let value = encode_intent(INTENT_RETIRE, &key, &blob_dir, now_ms, 0);
-            batch.put_cf(&intents, id_key(id), value);
-            if existed {
-                batch.merge_cf(&counters, KEY_OBJECT_COUNT, (-1i64).to_le_bytes());
+            let existing = db
+                .get_cf(&objects, key.as_bytes())?
+                .map(|v| decode_object(key.as_bytes(), &v))
+                .transpose()?;
+
+            let mut ops = Ops::default();
+            ops.delete(CF_OBJECTS, key.as_bytes());
+            ops.change(&key, now_ms);
+            if let (Some(blob_dir), Some(id)) = (&blob_dir, id) {
+                let value = encode_intent(INTENT_RETIRE, &key, blob_dir, now_ms, 0);
+                ops.put(CF_INTENTS, id_key(id), value);
             }
-            db.write_opt(batch, &write_opts(durability))?;
+            let mut _chunk_guard = None;
+            if let Some(existing) = existing {
+                ops.merge(CF_COUNTERS, KEY_OBJECT_COUNT, (-1i64).to_le_bytes());
+                if existing.size != 0 {
+                    ops.merge(CF_COUNTERS, KEY_BYTE_TOTAL, (-(existing.size as i64)).to_le_bytes());
+                }
+                if chunk_deltas(&mut ops, &[], &existing.chunks) {
+                    _chunk_guard = Some(chunk_gate.read().unwrap_or_else(|e| e.into_inner()));
+                }
+            }
+            committer.commit(&db, ops)?;
             Ok(id)
         })
         .await
@@ -686,10 +1512,11 @@ impl ObjectIndex {
 
     pub async fn delete_intent(&self, id: i64) -> Result<()> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         run_blocking(move || {
-            let intents = cf(&db, CF_INTENTS)?;
-            db.delete_cf_opt(&intents, id_key(id), &write_opts(durability))?;
+            let mut ops = Ops::default();
+            ops.delete(CF_INTENTS, id_key(id));
+            committer.commit(&db, ops)?;
             Ok(())
         })
         .await
@@ -697,7 +1524,7 @@ impl ObjectIndex {
 
     pub async fn bump_intent_attempts(&self, id: i64) -> Result<()> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         run_blocking(move || {
             let intents = cf(&db, CF_INTENTS)?;
             if let Some(value) = db.get_cf(&intents, id_key(id))? {
@@ -710,7 +1537,9 @@ impl ObjectIndex {
                     record.created_at_ms,
                     record.attempts,
                 );
-                db.put_cf_opt(&intents, id_key(id), value, &write_opts(durability))?;
+                let mut ops = Ops::default();
+                ops.put(CF_INTENTS, id_key(id), value);
+                committer.commit(&db, ops)?;
             }
             Ok(())
         })
@@ -736,7 +1565,7 @@ impl ObjectIndex {
     /// fresh name was chosen before anything touched the live tree).
     pub async fn update_intent_blob_dir(&self, id: i64, blob_dir: &str) -> Result<()> {
         let db = self.db.clone();
-        let durability = self.durability;
+        let committer = self.committer.clone();
         let blob_dir = blob_dir.to_string();
         run_blocking(move || {
             let intents = cf(&db, CF_INTENTS)?;
@@ -749,7 +1578,9 @@ impl ObjectIndex {
                     record.created_at_ms,
                     record.attempts,
                 );
-                db.put_cf_opt(&intents, id_key(id), value, &write_opts(durability))?;
+                let mut ops = Ops::default();
+                ops.put(CF_INTENTS, id_key(id), value);
+                committer.commit(&db, ops)?;
             }
             Ok(())
         })
@@ -793,7 +1624,8 @@ impl ObjectIndex {
     /// Bulk newer-wins insert used by the rebuild pipeline. Per key, the entry
     /// with the highest `last_modified_ms` wins (path as tie-break for
     /// determinism); every displaced blob dir is reported so the caller can
-    /// trash it. Maintains the object counter as it goes.
+    /// trash it (an inline loser has none and is simply dropped). Maintains
+    /// the object and byte counters and the chunk references as it goes.
     pub async fn insert_rebuild_batch(&self, entries: &[ObjectRecord]) -> Result<RebuildBatchOutcome> {
         let db = self.db.clone();
         let durability = self.durability;
@@ -801,18 +1633,22 @@ impl ObjectIndex {
         run_blocking(move || {
             let objects = cf(&db, CF_OBJECTS)?;
             let counters = cf(&db, CF_COUNTERS)?;
+            let chunks = cf(&db, CF_CHUNKS)?;
             let mut outcome = RebuildBatchOutcome::default();
+            let mut lost = |record: &ObjectRecord| {
+                if let Some(dir) = record.on_disk() {
+                    outcome.loser_blob_dirs.push(dir.to_string());
+                }
+            };
 
             // Adjudicate duplicates within the batch first so the DB sees at
             // most one candidate per key.
             let mut best: std::collections::HashMap<&str, &ObjectRecord> = std::collections::HashMap::new();
             for entry in &entries {
                 match best.get(entry.object_key.as_str()) {
-                    Some(current) if !newer_wins(entry, current) => {
-                        outcome.loser_blob_dirs.push(entry.blob_dir.clone());
-                    }
+                    Some(current) if !newer_wins(entry, current) => lost(entry),
                     Some(current) => {
-                        outcome.loser_blob_dirs.push(current.blob_dir.clone());
+                        lost(current);
                         best.insert(&entry.object_key, entry);
                     }
                     None => {
@@ -822,7 +1658,10 @@ impl ObjectIndex {
             }
 
             let mut batch = WriteBatch::default();
+            let mut inserted = 0;
             let mut delta: i64 = 0;
+            let mut byte_delta: i64 = 0;
+            let mut chunk_refs = Ops::default();
             for entry in best.values() {
                 let existing = db
                     .get_cf(&objects, entry.object_key.as_bytes())?
@@ -830,20 +1669,33 @@ impl ObjectIndex {
                     .transpose()?;
                 if let Some(existing) = existing {
                     if !newer_wins(entry, &existing) {
-                        outcome.loser_blob_dirs.push(entry.blob_dir.clone());
+                        lost(entry);
                         continue;
                     }
-                    outcome.loser_blob_dirs.push(existing.blob_dir);
+                    byte_delta -= existing.size as i64;
+                    chunk_deltas(&mut chunk_refs, &entry.chunks, &existing.chunks);
+                    lost(&existing);
                 } else {
+                    chunk_deltas(&mut chunk_refs, &entry.chunks, &[]);
                     delta += 1;
                 }
+                byte_delta += entry.size as i64;
                 batch.put_cf(&objects, entry.object_key.as_bytes(), encode_object(entry));
-                outcome.inserted += 1;
+                inserted += 1;
             }
             if delta != 0 {
                 batch.merge_cf(&counters, KEY_OBJECT_COUNT, delta.to_le_bytes());
             }
+            if byte_delta != 0 {
+                batch.merge_cf(&counters, KEY_BYTE_TOTAL, byte_delta.to_le_bytes());
+            }
+            for op in chunk_refs.0 {
+                if let Op::Merge(_, id, value) = op {
+                    batch.merge_cf(&chunks, id, value);
+                }
+            }
             db.write_opt(batch, &write_opts(durability))?;
+            outcome.inserted = inserted;
             Ok(outcome)
         })
         .await
@@ -1039,6 +1891,8 @@ mod tests {
             size: 1,
             etag: "e".to_string(),
             last_modified_ms: lm,
+            chunks: Vec::new(),
+            inline: None,
         }
     }
 
@@ -1050,7 +1904,7 @@ mod tests {
     async fn put(index: &ObjectIndex, key: &str, blob_dir: &str, lm: i64) {
         let intent = index.insert_publish_intent(key, blob_dir, lm).await.unwrap();
         index
-            .commit_publish(&record(key, blob_dir, lm), intent, None, lm)
+            .commit_publish(&record(key, blob_dir, lm), intent, None, lm, None)
             .await
             .unwrap();
     }
@@ -1062,7 +1916,7 @@ mod tests {
         let intent = index.insert_publish_intent("k", "objects/x", 1).await.unwrap();
         assert_eq!(index.stale_intents(10, 0, 10).await.unwrap().len(), 1);
         let retire = index
-            .commit_publish(&record("k", "objects/x", 5), intent, None, 5)
+            .commit_publish(&record("k", "objects/x", 5), intent, None, 5, None)
             .await
             .unwrap();
         assert!(retire.is_none());
@@ -1084,10 +1938,66 @@ mod tests {
         // Overwrites do not change the count; deletes decrement it.
         put(&index, "a", "objects/y", 2).await;
         assert_eq!(index.object_count().await.unwrap(), 3);
-        index.commit_delete("a", "objects/y", 3).await.unwrap();
+        index.commit_delete("a", Some("objects/y"), 3).await.unwrap();
         assert_eq!(index.object_count().await.unwrap(), 2);
     }
 
+    #[tokio::test]
+    async fn byte_total_tracks_sizes_and_is_seeded_for_older_indexes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await;
+        let sized = |key: &str, size: u64| ObjectRecord { size, ..record(key, "objects/x", 1) };
+        for (key, size) in [("a", 10), ("b", 20)] {
+            let intent = index.insert_publish_intent(key, "objects/x", 1).await.unwrap();
+            index.commit_publish(&sized(key, size), intent, None, 1, None).await.unwrap();
+        }
+        let intent = index.insert_publish_intent("a", "objects/x", 2).await.unwrap();
+        index
+            .commit_publish(&sized("a", 4), intent, Some("objects/x"), 2, None)
+            .await
+            .unwrap();
+        assert_eq!(index.usage().await.unwrap(), BucketUsage { bytes: 24, objects: 2 });
+        index.commit_delete("b", Some("objects/x"), 3).await.unwrap();
+        assert_eq!(index.usage().await.unwrap(), BucketUsage { bytes: 4, objects: 1 });
+
+        // An index from before the counter existed is seeded on open.
+        let db = index.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let counters = cf(&db, CF_COUNTERS).unwrap();
+            db.delete_cf(&counters, KEY_BYTE_TOTAL).unwrap();
+        })
+        .await
+        .unwrap();
+        index.close().await;
+        drop(index);
+        let index = open_tmp(&tmp).await;
+        assert_eq!(index.usage().await.unwrap().bytes, 4);
+    }
+
+    #[tokio::test]
+    async fn hard_quota_rejects_commit_without_writing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await;
+        let quota = BucketQuota { hard_objects: Some(1), ..Default::default() };
+        put(&index, "a", "objects/a", 1).await;
+        let intent = index.insert_publish_intent("b", "objects/b", 2).await.unwrap();
+        let err = index
+            .commit_publish(&record("b", "objects/b", 2), intent, None, 2, Some(&quota))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QuotaExceeded(_)));
+        assert!(index.get("b").await.unwrap().is_none());
+        assert_eq!(index.usage().await.unwrap(), BucketUsage { bytes: 1, objects: 1 });
+        // The intent is untouched; cleaning it up is the caller's job.
+        assert!(index.get_intent(intent).await.unwrap().is_some());
+        // Overwriting the existing key adds no object, so it still fits.
+        let intent = index.insert_publish_intent("a", "objects/c", 3).await.unwrap();
+        index
+            .commit_publish(&record("a", "objects/c", 3), intent, Some("objects/a"), 3, Some(&quota))
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn overwrite_commit_records_retire_intent() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1095,7 +2005,7 @@ mod tests {
         put(&index, "k", "objects/a", 5).await;
         let i2 = index.insert_publish_intent("k", "objects/b", 6).await.unwrap();
         let retire = index
-            .commit_publish(&record("k", "objects/b", 7), i2, Some("objects/a"), 7)
+            .commit_publish(&record("k", "objects/b", 7), i2, Some("objects/a"), 7, None)
             .await
             .unwrap()
             .unwrap();
@@ -1112,11 +2022,11 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let index = open_tmp(&tmp).await;
         put(&index, "k", "objects/a", 5).await;
-        let retire = index.commit_delete("k", "objects/a", 6).await.unwrap();
+        let retire = index.commit_delete("k", Some("objects/a"), 6).await.unwrap();
         assert!(index.get("k").await.unwrap().is_none());
         let intents = index.stale_intents(100, 0, 10).await.unwrap();
         assert_eq!(intents.len(), 1);
-        assert_eq!(intents[0].id, retire);
+        assert_eq!(Some(intents[0].id), retire);
     }
 
     #[tokio::test]
@@ -1154,6 +2064,79 @@ mod tests {
         assert_eq!(keys, vec!["legacy".to_string()]);
     }
 
+    #[tokio::test]
+    async fn chunk_references_move_with_their_rows() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await;
+        let chunk = |id: &str| ChunkRef { id: id.to_string(), size: 10 };
+        let shared = |key: &str, lm: i64, chunks: &[&str]| ObjectRecord {
+            chunks: chunks.iter().map(|id| chunk(id)).collect(),
+            ..record(key, "objects/x", lm)
+        };
+        for (key, chunks) in [("a", vec!["c1", "c2"]), ("b", vec!["c1"])] {
+            let intent = index.insert_publish_intent(key, "objects/x", 1).await.unwrap();
+            index
+                .commit_publish(&shared(key, 1, &chunks), intent, None, 1, None)
+                .await
+                .unwrap();
+        }
+        assert_eq!(index.chunk_refs("c1").await.unwrap(), 2);
+        let usage = index.dedup_usage().await.unwrap();
+        assert_eq!((usage.chunks, usage.stored_bytes, usage.saved_bytes()), (2, 20, 10));
+
+        // Replacing `a` keeps c1, drops c2.
+        let intent = index.insert_publish_intent("a", "objects/y", 2).await.unwrap();
+        index
+            .commit_publish(&shared("a", 2, &["c1"]), intent, Some("objects/x"), 2, None)
+            .await
+            .unwrap();
+        assert_eq!(index.chunk_refs("c1").await.unwrap(), 2);
+        assert_eq!(index.chunk_refs("c2").await.unwrap(), 0);
+
+        index.commit_delete("b", Some("objects/x"), 3).await.unwrap();
+        let dropped = index
+            .drop_unreferenced_chunks(vec!["c1".into(), "c2".into(), "c3".into()])
+            .await
+            .unwrap();
+        assert_eq!(dropped, vec!["c2".to_string(), "c3".to_string()]);
+        assert_eq!(index.chunk_refs("c1").await.unwrap(), 1);
+        assert_eq!(index.dedup_usage().await.unwrap().chunks, 1);
+        assert_eq!(index.get("a").await.unwrap().unwrap().chunks, vec![chunk("c1")]);
+    }
+
+    #[tokio::test]
+    async fn inline_rows_commit_and_delete_without_intents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await;
+        put(&index, "k", "objects/x", 1).await;
+        let meta: ObjectMeta = serde_json::from_value(serde_json::json!({
+            "format_version": 1, "bucket": "b", "object_key": "k", "storage": "single",
+            "size": 3, "etag": "e", "last_modified_ms": 2, "content_type": "text/plain",
+            "parts": [{"number": 1, "file": "part.1", "size": 3, "etag": "e"}],
+        }))
+        .unwrap();
+        let inline = ObjectRecord {
+            blob_dir: String::new(),
+            size: 3,
+            inline: Some(Box::new(InlineObject { meta, data: b"abc".to_vec() })),
+            ..record("k", "", 2)
+        };
+
+        // Overwriting an on-disk row retires its dir; the inline row has none.
+        let retire = index.commit_inline(&inline, Some("objects/x"), 2, None).await.unwrap();
+        let intents = index.stale_intents(100, 0, 10).await.unwrap();
+        assert_eq!(intents.iter().map(|i| Some(i.id)).collect::<Vec<_>>(), vec![retire]);
+        index.delete_intent(retire.unwrap()).await.unwrap();
+        let row = index.get("k").await.unwrap().unwrap();
+        assert_eq!(row, inline);
+        assert_eq!(row.on_disk(), None);
+        assert_eq!(index.usage().await.unwrap().bytes, 3);
+
+        assert_eq!(index.commit_delete("k", row.on_disk(), 3).await.unwrap(), None);
+        assert!(index.stale_intents(100, 0, 10).await.unwrap().is_empty());
+        assert_eq!(index.usage().await.unwrap().objects, 0);
+    }
+
     #[tokio::test]
     async fn rebuild_batch_newer_wins_reports_losers() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1213,6 +2196,119 @@ mod tests {
         assert!(fresh > intents[0].id);
     }
 
+    #[tokio::test]
+    async fn change_log_numbers_commits_and_survives_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        let epoch = {
+            let index = open_tmp(&tmp).await;
+            put(&index, "a", "objects/a", 5).await;
+            put(&index, "b", "objects/b", 6).await;
+            index.commit_delete("a", Some("objects/a"), 7).await.unwrap();
+            // Intent bookkeeping and blob moves are not changes.
+            index.insert_publish_intent("p", "objects/p", 8).await.unwrap();
+            assert!(index.update_blob_dir("b", "objects/b", "objects/b2").await.unwrap());
+            let cursor = index.change_cursor().await.unwrap();
+            assert_eq!(cursor.seq, 3);
+            assert_eq!(cursor.epoch.len(), 16);
+            assert_eq!(
+                index.changes_after(0, 10).await.unwrap(),
+                vec![(1, "a".to_string()), (2, "b".to_string()), (3, "a".to_string())]
+            );
+            assert_eq!(index.changes_after(2, 10).await.unwrap(), vec![(3, "a".to_string())]);
+            index.close().await;
+            cursor.epoch
+        };
+        let index = open_tmp(&tmp).await;
+        put(&index, "c", "objects/c", 9).await;
+        let cursor = index.change_cursor().await.unwrap();
+        assert_eq!(cursor, ChangeCursor { epoch, seq: 4 });
+        index.fork_change_log().await.unwrap();
+        assert_ne!(index.change_cursor().await.unwrap().epoch, cursor.epoch);
+    }
+
+    #[tokio::test]
+    async fn pruning_changes_raises_the_floor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await;
+        put(&index, "a", "objects/a", 100).await;
+        put(&index, "b", "objects/b", 200).await;
+        put(&index, "c", "objects/c", 300).await;
+        assert_eq!(index.change_floor().await.unwrap(), 0);
+        assert_eq!(index.prune_changes(250).await.unwrap(), 2);
+        assert_eq!(index.change_floor().await.unwrap(), 2);
+        assert_eq!(index.changes_after(0, 10).await.unwrap(), vec![(3, "c".to_string())]);
+        assert_eq!(index.prune_changes(250).await.unwrap(), 0);
+        assert_eq!(index.change_cursor().await.unwrap().seq, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn full_durability_commits_share_synced_groups() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = ObjectIndex::open(tmp.path(), Durability::Full)
+            .await
+            .unwrap()
+            .with_commit_window(Duration::from_millis(20));
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                let index = index.clone();
+                tokio::spawn(async move { put(&index, &format!("k{i:02}"), &format!("objects/{i}"), i).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        let stats = index.group_commit_stats();
+        // One publish intent and one commit per PUT, far fewer syncs.
+        assert_eq!(stats.commits, 32);
+        assert!(stats.groups < stats.commits, "{stats:?}");
+        assert_eq!(index.object_count().await.unwrap(), 16);
+        assert_eq!(index.usage().await.unwrap().bytes, 16);
+        assert!(index.stale_intents(100, 0, 100).await.unwrap().is_empty());
+        for i in 0..16 {
+            let row = index.get(&format!("k{i:02}")).await.unwrap().unwrap();
+            assert_eq!(row.blob_dir, format!("objects/{i}"));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn grouped_commits_to_one_key_land_in_lock_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = ObjectIndex::open(tmp.path(), Durability::Full)
+            .await
+            .unwrap()
+            .with_commit_window(Duration::from_millis(5));
+        // Stand-in for the per-key write lock the store holds around a commit.
+        let key_lock = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let (index, key_lock) = (index.clone(), key_lock.clone());
+                tokio::spawn(async move {
+                    let mut order = key_lock.lock().await;
+                    put(&index, "k", &format!("objects/{i}"), i).await;
+                    order.push(i);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        let last = *key_lock.lock().await.last().unwrap();
+        index.close().await;
+        drop(index);
+        let index = ObjectIndex::open(tmp.path(), Durability::Full).await.unwrap();
+        assert_eq!(index.get("k").await.unwrap().unwrap().blob_dir, format!("objects/{last}"));
+        assert_eq!(index.object_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn relaxed_durability_writes_directly() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = open_tmp(&tmp).await.with_commit_window(Duration::from_millis(20));
+        put(&index, "k", "objects/a", 1).await;
+        assert_eq!(index.group_commit_stats(), GroupCommitStats::default());
+        assert!(index.get("k").await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn newer_value_version_is_rejected() {
         // A value tagged v2 must fail closed, not be misread as v1.
@@ -1,25 +1,114 @@
+//! Where a bucket lives on disk.
+//!
+//! A store has one or more data roots. The first is `base_dir`, which also
+//! holds the server's own databases; the rest come from `storage.data_roots`,
+//! typically one per disk. Each bucket lives wholly under
+//! `<root>/buckets/<bucket>` on exactly one of them, so its renames, hardlinks
+//! and index never cross a filesystem.
+//!
+//! Which root holds a bucket is not recorded anywhere but the tree itself: the
+//! root with `buckets/<bucket>/bucket.json` is the one. The answer is cached
+//! on first lookup and updated by create, move and delete, so the common path
+//! is one hash lookup. With a single root there is nothing to look up at all.
+
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
 use super::encoding::validate_bucket_name;
 use super::errors::Result;
 
 #[derive(Debug, Clone)]
 pub struct StorageLayout {
-    root: PathBuf,
+    roots: Arc<[PathBuf]>,
+    /// Bucket → index into `roots`, for buckets found or placed so far.
+    placement: Arc<RwLock<HashMap<String, usize>>>,
 }
 
 impl StorageLayout {
     pub fn new(root: impl Into<PathBuf>) -> Self {
-        Self { root: root.into() }
+        Self::with_data_roots(root, Vec::<PathBuf>::new())
+    }
+
+    /// A layout over `root` plus extra data roots. Repeats of a root are
+    /// dropped, so listing `base_dir` again is harmless.
+    pub fn with_data_roots<P: Into<PathBuf>>(
+        root: impl Into<PathBuf>,
+        data_roots: impl IntoIterator<Item = P>,
+    ) -> Self {
+        let mut roots = vec![root.into()];
+        for extra in data_roots {
+            let extra = extra.into();
+            if !roots.contains(&extra) {
+                roots.push(extra);
+            }
+        }
+        Self {
+            roots: roots.into(),
+            placement: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
+    /// The primary root, `base_dir`.
     pub fn root(&self) -> &Path {
-        &self.root
+        &self.roots[0]
     }
 
-    pub fn bucket_dir(&self, bucket: &str) -> Result<PathBuf> {
+    /// Every data root, the primary first.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Index into [`roots`](Self::roots) of the root holding `bucket`, or of
+    /// the primary root when none does.
+    pub fn bucket_root(&self, bucket: &str) -> Result<usize> {
         validate_bucket_name(bucket)?;
-        Ok(self.root.join("buckets").join(bucket))
+        if self.roots.len() == 1 {
+            return Ok(0);
+        }
+        if let Some(&root) = self.placement.read().unwrap().get(bucket) {
+            return Ok(root);
+        }
+        let found = self.roots.iter().position(|root| {
+            root.join("buckets")
+                .join(bucket)
+                .join("bucket.json")
+                .exists()
+        });
+        if let Some(root) = found {
+            self.place(bucket, root);
+        }
+        Ok(found.unwrap_or(0))
+    }
+
+    /// Records that `bucket` lives on root `root` from now on.
+    pub(crate) fn place(&self, bucket: &str, root: usize) {
+        self.placement
+            .write()
+            .unwrap()
+            .insert(bucket.to_string(), root);
+    }
+
+    pub(crate) fn forget(&self, bucket: &str) {
+        self.placement.write().unwrap().remove(bucket);
+    }
+
+    /// The root with the most space left, where a new bucket goes by default.
+    /// Ties, and roots that cannot be measured, go to the earlier root.
+    pub fn roomiest_root(&self) -> usize {
+        let mut best = (0, 0);
+        for (i, root) in self.roots.iter().enumerate() {
+            let available = disk_space(root).map_or(0, |space| space.available);
+            if i == 0 || available > best.1 {
+                best = (i, available);
+            }
+        }
+        best.0
+    }
+
+    pub fn bucket_dir(&self, bucket: &str) -> Result<PathBuf> {
+        let root = self.bucket_root(bucket)?;
+        Ok(self.roots[root].join("buckets").join(bucket))
     }
 
     pub fn bucket_meta_path(&self, bucket: &str) -> Result<PathBuf> {
@@ -49,6 +138,40 @@ impl StorageLayout {
     pub fn object_trash_dir(&self, bucket: &str, trash_id: &str) -> Result<PathBuf> {
         Ok(self.trash_dir(bucket)?.join(trash_id))
     }
+
+    pub fn chunks_dir(&self, bucket: &str) -> Result<PathBuf> {
+        Ok(self.bucket_dir(bucket)?.join("chunks"))
+    }
+}
+
+/// Capacity of the filesystem holding a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskSpace {
+    pub total: u64,
+    /// Bytes an unprivileged writer can still use (`f_bavail`), which is what
+    /// the server process will actually get before writes start failing.
+    pub available: u64,
+}
+
+/// `statvfs(2)` on `path`. Portable across Unixes; `None` only when the call
+/// itself fails.
+// The statvfs field widths differ by platform, hence the casts.
+#[allow(clippy::unnecessary_cast)]
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is written by the call
+    // before it is read.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    let block = stat.f_frsize as u64;
+    Some(DiskSpace {
+        total: (stat.f_blocks as u64).saturating_mul(block),
+        available: (stat.f_bavail as u64).saturating_mul(block),
+    })
 }
 
 #[cfg(test)]
@@ -61,4 +184,30 @@ mod tests {
         let dir = layout.bucket_dir("bucket").unwrap();
         assert_eq!(dir, std::path::Path::new("/tmp/root/buckets/bucket"));
     }
+
+    #[test]
+    fn buckets_are_found_on_whichever_root_holds_them() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (a, b) = (tmp.path().join("a"), tmp.path().join("b"));
+        let layout = StorageLayout::with_data_roots(&a, [&b, &a]);
+        assert_eq!(layout.roots(), [a.clone(), b.clone()]);
+
+        std::fs::create_dir_all(b.join("buckets/far")).unwrap();
+        std::fs::write(b.join("buckets/far/bucket.json"), b"{}").unwrap();
+        assert_eq!(layout.bucket_dir("far").unwrap(), b.join("buckets/far"));
+        // Unknown buckets resolve to the primary root, and are not cached.
+        assert_eq!(layout.bucket_dir("near").unwrap(), a.join("buckets/near"));
+        layout.place("near", 1);
+        assert_eq!(layout.bucket_dir("near").unwrap(), b.join("buckets/near"));
+        layout.forget("near");
+        assert_eq!(layout.bucket_root("near").unwrap(), 0);
+    }
+
+    #[test]
+    fn disk_space_reports_the_filesystem_of_a_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let space = disk_space(tmp.path()).unwrap();
+        assert!(space.total > 0 && space.available <= space.total);
+        assert_eq!(disk_space(&tmp.path().join("missing")), None);
+    }
 }
@@ -15,8 +15,40 @@ pub enum ObjectStorageKind {
 pub struct PartMeta {
     pub number: u16,
     pub file: String,
+    /// Logical size — what the client wrote and what a GET returns.
     pub size: u64,
     pub etag: String,
+    /// Set when the part file is stored compressed; absent means raw bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<PartCompression>,
+    /// Content hash of the part file when it is shared through the bucket's
+    /// chunk store; absent for a part this object owns outright.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<String>,
+}
+
+impl PartMeta {
+    /// Length of the part file on disk.
+    pub fn stored_size(&self) -> u64 {
+        self.compression
+            .as_ref()
+            .map_or(self.size, |compression| compression.stored_size)
+    }
+}
+
+/// Compression codecs a bucket can opt into.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionCodec {
+    /// zstd in the seekable format: independent frames plus a seek table, so
+    /// ranges decompress only the frames they touch.
+    Zstd,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PartCompression {
+    pub codec: CompressionCodec,
+    pub stored_size: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -33,6 +65,8 @@ pub struct ObjectMeta {
     pub content_encoding: Option<String>,
     #[serde(default)]
     pub content_language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
     #[serde(default = "default_storage_class")]
     pub storage_class: String,
     #[serde(default)]
@@ -53,10 +87,14 @@ pub struct PutMeta {
     pub content_encoding: Option<String>,
     #[serde(default)]
     pub content_language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
     #[serde(default = "default_storage_class")]
     pub storage_class: String,
     #[serde(default)]
     pub user_meta: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<PartCompression>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -70,6 +108,8 @@ pub struct UploadMeta {
     pub content_encoding: Option<String>,
     #[serde(default)]
     pub content_language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
     #[serde(default = "default_storage_class")]
     pub storage_class: String,
     #[serde(default)]
@@ -86,6 +126,120 @@ pub struct BucketMeta {
     /// an implicit server rule and is deliberately not persisted here.
     #[serde(default)]
     pub cors: Vec<CorsRule>,
+    /// Server access logging destination; `None` leaves logging disabled.
+    #[serde(default)]
+    pub logging: Option<BucketLogging>,
+    /// Storage limits; `None` leaves the bucket unlimited.
+    #[serde(default)]
+    pub quota: Option<BucketQuota>,
+    /// Codec new part files are written with; `None` stores them raw.
+    /// Changing it never rewrites existing objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionCodec>,
+    /// Publish part files into the content-addressed chunk store so identical
+    /// parts are kept once. Turning it off leaves already-shared parts shared.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dedup: bool,
+}
+
+/// Per-bucket storage limits. A write that would take usage past a `hard_*`
+/// limit is rejected at its commit point; crossing a `soft_*` limit is only
+/// logged and flagged in the console. Absent limits are unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BucketQuota {
+    #[serde(default)]
+    pub hard_bytes: Option<u64>,
+    #[serde(default)]
+    pub soft_bytes: Option<u64>,
+    #[serde(default)]
+    pub hard_objects: Option<u64>,
+    #[serde(default)]
+    pub soft_objects: Option<u64>,
+}
+
+/// Live totals for a bucket, read from the index counters.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct BucketUsage {
+    pub bytes: u64,
+    pub objects: u64,
+}
+
+/// What deduplication saves a bucket, read from the chunk reference counts.
+/// Sizes are bytes on disk, after any compression.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct DedupUsage {
+    /// Distinct chunks with at least one reference.
+    pub chunks: u64,
+    /// What those chunks occupy, each stored once.
+    pub stored_bytes: u64,
+    /// What the referencing parts would occupy if none were shared.
+    pub referenced_bytes: u64,
+}
+
+impl DedupUsage {
+    pub fn saved_bytes(&self) -> u64 {
+        self.referenced_bytes.saturating_sub(self.stored_bytes)
+    }
+}
+
+impl BucketUsage {
+    /// Usage after a write of `new_size` bytes replaces an object of
+    /// `old_size` (`None` when the key is new).
+    pub fn after_replace(self, old_size: Option<u64>, new_size: u64) -> BucketUsage {
+        BucketUsage {
+            bytes: self
+                .bytes
+                .saturating_sub(old_size.unwrap_or(0))
+                .saturating_add(new_size),
+            objects: self.objects + u64::from(old_size.is_none()),
+        }
+    }
+}
+
+impl BucketQuota {
+    pub fn is_empty(&self) -> bool {
+        self == &BucketQuota::default()
+    }
+
+    pub fn has_hard_limits(&self) -> bool {
+        self.hard_bytes.is_some() || self.hard_objects.is_some()
+    }
+
+    /// Describes the hard limit a move from `before` to `after` breaks. Only
+    /// growth is checked, so a bucket already over its limit (the limit was
+    /// lowered, say) can still be trimmed by overwrites and deletes.
+    pub fn hard_violation(&self, before: BucketUsage, after: BucketUsage) -> Option<String> {
+        exceeded(self.hard_bytes, before.bytes, after.bytes, "hard byte limit")
+            .or_else(|| exceeded(self.hard_objects, before.objects, after.objects, "hard object limit"))
+    }
+
+    /// Describes the soft limit a move from `before` to `after` crosses.
+    /// Writes that stay above an already-crossed limit report nothing, so the
+    /// warning fires once per crossing rather than on every write.
+    pub fn soft_crossing(&self, before: BucketUsage, after: BucketUsage) -> Option<String> {
+        let crossed = |limit: Option<u64>, before: u64, after: u64| {
+            limit.filter(|&limit| before <= limit && after > limit)
+        };
+        crossed(self.soft_bytes, before.bytes, after.bytes)
+            .map(|limit| format!("soft byte limit of {limit} crossed ({} bytes stored)", after.bytes))
+            .or_else(|| {
+                crossed(self.soft_objects, before.objects, after.objects).map(|limit| {
+                    format!("soft object limit of {limit} crossed ({} objects stored)", after.objects)
+                })
+            })
+    }
+}
+
+fn exceeded(limit: Option<u64>, before: u64, after: u64, what: &str) -> Option<String> {
+    let limit = limit?;
+    (after > before && after > limit).then(|| format!("{what} of {limit} would be exceeded ({after} after this write)"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BucketLogging {
+    pub target_bucket: String,
+    #[serde(default)]
+    pub target_prefix: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -123,6 +277,13 @@ pub fn content_language_or_none(value: Option<&str>) -> Option<String> {
         .map(str::to_string)
 }
 
+pub fn cache_control_or_none(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
 pub fn storage_class_or_default(value: Option<&str>) -> String {
     value
         .map(str::trim)
@@ -220,6 +381,7 @@ mod tests {
             content_type: "text/plain".to_string(),
             content_encoding: None,
             content_language: None,
+            cache_control: None,
             storage_class: DEFAULT_STORAGE_CLASS.to_string(),
             user_meta: BTreeMap::new(),
             parts: vec![PartMeta {
@@ -227,6 +389,8 @@ mod tests {
                 file: "part.1".to_string(),
                 size: 3,
                 etag: "etag".to_string(),
+                compression: None,
+                chunk: None,
             }],
         };
         let json = serde_json::to_string(&meta).unwrap();
@@ -249,8 +413,42 @@ mod tests {
         let meta: ObjectMeta = serde_json::from_str(json).unwrap();
         assert_eq!(meta.content_encoding, None);
         assert_eq!(meta.content_language, None);
+        assert_eq!(meta.cache_control, None);
         assert_eq!(meta.storage_class, DEFAULT_STORAGE_CLASS);
         assert!(meta.user_meta.is_empty());
         assert_eq!(meta.object_key, "key");
     }
+
+    #[test]
+    fn quota_checks_only_growth_and_reports_soft_crossings_once() {
+        let quota = BucketQuota {
+            hard_bytes: Some(100),
+            soft_bytes: Some(50),
+            hard_objects: Some(2),
+            soft_objects: None,
+        };
+        let usage = BucketUsage { bytes: 40, objects: 1 };
+
+        let grown = usage.after_replace(None, 20);
+        assert_eq!(grown, BucketUsage { bytes: 60, objects: 2 });
+        assert_eq!(quota.hard_violation(usage, grown), None);
+        assert!(quota.soft_crossing(usage, grown).unwrap().contains("soft byte limit of 50"));
+        assert_eq!(quota.soft_crossing(grown, grown.after_replace(Some(20), 30)), None);
+
+        let too_big = usage.after_replace(None, 61);
+        assert!(quota.hard_violation(usage, too_big).unwrap().contains("hard byte limit of 100"));
+        let third = grown.after_replace(None, 1);
+        assert!(quota.hard_violation(grown, third).unwrap().contains("hard object limit of 2"));
+
+        // Over the limit already (it was lowered): shrinking overwrites pass.
+        let over = BucketUsage { bytes: 500, objects: 5 };
+        assert_eq!(quota.hard_violation(over, over.after_replace(Some(300), 10)), None);
+        assert!(quota.hard_violation(over, over.after_replace(Some(10), 11)).is_some());
+    }
+
+    #[test]
+    fn bucket_meta_without_quota_deserializes_unlimited() {
+        let meta: BucketMeta = serde_json::from_str(r#"{"created_at_ms":1}"#).unwrap();
+        assert_eq!(meta.quota, None);
+    }
 }
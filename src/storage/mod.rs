@@ -1,5 +1,8 @@
 pub mod aws_chunked;
+pub mod backup;
 pub mod cache;
+pub mod chunks;
+pub mod compression;
 pub mod config;
 pub mod encoding;
 pub mod errors;
@@ -7,8 +10,10 @@ pub mod index;
 pub mod layout;
 pub mod locks;
 pub mod metadata;
+pub mod placement;
 pub mod rawdb;
 pub mod scan;
+pub mod snapshot;
 pub mod staging;
 pub mod store;
 pub mod sweeper;
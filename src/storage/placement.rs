@@ -0,0 +1,678 @@
+//! Moving a bucket to another data root.
+//!
+//! A move first copies the bucket's tree to `<target>/buckets/.moving-<bucket>`
+//! while the bucket stays fully live. Then it switches over:
+//!
+//! 1. requests against the bucket get 503 ([`StorageError::BucketMoving`]);
+//! 2. the bucket's index is sealed under the [`CommitGate`] and checkpointed
+//!    into the copy, so a request still holding the old handle fails its
+//!    commit instead of landing in a database that is about to go;
+//! 3. the source is renamed to `.leaving-<bucket>`, which fails any write
+//!    still aimed at its paths, and a second pass copies what changed since
+//!    the first and drops what went away;
+//! 4. a `.move-complete` marker goes into the copy, the copy is renamed into
+//!    place, the placement flips, and the old tree is deleted.
+//!
+//! The bucket is unavailable for as long as the second pass takes — a walk of
+//! its tree plus whatever was written during the first pass. Other buckets are
+//! held up only for the instant of the seal. Uploads in flight across the
+//! switch fail and are retried by their clients; nothing acknowledged is lost.
+//!
+//! Hardlinked files (dedup chunks and the parts sharing them, linked copies)
+//! stay linked on the target. A crash leaves at most one `.moving-` and one
+//! `.leaving-` directory per bucket, which
+//! [`recover_bucket_moves`](LocalObjectStore::recover_bucket_moves) settles at
+//! startup: a complete copy whose source already left wins, anything else
+//! rolls back to the source.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use super::encoding::validate_bucket_name;
+use super::errors::{Result, StorageError};
+use super::index::{index_db_path, CommitGate, Durability};
+use super::layout::disk_space;
+use super::store::LocalObjectStore;
+
+const MOVING_PREFIX: &str = ".moving-";
+const LEAVING_PREFIX: &str = ".leaving-";
+const COMPLETE_MARKER: &str = ".move-complete";
+const INDEX_DIR: &str = "index.rocksdb";
+
+/// One data root: its capacity and the buckets placed on it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootUsage {
+    pub path: String,
+    /// `base_dir`, which also holds the server's own databases.
+    pub primary: bool,
+    /// Filesystem size and free bytes; `None` when it cannot be measured.
+    pub total_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+    pub buckets: Vec<String>,
+}
+
+/// What [`LocalObjectStore::move_bucket`] reports as it goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveProgress {
+    /// Bytes the first pass will copy.
+    Measured(u64),
+    /// Bytes copied since the last report.
+    Copied(u64),
+    /// The bucket is unavailable until the move returns.
+    Switching,
+}
+
+impl LocalObjectStore {
+    /// Every data root, the primary first, with its capacity and buckets.
+    pub async fn root_usage(&self) -> Result<Vec<RootUsage>> {
+        let layout = self.layout();
+        let mut out: Vec<RootUsage> = layout
+            .roots()
+            .iter()
+            .enumerate()
+            .map(|(i, root)| {
+                let space = disk_space(root);
+                RootUsage {
+                    path: root.display().to_string(),
+                    primary: i == 0,
+                    total_bytes: space.map(|s| s.total),
+                    available_bytes: space.map(|s| s.available),
+                    buckets: Vec::new(),
+                }
+            })
+            .collect();
+        for (bucket, _) in self.list_buckets().await? {
+            let root = layout.bucket_root(&bucket)?;
+            out[root].buckets.push(bucket);
+        }
+        Ok(out)
+    }
+
+    /// Moves `bucket` to data root `target` (an index into the layout's roots)
+    /// while it stays in service; see the module docs. `cancel` stops the
+    /// copy, but not the switch-over once it has begun.
+    pub async fn move_bucket(
+        &self,
+        bucket: &str,
+        target: usize,
+        cancel: &CancellationToken,
+        on_progress: &(dyn Fn(MoveProgress) + Send + Sync),
+    ) -> Result<()> {
+        validate_bucket_name(bucket)?;
+        let roots = self.layout().roots().to_vec();
+        let Some(target_root) = roots.get(target) else {
+            return Err(StorageError::InvalidPlacement(format!(
+                "no data root #{target}"
+            )));
+        };
+        if !self.bucket_exists(bucket).await {
+            return Err(StorageError::BucketNotFound(bucket.to_string()));
+        }
+        let source = self.layout().bucket_root(bucket)?;
+        if source == target {
+            return Err(StorageError::InvalidPlacement(format!(
+                "{bucket} is already on {}",
+                target_root.display()
+            )));
+        }
+        if !self.try_begin_move(bucket) {
+            return Err(StorageError::InvalidPlacement(format!(
+                "{bucket} is already moving or being rebuilt"
+            )));
+        }
+        struct Claim<'a>(&'a LocalObjectStore, &'a str);
+        impl Drop for Claim<'_> {
+            fn drop(&mut self) {
+                self.0.end_move(self.1);
+            }
+        }
+        let _claim = Claim(self, bucket);
+
+        let paths = MovePaths {
+            live: self.layout().bucket_dir(bucket)?,
+            leaving: roots[source]
+                .join("buckets")
+                .join(format!("{LEAVING_PREFIX}{bucket}")),
+            staged: target_root
+                .join("buckets")
+                .join(format!("{MOVING_PREFIX}{bucket}")),
+            landed: target_root.join("buckets").join(bucket),
+        };
+        remove_tree(&paths.staged).await?;
+        tokio::fs::create_dir_all(&paths.staged).await?;
+        let mut sync = TreeSync::new(self.durability() == Durability::Full);
+
+        let total = tree_size(&paths.live).await;
+        on_progress(MoveProgress::Measured(total));
+        log::info!(
+            "moving bucket {bucket} from {} to {} bytes={total}",
+            roots[source].display(),
+            target_root.display()
+        );
+        let copied = sync
+            .run(&paths.live, &paths.staged, false, Some(cancel), &|n| {
+                on_progress(MoveProgress::Copied(n))
+            })
+            .await;
+        if let Err(err) = copied {
+            let _ = remove_tree(&paths.staged).await;
+            return Err(err);
+        }
+
+        on_progress(MoveProgress::Switching);
+        let result = self.switch_over(bucket, &paths, &mut sync).await;
+        match &result {
+            Ok(()) => {
+                self.layout().place(bucket, target);
+                let _ = tokio::fs::remove_file(paths.landed.join(COMPLETE_MARKER)).await;
+                log::info!("bucket {bucket} now lives on {}", target_root.display());
+            }
+            Err(err) => log::warn!("moving bucket {bucket} failed, left in place: {err}"),
+        }
+        // From here on requests find the bucket wherever it ended up.
+        self.end_move(bucket);
+        if result.is_ok() {
+            if let Err(err) = remove_tree(&paths.leaving).await {
+                log::warn!("could not remove {}: {err}", paths.leaving.display());
+            }
+        }
+        result
+    }
+
+    async fn switch_over(
+        &self,
+        bucket: &str,
+        paths: &MovePaths,
+        sync: &mut TreeSync,
+    ) -> Result<()> {
+        let index = match self.index(bucket).await {
+            Ok(index) => index,
+            Err(err) => {
+                let _ = remove_tree(&paths.staged).await;
+                return Err(err);
+            }
+        };
+        self.begin_switch(bucket);
+        let sealed = {
+            let gate: CommitGate = self.commit_gate().clone();
+            let index = index.clone();
+            let reason = StorageError::BucketMoving(bucket.to_string());
+            let checkpoint = index_db_path(&paths.staged);
+            run_blocking(move || {
+                drop({
+                    let frozen = gate.freeze();
+                    index.seal(reason);
+                    frozen
+                });
+                remove_tree_blocking(&checkpoint)?;
+                index.checkpoint(&checkpoint)
+            })
+            .await
+        };
+        // Sealed or not, the cached handle must go: the next access reopens
+        // the database from wherever the bucket ends up.
+        self.evict_index(bucket).await;
+        drop(index);
+        if let Err(err) = sealed {
+            let _ = remove_tree(&paths.staged).await;
+            return Err(err);
+        }
+
+        if let Err(err) = tokio::fs::rename(&paths.live, &paths.leaving).await {
+            let _ = remove_tree(&paths.staged).await;
+            return Err(err.into());
+        }
+        let finished = async {
+            sync.run(&paths.leaving, &paths.staged, true, None, &|_| {})
+                .await?;
+            let marker = paths.staged.join(COMPLETE_MARKER);
+            tokio::fs::write(&marker, b"").await?;
+            if sync.durable {
+                fsync_path(&marker).await?;
+                fsync_path(&paths.staged).await?;
+            }
+            tokio::fs::rename(&paths.staged, &paths.landed).await?;
+            if sync.durable {
+                for dir in [&paths.landed, &paths.leaving] {
+                    if let Some(parent) = dir.parent() {
+                        fsync_path(parent).await?;
+                    }
+                }
+            }
+            Ok::<(), StorageError>(())
+        }
+        .await;
+        if let Err(err) = finished {
+            if tokio::fs::metadata(&paths.landed).await.is_err() {
+                tokio::fs::rename(&paths.leaving, &paths.live).await?;
+                let _ = remove_tree(&paths.staged).await;
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Settles moves a crash interrupted. Run at startup, before serving: a
+    /// complete copy whose source had already been renamed away is put in
+    /// place; every other half-done move rolls back to its source.
+    pub async fn recover_bucket_moves(&self) -> Result<()> {
+        let roots = self.layout().roots().to_vec();
+        let mut moving: HashMap<String, PathBuf> = HashMap::new();
+        let mut leaving: Vec<(String, PathBuf)> = Vec::new();
+        for root in &roots {
+            let mut entries = match tokio::fs::read_dir(root.join("buckets")).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if let Some(bucket) = name.strip_prefix(MOVING_PREFIX) {
+                    moving.insert(bucket.to_string(), entry.path());
+                } else if let Some(bucket) = name.strip_prefix(LEAVING_PREFIX) {
+                    leaving.push((bucket.to_string(), entry.path()));
+                }
+            }
+        }
+        for (bucket, source) in leaving {
+            let live = roots
+                .iter()
+                .map(|root| root.join("buckets").join(&bucket))
+                .find(|dir| dir.join("bucket.json").exists());
+            let copy = moving
+                .get(&bucket)
+                .filter(|dir| dir.join(COMPLETE_MARKER).exists())
+                .cloned();
+            if let Some(live) = live {
+                log::warn!(
+                    "bucket {bucket}: finishing an interrupted move to {}",
+                    live.display()
+                );
+            } else if let Some(copy) = copy {
+                moving.remove(&bucket);
+                let landed = copy.with_file_name(&bucket);
+                log::warn!(
+                    "bucket {bucket}: completing an interrupted move to {}",
+                    landed.display()
+                );
+                tokio::fs::rename(&copy, &landed).await?;
+                let _ = tokio::fs::remove_file(landed.join(COMPLETE_MARKER)).await;
+            } else {
+                log::warn!("bucket {bucket}: rolling back an interrupted move");
+                tokio::fs::rename(&source, source.with_file_name(&bucket)).await?;
+                continue;
+            }
+            remove_tree(&source).await?;
+        }
+        for (bucket, copy) in moving {
+            log::warn!(
+                "bucket {bucket}: discarding an unfinished copy at {}",
+                copy.display()
+            );
+            remove_tree(&copy).await?;
+        }
+        Ok(())
+    }
+}
+
+struct MovePaths {
+    /// The bucket's directory before the move.
+    live: PathBuf,
+    /// Where the source sits during the switch-over.
+    leaving: PathBuf,
+    /// The copy being built on the target root.
+    staged: PathBuf,
+    /// The bucket's directory after the move.
+    landed: PathBuf,
+}
+
+/// Repeated one-way sync of a bucket tree, rsync-style: a file is copied when
+/// the destination lacks it or differs in size or mtime, and a file the source
+/// has hardlinked elsewhere in the tree is linked to the copy made first.
+/// The index database is left out; the move checkpoints it instead.
+struct TreeSync {
+    durable: bool,
+    /// Source (device, inode) → the destination path it was copied to.
+    links: HashMap<(u64, u64), PathBuf>,
+}
+
+impl TreeSync {
+    fn new(durable: bool) -> Self {
+        Self {
+            durable,
+            links: HashMap::new(),
+        }
+    }
+
+    /// One pass from `src` into `dst`. With `prune`, whatever `dst` has that
+    /// `src` does not is deleted, and every directory is fsynced when durable.
+    /// Entries vanishing from a live source mid-walk are skipped.
+    async fn run(
+        &mut self,
+        src: &Path,
+        dst: &Path,
+        prune: bool,
+        cancel: Option<&CancellationToken>,
+        on_copied: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<()> {
+        let mut pending = vec![PathBuf::new()];
+        while let Some(rel) = pending.pop() {
+            let (from, to) = (src.join(&rel), dst.join(&rel));
+            let mut entries = match tokio::fs::read_dir(&from).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            tokio::fs::create_dir_all(&to).await?;
+            let mut seen: HashSet<OsString> = HashSet::new();
+            while let Some(entry) = entries.next_entry().await? {
+                if cancel.is_some_and(|c| c.is_cancelled()) {
+                    return Err(StorageError::Io("bucket move cancelled".to_string()));
+                }
+                let name = entry.file_name();
+                if rel.as_os_str().is_empty() && name == INDEX_DIR {
+                    continue;
+                }
+                let meta = match entry.metadata().await {
+                    Ok(meta) => meta,
+                    Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                seen.insert(name.clone());
+                if meta.is_dir() {
+                    pending.push(rel.join(&name));
+                } else if meta.is_file() {
+                    match self.sync_file(&entry.path(), &to.join(&name), &meta).await {
+                        Ok(copied) => on_copied(copied),
+                        Err(err) if err.kind() == ErrorKind::NotFound => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+            }
+            if prune {
+                let mut existing = tokio::fs::read_dir(&to).await?;
+                while let Some(entry) = existing.next_entry().await? {
+                    let name = entry.file_name();
+                    let keep =
+                        seen.contains(&name) || (rel.as_os_str().is_empty() && name == INDEX_DIR);
+                    if !keep {
+                        remove_tree(&entry.path()).await?;
+                    }
+                }
+                if self.durable {
+                    fsync_path(&to).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Brings one file up to date and returns the bytes copied for it.
+    async fn sync_file(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        meta: &std::fs::Metadata,
+    ) -> std::io::Result<u64> {
+        let inode = (meta.dev(), meta.ino());
+        let linked = meta.nlink() > 1;
+        let modified = meta.modified()?;
+        if let Ok(existing) = tokio::fs::metadata(to).await {
+            if existing.len() == meta.len() && existing.modified()? == modified {
+                if linked {
+                    self.links.entry(inode).or_insert_with(|| to.to_path_buf());
+                }
+                return Ok(0);
+            }
+            // Never write through: the old copy may share its inode.
+            tokio::fs::remove_file(to).await?;
+        }
+        if linked {
+            if let Some(first) = self.links.get(&inode) {
+                if tokio::fs::hard_link(first, to).await.is_ok() {
+                    return Ok(0);
+                }
+            }
+        }
+        tokio::fs::copy(from, to).await?;
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(to)
+            .await?
+            .into_std()
+            .await;
+        let durable = self.durable;
+        tokio::task::spawn_blocking(move || {
+            file.set_modified(modified)?;
+            if durable {
+                file.sync_all()?;
+            }
+            Ok::<(), std::io::Error>(())
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        if linked {
+            self.links.insert(inode, to.to_path_buf());
+        }
+        Ok(meta.len())
+    }
+}
+
+/// Bytes in the regular files under `dir`, the index database aside.
+async fn tree_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_name() == INDEX_DIR {
+                continue;
+            }
+            match entry.metadata().await {
+                Ok(meta) if meta.is_dir() => pending.push(entry.path()),
+                Ok(meta) if meta.is_file() => total += meta.len(),
+                _ => {}
+            }
+        }
+    }
+    total
+}
+
+async fn remove_tree(path: &Path) -> Result<()> {
+    let path = path.to_path_buf();
+    run_blocking(move || remove_tree_blocking(&path)).await
+}
+
+fn remove_tree_blocking(path: &Path) -> Result<()> {
+    let removed = match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(path),
+        Ok(_) => std::fs::remove_file(path),
+        Err(err) => Err(err),
+    };
+    match removed {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+async fn fsync_path(path: &Path) -> Result<()> {
+    let path = path.to_path_buf();
+    run_blocking(move || Ok(std::fs::File::open(&path)?.sync_all()?)).await
+}
+
+async fn run_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => Err(StorageError::Io(format!(
+            "bucket move task panicked: {err}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::config::StorageConfig;
+
+    fn two_roots(tmp: &tempfile::TempDir) -> LocalObjectStore {
+        let config = StorageConfig {
+            data_roots: vec![tmp.path().join("disk2").display().to_string()],
+            ..StorageConfig::default()
+        };
+        LocalObjectStore::from_storage_config(tmp.path().join("disk1"), &config)
+    }
+
+    async fn body(store: &LocalObjectStore, key: &str) -> Vec<u8> {
+        let read = store.read_object("bucket", key).await.unwrap();
+        let mut out = Vec::new();
+        for part in &read.meta.parts {
+            out.extend(
+                tokio::fs::read(read.object_dir.join(&part.file))
+                    .await
+                    .unwrap(),
+            );
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn a_moved_bucket_keeps_its_objects_and_links() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = two_roots(&tmp);
+        store.create_bucket_on("bucket", Some(0)).await.unwrap();
+        store.set_bucket_dedup("bucket", true).await.unwrap();
+        let body_a = vec![7u8; 4096];
+        store
+            .put_object("bucket", "a", &body_a, None, None, false)
+            .await
+            .unwrap();
+        store
+            .put_object("bucket", "b", &body_a, None, None, false)
+            .await
+            .unwrap();
+        store
+            .put_object("bucket", "c", b"three", None, None, false)
+            .await
+            .unwrap();
+        let index = store.index("bucket").await.unwrap();
+
+        let reports = std::sync::Mutex::new(Vec::new());
+        store
+            .move_bucket("bucket", 1, &CancellationToken::new(), &|p| {
+                reports.lock().unwrap().push(p)
+            })
+            .await
+            .unwrap();
+
+        let disk2 = tmp.path().join("disk2/buckets/bucket");
+        assert_eq!(store.layout().bucket_dir("bucket").unwrap(), disk2);
+        assert!(!tmp.path().join("disk1/buckets/bucket").exists());
+        assert!(!tmp.path().join("disk1/buckets/.leaving-bucket").exists());
+        assert!(!disk2.join(COMPLETE_MARKER).exists());
+        assert_eq!(body(&store, "a").await, body_a);
+        assert_eq!(body(&store, "c").await, b"three");
+        // Deduplicated parts still share one chunk file on the new disk.
+        let a = store.read_object("bucket", "a").await.unwrap();
+        let part = std::fs::metadata(a.object_dir.join(&a.meta.parts[0].file)).unwrap();
+        assert!(part.nlink() >= 3, "nlink={}", part.nlink());
+
+        // The old handle is sealed; the bucket takes writes through the new one.
+        assert!(matches!(
+            index.commit_delete("a", None, 0).await,
+            Err(StorageError::BucketMoving(_))
+        ));
+        store
+            .put_object("bucket", "d", b"after", None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .index("bucket")
+                .await
+                .unwrap()
+                .object_count()
+                .await
+                .unwrap(),
+            4
+        );
+
+        let reports = reports.into_inner().unwrap();
+        let MoveProgress::Measured(total) = reports[0] else {
+            panic!("{reports:?}")
+        };
+        let copied: u64 = reports
+            .iter()
+            .map(|p| match p {
+                MoveProgress::Copied(n) => *n,
+                _ => 0,
+            })
+            .sum();
+        assert!(copied > 0 && copied <= total, "{copied} of {total}");
+        assert_eq!(reports.last(), Some(&MoveProgress::Switching));
+
+        let usage = store.root_usage().await.unwrap();
+        assert!(usage[0].buckets.is_empty() && usage[0].primary);
+        assert_eq!(usage[1].buckets, ["bucket"]);
+    }
+
+    #[tokio::test]
+    async fn interrupted_moves_settle_at_startup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = two_roots(&tmp);
+        for bucket in ["done", "undone", "copying"] {
+            store.create_bucket_on(bucket, Some(0)).await.unwrap();
+        }
+        let (disk1, disk2) = (
+            tmp.path().join("disk1/buckets"),
+            tmp.path().join("disk2/buckets"),
+        );
+        drop(store);
+        // Source renamed away and the copy complete: the copy wins.
+        std::fs::rename(disk1.join("done"), disk1.join(".leaving-done")).unwrap();
+        copy_dir(&disk1.join(".leaving-done"), &disk2.join(".moving-done"));
+        std::fs::write(disk2.join(".moving-done").join(COMPLETE_MARKER), b"").unwrap();
+        // Source renamed away but the copy unfinished: roll back.
+        std::fs::rename(disk1.join("undone"), disk1.join(".leaving-undone")).unwrap();
+        std::fs::create_dir_all(disk2.join(".moving-undone")).unwrap();
+        // Still copying: the copy is discarded.
+        std::fs::create_dir_all(disk2.join(".moving-copying")).unwrap();
+
+        let store = two_roots(&tmp);
+        store.recover_bucket_moves().await.unwrap();
+        let names: Vec<_> = std::fs::read_dir(&disk1)
+            .unwrap()
+            .chain(std::fs::read_dir(&disk2).unwrap())
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(names.iter().all(|n| !n.starts_with('.')), "{names:?}");
+        assert_eq!(store.layout().bucket_root("done").unwrap(), 1);
+        assert_eq!(store.layout().bucket_root("undone").unwrap(), 0);
+        assert_eq!(store.layout().bucket_root("copying").unwrap(), 0);
+        assert!(!disk2.join("done").join(COMPLETE_MARKER).exists());
+        assert_eq!(store.list_buckets().await.unwrap().len(), 3);
+    }
+
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to.join(entry.file_name()));
+            } else {
+                std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+            }
+        }
+    }
+}
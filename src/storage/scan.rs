@@ -28,7 +28,9 @@
 //! thing: every `meta.json` parsed and every part file stat'ed.
 //!
 //! 1. **index sweep** — sum row sizes, and record `hash64(blob_dir) →
-//!    fingerprint(size, etag, last_modified)` for every row.
+//!    fingerprint(size, etag, last_modified)` for every row. A row holding its
+//!    object inline has no blob dir to find; its body is checked against the
+//!    row on the spot instead.
 //! 2. **tree walk** — parse each `meta.json`, stat each part, and classify the
 //!    dir by looking up (and *removing*) its hash: absent → orphan candidate;
 //!    present but fingerprint differs → drift candidate.
@@ -65,6 +67,7 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 
+use md5::{Digest, Md5};
 use serde::{Deserialize, Serialize};
 use tokio::task::yield_now;
 use tokio_util::sync::CancellationToken;
@@ -133,6 +136,9 @@ pub enum FindingKind {
     CorruptObject,
     /// Row and `meta.json` disagree on size/etag/last-modified.
     IndexDrift,
+    /// A row holding its object inline whose body does not match the row's
+    /// size or ETag. There are no other bytes anywhere to fall back on.
+    CorruptInline,
     /// Empty fanout directories under `objects/` — wasted inodes, no data.
     /// Aggregated into one finding per bucket.
     EmptyFanout,
@@ -147,6 +153,7 @@ impl FindingKind {
             FindingKind::UnreadableBlob => "unreadable_blob",
             FindingKind::CorruptObject => "corrupt_object",
             FindingKind::IndexDrift => "index_drift",
+            FindingKind::CorruptInline => "corrupt_inline",
             FindingKind::EmptyFanout => "empty_fanout",
         }
     }
@@ -161,7 +168,7 @@ impl FindingKind {
             // is to act on. If a row *does* point here, that row shows up
             // separately as a missing blob, with its own row-deleting repair.
             FindingKind::UnreadableBlob => &[RepairAction::TrashBlob],
-            FindingKind::MissingBlob => &[RepairAction::DeleteRow],
+            FindingKind::MissingBlob | FindingKind::CorruptInline => &[RepairAction::DeleteRow],
             FindingKind::CorruptObject => &[
                 RepairAction::Quarantine,
                 RepairAction::TrashBlob,
@@ -339,6 +346,18 @@ pub struct BucketReport {
     pub logical_bytes: u64,
     /// Bytes physically present under `objects/` (includes orphans).
     pub objects_bytes: u64,
+    /// Logical bytes of the part files stored compressed, and what they
+    /// actually take on disk — the saving is the difference.
+    #[serde(default)]
+    pub compressed_logical_bytes: u64,
+    #[serde(default)]
+    pub compressed_stored_bytes: u64,
+    /// Rows that hold their object inline (counted in `objects_indexed`), and
+    /// the bytes they carry — space in the index rather than under `objects/`.
+    #[serde(default)]
+    pub inline_objects: u64,
+    #[serde(default)]
+    pub inline_bytes: u64,
     pub trash_bytes: u64,
     pub staging_bytes: u64,
     pub index_bytes: u64,
@@ -509,6 +528,24 @@ impl LocalObjectStore {
             }
             for row in &batch {
                 report.logical_bytes += row.size;
+                if row.inline.is_some() {
+                    report.inline_objects += 1;
+                    report.inline_bytes += row.size;
+                    if let Some(defect) = inline_defect(row) {
+                        let finding = Finding::new(
+                            bucket,
+                            FindingKind::CorruptInline,
+                            Some(row.object_key.clone()),
+                            None,
+                            defect,
+                            row.size,
+                        )
+                        .with("index_size", row.size)
+                        .with("index_etag", row.etag.trim_matches('"').to_string());
+                        emit(sink, &mut report, progress, finding).await?;
+                    }
+                    continue;
+                }
                 rows.insert(
                     hash64(&row.blob_dir),
                     attr_fingerprint(row.size, &row.etag, row.last_modified_ms),
@@ -522,7 +559,7 @@ impl LocalObjectStore {
         }
         // The counter is maintained by merge operator and can drift from the
         // rows actually present; the sweep just counted them, so trust that.
-        report.objects_indexed = rows.len() as u64;
+        report.objects_indexed = rows.len() as u64 + report.inline_objects;
         progress
             .objects_total
             .store(report.objects_indexed, Ordering::Relaxed);
@@ -707,15 +744,21 @@ impl LocalObjectStore {
             // one stat per part, which the read_dir above already paid.
             let mut problems: Vec<String> = Vec::new();
             let mut parts_bytes: u64 = 0;
+            let (mut compressed_logical, mut compressed_stored) = (0u64, 0u64);
             for part in &meta.parts {
                 report.parts_checked += 1;
                 progress.parts_checked.fetch_add(1, Ordering::Relaxed);
                 parts_bytes += part.size;
+                if part.compression.is_some() {
+                    compressed_logical += part.size;
+                    compressed_stored += part.stored_size();
+                }
                 match files.iter().find(|(name, _)| *name == part.file) {
                     None => problems.push(format!("part {} ({}) missing", part.number, part.file)),
-                    Some((_, on_disk)) if *on_disk != part.size => problems.push(format!(
+                    Some((_, on_disk)) if *on_disk != part.stored_size() => problems.push(format!(
                         "part {} is {on_disk}B on disk, meta says {}B",
-                        part.number, part.size
+                        part.number,
+                        part.stored_size()
                     )),
                     Some(_) => {}
                 }
@@ -755,7 +798,10 @@ impl LocalObjectStore {
             // cleanly becomes a candidate resolved exactly below.
             let fingerprint = attr_fingerprint(meta.size, &meta.etag, meta.last_modified_ms);
             match row_fingerprint {
-                Some(row_fp) if row_fp == fingerprint => {}
+                Some(row_fp) if row_fp == fingerprint => {
+                    report.compressed_logical_bytes += compressed_logical;
+                    report.compressed_stored_bytes += compressed_stored;
+                }
                 Some(_) => candidates.push(Candidate {
                     rel,
                     object_key: meta.object_key.clone(),
@@ -810,7 +856,10 @@ impl LocalObjectStore {
                         FindingKind::SupersededBlob,
                         Some(candidate.object_key.clone()),
                         Some(candidate.rel.clone()),
-                        format!("the live row points at {}", row.blob_dir),
+                        match row.on_disk() {
+                            Some(dir) => format!("the live row points at {dir}"),
+                            None => "the live row holds the object inline".to_string(),
+                        },
                         candidate.bytes,
                     )
                     .with("live_blob_dir", row.blob_dir.clone())
@@ -1091,6 +1140,7 @@ impl LocalObjectStore {
                         ));
                     }
                     // For a missing-blob finding, confirm it is still missing.
+                    // (A corrupt-inline finding has no blob dir.)
                     if finding.kind == FindingKind::MissingBlob
                         && tokio::fs::metadata(bucket_dir.join(rel).join("meta.json"))
                             .await
@@ -1102,6 +1152,12 @@ impl LocalObjectStore {
                         ));
                     }
                 }
+                if finding.kind == FindingKind::CorruptInline && inline_defect(&row).is_none() {
+                    return Ok((
+                        FindingState::Stale,
+                        "the row no longer holds a damaged inline object".to_string(),
+                    ));
+                }
                 self.delete_row(bucket, &object_key, &row).await?;
                 Ok((FindingState::Repaired, "index row deleted".to_string()))
             }
@@ -1187,13 +1243,36 @@ impl LocalObjectStore {
     /// either already gone or handled by the caller.
     async fn delete_row(&self, bucket: &str, key: &str, row: &ObjectRecord) -> Result<()> {
         let index = self.index(bucket).await?;
-        let retire_id = index.commit_delete(key, &row.blob_dir, now_ms()).await?;
+        let retire_id = index.commit_delete(key, row.on_disk(), now_ms()).await?;
         self.forget_cached_meta(bucket, key);
-        let _ = index.delete_intent(retire_id).await;
+        if let Some(retire_id) = retire_id {
+            let _ = index.delete_intent(retire_id).await;
+        }
         Ok(())
     }
 }
 
+/// What is wrong with the body an inline row carries, if anything: it must
+/// be the row's size and, for a single-part ETag, hash to it.
+fn inline_defect(row: &ObjectRecord) -> Option<String> {
+    let inline = row.inline.as_ref()?;
+    let len = inline.data.len() as u64;
+    if len != row.size || inline.meta.size != row.size {
+        return Some(format!(
+            "inline body is {len}B, meta says {}B, index says {}B",
+            inline.meta.size, row.size
+        ));
+    }
+    let etag = row.etag.trim_matches('"');
+    if !etag.contains('-') {
+        let md5 = format!("{:x}", Md5::digest(&inline.data));
+        if md5 != etag {
+            return Some(format!("inline body hashes to {md5}, etag is {etag}"));
+        }
+    }
+    None
+}
+
 /// Exact re-comparison of a row against the meta a walk read, for the drift
 /// branch. `None` means they actually agree (the hash lied).
 fn drift_detail(row: &ObjectRecord, candidate: &Candidate) -> Option<String> {
@@ -1320,6 +1399,7 @@ fn is_legacy_layout(blob_dir: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::metadata::CompressionCodec;
 
     struct Scanned {
         report: BucketReport,
@@ -1370,7 +1450,7 @@ mod tests {
             .insert_publish_intent(key, &aged.blob_dir, now_ms())
             .await
             .unwrap();
-        index.commit_publish(&aged, intent, None, now_ms()).await.unwrap();
+        index.commit_publish(&aged, intent, None, now_ms(), None).await.unwrap();
         aged
     }
 
@@ -1402,6 +1482,56 @@ mod tests {
         assert_eq!(result.report.parts_checked, 1);
     }
 
+    #[tokio::test]
+    async fn compressed_parts_are_checked_by_stored_size_and_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = LocalObjectStore::new(tmp.path());
+        store.create_bucket("bkt").await.unwrap();
+        store
+            .set_bucket_compression("bkt", Some(CompressionCodec::Zstd))
+            .await
+            .unwrap();
+        let body = vec![b'z'; 100_000];
+        store.put_object("bkt", "z", &body, None, None, false).await.unwrap();
+        let result = scan(&store, "bkt").await;
+        assert!(result.findings.is_empty(), "{:?}", result.findings);
+        assert_eq!(result.report.logical_bytes, 100_000);
+        assert_eq!(result.report.compressed_logical_bytes, 100_000);
+        let stored = result.report.compressed_stored_bytes;
+        assert!(stored > 0 && stored < 1_000, "{stored}");
+    }
+
+    #[tokio::test]
+    async fn damaged_inline_object_is_reported_and_its_row_deleted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = crate::storage::config::StorageConfig {
+            inline_threshold: 16,
+            ..Default::default()
+        };
+        let store = LocalObjectStore::from_storage_config(tmp.path(), &config);
+        store.create_bucket("bkt").await.unwrap();
+        store.put_object("bkt", "a.txt", b"hello", None, None, false).await.unwrap();
+        store.put_object("bkt", "b.txt", b"intact", None, None, false).await.unwrap();
+        let index = store.index("bkt").await.unwrap();
+        let mut row = index.get("a.txt").await.unwrap().unwrap();
+        row.inline.as_mut().unwrap().data = b"jello".to_vec();
+        index.commit_inline(&row, None, now_ms(), None).await.unwrap();
+
+        let result = scan(&store, "bkt").await;
+        assert_eq!(kinds(&result), vec![FindingKind::CorruptInline]);
+        assert!(result.findings[0].detail.contains("hashes to"));
+        assert_eq!((result.report.objects_indexed, result.report.inline_objects), (2, 2));
+        assert_eq!(result.report.inline_bytes, 11);
+        assert_eq!(result.report.blob_dirs, 0);
+
+        let (state, _) = store
+            .repair_finding(&result.findings[0], RepairAction::DeleteRow)
+            .await;
+        assert_eq!(state, FindingState::Repaired);
+        assert!(index.get("a.txt").await.unwrap().is_none());
+        assert!(scan(&store, "bkt").await.findings.is_empty());
+    }
+
     #[tokio::test]
     async fn missing_part_file_is_corrupt() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1538,6 +1668,7 @@ mod tests {
                 intent,
                 None,
                 now_ms(),
+                None,
             )
             .await
             .unwrap();
@@ -0,0 +1,753 @@
+//! Online snapshots of a whole data root, and restore from one.
+//!
+//! A snapshot is a directory that *is* a data root in miniature:
+//!
+//! ```text
+//! <snapshots>/<id>/
+//!   manifest.json                 written last — its presence marks completion
+//!   admin.rocksdb/                RocksDB checkpoint (any CheckpointSource)
+//!   buckets/<bucket>/bucket.json
+//!   buckets/<bucket>/index.rocksdb/   RocksDB checkpoint
+//!   buckets/<bucket>/objects/…    hardlinks to every indexed blob dir
+//!   buckets/<bucket>/chunks/…     hardlinks to the dedup chunk store
+//! ```
+//!
+//! ## One cut
+//!
+//! Every bucket index commits through a shared [`CommitGate`]. A snapshot
+//! freezes it, checkpoints every index and every extra database, copies each
+//! `bucket.json`, and releases it — writers stall at their commit point for
+//! the few milliseconds that takes and never fail. Because the index row *is*
+//! the object, that instant is the cut: the snapshot holds exactly the objects
+//! whose rows the checkpoints hold.
+//!
+//! ## Blobs after the cut
+//!
+//! Blob dirs are immutable once published, so they are hardlinked after the
+//! gate reopens, walking the *checkpointed* rows. A blob a row points at can
+//! only move after the cut: an overwrite or delete retires it to the bucket's
+//! trash (kept for at least the trash retention, hours), and a layout
+//! migration repoints the live row at a new dir. Both are looked up when the
+//! recorded path is gone, so a busy server still produces a complete
+//! snapshot.
+//!
+//! Links need the snapshot directory on the same filesystem as the data root;
+//! anywhere else each file falls back to a byte copy.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::errors::{Result, StorageError};
+use super::index::{index_db_path, CommitGate, Durability, ObjectIndex, ObjectRecord};
+use super::staging::epoch_ms_from_staging_id;
+use super::store::LocalObjectStore;
+use super::time::now_ms;
+
+pub const MANIFEST: &str = "manifest.json";
+
+/// Suffix of a snapshot still being written. Never listed, never restored,
+/// and swept by the next snapshot if a crash left one behind.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Rows read from a checkpointed index per linking pass.
+const PAGE: i64 = 1000;
+
+/// A database outside the bucket tree that belongs in the cut — the IAM
+/// store's `admin.rocksdb`.
+pub trait CheckpointSource: Send + Sync {
+    /// Directory name, both in the snapshot and in the restored data root.
+    fn name(&self) -> &str;
+    /// Writes a consistent copy to `dest`, which does not exist yet. Blocking;
+    /// called while index commits are frozen, so it must not commit to an
+    /// object index itself.
+    fn checkpoint(&self, dest: &Path) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    #[serde(default = "manifest_version")]
+    pub v: u32,
+    pub id: String,
+    /// The cut: when index commits were frozen.
+    pub created_at_ms: i64,
+    /// Extra databases captured alongside the buckets, by directory name.
+    #[serde(default)]
+    pub databases: Vec<String>,
+    pub buckets: Vec<SnapshotBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotBucket {
+    pub name: String,
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+fn manifest_version() -> u32 {
+    MANIFEST_VERSION
+}
+
+impl SnapshotManifest {
+    pub fn objects(&self) -> u64 {
+        self.buckets.iter().map(|b| b.objects).sum()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.buckets.iter().map(|b| b.bytes).sum()
+    }
+}
+
+/// Sortable, filesystem-safe id from the snapshot's start time, e.g.
+/// `20261018T101500123Z`.
+fn snapshot_id(epoch_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(epoch_ms)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%S%3fZ")
+        .to_string()
+}
+
+/// What a blob's `meta.json` must say for it to be the blob a row recorded.
+#[derive(Deserialize)]
+struct BlobIdentity {
+    object_key: String,
+    etag: String,
+    last_modified_ms: i64,
+}
+
+type BlobKey = (String, String, i64);
+
+fn blob_key(record: &ObjectRecord) -> BlobKey {
+    (
+        record.object_key.clone(),
+        record.etag.clone(),
+        record.last_modified_ms,
+    )
+}
+
+impl LocalObjectStore {
+    /// Takes a snapshot of every bucket plus `extras` into a new directory
+    /// under `snapshots_dir` and returns its manifest. Safe against a live
+    /// server; one runs at a time. `on_progress` is called with the number of
+    /// objects linked since the last call. A bucket mid-rebuild fails the
+    /// snapshot with [`StorageError::BucketRebuilding`] rather than leaving
+    /// it out.
+    pub async fn snapshot(
+        &self,
+        snapshots_dir: &Path,
+        extras: &[Arc<dyn CheckpointSource>],
+        on_progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<SnapshotManifest> {
+        let _one_at_a_time = self.snapshot_lock().lock().await;
+        tokio::fs::create_dir_all(snapshots_dir).await?;
+        remove_partials(snapshots_dir).await;
+
+        // Open every index before freezing: opening is async and fallible,
+        // and the freeze must cover nothing but the checkpoints.
+        let mut indexes = Vec::new();
+        for (bucket, _) in self.list_buckets().await? {
+            match self.index(&bucket).await {
+                Ok(index) => indexes.push((bucket, index)),
+                Err(StorageError::BucketNotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let id = snapshot_id(now_ms());
+        let partial = snapshots_dir.join(format!("{id}{PARTIAL_SUFFIX}"));
+        match self
+            .write_snapshot(&partial, id.clone(), indexes, extras, on_progress)
+            .await
+        {
+            Ok(manifest) => {
+                tokio::fs::rename(&partial, snapshots_dir.join(&id)).await?;
+                log::info!(
+                    "snapshot {id} complete buckets={} objects={} bytes={}",
+                    manifest.buckets.len(),
+                    manifest.objects(),
+                    manifest.bytes()
+                );
+                Ok(manifest)
+            }
+            Err(err) => {
+                let _ = tokio::fs::remove_dir_all(&partial).await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn write_snapshot(
+        &self,
+        partial: &Path,
+        id: String,
+        indexes: Vec<(String, ObjectIndex)>,
+        extras: &[Arc<dyn CheckpointSource>],
+        on_progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<SnapshotManifest> {
+        let cut_ms = {
+            let gate: CommitGate = self.commit_gate().clone();
+            let layout = self.layout().clone();
+            let partial = partial.to_path_buf();
+            let indexes = indexes.clone();
+            let extras = extras.to_vec();
+            run_blocking(move || {
+                std::fs::create_dir_all(partial.join("buckets"))?;
+                let _frozen = gate.freeze();
+                let cut_ms = now_ms();
+                for (bucket, index) in &indexes {
+                    let dest = partial.join("buckets").join(bucket);
+                    std::fs::create_dir_all(&dest)?;
+                    index.checkpoint(&index_db_path(&dest))?;
+                    std::fs::copy(layout.bucket_meta_path(bucket)?, dest.join("bucket.json"))?;
+                }
+                for extra in &extras {
+                    extra.checkpoint(&partial.join(extra.name()))?;
+                }
+                Ok(cut_ms)
+            })
+            .await?
+        };
+
+        let mut buckets = Vec::with_capacity(indexes.len());
+        for (bucket, live) in &indexes {
+            buckets.push(
+                self.link_bucket_blobs(partial, bucket, live, cut_ms, on_progress)
+                    .await?,
+            );
+        }
+        let manifest = SnapshotManifest {
+            v: MANIFEST_VERSION,
+            id,
+            created_at_ms: cut_ms,
+            databases: extras.iter().map(|e| e.name().to_string()).collect(),
+            buckets,
+        };
+        tokio::fs::write(
+            partial.join(MANIFEST),
+            serde_json::to_vec_pretty(&manifest)?,
+        )
+        .await?;
+        Ok(manifest)
+    }
+
+    /// Hardlinks every blob dir the checkpointed index references into the
+    /// snapshot, following blobs that moved after the cut.
+    async fn link_bucket_blobs(
+        &self,
+        partial: &Path,
+        bucket: &str,
+        live: &ObjectIndex,
+        cut_ms: i64,
+        on_progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<SnapshotBucket> {
+        let bucket_dir = self.layout().bucket_dir(bucket)?;
+        let snap_dir = partial.join("buckets").join(bucket);
+        let checkpoint =
+            ObjectIndex::open_at(&index_db_path(&snap_dir), Durability::Relaxed).await?;
+        let mut summary = SnapshotBucket {
+            name: bucket.to_string(),
+            objects: 0,
+            bytes: 0,
+        };
+        let mut trash: Option<HashMap<BlobKey, PathBuf>> = None;
+        let mut after: Option<String> = None;
+        loop {
+            let page = checkpoint.all_entries_after(after.as_deref(), PAGE).await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.object_key.clone());
+            let missing = {
+                let (bucket_dir, snap_dir, page) =
+                    (bucket_dir.clone(), snap_dir.clone(), page.clone());
+                run_blocking(move || {
+                    let mut missing = Vec::new();
+                    // Inline objects travel inside the checkpoint itself.
+                    for record in page.into_iter().filter(|r| r.inline.is_none()) {
+                        let dest = snap_dir.join(&record.blob_dir);
+                        if !link_blob_dir(&bucket_dir.join(&record.blob_dir), &dest)? {
+                            missing.push(record);
+                        }
+                    }
+                    Ok(missing)
+                })
+                .await?
+            };
+            for record in missing {
+                let source = self
+                    .find_moved_blob(bucket, &bucket_dir, live, &record, cut_ms, &mut trash)
+                    .await?
+                    .ok_or_else(|| {
+                        StorageError::CorruptObject(format!(
+                            "{bucket}/{}: blob {} vanished during the snapshot",
+                            record.object_key, record.blob_dir
+                        ))
+                    })?;
+                let dest = snap_dir.join(&record.blob_dir);
+                if !run_blocking(move || link_blob_dir(&source, &dest)).await? {
+                    return Err(StorageError::CorruptObject(format!(
+                        "{bucket}/{}: blob moved twice during the snapshot",
+                        record.object_key
+                    )));
+                }
+            }
+            summary.objects += page.len() as u64;
+            summary.bytes += page.iter().map(|r| r.size).sum::<u64>();
+            on_progress(page.len() as u64);
+        }
+        checkpoint.close().await;
+        // Rows hold chunk ids, not paths, so the chunk store is taken as
+        // found: a restored dedup bucket keeps sharing new writes with the
+        // chunks its objects already hold.
+        let (chunks, snap_chunks) = (bucket_dir.join("chunks"), snap_dir.join("chunks"));
+        run_blocking(move || {
+            link_chunk_store(&chunks, &snap_chunks);
+            Ok(())
+        })
+        .await?;
+        Ok(summary)
+    }
+
+    /// Where the blob `record` pointed at went after the cut: the dir a layout
+    /// migration repointed the live row to, or the trash dir an overwrite or
+    /// delete retired it into. The trash is indexed once per bucket, and only
+    /// entries retired since the cut are considered.
+    async fn find_moved_blob(
+        &self,
+        bucket: &str,
+        bucket_dir: &Path,
+        live: &ObjectIndex,
+        record: &ObjectRecord,
+        cut_ms: i64,
+        trash: &mut Option<HashMap<BlobKey, PathBuf>>,
+    ) -> Result<Option<PathBuf>> {
+        if let Some(row) = live.get(&record.object_key).await? {
+            if blob_key(&row) == blob_key(record) && row.blob_dir != record.blob_dir {
+                let dir = bucket_dir.join(&row.blob_dir);
+                if dir.is_dir() {
+                    return Ok(Some(dir));
+                }
+            }
+        }
+        if trash.is_none() {
+            let trash_dir = self.layout().trash_dir(bucket)?;
+            *trash = Some(run_blocking(move || index_trash(&trash_dir, cut_ms)).await?);
+        }
+        Ok(trash
+            .as_ref()
+            .and_then(|t| t.get(&blob_key(record)).cloned()))
+    }
+}
+
+/// Maps blob identity to trash dir for everything retired at or after
+/// `since_ms` (trash ids are stamped with their retirement time).
+fn index_trash(trash_dir: &Path, since_ms: i64) -> Result<HashMap<BlobKey, PathBuf>> {
+    let mut found = HashMap::new();
+    let entries = match std::fs::read_dir(trash_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(found),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(retired) = name.to_str().and_then(|n| epoch_ms_from_staging_id(n).ok()) else {
+            continue;
+        };
+        if retired < since_ms {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path().join("meta.json")) else {
+            continue;
+        };
+        if let Ok(meta) = serde_json::from_slice::<BlobIdentity>(&bytes) {
+            found.insert(
+                (meta.object_key, meta.etag, meta.last_modified_ms),
+                entry.path(),
+            );
+        }
+    }
+    Ok(found)
+}
+
+/// Links every file of blob dir `source` into `dest`. Returns `false` — with
+/// nothing left behind — if `source` is gone or disappears mid-way.
+fn link_blob_dir(source: &Path, dest: &Path) -> Result<bool> {
+    match copy_tree(source, dest, true) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let _ = std::fs::remove_dir_all(dest);
+            Ok(false)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Links every chunk under `source` into `dest`, skipping any the collector
+/// removes mid-walk — a chunk it can remove is one no row references.
+fn link_chunk_store(source: &Path, dest: &Path) {
+    let Ok(fanout) = std::fs::read_dir(source) else {
+        return;
+    };
+    for dir in fanout.flatten() {
+        let Ok(entries) = std::fs::read_dir(dir.path()) else {
+            continue;
+        };
+        let target_dir = dest.join(dir.file_name());
+        if std::fs::create_dir_all(&target_dir).is_err() {
+            continue;
+        }
+        for entry in entries.flatten() {
+            let target = target_dir.join(entry.file_name());
+            if std::fs::hard_link(entry.path(), &target).is_err() {
+                let _ = std::fs::copy(entry.path(), &target);
+            }
+        }
+    }
+}
+
+/// Recreates `source` at `dest`. With `link`, files are hardlinked (copied
+/// when the two are on different filesystems); otherwise always copied — the
+/// right choice for a RocksDB directory that will be opened and written.
+fn copy_tree(source: &Path, dest: &Path, link: bool) -> std::io::Result<()> {
+    let entries = std::fs::read_dir(source)?;
+    std::fs::create_dir_all(dest)?;
+    for entry in entries {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target, link)?;
+        } else if !link || std::fs::hard_link(entry.path(), &target).is_err() {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+async fn remove_partials(snapshots_dir: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(snapshots_dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .ends_with(PARTIAL_SUFFIX)
+        {
+            log::warn!("removing unfinished snapshot {}", entry.path().display());
+            let _ = tokio::fs::remove_dir_all(entry.path()).await;
+        }
+    }
+}
+
+async fn run_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => Err(StorageError::Io(format!("snapshot task panicked: {err}"))),
+    }
+}
+
+async fn read_manifest(dir: &Path) -> Result<SnapshotManifest> {
+    let bytes = tokio::fs::read(dir.join(MANIFEST)).await.map_err(|err| {
+        StorageError::Io(format!(
+            "{} is not a complete snapshot: {err}",
+            dir.display()
+        ))
+    })?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Completed snapshots under `snapshots_dir`, oldest first. Unfinished ones and
+/// stray directories are skipped.
+pub async fn list_snapshots(snapshots_dir: &Path) -> Result<Vec<SnapshotManifest>> {
+    let mut out = Vec::new();
+    let mut entries = match tokio::fs::read_dir(snapshots_dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(out),
+        Err(err) => return Err(err.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .ends_with(PARTIAL_SUFFIX)
+        {
+            continue;
+        }
+        if let Ok(manifest) = read_manifest(&entry.path()).await {
+            out.push(manifest);
+        }
+    }
+    out.sort_by(|a, b| (a.created_at_ms, &a.id).cmp(&(b.created_at_ms, &b.id)));
+    Ok(out)
+}
+
+/// Resolves `id` to a completed snapshot's directory. Ids are plain names, so
+/// anything path-like is rejected before it touches the filesystem.
+pub async fn snapshot_path(snapshots_dir: &Path, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+        return Err(StorageError::Io(format!("invalid snapshot id {id:?}")));
+    }
+    let dir = snapshots_dir.join(id);
+    read_manifest(&dir).await?;
+    Ok(dir)
+}
+
+pub async fn delete_snapshot(snapshots_dir: &Path, id: &str) -> Result<()> {
+    let dir = snapshot_path(snapshots_dir, id).await?;
+    // Drop the manifest first, so a crash mid-delete leaves a directory that is
+    // plainly not a snapshot rather than a damaged one that still lists.
+    tokio::fs::remove_file(dir.join(MANIFEST)).await?;
+    tokio::fs::remove_dir_all(&dir).await?;
+    Ok(())
+}
+
+/// Deletes all but the newest `keep` snapshots and returns the removed ids.
+pub async fn prune_snapshots(snapshots_dir: &Path, keep: usize) -> Result<Vec<String>> {
+    let snapshots = list_snapshots(snapshots_dir).await?;
+    let excess = snapshots.len().saturating_sub(keep);
+    let mut removed = Vec::with_capacity(excess);
+    for manifest in snapshots.into_iter().take(excess) {
+        delete_snapshot(snapshots_dir, &manifest.id).await?;
+        removed.push(manifest.id);
+    }
+    Ok(removed)
+}
+
+/// Rebuilds a data root from a snapshot directory. The target must not hold a
+/// store already — no bucket, none of the snapshot's databases — so a restore
+/// can never merge into or clobber live data; the server must not be running
+/// on it. Databases are copied (they will be written to); blob files are
+/// hardlinked when the two share a filesystem, since nothing rewrites them.
+///
+/// The restored indexes may hold intents from operations that were in flight
+/// at the cut; the server resolves them at startup like after any crash.
+pub async fn restore_snapshot(snapshot_dir: &Path, data_root: &Path) -> Result<SnapshotManifest> {
+    let manifest = read_manifest(snapshot_dir).await?;
+    let buckets_root = data_root.join("buckets");
+    if let Ok(mut entries) = tokio::fs::read_dir(&buckets_root).await {
+        if entries.next_entry().await?.is_some() {
+            return Err(StorageError::Io(format!(
+                "{} already holds buckets; restore needs an empty data directory",
+                data_root.display()
+            )));
+        }
+    }
+    for name in &manifest.databases {
+        if data_root.join(name).exists() {
+            return Err(StorageError::Io(format!(
+                "{} already exists; restore needs an empty data directory",
+                data_root.join(name).display()
+            )));
+        }
+    }
+
+    let (source, root, plan) = (
+        snapshot_dir.to_path_buf(),
+        data_root.to_path_buf(),
+        manifest.clone(),
+    );
+    run_blocking(move || {
+        for name in &plan.databases {
+            copy_tree(&source.join(name), &root.join(name), false)?;
+        }
+        for bucket in &plan.buckets {
+            let from = source.join("buckets").join(&bucket.name);
+            let to = root.join("buckets").join(&bucket.name);
+            std::fs::create_dir_all(&to)?;
+            for entry in std::fs::read_dir(&from)? {
+                let entry = entry?;
+                let target = to.join(entry.file_name());
+                if entry.file_name() == "index.rocksdb" {
+                    copy_tree(&entry.path(), &target, false)?;
+                } else if entry.file_type()?.is_dir() {
+                    copy_tree(&entry.path(), &target, true)?;
+                } else {
+                    std::fs::copy(entry.path(), &target)?;
+                }
+            }
+        }
+        Ok(())
+    })
+    .await?;
+    // The restored indexes go on to record their own changes; give each a
+    // new change-log epoch so an incremental backup cursor taken against the
+    // source is not continued from here.
+    for bucket in &manifest.buckets {
+        let db_path = index_db_path(&buckets_root.join(&bucket.name));
+        let index = ObjectIndex::open_at(&db_path, Durability::Relaxed).await?;
+        index.fork_change_log().await?;
+        index.close().await;
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeDb;
+
+    impl CheckpointSource for FakeDb {
+        fn name(&self) -> &str {
+            "admin.rocksdb"
+        }
+
+        fn checkpoint(&self, dest: &Path) -> Result<()> {
+            std::fs::create_dir_all(dest)?;
+            std::fs::write(dest.join("CURRENT"), b"iam")?;
+            Ok(())
+        }
+    }
+
+    async fn store_with(root: &Path, objects: &[(&str, &[u8])]) -> LocalObjectStore {
+        let store = LocalObjectStore::new(root);
+        store.create_bucket("bucket").await.unwrap();
+        for (key, body) in objects {
+            store
+                .put_object("bucket", key, body, None, None, false)
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    async fn body(store: &LocalObjectStore, key: &str) -> Vec<u8> {
+        let read = store.read_object("bucket", key).await.unwrap();
+        let mut out = Vec::new();
+        for part in &read.meta.parts {
+            out.extend(
+                tokio::fs::read(read.object_dir.join(&part.file))
+                    .await
+                    .unwrap(),
+            );
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn restore_reproduces_the_cut_not_later_writes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store_with(&tmp.path().join("data"), &[("a", b"one"), ("b", b"two")]).await;
+        let snaps = tmp.path().join("snaps");
+        let extras: Vec<Arc<dyn CheckpointSource>> = vec![Arc::new(FakeDb)];
+        let manifest = store.snapshot(&snaps, &extras, &|_| {}).await.unwrap();
+        assert_eq!(manifest.objects(), 2);
+        assert_eq!(manifest.bytes(), 6);
+        assert_eq!(manifest.databases, vec!["admin.rocksdb".to_string()]);
+
+        // Writes after the cut must not leak into the snapshot.
+        store
+            .put_object("bucket", "a", b"changed", None, None, false)
+            .await
+            .unwrap();
+        store.delete_object("bucket", "b").await.unwrap();
+        store
+            .put_object("bucket", "c", b"new", None, None, false)
+            .await
+            .unwrap();
+
+        let restored_root = tmp.path().join("restored");
+        let dir = snapshot_path(&snaps, &manifest.id).await.unwrap();
+        restore_snapshot(&dir, &restored_root).await.unwrap();
+        assert!(restored_root.join("admin.rocksdb/CURRENT").exists());
+        let restored = LocalObjectStore::new(&restored_root);
+        assert_eq!(body(&restored, "a").await, b"one");
+        assert_eq!(body(&restored, "b").await, b"two");
+        assert!(restored.read_object("bucket", "c").await.is_err());
+        assert_eq!(
+            restored
+                .index("bucket")
+                .await
+                .unwrap()
+                .usage()
+                .await
+                .unwrap()
+                .objects,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn blob_retired_after_the_cut_is_found_in_trash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store_with(tmp.path(), &[("k", b"payload")]).await;
+        let index = store.index("bucket").await.unwrap();
+        let record = index.get("k").await.unwrap().unwrap();
+        let bucket_dir = store.layout().bucket_dir("bucket").unwrap();
+        let cut_ms = now_ms();
+        store.delete_object("bucket", "k").await.unwrap();
+        assert!(!bucket_dir.join(&record.blob_dir).exists());
+
+        let mut trash = None;
+        let found = store
+            .find_moved_blob("bucket", &bucket_dir, &index, &record, cut_ms, &mut trash)
+            .await
+            .unwrap()
+            .expect("retired blob should be in trash");
+        assert!(found.starts_with(bucket_dir.join("trash")));
+        // Nothing retired before the cut is a candidate.
+        let mut trash = None;
+        let later = now_ms() + 60_000;
+        let none = store
+            .find_moved_blob("bucket", &bucket_dir, &index, &record, later, &mut trash)
+            .await
+            .unwrap();
+        assert!(none.is_none());
+    }
+
+    #[tokio::test]
+    async fn list_and_prune_keep_the_newest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store_with(&tmp.path().join("data"), &[("k", b"v")]).await;
+        let snaps = tmp.path().join("snaps");
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(store.snapshot(&snaps, &[], &|_| {}).await.unwrap().id);
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // An unfinished snapshot is never listed.
+        tokio::fs::create_dir_all(snaps.join("x.partial"))
+            .await
+            .unwrap();
+        let listed: Vec<String> = list_snapshots(&snaps)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(listed, ids);
+
+        let removed = prune_snapshots(&snaps, 1).await.unwrap();
+        assert_eq!(removed, ids[..2].to_vec());
+        let listed: Vec<String> = list_snapshots(&snaps)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(listed, ids[2..].to_vec());
+        assert!(snapshot_path(&snaps, "../data").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_a_populated_data_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store_with(&tmp.path().join("data"), &[("k", b"v")]).await;
+        let snaps = tmp.path().join("snaps");
+        let manifest = store.snapshot(&snaps, &[], &|_| {}).await.unwrap();
+        let dir = snaps.join(&manifest.id);
+        let err = restore_snapshot(&dir, &tmp.path().join("data"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("empty data directory"), "{err}");
+    }
+}
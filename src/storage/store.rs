@@ -21,6 +21,7 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use bytes::{Buf, Bytes, BytesMut};
 use futures::{Stream, StreamExt};
@@ -28,24 +29,28 @@ use tokio_util::sync::CancellationToken;
 
 use md5::{Digest, Md5};
 use sha2::Sha256;
-use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use super::aws_chunked::decode_aws_chunked;
 use super::cache::BoundedLruCache;
-use super::config::{DurabilityMode, StorageConfig};
+use super::chunks;
+use super::compression::{open_part_range, PartReader, PartWriter};
+use super::config::{CopyMode, DurabilityMode, StorageConfig};
 use super::encoding::{
     fanout_segment, object_dir_prefix, object_dir_random_suffix, validate_bucket_name,
     validate_object_key,
 };
 use super::errors::{Result, StorageError};
 use super::index::{
-    self, Durability, IntentRecord, ListPage, ObjectRecord, ObjectIndex, INTENT_RETIRE,
+    self, ChunkRef, CommitGate, Durability, InlineObject, IntentRecord, ListPage, ObjectRecord,
+    ObjectIndex, INTENT_RETIRE,
 };
 use super::layout::StorageLayout;
 use super::locks::ObjectLockTable;
 use super::metadata::{
-    content_encoding_or_none, content_language_or_none, content_type_or_default,
-    storage_class_or_default, unquote_etag, BucketMeta, CorsRule, ObjectMeta, ObjectStorageKind, PartMeta,
+    cache_control_or_none, content_encoding_or_none, content_language_or_none,
+    content_type_or_default,
+    storage_class_or_default, unquote_etag, BucketLogging, BucketMeta, BucketQuota, BucketUsage, CompressionCodec, CorsRule, DedupUsage, ObjectMeta, ObjectStorageKind, PartCompression, PartMeta,
     PutMeta, UploadMeta,
 };
 use super::staging::{new_staging_id, validate_staging_id};
@@ -66,9 +71,12 @@ const COPY_BUFFER_SIZE: usize = 256 * 1024;
 pub struct LocalObjectStore {
     layout: StorageLayout,
     durability: Durability,
+    group_commit_window: Duration,
     rebuild_reader_threads: usize,
     rebuild_queue_bound: usize,
     rebuild_batch_size: usize,
+    copy_mode: CopyMode,
+    inline_threshold: u64,
     locks: ObjectLockTable,
     index_cache: Arc<Mutex<HashMap<String, ObjectIndex>>>,
     /// Per-bucket async locks that serialise the "cache miss -> open the
@@ -84,8 +92,17 @@ pub struct LocalObjectStore {
     rebuilding: Arc<Mutex<HashSet<String>>>,
     /// Live progress per rebuilding bucket, for status displays.
     rebuild_progress: Arc<Mutex<HashMap<String, RebuildProgress>>>,
+    /// Buckets being moved to another data root, one move per bucket.
+    moving: Arc<Mutex<HashSet<String>>>,
+    /// Moving buckets in their final switch-over; requests against them fail
+    /// with [`StorageError::BucketMoving`] (503).
+    switching: Arc<Mutex<HashSet<String>>>,
     /// Cooperative shutdown signal — cancel this to stop all background tasks.
     shutdown: CancellationToken,
+    /// Shared by every bucket index; a snapshot freezes it to take one cut.
+    commit_gate: CommitGate,
+    /// One snapshot at a time — two would just contend for the same disks.
+    snapshot_lock: Arc<tokio::sync::Mutex<()>>,
     #[cfg(test)]
     crash_points: Arc<Mutex<HashSet<String>>>,
 }
@@ -127,15 +144,22 @@ pub enum Precondition {
     /// `If-Match: "<etag>"` — the write succeeds only if the current object's
     /// ETag matches (compared ignoring surrounding quotes, weak prefix, case).
     IfMatch(String),
+    /// The write succeeds only if the current object was last modified at
+    /// exactly this time. Unlike the ETag, this also moves on a metadata-only
+    /// rewrite, so the console's metadata editor uses it.
+    IfLastModified(i64),
 }
 
 impl Precondition {
-    /// Is the precondition satisfied given the ETag currently at the key
+    /// Is the precondition satisfied given the object currently at the key
     /// (`None` when the key is empty)?
-    fn is_met(&self, current_etag: Option<&str>) -> bool {
+    fn is_met(&self, current: Option<&ObjectRecord>) -> bool {
         match self {
-            Precondition::IfNoneMatchStar => current_etag.is_none(),
-            Precondition::IfMatch(want) => current_etag.is_some_and(|have| etag_eq(have, want)),
+            Precondition::IfNoneMatchStar => current.is_none(),
+            Precondition::IfMatch(want) => current.is_some_and(|have| etag_eq(&have.etag, want)),
+            Precondition::IfLastModified(want) => {
+                current.is_some_and(|have| have.last_modified_ms == *want)
+            }
         }
     }
 }
@@ -173,7 +197,8 @@ pub struct IntentResolution {
 ///
 /// Contains only the metadata and the on-disk location; the body is never
 /// buffered in memory.  Use [`ReadObject::object_dir`] plus the part file
-/// names in `meta.parts` to stream content directly from disk.
+/// names in `meta.parts` to stream content directly from disk — or, for an
+/// object stored in its index row, the bytes already in `inline`.
 #[derive(Debug, Clone)]
 pub struct ReadObject {
     pub meta: ObjectMeta,
@@ -181,6 +206,40 @@ pub struct ReadObject {
     pub part_offsets: Vec<u64>,
     /// Directory that contains the part files (`part.1`, `part.2`, …).
     pub object_dir: std::path::PathBuf,
+    /// The whole (single-part, uncompressed) body of an inline object; it
+    /// has no part files.
+    pub inline: Option<Bytes>,
+}
+
+impl ReadObject {
+    /// Reads `take` logical bytes of `part` starting at `skip`, from its part
+    /// file or from the inline body.
+    pub async fn open_part(
+        &self,
+        part: &PartMeta,
+        skip: u64,
+        take: u64,
+    ) -> std::io::Result<PartReader> {
+        if let Some(data) = &self.inline {
+            let len = data.len() as u64;
+            let (start, end) = (skip.min(len), skip.saturating_add(take).min(len));
+            return Ok(Box::pin(std::io::Cursor::new(
+                data.slice(start as usize..end as usize),
+            )));
+        }
+        let file = tokio::fs::File::open(self.object_dir.join(&part.file)).await?;
+        open_part_range(file, part, skip, take).await
+    }
+
+    /// Reads `part` exactly as stored — still compressed, if it is.
+    pub async fn open_stored_part(&self, part: &PartMeta) -> std::io::Result<PartReader> {
+        match &self.inline {
+            Some(data) => Ok(Box::pin(std::io::Cursor::new(data.clone()))),
+            None => Ok(Box::pin(
+                tokio::fs::File::open(self.object_dir.join(&part.file)).await?,
+            )),
+        }
+    }
 }
 
 /// One part in a `CompleteMultipartUpload` request.
@@ -222,7 +281,10 @@ impl LocalObjectStore {
     }
 
     pub fn from_storage_config(root: impl Into<PathBuf>, config: &StorageConfig) -> Self {
-        Self::inner(StorageLayout::new(root), config)
+        Self::inner(
+            StorageLayout::with_data_roots(root, &config.data_roots),
+            config,
+        )
     }
 
     pub fn with_layout(layout: StorageLayout) -> Self {
@@ -236,9 +298,12 @@ impl LocalObjectStore {
                 DurabilityMode::Full => Durability::Full,
                 DurabilityMode::Relaxed => Durability::Relaxed,
             },
+            group_commit_window: Duration::from_micros(config.group_commit_window_us),
             rebuild_reader_threads: config.rebuild_reader_threads, // 0 = auto (core count)
             rebuild_queue_bound: config.rebuild_queue_bound.max(1),
             rebuild_batch_size: config.rebuild_batch_size.max(1),
+            copy_mode: config.copy_mode,
+            inline_threshold: config.inline_threshold,
             locks: ObjectLockTable::default(),
             index_cache: Arc::new(Mutex::new(HashMap::new())),
             index_open_locks: Arc::new(Mutex::new(HashMap::new())),
@@ -252,7 +317,11 @@ impl LocalObjectStore {
             )),
             rebuilding: Arc::new(Mutex::new(HashSet::new())),
             rebuild_progress: Arc::new(Mutex::new(HashMap::new())),
+            moving: Arc::new(Mutex::new(HashSet::new())),
+            switching: Arc::new(Mutex::new(HashSet::new())),
             shutdown: CancellationToken::new(),
+            commit_gate: CommitGate::default(),
+            snapshot_lock: Arc::new(tokio::sync::Mutex::new(())),
             #[cfg(test)]
             crash_points: Arc::new(Mutex::new(HashSet::new())),
         }
@@ -263,6 +332,14 @@ impl LocalObjectStore {
         self.shutdown.clone()
     }
 
+    pub(crate) fn commit_gate(&self) -> &CommitGate {
+        &self.commit_gate
+    }
+
+    pub(crate) fn snapshot_lock(&self) -> &Arc<tokio::sync::Mutex<()>> {
+        &self.snapshot_lock
+    }
+
     /// Takes the per-key write lock. Exposed for the health-scan repair path
     /// (`super::scan`), which mutates rows and blob dirs and must serialise
     /// against publish/delete of the same key exactly as the write path does.
@@ -284,6 +361,10 @@ impl LocalObjectStore {
         &self.layout
     }
 
+    pub(crate) fn durability(&self) -> Durability {
+        self.durability
+    }
+
     /// Crash injection for tests: a labeled point in a commit sequence
     /// panics when armed, simulating a crash at exactly that step.
     #[cfg(test)]
@@ -305,7 +386,23 @@ impl LocalObjectStore {
     // ── bucket management ─────────────────────────────────────────────────────
 
     pub async fn create_bucket(&self, bucket: &str) -> Result<()> {
+        self.create_bucket_on(bucket, None).await
+    }
+
+    /// Creates `bucket` on data root `root` (an index into the layout's
+    /// roots), or on the root with the most free space when `None`. A bucket
+    /// that already exists stays where it is.
+    pub async fn create_bucket_on(&self, bucket: &str, root: Option<usize>) -> Result<()> {
         validate_bucket_name(bucket)?;
+        if let Some(root) = root {
+            if root >= self.layout.roots().len() {
+                return Err(StorageError::InvalidPlacement(format!("no data root #{root}")));
+            }
+        }
+        if !self.bucket_exists(bucket).await && self.layout.roots().len() > 1 {
+            let root = root.unwrap_or_else(|| self.layout.roomiest_root());
+            self.layout.place(bucket, root);
+        }
         let bucket_dir = self.layout.bucket_dir(bucket)?;
         tokio::fs::create_dir_all(&bucket_dir).await?;
         let bucket_meta_path = self.layout.bucket_meta_path(bucket)?;
@@ -314,6 +411,10 @@ impl LocalObjectStore {
                 created_at_ms: now_ms(),
                 storage_version: "v2".to_string(),
                 cors: Vec::new(),
+                logging: None,
+                quota: None,
+                compression: None,
+                dedup: false,
             };
             write_json_atomic(&bucket_meta_path, &meta).await?;
             self.bucket_meta_cache.insert(bucket.to_string(), meta);
@@ -331,6 +432,10 @@ impl LocalObjectStore {
     }
 
     pub async fn bucket_exists(&self, bucket: &str) -> bool {
+        // Mid-switch the bucket is briefly under neither name; it still exists.
+        if self.switching.lock().unwrap().contains(bucket) {
+            return true;
+        }
         self.layout
             .bucket_meta_path(bucket)
             .ok()
@@ -370,27 +475,120 @@ impl LocalObjectStore {
         Ok(())
     }
 
+    pub async fn set_bucket_logging(
+        &self,
+        bucket: &str,
+        logging: Option<BucketLogging>,
+    ) -> Result<()> {
+        validate_bucket_name(bucket)?;
+        let _guard = self.locks.lock(bucket, "\0bucket-meta").await;
+        let path = self.layout.bucket_meta_path(bucket)?;
+        if !path.exists() {
+            return Err(StorageError::BucketNotFound(bucket.to_string()));
+        }
+        let mut meta: BucketMeta = read_json(&path).await?;
+        meta.logging = logging;
+        write_json_atomic(&path, &meta).await?;
+        if self.durability == Durability::Full {
+            fsync_file(&path).await?;
+            fsync_dir(path.parent().unwrap_or(self.layout.root())).await?;
+        }
+        self.bucket_meta_cache.insert(bucket.to_string(), meta);
+        Ok(())
+    }
+
+    /// Replaces the bucket's quota; an empty quota is stored as `None`.
+    /// Lowering a limit below current usage is allowed — it only stops growth.
+    pub async fn set_bucket_quota(&self, bucket: &str, quota: Option<BucketQuota>) -> Result<()> {
+        validate_bucket_name(bucket)?;
+        let _guard = self.locks.lock(bucket, "\0bucket-meta").await;
+        let path = self.layout.bucket_meta_path(bucket)?;
+        if !path.exists() {
+            return Err(StorageError::BucketNotFound(bucket.to_string()));
+        }
+        let mut meta: BucketMeta = read_json(&path).await?;
+        meta.quota = quota.filter(|q| !q.is_empty());
+        write_json_atomic(&path, &meta).await?;
+        if self.durability == Durability::Full {
+            fsync_file(&path).await?;
+            fsync_dir(path.parent().unwrap_or(self.layout.root())).await?;
+        }
+        self.bucket_meta_cache.insert(bucket.to_string(), meta);
+        Ok(())
+    }
+
+    /// Sets the codec for part files written from now on. Existing objects
+    /// keep the form they were written in; reads handle both.
+    pub async fn set_bucket_compression(
+        &self,
+        bucket: &str,
+        compression: Option<CompressionCodec>,
+    ) -> Result<()> {
+        validate_bucket_name(bucket)?;
+        let _guard = self.locks.lock(bucket, "\0bucket-meta").await;
+        let path = self.layout.bucket_meta_path(bucket)?;
+        if !path.exists() {
+            return Err(StorageError::BucketNotFound(bucket.to_string()));
+        }
+        let mut meta: BucketMeta = read_json(&path).await?;
+        meta.compression = compression;
+        write_json_atomic(&path, &meta).await?;
+        if self.durability == Durability::Full {
+            fsync_file(&path).await?;
+            fsync_dir(path.parent().unwrap_or(self.layout.root())).await?;
+        }
+        self.bucket_meta_cache.insert(bucket.to_string(), meta);
+        Ok(())
+    }
+
+    /// Turns deduplication on or off for objects published from now on.
+    /// Objects already sharing chunks keep sharing them either way.
+    pub async fn set_bucket_dedup(&self, bucket: &str, dedup: bool) -> Result<()> {
+        validate_bucket_name(bucket)?;
+        let _guard = self.locks.lock(bucket, "\0bucket-meta").await;
+        let path = self.layout.bucket_meta_path(bucket)?;
+        if !path.exists() {
+            return Err(StorageError::BucketNotFound(bucket.to_string()));
+        }
+        let mut meta: BucketMeta = read_json(&path).await?;
+        meta.dedup = dedup;
+        write_json_atomic(&path, &meta).await?;
+        if self.durability == Durability::Full {
+            fsync_file(&path).await?;
+            fsync_dir(path.parent().unwrap_or(self.layout.root())).await?;
+        }
+        self.bucket_meta_cache.insert(bucket.to_string(), meta);
+        Ok(())
+    }
+
     pub async fn list_buckets(&self) -> Result<Vec<(String, BucketMeta)>> {
         tokio::fs::create_dir_all(self.layout.root().join("buckets")).await?;
         let mut entries = Vec::new();
-        let mut dirs = tokio::fs::read_dir(self.layout.root().join("buckets")).await?;
-        while let Some(dir) = dirs.next_entry().await? {
-            let path = dir.path();
-            if !path.is_dir() {
-                continue;
-            }
-            let Some(name) = path.file_name().and_then(|v| v.to_str()) else {
-                continue;
+        for root in self.layout.roots() {
+            let mut dirs = match tokio::fs::read_dir(root.join("buckets")).await {
+                Ok(dirs) => dirs,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
             };
-            if validate_bucket_name(name).is_err() {
-                continue;
-            }
-            let meta_path = self.layout.bucket_meta_path(name)?;
-            if !meta_path.exists() {
-                continue;
+            while let Some(dir) = dirs.next_entry().await? {
+                let path = dir.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                let Some(name) = path.file_name().and_then(|v| v.to_str()) else {
+                    continue;
+                };
+                if validate_bucket_name(name).is_err() {
+                    continue;
+                }
+                // A name on two roots is resolved the way lookups resolve it.
+                let meta_path = self.layout.bucket_meta_path(name)?;
+                if meta_path.parent() != Some(path.as_path()) || !meta_path.exists() {
+                    continue;
+                }
+                let meta = read_json(&meta_path).await?;
+                entries.push((name.to_string(), meta));
             }
-            let meta = read_json(&meta_path).await?;
-            entries.push((name.to_string(), meta));
         }
         entries.sort_by(|a, b| a.0.cmp(&b.0));
         Ok(entries)
@@ -413,6 +611,7 @@ impl LocalObjectStore {
             index.close().await;
         }
         tokio::fs::remove_dir_all(&bucket_dir).await?;
+        self.layout.forget(bucket);
         if self.durability == Durability::Full {
             // Make the directory-entry removal durable before we ack, so a
             // crash can't resurrect the just-deleted bucket. The parent is
@@ -430,6 +629,9 @@ impl LocalObjectStore {
     /// completes.
     pub async fn index(&self, bucket: &str) -> Result<ObjectIndex> {
         let bucket_dir = self.layout.bucket_dir(bucket)?;
+        if self.switching.lock().unwrap().contains(bucket) {
+            return Err(StorageError::BucketMoving(bucket.to_string()));
+        }
         if !self.bucket_exists(bucket).await {
             return Err(StorageError::BucketNotFound(bucket.to_string()));
         }
@@ -459,7 +661,9 @@ impl LocalObjectStore {
             return Err(StorageError::BucketRebuilding(bucket.to_string()));
         }
         let index = match ObjectIndex::open(&bucket_dir, self.durability).await {
-            Ok(index) => index,
+            Ok(index) => index
+                .with_commit_window(self.group_commit_window)
+                .with_commit_gate(self.commit_gate.clone()),
             Err(StorageError::IndexOutdated(_)) => {
                 self.start_rebuild_background(bucket);
                 return Err(StorageError::BucketRebuilding(bucket.to_string()));
@@ -526,6 +730,7 @@ impl LocalObjectStore {
             content_encoding,
             None,
             None,
+            None,
             &BTreeMap::new(),
             aws_chunked,
             expected_sha256,
@@ -544,6 +749,7 @@ impl LocalObjectStore {
         content_encoding: Option<&str>,
         storage_class: Option<&str>,
         content_language: Option<&str>,
+        cache_control: Option<&str>,
         user_meta: &BTreeMap<String, String>,
         aws_chunked: bool,
         expected_sha256: Option<&str>,
@@ -563,6 +769,7 @@ impl LocalObjectStore {
                 content_encoding,
                 storage_class,
                 content_language,
+                cache_control,
                 user_meta,
                 expected_decoded_len,
             )
@@ -576,6 +783,7 @@ impl LocalObjectStore {
                 content_encoding,
                 storage_class,
                 content_language,
+                cache_control,
                 user_meta,
                 expected_sha256,
             )
@@ -601,6 +809,7 @@ impl LocalObjectStore {
             content_encoding,
             None,
             None,
+            None,
             &BTreeMap::new(),
         )
         .await
@@ -615,6 +824,7 @@ impl LocalObjectStore {
         content_encoding: Option<&str>,
         storage_class: Option<&str>,
         content_language: Option<&str>,
+        cache_control: Option<&str>,
         user_meta: &BTreeMap<String, String>,
     ) -> Result<String> {
         self.ensure_bucket_and_key(bucket, key).await?;
@@ -622,7 +832,8 @@ impl LocalObjectStore {
         let staging_dir = self.layout.put_staging_dir(bucket, &staging_id)?;
         tokio::fs::create_dir_all(&staging_dir).await?;
         let part_path = staging_dir.join("part.1");
-        let etag = write_file_with_md5(&part_path, bytes).await?;
+        let codec = self.bucket_meta(bucket).await?.compression;
+        let (etag, compression) = write_file_with_md5(&part_path, bytes, codec).await?;
         let meta = PutMeta {
             bucket: bucket.to_string(),
             object_key: key.to_string(),
@@ -633,8 +844,10 @@ impl LocalObjectStore {
             content_type: content_type_or_default(content_type),
             content_encoding: content_encoding_or_none(content_encoding),
             content_language: content_language_or_none(content_language),
+            cache_control: cache_control_or_none(cache_control),
             storage_class: storage_class_or_default(storage_class),
             user_meta: user_meta.clone(),
+            compression,
         };
         write_json_atomic(&staging_dir.join("put.json"), &meta).await?;
         Ok(staging_id)
@@ -661,6 +874,7 @@ impl LocalObjectStore {
             content_encoding,
             None,
             None,
+            None,
             &BTreeMap::new(),
             expected_sha256,
         )
@@ -676,6 +890,7 @@ impl LocalObjectStore {
         content_encoding: Option<&str>,
         storage_class: Option<&str>,
         content_language: Option<&str>,
+        cache_control: Option<&str>,
         user_meta: &BTreeMap<String, String>,
         expected_sha256: Option<&str>,
     ) -> Result<String>
@@ -688,7 +903,8 @@ impl LocalObjectStore {
         let staging_dir = self.layout.put_staging_dir(bucket, &staging_id)?;
         tokio::fs::create_dir_all(&staging_dir).await?;
         let part_path = staging_dir.join("part.1");
-        let written = match write_stream_with_hashes(&part_path, stream).await {
+        let codec = self.bucket_meta(bucket).await?.compression;
+        let written = match write_stream_with_hashes(&part_path, stream, codec).await {
             Ok(written) => written,
             Err(err) => {
                 let _ = tokio::fs::remove_dir_all(&staging_dir).await;
@@ -714,8 +930,10 @@ impl LocalObjectStore {
             content_type: content_type_or_default(content_type),
             content_encoding: content_encoding_or_none(content_encoding),
             content_language: content_language_or_none(content_language),
+            cache_control: cache_control_or_none(cache_control),
             storage_class: storage_class_or_default(storage_class),
             user_meta: user_meta.clone(),
+            compression: written.compression,
         };
         write_json_atomic(&staging_dir.join("put.json"), &meta).await?;
         Ok(staging_id)
@@ -741,6 +959,7 @@ impl LocalObjectStore {
             content_encoding,
             None,
             None,
+            None,
             &BTreeMap::new(),
             None,
         )
@@ -756,6 +975,7 @@ impl LocalObjectStore {
         content_encoding: Option<&str>,
         storage_class: Option<&str>,
         content_language: Option<&str>,
+        cache_control: Option<&str>,
         user_meta: &BTreeMap<String, String>,
         expected_decoded_len: Option<u64>,
     ) -> Result<String>
@@ -768,7 +988,8 @@ impl LocalObjectStore {
         let staging_dir = self.layout.put_staging_dir(bucket, &staging_id)?;
         tokio::fs::create_dir_all(&staging_dir).await?;
         let part_path = staging_dir.join("part.1");
-        let written = match write_aws_chunked_stream_with_hashes(&part_path, stream).await {
+        let codec = self.bucket_meta(bucket).await?.compression;
+        let written = match write_aws_chunked_stream_with_hashes(&part_path, stream, codec).await {
             Ok(written) => written,
             Err(err) => {
                 let _ = tokio::fs::remove_dir_all(&staging_dir).await;
@@ -799,8 +1020,10 @@ impl LocalObjectStore {
             content_type: content_type_or_default(content_type),
             content_encoding: content_encoding_or_none(content_encoding),
             content_language: content_language_or_none(content_language),
+            cache_control: cache_control_or_none(cache_control),
             storage_class: storage_class_or_default(storage_class),
             user_meta: user_meta.clone(),
+            compression: written.compression,
         };
         write_json_atomic(&staging_dir.join("put.json"), &meta).await?;
         Ok(staging_id)
@@ -829,6 +1052,10 @@ impl LocalObjectStore {
             staging_dir.join("object").join("part.1")
         };
         ensure_file_exists(&staged_part).await?;
+        if let Err(err) = self.check_quota(bucket, key, put_meta.size).await {
+            let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+            return Err(err);
+        }
 
         let object_meta = ObjectMeta {
             format_version: 1,
@@ -841,6 +1068,7 @@ impl LocalObjectStore {
             content_type: put_meta.content_type.clone(),
             content_encoding: put_meta.content_encoding.clone(),
             content_language: put_meta.content_language.clone(),
+            cache_control: put_meta.cache_control.clone(),
             storage_class: put_meta.storage_class.clone(),
             user_meta: put_meta.user_meta.clone(),
             parts: vec![PartMeta {
@@ -848,6 +1076,8 @@ impl LocalObjectStore {
                 file: "part.1".to_string(),
                 size: put_meta.size,
                 etag: put_meta.etag.clone(),
+                compression: put_meta.compression.clone(),
+                chunk: None,
             }],
         };
         let publish_dir =
@@ -856,16 +1086,39 @@ impl LocalObjectStore {
         // Remove staging on every path — a rejected precondition must not leak
         // the staged blob.
         let result = self
-            .publish_prepared_dir(bucket, key, &publish_dir, object_meta, precondition)
+            .publish_prepared_dir(bucket, key, &publish_dir, object_meta, precondition, now_ms())
             .await;
         let _ = tokio::fs::remove_dir_all(&staging_dir).await;
         result
     }
 
+    /// Early quota check for a write of `size` bytes to `key`, made before
+    /// any data is moved toward the live tree so a rejected write costs
+    /// nothing. It is advisory — the binding check is the one
+    /// [`ObjectIndex::commit_publish`] makes atomically — but it is also where
+    /// crossing a soft limit gets logged.
+    async fn check_quota(&self, bucket: &str, key: &str, size: u64) -> Result<()> {
+        let Some(quota) = self.bucket_meta(bucket).await?.quota else {
+            return Ok(());
+        };
+        let index = self.index(bucket).await?;
+        let old_size = index.get(key).await?.map(|old| old.size);
+        let before = index.usage().await?;
+        let after = before.after_replace(old_size, size);
+        if let Some(reason) = quota.hard_violation(before, after) {
+            return Err(StorageError::QuotaExceeded(format!("bucket {bucket}: {reason}")));
+        }
+        if let Some(crossing) = quota.soft_crossing(before, after) {
+            log::warn!("bucket {bucket}: {crossing}");
+        }
+        Ok(())
+    }
+
     /// The shared commit section for PUT / CopyObject / CompleteMultipart:
     /// intent → fresh-name rename into the live tree → atomic row flip →
     /// retire the displaced dir. `publish_dir` must already contain the
-    /// complete blob (parts + meta.json).
+    /// complete blob (parts + meta.json). The published version is stamped
+    /// no earlier than `not_before_ms` — the current time for a new write.
     async fn publish_prepared_dir(
         &self,
         bucket: &str,
@@ -873,8 +1126,18 @@ impl LocalObjectStore {
         publish_dir: &Path,
         mut object_meta: ObjectMeta,
         precondition: Option<Precondition>,
+        not_before_ms: i64,
     ) -> Result<PutResult> {
+        if self.stores_inline(&object_meta) {
+            return self
+                .publish_inline(bucket, key, publish_dir, object_meta, precondition, not_before_ms)
+                .await;
+        }
         let bucket_dir = self.layout.bucket_dir(bucket)?;
+        let dedup = self.bucket_meta(bucket).await?.dedup;
+        if self.intern_chunks(bucket, publish_dir, &mut object_meta, dedup).await? {
+            write_json_atomic(&publish_dir.join("meta.json"), &object_meta).await?;
+        }
         if self.durability == Durability::Full {
             fsync_publish_dir(publish_dir, &object_meta).await?;
         }
@@ -894,8 +1157,9 @@ impl LocalObjectStore {
         // currently at the key, atomically under the lock we already hold, so
         // the check and the publish cannot race. On failure, abandon the intent
         // and the staged blob and report 412.
+        let quota = self.bucket_meta(bucket).await?.quota;
         if let Some(precondition) = &precondition {
-            if !precondition.is_met(old.as_ref().map(|o| o.etag.as_str())) {
+            if !precondition.is_met(old.as_ref()) {
                 let _ = index.delete_intent(intent_id).await;
                 let _ = tokio::fs::remove_dir_all(publish_dir).await;
                 return Err(StorageError::PreconditionFailed {
@@ -905,13 +1169,7 @@ impl LocalObjectStore {
             }
         }
 
-        // Monotonic per-key clamp: newer-wins adjudication (rebuild, DR)
-        // requires timestamps to order versions even across clock steps.
-        let last_modified_ms = old
-            .as_ref()
-            .map(|o| o.last_modified_ms.saturating_add(1))
-            .unwrap_or(i64::MIN)
-            .max(now_ms());
+        let last_modified_ms = next_last_modified(old.as_ref(), not_before_ms);
         if last_modified_ms != object_meta.last_modified_ms {
             object_meta.last_modified_ms = last_modified_ms;
             write_json_atomic(&publish_dir.join("meta.json"), &object_meta).await?;
@@ -995,33 +1253,40 @@ impl LocalObjectStore {
             size: object_meta.size,
             etag: object_meta.etag.clone(),
             last_modified_ms,
+            chunks: chunk_refs(&object_meta),
+            inline: None,
         };
-        let retire_id = index
+        let retire_id = match index
             .commit_publish(
                 &record,
                 intent_id,
-                old.as_ref().map(|o| o.blob_dir.as_str()),
+                old.as_ref().and_then(ObjectRecord::on_disk),
                 now_ms(),
+                quota.as_ref(),
             )
-            .await?;
+            .await
+        {
+            Ok(retire_id) => retire_id,
+            Err(StorageError::QuotaExceeded(reason)) => {
+                // A concurrent write used up the room the early check saw.
+                // The row never flipped: hand the blob back to its staging
+                // dir (a multipart upload stays completable) and drop the
+                // intent.
+                if tokio::fs::rename(&dest, publish_dir).await.is_err() {
+                    let _ = tokio::fs::remove_dir_all(&dest).await;
+                }
+                let _ = index.delete_intent(intent_id).await;
+                return Err(StorageError::QuotaExceeded(format!("bucket {bucket}: {reason}")));
+            }
+            Err(err) => return Err(err),
+        };
         self.crash_point("publish_after_commit");
 
         self.meta_cache.remove(&ObjectCacheKey::new(bucket, key));
 
         if let (Some(old), Some(retire_id)) = (&old, retire_id) {
-            let old_abs = bucket_dir.join(&old.blob_dir);
-            match move_object_dir_to_trash(&self.layout, bucket, &old_abs).await {
-                Ok(_) => {
-                    let _ = index.delete_intent(retire_id).await;
-                }
-                Err(err) => {
-                    // The retire intent stays; the resolver finishes the job.
-                    log::warn!(
-                        "retire failed bucket={bucket} key={key} dir={} error={err}",
-                        old.blob_dir
-                    );
-                }
-            }
+            self.retire_displaced(&index, bucket, key, &old.blob_dir, retire_id)
+                .await;
         }
 
         Ok(PutResult {
@@ -1031,6 +1296,152 @@ impl LocalObjectStore {
         })
     }
 
+    /// Whether a prepared object goes into its index row rather than the blob
+    /// heap: a single part no larger than the configured threshold.
+    fn stores_inline(&self, meta: &ObjectMeta) -> bool {
+        self.inline_threshold > 0 && meta.size <= self.inline_threshold && meta.parts.len() == 1
+    }
+
+    /// [`publish_prepared_dir`](Self::publish_prepared_dir) for an object kept
+    /// in its row. The staged part is read back (decompressed) into the row,
+    /// which one commit writes, so no intent guards anything: nothing enters
+    /// the live tree. Overwriting an on-disk object retires its blob dir as
+    /// usual, which is how small objects migrate out of the heap.
+    async fn publish_inline(
+        &self,
+        bucket: &str,
+        key: &str,
+        publish_dir: &Path,
+        mut object_meta: ObjectMeta,
+        precondition: Option<Precondition>,
+        not_before_ms: i64,
+    ) -> Result<PutResult> {
+        let part = object_meta.parts[0].clone();
+        let file = tokio::fs::File::open(publish_dir.join(&part.file)).await?;
+        let mut data = Vec::with_capacity(part.size as usize);
+        open_part_range(file, &part, 0, part.size)
+            .await?
+            .read_to_end(&mut data)
+            .await?;
+        if data.len() as u64 != object_meta.size {
+            return Err(StorageError::CorruptObject(format!(
+                "staged {bucket}/{key} holds {} bytes, meta says {}",
+                data.len(),
+                object_meta.size
+            )));
+        }
+        object_meta.parts[0].compression = None;
+        object_meta.parts[0].chunk = None;
+
+        let index = self.index(bucket).await?;
+        let _guard = self.locks.lock(bucket, key).await;
+        let old = index.get(key).await?;
+        let quota = self.bucket_meta(bucket).await?.quota;
+        if let Some(precondition) = &precondition {
+            if !precondition.is_met(old.as_ref()) {
+                let _ = tokio::fs::remove_dir_all(publish_dir).await;
+                return Err(StorageError::PreconditionFailed {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                });
+            }
+        }
+        object_meta.last_modified_ms = next_last_modified(old.as_ref(), not_before_ms);
+
+        let record = ObjectRecord {
+            object_key: key.to_string(),
+            blob_dir: String::new(),
+            size: object_meta.size,
+            etag: object_meta.etag.clone(),
+            last_modified_ms: object_meta.last_modified_ms,
+            chunks: Vec::new(),
+            inline: Some(Box::new(InlineObject {
+                meta: object_meta.clone(),
+                data,
+            })),
+        };
+        let displaced = old.as_ref().and_then(ObjectRecord::on_disk);
+        let retire_id = match index
+            .commit_inline(&record, displaced, now_ms(), quota.as_ref())
+            .await
+        {
+            Ok(retire_id) => retire_id,
+            // The staged blob stays put, so a multipart upload stays
+            // completable.
+            Err(StorageError::QuotaExceeded(reason)) => {
+                return Err(StorageError::QuotaExceeded(format!("bucket {bucket}: {reason}")));
+            }
+            Err(err) => return Err(err),
+        };
+        self.crash_point("publish_after_commit");
+        self.meta_cache.remove(&ObjectCacheKey::new(bucket, key));
+        let _ = tokio::fs::remove_dir_all(publish_dir).await;
+
+        if let (Some(old_dir), Some(retire_id)) = (displaced, retire_id) {
+            self.retire_displaced(&index, bucket, key, old_dir, retire_id)
+                .await;
+        }
+
+        Ok(PutResult {
+            etag: object_meta.etag,
+            size: object_meta.size,
+            last_modified_ms: object_meta.last_modified_ms,
+        })
+    }
+
+    /// Moves the blob dir an overwrite displaced to trash and clears its
+    /// retire intent; on failure the intent stays for the resolver.
+    async fn retire_displaced(
+        &self,
+        index: &ObjectIndex,
+        bucket: &str,
+        key: &str,
+        blob_dir: &str,
+        retire_id: i64,
+    ) {
+        let abs = match self.layout.bucket_dir(bucket) {
+            Ok(bucket_dir) => bucket_dir.join(blob_dir),
+            Err(_) => return,
+        };
+        match move_object_dir_to_trash(&self.layout, bucket, &abs).await {
+            Ok(_) => {
+                let _ = index.delete_intent(retire_id).await;
+            }
+            Err(err) => {
+                log::warn!("retire failed bucket={bucket} key={key} dir={blob_dir} error={err}");
+            }
+        }
+    }
+
+    /// Points each part of a blob being published at its shared chunk when
+    /// the bucket dedups, or clears chunk ids it carried in from elsewhere
+    /// (a copy or an import) when it does not — a row only ever references
+    /// chunks of its own bucket's store. Returns whether `meta` changed and
+    /// must be rewritten.
+    async fn intern_chunks(
+        &self,
+        bucket: &str,
+        publish_dir: &Path,
+        meta: &mut ObjectMeta,
+        dedup: bool,
+    ) -> Result<bool> {
+        let chunks_dir = self.layout.chunks_dir(bucket)?;
+        let mut changed = false;
+        for part in &mut meta.parts {
+            // An empty part costs nothing to keep apart.
+            let chunk = if dedup && part.stored_size() > 0 {
+                chunks::intern_part(&chunks_dir, &publish_dir.join(&part.file)).await?
+            } else {
+                None
+            };
+            if part.chunk != chunk {
+                part.chunk = chunk;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
     // ── reads (strictly read-only) ────────────────────────────────────────────
 
     /// Resolves `(bucket, key)` through its row — the single source of
@@ -1053,6 +1464,14 @@ impl LocalObjectStore {
                     key: key.to_string(),
                 });
             };
+            if let Some(inline) = row.inline {
+                return Ok(ReadObject {
+                    part_offsets: part_offsets(&inline.meta),
+                    meta: inline.meta,
+                    object_dir: bucket_dir,
+                    inline: Some(Bytes::from(inline.data)),
+                });
+            }
             let object_dir = bucket_dir.join(&row.blob_dir);
 
             if let Some(cached) = self
@@ -1064,6 +1483,7 @@ impl LocalObjectStore {
                     meta: cached.meta,
                     part_offsets: cached.part_offsets,
                     object_dir,
+                    inline: None,
                 });
             }
 
@@ -1081,6 +1501,7 @@ impl LocalObjectStore {
                         meta: cached.meta,
                         part_offsets: cached.part_offsets,
                         object_dir,
+                        inline: None,
                     });
                 }
                 Err(err) => {
@@ -1109,9 +1530,12 @@ impl LocalObjectStore {
             return Ok(None); // Idempotent: deleting an absent key succeeds.
         };
         let deleted_size = row.size;
-        let retire_id = index.commit_delete(key, &row.blob_dir, now_ms()).await?;
+        let retire_id = index.commit_delete(key, row.on_disk(), now_ms()).await?;
         self.crash_point("delete_after_commit");
         self.meta_cache.remove(&ObjectCacheKey::new(bucket, key));
+        let Some(retire_id) = retire_id else {
+            return Ok(Some(deleted_size)); // Inline: the row was the object.
+        };
         let abs = self.layout.bucket_dir(bucket)?.join(&row.blob_dir);
         match move_object_dir_to_trash(&self.layout, bucket, &abs).await {
             Ok(_) => {
@@ -1197,6 +1621,11 @@ impl LocalObjectStore {
             }
             return index.delete_intent(intent.id).await;
         }
+        if intent.blob_dir.is_empty() {
+            // Inline rows have no blob dir and never get an intent; an empty
+            // path would name the bucket dir itself.
+            return index.delete_intent(intent.id).await;
+        }
         let age_ms = now_ms().saturating_sub(intent.created_at_ms);
         let abs = bucket_dir.join(&intent.blob_dir);
         if tokio::fs::metadata(&abs).await.is_ok() {
@@ -1275,6 +1704,16 @@ impl LocalObjectStore {
         self.index(bucket).await?.object_count().await
     }
 
+    pub async fn bucket_usage(&self, bucket: &str) -> Result<BucketUsage> {
+        validate_bucket_name(bucket)?;
+        self.index(bucket).await?.usage().await
+    }
+
+    pub async fn bucket_dedup_usage(&self, bucket: &str) -> Result<DedupUsage> {
+        validate_bucket_name(bucket)?;
+        self.index(bucket).await?.dedup_usage().await
+    }
+
     /// One page of `ListObjectVersions`, resuming strictly after `key_marker`.
     ///
     /// This server is unversioned, so a key has exactly one version — the live
@@ -1313,6 +1752,15 @@ impl LocalObjectStore {
 
         let mut versions: Vec<ObjectVersionEntry> = Vec::new();
         for row in live.entries.iter().take(max_keys) {
+            if let Some(inline) = &row.inline {
+                // No blob dir to name the version after.
+                versions.push(ObjectVersionEntry {
+                    meta: inline.meta.clone(),
+                    version_id: "null".to_string(),
+                    is_latest: true,
+                });
+                continue;
+            }
             let dir = bucket_dir.join(&row.blob_dir);
             let Ok(meta) = read_json::<ObjectMeta>(&dir.join("meta.json")).await else {
                 log::warn!(
@@ -1370,6 +1818,7 @@ impl LocalObjectStore {
             content_encoding,
             None,
             None,
+            None,
             &BTreeMap::new(),
         )
         .await
@@ -1383,6 +1832,7 @@ impl LocalObjectStore {
         content_encoding: Option<&str>,
         storage_class: Option<&str>,
         content_language: Option<&str>,
+        cache_control: Option<&str>,
         user_meta: &BTreeMap<String, String>,
     ) -> Result<String> {
         self.ensure_bucket_and_key(bucket, key).await?;
@@ -1397,6 +1847,7 @@ impl LocalObjectStore {
             content_type: content_type_or_default(content_type),
             content_encoding: content_encoding_or_none(content_encoding),
             content_language: content_language_or_none(content_language),
+            cache_control: cache_control_or_none(cache_control),
             storage_class: storage_class_or_default(storage_class),
             user_meta: user_meta.clone(),
         };
@@ -1417,16 +1868,17 @@ impl LocalObjectStore {
         upload_id: &str,
         part_number: u16,
         temp_path: &Path,
-        size: u64,
-        etag: &str,
+        written: &WrittenHashes,
     ) -> Result<()> {
         let staging_dir = self.layout.multipart_staging_dir(bucket, upload_id)?;
         let file_name = format!("part.{part_number}");
         let part = PartMeta {
             number: part_number,
             file: file_name.clone(),
-            size,
-            etag: etag.to_string(),
+            size: written.size,
+            etag: written.md5.clone(),
+            compression: written.compression.clone(),
+            chunk: None,
         };
         // `\0mpu/` prefix (NUL is illegal in object keys) guarantees this lock
         // never collides with a real object-key lock.
@@ -1475,24 +1927,31 @@ impl LocalObjectStore {
         let staging_dir = self.layout.multipart_staging_dir(bucket, upload_id)?;
         // Stream to a per-attempt temp, then atomically swap into place.
         let temp_path = part_temp_path(&staging_dir, part_number);
-        let etag = match write_file_with_md5(&temp_path, &payload).await {
-            Ok(etag) => etag,
+        let codec = self.bucket_meta(bucket).await?.compression;
+        let (etag, compression) = match write_file_with_md5(&temp_path, payload, codec).await {
+            Ok(written) => written,
             Err(err) => {
                 let _ = tokio::fs::remove_file(&temp_path).await;
                 return Err(err);
             }
         };
-        let size = payload.len() as u64;
+        // A part's ETag is its MD5; nothing downstream reads the SHA-256.
+        let written = WrittenHashes {
+            size: payload.len() as u64,
+            md5: etag,
+            sha256: String::new(),
+            compression,
+        };
         if let Err(err) = self
-            .commit_staged_part(bucket, upload_id, part_number, &temp_path, size, &etag)
+            .commit_staged_part(bucket, upload_id, part_number, &temp_path, &written)
             .await
         {
             let _ = tokio::fs::remove_file(&temp_path).await;
             return Err(err);
         }
         Ok(PutResult {
-            etag,
-            size,
+            etag: written.md5,
+            size: written.size,
             last_modified_ms: now_ms(),
         })
     }
@@ -1523,10 +1982,11 @@ impl LocalObjectStore {
         // is verified complete, so a truncated/racing upload never becomes a
         // committed part.
         let temp_path = part_temp_path(&staging_dir, part_number);
+        let codec = self.bucket_meta(bucket).await?.compression;
         let written = match if aws_chunked {
-            write_aws_chunked_stream_with_hashes(&temp_path, stream).await
+            write_aws_chunked_stream_with_hashes(&temp_path, stream, codec).await
         } else {
-            write_stream_with_hashes(&temp_path, stream).await
+            write_stream_with_hashes(&temp_path, stream, codec).await
         } {
             Ok(written) => written,
             Err(err) => {
@@ -1557,14 +2017,7 @@ impl LocalObjectStore {
             }
         }
         if let Err(err) = self
-            .commit_staged_part(
-                bucket,
-                upload_id,
-                part_number,
-                &temp_path,
-                written.size,
-                &written.md5,
-            )
+            .commit_staged_part(bucket, upload_id, part_number, &temp_path, &written)
             .await
         {
             let _ = tokio::fs::remove_file(&temp_path).await;
@@ -1595,28 +2048,53 @@ impl LocalObjectStore {
         }
         let staging_dir = self.layout.multipart_staging_dir(dst_bucket, upload_id)?;
         let temp_path = part_temp_path(&staging_dir, part_number);
+        let codec = self.bucket_meta(dst_bucket).await?.compression;
         // Hold the source lock only while reading/copying the source bytes into
         // the temp; drop it before the (separately-locked) commit swap.
         let written = {
             let _source_guard = self.locks.lock(src_bucket, src_key).await;
             let src = self.read_object(src_bucket, src_key).await?;
-            match copy_object_data_with_hashes(&src, &temp_path, range).await {
-                Ok(written) => written,
-                Err(err) => {
-                    let _ = tokio::fs::remove_file(&temp_path).await;
-                    return Err(err);
+            let shared = match whole_source_part(&src, range) {
+                Some(part) if self.copy_mode != CopyMode::Copy => {
+                    let linked = async {
+                        tokio::fs::create_dir_all(&staging_dir).await?;
+                        link_part(&src.object_dir.join(&part.file), &temp_path, self.copy_mode)
+                            .await
+                    };
+                    match linked.await {
+                        // A part's ETag is its MD5; nothing downstream of a
+                        // part copy reads the SHA-256.
+                        Ok(()) => Some(WrittenHashes {
+                            size: part.size,
+                            md5: part.etag.clone(),
+                            sha256: String::new(),
+                            compression: part.compression.clone(),
+                        }),
+                        Err(err) => {
+                            log::debug!(
+                                "copy part {src_bucket}/{src_key}: cannot share {} ({err}); copying bytes",
+                                part.file
+                            );
+                            let _ = tokio::fs::remove_file(&temp_path).await;
+                            None
+                        }
+                    }
                 }
+                _ => None,
+            };
+            match shared {
+                Some(written) => written,
+                None => match copy_object_data_with_hashes(&src, &temp_path, range, codec).await {
+                    Ok(written) => written,
+                    Err(err) => {
+                        let _ = tokio::fs::remove_file(&temp_path).await;
+                        return Err(err);
+                    }
+                },
             }
         };
         if let Err(err) = self
-            .commit_staged_part(
-                dst_bucket,
-                upload_id,
-                part_number,
-                &temp_path,
-                written.size,
-                &written.md5,
-            )
+            .commit_staged_part(dst_bucket, upload_id, part_number, &temp_path, &written)
             .await
         {
             let _ = tokio::fs::remove_file(&temp_path).await;
@@ -1674,6 +2152,7 @@ impl LocalObjectStore {
         }
 
         let size = parts.iter().map(|p| p.size).sum();
+        self.check_quota(bucket, key, size).await?;
         let etag = multipart_etag(&parts)?;
         let object_meta = ObjectMeta {
             format_version: 1,
@@ -1686,6 +2165,7 @@ impl LocalObjectStore {
             content_type: upload.content_type.clone(),
             content_encoding: upload.content_encoding.clone(),
             content_language: upload.content_language.clone(),
+            cache_control: upload.cache_control.clone(),
             storage_class: upload.storage_class.clone(),
             user_meta: upload.user_meta.clone(),
             parts: parts.clone(),
@@ -1695,12 +2175,40 @@ impl LocalObjectStore {
         // Multipart completion is unconditional (object_store never sends a
         // precondition with it).
         let result = self
-            .publish_prepared_dir(bucket, key, &publish_dir, object_meta, None)
+            .publish_prepared_dir(bucket, key, &publish_dir, object_meta, None, now_ms())
             .await?;
         let _ = tokio::fs::remove_dir_all(&staging_dir).await;
         Ok(result)
     }
 
+    /// Publishes an object replayed from a backup archive. The put staging
+    /// dir `staging_id` holds the blob under `object/` (parts + meta.json).
+    /// The archived modification time is kept unless the key already holds
+    /// a newer version, which the usual per-key clamp then steps past.
+    pub(crate) async fn import_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        staging_id: &str,
+        object_meta: ObjectMeta,
+    ) -> Result<PutResult> {
+        self.ensure_bucket_and_key(bucket, key).await?;
+        let staging_dir = self.layout.put_staging_dir(bucket, staging_id)?;
+        let not_before_ms = object_meta.last_modified_ms;
+        let result = self
+            .publish_prepared_dir(
+                bucket,
+                key,
+                &staging_dir.join("object"),
+                object_meta,
+                None,
+                not_before_ms,
+            )
+            .await;
+        let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+        result
+    }
+
     pub async fn copy_object(
         &self,
         src_bucket: &str,
@@ -1709,7 +2217,7 @@ impl LocalObjectStore {
         dst_key: &str,
     ) -> Result<PutResult> {
         self.copy_object_with_metadata(
-            src_bucket, src_key, dst_bucket, dst_key, None, None, None, None,
+            src_bucket, src_key, dst_bucket, dst_key, None, None, None, None, None, None,
         )
         .await
     }
@@ -1724,10 +2232,13 @@ impl LocalObjectStore {
         replacement_user_meta: Option<&BTreeMap<String, String>>,
         replacement_content_type: Option<&str>,
         replacement_content_language: Option<&str>,
+        replacement_cache_control: Option<Option<&str>>,
+        precondition: Option<Precondition>,
     ) -> Result<PutResult> {
         self.ensure_bucket_and_key(dst_bucket, dst_key).await?;
         let _source_guard = self.locks.lock(src_bucket, src_key).await;
         let src = self.read_object(src_bucket, src_key).await?;
+        self.check_quota(dst_bucket, dst_key, src.meta.size).await?;
         let content_type = replacement_content_type
             .map(str::to_string)
             .unwrap_or_else(|| src.meta.content_type.clone());
@@ -1735,6 +2246,11 @@ impl LocalObjectStore {
         let content_language = replacement_content_language
             .map(str::to_string)
             .or_else(|| src.meta.content_language.clone());
+        // `Some(None)` replaces the source's Cache-Control with none at all.
+        let cache_control = match replacement_cache_control {
+            Some(value) => cache_control_or_none(value),
+            None => src.meta.cache_control.clone(),
+        };
         let copied_storage_class = storage_class
             .map(str::to_string)
             .unwrap_or_else(|| src.meta.storage_class.clone());
@@ -1744,7 +2260,55 @@ impl LocalObjectStore {
         let staging_id = new_staging_id(now_ms());
         let staging_dir = self.layout.put_staging_dir(dst_bucket, &staging_id)?;
         tokio::fs::create_dir_all(&staging_dir).await?;
-        let written = copy_object_data_with_hashes(&src, &staging_dir.join("part.1"), None).await?;
+        if self.copy_mode != CopyMode::Copy && src.inline.is_none() {
+            // Share the source's part files under a fresh meta.json. The copy
+            // keeps the source's layout, so a multipart source stays multipart
+            // with its `-N` ETag, exactly as MinIO reports it.
+            let publish_dir = staging_dir.join("object");
+            match link_object_parts(&src, &publish_dir, self.copy_mode).await {
+                Ok(()) => {
+                    let object_meta = ObjectMeta {
+                        bucket: dst_bucket.to_string(),
+                        object_key: dst_key.to_string(),
+                        last_modified_ms: now_ms(),
+                        content_type,
+                        content_encoding,
+                        content_language,
+                        cache_control,
+                        storage_class: copied_storage_class,
+                        user_meta,
+                        ..src.meta
+                    };
+                    drop(_source_guard);
+                    let meta_path = publish_dir.join("meta.json");
+                    let result = match write_json_atomic(&meta_path, &object_meta).await {
+                        Ok(()) => {
+                            self.publish_prepared_dir(
+                                dst_bucket,
+                                dst_key,
+                                &publish_dir,
+                                object_meta,
+                                precondition,
+                                now_ms(),
+                            )
+                            .await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+                    return result;
+                }
+                Err(err) => {
+                    log::debug!(
+                        "copy {src_bucket}/{src_key} -> {dst_bucket}/{dst_key}: cannot share part files ({err}); copying bytes"
+                    );
+                    let _ = tokio::fs::remove_dir_all(&publish_dir).await;
+                }
+            }
+        }
+        let codec = self.bucket_meta(dst_bucket).await?.compression;
+        let written =
+            copy_object_data_with_hashes(&src, &staging_dir.join("part.1"), None, codec).await?;
         let meta = PutMeta {
             bucket: dst_bucket.to_string(),
             object_key: dst_key.to_string(),
@@ -1755,12 +2319,14 @@ impl LocalObjectStore {
             content_type,
             content_encoding,
             content_language,
+            cache_control,
             storage_class: copied_storage_class,
             user_meta,
+            compression: written.compression,
         };
         write_json_atomic(&staging_dir.join("put.json"), &meta).await?;
         drop(_source_guard);
-        self.commit_staged_put(dst_bucket, dst_key, &staging_id, None)
+        self.commit_staged_put(dst_bucket, dst_key, &staging_id, precondition)
             .await
     }
 
@@ -1875,6 +2441,36 @@ impl LocalObjectStore {
         self.rebuilding.lock().unwrap().remove(bucket);
     }
 
+    /// Claims `bucket` for a move to another data root. `false` while it is
+    /// already moving or being rebuilt.
+    pub(crate) fn try_begin_move(&self, bucket: &str) -> bool {
+        if self.rebuilding.lock().unwrap().contains(bucket) {
+            return false;
+        }
+        self.moving.lock().unwrap().insert(bucket.to_string())
+    }
+
+    /// Releases a move's claim, and its 503 gate if the switch-over left it up.
+    pub(crate) fn end_move(&self, bucket: &str) {
+        self.switching.lock().unwrap().remove(bucket);
+        self.moving.lock().unwrap().remove(bucket);
+    }
+
+    /// Raises the 503 gate for a move's switch-over; [`end_move`](Self::end_move)
+    /// lowers it.
+    pub(crate) fn begin_switch(&self, bucket: &str) {
+        self.switching.lock().unwrap().insert(bucket.to_string());
+    }
+
+    /// Drops and flushes the cached index handle for `bucket`, so the next
+    /// access opens the database afresh from wherever the bucket now lives.
+    pub(crate) async fn evict_index(&self, bucket: &str) {
+        let removed = self.index_cache.lock().unwrap().remove(bucket);
+        if let Some(index) = removed {
+            index.close().await;
+        }
+    }
+
     /// One empty-directory reclamation pass: `remove_dir` each fanout dir at the
     /// `objects/` root. The single-level layout means every fanout dir is a
     /// direct child of `objects/`, and object (leaf) dirs are always valid — so
@@ -2128,6 +2724,7 @@ impl LocalObjectStore {
         let _ = tokio::fs::remove_dir_all(&tmp_path).await;
         let tmp = ObjectIndex::open_at(&tmp_path, Durability::Relaxed).await?;
         tmp.create_schema().await?;
+        let carried = self.carry_inline_rows(bucket, &bucket_dir, &tmp).await?;
 
         // One pool of workers traverses and parses concurrently over a
         // work-stealing frontier. A serial walker starves on hash-fanout
@@ -2190,6 +2787,8 @@ impl LocalObjectStore {
                                         size: meta.size,
                                         etag: meta.etag.clone(),
                                         last_modified_ms: meta.last_modified_ms,
+                                        chunks: chunk_refs(&meta),
+                                        inline: None,
                                     },
                                     dir,
                                 ))
@@ -2225,7 +2824,7 @@ impl LocalObjectStore {
         // over the tree, so it doubles as the audit.
         let mut batch: Vec<ObjectRecord> = Vec::with_capacity(self.rebuild_batch_size);
         let mut dirs_by_rel: HashMap<String, PathBuf> = HashMap::new();
-        let mut indexed = 0usize;
+        let mut indexed = carried;
         let mut trashed = 0usize;
         loop {
             let message = entry_rx.recv().await;
@@ -2306,6 +2905,60 @@ impl LocalObjectStore {
         Ok(indexed)
     }
 
+    /// Inline objects have no blob dir for the walk to find: their only copy
+    /// is the outgoing index, so its inline rows are carried into the new one
+    /// first (the walk then adjudicates them newer-wins like any other row).
+    /// An outgoing index that cannot be read loses them, with a warning.
+    async fn carry_inline_rows(
+        &self,
+        bucket: &str,
+        bucket_dir: &Path,
+        tmp: &ObjectIndex,
+    ) -> Result<usize> {
+        let live_path = index::index_db_path(bucket_dir);
+        if !live_path.exists() {
+            return Ok(0);
+        }
+        let live = match ObjectIndex::open_at(&live_path, Durability::Relaxed).await {
+            Ok(live) => live,
+            Err(err) => {
+                log::warn!("rebuild cannot read inline objects bucket={bucket} error={err}");
+                return Ok(0);
+            }
+        };
+        let mut carried = 0;
+        let mut after: Option<String> = None;
+        let walked = loop {
+            let page = match live
+                .all_entries_after(after.as_deref(), self.rebuild_batch_size as i64)
+                .await
+            {
+                Ok(page) => page,
+                Err(err) => break Err(err),
+            };
+            let Some(last) = page.last() else {
+                break Ok(());
+            };
+            after = Some(last.object_key.clone());
+            let inline: Vec<ObjectRecord> =
+                page.into_iter().filter(|r| r.inline.is_some()).collect();
+            if !inline.is_empty() {
+                match tmp.insert_rebuild_batch(&inline).await {
+                    Ok(outcome) => carried += outcome.inserted,
+                    Err(err) => break Err(err),
+                }
+            }
+        };
+        live.close().await;
+        if let Err(err) = walked {
+            log::warn!(
+                "rebuild stopped carrying inline objects bucket={bucket} carried={carried} error={err}"
+            );
+        }
+        log::info!("rebuild carried inline objects bucket={bucket} count={carried}");
+        Ok(carried)
+    }
+
     async fn ensure_bucket_and_key(&self, bucket: &str, key: &str) -> Result<()> {
         validate_bucket_name(bucket)?;
         validate_object_key(key)?;
@@ -2444,6 +3097,27 @@ async fn fsync_dir(path: &Path) -> Result<()> {
 
 /// Makes a prepared publish dir durable before anything references it:
 /// every part file, meta.json, and the directory itself.
+/// The chunk references of a published blob, one per shared part.
+/// Monotonic per-key clamp: newer-wins adjudication (rebuild, DR) requires
+/// timestamps to order versions even across clock steps.
+fn next_last_modified(old: Option<&ObjectRecord>, not_before_ms: i64) -> i64 {
+    old.map(|o| o.last_modified_ms.saturating_add(1))
+        .unwrap_or(i64::MIN)
+        .max(not_before_ms)
+}
+
+fn chunk_refs(meta: &ObjectMeta) -> Vec<ChunkRef> {
+    meta.parts
+        .iter()
+        .filter_map(|part| {
+            part.chunk.as_ref().map(|id| ChunkRef {
+                id: id.clone(),
+                size: part.stored_size(),
+            })
+        })
+        .collect()
+}
+
 async fn fsync_publish_dir(publish_dir: &Path, meta: &ObjectMeta) -> Result<()> {
     for part in &meta.parts {
         fsync_file(&publish_dir.join(&part.file)).await?;
@@ -2533,19 +3207,23 @@ fn part_temp_path(staging_dir: &Path, part_number: u16) -> PathBuf {
     staging_dir.join(format!("part.{part_number}.tmp.{}", new_staging_id(now_ms())))
 }
 
-async fn write_file_with_md5(path: &Path, bytes: &[u8]) -> Result<String> {
+async fn write_file_with_md5(
+    path: &Path,
+    bytes: &[u8],
+    codec: Option<CompressionCodec>,
+) -> Result<(String, Option<PartCompression>)> {
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
-    let mut file = tokio::fs::File::create(path).await?;
+    let mut file = PartWriter::create(path, codec).await?;
     let mut hasher = Md5::new();
     const CHUNK: usize = 256 * 1024;
     for chunk in bytes.chunks(CHUNK) {
         hasher.update(chunk);
-        file.write_all(chunk).await?;
+        file.write(chunk).await?;
     }
-    file.flush().await?;
-    Ok(format!("{:x}", hasher.finalize()))
+    let compression = file.finish().await?;
+    Ok((format!("{:x}", hasher.finalize()), compression))
 }
 
 /// Copies an immutable object (or inclusive byte range) into one staging file.
@@ -2555,6 +3233,7 @@ async fn copy_object_data_with_hashes(
     source: &ReadObject,
     path: &Path,
     range: Option<(u64, u64)>,
+    codec: Option<CompressionCodec>,
 ) -> Result<WrittenHashes> {
     let (range_start, range_end) = match range {
         Some((start, end)) if start <= end && end < source.meta.size => (start, end),
@@ -2569,7 +3248,7 @@ async fn copy_object_data_with_hashes(
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
-    let mut output = tokio::fs::File::create(path).await?;
+    let mut output = PartWriter::create(path, codec).await?;
     let mut md5 = Md5::new();
     let mut sha256 = Sha256::new();
     let mut size = 0u64;
@@ -2583,12 +3262,8 @@ async fn copy_object_data_with_hashes(
             if copy_start >= copy_end {
                 continue;
             }
-            let mut input = tokio::fs::File::open(source.object_dir.join(&part.file)).await?;
-            let skip = copy_start - part_start;
-            if skip != 0 {
-                input.seek(SeekFrom::Start(skip)).await?;
-            }
             let mut remaining = copy_end - copy_start;
+            let mut input = source.open_part(part, copy_start - part_start, remaining).await?;
             while remaining != 0 {
                 let wanted = remaining.min(buffer.len() as u64) as usize;
                 let read = input.read(&mut buffer[..wanted]).await?;
@@ -2601,28 +3276,104 @@ async fn copy_object_data_with_hashes(
                 let chunk = &buffer[..read];
                 md5.update(chunk);
                 sha256.update(chunk);
-                output.write_all(chunk).await?;
+                output.write(chunk).await?;
                 size += read as u64;
                 remaining -= read as u64;
             }
         }
     }
-    output.flush().await?;
     Ok(WrittenHashes {
         size,
         md5: format!("{:x}", md5.finalize()),
         sha256: format!("{:x}", sha256.finalize()),
+        compression: output.finish().await?,
     })
 }
 
+/// The source part a part-copy reads in full, if its range is exactly one
+/// part — then the part file can be shared and its ETag (the MD5 of exactly
+/// those bytes) reused.
+fn whole_source_part(source: &ReadObject, range: Option<(u64, u64)>) -> Option<&PartMeta> {
+    if source.inline.is_some() {
+        return None; // No part file to share.
+    }
+    let (start, end) = match range {
+        Some(range) => range,
+        None if source.meta.parts.len() == 1 => return source.meta.parts.first(),
+        None => return None,
+    };
+    source
+        .meta
+        .parts
+        .iter()
+        .zip(&source.part_offsets)
+        .find(|(part, &offset)| offset == start && part.size > 0 && offset + part.size - 1 == end)
+        .map(|(part, _)| part)
+}
+
+/// Gives `dest` every part file of `source` without copying bytes.
+async fn link_object_parts(
+    source: &ReadObject,
+    dest: &Path,
+    mode: CopyMode,
+) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dest).await?;
+    for part in &source.meta.parts {
+        let file = &part.file;
+        link_part(&source.object_dir.join(file), &dest.join(file), mode).await?;
+    }
+    Ok(())
+}
+
+/// A hardlink (for [`CopyMode::Link`]) or else a reflink of one immutable
+/// blob file. Either fails across filesystems, which the caller answers with
+/// a byte copy.
+async fn link_part(source: &Path, dest: &Path, mode: CopyMode) -> std::io::Result<()> {
+    if mode == CopyMode::Link && tokio::fs::hard_link(source, dest).await.is_ok() {
+        return Ok(());
+    }
+    let (source, dest) = (source.to_path_buf(), dest.to_path_buf());
+    tokio::task::spawn_blocking(move || reflink(&source, &dest))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, dest: &Path) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let input = std::fs::File::open(source)?;
+    let output = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dest)?;
+    // SAFETY: both descriptors stay open for the duration of the call.
+    if unsafe { libc::ioctl(output.as_raw_fd(), libc::FICLONE, input.as_raw_fd()) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    drop(output);
+    let _ = std::fs::remove_file(dest);
+    Err(err)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_source: &Path, _dest: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct WrittenHashes {
     size: u64,
     md5: String,
     sha256: String,
+    compression: Option<PartCompression>,
 }
 
-async fn write_stream_with_hashes<S, E>(path: &Path, mut stream: S) -> Result<WrittenHashes>
+async fn write_stream_with_hashes<S, E>(
+    path: &Path,
+    mut stream: S,
+    codec: Option<CompressionCodec>,
+) -> Result<WrittenHashes>
 where
     S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
     E: std::fmt::Display,
@@ -2630,7 +3381,7 @@ where
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
-    let mut file = tokio::fs::File::create(path).await?;
+    let mut file = PartWriter::create(path, codec).await?;
     let mut md5 = Md5::new();
     let mut sha256 = Sha256::new();
     let mut size = 0u64;
@@ -2639,19 +3390,20 @@ where
         size += chunk.len() as u64;
         md5.update(&chunk);
         sha256.update(&chunk);
-        file.write_all(&chunk).await?;
+        file.write(&chunk).await?;
     }
-    file.flush().await?;
     Ok(WrittenHashes {
         size,
         md5: format!("{:x}", md5.finalize()),
         sha256: format!("{:x}", sha256.finalize()),
+        compression: file.finish().await?,
     })
 }
 
 async fn write_aws_chunked_stream_with_hashes<S, E>(
     path: &Path,
     mut stream: S,
+    codec: Option<CompressionCodec>,
 ) -> Result<WrittenHashes>
 where
     S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
@@ -2660,7 +3412,7 @@ where
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
-    let mut file = tokio::fs::File::create(path).await?;
+    let mut file = PartWriter::create(path, codec).await?;
     let mut md5 = Md5::new();
     let mut sha256 = Sha256::new();
     let mut size = 0u64;
@@ -2712,7 +3464,7 @@ where
             size += data.len() as u64;
             md5.update(data);
             sha256.update(data);
-            file.write_all(data).await?;
+            file.write(data).await?;
             buffer.advance(framed_end);
         }
 
@@ -2727,11 +3479,11 @@ where
         ));
     }
 
-    file.flush().await?;
     Ok(WrittenHashes {
         size,
         md5: format!("{:x}", md5.finalize()),
         sha256: format!("{:x}", sha256.finalize()),
+        compression: file.finish().await?,
     })
 }
 
@@ -2764,13 +3516,15 @@ async fn validate_object_parts(object_dir: &Path, meta: &ObjectMeta) -> Result<(
             )));
         }
         let len = file_meta.len();
-        if len != part.size {
+        if len != part.stored_size() {
             return Err(StorageError::CorruptObject(format!(
                 "part {} size mismatch: meta={} actual={}",
-                part.file, part.size, len
+                part.file,
+                part.stored_size(),
+                len
             )));
         }
-        total += len;
+        total += part.size;
     }
     if total != meta.size {
         return Err(StorageError::CorruptObject(format!(
@@ -2932,6 +3686,8 @@ mod tests {
                                     size: meta.size,
                                     etag: meta.etag.clone(),
                                     last_modified_ms: meta.last_modified_ms,
+                                    chunks: chunk_refs(&meta),
+                                    inline: None,
                                 })
                                 .await;
                         }
@@ -2989,6 +3745,75 @@ mod tests {
         eprintln!("sqlite(write): {} ms (serial)", ms(sqlite_nanos));
     }
 
+    /// Small-object PUT throughput under `durability: full`, reporting how many
+    /// index commits shared each synced group. Run it on the disk you care
+    /// about — the point is the fsync count, which a tmpfs hides:
+    ///
+    ///   BENCH_DIR=/opt/rusts3/bench \
+    ///   cargo test -p rust-s3-server --release bench_full_durability_puts -- --ignored --nocapture
+    ///
+    /// Optional: BENCH_PUTS=N (default 2000), BENCH_CONCURRENCY=N (default 64),
+    /// BENCH_WINDOW_US=N (default 200; compare against 0).
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn bench_full_durability_puts() {
+        use std::time::Instant;
+
+        let env = |name: &str, default: usize| {
+            std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        let puts = env("BENCH_PUTS", 2000);
+        let concurrency = env("BENCH_CONCURRENCY", 64).max(1);
+        let window_us = env("BENCH_WINDOW_US", 200) as u64;
+
+        let base = std::env::var("BENCH_DIR").map(std::path::PathBuf::from).ok();
+        let tmp = match &base {
+            Some(dir) => tempfile::tempdir_in(dir).unwrap(),
+            None => tempfile::tempdir().unwrap(),
+        };
+        let config = StorageConfig {
+            durability: DurabilityMode::Full,
+            group_commit_window_us: window_us,
+            ..StorageConfig::default()
+        };
+        let store = LocalObjectStore::from_storage_config(tmp.path(), &config);
+        store.create_bucket("bench").await.unwrap();
+
+        let next = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let t0 = Instant::now();
+        let workers: Vec<_> = (0..concurrency)
+            .map(|_| {
+                let (store, next) = (store.clone(), next.clone());
+                tokio::spawn(async move {
+                    loop {
+                        let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        if i >= puts {
+                            break;
+                        }
+                        let key = format!("obj/{i:08}");
+                        store.put_object("bench", &key, &[b'x'; 1024], None, None, false).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.await.unwrap();
+        }
+        let wall = t0.elapsed();
+        let stats = store.index("bench").await.unwrap().group_commit_stats();
+
+        eprintln!("──────── full-durability PUT benchmark ────────");
+        eprintln!("puts={puts} concurrency={concurrency} window={window_us}µs size=1KiB");
+        eprintln!("WALL         : {wall:?}");
+        eprintln!("throughput   : {:.0} PUT/s", puts as f64 / wall.as_secs_f64());
+        eprintln!(
+            "index syncs  : {} groups for {} commits ({:.1} commits/sync)",
+            stats.groups,
+            stats.commits,
+            stats.commits as f64 / stats.groups.max(1) as f64
+        );
+    }
+
     async fn store_and_bucket() -> (tempfile::TempDir, LocalObjectStore) {
         let tmp = tempfile::tempdir().unwrap();
         let store = LocalObjectStore::new(tmp.path());
@@ -3169,8 +3994,16 @@ mod tests {
         let index = store.index(bucket).await.unwrap();
         let bucket_dir = store.layout().bucket_dir(bucket).unwrap();
         let rows = index.all_entries_after(None, i64::MAX).await.unwrap();
+        let usage = index.usage().await.unwrap();
+        assert_eq!(usage.objects, rows.len() as u64, "object counter drifted");
+        assert_eq!(usage.bytes, rows.iter().map(|r| r.size).sum::<u64>(), "byte counter drifted");
         for row in rows {
-            let dir = bucket_dir.join(&row.blob_dir);
+            let Some(blob_dir) = row.on_disk() else {
+                let inline = row.inline.as_ref().unwrap();
+                assert_eq!(inline.data.len() as u64, row.size, "inline body size mismatch");
+                continue;
+            };
+            let dir = bucket_dir.join(blob_dir);
             let meta: ObjectMeta = read_json(&dir.join("meta.json"))
                 .await
                 .unwrap_or_else(|e| panic!("row {} references broken blob: {e}", row.object_key));
@@ -3190,7 +4023,8 @@ mod tests {
         let read = store.read_object(bucket, key).await.unwrap();
         let mut body = Vec::new();
         for part in &read.meta.parts {
-            body.extend(tokio::fs::read(read.object_dir.join(&part.file)).await.unwrap());
+            let mut reader = read.open_part(part, 0, part.size).await.unwrap();
+            reader.read_to_end(&mut body).await.unwrap();
         }
         body
     }
@@ -3364,7 +4198,7 @@ mod tests {
         let row = index.get("k").await.unwrap().unwrap();
         // Simulate a bogus retire intent pointing at the live dir.
         index
-            .commit_delete("k", &row.blob_dir, now_ms())
+            .commit_delete("k", Some(&row.blob_dir), now_ms())
             .await
             .unwrap();
         // Re-insert the row (as if a concurrent PUT recommitted it).
@@ -3372,7 +4206,7 @@ mod tests {
             .insert_publish_intent("k", &row.blob_dir, now_ms())
             .await
             .unwrap();
-        index.commit_publish(&row, intent, None, now_ms()).await.unwrap();
+        index.commit_publish(&row, intent, None, now_ms(), None).await.unwrap();
         store.drain_intents("bucket").await.unwrap();
         // The live blob survived the stale retire intent.
         assert_eq!(read_body(&store, "bucket", "k").await, b"data");
@@ -3602,6 +4436,64 @@ mod tests {
         assert_invariants(&store, "bucket").await;
     }
 
+    #[tokio::test]
+    async fn hard_quota_rejects_writes_before_they_commit() {
+        let (_tmp, store) = store_and_bucket().await;
+        store
+            .put_object("bucket", "small", b"0123456789", None, None, false)
+            .await
+            .unwrap();
+        let quota = BucketQuota {
+            hard_bytes: Some(MIN_MULTIPART_PART_SIZE),
+            hard_objects: Some(2),
+            ..Default::default()
+        };
+        store.set_bucket_quota("bucket", Some(quota)).await.unwrap();
+        assert!(store.bucket_meta("bucket").await.unwrap().quota.is_some());
+
+        // Growing past the byte limit is refused and leaves no staging behind.
+        let big = vec![7u8; MIN_MULTIPART_PART_SIZE as usize];
+        let err = store
+            .put_object("bucket", "big", &big, None, None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QuotaExceeded(_)), "{err}");
+        let bucket_dir = store.layout().bucket_dir("bucket").unwrap();
+        assert!(!has_active_staging(&bucket_dir).await.unwrap());
+
+        // A copy to a new key fits; a third key breaks the object limit.
+        store.copy_object("bucket", "small", "bucket", "copy").await.unwrap();
+        let err = store
+            .copy_object("bucket", "small", "bucket", "third")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QuotaExceeded(_)), "{err}");
+
+        // A refused multipart complete keeps the upload, so it can be retried
+        // once there is room.
+        let upload_id = store.initiate_multipart("bucket", "copy", None, None).await.unwrap();
+        let part = store
+            .put_multipart_part("bucket", "copy", &upload_id, 1, &big, false)
+            .await
+            .unwrap();
+        let parts = [CompletePartRequest { number: 1, etag: part.etag }];
+        let err = store
+            .complete_multipart("bucket", "copy", &upload_id, &parts)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::QuotaExceeded(_)), "{err}");
+        store.delete_object("bucket", "small").await.unwrap();
+        store.complete_multipart("bucket", "copy", &upload_id, &parts).await.unwrap();
+        assert_eq!(
+            store.bucket_usage("bucket").await.unwrap(),
+            BucketUsage { bytes: MIN_MULTIPART_PART_SIZE, objects: 1 }
+        );
+
+        store.set_bucket_quota("bucket", Some(BucketQuota::default())).await.unwrap();
+        assert_eq!(store.bucket_meta("bucket").await.unwrap().quota, None);
+        assert_invariants(&store, "bucket").await;
+    }
+
     #[tokio::test]
     async fn concurrent_same_part_upload_stays_consistent() {
         // Two concurrent uploads of the SAME part number must resolve
@@ -3681,6 +4573,323 @@ mod tests {
         assert_invariants(&store, "bucket").await;
     }
 
+    #[tokio::test]
+    async fn dedup_buckets_share_identical_parts_until_the_last_reference() {
+        use crate::storage::sweeper::{delete_chunks_bucket, delete_trash_bucket, SweepConfig};
+        use std::os::unix::fs::MetadataExt;
+
+        let (_tmp, store) = store_and_bucket().await;
+        store.create_bucket("plain").await.unwrap();
+        store.set_bucket_dedup("bucket", true).await.unwrap();
+        let body = b"the same build artifact\n".repeat(1000);
+        for key in ["a", "b", "c"] {
+            store.put_object("bucket", key, &body, None, None, false).await.unwrap();
+        }
+        store
+            .put_object("bucket", "other", b"something else", None, None, false)
+            .await
+            .unwrap();
+
+        let part_file = |read: &ReadObject| read.object_dir.join(&read.meta.parts[0].file);
+        let a = store.read_object("bucket", "a").await.unwrap();
+        let b = store.read_object("bucket", "b").await.unwrap();
+        let id = a.meta.parts[0].chunk.clone().expect("part is shared");
+        assert_eq!(b.meta.parts[0].chunk.as_ref(), Some(&id));
+        let ino = |path: PathBuf| std::fs::metadata(path).unwrap().ino();
+        assert_eq!(ino(part_file(&a)), ino(part_file(&b)));
+        assert_eq!(read_body(&store, "bucket", "b").await, body);
+        let usage = store.bucket_dedup_usage("bucket").await.unwrap();
+        assert_eq!((usage.chunks, usage.saved_bytes()), (2, 2 * body.len() as u64));
+
+        // A rebuilt index recounts the references from meta.json.
+        store.rebuild_index("bucket").await.unwrap();
+        let index = store.index("bucket").await.unwrap();
+        assert_eq!(index.chunk_refs(&id).await.unwrap(), 3);
+
+        // A copy into a bucket without dedup owns its part outright.
+        store.copy_object("bucket", "c", "plain", "c").await.unwrap();
+        let copy = store.read_object("plain", "c").await.unwrap();
+        assert_eq!(copy.meta.parts[0].chunk, None);
+        assert_eq!(store.bucket_dedup_usage("plain").await.unwrap().chunks, 0);
+
+        // Overwrites and deletes drop references; the chunk outlives them
+        // while any remains.
+        store.put_object("bucket", "a", b"new", None, None, false).await.unwrap();
+        store.delete_object("bucket", "b").await.unwrap();
+        assert_eq!(index.chunk_refs(&id).await.unwrap(), 1);
+        let config = SweepConfig {
+            intent_grace_period_ms: 0,
+            trash_expiry_ms: 0,
+            ..SweepConfig::default()
+        };
+        let chunk = chunks::chunk_path(&store.layout.chunks_dir("bucket").unwrap(), &id);
+        let later = now_ms() + 1000;
+        delete_trash_bucket(&store, "bucket", &config, later).await.unwrap();
+        assert_eq!(delete_chunks_bucket(&store, "bucket", &config, later).await.unwrap(), 0);
+        assert!(chunk.exists());
+
+        // The last reference retired: once neither the trash nor the copy
+        // (a hardlink of the same file) links the chunk, the collector
+        // removes it.
+        store.delete_object("bucket", "c").await.unwrap();
+        assert_eq!(delete_chunks_bucket(&store, "bucket", &config, later).await.unwrap(), 0);
+        delete_trash_bucket(&store, "bucket", &config, later).await.unwrap();
+        store.delete_object("plain", "c").await.unwrap();
+        delete_trash_bucket(&store, "plain", &config, later).await.unwrap();
+        assert_eq!(delete_chunks_bucket(&store, "bucket", &config, later).await.unwrap(), 1);
+        assert!(!chunk.exists());
+        assert_eq!(index.chunk_refs(&id).await.unwrap(), 0);
+        let usage = store.bucket_dedup_usage("bucket").await.unwrap();
+        assert_eq!((usage.chunks, usage.saved_bytes()), (2, 0));
+        assert_eq!(read_body(&store, "bucket", "other").await, b"something else");
+    }
+
+    #[tokio::test]
+    async fn compressed_buckets_keep_sizes_and_etags_logical() {
+        let (_tmp, store) = store_and_bucket().await;
+        store.create_bucket("plain").await.unwrap();
+        store
+            .set_bucket_compression("bucket", Some(CompressionCodec::Zstd))
+            .await
+            .unwrap();
+        let text: Vec<u8> = b"compressible line of text\n"
+            .iter()
+            .cycle()
+            .take(MIN_MULTIPART_PART_SIZE as usize + 1000)
+            .copied()
+            .collect();
+        let put = store
+            .put_object("bucket", "text", &text, None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(put.etag, format!("{:x}", Md5::digest(&text)));
+        let read = store.read_object("bucket", "text").await.unwrap();
+        let part = &read.meta.parts[0];
+        assert_eq!((read.meta.size, part.size), (text.len() as u64, text.len() as u64));
+        let stored = part.compression.as_ref().expect("compressed").stored_size;
+        assert!(stored < text.len() as u64 / 10, "{stored}");
+        assert_eq!(read_body(&store, "bucket", "text").await, text);
+
+        // Noise is not worth compressing and lands raw.
+        let mut noise = vec![0u8; 300_000];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut noise);
+        store
+            .put_object("bucket", "noise", &noise, None, None, false)
+            .await
+            .unwrap();
+        let read = store.read_object("bucket", "noise").await.unwrap();
+        assert_eq!(read.meta.parts[0].compression, None);
+
+        // Multipart parts compress individually; the `-N` ETag is unchanged.
+        let upload_id = store
+            .initiate_multipart("bucket", "big", None, None)
+            .await
+            .unwrap();
+        let e1 = store
+            .put_multipart_part("bucket", "big", &upload_id, 1, &text, false)
+            .await
+            .unwrap();
+        let e2 = store
+            .put_multipart_part("bucket", "big", &upload_id, 2, b"tail", false)
+            .await
+            .unwrap();
+        let parts = [
+            CompletePartRequest { number: 1, etag: e1.etag.clone() },
+            CompletePartRequest { number: 2, etag: e2.etag },
+        ];
+        let done = store
+            .complete_multipart("bucket", "big", &upload_id, &parts)
+            .await
+            .unwrap();
+        assert!(done.etag.ends_with("-2"));
+        assert_eq!(e1.etag, put.etag);
+        let mut whole = text.clone();
+        whole.extend_from_slice(b"tail");
+        assert_eq!(read_body(&store, "bucket", "big").await, whole);
+
+        // A ranged part copy into an uncompressed bucket decodes the source.
+        let upload_id = store
+            .initiate_multipart("plain", "slice", None, None)
+            .await
+            .unwrap();
+        let slice = store
+            .copy_multipart_part(
+                "plain",
+                "slice",
+                &upload_id,
+                1,
+                "bucket",
+                "big",
+                Some((10, 300_009)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(slice.etag, format!("{:x}", Md5::digest(&whole[10..300_010])));
+        let staging = store.layout.multipart_staging_dir("plain", &upload_id).unwrap();
+        assert_eq!(std::fs::read(staging.join("part.1")).unwrap(), &whole[10..300_010]);
+        store.copy_object("bucket", "big", "plain", "copy").await.unwrap();
+        assert_eq!(read_body(&store, "plain", "copy").await, whole);
+
+        assert_invariants(&store, "bucket").await;
+        assert_invariants(&store, "plain").await;
+    }
+
+    #[tokio::test]
+    async fn copy_object_shares_part_files_and_keeps_the_source_layout() {
+        use std::os::unix::fs::MetadataExt;
+        let inode = |read: &ReadObject, part: &PartMeta| {
+            std::fs::metadata(read.object_dir.join(&part.file)).unwrap().ino()
+        };
+        let (_tmp, store) = store_and_bucket().await;
+        let upload_id = store
+            .initiate_multipart("bucket", "src", None, None)
+            .await
+            .unwrap();
+        let part1 = vec![7u8; MIN_MULTIPART_PART_SIZE as usize];
+        let e1 = store
+            .put_multipart_part("bucket", "src", &upload_id, 1, &part1, false)
+            .await
+            .unwrap();
+        let e2 = store
+            .put_multipart_part("bucket", "src", &upload_id, 2, b"tail", false)
+            .await
+            .unwrap();
+        store
+            .complete_multipart(
+                "bucket",
+                "src",
+                &upload_id,
+                &[
+                    CompletePartRequest { number: 1, etag: e1.etag },
+                    CompletePartRequest { number: 2, etag: e2.etag.clone() },
+                ],
+            )
+            .await
+            .unwrap();
+
+        let copied = store.copy_object("bucket", "src", "bucket", "dst").await.unwrap();
+        let src = store.read_object("bucket", "src").await.unwrap();
+        let dst = store.read_object("bucket", "dst").await.unwrap();
+        assert_eq!(copied.etag, src.meta.etag);
+        assert!(copied.etag.ends_with("-2"));
+        assert_eq!(dst.meta.object_key, "dst");
+        assert_ne!(src.object_dir, dst.object_dir);
+        for (a, b) in src.meta.parts.iter().zip(&dst.meta.parts) {
+            assert_eq!(inode(&src, a), inode(&dst, b));
+        }
+
+        // A part copy of exactly one source part shares it too.
+        let upload_id = store
+            .initiate_multipart("bucket", "parts", None, None)
+            .await
+            .unwrap();
+        let tail_start = MIN_MULTIPART_PART_SIZE;
+        let part = store
+            .copy_multipart_part(
+                "bucket",
+                "parts",
+                &upload_id,
+                1,
+                "bucket",
+                "src",
+                Some((tail_start, tail_start + 3)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(part.etag, e2.etag);
+        let staging = store.layout.multipart_staging_dir("bucket", &upload_id).unwrap();
+        let staged = std::fs::metadata(staging.join("part.1")).unwrap().ino();
+        assert_eq!(staged, inode(&src, &src.meta.parts[1]));
+
+        // Deleting the source leaves the copy intact.
+        store.delete_object("bucket", "src").await.unwrap();
+        assert_eq!(read_body(&store, "bucket", "dst").await.len(), part1.len() + 4);
+        assert_invariants(&store, "bucket").await;
+    }
+
+    #[tokio::test]
+    async fn copy_mode_copy_streams_bytes() {
+        use std::os::unix::fs::MetadataExt;
+        let tmp = tempfile::tempdir().unwrap();
+        let config = StorageConfig {
+            copy_mode: CopyMode::Copy,
+            ..StorageConfig::default()
+        };
+        let store = LocalObjectStore::from_storage_config(tmp.path(), &config);
+        store.create_bucket("bucket").await.unwrap();
+        store
+            .put_object("bucket", "src", b"payload", None, None, false)
+            .await
+            .unwrap();
+        store.copy_object("bucket", "src", "bucket", "dst").await.unwrap();
+        let src = store.read_object("bucket", "src").await.unwrap();
+        let dst = store.read_object("bucket", "dst").await.unwrap();
+        let inode = |read: &ReadObject| {
+            std::fs::metadata(read.object_dir.join("part.1")).unwrap().ino()
+        };
+        assert_ne!(inode(&src), inode(&dst));
+        assert_eq!(read_body(&store, "bucket", "dst").await, b"payload");
+    }
+
+    #[tokio::test]
+    async fn small_objects_live_in_their_row_and_migrate_on_overwrite() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = StorageConfig {
+            inline_threshold: 64,
+            ..StorageConfig::default()
+        };
+        let store = LocalObjectStore::from_storage_config(tmp.path(), &config);
+        store.create_bucket("bucket").await.unwrap();
+        store.set_bucket_compression("bucket", Some(CompressionCodec::Zstd)).await.unwrap();
+        let bucket_dir = store.layout().bucket_dir("bucket").unwrap();
+        let row = |key: &'static str| {
+            let store = &store;
+            async move {
+                let index = store.index("bucket").await.unwrap();
+                index.get(key).await.unwrap().unwrap()
+            }
+        };
+
+        store.put_object("bucket", "k", b"hello world", None, None, false).await.unwrap();
+        assert_eq!(row("k").await.on_disk(), None);
+        assert_eq!(read_body(&store, "bucket", "k").await, b"hello world");
+        let read = store.read_object("bucket", "k").await.unwrap();
+        let mut range = Vec::new();
+        let mut reader = read.open_part(&read.meta.parts[0], 6, 5).await.unwrap();
+        reader.read_to_end(&mut range).await.unwrap();
+        assert_eq!(range, b"world");
+        assert!(read.meta.parts[0].compression.is_none());
+
+        // Growing past the threshold moves it to a blob dir...
+        let big = b"x".repeat(1000);
+        store.put_object("bucket", "k", &big, None, None, false).await.unwrap();
+        let on_disk = row("k").await.blob_dir;
+        assert!(bucket_dir.join(&on_disk).join("meta.json").exists());
+        assert_eq!(read_body(&store, "bucket", "k").await, big);
+        // ...and shrinking back retires that dir.
+        store.put_object("bucket", "k", b"small", None, None, false).await.unwrap();
+        assert_eq!(row("k").await.on_disk(), None);
+        assert!(!bucket_dir.join(&on_disk).exists());
+
+        store.copy_object("bucket", "k", "bucket", "copy").await.unwrap();
+        assert_eq!(row("copy").await.on_disk(), None);
+        assert_eq!(read_body(&store, "bucket", "copy").await, b"small");
+        let staging = store.stage_put("bucket", "k", b"x", None, None).await.unwrap();
+        let err = store
+            .commit_staged_put("bucket", "k", &staging, Some(Precondition::IfNoneMatchStar))
+            .await;
+        assert!(matches!(err, Err(StorageError::PreconditionFailed { .. })));
+        assert_invariants(&store, "bucket").await;
+
+        // The blob tree knows nothing of inline objects; a rebuild keeps them.
+        assert_eq!(store.rebuild_index("bucket").await.unwrap(), 2);
+        assert_eq!(read_body(&store, "bucket", "copy").await, b"small");
+
+        store.delete_object("bucket", "k").await.unwrap();
+        assert!(store.read_object("bucket", "k").await.is_err());
+        assert_invariants(&store, "bucket").await;
+    }
+
     #[tokio::test]
     async fn copy_object_to_same_key_does_not_deadlock() {
         let (_tmp, store) = store_and_bucket().await;
@@ -3698,6 +4907,45 @@ mod tests {
         assert_eq!(read_body(&store, "bucket", "key").await, b"payload");
     }
 
+    #[tokio::test]
+    async fn metadata_rewrite_checks_last_modified_at_commit() {
+        let (_tmp, store) = store_and_bucket().await;
+        let big = b"x".repeat(1000);
+        for (key, body) in [("small", &b"payload"[..]), ("big", &big[..])] {
+            let opened = store.put_object("bucket", key, body, None, None, false).await.unwrap();
+            let rewrite = |at: i64, content_type: &'static str| {
+                let store = store.clone();
+                async move {
+                    store
+                        .copy_object_with_metadata(
+                            "bucket",
+                            key,
+                            "bucket",
+                            key,
+                            None,
+                            None,
+                            Some(content_type),
+                            None,
+                            None,
+                            Some(Precondition::IfLastModified(at)),
+                        )
+                        .await
+                }
+            };
+            // Someone else's metadata-only rewrite keeps the ETag but not the time.
+            let theirs = rewrite(opened.last_modified_ms, "text/theirs").await.unwrap();
+            assert_eq!(theirs.etag, opened.etag);
+            let stale = rewrite(opened.last_modified_ms, "text/mine").await;
+            assert!(matches!(stale, Err(StorageError::PreconditionFailed { .. })), "{key}");
+            let read = store.read_object("bucket", key).await.unwrap();
+            assert_eq!(read.meta.content_type, "text/theirs");
+            rewrite(theirs.last_modified_ms, "text/mine").await.unwrap();
+            let read = store.read_object("bucket", key).await.unwrap();
+            assert_eq!(read.meta.content_type, "text/mine");
+        }
+        assert_invariants(&store, "bucket").await;
+    }
+
     #[tokio::test]
     async fn upload_part_copy_streams_inclusive_range() {
         let (_tmp, store) = store_and_bucket().await;
@@ -3731,7 +4979,8 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let body = format!("{:x}\r\nx\r\n", usize::MAX);
         let stream = futures::stream::iter(vec![Ok::<Bytes, std::io::Error>(Bytes::from(body))]);
-        let result = write_aws_chunked_stream_with_hashes(&tmp.path().join("part"), stream).await;
+        let result =
+            write_aws_chunked_stream_with_hashes(&tmp.path().join("part"), stream, None).await;
         assert!(matches!(result, Err(StorageError::InvalidAwsChunkedBody(_))));
     }
 }
@@ -10,6 +10,8 @@
 //!   tree.
 //! * **Staging expiry** — abandoned uploads and multiparts.
 //! * **Trash expiry** — retired blob dirs past their grace window.
+//! * **Chunk collection** — dedup chunks no row references any more, once
+//!   the trash no longer shares them either.
 //!
 //! A staging directory is safe to delete only when BOTH its folder-name
 //! epoch is older than the expiry window AND every file inside it has an
@@ -21,6 +23,7 @@ use std::time::SystemTime;
 
 use tokio::task::yield_now;
 
+use super::chunks::{idle_chunks, remove_chunk};
 use super::errors::Result;
 use super::staging::epoch_ms_from_staging_id;
 use super::store::LocalObjectStore;
@@ -44,6 +47,7 @@ pub struct SweepStats {
     pub intents_resolved: usize,
     pub staging_dirs_removed: usize,
     pub trash_dirs_removed: usize,
+    pub chunks_removed: usize,
 }
 
 impl Default for SweepConfig {
@@ -109,6 +113,44 @@ pub async fn delete_trash_bucket(
     Ok(stats.trash_dirs_removed)
 }
 
+/// Purpose 4 — delete chunk-store files for one bucket that no index row
+/// references and no other file links. A chunk whose inode changed within
+/// the intent grace period is left alone: a publish may have just linked it
+/// and not yet committed the row. Returns the number removed.
+pub async fn delete_chunks_bucket(
+    store: &LocalObjectStore,
+    bucket: &str,
+    config: &SweepConfig,
+    now_ms: i64,
+) -> Result<usize> {
+    let chunks_dir = store.layout().chunks_dir(bucket)?;
+    let grace_ms = config.intent_grace_period_ms;
+    let idle = {
+        let chunks_dir = chunks_dir.clone();
+        tokio::task::spawn_blocking(move || idle_chunks(&chunks_dir, now_ms, grace_ms))
+            .await
+            .unwrap_or_default()
+    };
+    if idle.is_empty() {
+        return Ok(0);
+    }
+    let index = store.index(bucket).await?;
+    let mut removed = 0;
+    for id in index.drop_unreferenced_chunks(idle).await? {
+        match remove_chunk(&chunks_dir, &id).await {
+            Ok(()) => {
+                removed += 1;
+                log::info!("sweeper removed chunk bucket={bucket} id={id}");
+            }
+            Err(err) => {
+                log::warn!("sweeper failed to remove chunk bucket={bucket} id={id} error={err}");
+            }
+        }
+        yield_now().await;
+    }
+    Ok(removed)
+}
+
 async fn sweep_staging(
     staging_dir: &Path,
     config: &SweepConfig,
@@ -257,8 +299,8 @@ mod tests {
     use super::*;
     use crate::storage::time::now_ms;
 
-    /// Test convenience: all three maintenance purposes for one bucket at one
-    /// evaluation time. Production drives the three entry points independently.
+    /// Test convenience: all four maintenance purposes for one bucket at one
+    /// evaluation time. Production drives the four entry points independently.
     async fn sweep_bucket(
         store: &LocalObjectStore,
         bucket: &str,
@@ -269,6 +311,7 @@ mod tests {
             intents_resolved: resolve_intents_bucket(store, bucket, config).await?,
             staging_dirs_removed: delete_staging_bucket(store, bucket, config, now_ms).await?,
             trash_dirs_removed: delete_trash_bucket(store, bucket, config, now_ms).await?,
+            chunks_removed: delete_chunks_bucket(store, bucket, config, now_ms).await?,
         })
     }
 